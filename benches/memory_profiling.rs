@@ -7,7 +7,7 @@
 //! interference with other benchmarks.
 
 use union_square::benchmark_types::*;
-use union_square::proxy::storage::RingBuffer;
+use union_square::proxy::storage::{AuditEventFormat, RingBuffer};
 use union_square::proxy::types::*;
 
 // Global allocator is required for dhat heap profiling to track all allocations
@@ -37,6 +37,7 @@ fn profile_ring_buffer() {
     let config = RingBufferConfig {
         buffer_size: BufferSize::try_new(16 * 1024 * 1024).expect("16MB is valid"),
         slot_size: SlotSize::try_new(128 * 1024).expect("128KB is valid"),
+        ..Default::default()
     };
 
     let ring_buffer = RingBuffer::new(&config);
@@ -112,7 +113,7 @@ fn profile_audit_events() {
             },
         };
 
-        let serialized = serde_json::to_vec(&event).unwrap();
+        let serialized = AuditEventFormat::Binary.encode(&event).unwrap();
         events.push(serialized);
     }
 
@@ -135,6 +136,7 @@ fn profile_concurrent_allocations() {
     let config = RingBufferConfig {
         buffer_size: BufferSize::try_new(128 * 1024 * 1024).expect("128MB is valid"),
         slot_size: SlotSize::try_new(64 * 1024).expect("64KB is valid"),
+        ..Default::default()
     };
     let ring_buffer = Arc::new(RingBuffer::new(&config));
 
@@ -3,12 +3,20 @@
 //! These benchmarks verify that the hot path maintains <5ms latency
 //! as required by ADR-0008 (Dual-path Architecture).
 
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
 use std::hint::black_box;
 use std::sync::Arc;
 use std::time::Duration;
 use union_square::proxy::{
-    paths::StreamingHotPathService, storage::RingBuffer, types::*, AuthConfig,
+    http::build_upstream_client,
+    observability::{
+        attributes, render_prometheus, span_names, HotPathMetrics, MetricsSnapshot, ProviderSlot,
+        SpanKind, Tracer, MAX_QUEUED_SPANS,
+    },
+    paths::{AuditCounters, StreamingHotPathService},
+    storage::{decode_audit_event, AuditEventFormat, RingBuffer},
+    types::*,
+    AuthConfig,
 };
 
 /// Benchmark ring buffer write performance - this is the critical hot path operation
@@ -22,6 +30,7 @@ fn bench_ring_buffer_performance(c: &mut Criterion) {
             let config = RingBufferConfig {
                 buffer_size: BufferSize::try_new(16 * 1024 * 1024).expect("16MB is valid"), // 16MB (power of 2)
                 slot_size: SlotSize::try_new(128 * 1024).expect("128KB is valid"), // 128KB slots
+                ..Default::default()
             };
             let ring_buffer = RingBuffer::new(&config);
             let data = vec![b'x'; *size];
@@ -38,6 +47,7 @@ fn bench_ring_buffer_performance(c: &mut Criterion) {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(128 * 1024 * 1024).expect("128MB is valid"), // 128MB (power of 2)
             slot_size: SlotSize::try_new(64 * 1024).expect("64KB is valid"), // 64KB slots
+            ..Default::default()
         };
         let ring_buffer = Arc::new(RingBuffer::new(&config));
         let data = vec![b'x'; 1024]; // 1KB payload
@@ -64,53 +74,127 @@ fn bench_ring_buffer_performance(c: &mut Criterion) {
 }
 
 /// Benchmark audit event serialization overhead
+///
+/// Each event is encoded both as JSON and in the compact binary format the
+/// hot path writes to the ring buffer; the chunk event shows the difference
+/// for body capture, where JSON has to base64-encode the bytes.
 fn bench_audit_event_serialization(c: &mut Criterion) {
     let mut group = c.benchmark_group("audit_event_serialization");
 
-    // Test serialization of different event types
-    group.bench_function("request_received_event", |b| {
-        let event = AuditEvent {
-            request_id: RequestId::new(),
-            session_id: SessionId::new(),
-            timestamp: chrono::Utc::now(),
-            event_type: AuditEventType::RequestReceived {
-                method: HttpMethod::try_new("POST".to_string()).unwrap(),
-                uri: RequestUri::try_new("/v1/chat/completions".to_string()).unwrap(),
-                headers: Headers::from_vec(vec![
-                    ("content-type".to_string(), "application/json".to_string()),
-                    ("authorization".to_string(), "Bearer test-key".to_string()),
-                ])
-                .unwrap(),
-                body_size: BodySize::from(1024),
+    let events = [
+        (
+            "request_received_event",
+            AuditEvent {
+                request_id: RequestId::new(),
+                session_id: SessionId::new(),
+                timestamp: chrono::Utc::now(),
+                event_type: AuditEventType::RequestReceived {
+                    method: HttpMethod::try_new("POST".to_string()).unwrap(),
+                    uri: RequestUri::try_new("/v1/chat/completions".to_string()).unwrap(),
+                    headers: Headers::from_vec(vec![
+                        ("content-type".to_string(), "application/json".to_string()),
+                        ("authorization".to_string(), "Bearer test-key".to_string()),
+                    ])
+                    .unwrap(),
+                    body_size: BodySize::from(1024),
+                },
             },
-        };
+        ),
+        (
+            "response_received_event",
+            AuditEvent {
+                request_id: RequestId::new(),
+                session_id: SessionId::new(),
+                timestamp: chrono::Utc::now(),
+                event_type: AuditEventType::ResponseReceived {
+                    status: HttpStatusCode::try_new(200).unwrap(),
+                    headers: Headers::from_vec(vec![(
+                        "content-type".to_string(),
+                        "application/json".to_string(),
+                    )])
+                    .unwrap(),
+                    body_size: BodySize::from(2048),
+                    duration_ms: DurationMillis::from(15),
+                },
+            },
+        ),
+        (
+            "response_chunk_16kb_event",
+            AuditEvent {
+                request_id: RequestId::new(),
+                session_id: SessionId::new(),
+                timestamp: chrono::Utc::now(),
+                event_type: AuditEventType::ResponseChunk {
+                    offset: ChunkOffset::from(0),
+                    data: (0..16 * 1024).map(|i| (i % 251) as u8).collect(),
+                },
+            },
+        ),
+    ];
+
+    for (name, event) in &events {
+        for (format_name, format) in [
+            ("json", AuditEventFormat::Json),
+            ("binary", AuditEventFormat::Binary),
+        ] {
+            let encoded = format.encode(event).unwrap();
+            group.throughput(Throughput::Bytes(encoded.len() as u64));
+
+            group.bench_function(format!("{name}_{format_name}"), |b| {
+                b.iter(|| black_box(format.encode(black_box(event)).unwrap()));
+            });
+            group.bench_function(format!("{name}_{format_name}_decode"), |b| {
+                b.iter(|| black_box(decode_audit_event(black_box(&encoded)).unwrap()));
+            });
+        }
+    }
+
+    group.finish();
+}
 
+/// Benchmark recording a request and rendering a scrape
+fn bench_hot_path_metrics(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_path_metrics");
+    let metrics = Arc::new(HotPathMetrics::new());
+
+    group.bench_function("record", |b| {
         b.iter(|| {
-            let serialized = serde_json::to_vec(&event).unwrap();
-            black_box(serialized);
+            metrics.record(
+                black_box(ProviderSlot::PASSTHROUGH),
+                black_box(Duration::from_micros(1_500)),
+                black_box(true),
+            )
         });
     });
 
-    group.bench_function("response_received_event", |b| {
-        let event = AuditEvent {
-            request_id: RequestId::new(),
-            session_id: SessionId::new(),
-            timestamp: chrono::Utc::now(),
-            event_type: AuditEventType::ResponseReceived {
-                status: HttpStatusCode::try_new(200).unwrap(),
-                headers: Headers::from_vec(vec![(
-                    "content-type".to_string(),
-                    "application/json".to_string(),
-                )])
-                .unwrap(),
-                body_size: BodySize::from(2048),
-                duration_ms: DurationMillis::from(15),
-            },
-        };
+    group.bench_function("record_contended", |b| {
+        b.iter(|| {
+            std::thread::scope(|scope| {
+                for _ in 0..4 {
+                    scope.spawn(|| {
+                        for _ in 0..1_000 {
+                            metrics.record(
+                                ProviderSlot::PASSTHROUGH,
+                                Duration::from_micros(1_500),
+                                true,
+                            );
+                        }
+                    });
+                }
+            })
+        });
+    });
 
+    let ring_buffer = RingBuffer::new(&RingBufferConfig::default());
+    group.bench_function("render_prometheus", |b| {
         b.iter(|| {
-            let serialized = serde_json::to_vec(&event).unwrap();
-            black_box(serialized);
+            black_box(render_prometheus(&MetricsSnapshot {
+                hot_path: metrics.snapshot(),
+                ring_buffer: ring_buffer.stats(),
+                ring_buffer_shards: ring_buffer.shard_count(),
+                spill: None,
+                audit: AuditCounters::default(),
+            }))
         });
     });
 
@@ -158,6 +242,7 @@ fn bench_hot_path_simulation(c: &mut Criterion) {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(128 * 1024 * 1024).expect("128MB is valid"), // 128MB
             slot_size: SlotSize::try_new(64 * 1024).expect("64KB is valid"),              // 64KB
+            ..Default::default()
         };
         let ring_buffer = RingBuffer::new(&config);
 
@@ -187,7 +272,7 @@ fn bench_hot_path_simulation(c: &mut Criterion) {
             };
 
             // 4. Serialize event (this is the main overhead)
-            let serialized = serde_json::to_vec(&audit_event).unwrap();
+            let serialized = AuditEventFormat::Binary.encode(&audit_event).unwrap();
 
             // 5. Write to ring buffer (sub-microsecond)
             let _ = ring_buffer.write(request_id, &serialized);
@@ -201,6 +286,7 @@ fn bench_hot_path_simulation(c: &mut Criterion) {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(128 * 1024 * 1024).expect("128MB is valid"),
             slot_size: SlotSize::try_new(64 * 1024).expect("64KB is valid"),
+            ..Default::default()
         };
         let ring_buffer = RingBuffer::new(&config);
 
@@ -223,7 +309,7 @@ fn bench_hot_path_simulation(c: &mut Criterion) {
                         body_size: BodySize::from(1024),
                     },
                 };
-                let serialized = serde_json::to_vec(&audit_event).unwrap();
+                let serialized = AuditEventFormat::Binary.encode(&audit_event).unwrap();
                 let _ = ring_buffer.write(request_id, &serialized);
 
                 total_duration += start.elapsed();
@@ -275,7 +361,8 @@ fn bench_streaming_service_performance(c: &mut Criterion) {
     group.bench_function("uri_construction", |b| {
         let config = ProxyConfig::default();
         let ring_buffer = Arc::new(RingBuffer::new(&config.ring_buffer));
-        let _service = StreamingHotPathService::new(config, ring_buffer);
+        let client = build_upstream_client(&config.upstream_tls).unwrap();
+        let _service = StreamingHotPathService::new(config, ring_buffer, client);
 
         b.iter(|| {
             // Simulate URI construction for different scenarios
@@ -418,7 +505,7 @@ fn bench_complete_proxy_flow_simulation(c: &mut Criterion) {
                     body_size: BodySize::from(1024),
                 },
             };
-            let request_serialized = serde_json::to_vec(&request_event).unwrap();
+            let request_serialized = AuditEventFormat::Binary.encode(&request_event).unwrap();
 
             // 6. Ring buffer write (fire-and-forget)
             let _ = ring_buffer.write(request_id, &request_serialized);
@@ -444,7 +531,7 @@ fn bench_complete_proxy_flow_simulation(c: &mut Criterion) {
                     duration_ms,
                 },
             };
-            let response_serialized = serde_json::to_vec(&response_event).unwrap();
+            let response_serialized = AuditEventFormat::Binary.encode(&response_event).unwrap();
 
             // 9. Ring buffer write for response
             let _ = ring_buffer.write(request_id, &response_serialized);
@@ -483,7 +570,7 @@ fn bench_complete_proxy_flow_simulation(c: &mut Criterion) {
                         body_size: BodySize::from(1024),
                     },
                 };
-                let serialized = serde_json::to_vec(&audit_event).unwrap();
+                let serialized = AuditEventFormat::Binary.encode(&audit_event).unwrap();
                 let _ = ring_buffer.write(request_id, &serialized);
 
                 let elapsed = start.elapsed();
@@ -538,10 +625,46 @@ fn bench_error_handling_performance(c: &mut Criterion) {
 }
 
 // Criterion benchmark groups
+/// Benchmark recording a span on the request path
+///
+/// Spans are only recorded when an OTLP collector is configured.
+fn bench_span_recording(c: &mut Criterion) {
+    let mut group = c.benchmark_group("span_recording");
+    let (tracer, mut spans) = Tracer::channel(MAX_QUEUED_SPANS);
+    // Stand-in for the exporter, draining the queue so it never fills
+    std::thread::spawn(move || loop {
+        while spans.try_recv().is_ok() {}
+        std::thread::sleep(Duration::from_millis(1));
+    });
+
+    let mut headers = hyper::HeaderMap::new();
+    headers.insert(
+        "traceparent",
+        hyper::header::HeaderValue::from_static(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        ),
+    );
+
+    group.bench_function("propagated_span", |b| {
+        b.iter(|| {
+            let mut headers = headers.clone();
+            let mut span =
+                tracer.start_propagated(span_names::PROXY, SpanKind::Server, &mut headers);
+            span.set_attribute(attributes::HTTP_REQUEST_METHOD, "POST");
+            span.set_attribute(attributes::HTTP_RESPONSE_STATUS_CODE, 200u16);
+            black_box(span).end();
+        });
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_ring_buffer_performance,
     bench_audit_event_serialization,
+    bench_hot_path_metrics,
+    bench_span_recording,
     bench_newtype_validation,
     bench_hot_path_simulation,
     bench_memory_allocation,
@@ -0,0 +1,221 @@
+//! Adapter reading token usage from captured LLM response bodies
+//!
+//! Every provider reports usage in its own response format. The provider is
+//! taken from the model the request body was parsed as, and the body is read
+//! by [`ProviderResponseProcessor`] into [`ProviderMetadata`]. From there the
+//! usage can be priced with the [`PricingCatalog`].
+
+use crate::domain::llm::{LlmProvider, ModelVersion};
+use crate::domain::metrics::Timestamp;
+use crate::domain::pricing::{CostCalculation, PricingCatalog, TokenUsage};
+use crate::domain::types::{self, TokenCount};
+use crate::providers::bedrock::types::ModelId;
+use crate::providers::constants::provider_ids;
+use crate::providers::response_processor::ProviderResponseProcessor;
+use crate::providers::{ProviderId, ProviderMetadata};
+
+/// Read the model and usage from the response to a request for `model`
+///
+/// The requested model stands in when the response does not name one.
+/// Providers without their own format, such as OpenAI-compatible servers,
+/// are read as OpenAI responses.
+pub fn extract_response_metadata(model: &ModelVersion, body: &[u8]) -> ProviderMetadata {
+    let base_metadata = ProviderMetadata {
+        provider_id: provider_id(&model.provider),
+        model_id: ModelId::try_new(model.model_id.as_ref().to_string()).ok(),
+        ..ProviderMetadata::default()
+    };
+    ProviderResponseProcessor::new(base_metadata).process_complete_body(body)
+}
+
+/// Price the usage in `metadata` of a request for `model` received at `at`
+///
+/// The model that served the request is priced if the catalog knows it, so
+/// dated snapshots can have their own price; otherwise the requested model
+/// is. `None` when neither has a price or the response reported no usage.
+pub fn calculate_cost(
+    catalog: &PricingCatalog,
+    model: &ModelVersion,
+    metadata: &ProviderMetadata,
+    at: Timestamp,
+) -> Option<CostCalculation> {
+    let usage = billed_usage(&model.provider, metadata)?;
+    let served = metadata
+        .model_id
+        .as_ref()
+        .and_then(|model_id| types::ModelId::try_new(model_id.as_ref().to_string()).ok())
+        .filter(|model_id| *model_id != model.model_id)
+        .map(|model_id| ModelVersion {
+            provider: model.provider.clone(),
+            model_id,
+        });
+    served
+        .and_then(|served| catalog.calculate(&served, usage, at))
+        .or_else(|| catalog.calculate(model, usage, at))
+}
+
+/// The tokens to bill for the usage a provider reported
+///
+/// Gemini counts cached tokens in its prompt tokens; every other provider
+/// reports them separately, so only Gemini's are taken out of the input.
+fn billed_usage(provider: &LlmProvider, metadata: &ProviderMetadata) -> Option<TokenUsage> {
+    if metadata.request_tokens.is_none() && metadata.response_tokens.is_none() {
+        return None;
+    }
+    let count = |tokens: Option<u32>| TokenCount::try_new(tokens.unwrap_or(0)).ok();
+    let input = metadata.request_tokens.map(|tokens| tokens.into_inner());
+    let cache_read = metadata.cache_read_tokens.map(|tokens| tokens.into_inner());
+    let input = match provider {
+        LlmProvider::Google => input.map(|input| input.saturating_sub(cache_read.unwrap_or(0))),
+        _ => input,
+    };
+    Some(TokenUsage {
+        input: count(input)?,
+        output: count(metadata.response_tokens.map(|tokens| tokens.into_inner()))?,
+        cache_read: count(cache_read)?,
+        cache_write: count(
+            metadata
+                .cache_write_tokens
+                .map(|tokens| tokens.into_inner()),
+        )?,
+    })
+}
+
+/// The provider whose response format `provider` answers in
+fn provider_id(provider: &LlmProvider) -> ProviderId {
+    match provider {
+        LlmProvider::Anthropic => ProviderId::anthropic(),
+        LlmProvider::Google => ProviderId::vertex(),
+        LlmProvider::Azure => ProviderId::azure(),
+        LlmProvider::Other(name) if name.as_ref() == provider_ids::BEDROCK => ProviderId::bedrock(),
+        LlmProvider::OpenAI | LlmProvider::Other(_) => ProviderId::openai(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::config_types::ProviderName;
+    use crate::domain::pricing::{PricingEntry, TokenPrice};
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn model(provider: LlmProvider, model_id: &str) -> ModelVersion {
+        ModelVersion {
+            provider,
+            model_id: types::ModelId::try_new(model_id.to_string()).unwrap(),
+        }
+    }
+
+    fn price(provider: &str, model: &str, input: &str, output: &str) -> PricingEntry {
+        PricingEntry {
+            provider: ProviderName::try_new(provider.to_string()).unwrap(),
+            model: types::ModelId::try_new(model.to_string()).unwrap(),
+            effective_from: NaiveDate::from_str("2024-01-01").unwrap(),
+            input_per_million: TokenPrice::try_new(Decimal::from_str(input).unwrap()).unwrap(),
+            output_per_million: TokenPrice::try_new(Decimal::from_str(output).unwrap()).unwrap(),
+            cache_read_per_million: None,
+            cache_write_per_million: None,
+        }
+    }
+
+    fn received_at() -> Timestamp {
+        Timestamp::try_new(Utc.with_ymd_and_hms(2024, 11, 1, 9, 0, 0).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_extracts_openai_usage() {
+        let body = br#"{"model":"gpt-4o-2024-08-06","usage":{"prompt_tokens":12,"completion_tokens":5,"total_tokens":17}}"#;
+        let metadata = extract_response_metadata(&model(LlmProvider::OpenAI, "gpt-4o"), body);
+        assert_eq!(metadata.model_id.unwrap().as_ref(), "gpt-4o-2024-08-06");
+        assert_eq!(metadata.request_tokens.unwrap().into_inner(), 12);
+        assert_eq!(metadata.response_tokens.unwrap().into_inner(), 5);
+    }
+
+    #[test]
+    fn test_extracts_anthropic_cache_usage() {
+        let body = br#"{"model":"claude-3-5-sonnet-20241022","usage":{"input_tokens":30,"output_tokens":8,"cache_read_input_tokens":100,"cache_creation_input_tokens":20}}"#;
+        let metadata = extract_response_metadata(
+            &model(LlmProvider::Anthropic, "claude-3-5-sonnet-20241022"),
+            body,
+        );
+        assert_eq!(metadata.request_tokens.unwrap().into_inner(), 30);
+        assert_eq!(metadata.response_tokens.unwrap().into_inner(), 8);
+        assert_eq!(metadata.cache_read_tokens.unwrap().into_inner(), 100);
+        assert_eq!(metadata.cache_write_tokens.unwrap().into_inner(), 20);
+    }
+
+    #[test]
+    fn test_bedrock_usage_names_the_requested_model() {
+        let provider = LlmProvider::Other(ProviderName::try_new("bedrock".to_string()).unwrap());
+        let body = br#"{"output":{"message":{"role":"assistant","content":[{"text":"hi"}]}},"stopReason":"end_turn","usage":{"inputTokens":7,"outputTokens":3,"totalTokens":10}}"#;
+        let metadata = extract_response_metadata(
+            &model(provider, "anthropic.claude-3-haiku-20240307-v1:0"),
+            body,
+        );
+        assert_eq!(
+            metadata.model_id.unwrap().as_ref(),
+            "anthropic.claude-3-haiku-20240307-v1:0"
+        );
+        assert_eq!(metadata.request_tokens.unwrap().into_inner(), 7);
+        assert_eq!(metadata.response_tokens.unwrap().into_inner(), 3);
+    }
+
+    #[test]
+    fn test_cost_prefers_the_served_model_price() {
+        let catalog = PricingCatalog::new(vec![
+            price("openai", "gpt-4o", "2.50", "10"),
+            price("openai", "gpt-4o-2024-05-13", "5", "15"),
+        ])
+        .unwrap();
+        let requested = model(LlmProvider::OpenAI, "gpt-4o");
+
+        let snapshot = extract_response_metadata(
+            &requested,
+            br#"{"model":"gpt-4o-2024-05-13","usage":{"prompt_tokens":1000,"completion_tokens":100}}"#,
+        );
+        let cost = calculate_cost(&catalog, &requested, &snapshot, received_at()).unwrap();
+        assert_eq!(cost.model_version.model_id.as_ref(), "gpt-4o-2024-05-13");
+        assert_eq!(
+            cost.cost.total.into_inner(),
+            Decimal::from_str("0.0065").unwrap()
+        );
+
+        // An unpriced snapshot falls back to the requested model
+        let unpriced = extract_response_metadata(
+            &requested,
+            br#"{"model":"gpt-4o-2024-08-06","usage":{"prompt_tokens":1000,"completion_tokens":100}}"#,
+        );
+        let cost = calculate_cost(&catalog, &requested, &unpriced, received_at()).unwrap();
+        assert_eq!(cost.model_version, requested);
+        assert_eq!(
+            cost.cost.total.into_inner(),
+            Decimal::from_str("0.0035").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_gemini_cached_tokens_are_not_billed_as_input() {
+        let catalog =
+            PricingCatalog::new(vec![price("google", "gemini-1.5-pro", "1", "1")]).unwrap();
+        let requested = model(LlmProvider::Google, "gemini-1.5-pro");
+        let metadata = extract_response_metadata(
+            &requested,
+            br#"{"usageMetadata":{"promptTokenCount":11,"candidatesTokenCount":4,"totalTokenCount":15,"cachedContentTokenCount":8}}"#,
+        );
+
+        let cost = calculate_cost(&catalog, &requested, &metadata, received_at()).unwrap();
+        assert_eq!(cost.usage.input.into_inner(), 3);
+        assert_eq!(cost.usage.cache_read.into_inner(), 8);
+        assert_eq!(cost.usage.output.into_inner(), 4);
+    }
+
+    #[test]
+    fn test_responses_without_usage_have_no_cost() {
+        let catalog = PricingCatalog::new(vec![price("openai", "gpt-4o", "2.50", "10")]).unwrap();
+        let requested = model(LlmProvider::OpenAI, "gpt-4o");
+        let metadata = extract_response_metadata(&requested, b"not json");
+        assert!(calculate_cost(&catalog, &requested, &metadata, received_at()).is_none());
+    }
+}
@@ -5,4 +5,5 @@
 //! domain facts and handle conversion errors explicitly.
 
 pub mod llm_request_parser;
+pub mod llm_usage;
 pub mod proxy_audit;
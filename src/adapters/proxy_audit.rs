@@ -9,7 +9,10 @@ use crate::domain::{
     commands::audit_commands::{AuditCommandError, RecordAuditEvent},
     llm,
     metrics::Timestamp,
-    session::SessionId,
+    session::{
+        ApplicationContext, ApplicationId, ClientSessionId, ClientUserId, EnvironmentId,
+        MetadataKey, MetadataValue, SessionId, SessionMetadata,
+    },
 };
 
 /// Convert a proxy audit event into a domain `RecordAuditEvent` command.
@@ -41,6 +44,7 @@ pub fn convert_audit_event(
         audit_event,
         timestamp,
         parsed_request: None,
+        calculated_cost: None,
     })
 }
 
@@ -106,18 +110,53 @@ fn convert_audit_event_type(
                 duration_ms: audit_types::DurationMs::from(*duration_ms.as_ref()),
             })
         }
-        ProxyType::RequestBody { .. } => Err(AuditCommandError::InvalidField(
-            "RequestBody not supported at domain boundary".to_string(),
-        )),
-        ProxyType::ResponseBody { .. } => Err(AuditCommandError::InvalidField(
-            "ResponseBody not supported at domain boundary".to_string(),
-        )),
+        ProxyType::RequestBody { content, truncated } => {
+            Ok(audit_types::AuditEventType::RequestBodyCaptured {
+                body: audit_types::BodyContent::new(content.clone()),
+                truncated: *truncated,
+            })
+        }
+        ProxyType::ResponseBody { content, truncated } => {
+            Ok(audit_types::AuditEventType::ResponseBodyCaptured {
+                body: audit_types::BodyContent::new(content.clone()),
+                truncated: *truncated,
+            })
+        }
+        // Chunks are reassembled by the audit path before reaching the domain
         ProxyType::RequestChunk { .. } => Err(AuditCommandError::InvalidField(
             "RequestChunk not supported at domain boundary".to_string(),
         )),
         ProxyType::ResponseChunk { .. } => Err(AuditCommandError::InvalidField(
             "ResponseChunk not supported at domain boundary".to_string(),
         )),
+        ProxyType::RequestBodyComplete { .. } => Err(AuditCommandError::InvalidField(
+            "RequestBodyComplete not supported at domain boundary".to_string(),
+        )),
+        ProxyType::ResponseBodyComplete { .. } => Err(AuditCommandError::InvalidField(
+            "ResponseBodyComplete not supported at domain boundary".to_string(),
+        )),
+        ProxyType::SessionContextReceived { context } => {
+            Ok(audit_types::AuditEventType::SessionContextReceived {
+                metadata: convert_session_context(context),
+            })
+        }
+        ProxyType::ProviderResolved {
+            provider_id,
+            model_id,
+        } => {
+            let provider = crate::providers::ProviderId::try_new(provider_id.clone())
+                .ok()
+                .and_then(|provider_id| provider_id.llm_provider())
+                .ok_or_else(|| {
+                    AuditCommandError::InvalidField(format!("provider_id: {provider_id}"))
+                })?;
+            let model_id = model_id
+                .clone()
+                .map(crate::domain::types::ModelId::try_new)
+                .transpose()
+                .map_err(|e| AuditCommandError::InvalidField(format!("model_id: {e}")))?;
+            Ok(audit_types::AuditEventType::ProviderResolved { provider, model_id })
+        }
         ProxyType::Error { error, phase } => {
             let error = crate::domain::types::ErrorMessage::try_new(error.clone())
                 .map_err(|e| AuditCommandError::InvalidField(format!("error: {e}")))?;
@@ -141,6 +180,63 @@ fn convert_headers(
         .map_err(|e| AuditCommandError::InvalidField(format!("headers: {e}")))
 }
 
+/// Convert client-supplied session headers to domain `SessionMetadata`.
+///
+/// ADR-0012 leaves these values unvalidated by the client, so entries that
+/// fail domain validation are dropped rather than failing the whole event.
+fn convert_session_context(context: &crate::proxy::types::SessionContext) -> SessionMetadata {
+    let mut metadata = SessionMetadata::new();
+
+    if let Some(id) = context
+        .session_id
+        .clone()
+        .and_then(|raw| ClientSessionId::try_new(raw).ok())
+    {
+        metadata = metadata.with_client_session_id(id);
+    }
+    if let Some(id) = context
+        .parent_id
+        .clone()
+        .and_then(|raw| ClientSessionId::try_new(raw).ok())
+    {
+        metadata = metadata.with_parent_session_id(id);
+    }
+    if let Some(id) = context
+        .user_id
+        .clone()
+        .and_then(|raw| ClientUserId::try_new(raw).ok())
+    {
+        metadata = metadata.with_client_user_id(id);
+    }
+    if let Some(id) = context
+        .application_id
+        .clone()
+        .and_then(|raw| ApplicationId::try_new(raw).ok())
+    {
+        metadata = metadata.with_application_id(id);
+    }
+    if let Some(id) = context
+        .environment
+        .clone()
+        .and_then(|raw| EnvironmentId::try_new(raw).ok())
+    {
+        metadata = metadata.with_environment_id(id);
+    }
+    for (key, value) in &context.metadata {
+        if let (Ok(key), Ok(value)) = (
+            MetadataKey::try_new(key.clone()),
+            MetadataValue::try_new(value.clone()),
+        ) {
+            metadata = metadata.with_custom(key, value);
+        }
+    }
+    if let Some(value) = &context.application_context {
+        metadata = metadata.with_application_context(ApplicationContext::new(value.clone()));
+    }
+
+    metadata
+}
+
 /// Convert proxy `ErrorPhase` to domain `ErrorPhase`.
 fn convert_error_phase(proxy_phase: &crate::proxy::types::ErrorPhase) -> audit_types::ErrorPhase {
     match proxy_phase {
@@ -230,6 +326,117 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn converts_provider_resolved_to_the_domain_provider() {
+        let proxy_event = crate::proxy::types::AuditEvent {
+            request_id: crate::proxy::types::RequestId::new(),
+            session_id: crate::proxy::types::SessionId::new(),
+            timestamp: chrono::Utc::now(),
+            event_type: crate::proxy::types::AuditEventType::ProviderResolved {
+                provider_id: "vertex".to_string(),
+                model_id: Some("gemini-2.0-flash".to_string()),
+            },
+        };
+
+        let cmd = convert_audit_event(&proxy_event).unwrap();
+
+        assert_eq!(
+            cmd.audit_event,
+            audit_types::AuditEventType::ProviderResolved {
+                provider: llm::LlmProvider::Google,
+                model_id: Some(
+                    crate::domain::types::ModelId::try_new("gemini-2.0-flash".to_string()).unwrap()
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn converts_session_context_dropping_invalid_entries() {
+        let context = crate::proxy::types::SessionContext {
+            session_id: Some("conversation-17".to_string()),
+            parent_id: Some("   ".to_string()),
+            user_id: Some("user-9".to_string()),
+            application_id: Some("support-bot".to_string()),
+            environment: Some(" ".to_string()),
+            metadata: vec![
+                ("feature".to_string(), "chat".to_string()),
+                ("oversized".to_string(), "x".repeat(2048)),
+            ],
+            application_context: Some(serde_json::json!({"workflow": "support"})),
+        };
+        let proxy_event = crate::proxy::types::AuditEvent {
+            request_id: crate::proxy::types::RequestId::new(),
+            session_id: crate::proxy::types::SessionId::new(),
+            timestamp: chrono::Utc::now(),
+            event_type: crate::proxy::types::AuditEventType::SessionContextReceived { context },
+        };
+
+        let cmd = convert_audit_event(&proxy_event).unwrap();
+
+        let audit_types::AuditEventType::SessionContextReceived { metadata } = cmd.audit_event
+        else {
+            panic!("expected SessionContextReceived, got {:?}", cmd.audit_event);
+        };
+        assert_eq!(
+            metadata.client_session_id().map(|id| id.as_ref()),
+            Some("conversation-17")
+        );
+        assert!(metadata.parent_session_id().is_none());
+        assert_eq!(
+            metadata.client_user_id().map(|id| id.as_ref()),
+            Some("user-9")
+        );
+        assert_eq!(
+            metadata.application_id().map(|id| id.as_ref()),
+            Some("support-bot")
+        );
+        assert!(metadata.environment_id().is_none());
+        assert_eq!(metadata.custom().len(), 1);
+        assert!(metadata.application_context().is_some());
+    }
+
+    #[test]
+    fn converts_reassembled_response_body() {
+        let proxy_event = crate::proxy::types::AuditEvent {
+            request_id: crate::proxy::types::RequestId::new(),
+            session_id: crate::proxy::types::SessionId::new(),
+            timestamp: chrono::Utc::now(),
+            event_type: crate::proxy::types::AuditEventType::ResponseBody {
+                content: b"hello".to_vec(),
+                truncated: true,
+            },
+        };
+
+        let cmd = convert_audit_event(&proxy_event).unwrap();
+
+        assert_eq!(
+            cmd.audit_event,
+            audit_types::AuditEventType::ResponseBodyCaptured {
+                body: audit_types::BodyContent::new(b"hello".to_vec()),
+                truncated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unassembled_chunks() {
+        let proxy_event = crate::proxy::types::AuditEvent {
+            request_id: crate::proxy::types::RequestId::new(),
+            session_id: crate::proxy::types::SessionId::new(),
+            timestamp: chrono::Utc::now(),
+            event_type: crate::proxy::types::AuditEventType::RequestChunk {
+                offset: crate::proxy::types::ChunkOffset::from(0),
+                data: b"partial".to_vec(),
+            },
+        };
+
+        assert!(matches!(
+            convert_audit_event(&proxy_event),
+            Err(AuditCommandError::InvalidField(_))
+        ));
+    }
+
     #[test]
     fn parse_request_body_returns_none_for_invalid_json() {
         let uri = audit_types::RequestUri::try_new("/v1/chat/completions".to_string()).unwrap();
@@ -1,7 +1,26 @@
 use crate::config::Settings;
+use crate::domain::budget::BudgetCatalog;
+use crate::domain::pricing::PricingCatalog;
+use crate::error::Error;
+use crate::infrastructure::dead_letters::PostgresDeadLetterStore;
+use crate::infrastructure::eventcore::projections::{
+    BudgetLedger, LiveView, PostgresReadModel, ProjectionHandle, ProjectionRunner,
+};
+use crate::infrastructure::eventcore::{service::EventCoreService, EventCoreConfig};
+use crate::proxy::http::build_upstream_client;
+use crate::proxy::observability::OtlpExporter;
+use crate::proxy::paths::{AuditBatching, AuditProcessorHandle};
+use crate::proxy::types::RingBufferConfig;
+use crate::proxy::{AuthConfig, ProxyConfig, ProxyService};
 use crate::Result;
 use sqlx::PgPool;
-use tracing::{info, instrument};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tracing::{error, info, instrument};
+
+/// Seconds to wait for the EventCore connection pool to hand out a connection
+const EVENTCORE_CONNECTION_TIMEOUT_SECS: u32 = 30;
 
 /// Main application struct that coordinates all components
 pub struct Application {
@@ -23,20 +42,89 @@ impl Application {
         Ok(Self { settings, db_pool })
     }
 
+    /// Serve the proxy until SIGTERM or SIGINT is received
     #[instrument(skip(self))]
     pub async fn run(self) -> Result<()> {
-        info!(
-            "Starting Union Square server on {}:{}",
+        let address = format!(
+            "{}:{}",
             self.settings.application.host, self.settings.application.port
         );
+        info!("Starting Union Square server on {address}");
+
+        let event_store = self.connect_event_store().await?;
+        let projection = self
+            .start_session_projection(Arc::clone(&event_store))
+            .await?;
+        let read_model = PostgresReadModel::new(self.db_pool.clone());
+        let live_view = Arc::new(LiveView::new(self.settings.live_view.clone()));
+        let dead_letters = PostgresDeadLetterStore::new(self.db_pool.clone());
+        dead_letters.ensure_schema().await?;
+        let live_projection =
+            ProjectionRunner::new(Arc::clone(&event_store), Arc::clone(&live_view)).spawn();
+        let mut service = ProxyService::try_new(self.proxy_config())
+            .map_err(|e| Error::application(e.to_string()))?;
+        if let Some(spill) = &self.settings.proxy.spill {
+            service = service
+                .with_spill(spill)
+                .map_err(|e| Error::application(e.to_string()))?;
+        }
+        let exporter = match &self.settings.proxy.otlp {
+            Some(otlp) => {
+                let client = build_upstream_client(&self.settings.proxy.upstream_tls)
+                    .map_err(|e| Error::application(e.to_string()))?;
+                let (tracer, exporter) = OtlpExporter::spawn(otlp, client)
+                    .map_err(|e| Error::application(e.to_string()))?;
+                info!("Exporting spans to {}", otlp.endpoint);
+                service = service.with_tracer(tracer);
+                Some(exporter)
+            }
+            None => None,
+        };
+        let pricing = PricingCatalog::new(self.settings.pricing.clone())
+            .map_err(|e| Error::application(format!("Invalid pricing: {e}")))?;
+        if !pricing.is_empty() {
+            service = service.with_pricing(Arc::new(pricing));
+        }
+        let budgets = BudgetCatalog::new(self.settings.budgets.clone())
+            .map_err(|e| Error::application(format!("Invalid budgets: {e}")))?;
+        let budget_projection = if budgets.is_empty() {
+            None
+        } else {
+            let ledger = Arc::new(
+                BudgetLedger::new(Arc::new(budgets)).with_event_store(Arc::clone(&event_store)),
+            );
+            service = service.with_budget_ledger(Arc::clone(&ledger));
+            Some(ProjectionRunner::new(Arc::clone(&event_store), ledger).spawn())
+        };
+        let service = service
+            .with_event_store(event_store)
+            .with_audit_batching(self.audit_batching())
+            .with_dead_letter_store(Arc::new(dead_letters))
+            .with_live_view(live_view)
+            .with_session_directory(Arc::new(read_model.clone()))
+            .with_search_index(Arc::new(read_model.clone()))
+            .with_usage_rollup(Arc::new(read_model));
+        let (router, audit_handle) = service.into_router_with_audit_handle(self.auth_config());
 
-        // TODO: Implement the server startup logic here.
-        // Consider using an HTTP framework like `axum` or `warp` to handle requests.
-        // This should include routing, middleware, and graceful shutdown handling.
-        // Timeline: Prioritize this implementation before the first production release.
+        let listener = TcpListener::bind(&address).await?;
         info!("Application started successfully");
 
-        Ok(())
+        let result = serve(listener, router, audit_handle, shutdown_signal()).await;
+        for handle in [projection, live_projection]
+            .into_iter()
+            .chain(budget_projection)
+        {
+            if let Err(e) = handle.shutdown().await {
+                error!("Projection failed: {e}");
+            }
+        }
+        // The audit processors have stopped, so their last spans are queued
+        if let Some(exporter) = exporter {
+            if let Err(e) = exporter.shutdown().await {
+                error!("Span exporter failed: {e}");
+            }
+        }
+        result
     }
 
     pub fn settings(&self) -> &Settings {
@@ -46,11 +134,134 @@ impl Application {
     pub fn db_pool(&self) -> &PgPool {
         &self.db_pool
     }
+
+    async fn connect_event_store(&self) -> Result<Arc<EventCoreService>> {
+        let config = EventCoreConfig::try_new(
+            &self.settings.database_url(),
+            *self.settings.database.max_connections.as_ref(),
+            EVENTCORE_CONNECTION_TIMEOUT_SECS,
+        )
+        .map_err(|e| Error::application(e.to_string()))?;
+
+        let event_store = EventCoreService::new(config).await?;
+        event_store.migrate().await?;
+        Ok(Arc::new(event_store))
+    }
+
+    /// Create the read model tables and keep them in step with the event log
+    async fn start_session_projection(
+        &self,
+        event_store: Arc<EventCoreService>,
+    ) -> Result<ProjectionHandle> {
+        let read_model = PostgresReadModel::new(self.db_pool.clone());
+        read_model.ensure_schema().await?;
+        Ok(ProjectionRunner::new(event_store, read_model).spawn())
+    }
+
+    fn proxy_config(&self) -> ProxyConfig {
+        ProxyConfig {
+            bedrock_region: self.settings.proxy.bedrock_region.clone(),
+            upstream_tls: self.settings.proxy.upstream_tls.clone(),
+            audit_format: self.settings.proxy.audit_format,
+            providers: self.settings.providers.clone(),
+            ring_buffer: RingBufferConfig {
+                shards: self.settings.eventcore.audit_consumers,
+                ..RingBufferConfig::default()
+            },
+            ..ProxyConfig::default()
+        }
+    }
+
+    fn audit_batching(&self) -> AuditBatching {
+        AuditBatching {
+            batch_size: self.settings.eventcore.batch_size,
+            flush_interval: self.settings.eventcore.flush_interval_ms,
+        }
+    }
+
+    fn auth_config(&self) -> AuthConfig {
+        let proxy = &self.settings.proxy;
+        let bound = proxy.bound_api_keys.iter().map(|bound| &bound.key);
+        AuthConfig {
+            api_keys: proxy.api_keys.iter().chain(bound).cloned().collect(),
+            admin_api_keys: proxy.admin_api_keys.iter().cloned().collect(),
+            application_bindings: proxy
+                .bound_api_keys
+                .iter()
+                .map(|bound| (bound.key.clone(), bound.binding()))
+                .collect(),
+            ..AuthConfig::default()
+        }
+    }
+}
+
+/// Serve `router` until `shutdown` resolves, then flush the audit path
+///
+/// In-flight requests are allowed to complete before the audit processor is
+/// asked to drain whatever they left in the ring buffer.
+async fn serve(
+    listener: TcpListener,
+    router: axum::Router,
+    audit_handle: AuditProcessorHandle,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    info!("Listening on {}", listener.local_addr()?);
+
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown)
+        .await?;
+
+    info!("HTTP server stopped; flushing audit ring buffer");
+    audit_handle
+        .shutdown()
+        .await
+        .map_err(|e| Error::application(format!("Audit processor failed: {e}")))?;
+
+    info!("Shutdown complete");
+    Ok(())
+}
+
+/// Resolves when the process receives SIGINT (Ctrl+C) or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl+C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutdown signal received; draining in-flight requests");
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::proxy::types::{
+        AuditEvent, AuditEventType, BodySize, Headers, HttpMethod, RequestId, RequestUri, SessionId,
+    };
 
     #[tokio::test]
     #[ignore = "requires database connection"]
@@ -60,4 +271,74 @@ mod tests {
             .expect("Failed to create application");
         assert!(*app.settings().application.port.as_ref() > 0);
     }
+
+    #[tokio::test]
+    async fn test_serve_answers_requests_until_shutdown() {
+        let service = ProxyService::new(ProxyConfig::default());
+        let (router, audit_handle) = service.into_router_with_audit_handle(AuthConfig::default());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let server = tokio::spawn(serve(listener, router, audit_handle, async {
+            let _ = shutdown_rx.await;
+        }));
+
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        tokio::io::AsyncWriteExt::write_all(
+            &mut stream,
+            b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        )
+        .await
+        .unwrap();
+        let mut response = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut stream, &mut response)
+            .await
+            .unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+
+        shutdown_tx.send(()).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_serve_flushes_ring_buffer_on_shutdown() {
+        let service = ProxyService::new(ProxyConfig::default());
+        let ring_buffer = service.ring_buffer();
+        let (router, audit_handle) = service.into_router_with_audit_handle(AuthConfig::default());
+
+        let event = AuditEvent {
+            request_id: RequestId::new(),
+            session_id: SessionId::new(),
+            timestamp: chrono::Utc::now(),
+            event_type: AuditEventType::RequestReceived {
+                method: HttpMethod::try_new("GET".to_string()).unwrap(),
+                uri: RequestUri::try_new("/test".to_string()).unwrap(),
+                headers: Headers::new(),
+                body_size: BodySize::from(0),
+            },
+        };
+        let serialized = serde_json::to_vec(&event).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let writes = ring_buffer.stats().total_writes;
+        ring_buffer.write(event.request_id, &serialized).unwrap();
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            serve(listener, router, audit_handle, async {}),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let stats = ring_buffer.stats();
+        assert_eq!(stats.total_writes, writes + 1);
+        assert_eq!(stats.total_reads, stats.total_writes);
+    }
 }
@@ -1,9 +1,18 @@
+use crate::domain::budget::BudgetEntry;
 use crate::domain::config_types::{
     BatchSize, DatabaseName, DatabasePassword, DatabaseUsername, FlushIntervalMs, Host, LogFormat,
     LogLevel, MaxConnections, Port,
 };
+use crate::domain::pricing::PricingEntry;
 use crate::domain::session::EnvironmentId;
+use crate::infrastructure::eventcore::projections::LiveViewConfig;
+use crate::providers::bedrock::types::AwsRegion;
+use crate::providers::config::ProviderConfig;
 use crate::providers::constants::{config_defaults, config_paths, environments};
+use crate::proxy::storage::AuditEventFormat;
+use crate::proxy::types::{
+    ApiKey, BoundApiKey, OtlpConfig, ShardCount, SpillConfig, UpstreamTlsConfig,
+};
 use config::{Config, Environment, File};
 use serde::Deserialize;
 use std::env;
@@ -17,6 +26,21 @@ pub struct Settings {
     pub database: DatabaseSettings,
     pub eventcore: EventCoreSettings,
     pub logging: LoggingSettings,
+    #[serde(default)]
+    pub proxy: ProxySettings,
+    /// Provider instances, each under its own path prefix; when empty, one
+    /// of each built-in provider is registered under its default prefix
+    #[serde(default)]
+    pub providers: Vec<ProviderConfig>,
+    /// Limits of the in-memory live view
+    #[serde(default)]
+    pub live_view: LiveViewConfig,
+    /// Prices of models, used to record the cost of each request
+    #[serde(default)]
+    pub pricing: Vec<PricingEntry>,
+    /// Spending budgets of applications, checked against recorded costs
+    #[serde(default)]
+    pub budgets: Vec<BudgetEntry>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -40,6 +64,8 @@ pub struct DatabaseSettings {
 pub struct EventCoreSettings {
     pub batch_size: BatchSize,
     pub flush_interval_ms: FlushIntervalMs,
+    /// Audit consumers persisting in parallel, each owning one ring buffer shard
+    pub audit_consumers: ShardCount,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -48,6 +74,39 @@ pub struct LoggingSettings {
     pub format: LogFormat,
 }
 
+/// Proxy settings; every field is optional so existing configs keep working
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ProxySettings {
+    /// API keys accepted by the proxy's auth middleware
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+    /// API keys for the session, search, usage, live view and dead letter APIs
+    #[serde(default)]
+    pub admin_api_keys: Vec<ApiKey>,
+    /// Proxy keys whose requests are attributed to an application; while
+    /// budgets are configured only these keys may make proxied requests
+    #[serde(default)]
+    pub bound_api_keys: Vec<BoundApiKey>,
+    /// Region used for the default Bedrock provider when no `providers` are configured
+    #[serde(default)]
+    pub bedrock_region: Option<AwsRegion>,
+    /// Extra trust roots and mTLS identity for upstream connections
+    #[serde(default)]
+    pub upstream_tls: UpstreamTlsConfig,
+    /// On-disk overflow for audit events; without it they are dropped when
+    /// the ring buffer is full
+    #[serde(default)]
+    pub spill: Option<SpillConfig>,
+    /// Encoding of audit events on the ring buffer and spill log; set to
+    /// `json` while releases that only read JSON may replay the spill log
+    #[serde(default)]
+    pub audit_format: AuditEventFormat,
+    /// Collector to export spans to; without it the proxy records no spans
+    /// and forwards `traceparent` headers unchanged
+    #[serde(default)]
+    pub otlp: Option<OtlpConfig>,
+}
+
 impl Settings {
     pub fn new() -> Result<Self, ConfigError> {
         let environment =
@@ -75,6 +134,10 @@ impl Settings {
                 "eventcore.flush_interval_ms",
                 config_defaults::EVENTCORE_FLUSH_INTERVAL,
             )?
+            .set_default(
+                "eventcore.audit_consumers",
+                config_defaults::EVENTCORE_AUDIT_CONSUMERS,
+            )?
             .set_default("logging.level", config_defaults::LOG_LEVEL)?
             .set_default("logging.format", config_defaults::LOG_FORMAT)?
             // Add configuration file if it exists
@@ -127,4 +190,136 @@ mod tests {
         assert!(url.contains(settings.database.username.as_ref()));
         assert!(url.contains(settings.database.database_name.as_ref()));
     }
+
+    #[test]
+    fn test_proxy_settings_default_to_empty() {
+        let settings: ProxySettings = serde_json::from_str("{}").unwrap();
+        assert!(settings.api_keys.is_empty());
+        assert!(settings.bedrock_region.is_none());
+        assert!(settings.upstream_tls.extra_root_certificates.is_empty());
+        assert!(settings.upstream_tls.client_certificate.is_none());
+        assert_eq!(settings.audit_format, AuditEventFormat::Binary);
+        assert!(settings.otlp.is_none());
+    }
+
+    #[test]
+    fn test_proxy_settings_deserialize_otlp() {
+        let settings: ProxySettings = serde_json::from_str(
+            r#"{"otlp": {
+                "endpoint": "http://otel-collector:4318",
+                "headers": {"authorization": "Bearer collector-token"}
+            }}"#,
+        )
+        .unwrap();
+        let otlp = settings.otlp.unwrap();
+        assert_eq!(otlp.endpoint.as_ref(), "http://otel-collector:4318");
+        assert_eq!(otlp.service_name.as_ref(), "union_square");
+        assert_eq!(*otlp.export_interval_ms.as_ref(), 1_000);
+        assert_eq!(otlp.headers["authorization"], "Bearer collector-token");
+    }
+
+    #[test]
+    fn test_proxy_settings_deserialize_audit_format() {
+        let settings: ProxySettings = serde_json::from_str(r#"{"audit_format": "json"}"#).unwrap();
+        assert_eq!(settings.audit_format, AuditEventFormat::Json);
+    }
+
+    #[test]
+    fn test_proxy_settings_deserialize() {
+        let settings: ProxySettings = serde_json::from_str(
+            r#"{"api_keys": ["key-one", "key-two"], "admin_api_keys": ["admin-key"], "bedrock_region": "us-west-2"}"#,
+        )
+        .unwrap();
+        assert_eq!(settings.api_keys.len(), 2);
+        assert_eq!(settings.admin_api_keys.len(), 1);
+        assert_eq!(settings.bedrock_region.unwrap().as_ref(), "us-west-2");
+    }
+
+    #[test]
+    fn test_proxy_settings_deserialize_bound_api_keys() {
+        let settings: ProxySettings = serde_json::from_str(
+            r#"{"bound_api_keys": [
+                {"key": "support-key", "application_id": "support-bot", "environment": "production"},
+                {"key": "billing-key", "application_id": "billing-bot"}
+            ]}"#,
+        )
+        .unwrap();
+        let support = settings.bound_api_keys[0].binding();
+        assert_eq!(support.application_id.as_ref(), "support-bot");
+        assert_eq!(support.environment.unwrap().as_ref(), "production");
+        assert!(settings.bound_api_keys[1].environment.is_none());
+    }
+
+    #[test]
+    fn test_proxy_settings_deserialize_upstream_tls() {
+        let settings: ProxySettings = serde_json::from_str(
+            r#"{"upstream_tls": {
+                "extra_root_certificates": ["/etc/union_square/corp-ca.pem"],
+                "client_certificate": {
+                    "certificate_chain": "/etc/union_square/egress.pem",
+                    "private_key": "/etc/union_square/egress-key.pem"
+                }
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(settings.upstream_tls.extra_root_certificates.len(), 1);
+        let client = settings.upstream_tls.client_certificate.unwrap();
+        assert!(client.private_key.ends_with("egress-key.pem"));
+    }
+
+    #[test]
+    fn test_settings_deserialize_providers() {
+        let providers: Vec<ProviderConfig> = serde_json::from_str(
+            r#"[
+                {"type": "bedrock", "region": "us-east-1"},
+                {"type": "bedrock", "path_prefix": "/bedrock-eu/", "region": "eu-west-1", "timeout_ms": 30000},
+                {"type": "openai", "path_prefix": "/openai-staging/", "base_url": "http://vllm.staging:8000"},
+                {"type": "azure", "resources": [{
+                    "name": "contoso",
+                    "deployments": {"prod-chat": "gpt-4o-2024-08-06"}
+                }]}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(providers.len(), 4);
+        assert_eq!(providers[1].resolved_path_prefix().as_ref(), "/bedrock-eu/");
+        assert_eq!(
+            providers[2].base_url.as_deref(),
+            Some("http://vllm.staging:8000")
+        );
+        assert_eq!(providers[3].resources[0].deployments.len(), 1);
+    }
+
+    #[test]
+    fn test_settings_deserialize_pricing() {
+        let pricing: Vec<PricingEntry> = serde_json::from_str(
+            r#"[
+                {"provider": "openai", "model": "gpt-4o", "effective_from": "2024-10-01",
+                 "input_per_million": "2.50", "output_per_million": "10.00",
+                 "cache_read_per_million": "1.25"},
+                {"provider": "anthropic", "model": "claude-3-5-sonnet-20241022",
+                 "effective_from": "2024-10-22", "input_per_million": 3, "output_per_million": 15}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(pricing.len(), 2);
+        assert_eq!(pricing[0].input_per_million.to_string(), "2.50");
+        assert_eq!(
+            pricing[0].cache_read_per_million.unwrap().to_string(),
+            "1.25"
+        );
+        assert!(pricing[1].cache_write_per_million.is_none());
+        assert_eq!(pricing[1].output_per_million.to_string(), "15");
+    }
+
+    #[test]
+    fn test_settings_reject_negative_prices() {
+        let result: Result<Vec<PricingEntry>, _> = serde_json::from_str(
+            r#"[{"provider": "openai", "model": "gpt-4o", "effective_from": "2024-10-01",
+                 "input_per_million": "-1", "output_per_million": "10"}]"#,
+        );
+        assert!(result.is_err());
+    }
 }
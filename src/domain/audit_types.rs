@@ -4,7 +4,12 @@
 //! raw proxy types that previously leaked into the domain core. These types
 //! represent business facts rather than transport structures.
 
-use crate::domain::{llm::RequestId, metrics::Timestamp, session::SessionId, types::ErrorMessage};
+use crate::domain::{
+    llm::{LlmProvider, RequestId},
+    metrics::Timestamp,
+    session::{SessionId, SessionMetadata},
+    types::{ErrorMessage, ModelId},
+};
 use serde::{Deserialize, Serialize};
 
 /// Size of HTTP body in bytes
@@ -23,6 +28,53 @@ impl AsRef<usize> for BodySize {
     }
 }
 
+/// Raw HTTP body bytes captured at the proxy boundary
+///
+/// Serialized as base64 so stored events stay compact JSON strings.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BodyContent(#[serde(with = "base64_bytes")] Vec<u8>);
+
+impl BodyContent {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for BodyContent {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Serde helper storing byte buffers as standard base64 strings
+pub(crate) mod base64_bytes {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD
+            .decode(encoded.as_bytes())
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 /// Audit event captured at the proxy boundary, converted to domain facts
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AuditEvent {
@@ -75,6 +127,17 @@ pub enum AuditEventType {
     },
     /// Response returned to client
     ResponseReturned { duration_ms: DurationMs },
+    /// Session identity and metadata supplied by the client with a request
+    SessionContextReceived { metadata: SessionMetadata },
+    /// Provider that served a routed request, and the model it reported
+    ProviderResolved {
+        provider: LlmProvider,
+        model_id: Option<ModelId>,
+    },
+    /// Request body reassembled from captured chunks
+    RequestBodyCaptured { body: BodyContent, truncated: bool },
+    /// Response body reassembled from captured chunks
+    ResponseBodyCaptured { body: BodyContent, truncated: bool },
     /// Error during processing
     Error {
         error: ErrorMessage,
@@ -250,6 +313,10 @@ pub enum AuditEventKind {
     RequestForwarded,
     ResponseReceived,
     ResponseReturned,
+    SessionContextReceived,
+    ProviderResolved,
+    RequestBodyCaptured,
+    ResponseBodyCaptured,
     Error,
 }
 
@@ -260,6 +327,10 @@ impl From<&AuditEventType> for AuditEventKind {
             AuditEventType::RequestForwarded { .. } => Self::RequestForwarded,
             AuditEventType::ResponseReceived { .. } => Self::ResponseReceived,
             AuditEventType::ResponseReturned { .. } => Self::ResponseReturned,
+            AuditEventType::SessionContextReceived { .. } => Self::SessionContextReceived,
+            AuditEventType::ProviderResolved { .. } => Self::ProviderResolved,
+            AuditEventType::RequestBodyCaptured { .. } => Self::RequestBodyCaptured,
+            AuditEventType::ResponseBodyCaptured { .. } => Self::ResponseBodyCaptured,
             AuditEventType::Error { .. } => Self::Error,
         }
     }
@@ -338,4 +409,13 @@ mod tests {
         let decoded: AuditEventType = serde_json::from_str(&json).unwrap();
         assert_eq!(event, decoded);
     }
+
+    #[test]
+    fn body_content_serializes_as_base64() {
+        let body = BodyContent::new(b"{\"model\":\"gpt-4\"}".to_vec());
+        let json = serde_json::to_string(&body).unwrap();
+        assert_eq!(json, "\"eyJtb2RlbCI6ImdwdC00In0=\"");
+        let decoded: BodyContent = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, body);
+    }
 }
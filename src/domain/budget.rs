@@ -0,0 +1,439 @@
+//! Spending budgets of applications
+//!
+//! A budget caps what one application, in one environment or in all of them,
+//! spends in a UTC day or calendar month. Spend crossing one of the budget's
+//! alert thresholds is recorded once per period. A budget with a hard limit
+//! also stops the application's new requests once its spend reaches the limit,
+//! until the next period starts.
+//!
+//! Spend is the cost recorded for requests received in the period, so it
+//! is known only once responses are priced and may run past the limit by the
+//! requests already in flight.
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveTime, TimeDelta, Utc};
+use nutype::nutype;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::domain::pricing::UsdAmount;
+use crate::domain::session::{ApplicationId, EnvironmentId};
+
+/// Largest alert threshold, in percent of the limit
+const MAX_THRESHOLD_PERCENT: u16 = 1000;
+
+/// Length of a budget period, aligned to UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    /// Start of the period `at` falls in
+    pub fn start_of(self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let day = at.date_naive();
+        let first = match self {
+            Self::Daily => day,
+            Self::Monthly => day.with_day(1).unwrap_or(day),
+        };
+        midnight(first)
+    }
+
+    /// Start of the period after the one `at` falls in
+    pub fn end_of(self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let start = self.start_of(at);
+        match self {
+            Self::Daily => start + TimeDelta::days(1),
+            Self::Monthly => start
+                .checked_add_months(Months::new(1))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+        }
+    }
+}
+
+impl fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn midnight(day: NaiveDate) -> DateTime<Utc> {
+    day.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// Share of a budget's limit, in percent, at which an alert is recorded
+#[nutype(
+    validate(greater = 0, less_or_equal = MAX_THRESHOLD_PERCENT),
+    derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Hash,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display
+    )
+)]
+pub struct AlertThreshold(u16);
+
+/// HTTP status requests over a hard limit are rejected with: 429 or 402
+#[nutype(
+    validate(predicate = |status| *status == 429 || *status == 402),
+    default = 429,
+    derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        Hash,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display,
+        Default
+    )
+)]
+pub struct RejectStatus(u16);
+
+/// Application, and optionally environment, a budget applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BudgetScope {
+    pub application_id: ApplicationId,
+    /// `None` covers every environment of the application
+    pub environment_id: Option<EnvironmentId>,
+}
+
+impl BudgetScope {
+    /// Whether requests of `application_id` in `environment_id` count against this scope
+    pub fn covers(
+        &self,
+        application_id: &ApplicationId,
+        environment_id: Option<&EnvironmentId>,
+    ) -> bool {
+        self.application_id == *application_id
+            && self
+                .environment_id
+                .as_ref()
+                .is_none_or(|scoped| Some(scoped) == environment_id)
+    }
+}
+
+impl fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.environment_id {
+            Some(environment_id) => write!(f, "{} in {environment_id}", self.application_id),
+            None => write!(f, "{}", self.application_id),
+        }
+    }
+}
+
+/// One configured budget, as written under `[[budgets]]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetEntry {
+    pub application_id: ApplicationId,
+    /// Environment the budget is limited to; every environment when not set
+    #[serde(default)]
+    pub environment: Option<EnvironmentId>,
+    pub period: BudgetPeriod,
+    pub limit_usd: UsdAmount,
+    /// Percentages of the limit at which an alert is recorded
+    #[serde(default)]
+    pub alert_at_percent: Vec<AlertThreshold>,
+    /// Reject new requests once spend reaches the limit
+    #[serde(default)]
+    pub hard_limit: bool,
+    #[serde(default)]
+    pub reject_status: RejectStatus,
+}
+
+/// Errors building a [`BudgetCatalog`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BudgetError {
+    #[error("{scope} has two {period} budgets")]
+    DuplicateBudget {
+        scope: BudgetScope,
+        period: BudgetPeriod,
+    },
+    #[error("the {period} budget of {scope} has a zero limit")]
+    ZeroLimit {
+        scope: BudgetScope,
+        period: BudgetPeriod,
+    },
+}
+
+/// A budget ready to be checked against spend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Budget {
+    pub scope: BudgetScope,
+    pub period: BudgetPeriod,
+    pub limit: UsdAmount,
+    /// Ascending and without duplicates
+    pub alerts: Vec<AlertThreshold>,
+    pub hard_limit: bool,
+    pub reject_status: RejectStatus,
+}
+
+impl Budget {
+    /// Thresholds spend passed on its way from `before` to `after`
+    pub fn crossed_thresholds(&self, before: Decimal, after: Decimal) -> Vec<AlertThreshold> {
+        self.alerts
+            .iter()
+            .copied()
+            .filter(|threshold| {
+                let amount = self.threshold_amount(*threshold);
+                before < amount && amount <= after
+            })
+            .collect()
+    }
+
+    /// Whether `spent` uses up the limit
+    pub fn is_spent(&self, spent: Decimal) -> bool {
+        spent >= self.limit.into_inner()
+    }
+
+    fn threshold_amount(&self, threshold: AlertThreshold) -> Decimal {
+        self.limit.into_inner() * Decimal::from(threshold.into_inner()) / Decimal::ONE_HUNDRED
+    }
+}
+
+/// Every configured budget
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BudgetCatalog {
+    budgets: Vec<Budget>,
+}
+
+impl BudgetCatalog {
+    /// Build the catalog from configured budgets
+    ///
+    /// A scope may have one daily and one monthly budget.
+    pub fn new(entries: Vec<BudgetEntry>) -> Result<Self, BudgetError> {
+        let mut budgets: Vec<Budget> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let scope = BudgetScope {
+                application_id: entry.application_id,
+                environment_id: entry.environment,
+            };
+            if entry.limit_usd.into_inner().is_zero() {
+                return Err(BudgetError::ZeroLimit {
+                    scope,
+                    period: entry.period,
+                });
+            }
+            if budgets
+                .iter()
+                .any(|budget| budget.scope == scope && budget.period == entry.period)
+            {
+                return Err(BudgetError::DuplicateBudget {
+                    scope,
+                    period: entry.period,
+                });
+            }
+            let mut alerts = entry.alert_at_percent;
+            alerts.sort_unstable();
+            alerts.dedup();
+            budgets.push(Budget {
+                scope,
+                period: entry.period,
+                limit: entry.limit_usd,
+                alerts,
+                hard_limit: entry.hard_limit,
+                reject_status: entry.reject_status,
+            });
+        }
+        Ok(Self { budgets })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.budgets.is_empty()
+    }
+
+    pub fn budgets(&self) -> &[Budget] {
+        &self.budgets
+    }
+
+    /// Start of the earliest period still running at `at`
+    pub fn earliest_period_start(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.budgets
+            .iter()
+            .map(|budget| budget.period.start_of(at))
+            .min()
+    }
+}
+
+/// Something a budget's spend did that is worth recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BudgetAlert {
+    /// Spend reached `threshold` percent of the limit
+    ThresholdCrossed {
+        threshold: AlertThreshold,
+        spent: UsdAmount,
+    },
+    /// Spend reached a hard limit; new requests are rejected
+    LimitReached { spent: UsdAmount },
+}
+
+/// Why a request was refused: its application spent a hard limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetRejection {
+    pub scope: BudgetScope,
+    pub period: BudgetPeriod,
+    pub limit: UsdAmount,
+    pub spent: UsdAmount,
+    /// When the next period starts and requests are accepted again
+    pub resets_at: DateTime<Utc>,
+    pub status: RejectStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::str::FromStr;
+
+    fn usd(value: &str) -> UsdAmount {
+        UsdAmount::try_new(Decimal::from_str(value).unwrap()).unwrap()
+    }
+
+    fn entry(environment: Option<&str>, period: BudgetPeriod) -> BudgetEntry {
+        BudgetEntry {
+            application_id: ApplicationId::try_new("support-bot".to_string()).unwrap(),
+            environment: environment.map(|name| EnvironmentId::try_new(name.to_string()).unwrap()),
+            period,
+            limit_usd: usd("200"),
+            alert_at_percent: [100, 50, 80, 50]
+                .into_iter()
+                .map(|percent| AlertThreshold::try_new(percent).unwrap())
+                .collect(),
+            hard_limit: true,
+            reject_status: RejectStatus::default(),
+        }
+    }
+
+    #[test]
+    fn periods_are_aligned_to_utc_days_and_months() {
+        let at = Utc.with_ymd_and_hms(2026, 12, 17, 15, 30, 0).unwrap();
+
+        assert_eq!(
+            BudgetPeriod::Daily.start_of(at),
+            Utc.with_ymd_and_hms(2026, 12, 17, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            BudgetPeriod::Daily.end_of(at),
+            Utc.with_ymd_and_hms(2026, 12, 18, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            BudgetPeriod::Monthly.start_of(at),
+            Utc.with_ymd_and_hms(2026, 12, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            BudgetPeriod::Monthly.end_of(at),
+            Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn thresholds_are_crossed_once_on_the_way_up() {
+        let catalog = BudgetCatalog::new(vec![entry(None, BudgetPeriod::Monthly)]).unwrap();
+        let budget = &catalog.budgets()[0];
+        let percents = |crossed: Vec<AlertThreshold>| -> Vec<u16> {
+            crossed
+                .into_iter()
+                .map(AlertThreshold::into_inner)
+                .collect()
+        };
+
+        assert_eq!(budget.alerts.len(), 3);
+        assert_eq!(
+            percents(budget.crossed_thresholds(Decimal::from(90), Decimal::from(170))),
+            vec![50, 80]
+        );
+        assert!(budget
+            .crossed_thresholds(Decimal::from(170), Decimal::from(180))
+            .is_empty());
+        assert_eq!(
+            percents(budget.crossed_thresholds(Decimal::from(180), Decimal::from(200))),
+            vec![100]
+        );
+        assert!(budget.is_spent(Decimal::from(200)));
+        assert!(!budget.is_spent(Decimal::from_str("199.99").unwrap()));
+    }
+
+    #[test]
+    fn scopes_without_an_environment_cover_all_of_them() {
+        let application_id = ApplicationId::try_new("support-bot".to_string()).unwrap();
+        let production = EnvironmentId::try_new("production".to_string()).unwrap();
+        let any = BudgetScope {
+            application_id: application_id.clone(),
+            environment_id: None,
+        };
+        let scoped = BudgetScope {
+            environment_id: Some(production.clone()),
+            ..any.clone()
+        };
+
+        assert!(any.covers(&application_id, None));
+        assert!(any.covers(&application_id, Some(&production)));
+        assert!(scoped.covers(&application_id, Some(&production)));
+        assert!(!scoped.covers(&application_id, None));
+        assert!(!scoped.covers(
+            &ApplicationId::try_new("search".to_string()).unwrap(),
+            Some(&production)
+        ));
+    }
+
+    #[test]
+    fn invalid_catalogs_are_rejected() {
+        assert!(matches!(
+            BudgetCatalog::new(vec![
+                entry(Some("production"), BudgetPeriod::Daily),
+                entry(Some("production"), BudgetPeriod::Daily),
+            ]),
+            Err(BudgetError::DuplicateBudget { .. })
+        ));
+        assert!(BudgetCatalog::new(vec![
+            entry(Some("production"), BudgetPeriod::Daily),
+            entry(None, BudgetPeriod::Daily),
+            entry(Some("production"), BudgetPeriod::Monthly),
+        ])
+        .is_ok());
+
+        let free = BudgetEntry {
+            limit_usd: usd("0"),
+            ..entry(None, BudgetPeriod::Daily)
+        };
+        assert!(matches!(
+            BudgetCatalog::new(vec![free]),
+            Err(BudgetError::ZeroLimit { .. })
+        ));
+        assert!(AlertThreshold::try_new(0).is_err());
+        assert!(RejectStatus::try_new(403).is_err());
+    }
+
+    #[test]
+    fn budget_entries_deserialize_with_defaults() {
+        let entry: BudgetEntry = serde_json::from_str(
+            r#"{"application_id": "support-bot", "period": "monthly", "limit_usd": "500"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(entry.environment, None);
+        assert!(entry.alert_at_percent.is_empty());
+        assert!(!entry.hard_limit);
+        assert_eq!(entry.reject_status.into_inner(), 429);
+    }
+}
@@ -4,6 +4,7 @@
 //! from chunk events and process them when complete.
 
 use crate::domain::llm::RequestId;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use thiserror::Error;
 
@@ -57,6 +58,7 @@ pub enum AuditBufferError {
 pub struct BufferedData {
     chunks: Vec<(ChunkOffset, ChunkData)>,
     total_size: usize,
+    last_chunk_at: Option<DateTime<Utc>>,
 }
 
 impl BufferedData {
@@ -64,6 +66,7 @@ impl BufferedData {
         Self {
             chunks: Vec::new(),
             total_size: 0,
+            last_chunk_at: None,
         }
     }
 
@@ -133,6 +136,7 @@ impl BufferedData {
 }
 
 /// Manager for buffering audit data across multiple requests
+#[derive(Debug, Clone)]
 pub struct AuditBufferManager {
     request_buffers: HashMap<RequestId, BufferedData>,
     response_buffers: HashMap<RequestId, BufferedData>,
@@ -146,30 +150,38 @@ impl AuditBufferManager {
         }
     }
 
-    /// Add a request chunk
+    /// Add a request chunk captured at `captured_at`
     pub fn add_request_chunk(
         &mut self,
         request_id: RequestId,
         offset: ChunkOffset,
         data: ChunkData,
+        captured_at: DateTime<Utc>,
     ) -> Result<(), AuditBufferError> {
-        self.request_buffers
-            .entry(request_id)
-            .or_insert_with(BufferedData::new)
-            .add_chunk(offset, data)
+        add_chunk(
+            &mut self.request_buffers,
+            request_id,
+            offset,
+            data,
+            captured_at,
+        )
     }
 
-    /// Add a response chunk
+    /// Add a response chunk captured at `captured_at`
     pub fn add_response_chunk(
         &mut self,
         request_id: RequestId,
         offset: ChunkOffset,
         data: ChunkData,
+        captured_at: DateTime<Utc>,
     ) -> Result<(), AuditBufferError> {
-        self.response_buffers
-            .entry(request_id)
-            .or_insert_with(BufferedData::new)
-            .add_chunk(offset, data)
+        add_chunk(
+            &mut self.response_buffers,
+            request_id,
+            offset,
+            data,
+            captured_at,
+        )
     }
 
     /// Check if request body is complete and return it
@@ -196,11 +208,77 @@ impl AuditBufferManager {
         None
     }
 
+    /// Finish the request body once the capture reports its final size
+    ///
+    /// The buffer is released either way; `None` means chunks are missing.
+    pub fn finish_request_body(
+        &mut self,
+        request_id: &RequestId,
+        total_size: usize,
+    ) -> Option<Vec<u8>> {
+        finish_body(self.request_buffers.remove(request_id), total_size)
+    }
+
+    /// Finish the response body once the capture reports its final size
+    ///
+    /// The buffer is released either way; `None` means chunks are missing.
+    pub fn finish_response_body(
+        &mut self,
+        request_id: &RequestId,
+        total_size: usize,
+    ) -> Option<Vec<u8>> {
+        finish_body(self.response_buffers.remove(request_id), total_size)
+    }
+
+    /// Number of requests with a partially received body
+    pub fn pending_count(&self) -> usize {
+        self.request_buffers.len() + self.response_buffers.len()
+    }
+
     /// Clean up old buffers for a request
     pub fn cleanup_request(&mut self, request_id: &RequestId) {
         self.request_buffers.remove(request_id);
         self.response_buffers.remove(request_id);
     }
+
+    /// Drop the bodies that received no chunk since `idle_since`
+    ///
+    /// A body whose completion event was lost would otherwise stay buffered
+    /// forever. Returns the number of bodies dropped.
+    pub fn evict_idle(&mut self, idle_since: DateTime<Utc>) -> usize {
+        let before = self.pending_count();
+        let is_active = |_: &RequestId, buffer: &mut BufferedData| {
+            buffer
+                .last_chunk_at
+                .is_some_and(|last_chunk_at| last_chunk_at >= idle_since)
+        };
+        self.request_buffers.retain(is_active);
+        self.response_buffers.retain(is_active);
+        before - self.pending_count()
+    }
+}
+
+fn add_chunk(
+    buffers: &mut HashMap<RequestId, BufferedData>,
+    request_id: RequestId,
+    offset: ChunkOffset,
+    data: ChunkData,
+    captured_at: DateTime<Utc>,
+) -> Result<(), AuditBufferError> {
+    let buffer = buffers.entry(request_id).or_insert_with(BufferedData::new);
+    buffer.last_chunk_at = Some(captured_at);
+    buffer.add_chunk(offset, data)
+}
+
+fn finish_body(buffer: Option<BufferedData>, total_size: usize) -> Option<Vec<u8>> {
+    match buffer {
+        // An empty body produces no chunks at all
+        None => (total_size == 0).then(Vec::new),
+        Some(mut buffer) => {
+            buffer.set_total_size(total_size);
+            buffer.reconstruct()
+        }
+    }
 }
 
 impl Default for AuditBufferManager {
@@ -280,14 +358,16 @@ mod tests {
             .add_request_chunk(
                 request_id.clone(),
                 ChunkOffset::new(0),
-                ChunkData::new(vec![1, 2, 3])
+                ChunkData::new(vec![1, 2, 3]),
+                Utc::now(),
             )
             .is_ok());
         assert!(manager
             .add_request_chunk(
                 request_id.clone(),
                 ChunkOffset::new(3),
-                ChunkData::new(vec![4, 5, 6])
+                ChunkData::new(vec![4, 5, 6]),
+                Utc::now(),
             )
             .is_ok());
 
@@ -300,4 +380,95 @@ mod tests {
         // Buffer should be cleaned up after reconstruction
         assert_eq!(manager.get_complete_request_body(&request_id), None);
     }
+
+    #[test]
+    fn test_finish_body_with_known_size() {
+        let mut manager = AuditBufferManager::new();
+        let request_id = RequestId::generate();
+
+        manager
+            .add_response_chunk(
+                request_id.clone(),
+                ChunkOffset::new(3),
+                ChunkData::new(vec![4, 5]),
+                Utc::now(),
+            )
+            .unwrap();
+        manager
+            .add_response_chunk(
+                request_id.clone(),
+                ChunkOffset::new(0),
+                ChunkData::new(vec![1, 2, 3]),
+                Utc::now(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            manager.finish_response_body(&request_id, 5),
+            Some(vec![1, 2, 3, 4, 5])
+        );
+        assert_eq!(manager.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_finish_body_with_missing_chunk_releases_buffer() {
+        let mut manager = AuditBufferManager::new();
+        let request_id = RequestId::generate();
+
+        manager
+            .add_request_chunk(
+                request_id.clone(),
+                ChunkOffset::new(0),
+                ChunkData::new(vec![1, 2, 3]),
+                Utc::now(),
+            )
+            .unwrap();
+
+        assert_eq!(manager.finish_request_body(&request_id, 6), None);
+        assert_eq!(manager.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_finish_empty_body() {
+        let mut manager = AuditBufferManager::new();
+        let request_id = RequestId::generate();
+
+        assert_eq!(
+            manager.finish_request_body(&request_id, 0),
+            Some(Vec::new())
+        );
+        assert_eq!(manager.finish_request_body(&request_id, 3), None);
+    }
+
+    #[test]
+    fn test_idle_bodies_are_evicted() {
+        let mut manager = AuditBufferManager::new();
+        let stalled = RequestId::generate();
+        let streaming = RequestId::generate();
+        let now = Utc::now();
+
+        manager
+            .add_request_chunk(
+                stalled.clone(),
+                ChunkOffset::new(0),
+                ChunkData::new(vec![1, 2, 3]),
+                now - chrono::Duration::minutes(20),
+            )
+            .unwrap();
+        manager
+            .add_response_chunk(
+                streaming.clone(),
+                ChunkOffset::new(0),
+                ChunkData::new(vec![4, 5, 6]),
+                now,
+            )
+            .unwrap();
+
+        assert_eq!(manager.evict_idle(now - chrono::Duration::minutes(10)), 1);
+        assert_eq!(manager.pending_count(), 1);
+        assert_eq!(
+            manager.finish_response_body(&streaming, 3),
+            Some(vec![4, 5, 6])
+        );
+    }
 }
@@ -3,17 +3,30 @@
 //! These commands map from the audit path events to EventCore commands,
 //! enabling persistence of all proxy operations to the event store.
 
-use eventcore::{CommandError, CommandLogic, NewEvents, StreamId};
+use eventcore::{
+    CommandError, CommandLogic, CommandStreams, NewEvents, StreamDeclarations, StreamId,
+};
 use eventcore_macros::Command;
 use serde::{Deserialize, Serialize};
 
 use crate::domain::{
-    audit_types, events::DomainEvent, llm, metrics::Timestamp, session::SessionId,
+    audit_types,
+    events::DomainEvent,
+    llm,
+    metrics::Timestamp,
+    session::{ApplicationId, EnvironmentId, SessionId},
 };
 
 use crate::domain::parsed_llm_request::ParsedLlmRequest;
+use crate::domain::pricing::CostCalculation;
+use crate::domain::types::{ErrorMessage, Latency};
 
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::sync::Arc;
+
+/// Lowest HTTP status with which a provider fails a request
+const FIRST_ERROR_STATUS: u16 = 400;
 
 /// Wrapper for parsed LLM request that includes any parsing error
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -87,6 +100,12 @@ pub enum RequestLifecycle {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RequestState {
     pub lifecycle: RequestLifecycle,
+    /// Whether the request body has been parsed into `LlmRequestReceived`
+    pub request_parsed: bool,
+    /// Application the request was made for, from its session context
+    pub application_id: Option<ApplicationId>,
+    /// Environment the request was made in, from its session context
+    pub environment_id: Option<EnvironmentId>,
 }
 
 impl fmt::Display for RequestState {
@@ -122,6 +141,9 @@ impl RequestState {
     pub const fn new() -> Self {
         Self {
             lifecycle: RequestLifecycle::NotStarted,
+            request_parsed: false,
+            application_id: None,
+            environment_id: None,
         }
     }
 
@@ -141,6 +163,14 @@ impl RequestState {
     /// Apply an event to update the state
     /// This enforces valid state transitions
     pub fn apply(&mut self, event: &DomainEvent) {
+        match event {
+            DomainEvent::LlmRequestReceived { .. } => self.request_parsed = true,
+            DomainEvent::SessionContextRecorded { metadata, .. } => {
+                self.application_id = metadata.application_id().cloned();
+                self.environment_id = metadata.environment_id().cloned();
+            }
+            _ => {}
+        }
         self.lifecycle = self.lifecycle.clone().transition(event);
     }
 
@@ -149,6 +179,11 @@ impl RequestState {
         self.lifecycle.is_request_received()
     }
 
+    /// Check if the request body has been parsed
+    pub const fn is_request_parsed(&self) -> bool {
+        self.request_parsed
+    }
+
     /// Check if the request has been forwarded
     pub const fn is_request_forwarded(&self) -> bool {
         self.lifecycle.is_request_forwarded()
@@ -309,6 +344,18 @@ impl RequestLifecycle {
         }
     }
 
+    /// When the request was received; failed requests no longer say
+    pub const fn received_at(&self) -> Option<Timestamp> {
+        match self {
+            RequestLifecycle::Deferred { received_at, .. }
+            | RequestLifecycle::Received { received_at, .. }
+            | RequestLifecycle::Forwarded { received_at, .. }
+            | RequestLifecycle::ResponseReceived { received_at, .. }
+            | RequestLifecycle::Completed { received_at, .. } => Some(*received_at),
+            RequestLifecycle::NotStarted | RequestLifecycle::Failed { .. } => None,
+        }
+    }
+
     /// Check if the request has been received (including deferred)
     pub const fn is_request_received(&self) -> bool {
         matches!(
@@ -376,9 +423,12 @@ pub struct RecordAuditEvent {
     pub session_id: SessionId,
     pub audit_event: audit_types::AuditEventType,
     pub timestamp: Timestamp,
-    /// Optional parsed request data (only used for RequestReceived events with body)
+    /// Optional parsed request data (only used for RequestReceived and RequestBodyCaptured events)
     #[serde(skip)]
     pub parsed_request: Option<ParsedLlmRequestWithError>,
+    /// Optional priced usage (only used for ResponseBodyCaptured events)
+    #[serde(skip)]
+    pub calculated_cost: Option<CostCalculation>,
 }
 
 impl RecordAuditEvent {
@@ -404,15 +454,31 @@ impl RecordAuditEvent {
     /// Parsing must happen at the adapter boundary; the domain command
     /// only accepts already-parsed semantic facts.
     pub fn with_parsed_request(mut self, parsed: Option<ParsedLlmRequestWithError>) -> Self {
-        // Only set parsed request for RequestReceived events
+        // Only set parsed request for events that carry the request body
         if matches!(
             &self.audit_event,
             audit_types::AuditEventType::RequestReceived { .. }
+                | audit_types::AuditEventType::RequestBodyCaptured { .. }
         ) {
             self.parsed_request = parsed;
         }
         self
     }
+
+    /// Set the cost of the usage reported by the response body.
+    ///
+    /// Pricing happens in the shell, where the catalog and the request's
+    /// model are known; the command records the result as a fact.
+    pub fn with_calculated_cost(mut self, cost: Option<CostCalculation>) -> Self {
+        // Only set the cost for ResponseBodyCaptured events
+        if matches!(
+            &self.audit_event,
+            audit_types::AuditEventType::ResponseBodyCaptured { .. }
+        ) {
+            self.calculated_cost = cost;
+        }
+        self
+    }
 }
 
 /// Error messages as constants for compile-time validation
@@ -482,11 +548,32 @@ mod transformers {
     }
 
     /// Transform ResponseReceived audit event to domain event
+    ///
+    /// Error statuses from the provider fail the request; other responses
+    /// complete it, with the provider's response time as latency.
     pub fn response_received_to_domain(
         request_stream: StreamId,
         request_id: llm::RequestId,
+        status: audit_types::HttpStatusCode,
+        duration_ms: audit_types::DurationMs,
         timestamp: Timestamp,
     ) -> Result<DomainEvent, CommandError> {
+        if *status.as_ref() >= FIRST_ERROR_STATUS {
+            let error_message =
+                ErrorMessage::try_new(format!("Provider responded with HTTP {}", status.as_ref()))
+                    .map_err(|e| {
+                        CommandError::ValidationError(format!(
+                            "Failed to create error message: {e}"
+                        ))
+                    })?;
+            return Ok(DomainEvent::LlmRequestFailed {
+                stream_id: request_stream,
+                request_id,
+                error_message,
+                failed_at: timestamp,
+            });
+        }
+
         // For now, we don't have the response body here
         // TODO: Implement response body parsing similar to request parsing
         let response_text = crate::domain::types::ResponseText::try_new(
@@ -498,7 +585,10 @@ mod transformers {
             ))
         })?;
 
-        let metadata = crate::domain::llm::ResponseMetadata::default();
+        let mut metadata = crate::domain::llm::ResponseMetadata::default();
+        if let Ok(latency) = Latency::try_new(*duration_ms.as_ref()) {
+            metadata = metadata.with_latency_ms(latency);
+        }
 
         Ok(DomainEvent::LlmResponseReceived {
             stream_id: request_stream,
@@ -515,7 +605,10 @@ impl CommandLogic for RecordAuditEvent {
     type Event = DomainEvent;
 
     fn apply(&self, mut state: Self::State, event: &Self::Event) -> Self::State {
-        state.apply(event);
+        // The session stream carries events for every request in the session
+        if event.request_id() == Some(&self.request_id) {
+            state.apply(event);
+        }
         state
     }
 
@@ -629,12 +722,18 @@ impl CommandLogic for RecordAuditEvent {
                     });
                 }
             }
-            ResponseReceived { .. } => {
+            ResponseReceived {
+                status,
+                duration_ms,
+                ..
+            } => {
                 // Only emit response if request has been forwarded and response not yet received
                 if state.is_request_forwarded() && !state.is_response_received() {
                     let event = transformers::response_received_to_domain(
                         self.request_stream.clone(),
                         self.request_id.clone(),
+                        *status,
+                        *duration_ms,
                         self.timestamp,
                     )?;
 
@@ -671,6 +770,70 @@ impl CommandLogic for RecordAuditEvent {
                 // For now, we don't emit any specific event for response returned
                 // The LlmResponseReceived event already captures the completion
             }
+            ProviderResolved { .. } => {
+                // Only the audit path needs it, to know the model of
+                // requests whose body does not name one
+            }
+            SessionContextReceived { metadata } => {
+                events.push(DomainEvent::SessionContextRecorded {
+                    stream_id: self.session_stream.clone(),
+                    session_id: self.session_id.clone(),
+                    request_id: self.request_id.clone(),
+                    metadata: metadata.clone(),
+                    recorded_at: self.timestamp,
+                });
+            }
+            RequestBodyCaptured { body, truncated } => {
+                events.push(DomainEvent::LlmRequestBodyCaptured {
+                    stream_id: self.request_stream.clone(),
+                    request_id: self.request_id.clone(),
+                    session_id: self.session_id.clone(),
+                    body: body.clone(),
+                    truncated: *truncated,
+                    captured_at: self.timestamp,
+                });
+
+                // A deferred request is received once its body is parsed
+                let parsed = self
+                    .parsed_request
+                    .as_ref()
+                    .and_then(|parsed_request| parsed_request.parsed.as_ref());
+                if let Some(parsed) = parsed {
+                    if state.request_id().is_some() && !state.is_request_parsed() {
+                        events.push(transformers::request_received_to_domain(
+                            self.session_stream.clone(),
+                            self.request_id.clone(),
+                            self.session_id.clone(),
+                            state.lifecycle.received_at().unwrap_or(self.timestamp),
+                            parsed,
+                        )?);
+                    }
+                }
+            }
+            ResponseBodyCaptured { body, truncated } => {
+                events.push(DomainEvent::LlmResponseBodyCaptured {
+                    stream_id: self.request_stream.clone(),
+                    request_id: self.request_id.clone(),
+                    session_id: self.session_id.clone(),
+                    body: body.clone(),
+                    truncated: *truncated,
+                    captured_at: self.timestamp,
+                });
+                if let Some(calculation) = &self.calculated_cost {
+                    events.push(DomainEvent::CostCalculated {
+                        stream_id: self.request_stream.clone(),
+                        request_id: self.request_id.clone(),
+                        session_id: self.session_id.clone(),
+                        application_id: state.application_id.clone(),
+                        environment_id: state.environment_id.clone(),
+                        model_version: calculation.model_version.clone(),
+                        usage: calculation.usage,
+                        cost: calculation.cost,
+                        price_effective_from: calculation.price_effective_from,
+                        calculated_at: self.timestamp,
+                    });
+                }
+            }
             _ => {
                 // Other audit event types not yet handled
 
@@ -692,6 +855,98 @@ impl CommandLogic for RecordAuditEvent {
     }
 }
 
+/// Several audit commands persisted in a single EventCore write
+///
+/// Commands are handled in order against per-request state, so a batch may
+/// carry several steps of one request's lifecycle: each command sees the
+/// events produced by the commands before it. Clones share the commands, so
+/// a caller can keep a handle on a batch it hands to the event store.
+#[derive(Debug, Clone)]
+pub struct RecordAuditEvents {
+    commands: Arc<Vec<RecordAuditEvent>>,
+    request_ids: Arc<HashSet<llm::RequestId>>,
+}
+
+impl RecordAuditEvents {
+    /// Batch the given commands, or `None` when there are none
+    pub fn new(commands: Vec<RecordAuditEvent>) -> Option<Self> {
+        if commands.is_empty() {
+            return None;
+        }
+        let request_ids = commands
+            .iter()
+            .map(|command| command.request_id.clone())
+            .collect();
+        Some(Self {
+            commands: Arc::new(commands),
+            request_ids: Arc::new(request_ids),
+        })
+    }
+
+    /// Number of audit events in the batch
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Always false; empty batches cannot be built
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// The batched commands, in order
+    ///
+    /// The commands are only copied if another clone of the batch is alive.
+    pub fn into_commands(self) -> Vec<RecordAuditEvent> {
+        Arc::try_unwrap(self.commands).unwrap_or_else(|shared| (*shared).clone())
+    }
+}
+
+impl CommandStreams for RecordAuditEvents {
+    fn stream_declarations(&self) -> StreamDeclarations {
+        let mut seen = HashSet::new();
+        let streams = self
+            .commands
+            .iter()
+            .flat_map(|command| [&command.session_stream, &command.request_stream])
+            .filter(|stream| seen.insert(*stream))
+            .cloned()
+            .collect::<Vec<_>>();
+        StreamDeclarations::try_from_streams(streams)
+            .expect("a non-empty batch declares distinct streams")
+    }
+}
+
+impl CommandLogic for RecordAuditEvents {
+    type State = HashMap<llm::RequestId, RequestState>;
+    type Event = DomainEvent;
+
+    fn apply(&self, mut state: Self::State, event: &Self::Event) -> Self::State {
+        // Session streams also carry events for requests outside the batch
+        if let Some(request_id) = event.request_id() {
+            if self.request_ids.contains(request_id) {
+                state.entry(request_id.clone()).or_default().apply(event);
+            }
+        }
+        state
+    }
+
+    fn handle(&self, mut state: Self::State) -> Result<NewEvents<Self::Event>, CommandError> {
+        let mut events = Vec::new();
+
+        for command in self.commands.iter() {
+            let request_state = state.remove(&command.request_id).unwrap_or_default();
+            let produced: Vec<DomainEvent> = command.handle(request_state.clone())?.into();
+            let request_state = produced.iter().fold(request_state, |request_state, event| {
+                command.apply(request_state, event)
+            });
+            state.insert(command.request_id.clone(), request_state);
+            events.extend(produced);
+        }
+
+        Ok(events.into())
+    }
+}
+
 // The redundant command structs have been removed in favor of the unified RecordAuditEvent command
 
 /// Command to process a pre-parsed LLM request and emit domain events.
@@ -716,7 +971,10 @@ impl CommandLogic for ProcessRequestBody {
     type Event = DomainEvent;
 
     fn apply(&self, mut state: Self::State, event: &Self::Event) -> Self::State {
-        state.apply(event);
+        // The session stream carries events for every request in the session
+        if event.request_id() == Some(&self.request_id) {
+            state.apply(event);
+        }
         state
     }
 
@@ -842,6 +1100,7 @@ mod tests {
             },
             timestamp: Timestamp::now(),
             parsed_request: None,
+            calculated_cost: None,
         };
 
         assert!(matches!(
@@ -889,6 +1148,7 @@ mod tests {
                 audit_event: audit_event.clone(),
                 timestamp: Timestamp::now(),
                 parsed_request: None,
+                calculated_cost: None,
             };
 
             assert!(
@@ -928,6 +1188,7 @@ mod tests {
             },
             timestamp: Timestamp::now(),
             parsed_request: None,
+            calculated_cost: None,
         };
 
         // Assert that RecordAuditEvent implements CommandLogic
@@ -962,6 +1223,7 @@ mod tests {
             },
             timestamp: Timestamp::now(),
             parsed_request: None,
+            calculated_cost: None,
         };
 
         // Apply body parsing at adapter boundary
@@ -1217,6 +1479,7 @@ mod tests {
             },
             timestamp: Timestamp::now(),
             parsed_request: None,
+            calculated_cost: None,
         }
         .with_parsed_request(Some(parsed));
 
@@ -1240,6 +1503,172 @@ mod tests {
         assert!(has_parsing_error, "Should emit parsing error event");
     }
 
+    #[tokio::test]
+    async fn test_calculated_cost_is_recorded_with_the_response_body() {
+        use crate::domain::pricing::{ModelPrice, TokenPrice, TokenUsage};
+        use crate::domain::types::TokenCount;
+        use eventcore::RetryPolicy;
+        use eventcore_memory::InMemoryEventStore;
+        use rust_decimal::Decimal;
+
+        let store = InMemoryEventStore::new();
+        let session_id = SessionId::generate();
+        let request_id = llm::RequestId::generate();
+
+        let price = ModelPrice {
+            effective_from: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            input: TokenPrice::try_new(Decimal::new(250, 2)).unwrap(),
+            output: TokenPrice::try_new(Decimal::from(10)).unwrap(),
+            cache_read: None,
+            cache_write: None,
+        };
+        let usage = TokenUsage {
+            input: TokenCount::try_new(1000).unwrap(),
+            output: TokenCount::try_new(100).unwrap(),
+            cache_read: TokenCount::try_new(0).unwrap(),
+            cache_write: TokenCount::try_new(0).unwrap(),
+        };
+        let calculation = CostCalculation {
+            model_version: llm::ModelVersion {
+                provider: llm::LlmProvider::OpenAI,
+                model_id: crate::domain::types::ModelId::try_new("gpt-4o".to_string()).unwrap(),
+            },
+            usage,
+            cost: price.cost(&usage).unwrap(),
+            price_effective_from: price.effective_from,
+        };
+        let command = RecordAuditEvent {
+            session_stream: session_stream(&session_id).unwrap(),
+            request_stream: request_stream(&request_id).unwrap(),
+            request_id: request_id.clone(),
+            session_id: session_id.clone(),
+            audit_event: audit_types::AuditEventType::ResponseBodyCaptured {
+                body: audit_types::BodyContent::new(b"{}".to_vec()),
+                truncated: false,
+            },
+            timestamp: Timestamp::now(),
+            parsed_request: None,
+            calculated_cost: None,
+        }
+        .with_calculated_cost(Some(calculation.clone()));
+
+        eventcore::execute(&store, command, RetryPolicy::default())
+            .await
+            .unwrap();
+
+        let events = store
+            .read_stream::<DomainEvent>(request_stream(&request_id).unwrap())
+            .await
+            .unwrap();
+        let recorded = events
+            .iter()
+            .find_map(|event| match event {
+                DomainEvent::CostCalculated {
+                    model_version,
+                    usage,
+                    cost,
+                    price_effective_from,
+                    ..
+                } => Some((model_version, usage, cost, price_effective_from)),
+                _ => None,
+            })
+            .expect("Should emit CostCalculated");
+        assert_eq!(recorded.0, &calculation.model_version);
+        assert_eq!(recorded.1, &calculation.usage);
+        assert_eq!(recorded.2.total.into_inner(), Decimal::new(35, 4));
+        assert_eq!(recorded.3, &calculation.price_effective_from);
+    }
+
+    #[tokio::test]
+    async fn test_costs_carry_the_application_of_their_own_request() {
+        use crate::domain::pricing::{ModelPrice, TokenPrice, TokenUsage};
+        use crate::domain::session::SessionMetadata;
+        use crate::domain::types::TokenCount;
+        use eventcore::RetryPolicy;
+        use eventcore_memory::InMemoryEventStore;
+        use rust_decimal::Decimal;
+
+        let store = InMemoryEventStore::new();
+        // Two bound keys sending the same client session ID
+        let session_id = SessionId::generate();
+        let command = |request_id: &llm::RequestId, audit_event| RecordAuditEvent {
+            session_stream: session_stream(&session_id).unwrap(),
+            request_stream: request_stream(request_id).unwrap(),
+            request_id: request_id.clone(),
+            session_id: session_id.clone(),
+            audit_event,
+            timestamp: Timestamp::now(),
+            parsed_request: None,
+            calculated_cost: None,
+        };
+        let context = |application: &str| audit_types::AuditEventType::SessionContextReceived {
+            metadata: SessionMetadata::new()
+                .with_application_id(ApplicationId::try_new(application.to_string()).unwrap()),
+        };
+        let price = ModelPrice {
+            effective_from: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            input: TokenPrice::try_new(Decimal::ONE).unwrap(),
+            output: TokenPrice::try_new(Decimal::ONE).unwrap(),
+            cache_read: None,
+            cache_write: None,
+        };
+        let usage = TokenUsage {
+            input: TokenCount::try_new(1000).unwrap(),
+            output: TokenCount::try_new(0).unwrap(),
+            cache_read: TokenCount::try_new(0).unwrap(),
+            cache_write: TokenCount::try_new(0).unwrap(),
+        };
+        let priced = |request_id: &llm::RequestId| {
+            command(
+                request_id,
+                audit_types::AuditEventType::ResponseBodyCaptured {
+                    body: audit_types::BodyContent::new(b"{}".to_vec()),
+                    truncated: false,
+                },
+            )
+            .with_calculated_cost(Some(CostCalculation {
+                model_version: llm::ModelVersion {
+                    provider: llm::LlmProvider::OpenAI,
+                    model_id: crate::domain::types::ModelId::try_new("gpt-4o".to_string()).unwrap(),
+                },
+                usage,
+                cost: price.cost(&usage).unwrap(),
+                price_effective_from: price.effective_from,
+            }))
+        };
+
+        let support = llm::RequestId::generate();
+        let billing = llm::RequestId::generate();
+        let batch = RecordAuditEvents::new(vec![
+            command(&support, context("support-bot")),
+            command(&billing, context("billing-bot")),
+            priced(&support),
+            priced(&billing),
+        ])
+        .unwrap();
+        eventcore::execute(&store, batch, RetryPolicy::default())
+            .await
+            .unwrap();
+
+        for (request_id, application) in [(&support, "support-bot"), (&billing, "billing-bot")] {
+            let events = store
+                .read_stream::<DomainEvent>(request_stream(request_id).unwrap())
+                .await
+                .unwrap();
+            let application_id = events
+                .iter()
+                .find_map(|event| match event {
+                    DomainEvent::CostCalculated { application_id, .. } => Some(application_id),
+                    _ => None,
+                })
+                .expect("Should emit CostCalculated");
+            assert_eq!(
+                application_id.as_ref().map(|id| id.as_ref()),
+                Some(application)
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_invalid_state_transition_events() {
         use eventcore::RetryPolicy;
@@ -1265,6 +1694,7 @@ mod tests {
             },
             timestamp: Timestamp::now(),
             parsed_request: None,
+            calculated_cost: None,
         };
 
         // Execute the command
@@ -1318,6 +1748,7 @@ mod tests {
             },
             timestamp: Timestamp::now(),
             parsed_request: None,
+            calculated_cost: None,
         };
 
         // Execute first time
@@ -1347,6 +1778,238 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_batched_commands_see_earlier_events_in_the_batch() {
+        use eventcore::RetryPolicy;
+        use eventcore_memory::InMemoryEventStore;
+
+        let store = InMemoryEventStore::new();
+        let session_id = SessionId::generate();
+        let command = |request_id: &llm::RequestId, audit_event| RecordAuditEvent {
+            session_stream: session_stream(&session_id).unwrap(),
+            request_stream: request_stream(request_id).unwrap(),
+            request_id: request_id.clone(),
+            session_id: session_id.clone(),
+            audit_event,
+            timestamp: Timestamp::now(),
+            parsed_request: None,
+            calculated_cost: None,
+        };
+        let received = || audit_types::AuditEventType::RequestReceived {
+            method: audit_types::HttpMethod::try_new("POST".to_string()).unwrap(),
+            uri: audit_types::RequestUri::try_new("/v1/chat/completions".to_string()).unwrap(),
+            headers: audit_types::HttpHeaders::new(),
+            body_size: audit_types::BodySize::from(0),
+        };
+        let forwarded = || audit_types::AuditEventType::RequestForwarded {
+            target_url: audit_types::TargetUrl::try_new(
+                "https://api.openai.com/v1/chat/completions".to_string(),
+            )
+            .unwrap(),
+            start_time: Timestamp::now(),
+        };
+
+        let first = llm::RequestId::generate();
+        let second = llm::RequestId::generate();
+        let batch = RecordAuditEvents::new(vec![
+            command(&first, received()),
+            command(&second, received()),
+            command(&first, forwarded()),
+        ])
+        .unwrap();
+        assert_eq!(batch.stream_declarations().len(), 3);
+
+        eventcore::execute(&store, batch, RetryPolicy::default())
+            .await
+            .unwrap();
+
+        let events = store
+            .read_stream::<DomainEvent>(request_stream(&first).unwrap())
+            .await
+            .unwrap();
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, DomainEvent::LlmRequestStarted { .. })),
+            "Forwarding should see the receipt earlier in the batch"
+        );
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, DomainEvent::InvalidStateTransition { .. })));
+    }
+
+    #[test]
+    fn test_empty_batch_is_rejected() {
+        assert!(RecordAuditEvents::new(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_batch_hands_back_its_commands_without_copying() {
+        let session_id = SessionId::generate();
+        let request_id = llm::RequestId::generate();
+        let commands = vec![RecordAuditEvent {
+            session_stream: session_stream(&session_id).unwrap(),
+            request_stream: request_stream(&request_id).unwrap(),
+            request_id,
+            session_id,
+            audit_event: audit_types::AuditEventType::RequestForwarded {
+                target_url: audit_types::TargetUrl::try_new(
+                    "https://api.openai.com/v1/chat/completions".to_string(),
+                )
+                .unwrap(),
+                start_time: Timestamp::now(),
+            },
+            timestamp: Timestamp::now(),
+            parsed_request: None,
+            calculated_cost: None,
+        }];
+        let allocation = commands.as_ptr();
+
+        let batch = RecordAuditEvents::new(commands).unwrap();
+        drop(batch.clone());
+
+        let handed_back = batch.into_commands();
+        assert_eq!(handed_back.as_ptr(), allocation);
+    }
+
+    async fn record_lifecycle(
+        status: u16,
+        request_body: Option<serde_json::Value>,
+    ) -> (Vec<DomainEvent>, Vec<DomainEvent>) {
+        use eventcore::RetryPolicy;
+        use eventcore_memory::InMemoryEventStore;
+
+        let store = InMemoryEventStore::new();
+        let session_id = SessionId::generate();
+        let request_id = llm::RequestId::generate();
+        let uri = audit_types::RequestUri::try_new("/v1/chat/completions".to_string()).unwrap();
+        let command = |audit_event| RecordAuditEvent {
+            session_stream: session_stream(&session_id).unwrap(),
+            request_stream: request_stream(&request_id).unwrap(),
+            request_id: request_id.clone(),
+            session_id: session_id.clone(),
+            audit_event,
+            timestamp: Timestamp::now(),
+            parsed_request: None,
+            calculated_cost: None,
+        };
+
+        let mut commands = vec![
+            command(audit_types::AuditEventType::RequestReceived {
+                method: audit_types::HttpMethod::try_new("POST".to_string()).unwrap(),
+                uri: uri.clone(),
+                headers: audit_types::HttpHeaders::new(),
+                body_size: audit_types::BodySize::from(0),
+            }),
+            command(audit_types::AuditEventType::RequestForwarded {
+                target_url: audit_types::TargetUrl::try_new(
+                    "https://api.openai.com/v1/chat/completions".to_string(),
+                )
+                .unwrap(),
+                start_time: Timestamp::now(),
+            }),
+        ];
+        if let Some(body) = request_body {
+            let bytes = body.to_string().into_bytes();
+            let parsed = crate::adapters::proxy_audit::parse_request_body(
+                &bytes,
+                &uri,
+                &audit_types::HttpHeaders::new(),
+            );
+            commands.push(
+                command(audit_types::AuditEventType::RequestBodyCaptured {
+                    body: audit_types::BodyContent::new(bytes),
+                    truncated: false,
+                })
+                .with_parsed_request(Some(parsed)),
+            );
+        }
+        commands.push(command(audit_types::AuditEventType::ResponseReceived {
+            status: audit_types::HttpStatusCode::try_new(status).unwrap(),
+            headers: audit_types::HttpHeaders::new(),
+            body_size: audit_types::BodySize::from(0),
+            duration_ms: audit_types::DurationMs::from(1500),
+        }));
+
+        eventcore::execute(
+            &store,
+            RecordAuditEvents::new(commands).unwrap(),
+            RetryPolicy::default(),
+        )
+        .await
+        .unwrap();
+
+        let session_events = store
+            .read_stream::<DomainEvent>(session_stream(&session_id).unwrap())
+            .await
+            .unwrap()
+            .into_iter()
+            .collect();
+        let request_events = store
+            .read_stream::<DomainEvent>(request_stream(&request_id).unwrap())
+            .await
+            .unwrap()
+            .into_iter()
+            .collect();
+        (session_events, request_events)
+    }
+
+    #[tokio::test]
+    async fn test_deferred_request_is_received_once_its_body_is_parsed() {
+        let (session_events, _) = record_lifecycle(
+            200,
+            Some(serde_json::json!({
+                "model": "gpt-4o",
+                "messages": [{"role": "user", "content": "Hello"}]
+            })),
+        )
+        .await;
+
+        let received: Vec<_> = session_events
+            .iter()
+            .filter_map(|event| match event {
+                DomainEvent::LlmRequestReceived { model_version, .. } => Some(model_version),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].model_id.as_ref(), "gpt-4o");
+        assert!(session_events
+            .iter()
+            .any(|e| matches!(e, DomainEvent::LlmRequestDeferred { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_response_records_latency() {
+        let (_, request_events) = record_lifecycle(200, None).await;
+
+        let latency = request_events
+            .iter()
+            .find_map(|event| match event {
+                DomainEvent::LlmResponseReceived { metadata, .. } => Some(metadata.latency_ms()),
+                _ => None,
+            })
+            .expect("Should emit LlmResponseReceived");
+        assert_eq!(latency.map(|l| *l.as_ref()), Some(1500));
+    }
+
+    #[tokio::test]
+    async fn test_error_status_fails_the_request() {
+        let (_, request_events) = record_lifecycle(503, None).await;
+
+        let failure = request_events
+            .iter()
+            .find_map(|event| match event {
+                DomainEvent::LlmRequestFailed { error_message, .. } => Some(error_message),
+                _ => None,
+            })
+            .expect("Should emit LlmRequestFailed");
+        assert!(failure.as_ref().contains("503"));
+        assert!(!request_events
+            .iter()
+            .any(|e| matches!(e, DomainEvent::LlmResponseReceived { .. })));
+    }
+
     #[tokio::test]
     async fn test_process_request_body_with_parsing_error() {
         use eventcore::RetryPolicy;
@@ -1467,6 +2130,7 @@ impl RecordAuditEventBuilder {
                 AuditCommandError::InvalidTimestamp("Missing timestamp".to_string())
             })?,
             parsed_request: self.parsed_request,
+            calculated_cost: None,
         })
     }
 }
@@ -70,6 +70,12 @@ mod test_helpers {
             .expect("valid canonical session stream id in tests")
     }
 
+    /// Create the canonical request stream ID for a domain request.
+    pub fn request_stream_for(request_id: &llm::RequestId) -> StreamId {
+        crate::domain::streams::request_stream(request_id)
+            .expect("valid canonical request stream id in tests")
+    }
+
     /// Create a valid OpenAI request body
     pub fn create_openai_request_body() -> Vec<u8> {
         serde_json::json!({
@@ -115,6 +121,7 @@ mod test_helpers {
             audit_event: audit_event.event_type.clone(),
             timestamp: audit_event.timestamp,
             parsed_request: None,
+            calculated_cost: None,
         })
     }
 }
@@ -1120,6 +1127,102 @@ mod headers_tests {
     }
 }
 
+// Requests that share a client-supplied session
+mod shared_sessions {
+    use super::*;
+    use crate::domain::session::{ClientSessionId, MetadataKey, MetadataValue, SessionMetadata};
+
+    #[tokio::test]
+    async fn test_requests_in_same_session_are_tracked_independently() {
+        let store = create_test_store();
+        let session_id = SessionId::generate();
+
+        for _ in 0..2 {
+            let audit_event = audit_types::AuditEvent {
+                request_id: llm::RequestId::generate(),
+                session_id: session_id.clone(),
+                timestamp: Timestamp::now(),
+                event_type: request_received_event(),
+            };
+            let command = build_record_command(&audit_event).unwrap();
+            eventcore::execute(&store, command, RetryPolicy::default())
+                .await
+                .unwrap();
+        }
+
+        let events = store
+            .read_stream::<DomainEvent>(session_stream_for(&session_id))
+            .await
+            .unwrap();
+        let deferred = events
+            .iter()
+            .filter(|e| matches!(e, DomainEvent::LlmRequestDeferred { .. }))
+            .count();
+        assert_eq!(deferred, 2);
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, DomainEvent::InvalidStateTransition { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_session_context_is_recorded_on_session_stream() {
+        let store = create_test_store();
+        let metadata = SessionMetadata::new()
+            .with_client_session_id(ClientSessionId::try_new("chat-1".to_string()).unwrap())
+            .with_custom(
+                MetadataKey::try_new("feature".to_string()).unwrap(),
+                MetadataValue::try_new("chat".to_string()).unwrap(),
+            );
+        let audit_event =
+            create_test_audit_event(audit_types::AuditEventType::SessionContextReceived {
+                metadata: metadata.clone(),
+            });
+
+        let command = build_record_command(&audit_event).unwrap();
+        eventcore::execute(&store, command, RetryPolicy::default())
+            .await
+            .unwrap();
+
+        let events = store
+            .read_stream::<DomainEvent>(session_stream_for(&audit_event.session_id))
+            .await
+            .unwrap();
+        let recorded: Vec<_> = events.iter().collect();
+        assert_eq!(recorded.len(), 1);
+        assert!(matches!(
+            recorded[0],
+            DomainEvent::SessionContextRecorded { metadata: m, .. } if *m == metadata
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_captured_response_body_is_recorded_on_request_stream() {
+        let store = create_test_store();
+        let body = audit_types::BodyContent::new(b"{\"choices\":[]}".to_vec());
+        let audit_event =
+            create_test_audit_event(audit_types::AuditEventType::ResponseBodyCaptured {
+                body: body.clone(),
+                truncated: false,
+            });
+
+        let command = build_record_command(&audit_event).unwrap();
+        eventcore::execute(&store, command, RetryPolicy::default())
+            .await
+            .unwrap();
+
+        let events = store
+            .read_stream::<DomainEvent>(request_stream_for(&audit_event.request_id))
+            .await
+            .unwrap();
+        let recorded: Vec<_> = events.iter().collect();
+        assert_eq!(recorded.len(), 1);
+        assert!(matches!(
+            recorded[0],
+            DomainEvent::LlmResponseBodyCaptured { body: b, truncated: false, .. } if *b == body
+        ));
+    }
+}
+
 // Benchmarks for performance regression testing
 #[cfg(test)]
 mod benchmarks {
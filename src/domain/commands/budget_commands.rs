@@ -0,0 +1,201 @@
+//! EventCore command recording budget alerts
+//!
+//! Alerts are derived from spend by a projection, which replays the spend of
+//! the current period after every restart. The budget stream remembers what it
+//! has recorded, so each alert is recorded once per period however often it
+//! is derived.
+
+use eventcore::{CommandError, CommandLogic, NewEvents, StreamId};
+use eventcore_macros::Command;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::domain::{
+    budget::{AlertThreshold, BudgetAlert, BudgetPeriod, BudgetScope},
+    events::DomainEvent,
+    metrics::Timestamp,
+    pricing::UsdAmount,
+    streams::budget_stream,
+};
+
+/// Alerts already recorded on a budget stream
+#[derive(Debug, Default, Clone)]
+pub struct BudgetAlertState {
+    /// Thresholds crossed, by period start
+    thresholds: BTreeSet<(Timestamp, AlertThreshold)>,
+    /// Starts of the periods whose hard limit was reached
+    limits: BTreeSet<Timestamp>,
+}
+
+impl BudgetAlertState {
+    pub fn apply(&mut self, event: &DomainEvent) {
+        match event {
+            DomainEvent::BudgetThresholdCrossed {
+                period_start,
+                threshold,
+                ..
+            } => {
+                self.thresholds.insert((*period_start, *threshold));
+            }
+            DomainEvent::BudgetLimitReached { period_start, .. } => {
+                self.limits.insert(*period_start);
+            }
+            _ => {}
+        }
+    }
+
+    fn is_recorded(&self, period_start: Timestamp, alert: &BudgetAlert) -> bool {
+        match alert {
+            BudgetAlert::ThresholdCrossed { threshold, .. } => {
+                self.thresholds.contains(&(period_start, *threshold))
+            }
+            BudgetAlert::LimitReached { .. } => self.limits.contains(&period_start),
+        }
+    }
+}
+
+/// Record what one budget's spend did in one period
+#[derive(Debug, Clone, Serialize, Deserialize, Command)]
+pub struct RecordBudgetAlerts {
+    #[stream]
+    budget_stream: StreamId,
+    pub scope: BudgetScope,
+    pub period: BudgetPeriod,
+    pub period_start: Timestamp,
+    pub limit: UsdAmount,
+    pub alerts: Vec<BudgetAlert>,
+    pub timestamp: Timestamp,
+}
+
+impl RecordBudgetAlerts {
+    pub fn new(
+        scope: BudgetScope,
+        period: BudgetPeriod,
+        period_start: Timestamp,
+        limit: UsdAmount,
+        alerts: Vec<BudgetAlert>,
+        timestamp: Timestamp,
+    ) -> Result<Self, CommandError> {
+        let budget_stream = budget_stream(&scope, period)
+            .map_err(|e| CommandError::ValidationError(e.to_string()))?;
+        Ok(Self {
+            budget_stream,
+            scope,
+            period,
+            period_start,
+            limit,
+            alerts,
+            timestamp,
+        })
+    }
+
+    fn to_event(&self, alert: &BudgetAlert) -> DomainEvent {
+        match *alert {
+            BudgetAlert::ThresholdCrossed { threshold, spent } => {
+                DomainEvent::BudgetThresholdCrossed {
+                    stream_id: self.budget_stream.clone(),
+                    application_id: self.scope.application_id.clone(),
+                    environment_id: self.scope.environment_id.clone(),
+                    period: self.period,
+                    period_start: self.period_start,
+                    threshold,
+                    limit: self.limit,
+                    spent,
+                    crossed_at: self.timestamp,
+                }
+            }
+            BudgetAlert::LimitReached { spent } => DomainEvent::BudgetLimitReached {
+                stream_id: self.budget_stream.clone(),
+                application_id: self.scope.application_id.clone(),
+                environment_id: self.scope.environment_id.clone(),
+                period: self.period,
+                period_start: self.period_start,
+                limit: self.limit,
+                spent,
+                reached_at: self.timestamp,
+            },
+        }
+    }
+}
+
+impl CommandLogic for RecordBudgetAlerts {
+    type State = BudgetAlertState;
+    type Event = DomainEvent;
+
+    fn apply(&self, mut state: Self::State, event: &Self::Event) -> Self::State {
+        state.apply(event);
+        state
+    }
+
+    fn handle(&self, state: Self::State) -> Result<NewEvents<Self::Event>, CommandError> {
+        let events: Vec<DomainEvent> = self
+            .alerts
+            .iter()
+            .filter(|alert| !state.is_recorded(self.period_start, alert))
+            .map(|alert| self.to_event(alert))
+            .collect();
+        Ok(events.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::session::ApplicationId;
+    use eventcore::RetryPolicy;
+    use eventcore_memory::InMemoryEventStore;
+    use eventcore_types::EventStore;
+    use rust_decimal::Decimal;
+
+    fn command(alerts: Vec<BudgetAlert>) -> RecordBudgetAlerts {
+        RecordBudgetAlerts::new(
+            BudgetScope {
+                application_id: ApplicationId::try_new("support-bot".to_string()).unwrap(),
+                environment_id: None,
+            },
+            BudgetPeriod::Daily,
+            Timestamp::from_timestamp_secs(1_760_000_000).unwrap(),
+            UsdAmount::try_new(Decimal::from(100)).unwrap(),
+            alerts,
+            Timestamp::now(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn alerts_are_recorded_once_per_period() {
+        let store = InMemoryEventStore::new();
+        let spent = UsdAmount::try_new(Decimal::from(100)).unwrap();
+        let crossed = BudgetAlert::ThresholdCrossed {
+            threshold: AlertThreshold::try_new(80).unwrap(),
+            spent,
+        };
+        let reached = BudgetAlert::LimitReached { spent };
+
+        eventcore::execute(&store, command(vec![crossed]), RetryPolicy::default())
+            .await
+            .unwrap();
+        // Replaying the period derives the same alert again
+        eventcore::execute(
+            &store,
+            command(vec![crossed, reached]),
+            RetryPolicy::default(),
+        )
+        .await
+        .unwrap();
+
+        let stream = command(Vec::new()).budget_stream;
+        let events = store
+            .read_stream::<DomainEvent>(stream)
+            .await
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            events[0],
+            DomainEvent::BudgetThresholdCrossed { .. }
+        ));
+        assert!(matches!(events[1], DomainEvent::BudgetLimitReached { .. }));
+    }
+}
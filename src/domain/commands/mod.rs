@@ -2,9 +2,15 @@
 
 pub mod audit_buffer;
 pub mod audit_commands;
+pub mod budget_commands;
 pub mod metrics_commands;
 pub mod version_commands;
 
-pub use audit_commands::{AuditCommandError, ProcessRequestBody, RecordAuditEvent};
+pub use audit_commands::{
+    AuditCommandError, ProcessRequestBody, RecordAuditEvent, RecordAuditEvents,
+};
+pub use budget_commands::RecordBudgetAlerts;
 pub use metrics_commands::{RecordApplicationFScore, RecordModelFScore};
-pub use version_commands::{DeactivateVersion, RecordVersionChange, RecordVersionUsage};
+pub use version_commands::{
+    DeactivateVersion, RecordVersionChange, RecordVersionUsage, RecordVersionUsages,
+};
@@ -3,10 +3,12 @@
 //! These commands implement the EventCore CommandLogic trait to provide
 //! multi-stream event sourcing for version tracking operations.
 
-use eventcore::{CommandError, CommandLogic, NewEvents, StreamId};
+use eventcore::{
+    CommandError, CommandLogic, CommandStreams, NewEvents, StreamDeclarations, StreamId,
+};
 use eventcore_macros::Command;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::domain::{
     events::DomainEvent,
@@ -63,7 +65,7 @@ impl VersionState {
 }
 
 /// Build a canonical stream ID for a model version.
-fn version_stream_id(model_version: &ModelVersion) -> Result<StreamId, CommandError> {
+pub fn version_stream_id(model_version: &ModelVersion) -> Result<StreamId, CommandError> {
     StreamId::try_new(format!("version:{}", model_version.to_version_string()))
         .map_err(|e| CommandError::ValidationError(format!("Invalid version stream ID: {e}")))
 }
@@ -104,6 +106,13 @@ impl CommandLogic for RecordVersionUsage {
     }
 
     fn handle(&self, state: Self::State) -> Result<NewEvents<Self::Event>, CommandError> {
+        Ok(self.events(&state).into())
+    }
+}
+
+impl RecordVersionUsage {
+    /// The events recording this usage against `state`
+    fn events(&self, state: &VersionState) -> Vec<DomainEvent> {
         let mut events = Vec::new();
 
         // Check if this is the first time we've seen this version
@@ -126,6 +135,57 @@ impl CommandLogic for RecordVersionUsage {
             recorded_at: self.timestamp,
         });
 
+        events
+    }
+}
+
+/// Command to record several usages of model versions in one write
+///
+/// Usages are handled in order, each seeing the events of those before it,
+/// so a version is first seen once however often a batch uses it.
+#[derive(Debug, Clone)]
+pub struct RecordVersionUsages {
+    usages: Vec<RecordVersionUsage>,
+}
+
+impl RecordVersionUsages {
+    /// Batch the given usages, or `None` when there are none
+    pub fn new(usages: Vec<RecordVersionUsage>) -> Option<Self> {
+        (!usages.is_empty()).then_some(Self { usages })
+    }
+}
+
+impl CommandStreams for RecordVersionUsages {
+    fn stream_declarations(&self) -> StreamDeclarations {
+        let mut seen = HashSet::new();
+        let streams = self
+            .usages
+            .iter()
+            .map(|usage| &usage.version_stream)
+            .filter(|stream| seen.insert(*stream))
+            .cloned()
+            .collect::<Vec<_>>();
+        StreamDeclarations::try_from_streams(streams)
+            .expect("a non-empty batch declares distinct streams")
+    }
+}
+
+impl CommandLogic for RecordVersionUsages {
+    type State = VersionState;
+    type Event = DomainEvent;
+
+    fn apply(&self, mut state: Self::State, event: &Self::Event) -> Self::State {
+        state.apply(event);
+        state
+    }
+
+    fn handle(&self, mut state: Self::State) -> Result<NewEvents<Self::Event>, CommandError> {
+        let mut events = Vec::new();
+        for usage in &self.usages {
+            let produced = usage.events(&state);
+            produced.iter().for_each(|event| state.apply(event));
+            events.extend(produced);
+        }
         Ok(events.into())
     }
 }
@@ -343,6 +403,42 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_record_version_usages_sees_each_version_first_once() {
+        let session_id = SessionId::generate();
+        let version = |model: &str| ModelVersion {
+            provider: crate::domain::llm::LlmProvider::OpenAI,
+            model_id: ModelId::try_new(model.to_string()).unwrap(),
+        };
+        let usage = |model: &str| {
+            RecordVersionUsage::new(session_id.clone(), version(model), Timestamp::now()).unwrap()
+        };
+
+        let store = InMemoryEventStore::new();
+        let batch =
+            RecordVersionUsages::new(vec![usage("gpt-4o"), usage("gpt-4o-mini"), usage("gpt-4o")])
+                .unwrap();
+        eventcore::execute(&store, batch, RetryPolicy::default())
+            .await
+            .unwrap();
+
+        let kinds = |model: &str| {
+            let store = &store;
+            let stream = version_stream_id(&version(model)).unwrap();
+            async move {
+                store
+                    .read_stream::<DomainEvent>(stream)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|event| matches!(event, DomainEvent::VersionFirstSeen { .. }))
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(kinds("gpt-4o").await, vec![true, false, false]);
+        assert_eq!(kinds("gpt-4o-mini").await, vec![true, false]);
+    }
+
     #[tokio::test]
     async fn test_record_version_change() {
         let session_id = SessionId::generate();
@@ -3,14 +3,17 @@
 //! This module defines all domain events that are stored
 //! in the event store using EventCore.
 
+use chrono::NaiveDate;
 use eventcore::StreamId;
 use serde::{Deserialize, Serialize};
 
 use crate::domain::{
-    audit_types::RequestUri,
+    audit_types::{BodyContent, RequestUri},
+    budget::{AlertThreshold, BudgetPeriod},
     llm::{ModelVersion, RequestId, ResponseMetadata},
     metrics::{SampleCount, Timestamp},
-    session::{ApplicationId, SessionId, SessionStatus},
+    pricing::{RequestCost, TokenUsage, UsdAmount},
+    session::{ApplicationId, EnvironmentId, SessionId, SessionMetadata, SessionStatus},
     types::{ChangeReason, ErrorMessage, LlmParameters, Prompt, ResponseText, Tag},
     user::{DisplayName, EmailAddress, UserId},
     version::{VersionChangeId, VersionComparison},
@@ -45,6 +48,13 @@ pub enum DomainEvent {
         tag: Tag,
         tagged_at: Timestamp,
     },
+    SessionContextRecorded {
+        stream_id: StreamId,
+        session_id: SessionId,
+        request_id: RequestId,
+        metadata: SessionMetadata,
+        recorded_at: Timestamp,
+    },
 
     // LLM Request Events
     LlmRequestDeferred {
@@ -74,6 +84,22 @@ pub enum DomainEvent {
         metadata: ResponseMetadata,
         received_at: Timestamp,
     },
+    LlmRequestBodyCaptured {
+        stream_id: StreamId,
+        request_id: RequestId,
+        session_id: SessionId,
+        body: BodyContent,
+        truncated: bool,
+        captured_at: Timestamp,
+    },
+    LlmResponseBodyCaptured {
+        stream_id: StreamId,
+        request_id: RequestId,
+        session_id: SessionId,
+        body: BodyContent,
+        truncated: bool,
+        captured_at: Timestamp,
+    },
     LlmRequestFailed {
         stream_id: StreamId,
         request_id: RequestId,
@@ -117,6 +143,49 @@ pub enum DomainEvent {
         occurred_at: Timestamp,
     },
 
+    // Cost Events
+    /// The request's token usage priced with the pricing catalog
+    CostCalculated {
+        stream_id: StreamId,
+        request_id: RequestId,
+        session_id: SessionId,
+        /// Application the request was made for, if its session named one
+        application_id: Option<ApplicationId>,
+        environment_id: Option<EnvironmentId>,
+        /// The model the price was found for
+        model_version: ModelVersion,
+        usage: TokenUsage,
+        cost: RequestCost,
+        price_effective_from: NaiveDate,
+        calculated_at: Timestamp,
+    },
+
+    // Budget Events
+    /// Spend in a budget period reached one of the budget's alert thresholds
+    BudgetThresholdCrossed {
+        stream_id: StreamId,
+        application_id: ApplicationId,
+        environment_id: Option<EnvironmentId>,
+        period: BudgetPeriod,
+        period_start: Timestamp,
+        threshold: AlertThreshold,
+        limit: UsdAmount,
+        spent: UsdAmount,
+        crossed_at: Timestamp,
+    },
+    /// Spend in a budget period reached its hard limit; the application's
+    /// requests are rejected until the period ends
+    BudgetLimitReached {
+        stream_id: StreamId,
+        application_id: ApplicationId,
+        environment_id: Option<EnvironmentId>,
+        period: BudgetPeriod,
+        period_start: Timestamp,
+        limit: UsdAmount,
+        spent: UsdAmount,
+        reached_at: Timestamp,
+    },
+
     // Version Tracking Events
     VersionFirstSeen {
         stream_id: StreamId,
@@ -197,15 +266,21 @@ impl eventcore::Event for DomainEvent {
             DomainEvent::SessionStarted { stream_id, .. } => stream_id,
             DomainEvent::SessionEnded { stream_id, .. } => stream_id,
             DomainEvent::SessionTagged { stream_id, .. } => stream_id,
+            DomainEvent::SessionContextRecorded { stream_id, .. } => stream_id,
             DomainEvent::LlmRequestDeferred { stream_id, .. } => stream_id,
             DomainEvent::LlmRequestReceived { stream_id, .. } => stream_id,
             DomainEvent::LlmRequestStarted { stream_id, .. } => stream_id,
             DomainEvent::LlmResponseReceived { stream_id, .. } => stream_id,
+            DomainEvent::LlmRequestBodyCaptured { stream_id, .. } => stream_id,
+            DomainEvent::LlmResponseBodyCaptured { stream_id, .. } => stream_id,
             DomainEvent::LlmRequestFailed { stream_id, .. } => stream_id,
             DomainEvent::LlmRequestCancelled { stream_id, .. } => stream_id,
             DomainEvent::LlmRequestParsingFailed { stream_id, .. } => stream_id,
             DomainEvent::InvalidStateTransition { stream_id, .. } => stream_id,
             DomainEvent::AuditEventProcessingFailed { stream_id, .. } => stream_id,
+            DomainEvent::CostCalculated { stream_id, .. } => stream_id,
+            DomainEvent::BudgetThresholdCrossed { stream_id, .. } => stream_id,
+            DomainEvent::BudgetLimitReached { stream_id, .. } => stream_id,
             DomainEvent::VersionFirstSeen { stream_id, .. } => stream_id,
             DomainEvent::VersionChanged { stream_id, .. } => stream_id,
             DomainEvent::VersionUsageRecorded { stream_id, .. } => stream_id,
@@ -233,15 +308,21 @@ impl DomainEvent {
             DomainEvent::SessionStarted { started_at, .. } => *started_at,
             DomainEvent::SessionEnded { ended_at, .. } => *ended_at,
             DomainEvent::SessionTagged { tagged_at, .. } => *tagged_at,
+            DomainEvent::SessionContextRecorded { recorded_at, .. } => *recorded_at,
             DomainEvent::LlmRequestDeferred { received_at, .. } => *received_at,
             DomainEvent::LlmRequestReceived { received_at, .. } => *received_at,
             DomainEvent::LlmRequestStarted { started_at, .. } => *started_at,
             DomainEvent::LlmResponseReceived { received_at, .. } => *received_at,
+            DomainEvent::LlmRequestBodyCaptured { captured_at, .. } => *captured_at,
+            DomainEvent::LlmResponseBodyCaptured { captured_at, .. } => *captured_at,
             DomainEvent::LlmRequestFailed { failed_at, .. } => *failed_at,
             DomainEvent::LlmRequestCancelled { cancelled_at, .. } => *cancelled_at,
             DomainEvent::LlmRequestParsingFailed { occurred_at, .. } => *occurred_at,
             DomainEvent::InvalidStateTransition { occurred_at, .. } => *occurred_at,
             DomainEvent::AuditEventProcessingFailed { occurred_at, .. } => *occurred_at,
+            DomainEvent::CostCalculated { calculated_at, .. } => *calculated_at,
+            DomainEvent::BudgetThresholdCrossed { crossed_at, .. } => *crossed_at,
+            DomainEvent::BudgetLimitReached { reached_at, .. } => *reached_at,
             DomainEvent::VersionFirstSeen { first_seen_at, .. } => *first_seen_at,
             DomainEvent::VersionChanged { changed_at, .. } => *changed_at,
             DomainEvent::VersionUsageRecorded { recorded_at, .. } => *recorded_at,
@@ -253,6 +334,39 @@ impl DomainEvent {
             DomainEvent::UserDeactivated { deactivated_at, .. } => *deactivated_at,
         }
     }
+
+    /// Get the LLM request this event belongs to, if any
+    pub fn request_id(&self) -> Option<&RequestId> {
+        match self {
+            DomainEvent::SessionContextRecorded { request_id, .. }
+            | DomainEvent::LlmRequestDeferred { request_id, .. }
+            | DomainEvent::LlmRequestReceived { request_id, .. }
+            | DomainEvent::LlmRequestStarted { request_id, .. }
+            | DomainEvent::LlmResponseReceived { request_id, .. }
+            | DomainEvent::LlmRequestBodyCaptured { request_id, .. }
+            | DomainEvent::LlmResponseBodyCaptured { request_id, .. }
+            | DomainEvent::LlmRequestFailed { request_id, .. }
+            | DomainEvent::LlmRequestCancelled { request_id, .. }
+            | DomainEvent::LlmRequestParsingFailed { request_id, .. }
+            | DomainEvent::InvalidStateTransition { request_id, .. }
+            | DomainEvent::AuditEventProcessingFailed { request_id, .. }
+            | DomainEvent::CostCalculated { request_id, .. } => Some(request_id),
+            DomainEvent::SessionStarted { .. }
+            | DomainEvent::SessionEnded { .. }
+            | DomainEvent::SessionTagged { .. }
+            | DomainEvent::BudgetThresholdCrossed { .. }
+            | DomainEvent::BudgetLimitReached { .. }
+            | DomainEvent::VersionFirstSeen { .. }
+            | DomainEvent::VersionChanged { .. }
+            | DomainEvent::VersionUsageRecorded { .. }
+            | DomainEvent::VersionDeactivated { .. }
+            | DomainEvent::FScoreCalculated { .. }
+            | DomainEvent::ApplicationFScoreCalculated { .. }
+            | DomainEvent::UserCreated { .. }
+            | DomainEvent::UserActivated { .. }
+            | DomainEvent::UserDeactivated { .. } => None,
+        }
+    }
 }
 
 // EventCore requires TryFrom<&'a ES::Event> for CommandExecutor
@@ -91,6 +91,7 @@ pub enum TimestampAge {
 }
 
 /// Validation function for metric timestamps
+#[allow(clippy::needless_bool)] // each bound is a guard clause
 fn is_valid_metric_timestamp(dt: &DateTime<Utc>) -> bool {
     let now = Utc::now();
 
@@ -4,6 +4,7 @@
 //! concepts of Union Square, following type-driven development principles.
 
 pub mod audit_types;
+pub mod budget;
 pub mod commands;
 pub mod config_types;
 pub mod events;
@@ -12,6 +13,7 @@ pub mod llm;
 pub mod metrics;
 pub mod network_types;
 pub mod parsed_llm_request;
+pub mod pricing;
 pub mod session;
 pub mod streams;
 pub mod test_case;
@@ -0,0 +1,379 @@
+//! Model pricing and the cost of LLM requests
+//!
+//! Prices are US dollars per million tokens, kept per provider and model.
+//! A model may have several prices, each taking effect on a date, so a price
+//! change applies to requests received from that day on and older requests
+//! keep the cost they were recorded with.
+//!
+//! Amounts are [`Decimal`] rather than floating point, so the cost of one
+//! request is exact and sums over many requests do not drift.
+
+use chrono::NaiveDate;
+use nutype::nutype;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::domain::config_types::ProviderName;
+use crate::domain::llm::ModelVersion;
+use crate::domain::metrics::Timestamp;
+use crate::domain::types::{ModelId, TokenCount};
+
+/// Tokens a [`TokenPrice`] is quoted for
+const TOKENS_PER_PRICE: u32 = 1_000_000;
+
+/// Price in US dollars per million tokens
+#[nutype(
+    validate(predicate = |price| *price >= Decimal::ZERO),
+    derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display
+    )
+)]
+pub struct TokenPrice(Decimal);
+
+/// An amount of US dollars
+#[nutype(
+    validate(predicate = |amount| *amount >= Decimal::ZERO),
+    derive(
+        Debug,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display
+    )
+)]
+pub struct UsdAmount(Decimal);
+
+impl UsdAmount {
+    /// Price `tokens` at `price`, or `None` if the amount does not fit
+    fn of_tokens(tokens: TokenCount, price: TokenPrice) -> Option<Self> {
+        let amount = Decimal::from(tokens.into_inner())
+            .checked_mul(price.into_inner())?
+            .checked_div(Decimal::from(TOKENS_PER_PRICE))?;
+        Self::try_new(amount.normalize()).ok()
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        let amount = self.into_inner().checked_add(other.into_inner())?;
+        Self::try_new(amount.normalize()).ok()
+    }
+}
+
+/// Tokens a request was billed for, by kind
+///
+/// `input` excludes tokens read from or written to the prompt cache, which
+/// are billed at their own prices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input: TokenCount,
+    pub output: TokenCount,
+    pub cache_read: TokenCount,
+    pub cache_write: TokenCount,
+}
+
+/// One price of a model, from its effective date until the next one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub effective_from: NaiveDate,
+    pub input: TokenPrice,
+    pub output: TokenPrice,
+    /// Defaults to the input price
+    pub cache_read: Option<TokenPrice>,
+    /// Defaults to the input price
+    pub cache_write: Option<TokenPrice>,
+}
+
+impl ModelPrice {
+    /// The cost of `usage` at this price, or `None` if it does not fit
+    pub fn cost(&self, usage: &TokenUsage) -> Option<RequestCost> {
+        let input = UsdAmount::of_tokens(usage.input, self.input)?;
+        let output = UsdAmount::of_tokens(usage.output, self.output)?;
+        let cache_read =
+            UsdAmount::of_tokens(usage.cache_read, self.cache_read.unwrap_or(self.input))?;
+        let cache_write =
+            UsdAmount::of_tokens(usage.cache_write, self.cache_write.unwrap_or(self.input))?;
+        let total = input
+            .checked_add(output)?
+            .checked_add(cache_read)?
+            .checked_add(cache_write)?;
+        Some(RequestCost {
+            input,
+            output,
+            cache_read,
+            cache_write,
+            total,
+        })
+    }
+}
+
+/// The cost of one request, by kind of token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestCost {
+    pub input: UsdAmount,
+    pub output: UsdAmount,
+    pub cache_read: UsdAmount,
+    pub cache_write: UsdAmount,
+    pub total: UsdAmount,
+}
+
+/// A request's usage priced with the catalog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostCalculation {
+    /// The model the price was found for
+    pub model_version: ModelVersion,
+    pub usage: TokenUsage,
+    pub cost: RequestCost,
+    /// Effective date of the price used
+    pub price_effective_from: NaiveDate,
+}
+
+/// One configured price of a model, as written under `[[pricing]]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PricingEntry {
+    /// Provider as requests name it: `openai`, `anthropic`, `google`,
+    /// `azure`, `bedrock`, ...
+    pub provider: ProviderName,
+    pub model: ModelId,
+    pub effective_from: NaiveDate,
+    pub input_per_million: TokenPrice,
+    pub output_per_million: TokenPrice,
+    #[serde(default)]
+    pub cache_read_per_million: Option<TokenPrice>,
+    #[serde(default)]
+    pub cache_write_per_million: Option<TokenPrice>,
+}
+
+/// Errors building a [`PricingCatalog`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PricingError {
+    #[error("{provider} model {model} has two prices effective from {effective_from}")]
+    DuplicatePrice {
+        provider: ProviderName,
+        model: ModelId,
+        effective_from: NaiveDate,
+    },
+}
+
+/// Prices of every configured model, by provider and model
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PricingCatalog {
+    /// Prices of each model, ordered by effective date
+    models: HashMap<(String, ModelId), Vec<ModelPrice>>,
+}
+
+impl PricingCatalog {
+    /// Build the catalog from configured prices
+    ///
+    /// A model may have any number of prices, but only one per date.
+    pub fn new(entries: Vec<PricingEntry>) -> Result<Self, PricingError> {
+        let mut models: HashMap<(String, ModelId), Vec<ModelPrice>> = HashMap::new();
+        for entry in entries {
+            let prices = models
+                .entry((entry.provider.as_ref().to_string(), entry.model.clone()))
+                .or_default();
+            if prices
+                .iter()
+                .any(|price| price.effective_from == entry.effective_from)
+            {
+                return Err(PricingError::DuplicatePrice {
+                    provider: entry.provider,
+                    model: entry.model,
+                    effective_from: entry.effective_from,
+                });
+            }
+            prices.push(ModelPrice {
+                effective_from: entry.effective_from,
+                input: entry.input_per_million,
+                output: entry.output_per_million,
+                cache_read: entry.cache_read_per_million,
+                cache_write: entry.cache_write_per_million,
+            });
+        }
+        for prices in models.values_mut() {
+            prices.sort_by_key(|price| price.effective_from);
+        }
+        Ok(Self { models })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// The price of `model` in effect at `at`
+    ///
+    /// Prices take effect at midnight UTC of their effective date.
+    pub fn price_at(&self, model: &ModelVersion, at: Timestamp) -> Option<&ModelPrice> {
+        let day = at.into_datetime().date_naive();
+        self.models
+            .get(&(model.provider.as_str().to_string(), model.model_id.clone()))?
+            .iter()
+            .rev()
+            .find(|price| price.effective_from <= day)
+    }
+
+    /// Price `usage` of a request for `model` received at `at`
+    ///
+    /// `None` when the catalog has no price for the model at that time.
+    pub fn calculate(
+        &self,
+        model: &ModelVersion,
+        usage: TokenUsage,
+        at: Timestamp,
+    ) -> Option<CostCalculation> {
+        let price = self.price_at(model, at)?;
+        Some(CostCalculation {
+            model_version: model.clone(),
+            usage,
+            cost: price.cost(&usage)?,
+            price_effective_from: price.effective_from,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::llm::LlmProvider;
+    use chrono::{TimeZone, Utc};
+    use std::str::FromStr;
+
+    fn decimal(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn price(value: &str) -> TokenPrice {
+        TokenPrice::try_new(decimal(value)).unwrap()
+    }
+
+    fn tokens(count: u32) -> TokenCount {
+        TokenCount::try_new(count).unwrap()
+    }
+
+    fn entry(model: &str, effective_from: &str, input: &str, output: &str) -> PricingEntry {
+        PricingEntry {
+            provider: ProviderName::try_new("openai".to_string()).unwrap(),
+            model: ModelId::try_new(model.to_string()).unwrap(),
+            effective_from: NaiveDate::from_str(effective_from).unwrap(),
+            input_per_million: price(input),
+            output_per_million: price(output),
+            cache_read_per_million: None,
+            cache_write_per_million: None,
+        }
+    }
+
+    fn gpt_4o() -> ModelVersion {
+        ModelVersion {
+            provider: LlmProvider::OpenAI,
+            model_id: ModelId::try_new("gpt-4o".to_string()).unwrap(),
+        }
+    }
+
+    fn at(year: i32, month: u32, day: u32) -> Timestamp {
+        Timestamp::try_new(Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()).unwrap()
+    }
+
+    fn usage(input: u32, output: u32) -> TokenUsage {
+        TokenUsage {
+            input: tokens(input),
+            output: tokens(output),
+            cache_read: tokens(0),
+            cache_write: tokens(0),
+        }
+    }
+
+    #[test]
+    fn test_negative_prices_are_rejected() {
+        assert!(TokenPrice::try_new(decimal("-0.01")).is_err());
+        assert!(TokenPrice::try_new(Decimal::ZERO).is_ok());
+    }
+
+    #[test]
+    fn test_cost_is_exact() {
+        let model_price = ModelPrice {
+            effective_from: NaiveDate::from_str("2024-01-01").unwrap(),
+            input: price("2.50"),
+            output: price("10"),
+            cache_read: Some(price("1.25")),
+            cache_write: None,
+        };
+        let cost = model_price
+            .cost(&TokenUsage {
+                input: tokens(1_000),
+                output: tokens(3),
+                cache_read: tokens(400),
+                cache_write: tokens(100),
+            })
+            .unwrap();
+
+        assert_eq!(cost.input.into_inner(), decimal("0.0025"));
+        assert_eq!(cost.output.into_inner(), decimal("0.00003"));
+        assert_eq!(cost.cache_read.into_inner(), decimal("0.0005"));
+        // Cache writes default to the input price
+        assert_eq!(cost.cache_write.into_inner(), decimal("0.00025"));
+        assert_eq!(cost.total.into_inner(), decimal("0.00328"));
+    }
+
+    #[test]
+    fn test_the_price_in_effect_is_used() {
+        let catalog = PricingCatalog::new(vec![
+            entry("gpt-4o", "2024-10-01", "2.50", "10"),
+            entry("gpt-4o", "2024-05-13", "5", "15"),
+        ])
+        .unwrap();
+
+        let before = catalog.calculate(&gpt_4o(), usage(1_000_000, 0), at(2024, 9, 30));
+        let after = catalog.calculate(&gpt_4o(), usage(1_000_000, 0), at(2024, 10, 1));
+
+        let before = before.unwrap();
+        assert_eq!(before.cost.total.into_inner(), decimal("5"));
+        assert_eq!(
+            before.price_effective_from,
+            NaiveDate::from_str("2024-05-13").unwrap()
+        );
+        assert_eq!(after.unwrap().cost.total.into_inner(), decimal("2.5"));
+    }
+
+    #[test]
+    fn test_unpriced_requests_have_no_cost() {
+        let catalog =
+            PricingCatalog::new(vec![entry("gpt-4o", "2024-10-01", "2.50", "10")]).unwrap();
+
+        // Before the first price
+        assert!(catalog
+            .calculate(&gpt_4o(), usage(10, 10), at(2024, 1, 1))
+            .is_none());
+        // Same model under another provider
+        let azure = ModelVersion {
+            provider: LlmProvider::Azure,
+            ..gpt_4o()
+        };
+        assert!(catalog
+            .calculate(&azure, usage(10, 10), at(2024, 11, 1))
+            .is_none());
+    }
+
+    #[test]
+    fn test_duplicate_prices_are_rejected() {
+        let result = PricingCatalog::new(vec![
+            entry("gpt-4o", "2024-10-01", "2.50", "10"),
+            entry("gpt-4o", "2024-10-01", "3", "12"),
+        ]);
+        assert!(matches!(result, Err(PricingError::DuplicatePrice { .. })));
+    }
+}
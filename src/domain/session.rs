@@ -2,8 +2,13 @@ use crate::domain::types::{IpAddress, Tag, UserAgent};
 use chrono::{DateTime, Utc};
 use nutype::nutype;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+/// Namespace for deriving [`SessionId`]s from client-supplied session identifiers
+pub const CLIENT_SESSION_NAMESPACE: Uuid =
+    Uuid::from_u128(0x5b9e_41c2_7d3a_4f08_a6e1_0c7f_2d94_b813);
+
 /// Unique identifier for a session
 #[nutype(derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, AsRef))]
 pub struct SessionId(Uuid);
@@ -15,12 +20,139 @@ impl SessionId {
     }
 }
 
+impl SessionId {
+    /// Deterministic session ID for a client-supplied identifier.
+    ///
+    /// Clients may use any string format (ADR-0012), so the identifier is
+    /// hashed into a v5 UUID under the caller's `scope`; every request of
+    /// that caller carrying the same identifier lands in the same session
+    /// stream.
+    pub fn from_client_session_id(scope: SessionScope, id: &ClientSessionId) -> Self {
+        Self::new(Uuid::new_v5(&scope.0, id.as_ref().as_bytes()))
+    }
+}
+
+/// Whose client session identifiers a [`SessionId`] is derived from
+///
+/// Clients choose session identifiers freely, so two applications may well
+/// both send `default` or `1`. Each caller's identifiers map to sessions of
+/// its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionScope(Uuid);
+
+impl SessionScope {
+    /// Sessions of an application, shared by every key bound to it
+    pub fn application(
+        application_id: &ApplicationId,
+        environment: Option<&EnvironmentId>,
+    ) -> Self {
+        let application = Uuid::new_v5(
+            &CLIENT_SESSION_NAMESPACE,
+            format!("application:{application_id}").as_bytes(),
+        );
+        let environment = environment.map_or("", |environment| environment.as_ref());
+        Self(Uuid::new_v5(&application, environment.as_bytes()))
+    }
+
+    /// Sessions of requests authenticated with a key bound to no application
+    pub fn api_key(key: &str) -> Self {
+        Self(Uuid::new_v5(
+            &CLIENT_SESSION_NAMESPACE,
+            format!("api-key:{key}").as_bytes(),
+        ))
+    }
+}
+
+/// Sessions of requests no key was checked for
+impl Default for SessionScope {
+    fn default() -> Self {
+        Self(CLIENT_SESSION_NAMESPACE)
+    }
+}
+
 impl Default for SessionId {
     fn default() -> Self {
         Self::generate()
     }
 }
 
+/// Session identifier chosen by the client (`X-UnionSquare-Session-Id`)
+#[nutype(
+    sanitize(trim),
+    validate(not_empty, len_char_max = 256),
+    derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        Hash,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display
+    )
+)]
+pub struct ClientSessionId(String);
+
+/// End-user identifier supplied by the client (`X-UnionSquare-User-Id`)
+#[nutype(
+    sanitize(trim),
+    validate(not_empty, len_char_max = 256),
+    derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        Hash,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display
+    )
+)]
+pub struct ClientUserId(String);
+
+/// Key of a custom metadata entry (`X-UnionSquare-Metadata-<Key>`)
+#[nutype(
+    sanitize(trim, lowercase),
+    validate(not_empty, len_char_max = 128),
+    derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Hash,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display
+    )
+)]
+pub struct MetadataKey(String);
+
+/// Value of a custom metadata entry
+#[nutype(
+    validate(len_char_max = 1024),
+    derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        Hash,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display
+    )
+)]
+pub struct MetadataValue(String);
+
+/// Structured application context (`X-UnionSquare-Application-Context`)
+#[nutype(derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, AsRef))]
+pub struct ApplicationContext(serde_json::Value);
+
 /// Application identifier
 #[nutype(
     validate(not_empty, len_char_max = 100),
@@ -84,13 +216,23 @@ pub enum SessionStatus {
 }
 
 /// Metadata associated with a session
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SessionMetadata {
     application_id: Option<ApplicationId>,
     environment_id: Option<EnvironmentId>,
     user_agent: Option<UserAgent>,
     ip_address: Option<IpAddress>,
     tags: Vec<Tag>,
+    #[serde(default)]
+    client_session_id: Option<ClientSessionId>,
+    #[serde(default)]
+    parent_session_id: Option<ClientSessionId>,
+    #[serde(default)]
+    client_user_id: Option<ClientUserId>,
+    #[serde(default)]
+    custom: BTreeMap<MetadataKey, MetadataValue>,
+    #[serde(default)]
+    application_context: Option<ApplicationContext>,
 }
 
 impl SessionMetadata {
@@ -101,6 +243,11 @@ impl SessionMetadata {
             user_agent: None,
             ip_address: None,
             tags: Vec::new(),
+            client_session_id: None,
+            parent_session_id: None,
+            client_user_id: None,
+            custom: BTreeMap::new(),
+            application_context: None,
         }
     }
 
@@ -129,6 +276,32 @@ impl SessionMetadata {
         self
     }
 
+    pub fn with_client_session_id(mut self, id: ClientSessionId) -> Self {
+        self.client_session_id = Some(id);
+        self
+    }
+
+    pub fn with_parent_session_id(mut self, id: ClientSessionId) -> Self {
+        self.parent_session_id = Some(id);
+        self
+    }
+
+    pub fn with_client_user_id(mut self, id: ClientUserId) -> Self {
+        self.client_user_id = Some(id);
+        self
+    }
+
+    /// Add a custom entry; a repeated key keeps the last value
+    pub fn with_custom(mut self, key: MetadataKey, value: MetadataValue) -> Self {
+        self.custom.insert(key, value);
+        self
+    }
+
+    pub fn with_application_context(mut self, context: ApplicationContext) -> Self {
+        self.application_context = Some(context);
+        self
+    }
+
     pub fn application_id(&self) -> Option<&ApplicationId> {
         self.application_id.as_ref()
     }
@@ -148,6 +321,26 @@ impl SessionMetadata {
     pub fn tags(&self) -> &[Tag] {
         &self.tags
     }
+
+    pub fn client_session_id(&self) -> Option<&ClientSessionId> {
+        self.client_session_id.as_ref()
+    }
+
+    pub fn parent_session_id(&self) -> Option<&ClientSessionId> {
+        self.parent_session_id.as_ref()
+    }
+
+    pub fn client_user_id(&self) -> Option<&ClientUserId> {
+        self.client_user_id.as_ref()
+    }
+
+    pub fn custom(&self) -> &BTreeMap<MetadataKey, MetadataValue> {
+        &self.custom
+    }
+
+    pub fn application_context(&self) -> Option<&ApplicationContext> {
+        self.application_context.as_ref()
+    }
 }
 
 impl Default for SessionMetadata {
@@ -302,6 +495,73 @@ mod tests {
         assert_eq!(cancelled.status(), &SessionStatus::Cancelled);
     }
 
+    #[test]
+    fn test_client_session_id_maps_to_stable_session_id() {
+        let scope = SessionScope::api_key("key-1");
+        let client_id = ClientSessionId::try_new("chat-42".to_string()).unwrap();
+        let same = ClientSessionId::try_new("  chat-42 ".to_string()).unwrap();
+        let other = ClientSessionId::try_new("chat-43".to_string()).unwrap();
+
+        assert_eq!(
+            SessionId::from_client_session_id(scope, &client_id),
+            SessionId::from_client_session_id(scope, &same)
+        );
+        assert_ne!(
+            SessionId::from_client_session_id(scope, &client_id),
+            SessionId::from_client_session_id(scope, &other)
+        );
+    }
+
+    #[test]
+    fn test_client_session_ids_are_scoped_to_the_caller() {
+        let client_id = ClientSessionId::try_new("default".to_string()).unwrap();
+        let application = |name: &str| ApplicationId::try_new(name.to_string()).unwrap();
+        let production = EnvironmentId::try_new("production".to_string()).unwrap();
+        let session = |scope| SessionId::from_client_session_id(scope, &client_id);
+
+        assert_eq!(
+            session(SessionScope::application(&application("support-bot"), None)),
+            session(SessionScope::application(&application("support-bot"), None))
+        );
+        let sessions = [
+            session(SessionScope::application(&application("support-bot"), None)),
+            session(SessionScope::application(
+                &application("support-bot"),
+                Some(&production),
+            )),
+            session(SessionScope::application(&application("billing-bot"), None)),
+            session(SessionScope::api_key("support-bot")),
+            session(SessionScope::default()),
+        ];
+        let distinct: std::collections::HashSet<_> = sessions.iter().collect();
+        assert_eq!(distinct.len(), sessions.len());
+    }
+
+    #[test]
+    fn test_session_metadata_client_fields() {
+        let metadata = SessionMetadata::new()
+            .with_client_session_id(ClientSessionId::try_new("s-1".to_string()).unwrap())
+            .with_parent_session_id(ClientSessionId::try_new("s-0".to_string()).unwrap())
+            .with_client_user_id(ClientUserId::try_new("user-7".to_string()).unwrap())
+            .with_custom(
+                MetadataKey::try_new("Feature".to_string()).unwrap(),
+                MetadataValue::try_new("chat".to_string()).unwrap(),
+            )
+            .with_application_context(ApplicationContext::new(
+                serde_json::json!({"workflow": "support"}),
+            ));
+
+        assert_eq!(metadata.client_session_id().unwrap().as_ref(), "s-1");
+        assert_eq!(metadata.parent_session_id().unwrap().as_ref(), "s-0");
+        assert_eq!(metadata.client_user_id().unwrap().as_ref(), "user-7");
+        let key = MetadataKey::try_new("feature".to_string()).unwrap();
+        assert_eq!(metadata.custom().get(&key).unwrap().as_ref(), "chat");
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let decoded: SessionMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(metadata, decoded);
+    }
+
     #[test]
     fn test_application_id_validation() {
         // Valid cases
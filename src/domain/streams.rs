@@ -4,6 +4,7 @@
 //! commands and EventCore infrastructure. They return `Result` because
 //! `StreamId` validation is fallible and production code must not panic.
 
+use crate::domain::budget::{BudgetPeriod, BudgetScope};
 use crate::domain::identifiers::{AnalysisId, ExtractionId};
 use crate::domain::session::SessionId;
 use crate::domain::user::UserId;
@@ -109,6 +110,15 @@ pub const STREAM_DOCUMENTATION: &[StreamDocumentation] = &[
         },
         related_streams: &["analysis:{analysis_id}", "session:{session_id}"],
     },
+    StreamDocumentation {
+        stream_pattern: "budget:{application_id}:{environment_id|_}:{period}",
+        purpose: "Records alerts and hard limits reached by one spending budget.",
+        lifecycle: StreamLifecycle::Ongoing {
+            created_by: "RecordBudgetAlerts",
+            retention: RetentionPolicy::Days(365),
+        },
+        related_streams: &["request-{request_id}"],
+    },
 ];
 
 pub fn session_stream(session_id: &SessionId) -> Result<StreamId, StreamNameError> {
@@ -131,6 +141,21 @@ pub fn extraction_stream(extraction_id: &ExtractionId) -> Result<StreamId, Strea
     stream_id(format!("extraction:{extraction_id}"))
 }
 
+/// Stream of the budget for `scope` over `period`; `_` stands for every environment
+pub fn budget_stream(
+    scope: &BudgetScope,
+    period: BudgetPeriod,
+) -> Result<StreamId, StreamNameError> {
+    let environment = scope
+        .environment_id
+        .as_ref()
+        .map_or("_", |environment_id| environment_id.as_ref());
+    stream_id(format!(
+        "budget:{}:{environment}:{period}",
+        scope.application_id.as_ref()
+    ))
+}
+
 pub fn session_with_analyses_streams(
     session_id: &SessionId,
     analysis_ids: &[AnalysisId],
@@ -159,6 +184,7 @@ fn stream_id(raw: String) -> Result<StreamId, StreamNameError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::budget::{BudgetPeriod, BudgetScope};
     use crate::domain::commands::metrics_commands::{RecordApplicationFScore, RecordModelFScore};
     use crate::domain::commands::version_commands::RecordVersionChange;
     use crate::domain::identifiers::{AnalysisId, ExtractionId};
@@ -198,6 +224,16 @@ mod tests {
             extraction_stream(&extraction_id).unwrap().as_ref(),
             format!("extraction:{extraction_id}")
         );
+        let scope = BudgetScope {
+            application_id: ApplicationId::try_new("support-bot".to_string()).unwrap(),
+            environment_id: None,
+        };
+        assert_eq!(
+            budget_stream(&scope, BudgetPeriod::Monthly)
+                .unwrap()
+                .as_ref(),
+            "budget:support-bot:_:monthly"
+        );
     }
 
     #[test]
@@ -212,6 +248,7 @@ mod tests {
         assert!(patterns.contains(&"analysis:{analysis_id}"));
         assert!(patterns.contains(&"user:{user_id}:settings"));
         assert!(patterns.contains(&"extraction:{extraction_id}"));
+        assert!(patterns.contains(&"budget:{application_id}:{environment_id|_}:{period}"));
     }
 
     #[test]
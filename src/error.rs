@@ -14,6 +14,9 @@ pub enum Error {
     #[error("EventCore error: {0}")]
     EventCore(ErrorMessage),
 
+    #[error("Event store unavailable: {0}")]
+    EventStoreUnavailable(ErrorMessage),
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
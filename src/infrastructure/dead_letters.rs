@@ -0,0 +1,506 @@
+//! Dead-letter store for audit events the audit path could not process
+//!
+//! Payloads are kept as the audit path last held them, so they can be
+//! inspected, replayed once the cause is fixed, or purged:
+//!
+//! - [`DeadLetterPhase::Deserialize`] and [`DeadLetterPhase::Convert`] hold an
+//!   encoded proxy audit event, exactly as read from the ring buffer for the
+//!   former and after body chunks were reassembled for the latter
+//! - [`DeadLetterPhase::Persist`] holds the domain command as JSON
+
+use crate::domain::audit_types::base64_bytes;
+use crate::infrastructure::eventcore::projections::PageSize;
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use nutype::nutype;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+/// Idempotent DDL for the dead-letter table, run by [`PostgresDeadLetterStore::ensure_schema`]
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS audit_dead_letters (
+        id UUID PRIMARY KEY,
+        request_id UUID,
+        phase TEXT NOT NULL,
+        error TEXT NOT NULL,
+        retry_count INTEGER NOT NULL DEFAULT 0,
+        payload BYTEA NOT NULL,
+        failed_at TIMESTAMPTZ NOT NULL,
+        last_retried_at TIMESTAMPTZ
+    )",
+    "CREATE INDEX IF NOT EXISTS audit_dead_letters_phase_idx
+        ON audit_dead_letters (phase, id)",
+];
+
+/// Identifier of a dead letter; time-ordered, so letters list oldest first
+#[nutype(derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    AsRef,
+    Display,
+    Serialize,
+    Deserialize
+))]
+pub struct DeadLetterId(Uuid);
+
+impl DeadLetterId {
+    pub fn generate() -> Self {
+        Self::new(Uuid::now_v7())
+    }
+}
+
+/// Audit path step an event failed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterPhase {
+    Deserialize,
+    Convert,
+    Persist,
+}
+
+impl DeadLetterPhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Deserialize => "deserialize",
+            Self::Convert => "convert",
+            Self::Persist => "persist",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "deserialize" => Some(Self::Deserialize),
+            "convert" => Some(Self::Convert),
+            "persist" => Some(Self::Persist),
+            _ => None,
+        }
+    }
+}
+
+/// A failed payload to store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewDeadLetter {
+    /// Request the payload belongs to
+    pub request_id: Option<Uuid>,
+    pub phase: DeadLetterPhase,
+    pub error: String,
+    pub payload: Vec<u8>,
+}
+
+/// A stored failed payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadLetter {
+    pub id: DeadLetterId,
+    pub request_id: Option<Uuid>,
+    pub phase: DeadLetterPhase,
+    /// Error of the most recent attempt
+    pub error: String,
+    /// Replays that have failed so far
+    pub retry_count: u32,
+    #[serde(with = "base64_bytes")]
+    pub payload: Vec<u8>,
+    pub failed_at: DateTime<Utc>,
+    pub last_retried_at: Option<DateTime<Utc>>,
+}
+
+/// A dead letter without its payload, for listings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadLetterSummary {
+    pub id: DeadLetterId,
+    pub request_id: Option<Uuid>,
+    pub phase: DeadLetterPhase,
+    pub error: String,
+    pub retry_count: u32,
+    pub payload_size: usize,
+    pub failed_at: DateTime<Utc>,
+    pub last_retried_at: Option<DateTime<Utc>>,
+}
+
+impl From<&DeadLetter> for DeadLetterSummary {
+    fn from(letter: &DeadLetter) -> Self {
+        Self {
+            id: letter.id,
+            request_id: letter.request_id,
+            phase: letter.phase,
+            error: letter.error.clone(),
+            retry_count: letter.retry_count,
+            payload_size: letter.payload.len(),
+            failed_at: letter.failed_at,
+            last_retried_at: letter.last_retried_at,
+        }
+    }
+}
+
+/// Which dead letters to list, oldest first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeadLetterQuery {
+    pub phase: Option<DeadLetterPhase>,
+    /// Only letters after this one
+    pub after: Option<DeadLetterId>,
+    pub limit: PageSize,
+}
+
+/// Storage for audit events that could not be processed
+#[async_trait]
+pub trait DeadLetterStore: Send + Sync {
+    /// Store a failed payload
+    async fn record(&self, letter: NewDeadLetter) -> Result<DeadLetterId>;
+
+    /// Dead letters matching `query`, oldest first
+    async fn list(&self, query: &DeadLetterQuery) -> Result<Vec<DeadLetterSummary>>;
+
+    /// One dead letter with its payload
+    async fn get(&self, id: DeadLetterId) -> Result<Option<DeadLetter>>;
+
+    /// Note a failed replay; returns false when the letter no longer exists
+    async fn record_retry(&self, id: DeadLetterId, error: &str) -> Result<bool>;
+
+    /// Remove one dead letter; returns false when it did not exist
+    async fn remove(&self, id: DeadLetterId) -> Result<bool>;
+
+    /// Remove every dead letter, or those of one phase; returns how many
+    async fn purge(&self, phase: Option<DeadLetterPhase>) -> Result<u64>;
+}
+
+/// Dead letters in the `audit_dead_letters` table
+#[derive(Debug, Clone)]
+pub struct PostgresDeadLetterStore {
+    pool: PgPool,
+}
+
+impl PostgresDeadLetterStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create the table if it does not exist yet
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for PostgresDeadLetterStore {
+    async fn record(&self, letter: NewDeadLetter) -> Result<DeadLetterId> {
+        let id = DeadLetterId::generate();
+        sqlx::query(
+            "INSERT INTO audit_dead_letters (id, request_id, phase, error, payload, failed_at)
+             VALUES ($1, $2, $3, $4, $5, NOW())",
+        )
+        .bind(id.into_inner())
+        .bind(letter.request_id)
+        .bind(letter.phase.as_str())
+        .bind(letter.error)
+        .bind(letter.payload)
+        .execute(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    async fn list(&self, query: &DeadLetterQuery) -> Result<Vec<DeadLetterSummary>> {
+        let mut sql = QueryBuilder::<Postgres>::new(
+            "SELECT id, request_id, phase, error, retry_count, octet_length(payload) AS payload_size,
+                failed_at, last_retried_at
+             FROM audit_dead_letters WHERE TRUE",
+        );
+        if let Some(phase) = query.phase {
+            sql.push(" AND phase = ").push_bind(phase.as_str());
+        }
+        if let Some(after) = query.after {
+            sql.push(" AND id > ").push_bind(after.into_inner());
+        }
+        sql.push(" ORDER BY id LIMIT ")
+            .push_bind(i64::from(*query.limit.as_ref()));
+
+        let rows = sql.build().fetch_all(&self.pool).await?;
+        rows.iter()
+            .map(|row| {
+                let payload_size: i32 = row.try_get("payload_size")?;
+                Ok(DeadLetterSummary {
+                    id: DeadLetterId::new(row.try_get("id")?),
+                    request_id: row.try_get("request_id")?,
+                    phase: phase_from_row(row)?,
+                    error: row.try_get("error")?,
+                    retry_count: retry_count_from_row(row)?,
+                    payload_size: payload_size.try_into().unwrap_or_default(),
+                    failed_at: row.try_get("failed_at")?,
+                    last_retried_at: row.try_get("last_retried_at")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn get(&self, id: DeadLetterId) -> Result<Option<DeadLetter>> {
+        let row = sqlx::query(
+            "SELECT id, request_id, phase, error, retry_count, payload, failed_at, last_retried_at
+             FROM audit_dead_letters WHERE id = $1",
+        )
+        .bind(id.into_inner())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(DeadLetter {
+                id,
+                request_id: row.try_get("request_id")?,
+                phase: phase_from_row(&row)?,
+                error: row.try_get("error")?,
+                retry_count: retry_count_from_row(&row)?,
+                payload: row.try_get("payload")?,
+                failed_at: row.try_get("failed_at")?,
+                last_retried_at: row.try_get("last_retried_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn record_retry(&self, id: DeadLetterId, error: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE audit_dead_letters
+             SET retry_count = retry_count + 1, error = $2, last_retried_at = NOW()
+             WHERE id = $1",
+        )
+        .bind(id.into_inner())
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn remove(&self, id: DeadLetterId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM audit_dead_letters WHERE id = $1")
+            .bind(id.into_inner())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn purge(&self, phase: Option<DeadLetterPhase>) -> Result<u64> {
+        let result = match phase {
+            Some(phase) => {
+                sqlx::query("DELETE FROM audit_dead_letters WHERE phase = $1")
+                    .bind(phase.as_str())
+                    .execute(&self.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("DELETE FROM audit_dead_letters")
+                    .execute(&self.pool)
+                    .await?
+            }
+        };
+        Ok(result.rows_affected())
+    }
+}
+
+fn phase_from_row(row: &sqlx::postgres::PgRow) -> Result<DeadLetterPhase> {
+    let phase: String = row.try_get("phase")?;
+    DeadLetterPhase::from_label(&phase)
+        .ok_or_else(|| crate::Error::application(format!("Unexpected dead-letter phase {phase}")))
+}
+
+fn retry_count_from_row(row: &sqlx::postgres::PgRow) -> Result<u32> {
+    let retry_count: i32 = row.try_get("retry_count")?;
+    Ok(retry_count.try_into().unwrap_or_default())
+}
+
+/// Dead letters held in memory, for tests and deployments without a database
+#[derive(Debug, Default)]
+pub struct InMemoryDeadLetterStore {
+    letters: Mutex<Vec<DeadLetter>>,
+}
+
+impl InMemoryDeadLetterStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for InMemoryDeadLetterStore {
+    async fn record(&self, letter: NewDeadLetter) -> Result<DeadLetterId> {
+        let id = DeadLetterId::generate();
+        self.letters.lock().push(DeadLetter {
+            id,
+            request_id: letter.request_id,
+            phase: letter.phase,
+            error: letter.error,
+            retry_count: 0,
+            payload: letter.payload,
+            failed_at: Utc::now(),
+            last_retried_at: None,
+        });
+        Ok(id)
+    }
+
+    async fn list(&self, query: &DeadLetterQuery) -> Result<Vec<DeadLetterSummary>> {
+        Ok(self
+            .letters
+            .lock()
+            .iter()
+            .filter(|letter| query.phase.is_none_or(|phase| letter.phase == phase))
+            .filter(|letter| query.after.is_none_or(|after| letter.id > after))
+            .take(*query.limit.as_ref() as usize)
+            .map(DeadLetterSummary::from)
+            .collect())
+    }
+
+    async fn get(&self, id: DeadLetterId) -> Result<Option<DeadLetter>> {
+        Ok(self
+            .letters
+            .lock()
+            .iter()
+            .find(|letter| letter.id == id)
+            .cloned())
+    }
+
+    async fn record_retry(&self, id: DeadLetterId, error: &str) -> Result<bool> {
+        let mut letters = self.letters.lock();
+        let Some(letter) = letters.iter_mut().find(|letter| letter.id == id) else {
+            return Ok(false);
+        };
+        letter.retry_count += 1;
+        letter.error = error.to_string();
+        letter.last_retried_at = Some(Utc::now());
+        Ok(true)
+    }
+
+    async fn remove(&self, id: DeadLetterId) -> Result<bool> {
+        let mut letters = self.letters.lock();
+        let before = letters.len();
+        letters.retain(|letter| letter.id != id);
+        Ok(letters.len() < before)
+    }
+
+    async fn purge(&self, phase: Option<DeadLetterPhase>) -> Result<u64> {
+        let mut letters = self.letters.lock();
+        let before = letters.len();
+        letters.retain(|letter| phase.is_some_and(|phase| letter.phase != phase));
+        Ok((before - letters.len()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letter(phase: DeadLetterPhase) -> NewDeadLetter {
+        NewDeadLetter {
+            request_id: Some(Uuid::now_v7()),
+            phase,
+            error: "boom".to_string(),
+            payload: vec![1, 2, 3],
+        }
+    }
+
+    async fn exercise(store: &dyn DeadLetterStore) {
+        let convert = store
+            .record(letter(DeadLetterPhase::Convert))
+            .await
+            .unwrap();
+        let persist = store
+            .record(letter(DeadLetterPhase::Persist))
+            .await
+            .unwrap();
+
+        let persisted_only = DeadLetterQuery {
+            phase: Some(DeadLetterPhase::Persist),
+            ..DeadLetterQuery::default()
+        };
+        let listed = store.list(&persisted_only).await.unwrap();
+        assert!(listed.iter().any(|summary| summary.id == persist));
+        assert!(listed.iter().all(|summary| summary.id != convert));
+
+        assert!(store.record_retry(convert, "still broken").await.unwrap());
+        let stored = store.get(convert).await.unwrap().unwrap();
+        assert_eq!(stored.retry_count, 1);
+        assert_eq!(stored.error, "still broken");
+        assert_eq!(stored.payload, vec![1, 2, 3]);
+        assert!(stored.last_retried_at.is_some());
+
+        assert!(store.remove(convert).await.unwrap());
+        assert!(!store.remove(convert).await.unwrap());
+        assert!(store.get(convert).await.unwrap().is_none());
+        assert!(!store.record_retry(convert, "gone").await.unwrap());
+
+        assert!(store.purge(Some(DeadLetterPhase::Persist)).await.unwrap() >= 1);
+        assert!(store.get(persist).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn phases_round_trip_through_their_labels() {
+        for phase in [
+            DeadLetterPhase::Deserialize,
+            DeadLetterPhase::Convert,
+            DeadLetterPhase::Persist,
+        ] {
+            assert_eq!(DeadLetterPhase::from_label(phase.as_str()), Some(phase));
+        }
+        assert_eq!(DeadLetterPhase::from_label("replay"), None);
+    }
+
+    #[tokio::test]
+    async fn in_memory_store_records_retries_and_purges() {
+        let store = InMemoryDeadLetterStore::new();
+        exercise(&store).await;
+
+        store
+            .record(letter(DeadLetterPhase::Deserialize))
+            .await
+            .unwrap();
+        assert_eq!(store.purge(None).await.unwrap(), 1);
+        assert!(store
+            .list(&DeadLetterQuery::default())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn listing_pages_after_a_letter() {
+        let store = InMemoryDeadLetterStore::new();
+        let first = store
+            .record(letter(DeadLetterPhase::Convert))
+            .await
+            .unwrap();
+        let second = store
+            .record(letter(DeadLetterPhase::Convert))
+            .await
+            .unwrap();
+
+        let page = store
+            .list(&DeadLetterQuery {
+                after: Some(first),
+                ..DeadLetterQuery::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, second);
+        assert_eq!(page[0].payload_size, 3);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database connection"]
+    async fn postgres_store_records_retries_and_purges() {
+        let settings = crate::config::Settings::new().unwrap();
+        let pool = PgPool::connect(&settings.database_url()).await.unwrap();
+        let store = PostgresDeadLetterStore::new(pool);
+        store.ensure_schema().await.unwrap();
+
+        exercise(&store).await;
+    }
+}
@@ -0,0 +1,498 @@
+//! In-memory ledger of budget spend
+//!
+//! Adds up the priced cost of each configured budget's requests in the
+//! current period, records an alert on the budget's stream when spend
+//! crosses a threshold or a hard limit, and tells the proxy which
+//! applications have spent a hard limit.
+//!
+//! Each cost counts against the application and environment recorded with
+//! its own request, never against whatever its session was last used for.
+//!
+//! Nothing is persisted. A fresh ledger starts at the beginning of the
+//! earliest running period, computed from the time-ordered event IDs, so a
+//! restart replays the period's spend. Alerts are derived again on replay,
+//! but the budget stream records each one once per period.
+
+use super::read_model::ReadModelUpdate;
+use super::runner::{position_at, ReadModelStore};
+use crate::domain::budget::{BudgetAlert, BudgetCatalog, BudgetRejection};
+use crate::domain::commands::RecordBudgetAlerts;
+use crate::domain::metrics::Timestamp;
+use crate::domain::pricing::UsdAmount;
+use crate::domain::session::{ApplicationId, EnvironmentId};
+use crate::infrastructure::eventcore::service::EventCoreService;
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eventcore_types::StreamPosition;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Budget ledger fed by a [`super::ProjectionRunner`]
+pub struct BudgetLedger {
+    catalog: Arc<BudgetCatalog>,
+    event_store: Option<Arc<EventCoreService>>,
+    state: RwLock<LedgerState>,
+}
+
+impl BudgetLedger {
+    pub fn new(catalog: Arc<BudgetCatalog>) -> Self {
+        let state = LedgerState::new(&catalog);
+        Self {
+            catalog,
+            event_store: None,
+            state: RwLock::new(state),
+        }
+    }
+
+    /// Record alerts in `event_store`; without one they are only logged
+    pub fn with_event_store(mut self, event_store: Arc<EventCoreService>) -> Self {
+        self.event_store = Some(event_store);
+        self
+    }
+
+    /// Why a new request of `application_id` in `environment_id` must be refused at `now`
+    ///
+    /// `None` unless a hard-limit budget covering the request is spent in
+    /// the current period. When several are, the one resetting last wins.
+    pub fn rejection(
+        &self,
+        application_id: &ApplicationId,
+        environment_id: Option<&EnvironmentId>,
+        now: DateTime<Utc>,
+    ) -> Option<BudgetRejection> {
+        let state = self.state.read();
+        self.catalog
+            .budgets()
+            .iter()
+            .zip(&state.spend)
+            .filter(|(budget, spend)| {
+                budget.hard_limit
+                    && budget.scope.covers(application_id, environment_id)
+                    && spend.start == budget.period.start_of(now)
+                    && budget.is_spent(spend.spent)
+            })
+            .map(|(budget, spend)| BudgetRejection {
+                scope: budget.scope.clone(),
+                period: budget.period,
+                limit: budget.limit,
+                spent: UsdAmount::try_new(spend.spent).unwrap_or(budget.limit),
+                resets_at: budget.period.end_of(now),
+                status: budget.reject_status,
+            })
+            .max_by_key(|rejection| rejection.resets_at)
+    }
+
+    /// Record `pending` alerts, keeping those that fail for the next batch
+    async fn record(&self, pending: Vec<PendingAlerts>) {
+        let mut failed = Vec::new();
+        for alerts in pending {
+            let budget = &self.catalog.budgets()[alerts.budget];
+            let Some(event_store) = &self.event_store else {
+                warn!(
+                    "{} {} budget alerts not recorded: {:?}",
+                    budget.scope, budget.period, alerts.alerts
+                );
+                continue;
+            };
+            let command = Timestamp::try_new(alerts.period_start)
+                .map_err(|e| e.to_string())
+                .and_then(|period_start| {
+                    RecordBudgetAlerts::new(
+                        budget.scope.clone(),
+                        budget.period,
+                        period_start,
+                        budget.limit,
+                        alerts.alerts.clone(),
+                        alerts.at,
+                    )
+                    .map_err(|e| e.to_string())
+                });
+            let result = match command {
+                Ok(command) => event_store
+                    .execute_command(command)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => {
+                    warn!(
+                        "Dropping {} {} budget alerts: {e}",
+                        budget.scope, budget.period
+                    );
+                    continue;
+                }
+            };
+            if let Err(e) = result {
+                warn!(
+                    "Failed to record {} {} budget alerts; retrying: {e}",
+                    budget.scope, budget.period
+                );
+                failed.push(alerts);
+            }
+        }
+
+        if !failed.is_empty() {
+            let mut state = self.state.write();
+            failed.append(&mut state.pending);
+            state.pending = failed;
+        }
+    }
+}
+
+#[async_trait]
+impl ReadModelStore for BudgetLedger {
+    async fn checkpoint(&self) -> Result<Option<StreamPosition>> {
+        Ok(Some(self.state.read().checkpoint.unwrap_or_else(|| {
+            let now = Utc::now();
+            position_at(self.catalog.earliest_period_start(now).unwrap_or(now))
+        })))
+    }
+
+    async fn apply(&self, updates: &[ReadModelUpdate], position: StreamPosition) -> Result<()> {
+        let pending = {
+            let mut state = self.state.write();
+            for update in updates {
+                state.apply(&self.catalog, update);
+            }
+            state.checkpoint = Some(position);
+            std::mem::take(&mut state.pending)
+        };
+        self.record(pending).await;
+        Ok(())
+    }
+
+    async fn reset(&self) -> Result<()> {
+        *self.state.write() = LedgerState::new(&self.catalog);
+        Ok(())
+    }
+}
+
+/// Spend of one budget in its latest period
+#[derive(Debug, Clone, Copy)]
+struct PeriodSpend {
+    start: DateTime<Utc>,
+    spent: Decimal,
+}
+
+/// Alerts derived for one budget's period but not yet recorded
+#[derive(Debug, Clone)]
+struct PendingAlerts {
+    /// Index of the budget in the catalog
+    budget: usize,
+    period_start: DateTime<Utc>,
+    alerts: Vec<BudgetAlert>,
+    /// When the cost that triggered them was calculated
+    at: Timestamp,
+}
+
+struct LedgerState {
+    checkpoint: Option<StreamPosition>,
+    /// One entry per budget, in catalog order
+    spend: Vec<PeriodSpend>,
+    pending: Vec<PendingAlerts>,
+}
+
+impl LedgerState {
+    fn new(catalog: &BudgetCatalog) -> Self {
+        Self {
+            checkpoint: None,
+            spend: vec![
+                PeriodSpend {
+                    start: DateTime::<Utc>::MIN_UTC,
+                    spent: Decimal::ZERO,
+                };
+                catalog.budgets().len()
+            ],
+            pending: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, catalog: &BudgetCatalog, update: &ReadModelUpdate) {
+        use ReadModelUpdate as U;
+
+        // Requests made without an application count against no budget
+        if let U::CostRecorded {
+            application_id: Some(application_id),
+            environment_id,
+            total,
+            calculated_at,
+            ..
+        } = update
+        {
+            self.spend(
+                catalog,
+                application_id,
+                environment_id.as_ref(),
+                *total,
+                *calculated_at,
+            );
+        }
+    }
+
+    /// Count `cost` against every budget covering the request's application
+    fn spend(
+        &mut self,
+        catalog: &BudgetCatalog,
+        application_id: &ApplicationId,
+        environment_id: Option<&EnvironmentId>,
+        cost: UsdAmount,
+        at: Timestamp,
+    ) {
+        for (index, budget) in catalog.budgets().iter().enumerate() {
+            if !budget.scope.covers(application_id, environment_id) {
+                continue;
+            }
+            let start = budget.period.start_of(at.into_datetime());
+            let spend = &mut self.spend[index];
+            // Costs of a period that has ended no longer count
+            if start < spend.start {
+                continue;
+            }
+            if start > spend.start {
+                *spend = PeriodSpend {
+                    start,
+                    spent: Decimal::ZERO,
+                };
+            }
+
+            let before = spend.spent;
+            spend.spent += cost.into_inner();
+            let after = spend.spent;
+            let Ok(spent) = UsdAmount::try_new(after) else {
+                continue;
+            };
+
+            let mut alerts: Vec<BudgetAlert> = budget
+                .crossed_thresholds(before, after)
+                .into_iter()
+                .map(|threshold| BudgetAlert::ThresholdCrossed { threshold, spent })
+                .collect();
+            if budget.hard_limit && !budget.is_spent(before) && budget.is_spent(after) {
+                alerts.push(BudgetAlert::LimitReached { spent });
+            }
+            if !alerts.is_empty() {
+                self.pending.push(PendingAlerts {
+                    budget: index,
+                    period_start: start,
+                    alerts,
+                    at,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::budget::{AlertThreshold, BudgetEntry, BudgetPeriod, RejectStatus};
+    use crate::domain::events::DomainEvent;
+    use crate::domain::llm::{LlmProvider, ModelVersion, RequestId};
+    use crate::domain::pricing::TokenUsage;
+    use crate::domain::session::{SessionId, SessionMetadata};
+    use crate::domain::streams::budget_stream;
+    use crate::domain::types::{ModelId, TokenCount};
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    fn usd(value: &str) -> UsdAmount {
+        UsdAmount::try_new(Decimal::from_str(value).unwrap()).unwrap()
+    }
+
+    fn application(name: &str) -> ApplicationId {
+        ApplicationId::try_new(name.to_string()).unwrap()
+    }
+
+    fn environment(name: &str) -> EnvironmentId {
+        EnvironmentId::try_new(name.to_string()).unwrap()
+    }
+
+    fn catalog(environment: Option<&str>, hard_limit: bool) -> Arc<BudgetCatalog> {
+        Arc::new(
+            BudgetCatalog::new(vec![BudgetEntry {
+                application_id: application("support-bot"),
+                environment: environment.map(self::environment),
+                period: BudgetPeriod::Daily,
+                limit_usd: usd("10"),
+                alert_at_percent: vec![AlertThreshold::try_new(50).unwrap()],
+                hard_limit,
+                reject_status: RejectStatus::default(),
+            }])
+            .unwrap(),
+        )
+    }
+
+    /// Cost of a request of `application_id` in `environment_id`
+    fn cost(application_id: &str, environment_id: &str, total: &str) -> ReadModelUpdate {
+        cost_in(
+            &SessionId::generate(),
+            application_id,
+            environment_id,
+            total,
+        )
+    }
+
+    fn cost_in(
+        session_id: &SessionId,
+        application_id: &str,
+        environment_id: &str,
+        total: &str,
+    ) -> ReadModelUpdate {
+        let tokens = TokenCount::try_new(0).unwrap();
+        ReadModelUpdate::CostRecorded {
+            request_id: RequestId::generate(),
+            session_id: session_id.clone(),
+            application_id: Some(application(application_id)),
+            environment_id: Some(environment(environment_id)),
+            model_version: ModelVersion {
+                provider: LlmProvider::Anthropic,
+                model_id: ModelId::try_new("claude-sonnet-4".to_string()).unwrap(),
+            },
+            usage: TokenUsage {
+                input: tokens,
+                output: tokens,
+                cache_read: tokens,
+                cache_write: tokens,
+            },
+            total: usd(total),
+            calculated_at: Timestamp::now(),
+        }
+    }
+
+    async fn apply(ledger: &BudgetLedger, updates: &[ReadModelUpdate]) {
+        ledger
+            .apply(updates, StreamPosition::new(Uuid::now_v7()))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn spent_hard_limit_rejects_the_application() {
+        let ledger = BudgetLedger::new(catalog(Some("production"), true));
+        let now = Utc::now();
+
+        apply(
+            &ledger,
+            &[
+                cost("support-bot", "staging", "20"),
+                cost("support-bot", "production", "6"),
+            ],
+        )
+        .await;
+        assert!(ledger
+            .rejection(
+                &application("support-bot"),
+                Some(&environment("production")),
+                now
+            )
+            .is_none());
+
+        apply(&ledger, &[cost("support-bot", "production", "4")]).await;
+        let rejection = ledger
+            .rejection(
+                &application("support-bot"),
+                Some(&environment("production")),
+                now,
+            )
+            .unwrap();
+        assert_eq!(rejection.spent, usd("10"));
+        assert_eq!(rejection.resets_at, BudgetPeriod::Daily.end_of(now));
+        assert_eq!(*rejection.status.as_ref(), 429);
+        assert!(ledger
+            .rejection(
+                &application("support-bot"),
+                Some(&environment("staging")),
+                now
+            )
+            .is_none());
+        assert!(ledger
+            .rejection(&application("support-bot"), None, now)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn soft_budget_never_rejects() {
+        let ledger = BudgetLedger::new(catalog(None, false));
+
+        apply(&ledger, &[cost("support-bot", "production", "25")]).await;
+
+        assert!(ledger
+            .rejection(&application("support-bot"), None, Utc::now())
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn costs_count_against_their_own_request_application() {
+        let ledger = BudgetLedger::new(catalog(None, true));
+        // Two keys bound to different applications sending the same session
+        let session_id = SessionId::generate();
+        let now = Utc::now();
+
+        apply(
+            &ledger,
+            &[
+                ReadModelUpdate::SessionContextRecorded {
+                    session_id: session_id.clone(),
+                    metadata: SessionMetadata::new()
+                        .with_application_id(application("billing-bot")),
+                },
+                cost_in(&session_id, "support-bot", "production", "4"),
+                cost_in(&session_id, "billing-bot", "production", "20"),
+                cost_in(&session_id, "support-bot", "production", "4"),
+            ],
+        )
+        .await;
+
+        assert!(ledger
+            .rejection(&application("support-bot"), None, now)
+            .is_none());
+
+        apply(
+            &ledger,
+            &[cost_in(&session_id, "support-bot", "production", "2")],
+        )
+        .await;
+        assert_eq!(
+            ledger
+                .rejection(&application("support-bot"), None, now)
+                .unwrap()
+                .spent,
+            usd("10")
+        );
+    }
+
+    #[tokio::test]
+    async fn alerts_are_recorded_once_across_replays() {
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+        let catalog = catalog(None, true);
+        let ledger = BudgetLedger::new(Arc::clone(&catalog)).with_event_store(event_store.clone());
+        let updates = [
+            cost("support-bot", "production", "3"),
+            cost("support-bot", "production", "3"),
+            cost("support-bot", "production", "5"),
+        ];
+
+        apply(&ledger, &updates).await;
+        ledger.reset().await.unwrap();
+        apply(&ledger, &updates).await;
+
+        let budget = &catalog.budgets()[0];
+        let stream = budget_stream(&budget.scope, budget.period).unwrap();
+        let events: Vec<DomainEvent> = event_store
+            .read_stream(stream)
+            .await
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            DomainEvent::BudgetThresholdCrossed { spent, .. } if *spent == usd("6")
+        ));
+        assert!(matches!(
+            &events[1],
+            DomainEvent::BudgetLimitReached { spent, .. } if *spent == usd("11")
+        ));
+    }
+}
@@ -0,0 +1,583 @@
+//! In-memory live view of current activity
+//!
+//! The Tier 1 projection from ADR-0010: sessions active in the last few
+//! minutes plus rolling request and error rates, answered from memory so
+//! dashboards never query PostgreSQL. Session and request tracking are capped
+//! by entry count and evict the least recently active entry first.
+//!
+//! Nothing is persisted. A fresh view starts at the beginning of the active
+//! window, computed from the time-ordered event IDs, so a restart or a
+//! [`ReadModelStore::reset`] rebuilds it by replaying only recent events.
+
+use super::read_model::ReadModelUpdate;
+use super::runner::{position_at, ReadModelStore};
+use crate::domain::llm::RequestId;
+use crate::domain::metrics::Timestamp;
+use crate::domain::session::{ApplicationId, SessionId};
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eventcore_types::StreamPosition;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::num::{NonZeroU64, NonZeroUsize};
+
+/// Limits and windows of the live view
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LiveViewConfig {
+    /// Sessions kept in memory; the least recently active is evicted first
+    pub max_sessions: NonZeroUsize,
+    /// Requests whose session is remembered, so outcomes can be attributed
+    pub max_requests: NonZeroUsize,
+    /// How long after its last event a session counts as active
+    pub active_window_secs: NonZeroU64,
+    /// Span the request and error rates are averaged over
+    pub rate_window_secs: NonZeroU64,
+}
+
+impl Default for LiveViewConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions: NonZeroUsize::new(10_000).expect("non-zero"),
+            max_requests: NonZeroUsize::new(50_000).expect("non-zero"),
+            active_window_secs: NonZeroU64::new(15 * 60).expect("non-zero"),
+            rate_window_secs: NonZeroU64::new(60).expect("non-zero"),
+        }
+    }
+}
+
+/// A session with recent activity
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActiveSession {
+    pub session_id: SessionId,
+    pub application_id: Option<ApplicationId>,
+    pub started_at: DateTime<Utc>,
+    pub last_activity_at: DateTime<Utc>,
+    pub requests: u64,
+    pub failures: u64,
+}
+
+/// What is happening right now
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LiveSnapshot {
+    pub generated_at: DateTime<Utc>,
+    /// Most recently active first
+    pub active_sessions: Vec<ActiveSession>,
+    pub rate_window_secs: u64,
+    pub requests_received: u64,
+    pub requests_completed: u64,
+    pub requests_failed: u64,
+    pub requests_per_second: f64,
+    /// Share of finished requests that failed; zero when none finished
+    pub error_rate: f64,
+}
+
+/// Live view fed by a [`super::ProjectionRunner`]
+pub struct LiveView {
+    config: LiveViewConfig,
+    state: RwLock<LiveState>,
+}
+
+impl LiveView {
+    pub fn new(config: LiveViewConfig) -> Self {
+        let state = LiveState::new(&config);
+        Self {
+            config,
+            state: RwLock::new(state),
+        }
+    }
+
+    /// Sessions active and rates as of `now`
+    pub fn snapshot(&self, now: DateTime<Utc>) -> LiveSnapshot {
+        let state = self.state.read();
+        let active_since = now - seconds(self.config.active_window_secs);
+        let rate_window = self.config.rate_window_secs.get();
+
+        let mut active_sessions: Vec<ActiveSession> = state
+            .sessions
+            .values()
+            .filter(|session| session.last_activity_at >= active_since)
+            .cloned()
+            .collect();
+        active_sessions.sort_by_key(|session| std::cmp::Reverse(session.last_activity_at));
+
+        let counts = state
+            .rates
+            .totals_since(now.timestamp() - rate_window as i64);
+        let finished = counts.completed + counts.failed;
+
+        LiveSnapshot {
+            generated_at: now,
+            active_sessions,
+            rate_window_secs: rate_window,
+            requests_received: counts.received,
+            requests_completed: counts.completed,
+            requests_failed: counts.failed,
+            requests_per_second: counts.received as f64 / rate_window as f64,
+            error_rate: if finished == 0 {
+                0.0
+            } else {
+                counts.failed as f64 / finished as f64
+            },
+        }
+    }
+
+    /// Position just before the first event of the active window
+    fn window_start(&self) -> StreamPosition {
+        position_at(Utc::now() - seconds(self.config.active_window_secs))
+    }
+}
+
+impl Default for LiveView {
+    fn default() -> Self {
+        Self::new(LiveViewConfig::default())
+    }
+}
+
+#[async_trait]
+impl ReadModelStore for LiveView {
+    async fn checkpoint(&self) -> Result<Option<StreamPosition>> {
+        Ok(Some(
+            self.state
+                .read()
+                .checkpoint
+                .unwrap_or_else(|| self.window_start()),
+        ))
+    }
+
+    async fn apply(&self, updates: &[ReadModelUpdate], position: StreamPosition) -> Result<()> {
+        let mut state = self.state.write();
+        for update in updates {
+            state.apply(update);
+        }
+        state.evict_inactive(seconds(self.config.active_window_secs));
+        state.rates.prune(self.config.rate_window_secs.get());
+        state.checkpoint = Some(position);
+        Ok(())
+    }
+
+    async fn reset(&self) -> Result<()> {
+        *self.state.write() = LiveState::new(&self.config);
+        Ok(())
+    }
+}
+
+struct LiveState {
+    checkpoint: Option<StreamPosition>,
+    sessions: Lru<SessionId, ActiveSession>,
+    requests: Lru<RequestId, SessionId>,
+    rates: RollingCounts,
+    /// Latest event time seen; inactivity is measured against it
+    latest: Option<DateTime<Utc>>,
+}
+
+impl LiveState {
+    fn new(config: &LiveViewConfig) -> Self {
+        Self {
+            checkpoint: None,
+            sessions: Lru::new(config.max_sessions),
+            requests: Lru::new(config.max_requests),
+            rates: RollingCounts::default(),
+            latest: None,
+        }
+    }
+
+    fn apply(&mut self, update: &ReadModelUpdate) {
+        use ReadModelUpdate as U;
+
+        match update {
+            U::SessionActivity { session_id, at } => self.touch(session_id, *at),
+            U::SessionStarted {
+                session_id,
+                application_id,
+                started_at,
+                ..
+            } => {
+                self.touch(session_id, *started_at);
+                if let Some(session) = self.sessions.get_mut(session_id) {
+                    session.application_id = Some(application_id.clone());
+                }
+            }
+            U::SessionEnded { session_id, .. } => {
+                self.sessions.remove(session_id);
+            }
+            U::SessionContextRecorded {
+                session_id,
+                metadata,
+            } => {
+                if let (Some(session), Some(application_id)) =
+                    (self.sessions.get_mut(session_id), metadata.application_id())
+                {
+                    session.application_id = Some(application_id.clone());
+                }
+            }
+            U::RequestDeferred {
+                request_id,
+                session_id,
+                received_at,
+            }
+            | U::RequestReceived {
+                request_id,
+                session_id,
+                received_at,
+                ..
+            } => {
+                // Deferred requests are received again once parsed; count them once
+                if self.requests.get_mut(request_id).is_none() {
+                    self.requests.insert(request_id.clone(), session_id.clone());
+                    self.rates.record(*received_at, Outcome::Received);
+                    if let Some(session) = self.sessions.get_mut(session_id) {
+                        session.requests += 1;
+                    }
+                }
+            }
+            U::ResponseReceived {
+                request_id,
+                received_at,
+                ..
+            } => {
+                self.rates.record(*received_at, Outcome::Completed);
+                self.requests.remove(request_id);
+            }
+            U::RequestFailed {
+                request_id,
+                failed_at,
+                ..
+            } => {
+                self.rates.record(*failed_at, Outcome::Failed);
+                if let Some(session_id) = self.requests.remove(request_id) {
+                    if let Some(session) = self.sessions.get_mut(&session_id) {
+                        session.failures += 1;
+                    }
+                }
+            }
+            U::RequestCancelled { request_id, .. } => {
+                self.requests.remove(request_id);
+            }
+            U::SessionTagged { .. } | U::RequestStarted { .. } | U::CostRecorded { .. } => {}
+        }
+    }
+
+    fn touch(&mut self, session_id: &SessionId, at: Timestamp) {
+        let at = at.into_datetime();
+        self.latest = Some(self.latest.map_or(at, |latest| latest.max(at)));
+
+        match self.sessions.get_mut(session_id) {
+            Some(session) => {
+                session.started_at = session.started_at.min(at);
+                session.last_activity_at = session.last_activity_at.max(at);
+            }
+            None => self.sessions.insert(
+                session_id.clone(),
+                ActiveSession {
+                    session_id: session_id.clone(),
+                    application_id: None,
+                    started_at: at,
+                    last_activity_at: at,
+                    requests: 0,
+                    failures: 0,
+                },
+            ),
+        }
+    }
+
+    /// Drop sessions idle for longer than `window` before the latest event
+    fn evict_inactive(&mut self, window: chrono::Duration) {
+        if let Some(latest) = self.latest {
+            let cutoff = latest - window;
+            self.sessions
+                .evict_while(|session| session.last_activity_at < cutoff);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Outcome {
+    Received,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Counts {
+    received: u64,
+    completed: u64,
+    failed: u64,
+}
+
+/// Request outcomes bucketed by second of event time
+#[derive(Debug, Default)]
+struct RollingCounts {
+    buckets: VecDeque<(i64, Counts)>,
+}
+
+impl RollingCounts {
+    fn record(&mut self, at: Timestamp, outcome: Outcome) {
+        let second = at.into_datetime().timestamp();
+        let position = self
+            .buckets
+            .iter()
+            .rposition(|(bucket, _)| *bucket <= second);
+
+        let counts = match position {
+            Some(index) if self.buckets[index].0 == second => &mut self.buckets[index].1,
+            Some(index) => {
+                self.buckets.insert(index + 1, (second, Counts::default()));
+                &mut self.buckets[index + 1].1
+            }
+            None => {
+                self.buckets.push_front((second, Counts::default()));
+                &mut self.buckets[0].1
+            }
+        };
+
+        match outcome {
+            Outcome::Received => counts.received += 1,
+            Outcome::Completed => counts.completed += 1,
+            Outcome::Failed => counts.failed += 1,
+        }
+    }
+
+    /// Drop buckets that have left the window behind the newest one
+    fn prune(&mut self, window_secs: u64) {
+        if let Some(&(newest, _)) = self.buckets.back() {
+            let cutoff = newest - window_secs as i64;
+            while self
+                .buckets
+                .front()
+                .is_some_and(|(second, _)| *second <= cutoff)
+            {
+                self.buckets.pop_front();
+            }
+        }
+    }
+
+    fn totals_since(&self, after_second: i64) -> Counts {
+        self.buckets
+            .iter()
+            .filter(|(second, _)| *second > after_second)
+            .fold(Counts::default(), |total, (_, counts)| Counts {
+                received: total.received + counts.received,
+                completed: total.completed + counts.completed,
+                failed: total.failed + counts.failed,
+            })
+    }
+}
+
+/// Map bounded by entry count that evicts the least recently used entry
+pub(super) struct Lru<K, V> {
+    capacity: NonZeroUsize,
+    entries: HashMap<K, (V, u64)>,
+    order: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K: Clone + Eq + Hash, V> Lru<K, V> {
+    pub(super) fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub(super) fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let (value, used) = self.entries.get_mut(key)?;
+        self.order.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.order.insert(self.tick, key.clone());
+        Some(value)
+    }
+
+    pub(super) fn insert(&mut self, key: K, value: V) {
+        self.remove(&key);
+        if self.entries.len() == self.capacity.get() {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.order.insert(self.tick, key.clone());
+        self.entries.insert(key, (value, self.tick));
+    }
+
+    pub(super) fn remove(&mut self, key: &K) -> Option<V> {
+        let (value, used) = self.entries.remove(key)?;
+        self.order.remove(&used);
+        Some(value)
+    }
+
+    /// Evict least recently used entries while `stale` holds for them
+    fn evict_while(&mut self, stale: impl Fn(&V) -> bool) {
+        while let Some((_, key)) = self.order.first_key_value() {
+            if !self.entries.get(key).is_some_and(|(value, _)| stale(value)) {
+                break;
+            }
+            if let Some((_, key)) = self.order.pop_first() {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.values().map(|(value, _)| value)
+    }
+}
+
+fn seconds(secs: NonZeroU64) -> chrono::Duration {
+    chrono::Duration::seconds(i64::try_from(secs.get()).unwrap_or(i64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::llm::ResponseMetadata;
+    use crate::domain::types::{ErrorMessage, ResponseText};
+    use uuid::Uuid;
+
+    fn at(secs: i64) -> Timestamp {
+        Timestamp::from_timestamp_secs(1_750_000_000 + secs).unwrap()
+    }
+
+    fn position() -> StreamPosition {
+        StreamPosition::new(Uuid::now_v7())
+    }
+
+    fn request(session_id: &SessionId, request_id: &RequestId, secs: i64) -> Vec<ReadModelUpdate> {
+        vec![
+            ReadModelUpdate::SessionActivity {
+                session_id: session_id.clone(),
+                at: at(secs),
+            },
+            ReadModelUpdate::RequestDeferred {
+                request_id: request_id.clone(),
+                session_id: session_id.clone(),
+                received_at: at(secs),
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn tracks_active_sessions_and_rates() {
+        let view = LiveView::default();
+        let session_id = SessionId::generate();
+        let ok = RequestId::generate();
+        let failed = RequestId::generate();
+
+        let mut updates = request(&session_id, &ok, 0);
+        updates.extend(request(&session_id, &failed, 1));
+        updates.push(ReadModelUpdate::ResponseReceived {
+            request_id: ok,
+            response_text: ResponseText::try_new("Hi".to_string()).unwrap(),
+            metadata: ResponseMetadata::new(),
+            received_at: at(2),
+        });
+        updates.push(ReadModelUpdate::RequestFailed {
+            request_id: failed,
+            error_message: ErrorMessage::try_new("upstream timeout".to_string()).unwrap(),
+            failed_at: at(3),
+        });
+        view.apply(&updates, position()).await.unwrap();
+
+        let snapshot = view.snapshot(at(10).into_datetime());
+
+        assert_eq!(snapshot.active_sessions.len(), 1);
+        let session = &snapshot.active_sessions[0];
+        assert_eq!((session.requests, session.failures), (2, 1));
+        assert_eq!(snapshot.requests_received, 2);
+        assert_eq!(snapshot.requests_per_second, 2.0 / 60.0);
+        assert_eq!(snapshot.error_rate, 0.5);
+
+        // Both the session and the rates age out
+        let later = view.snapshot(at(20 * 60).into_datetime());
+        assert!(later.active_sessions.is_empty());
+        assert_eq!(later.requests_received, 0);
+    }
+
+    #[tokio::test]
+    async fn deferred_then_received_counts_once() {
+        let view = LiveView::default();
+        let session_id = SessionId::generate();
+        let request_id = RequestId::generate();
+        let mut updates = request(&session_id, &request_id, 0);
+        updates.extend(request(&session_id, &request_id, 1));
+        view.apply(&updates, position()).await.unwrap();
+
+        let snapshot = view.snapshot(at(5).into_datetime());
+        assert_eq!(snapshot.active_sessions[0].requests, 1);
+        assert_eq!(snapshot.requests_received, 1);
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_active_session_at_capacity() {
+        let view = LiveView::new(LiveViewConfig {
+            max_sessions: NonZeroUsize::new(2).unwrap(),
+            ..LiveViewConfig::default()
+        });
+        let sessions: Vec<SessionId> = (0..3).map(|_| SessionId::generate()).collect();
+        let updates: Vec<ReadModelUpdate> = [(0, 0), (1, 1), (0, 2), (2, 3)]
+            .into_iter()
+            .map(|(index, secs)| ReadModelUpdate::SessionActivity {
+                session_id: sessions[index].clone(),
+                at: at(secs),
+            })
+            .collect();
+        view.apply(&updates, position()).await.unwrap();
+
+        let active: Vec<SessionId> = view
+            .snapshot(at(5).into_datetime())
+            .active_sessions
+            .into_iter()
+            .map(|s| s.session_id)
+            .collect();
+        assert_eq!(active, vec![sessions[2].clone(), sessions[0].clone()]);
+    }
+
+    #[tokio::test]
+    async fn ended_sessions_leave_the_view() {
+        let view = LiveView::default();
+        let session_id = SessionId::generate();
+        view.apply(
+            &[
+                ReadModelUpdate::SessionActivity {
+                    session_id: session_id.clone(),
+                    at: at(0),
+                },
+                ReadModelUpdate::SessionEnded {
+                    session_id,
+                    status: crate::domain::session::SessionStatus::Completed,
+                    ended_at: at(1),
+                },
+            ],
+            position(),
+        )
+        .await
+        .unwrap();
+
+        assert!(view
+            .snapshot(at(2).into_datetime())
+            .active_sessions
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn fresh_view_starts_at_the_active_window() {
+        let view = LiveView::default();
+        let before = Uuid::now_v7();
+
+        let start = view.checkpoint().await.unwrap().unwrap().into_inner();
+
+        assert!(start < before);
+        let applied = position();
+        view.apply(&[], applied).await.unwrap();
+        assert_eq!(view.checkpoint().await.unwrap(), Some(applied));
+
+        view.reset().await.unwrap();
+        assert!(view.checkpoint().await.unwrap().unwrap() < applied);
+    }
+}
@@ -2,5 +2,40 @@
 //!
 //! This module contains projection implementations for building read models
 //! from the event stream.
+//!
+//! The session read model (the Tier 2 projection from ADR-0010) keeps the
+//! `sessions`, `llm_requests` and `llm_responses` tables in PostgreSQL up to
+//! date. [`runner::ProjectionRunner`] resumes from the stored checkpoint and
+//! can rebuild the tables from the start of the log.
+//!
+//! [`live::LiveView`] is the Tier 1 projection: recent sessions and rates
+//! held in memory and fed by the same runner. [`budget::BudgetLedger`] is
+//! fed the same way and keeps each budget's spend in the current period.
+//!
+//! Queries read from both: [`session_list`] pages through the `sessions`
+//! table, while [`session_detail`] rebuilds one session from its streams.
+//! [`search`] runs full-text queries over the prompts and responses in the
+//! read model, and [`usage`] reports the hourly usage rollup the session
+//! read model maintains.
+
+pub mod budget;
+pub mod live;
+pub mod postgres;
+pub mod read_model;
+pub mod runner;
+pub mod search;
+pub mod session_detail;
+pub mod session_list;
+pub mod usage;
 
-// Placeholder - will be implemented after command integration
+pub use budget::BudgetLedger;
+pub use live::{LiveSnapshot, LiveView, LiveViewConfig};
+pub use postgres::PostgresReadModel;
+pub use read_model::{updates_for, ReadModelUpdate};
+pub use runner::{ProjectionHandle, ProjectionRunner, ReadModelStore};
+pub use search::{SearchField, SearchHit, SearchIndex, SearchQuery, SearchResults, SearchText};
+pub use session_detail::{load_session_detail, SessionDetail};
+pub use session_list::{
+    PageSize, SessionCursor, SessionDirectory, SessionListQuery, SessionPage, SessionSummary,
+};
+pub use usage::{UsageBucket, UsageDimension, UsagePoint, UsageQuery, UsageReport, UsageRollup};
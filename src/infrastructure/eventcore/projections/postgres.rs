@@ -0,0 +1,466 @@
+//! PostgreSQL storage for the session read model
+//!
+//! Maintains the `sessions`, `llm_requests` and `llm_responses` tables and
+//! the `usage_hourly` rollup, and keeps the projection's checkpoint in
+//! `projection_checkpoints`, written in the same transaction as the rows it
+//! covers.
+
+use super::read_model::{request_status_label, session_status_label, ReadModelUpdate};
+use super::runner::ReadModelStore;
+use super::usage::{
+    apply_usage_changes, changing_sessions, load_request_usage, usage_changes, UsageScope,
+};
+use crate::domain::llm::RequestStatus;
+use crate::domain::session::SessionStatus;
+use crate::Result;
+use async_trait::async_trait;
+use eventcore_types::StreamPosition;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+/// Checkpoint name of the session read model
+pub const SESSIONS_PROJECTION: &str = "sessions";
+
+/// Idempotent DDL for the read model, run in order by [`PostgresReadModel::ensure_schema`]
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS projection_checkpoints (
+        name TEXT PRIMARY KEY,
+        position UUID NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )",
+    "CREATE TABLE IF NOT EXISTS sessions (
+        session_id UUID PRIMARY KEY,
+        user_id UUID,
+        application_id TEXT,
+        client_session_id TEXT,
+        status TEXT NOT NULL,
+        tags TEXT[] NOT NULL DEFAULT '{}',
+        metadata JSONB,
+        started_at TIMESTAMPTZ NOT NULL,
+        last_activity_at TIMESTAMPTZ NOT NULL,
+        ended_at TIMESTAMPTZ
+    )",
+    "CREATE INDEX IF NOT EXISTS sessions_started_at_idx
+        ON sessions (started_at DESC)",
+    "CREATE INDEX IF NOT EXISTS sessions_application_started_at_idx
+        ON sessions (application_id, started_at DESC)",
+    "CREATE INDEX IF NOT EXISTS sessions_status_started_at_idx
+        ON sessions (status, started_at DESC)",
+    "CREATE TABLE IF NOT EXISTS llm_requests (
+        request_id UUID PRIMARY KEY,
+        session_id UUID NOT NULL,
+        status TEXT NOT NULL,
+        provider TEXT,
+        model_id TEXT,
+        prompt TEXT,
+        parameters JSONB,
+        error_message TEXT,
+        received_at TIMESTAMPTZ NOT NULL,
+        started_at TIMESTAMPTZ,
+        completed_at TIMESTAMPTZ
+    )",
+    "CREATE INDEX IF NOT EXISTS llm_requests_session_received_at_idx
+        ON llm_requests (session_id, received_at)",
+    "CREATE INDEX IF NOT EXISTS llm_requests_status_idx
+        ON llm_requests (status)",
+    "CREATE TABLE IF NOT EXISTS llm_responses (
+        request_id UUID PRIMARY KEY,
+        session_id UUID,
+        response_text TEXT NOT NULL,
+        tokens_used BIGINT,
+        latency_ms BIGINT,
+        finish_reason TEXT,
+        model_used TEXT,
+        received_at TIMESTAMPTZ NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS llm_responses_session_idx
+        ON llm_responses (session_id)",
+    // Full-text search, see `super::search`
+    "ALTER TABLE llm_requests ADD COLUMN IF NOT EXISTS prompt_search TSVECTOR
+        GENERATED ALWAYS AS (to_tsvector('english', COALESCE(prompt, ''))) STORED",
+    "CREATE INDEX IF NOT EXISTS llm_requests_prompt_search_idx
+        ON llm_requests USING GIN (prompt_search)",
+    "ALTER TABLE llm_responses ADD COLUMN IF NOT EXISTS response_search TSVECTOR
+        GENERATED ALWAYS AS (to_tsvector('english', response_text)) STORED",
+    "CREATE INDEX IF NOT EXISTS llm_responses_response_search_idx
+        ON llm_responses USING GIN (response_search)",
+    // Usage rollup, see `super::usage`
+    "ALTER TABLE sessions ADD COLUMN IF NOT EXISTS environment_id TEXT",
+    "ALTER TABLE llm_requests
+        ADD COLUMN IF NOT EXISTS input_tokens BIGINT,
+        ADD COLUMN IF NOT EXISTS output_tokens BIGINT,
+        ADD COLUMN IF NOT EXISTS cache_read_tokens BIGINT,
+        ADD COLUMN IF NOT EXISTS cache_write_tokens BIGINT,
+        ADD COLUMN IF NOT EXISTS cost_usd NUMERIC",
+    "CREATE TABLE IF NOT EXISTS usage_hourly (
+        hour TIMESTAMPTZ NOT NULL,
+        application_id TEXT NOT NULL,
+        environment_id TEXT NOT NULL,
+        provider TEXT NOT NULL,
+        model_id TEXT NOT NULL,
+        request_count BIGINT NOT NULL,
+        error_count BIGINT NOT NULL,
+        input_tokens BIGINT NOT NULL,
+        output_tokens BIGINT NOT NULL,
+        cache_read_tokens BIGINT NOT NULL,
+        cache_write_tokens BIGINT NOT NULL,
+        cost_usd NUMERIC NOT NULL,
+        latency_histogram BIGINT[] NOT NULL,
+        PRIMARY KEY (hour, application_id, environment_id, provider, model_id)
+    )",
+];
+
+/// Session read model backed by PostgreSQL
+#[derive(Clone)]
+pub struct PostgresReadModel {
+    pool: PgPool,
+}
+
+impl PostgresReadModel {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Create the read model tables if they do not exist
+    pub async fn ensure_schema(&self) -> Result<()> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ReadModelStore for PostgresReadModel {
+    async fn checkpoint(&self) -> Result<Option<StreamPosition>> {
+        let row = sqlx::query("SELECT position FROM projection_checkpoints WHERE name = $1")
+            .bind(SESSIONS_PROJECTION)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row
+            .map(|row| row.try_get::<Uuid, _>("position"))
+            .transpose()?
+            .map(StreamPosition::new))
+    }
+
+    async fn apply(&self, updates: &[ReadModelUpdate], position: StreamPosition) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        // The rollup takes the difference in what the touched requests contribute
+        let scope = UsageScope::of(updates);
+        let sessions = changing_sessions(&mut tx, &scope.sessions).await?;
+        let before = load_request_usage(&mut tx, &scope.requests, &sessions).await?;
+        for update in updates {
+            apply_update(&mut tx, update).await?;
+        }
+        let after = load_request_usage(&mut tx, &scope.requests, &sessions).await?;
+        apply_usage_changes(&mut tx, &usage_changes(&before, &after)).await?;
+
+        sqlx::query(
+            "INSERT INTO projection_checkpoints (name, position, updated_at)
+             VALUES ($1, $2, NOW())
+             ON CONFLICT (name) DO UPDATE SET position = $2, updated_at = NOW()",
+        )
+        .bind(SESSIONS_PROJECTION)
+        .bind(position.into_inner())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn reset(&self) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("TRUNCATE sessions, llm_requests, llm_responses, usage_hourly")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM projection_checkpoints WHERE name = $1")
+            .bind(SESSIONS_PROJECTION)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// Run the statement for one update
+///
+/// Request outcome events only carry the request ID, so they update an
+/// existing `llm_requests` row; the audit path always records the request
+/// before its outcome.
+async fn apply_update(tx: &mut Transaction<'_, Postgres>, update: &ReadModelUpdate) -> Result<()> {
+    use ReadModelUpdate as U;
+
+    let query = match update {
+        U::SessionActivity { session_id, at } => sqlx::query(
+            "INSERT INTO sessions (session_id, status, started_at, last_activity_at)
+             VALUES ($1, $2, $3, $3)
+             ON CONFLICT (session_id) DO UPDATE SET
+                started_at = LEAST(sessions.started_at, EXCLUDED.started_at),
+                last_activity_at = GREATEST(sessions.last_activity_at, EXCLUDED.last_activity_at)",
+        )
+        .bind(*session_id.as_ref())
+        .bind(session_status_label(&SessionStatus::Active))
+        .bind(at.into_datetime()),
+        U::SessionStarted {
+            session_id,
+            user_id,
+            application_id,
+            started_at,
+        } => sqlx::query(
+            "INSERT INTO sessions
+                (session_id, user_id, application_id, status, started_at, last_activity_at)
+             VALUES ($1, $2, $3, $4, $5, $5)
+             ON CONFLICT (session_id) DO UPDATE SET
+                user_id = EXCLUDED.user_id,
+                application_id = EXCLUDED.application_id,
+                started_at = LEAST(sessions.started_at, EXCLUDED.started_at),
+                last_activity_at = GREATEST(sessions.last_activity_at, EXCLUDED.last_activity_at)",
+        )
+        .bind(*session_id.as_ref())
+        .bind(*user_id.as_ref())
+        .bind(application_id.as_ref())
+        .bind(session_status_label(&SessionStatus::Active))
+        .bind(started_at.into_datetime()),
+        U::SessionEnded {
+            session_id,
+            status,
+            ended_at,
+        } => sqlx::query("UPDATE sessions SET status = $2, ended_at = $3 WHERE session_id = $1")
+            .bind(*session_id.as_ref())
+            .bind(session_status_label(status))
+            .bind(ended_at.into_datetime()),
+        U::SessionTagged { session_id, tag } => sqlx::query(
+            "UPDATE sessions SET tags = array_append(tags, $2)
+             WHERE session_id = $1 AND NOT ($2 = ANY(tags))",
+        )
+        .bind(*session_id.as_ref())
+        .bind(tag.as_ref()),
+        U::SessionContextRecorded {
+            session_id,
+            metadata,
+        } => {
+            let tags: Vec<String> = metadata.tags().iter().map(|t| t.to_string()).collect();
+            sqlx::query(
+                "UPDATE sessions SET
+                    metadata = $2::jsonb,
+                    application_id = COALESCE($3, application_id),
+                    client_session_id = COALESCE($4, client_session_id),
+                    tags = ARRAY(
+                        SELECT DISTINCT t FROM unnest(tags || $5::text[]) AS t ORDER BY t
+                    ),
+                    environment_id = COALESCE($6, environment_id)
+                 WHERE session_id = $1",
+            )
+            .bind(*session_id.as_ref())
+            .bind(serde_json::to_string(metadata)?)
+            .bind(metadata.application_id().map(|id| id.to_string()))
+            .bind(metadata.client_session_id().map(|id| id.to_string()))
+            .bind(tags)
+            .bind(metadata.environment_id().map(|id| id.to_string()))
+        }
+        U::RequestDeferred {
+            request_id,
+            session_id,
+            received_at,
+        } => sqlx::query(
+            "INSERT INTO llm_requests (request_id, session_id, status, received_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (request_id) DO NOTHING",
+        )
+        .bind(*request_id.as_ref())
+        .bind(*session_id.as_ref())
+        .bind(request_status_label(&RequestStatus::Pending))
+        .bind(received_at.into_datetime()),
+        // A deferred request is parsed later, possibly after it completed,
+        // so only the parsed fields are overwritten
+        U::RequestReceived {
+            request_id,
+            session_id,
+            model_version,
+            prompt,
+            parameters,
+            received_at,
+        } => sqlx::query(
+            "INSERT INTO llm_requests
+                (request_id, session_id, status, provider, model_id, prompt, parameters, received_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7::jsonb, $8)
+             ON CONFLICT (request_id) DO UPDATE SET
+                provider = EXCLUDED.provider,
+                model_id = EXCLUDED.model_id,
+                prompt = EXCLUDED.prompt,
+                parameters = EXCLUDED.parameters",
+        )
+        .bind(*request_id.as_ref())
+        .bind(*session_id.as_ref())
+        .bind(request_status_label(&RequestStatus::Pending))
+        .bind(model_version.provider.as_str().to_string())
+        .bind(model_version.model_id.to_string())
+        .bind(prompt.to_string())
+        .bind(parameters.clone().into_inner().to_string())
+        .bind(received_at.into_datetime()),
+        U::RequestStarted {
+            request_id,
+            started_at,
+        } => sqlx::query(
+            "UPDATE llm_requests SET status = $2, started_at = $3
+             WHERE request_id = $1 AND status = $4",
+        )
+        .bind(*request_id.as_ref())
+        .bind(request_status_label(&RequestStatus::InProgress))
+        .bind(started_at.into_datetime())
+        .bind(request_status_label(&RequestStatus::Pending)),
+        U::ResponseReceived {
+            request_id,
+            response_text,
+            metadata,
+            received_at,
+        } => {
+            sqlx::query(
+                "INSERT INTO llm_responses
+                    (request_id, session_id, response_text, tokens_used, latency_ms,
+                     finish_reason, model_used, received_at)
+                 VALUES (
+                    $1, (SELECT session_id FROM llm_requests WHERE request_id = $1),
+                    $2, $3, $4, $5, $6, $7
+                 )
+                 ON CONFLICT (request_id) DO NOTHING",
+            )
+            .bind(*request_id.as_ref())
+            .bind(response_text.to_string())
+            .bind(metadata.tokens_used().map(|t| i64::from(t.into_inner())))
+            .bind(
+                metadata
+                    .latency_ms()
+                    .and_then(|l| i64::try_from(l.into_inner()).ok()),
+            )
+            .bind(metadata.finish_reason().map(|r| r.to_string()))
+            .bind(metadata.model_used().map(|m| m.to_string()))
+            .bind(received_at.into_datetime())
+            .execute(&mut **tx)
+            .await?;
+
+            sqlx::query(
+                "UPDATE llm_requests SET status = $2, completed_at = $3 WHERE request_id = $1",
+            )
+            .bind(*request_id.as_ref())
+            .bind(request_status_label(&RequestStatus::Completed))
+            .bind(received_at.into_datetime())
+        }
+        U::RequestFailed {
+            request_id,
+            error_message,
+            failed_at,
+        } => sqlx::query(
+            "UPDATE llm_requests SET status = $2, error_message = $3, completed_at = $4
+             WHERE request_id = $1",
+        )
+        .bind(*request_id.as_ref())
+        .bind(request_status_label(&RequestStatus::Failed))
+        .bind(error_message.to_string())
+        .bind(failed_at.into_datetime()),
+        U::RequestCancelled {
+            request_id,
+            cancelled_at,
+        } => sqlx::query(
+            "UPDATE llm_requests SET status = $2, completed_at = $3 WHERE request_id = $1",
+        )
+        .bind(*request_id.as_ref())
+        .bind(request_status_label(&RequestStatus::Cancelled))
+        .bind(cancelled_at.into_datetime()),
+        // The requested model, once parsed, takes precedence over the priced one
+        U::CostRecorded {
+            request_id,
+            model_version,
+            usage,
+            total,
+            ..
+        } => sqlx::query(
+            "UPDATE llm_requests SET
+                provider = COALESCE(provider, $2),
+                model_id = COALESCE(model_id, $3),
+                input_tokens = $4,
+                output_tokens = $5,
+                cache_read_tokens = $6,
+                cache_write_tokens = $7,
+                cost_usd = $8::numeric
+             WHERE request_id = $1",
+        )
+        .bind(*request_id.as_ref())
+        .bind(model_version.provider.as_str().to_string())
+        .bind(model_version.model_id.to_string())
+        .bind(i64::from(usage.input.into_inner()))
+        .bind(i64::from(usage.output.into_inner()))
+        .bind(i64::from(usage.cache_read.into_inner()))
+        .bind(i64::from(usage.cache_write.into_inner()))
+        .bind(total.to_string()),
+    };
+
+    query.execute(&mut **tx).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::llm::RequestId;
+    use crate::domain::metrics::Timestamp;
+    use crate::domain::session::SessionId;
+    use crate::domain::types::ErrorMessage;
+
+    async fn read_model() -> PostgresReadModel {
+        let settings = crate::config::Settings::new().unwrap();
+        let pool = PgPool::connect(&settings.database_url()).await.unwrap();
+        let read_model = PostgresReadModel::new(pool);
+        read_model.ensure_schema().await.unwrap();
+        read_model
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database connection"]
+    async fn applies_updates_and_checkpoint_together() {
+        let read_model = read_model().await;
+        let session_id = SessionId::generate();
+        let request_id = RequestId::generate();
+        let at = Timestamp::now();
+        let position = StreamPosition::new(Uuid::now_v7());
+
+        read_model
+            .apply(
+                &[
+                    ReadModelUpdate::SessionActivity {
+                        session_id: session_id.clone(),
+                        at,
+                    },
+                    ReadModelUpdate::RequestDeferred {
+                        request_id: request_id.clone(),
+                        session_id: session_id.clone(),
+                        received_at: at,
+                    },
+                    ReadModelUpdate::RequestFailed {
+                        request_id: request_id.clone(),
+                        error_message: ErrorMessage::try_new("boom".to_string()).unwrap(),
+                        failed_at: at,
+                    },
+                ],
+                position,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(read_model.checkpoint().await.unwrap(), Some(position));
+        let status: String =
+            sqlx::query_scalar("SELECT status FROM llm_requests WHERE request_id = $1")
+                .bind(*request_id.as_ref())
+                .fetch_one(read_model.pool())
+                .await
+                .unwrap();
+        assert_eq!(status, request_status_label(&RequestStatus::Failed));
+    }
+}
@@ -0,0 +1,431 @@
+//! Row updates for the session read model
+//!
+//! Maps each [`DomainEvent`] to the changes it makes to the `sessions`,
+//! `llm_requests` and `llm_responses` tables, which also drive the hourly
+//! usage rollup in [`super::usage`]. Keeping this free of SQL lets
+//! the mapping be tested without a database; [`super::postgres`] turns each
+//! update into a statement.
+//!
+//! The audit path does not always emit `SessionStarted`, so any event that
+//! names a session also records activity on it, creating the row if needed.
+
+use crate::domain::events::DomainEvent;
+use crate::domain::llm::{ModelVersion, RequestId, RequestStatus, ResponseMetadata};
+use crate::domain::metrics::Timestamp;
+use crate::domain::pricing::{TokenUsage, UsdAmount};
+use crate::domain::session::{
+    ApplicationId, EnvironmentId, SessionId, SessionMetadata, SessionStatus,
+};
+use crate::domain::types::{ErrorMessage, LlmParameters, Prompt, ResponseText, Tag};
+use crate::domain::user::UserId;
+
+/// One change to the session read model
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadModelUpdate {
+    /// Something happened in the session; widens its time range
+    SessionActivity {
+        session_id: SessionId,
+        at: Timestamp,
+    },
+    SessionStarted {
+        session_id: SessionId,
+        user_id: UserId,
+        application_id: ApplicationId,
+        started_at: Timestamp,
+    },
+    SessionEnded {
+        session_id: SessionId,
+        status: SessionStatus,
+        ended_at: Timestamp,
+    },
+    SessionTagged {
+        session_id: SessionId,
+        tag: Tag,
+    },
+    SessionContextRecorded {
+        session_id: SessionId,
+        metadata: SessionMetadata,
+    },
+    /// The request arrived but its body has not been parsed yet
+    RequestDeferred {
+        request_id: RequestId,
+        session_id: SessionId,
+        received_at: Timestamp,
+    },
+    RequestReceived {
+        request_id: RequestId,
+        session_id: SessionId,
+        model_version: ModelVersion,
+        prompt: Prompt,
+        parameters: LlmParameters,
+        received_at: Timestamp,
+    },
+    RequestStarted {
+        request_id: RequestId,
+        started_at: Timestamp,
+    },
+    ResponseReceived {
+        request_id: RequestId,
+        response_text: ResponseText,
+        metadata: ResponseMetadata,
+        received_at: Timestamp,
+    },
+    RequestFailed {
+        request_id: RequestId,
+        error_message: ErrorMessage,
+        failed_at: Timestamp,
+    },
+    RequestCancelled {
+        request_id: RequestId,
+        cancelled_at: Timestamp,
+    },
+    /// The request's token usage was priced with `model_version`'s price
+    CostRecorded {
+        request_id: RequestId,
+        session_id: SessionId,
+        application_id: Option<ApplicationId>,
+        environment_id: Option<EnvironmentId>,
+        model_version: ModelVersion,
+        usage: TokenUsage,
+        total: UsdAmount,
+        calculated_at: Timestamp,
+    },
+}
+
+/// The updates `event` makes, in the order they must be applied
+///
+/// Events the read model does not track produce no updates.
+pub fn updates_for(event: &DomainEvent) -> Vec<ReadModelUpdate> {
+    use ReadModelUpdate as U;
+
+    match event {
+        DomainEvent::SessionStarted {
+            session_id,
+            user_id,
+            application_id,
+            started_at,
+            ..
+        } => vec![U::SessionStarted {
+            session_id: session_id.clone(),
+            user_id: user_id.clone(),
+            application_id: application_id.clone(),
+            started_at: *started_at,
+        }],
+        DomainEvent::SessionEnded {
+            session_id,
+            ended_at,
+            final_status,
+            ..
+        } => vec![
+            U::SessionActivity {
+                session_id: session_id.clone(),
+                at: *ended_at,
+            },
+            U::SessionEnded {
+                session_id: session_id.clone(),
+                status: final_status.clone(),
+                ended_at: *ended_at,
+            },
+        ],
+        DomainEvent::SessionTagged {
+            session_id,
+            tag,
+            tagged_at,
+            ..
+        } => vec![
+            U::SessionActivity {
+                session_id: session_id.clone(),
+                at: *tagged_at,
+            },
+            U::SessionTagged {
+                session_id: session_id.clone(),
+                tag: tag.clone(),
+            },
+        ],
+        DomainEvent::SessionContextRecorded {
+            session_id,
+            metadata,
+            recorded_at,
+            ..
+        } => vec![
+            U::SessionActivity {
+                session_id: session_id.clone(),
+                at: *recorded_at,
+            },
+            U::SessionContextRecorded {
+                session_id: session_id.clone(),
+                metadata: metadata.clone(),
+            },
+        ],
+        DomainEvent::LlmRequestDeferred {
+            request_id,
+            session_id,
+            received_at,
+            ..
+        } => vec![
+            U::SessionActivity {
+                session_id: session_id.clone(),
+                at: *received_at,
+            },
+            U::RequestDeferred {
+                request_id: request_id.clone(),
+                session_id: session_id.clone(),
+                received_at: *received_at,
+            },
+        ],
+        DomainEvent::LlmRequestReceived {
+            request_id,
+            session_id,
+            model_version,
+            prompt,
+            parameters,
+            received_at,
+            ..
+        } => vec![
+            U::SessionActivity {
+                session_id: session_id.clone(),
+                at: *received_at,
+            },
+            U::RequestReceived {
+                request_id: request_id.clone(),
+                session_id: session_id.clone(),
+                model_version: model_version.clone(),
+                prompt: prompt.clone(),
+                parameters: parameters.clone(),
+                received_at: *received_at,
+            },
+        ],
+        DomainEvent::LlmRequestStarted {
+            request_id,
+            started_at,
+            ..
+        } => vec![U::RequestStarted {
+            request_id: request_id.clone(),
+            started_at: *started_at,
+        }],
+        DomainEvent::LlmResponseReceived {
+            request_id,
+            response_text,
+            metadata,
+            received_at,
+            ..
+        } => vec![U::ResponseReceived {
+            request_id: request_id.clone(),
+            response_text: response_text.clone(),
+            metadata: metadata.clone(),
+            received_at: *received_at,
+        }],
+        DomainEvent::LlmRequestFailed {
+            request_id,
+            error_message,
+            failed_at,
+            ..
+        } => vec![U::RequestFailed {
+            request_id: request_id.clone(),
+            error_message: error_message.clone(),
+            failed_at: *failed_at,
+        }],
+        DomainEvent::LlmRequestCancelled {
+            request_id,
+            cancelled_at,
+            ..
+        } => vec![U::RequestCancelled {
+            request_id: request_id.clone(),
+            cancelled_at: *cancelled_at,
+        }],
+        DomainEvent::CostCalculated {
+            request_id,
+            session_id,
+            application_id,
+            environment_id,
+            model_version,
+            usage,
+            cost,
+            calculated_at,
+            ..
+        } => vec![U::CostRecorded {
+            request_id: request_id.clone(),
+            session_id: session_id.clone(),
+            application_id: application_id.clone(),
+            environment_id: environment_id.clone(),
+            model_version: model_version.clone(),
+            usage: *usage,
+            total: cost.total,
+            calculated_at: *calculated_at,
+        }],
+        DomainEvent::LlmRequestBodyCaptured { .. }
+        | DomainEvent::LlmResponseBodyCaptured { .. }
+        | DomainEvent::LlmRequestParsingFailed { .. }
+        | DomainEvent::InvalidStateTransition { .. }
+        | DomainEvent::AuditEventProcessingFailed { .. }
+        | DomainEvent::BudgetThresholdCrossed { .. }
+        | DomainEvent::BudgetLimitReached { .. }
+        | DomainEvent::VersionFirstSeen { .. }
+        | DomainEvent::VersionChanged { .. }
+        | DomainEvent::VersionUsageRecorded { .. }
+        | DomainEvent::VersionDeactivated { .. }
+        | DomainEvent::FScoreCalculated { .. }
+        | DomainEvent::ApplicationFScoreCalculated { .. }
+        | DomainEvent::UserCreated { .. }
+        | DomainEvent::UserActivated { .. }
+        | DomainEvent::UserDeactivated { .. } => Vec::new(),
+    }
+}
+
+/// Value stored in `sessions.status`
+pub fn session_status_label(status: &SessionStatus) -> &'static str {
+    match status {
+        SessionStatus::Active => "active",
+        SessionStatus::Completed => "completed",
+        SessionStatus::Failed => "failed",
+        SessionStatus::Cancelled => "cancelled",
+    }
+}
+
+/// Inverse of [`session_status_label`]
+pub fn session_status_from_label(label: &str) -> Option<SessionStatus> {
+    match label {
+        "active" => Some(SessionStatus::Active),
+        "completed" => Some(SessionStatus::Completed),
+        "failed" => Some(SessionStatus::Failed),
+        "cancelled" => Some(SessionStatus::Cancelled),
+        _ => None,
+    }
+}
+
+/// Value stored in `llm_requests.status`
+pub fn request_status_label(status: &RequestStatus) -> &'static str {
+    match status {
+        RequestStatus::Pending => "pending",
+        RequestStatus::InProgress => "in_progress",
+        RequestStatus::Completed => "completed",
+        RequestStatus::Failed => "failed",
+        RequestStatus::Cancelled => "cancelled",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::llm::LlmProvider;
+    use crate::domain::types::ModelId;
+    use eventcore::StreamId;
+
+    fn stream() -> StreamId {
+        StreamId::try_new("session-test".to_string()).unwrap()
+    }
+
+    #[test]
+    fn request_events_record_session_activity_first() {
+        let session_id = SessionId::generate();
+        let request_id = RequestId::generate();
+        let received_at = Timestamp::now();
+        let event = DomainEvent::LlmRequestReceived {
+            stream_id: stream(),
+            request_id: request_id.clone(),
+            session_id: session_id.clone(),
+            model_version: ModelVersion {
+                provider: LlmProvider::Anthropic,
+                model_id: ModelId::try_new("claude-3-haiku".to_string()).unwrap(),
+            },
+            prompt: Prompt::try_new("Hello".to_string()).unwrap(),
+            parameters: LlmParameters::new(serde_json::json!({"max_tokens": 10})),
+            received_at,
+        };
+
+        let updates = updates_for(&event);
+
+        assert_eq!(updates.len(), 2);
+        assert_eq!(
+            updates[0],
+            ReadModelUpdate::SessionActivity {
+                session_id: session_id.clone(),
+                at: received_at,
+            }
+        );
+        assert!(matches!(
+            &updates[1],
+            ReadModelUpdate::RequestReceived { request_id: id, session_id: sid, .. }
+                if *id == request_id && *sid == session_id
+        ));
+    }
+
+    #[test]
+    fn request_outcomes_update_only_the_request() {
+        let request_id = RequestId::generate();
+        let at = Timestamp::now();
+        let events = [
+            DomainEvent::LlmRequestStarted {
+                stream_id: stream(),
+                request_id: request_id.clone(),
+                started_at: at,
+            },
+            DomainEvent::LlmResponseReceived {
+                stream_id: stream(),
+                request_id: request_id.clone(),
+                response_text: ResponseText::try_new("Hi".to_string()).unwrap(),
+                metadata: ResponseMetadata::new(),
+                received_at: at,
+            },
+            DomainEvent::LlmRequestFailed {
+                stream_id: stream(),
+                request_id: request_id.clone(),
+                error_message: ErrorMessage::try_new("upstream timeout".to_string()).unwrap(),
+                failed_at: at,
+            },
+            DomainEvent::LlmRequestCancelled {
+                stream_id: stream(),
+                request_id: request_id.clone(),
+                cancelled_at: at,
+            },
+        ];
+
+        for event in &events {
+            let updates = updates_for(event);
+            assert_eq!(updates.len(), 1, "{event:?}");
+            assert!(!matches!(
+                updates[0],
+                ReadModelUpdate::SessionActivity { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn untracked_events_produce_no_updates() {
+        let event = DomainEvent::UserActivated {
+            stream_id: stream(),
+            user_id: UserId::generate(),
+            activated_at: Timestamp::now(),
+        };
+
+        assert!(updates_for(&event).is_empty());
+    }
+
+    #[test]
+    fn status_labels_are_distinct() {
+        let requests = [
+            RequestStatus::Pending,
+            RequestStatus::InProgress,
+            RequestStatus::Completed,
+            RequestStatus::Failed,
+            RequestStatus::Cancelled,
+        ]
+        .map(|s| request_status_label(&s));
+        let mut unique = requests.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), requests.len());
+
+        for status in [
+            SessionStatus::Active,
+            SessionStatus::Completed,
+            SessionStatus::Failed,
+            SessionStatus::Cancelled,
+        ] {
+            assert_eq!(
+                session_status_from_label(session_status_label(&status)),
+                Some(status)
+            );
+        }
+    }
+}
@@ -0,0 +1,292 @@
+//! Checkpointed projection runner
+//!
+//! Reads the global event log in pages, starting after the store's last
+//! checkpoint, and hands each page's updates to the store together with the
+//! position of the page's last event. Stores apply both atomically, so a
+//! restart resumes exactly where the previous run stopped.
+
+use super::read_model::{updates_for, ReadModelUpdate};
+use crate::domain::events::DomainEvent;
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eventcore_types::{BatchSize, EventFilter, EventPage, EventReader, StreamPosition};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::{JoinError, JoinHandle};
+use tracing::{debug, info, warn};
+
+/// Events read per page when catching up
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// Time between polls once the projection has caught up
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Position just before the first event recorded at or after `at`
+///
+/// Event IDs are time-ordered, so in-memory stores start here to replay only
+/// recent events.
+pub fn position_at(at: DateTime<Utc>) -> StreamPosition {
+    let millis = u64::try_from(at.timestamp_millis()).unwrap_or_default();
+    // The lowest v7 UUID for that millisecond sorts before every event ID in it
+    StreamPosition::new(uuid::Builder::from_unix_timestamp_millis(millis, &[0; 10]).into_uuid())
+}
+
+/// Destination of a projection's updates
+#[async_trait]
+pub trait ReadModelStore: Send + Sync {
+    /// Position of the last event applied, or `None` if nothing has been
+    async fn checkpoint(&self) -> Result<Option<StreamPosition>>;
+
+    /// Apply `updates` and move the checkpoint to `position` atomically
+    async fn apply(&self, updates: &[ReadModelUpdate], position: StreamPosition) -> Result<()>;
+
+    /// Remove every row and the checkpoint so the next run starts from scratch
+    async fn reset(&self) -> Result<()>;
+}
+
+#[async_trait]
+impl<T: ReadModelStore + ?Sized> ReadModelStore for Arc<T> {
+    async fn checkpoint(&self) -> Result<Option<StreamPosition>> {
+        (**self).checkpoint().await
+    }
+
+    async fn apply(&self, updates: &[ReadModelUpdate], position: StreamPosition) -> Result<()> {
+        (**self).apply(updates, position).await
+    }
+
+    async fn reset(&self) -> Result<()> {
+        (**self).reset().await
+    }
+}
+
+/// Keeps a [`ReadModelStore`] up to date with the event log
+pub struct ProjectionRunner<R, S> {
+    reader: Arc<R>,
+    store: S,
+    batch_size: BatchSize,
+    poll_interval: Duration,
+}
+
+impl<R, S> ProjectionRunner<R, S>
+where
+    R: EventReader + Send + Sync + 'static,
+    R::Error: std::fmt::Display,
+    S: ReadModelStore + 'static,
+{
+    pub fn new(reader: Arc<R>, store: S) -> Self {
+        Self {
+            reader,
+            store,
+            batch_size: BatchSize::new(DEFAULT_BATCH_SIZE),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: BatchSize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Apply every event after the checkpoint; returns how many were read
+    pub async fn catch_up(&self) -> Result<usize> {
+        let mut position = self.store.checkpoint().await?;
+        let mut processed = 0;
+
+        loop {
+            let page = match position {
+                Some(after) => EventPage::after(after, self.batch_size),
+                None => EventPage::first(self.batch_size),
+            };
+            let events: Vec<(DomainEvent, StreamPosition)> = self
+                .reader
+                .read_events(EventFilter::all(), page)
+                .await
+                .map_err(|e| crate::Error::application(format!("Failed to read events: {e}")))?;
+
+            let Some(&(_, last)) = events.last() else {
+                return Ok(processed);
+            };
+
+            let updates: Vec<ReadModelUpdate> = events
+                .iter()
+                .flat_map(|(event, _)| updates_for(event))
+                .collect();
+            self.store.apply(&updates, last).await?;
+
+            processed += events.len();
+            position = Some(last);
+        }
+    }
+
+    /// Discard the read model and project the whole log again
+    pub async fn rebuild(&self) -> Result<usize> {
+        self.store.reset().await?;
+        self.catch_up().await
+    }
+
+    /// Poll for new events until a shutdown signal arrives
+    ///
+    /// Failures are logged and retried on the next poll; the checkpoint only
+    /// moves when a page has been applied.
+    pub async fn run(self, mut shutdown_rx: mpsc::Receiver<()>) {
+        info!("Session projection started");
+
+        loop {
+            match self.catch_up().await {
+                Ok(0) => {}
+                Ok(count) => debug!("Session projection applied {count} events"),
+                Err(e) => warn!("Session projection failed; retrying: {e}"),
+            }
+
+            tokio::select! {
+                _ = shutdown_rx.recv() => break,
+                _ = tokio::time::sleep(self.poll_interval) => {}
+            }
+        }
+
+        info!("Session projection stopped");
+    }
+
+    /// Spawn [`Self::run`] on the Tokio runtime
+    pub fn spawn(self) -> ProjectionHandle {
+        let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        ProjectionHandle {
+            shutdown_tx,
+            task: tokio::spawn(self.run(shutdown_rx)),
+        }
+    }
+}
+
+/// Handle to a spawned projection runner
+pub struct ProjectionHandle {
+    shutdown_tx: mpsc::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl ProjectionHandle {
+    /// Stop polling and wait for the current page to finish
+    pub async fn shutdown(self) -> std::result::Result<(), JoinError> {
+        // A closed channel means the runner has already stopped
+        let _ = self.shutdown_tx.send(()).await;
+        self.task.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::llm::RequestId;
+    use crate::domain::metrics::Timestamp;
+    use crate::domain::session::SessionId;
+    use crate::domain::streams::session_stream;
+    use eventcore_memory::InMemoryEventStore;
+    use eventcore_types::{EventStore, StreamVersion, StreamWrites};
+    use std::sync::Mutex;
+
+    /// Records applied updates in memory
+    #[derive(Clone, Default)]
+    struct RecordingStore {
+        state: Arc<Mutex<(Vec<ReadModelUpdate>, Option<StreamPosition>)>>,
+    }
+
+    impl RecordingStore {
+        fn updates(&self) -> Vec<ReadModelUpdate> {
+            self.state.lock().unwrap().0.clone()
+        }
+    }
+
+    #[async_trait]
+    impl ReadModelStore for RecordingStore {
+        async fn checkpoint(&self) -> Result<Option<StreamPosition>> {
+            Ok(self.state.lock().unwrap().1)
+        }
+
+        async fn apply(&self, updates: &[ReadModelUpdate], position: StreamPosition) -> Result<()> {
+            let mut state = self.state.lock().unwrap();
+            state.0.extend_from_slice(updates);
+            state.1 = Some(position);
+            Ok(())
+        }
+
+        async fn reset(&self) -> Result<()> {
+            *self.state.lock().unwrap() = (Vec::new(), None);
+            Ok(())
+        }
+    }
+
+    async fn append_deferred(store: &InMemoryEventStore, count: usize) {
+        let session_id = SessionId::generate();
+        let stream_id = session_stream(&session_id).unwrap();
+        let mut writes = StreamWrites::new()
+            .register_stream(stream_id.clone(), StreamVersion::new(0))
+            .unwrap();
+        for _ in 0..count {
+            writes = writes
+                .append(DomainEvent::LlmRequestDeferred {
+                    stream_id: stream_id.clone(),
+                    request_id: RequestId::generate(),
+                    session_id: session_id.clone(),
+                    received_at: Timestamp::now(),
+                })
+                .unwrap();
+        }
+        store.append_events(writes).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn catch_up_pages_through_the_log_and_resumes_from_checkpoint() {
+        let events = Arc::new(InMemoryEventStore::new());
+        append_deferred(&events, 5).await;
+        let store = RecordingStore::default();
+        let runner = ProjectionRunner::new(Arc::clone(&events), store.clone())
+            .with_batch_size(BatchSize::new(2));
+
+        assert_eq!(runner.catch_up().await.unwrap(), 5);
+        // Each deferred request records session activity and the request
+        assert_eq!(store.updates().len(), 10);
+
+        append_deferred(&events, 1).await;
+        assert_eq!(runner.catch_up().await.unwrap(), 1);
+        assert_eq!(runner.catch_up().await.unwrap(), 0);
+        assert_eq!(store.updates().len(), 12);
+    }
+
+    #[tokio::test]
+    async fn rebuild_replays_the_whole_log() {
+        let events = Arc::new(InMemoryEventStore::new());
+        append_deferred(&events, 3).await;
+        let store = RecordingStore::default();
+        let runner = ProjectionRunner::new(events, store.clone());
+        runner.catch_up().await.unwrap();
+
+        assert_eq!(runner.rebuild().await.unwrap(), 3);
+        assert_eq!(store.updates().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn spawned_runner_projects_and_shuts_down() {
+        let events = Arc::new(InMemoryEventStore::new());
+        append_deferred(&events, 2).await;
+        let store = RecordingStore::default();
+        let handle = ProjectionRunner::new(events, store.clone())
+            .with_poll_interval(Duration::from_millis(10))
+            .spawn();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while store.updates().len() < 4 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("projection caught up");
+
+        handle.shutdown().await.unwrap();
+    }
+}
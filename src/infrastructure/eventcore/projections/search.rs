@@ -0,0 +1,281 @@
+//! Full-text search over captured prompts and responses
+//!
+//! The read model keeps a generated `tsvector` next to every prompt in
+//! `llm_requests` and every response in `llm_responses`, so the index follows
+//! the projection without extra writes. Queries use PostgreSQL's web search
+//! syntax: words are ANDed, `"quoted text"` matches a phrase, `or` gives
+//! alternatives and `-word` excludes.
+
+use super::postgres::PostgresReadModel;
+use super::session_list::PageSize;
+use crate::domain::session::ApplicationId;
+use crate::domain::types::ModelId;
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use nutype::nutype;
+use serde::Serialize;
+use sqlx::{Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+/// Text search configuration the `tsvector` columns are generated with
+pub const SEARCH_CONFIG: &str = "english";
+
+/// Markers placed around matched words in snippets
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_STOP: &str = "</mark>";
+
+/// What to search for, in web search syntax
+#[nutype(
+    sanitize(trim),
+    validate(not_empty, len_char_max = 1000),
+    derive(Debug, Clone, PartialEq, Eq, AsRef, Display)
+)]
+pub struct SearchText(String);
+
+/// A full-text search with optional filters
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub text: SearchText,
+    pub application_id: Option<ApplicationId>,
+    pub model_id: Option<ModelId>,
+    /// Requests received at or after this instant
+    pub received_from: Option<DateTime<Utc>>,
+    /// Requests received before this instant
+    pub received_to: Option<DateTime<Utc>>,
+    pub limit: PageSize,
+}
+
+impl SearchQuery {
+    pub fn new(text: SearchText) -> Self {
+        Self {
+            text,
+            application_id: None,
+            model_id: None,
+            received_from: None,
+            received_to: None,
+            limit: PageSize::default(),
+        }
+    }
+}
+
+/// Which side of the exchange matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Prompt,
+    Response,
+}
+
+impl SearchField {
+    fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "prompt" => Some(Self::Prompt),
+            "response" => Some(Self::Response),
+            _ => None,
+        }
+    }
+}
+
+/// One matching prompt or response
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchHit {
+    pub session_id: Uuid,
+    pub request_id: Uuid,
+    pub application_id: Option<String>,
+    pub model_id: Option<String>,
+    pub field: SearchField,
+    /// Matching fragments with matched words wrapped in `<mark>` tags
+    pub snippet: String,
+    pub rank: f32,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Hits ordered by relevance, then most recent first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchResults {
+    pub hits: Vec<SearchHit>,
+}
+
+/// Source of search results
+#[async_trait]
+pub trait SearchIndex: Send + Sync {
+    async fn search(&self, query: &SearchQuery) -> Result<SearchResults>;
+}
+
+#[async_trait]
+impl SearchIndex for PostgresReadModel {
+    async fn search(&self, query: &SearchQuery) -> Result<SearchResults> {
+        let headline_options = format!(
+            "StartSel={HIGHLIGHT_START}, StopSel={HIGHLIGHT_STOP}, MaxFragments=2, MaxWords=30, MinWords=10"
+        );
+
+        let mut sql = QueryBuilder::<Postgres>::new("WITH q AS (SELECT websearch_to_tsquery(");
+        sql.push_bind(SEARCH_CONFIG)
+            .push("::regconfig, ")
+            .push_bind(query.text.to_string())
+            .push(") AS query) SELECT * FROM (");
+
+        sql.push(
+            "SELECT r.session_id, r.request_id, s.application_id, r.model_id,
+                'prompt' AS field, ts_headline(",
+        )
+        .push_bind(SEARCH_CONFIG)
+        .push("::regconfig, r.prompt, q.query, ")
+        .push_bind(headline_options.clone())
+        .push(
+            ") AS snippet, ts_rank(r.prompt_search, q.query) AS rank, r.received_at
+             FROM llm_requests r CROSS JOIN q
+             LEFT JOIN sessions s ON s.session_id = r.session_id
+             WHERE r.prompt_search @@ q.query",
+        );
+        push_filters(&mut sql, query);
+
+        sql.push(
+            " UNION ALL SELECT r.session_id, r.request_id, s.application_id, r.model_id,
+                'response' AS field, ts_headline(",
+        )
+        .push_bind(SEARCH_CONFIG)
+        .push("::regconfig, p.response_text, q.query, ")
+        .push_bind(headline_options)
+        .push(
+            ") AS snippet, ts_rank(p.response_search, q.query) AS rank, r.received_at
+             FROM llm_responses p CROSS JOIN q
+             JOIN llm_requests r ON r.request_id = p.request_id
+             LEFT JOIN sessions s ON s.session_id = r.session_id
+             WHERE p.response_search @@ q.query",
+        );
+        push_filters(&mut sql, query);
+
+        sql.push(") hits ORDER BY rank DESC, received_at DESC LIMIT ")
+            .push_bind(i64::from(*query.limit.as_ref()));
+
+        let rows = sql.build().fetch_all(self.pool()).await?;
+        let hits = rows
+            .iter()
+            .map(|row| {
+                let field: String = row.try_get("field")?;
+                Ok(SearchHit {
+                    session_id: row.try_get("session_id")?,
+                    request_id: row.try_get("request_id")?,
+                    application_id: row.try_get("application_id")?,
+                    model_id: row.try_get("model_id")?,
+                    field: SearchField::from_label(&field).ok_or_else(|| {
+                        crate::Error::application(format!("Unexpected search field {field}"))
+                    })?,
+                    snippet: row.try_get("snippet")?,
+                    rank: row.try_get("rank")?,
+                    received_at: row.try_get("received_at")?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(SearchResults { hits })
+    }
+}
+
+/// Filters shared by the prompt and response halves of the query
+fn push_filters(sql: &mut QueryBuilder<'_, Postgres>, query: &SearchQuery) {
+    if let Some(application_id) = &query.application_id {
+        sql.push(" AND s.application_id = ")
+            .push_bind(application_id.to_string());
+    }
+    if let Some(model_id) = &query.model_id {
+        sql.push(" AND r.model_id = ")
+            .push_bind(model_id.to_string());
+    }
+    if let Some(from) = query.received_from {
+        sql.push(" AND r.received_at >= ").push_bind(from);
+    }
+    if let Some(to) = query.received_to {
+        sql.push(" AND r.received_at < ").push_bind(to);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::llm::{LlmProvider, ModelVersion, RequestId, ResponseMetadata};
+    use crate::domain::metrics::Timestamp;
+    use crate::domain::session::SessionId;
+    use crate::domain::types::{LlmParameters, Prompt, ResponseText};
+    use crate::infrastructure::eventcore::projections::{ReadModelStore, ReadModelUpdate};
+    use eventcore_types::StreamPosition;
+
+    #[test]
+    fn search_text_is_trimmed_and_required() {
+        assert_eq!(
+            SearchText::try_new("  refund policy ".to_string())
+                .unwrap()
+                .as_ref(),
+            "refund policy"
+        );
+        assert!(SearchText::try_new("   ".to_string()).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database connection"]
+    async fn finds_phrases_in_responses_with_highlights() {
+        let settings = crate::config::Settings::new().unwrap();
+        let pool = sqlx::PgPool::connect(&settings.database_url())
+            .await
+            .unwrap();
+        let read_model = PostgresReadModel::new(pool);
+        read_model.ensure_schema().await.unwrap();
+
+        let session_id = SessionId::generate();
+        let request_id = RequestId::generate();
+        let model_id = ModelId::try_new(format!("search-{}", Uuid::now_v7())).unwrap();
+        let at = Timestamp::now();
+        read_model
+            .apply(
+                &[
+                    ReadModelUpdate::SessionActivity {
+                        session_id: session_id.clone(),
+                        at,
+                    },
+                    ReadModelUpdate::RequestReceived {
+                        request_id: request_id.clone(),
+                        session_id: session_id.clone(),
+                        model_version: ModelVersion {
+                            provider: LlmProvider::OpenAI,
+                            model_id: model_id.clone(),
+                        },
+                        prompt: Prompt::try_new("Can I get my money back?".to_string()).unwrap(),
+                        parameters: LlmParameters::new(serde_json::json!({})),
+                        received_at: at,
+                    },
+                    ReadModelUpdate::ResponseReceived {
+                        request_id: request_id.clone(),
+                        response_text: ResponseText::try_new(
+                            "Our refund policy allows returns within 30 days.".to_string(),
+                        )
+                        .unwrap(),
+                        metadata: ResponseMetadata::new(),
+                        received_at: at,
+                    },
+                ],
+                StreamPosition::new(Uuid::now_v7()),
+            )
+            .await
+            .unwrap();
+
+        let mut query =
+            SearchQuery::new(SearchText::try_new("\"refund policy\"".to_string()).unwrap());
+        query.model_id = Some(model_id);
+        let results = read_model.search(&query).await.unwrap();
+
+        assert_eq!(results.hits.len(), 1);
+        let hit = &results.hits[0];
+        assert_eq!(hit.field, SearchField::Response);
+        assert_eq!(hit.request_id, *request_id.as_ref());
+        assert!(
+            hit.snippet.contains("<mark>refund</mark>"),
+            "{}",
+            hit.snippet
+        );
+
+        query.text = SearchText::try_new("\"policy refund\"".to_string()).unwrap();
+        assert!(read_model.search(&query).await.unwrap().hits.is_empty());
+    }
+}
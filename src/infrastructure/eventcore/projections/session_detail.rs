@@ -0,0 +1,403 @@
+//! One session reconstructed from its events
+//!
+//! Built on demand instead of from the read model tables, so a session can be
+//! inspected exactly as recorded, including captured bodies and parse
+//! failures the tables do not keep. The session stream names every request;
+//! each request's outcome lives in its own request stream.
+
+use crate::domain::audit_types::BodyContent;
+use crate::domain::events::DomainEvent;
+use crate::domain::llm::{ModelVersion, RequestId, RequestStatus, ResponseMetadata};
+use crate::domain::session::{ApplicationId, SessionId, SessionMetadata, SessionStatus};
+use crate::domain::streams::{request_stream, session_stream};
+use crate::domain::types::{ErrorMessage, LlmParameters, Prompt, ResponseText, Tag};
+use crate::domain::user::UserId;
+use crate::infrastructure::eventcore::service::EventCoreService;
+use crate::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A session and every request made in it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionDetail {
+    pub session_id: SessionId,
+    pub user_id: Option<UserId>,
+    pub application_id: Option<ApplicationId>,
+    pub status: SessionStatus,
+    pub tags: Vec<Tag>,
+    /// Context from the most recent request's session headers
+    pub metadata: Option<SessionMetadata>,
+    pub started_at: DateTime<Utc>,
+    pub last_activity_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// In the order they were received
+    pub requests: Vec<RequestDetail>,
+}
+
+/// One request and its outcome
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequestDetail {
+    pub request_id: RequestId,
+    pub status: RequestStatus,
+    pub model_version: Option<ModelVersion>,
+    pub prompt: Option<Prompt>,
+    pub parameters: Option<LlmParameters>,
+    pub request_body: Option<CapturedBody>,
+    pub received_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub response: Option<ResponseDetail>,
+    pub response_body: Option<CapturedBody>,
+    /// Why the request failed or could not be parsed
+    pub errors: Vec<ErrorMessage>,
+}
+
+/// The provider's answer to a request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseDetail {
+    pub response_text: ResponseText,
+    pub metadata: ResponseMetadata,
+    pub received_at: DateTime<Utc>,
+}
+
+/// A body as captured by the proxy, decoded as UTF-8 where possible
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapturedBody {
+    pub content: String,
+    pub truncated: bool,
+}
+
+impl CapturedBody {
+    fn new(body: &BodyContent, truncated: bool) -> Self {
+        Self {
+            content: String::from_utf8_lossy(body.as_ref()).into_owned(),
+            truncated,
+        }
+    }
+}
+
+impl RequestDetail {
+    fn new(request_id: RequestId, received_at: DateTime<Utc>) -> Self {
+        Self {
+            request_id,
+            status: RequestStatus::Pending,
+            model_version: None,
+            prompt: None,
+            parameters: None,
+            request_body: None,
+            received_at,
+            started_at: None,
+            completed_at: None,
+            response: None,
+            response_body: None,
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl SessionDetail {
+    /// Fold the session's events, from any of its streams, into a detail
+    ///
+    /// Events are ordered by when they occurred. Returns `None` when no event
+    /// belongs to the session.
+    pub fn from_events(session_id: &SessionId, events: &[DomainEvent]) -> Option<Self> {
+        let mut ordered: Vec<&DomainEvent> = events.iter().collect();
+        ordered.sort_by_key(|event| event.occurred_at());
+        let first = ordered.first()?.occurred_at().into_datetime();
+
+        let mut detail = Self {
+            session_id: session_id.clone(),
+            user_id: None,
+            application_id: None,
+            status: SessionStatus::Active,
+            tags: Vec::new(),
+            metadata: None,
+            started_at: first,
+            last_activity_at: first,
+            ended_at: None,
+            requests: Vec::new(),
+        };
+        let mut index: HashMap<RequestId, usize> = HashMap::new();
+
+        for event in ordered {
+            let at = event.occurred_at().into_datetime();
+            detail.last_activity_at = detail.last_activity_at.max(at);
+
+            let request = match event.request_id() {
+                Some(request_id)
+                    if !matches!(event, DomainEvent::SessionContextRecorded { .. }) =>
+                {
+                    let slot = *index.entry(request_id.clone()).or_insert_with(|| {
+                        detail
+                            .requests
+                            .push(RequestDetail::new(request_id.clone(), at));
+                        detail.requests.len() - 1
+                    });
+                    detail.requests.get_mut(slot)
+                }
+                _ => None,
+            };
+
+            match (event, request) {
+                (
+                    DomainEvent::SessionStarted {
+                        user_id,
+                        application_id,
+                        started_at,
+                        ..
+                    },
+                    _,
+                ) => {
+                    detail.user_id = Some(user_id.clone());
+                    detail.application_id = Some(application_id.clone());
+                    detail.started_at = detail.started_at.min(started_at.into_datetime());
+                }
+                (
+                    DomainEvent::SessionEnded {
+                        final_status,
+                        ended_at,
+                        ..
+                    },
+                    _,
+                ) => {
+                    detail.status = final_status.clone();
+                    detail.ended_at = Some(ended_at.into_datetime());
+                }
+                (DomainEvent::SessionTagged { tag, .. }, _) => {
+                    if !detail.tags.contains(tag) {
+                        detail.tags.push(tag.clone());
+                    }
+                }
+                (DomainEvent::SessionContextRecorded { metadata, .. }, _) => {
+                    if detail.application_id.is_none() {
+                        detail.application_id = metadata.application_id().cloned();
+                    }
+                    for tag in metadata.tags() {
+                        if !detail.tags.contains(tag) {
+                            detail.tags.push(tag.clone());
+                        }
+                    }
+                    detail.metadata = Some(metadata.clone());
+                }
+                (
+                    DomainEvent::LlmRequestReceived {
+                        model_version,
+                        prompt,
+                        parameters,
+                        ..
+                    },
+                    Some(request),
+                ) => {
+                    request.model_version = Some(model_version.clone());
+                    request.prompt = Some(prompt.clone());
+                    request.parameters = Some(parameters.clone());
+                }
+                (DomainEvent::LlmRequestStarted { started_at, .. }, Some(request)) => {
+                    request.started_at = Some(started_at.into_datetime());
+                    if request.status == RequestStatus::Pending {
+                        request.status = RequestStatus::InProgress;
+                    }
+                }
+                (
+                    DomainEvent::LlmResponseReceived {
+                        response_text,
+                        metadata,
+                        received_at,
+                        ..
+                    },
+                    Some(request),
+                ) => {
+                    request.status = RequestStatus::Completed;
+                    request.completed_at = Some(received_at.into_datetime());
+                    request.response = Some(ResponseDetail {
+                        response_text: response_text.clone(),
+                        metadata: metadata.clone(),
+                        received_at: received_at.into_datetime(),
+                    });
+                }
+                (
+                    DomainEvent::LlmRequestBodyCaptured {
+                        body, truncated, ..
+                    },
+                    Some(request),
+                ) => request.request_body = Some(CapturedBody::new(body, *truncated)),
+                (
+                    DomainEvent::LlmResponseBodyCaptured {
+                        body, truncated, ..
+                    },
+                    Some(request),
+                ) => request.response_body = Some(CapturedBody::new(body, *truncated)),
+                (
+                    DomainEvent::LlmRequestFailed {
+                        error_message,
+                        failed_at,
+                        ..
+                    },
+                    Some(request),
+                ) => {
+                    request.status = RequestStatus::Failed;
+                    request.completed_at = Some(failed_at.into_datetime());
+                    request.errors.push(error_message.clone());
+                }
+                (DomainEvent::LlmRequestCancelled { cancelled_at, .. }, Some(request)) => {
+                    request.status = RequestStatus::Cancelled;
+                    request.completed_at = Some(cancelled_at.into_datetime());
+                }
+                (DomainEvent::LlmRequestParsingFailed { parsing_error, .. }, Some(request)) => {
+                    request.errors.push(parsing_error.clone());
+                }
+                _ => {}
+            }
+        }
+
+        Some(detail)
+    }
+}
+
+/// Read the session's stream and the streams of its requests, then fold them
+pub async fn load_session_detail(
+    event_store: &EventCoreService,
+    session_id: &SessionId,
+) -> Result<Option<SessionDetail>> {
+    let stream = session_stream(session_id)
+        .map_err(|e| crate::Error::application(format!("Invalid session stream: {e}")))?;
+    let mut events: Vec<DomainEvent> = event_store
+        .read_stream::<DomainEvent>(stream)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut request_ids: Vec<RequestId> = Vec::new();
+    for request_id in events.iter().filter_map(DomainEvent::request_id) {
+        if !request_ids.contains(request_id) {
+            request_ids.push(request_id.clone());
+        }
+    }
+
+    for request_id in &request_ids {
+        let stream = request_stream(request_id)
+            .map_err(|e| crate::Error::application(format!("Invalid request stream: {e}")))?;
+        events.extend(event_store.read_stream::<DomainEvent>(stream).await?);
+    }
+
+    Ok(SessionDetail::from_events(session_id, &events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::llm::LlmProvider;
+    use crate::domain::metrics::Timestamp;
+    use crate::domain::types::ModelId;
+    use eventcore_types::{EventStore, StreamVersion, StreamWrites};
+
+    fn at(offset_ms: i64) -> Timestamp {
+        let base = DateTime::from_timestamp_millis(1_760_000_000_000).unwrap();
+        Timestamp::try_new(base + chrono::Duration::milliseconds(offset_ms)).unwrap()
+    }
+
+    fn received(session_id: &SessionId, request_id: &RequestId, ms: i64) -> DomainEvent {
+        DomainEvent::LlmRequestReceived {
+            stream_id: session_stream(session_id).unwrap(),
+            request_id: request_id.clone(),
+            session_id: session_id.clone(),
+            model_version: ModelVersion {
+                provider: LlmProvider::OpenAI,
+                model_id: ModelId::try_new("gpt-4o".to_string()).unwrap(),
+            },
+            prompt: Prompt::try_new("Hello".to_string()).unwrap(),
+            parameters: LlmParameters::new(serde_json::json!({})),
+            received_at: at(ms),
+        }
+    }
+
+    #[test]
+    fn requests_are_ordered_and_carry_their_outcomes() {
+        let session_id = SessionId::generate();
+        let first = RequestId::generate();
+        let second = RequestId::generate();
+        let events = vec![
+            // Outcomes arrive from request streams after the session stream
+            DomainEvent::LlmRequestFailed {
+                stream_id: request_stream(&second).unwrap(),
+                request_id: second.clone(),
+                error_message: ErrorMessage::try_new("rate limited".to_string()).unwrap(),
+                failed_at: at(30),
+            },
+            DomainEvent::LlmResponseReceived {
+                stream_id: request_stream(&first).unwrap(),
+                request_id: first.clone(),
+                response_text: ResponseText::try_new("Hi".to_string()).unwrap(),
+                metadata: ResponseMetadata::new(),
+                received_at: at(10),
+            },
+            received(&session_id, &first, 0),
+            received(&session_id, &second, 20),
+        ];
+
+        let detail = SessionDetail::from_events(&session_id, &events).unwrap();
+
+        assert_eq!(detail.started_at, at(0).into_datetime());
+        assert_eq!(detail.last_activity_at, at(30).into_datetime());
+        let ids: Vec<_> = detail
+            .requests
+            .iter()
+            .map(|r| r.request_id.clone())
+            .collect();
+        assert_eq!(ids, vec![first, second]);
+        assert_eq!(detail.requests[0].status, RequestStatus::Completed);
+        assert!(detail.requests[0].response.is_some());
+        assert_eq!(detail.requests[1].status, RequestStatus::Failed);
+        assert_eq!(detail.requests[1].errors.len(), 1);
+    }
+
+    #[test]
+    fn no_events_means_no_session() {
+        assert_eq!(
+            SessionDetail::from_events(&SessionId::generate(), &[]),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn loads_request_streams_named_by_the_session() {
+        let event_store = EventCoreService::with_memory_store();
+        let session_id = SessionId::generate();
+        let request_id = RequestId::generate();
+        let session = session_stream(&session_id).unwrap();
+        let request = request_stream(&request_id).unwrap();
+
+        let writes = StreamWrites::new()
+            .register_stream(session.clone(), StreamVersion::new(0))
+            .unwrap()
+            .register_stream(request.clone(), StreamVersion::new(0))
+            .unwrap()
+            .append(received(&session_id, &request_id, 0))
+            .unwrap()
+            .append(DomainEvent::LlmRequestStarted {
+                stream_id: request,
+                request_id: request_id.clone(),
+                started_at: at(5),
+            })
+            .unwrap();
+        event_store
+            .memory_store()
+            .unwrap()
+            .append_events(writes)
+            .await
+            .unwrap();
+
+        let detail = load_session_detail(&event_store, &session_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(detail.requests.len(), 1);
+        assert_eq!(detail.requests[0].status, RequestStatus::InProgress);
+        assert!(load_session_detail(&event_store, &SessionId::generate())
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,287 @@
+//! Session listing over the session read model
+//!
+//! Pages through the `sessions` table newest first. Pages are addressed by an
+//! opaque keyset cursor on `(started_at, session_id)`, so sessions created
+//! while a client is paging never shift or repeat entries.
+
+use super::postgres::PostgresReadModel;
+use super::read_model::{request_status_label, session_status_label};
+use crate::domain::llm::RequestStatus;
+use crate::domain::session::{
+    ApplicationId, ClientSessionId, MetadataKey, MetadataValue, SessionId, SessionStatus,
+};
+use crate::domain::types::Tag;
+use crate::Result;
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use nutype::nutype;
+use serde::Serialize;
+use sqlx::{Postgres, QueryBuilder, Row};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Sessions returned per page
+#[nutype(
+    validate(greater = 0, less_or_equal = 200),
+    default = 50,
+    derive(Debug, Clone, Copy, PartialEq, Eq, Default, AsRef, Display)
+)]
+pub struct PageSize(u32);
+
+/// Position after the last session of a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionCursor {
+    pub started_at: DateTime<Utc>,
+    pub session_id: SessionId,
+}
+
+impl SessionCursor {
+    /// Opaque, URL-safe form handed to clients
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{}:{}",
+            self.started_at.timestamp_micros(),
+            self.session_id.as_ref()
+        );
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Parse a cursor produced by [`Self::encode`]
+    pub fn decode(encoded: &str) -> Option<Self> {
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(encoded)
+            .ok()?;
+        let raw = String::from_utf8(bytes).ok()?;
+        let (micros, session_id) = raw.split_once(':')?;
+        Some(Self {
+            started_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            session_id: SessionId::new(session_id.parse().ok()?),
+        })
+    }
+}
+
+/// Filters for listing sessions; unset filters match everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionListQuery {
+    pub application_id: Option<ApplicationId>,
+    pub status: Option<SessionStatus>,
+    /// Sessions started at or after this instant
+    pub started_from: Option<DateTime<Utc>>,
+    /// Sessions started before this instant
+    pub started_to: Option<DateTime<Utc>>,
+    /// Only sessions with (or without) a failed request
+    pub has_errors: Option<bool>,
+    pub tag: Option<Tag>,
+    pub client_session_id: Option<ClientSessionId>,
+    /// Custom metadata entries that must all be present with these values
+    pub metadata: BTreeMap<MetadataKey, MetadataValue>,
+    pub cursor: Option<SessionCursor>,
+    pub limit: PageSize,
+}
+
+/// One row of a session listing
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionSummary {
+    pub session_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub application_id: Option<String>,
+    pub client_session_id: Option<String>,
+    pub status: String,
+    pub tags: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    pub last_activity_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub request_count: i64,
+    pub failed_request_count: i64,
+}
+
+/// A page of sessions, most recently started first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionPage {
+    pub sessions: Vec<SessionSummary>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Source of session listings
+#[async_trait]
+pub trait SessionDirectory: Send + Sync {
+    async fn list_sessions(&self, query: &SessionListQuery) -> Result<SessionPage>;
+}
+
+#[async_trait]
+impl SessionDirectory for PostgresReadModel {
+    async fn list_sessions(&self, query: &SessionListQuery) -> Result<SessionPage> {
+        let limit = *query.limit.as_ref() as usize;
+        let failed = request_status_label(&RequestStatus::Failed);
+
+        let mut sql = QueryBuilder::<Postgres>::new(
+            "SELECT s.session_id, s.user_id, s.application_id, s.client_session_id, s.status,
+                s.tags, s.started_at, s.last_activity_at, s.ended_at,
+                (SELECT COUNT(*) FROM llm_requests r
+                 WHERE r.session_id = s.session_id) AS request_count,
+                (SELECT COUNT(*) FROM llm_requests r
+                 WHERE r.session_id = s.session_id AND r.status = ",
+        );
+        sql.push_bind(failed);
+        sql.push(") AS failed_request_count FROM sessions s WHERE TRUE");
+
+        if let Some(application_id) = &query.application_id {
+            sql.push(" AND s.application_id = ")
+                .push_bind(application_id.to_string());
+        }
+        if let Some(status) = &query.status {
+            sql.push(" AND s.status = ")
+                .push_bind(session_status_label(status));
+        }
+        if let Some(from) = query.started_from {
+            sql.push(" AND s.started_at >= ").push_bind(from);
+        }
+        if let Some(to) = query.started_to {
+            sql.push(" AND s.started_at < ").push_bind(to);
+        }
+        if let Some(has_errors) = query.has_errors {
+            sql.push(if has_errors {
+                " AND EXISTS"
+            } else {
+                " AND NOT EXISTS"
+            });
+            sql.push(
+                " (SELECT 1 FROM llm_requests r WHERE r.session_id = s.session_id AND r.status = ",
+            )
+            .push_bind(failed)
+            .push(")");
+        }
+        if let Some(tag) = &query.tag {
+            sql.push(" AND ")
+                .push_bind(tag.to_string())
+                .push(" = ANY(s.tags)");
+        }
+        if let Some(client_session_id) = &query.client_session_id {
+            sql.push(" AND s.client_session_id = ")
+                .push_bind(client_session_id.to_string());
+        }
+        for (key, value) in &query.metadata {
+            sql.push(" AND s.metadata -> 'custom' ->> ")
+                .push_bind(key.to_string())
+                .push(" = ")
+                .push_bind(value.to_string());
+        }
+        if let Some(cursor) = &query.cursor {
+            sql.push(" AND (s.started_at, s.session_id) < (")
+                .push_bind(cursor.started_at)
+                .push(", ")
+                .push_bind(*cursor.session_id.as_ref())
+                .push(")");
+        }
+        // One extra row tells whether another page follows
+        sql.push(" ORDER BY s.started_at DESC, s.session_id DESC LIMIT ")
+            .push_bind(limit as i64 + 1);
+
+        let rows = sql.build().fetch_all(self.pool()).await?;
+        let mut sessions = rows
+            .iter()
+            .map(|row| {
+                Ok(SessionSummary {
+                    session_id: row.try_get("session_id")?,
+                    user_id: row.try_get("user_id")?,
+                    application_id: row.try_get("application_id")?,
+                    client_session_id: row.try_get("client_session_id")?,
+                    status: row.try_get("status")?,
+                    tags: row.try_get("tags")?,
+                    started_at: row.try_get("started_at")?,
+                    last_activity_at: row.try_get("last_activity_at")?,
+                    ended_at: row.try_get("ended_at")?,
+                    request_count: row.try_get("request_count")?,
+                    failed_request_count: row.try_get("failed_request_count")?,
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, sqlx::Error>>()?;
+
+        let next_cursor = if sessions.len() > limit {
+            sessions.truncate(limit);
+            sessions.last().map(|last| {
+                SessionCursor {
+                    started_at: last.started_at,
+                    session_id: SessionId::new(last.session_id),
+                }
+                .encode()
+            })
+        } else {
+            None
+        };
+
+        Ok(SessionPage {
+            sessions,
+            next_cursor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::metrics::Timestamp;
+    use crate::infrastructure::eventcore::projections::{ReadModelStore, ReadModelUpdate};
+    use eventcore_types::StreamPosition;
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = SessionCursor {
+            started_at: DateTime::from_timestamp_micros(1_760_000_000_123_456).unwrap(),
+            session_id: SessionId::generate(),
+        };
+
+        assert_eq!(SessionCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(SessionCursor::decode("not a cursor"), None);
+    }
+
+    #[test]
+    fn page_size_defaults_and_is_bounded() {
+        assert_eq!(*PageSize::default().as_ref(), 50);
+        assert!(PageSize::try_new(0).is_err());
+        assert!(PageSize::try_new(201).is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database connection"]
+    async fn lists_sessions_newest_first_across_pages() {
+        let settings = crate::config::Settings::new().unwrap();
+        let pool = sqlx::PgPool::connect(&settings.database_url())
+            .await
+            .unwrap();
+        let read_model = PostgresReadModel::new(pool);
+        read_model.ensure_schema().await.unwrap();
+
+        let application_id = ApplicationId::try_new(format!("list-{}", Uuid::now_v7())).unwrap();
+        let mut updates = Vec::new();
+        for _ in 0..3 {
+            let session_id = SessionId::generate();
+            updates.push(ReadModelUpdate::SessionStarted {
+                session_id,
+                user_id: crate::domain::user::UserId::generate(),
+                application_id: application_id.clone(),
+                started_at: Timestamp::now(),
+            });
+        }
+        read_model
+            .apply(&updates, StreamPosition::new(Uuid::now_v7()))
+            .await
+            .unwrap();
+
+        let mut query = SessionListQuery {
+            application_id: Some(application_id),
+            limit: PageSize::try_new(2).unwrap(),
+            ..SessionListQuery::default()
+        };
+        let first = read_model.list_sessions(&query).await.unwrap();
+        assert_eq!(first.sessions.len(), 2);
+        assert!(first.sessions[0].started_at >= first.sessions[1].started_at);
+
+        query.cursor = first.next_cursor.as_deref().and_then(SessionCursor::decode);
+        let second = read_model.list_sessions(&query).await.unwrap();
+        assert_eq!(second.sessions.len(), 1);
+        assert_eq!(second.next_cursor, None);
+    }
+}
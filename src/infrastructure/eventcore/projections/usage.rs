@@ -0,0 +1,1052 @@
+//! Hourly usage rollup
+//!
+//! `usage_hourly` holds one row per UTC hour and (application, environment,
+//! provider, model) with request and error counts, token and cost sums and a
+//! latency histogram. The session projection keeps it exact: before applying
+//! a page it loads what each request the page touches contributes, and after
+//! applying it writes the difference. Facts that arrive late, such as a model
+//! parsed after the response, move a request between rows instead of
+//! counting it twice.
+//!
+//! Queries add the hourly rows up into coarser buckets and groups, and read
+//! latency percentiles from the merged histograms.
+
+use super::postgres::PostgresReadModel;
+use super::read_model::{request_status_label, ReadModelUpdate};
+use crate::domain::config_types::ProviderName;
+use crate::domain::llm::RequestStatus;
+use crate::domain::session::{ApplicationId, EnvironmentId};
+use crate::domain::types::ModelId;
+use crate::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, DurationRound, TimeDelta, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{Postgres, QueryBuilder, Row, Transaction};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds
+///
+/// A last bucket takes everything slower.
+pub const LATENCY_BOUNDS_MS: [u64; 12] = [
+    25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000, 60_000, 120_000,
+];
+
+/// Number of latency histogram buckets
+pub const LATENCY_BUCKETS: usize = LATENCY_BOUNDS_MS.len() + 1;
+
+/// Most buckets a query may return
+pub const MAX_BUCKETS: i64 = 2_000;
+
+/// Value stored for a dimension that is not known
+const UNKNOWN: &str = "";
+
+/// Request counts by latency bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyHistogram([i64; LATENCY_BUCKETS]);
+
+impl LatencyHistogram {
+    /// A histogram of one request
+    pub fn of(latency_ms: u64) -> Self {
+        let bucket = LATENCY_BOUNDS_MS.partition_point(|bound| *bound < latency_ms);
+        let mut counts = [0; LATENCY_BUCKETS];
+        counts[bucket] = 1;
+        Self(counts)
+    }
+
+    /// The histogram with these counts, if there is one per bucket
+    pub fn from_counts(counts: &[i64]) -> Option<Self> {
+        counts.try_into().ok().map(Self)
+    }
+
+    pub fn counts(&self) -> &[i64] {
+        &self.0
+    }
+
+    /// Number of requests in the histogram
+    pub fn count(&self) -> i64 {
+        self.0.iter().sum()
+    }
+
+    /// Estimate the latency below which `quantile` of the requests fall
+    ///
+    /// Interpolates linearly within the bucket holding the quantile. Requests
+    /// slower than the last bound are estimated at that bound. `None` for an
+    /// empty histogram.
+    pub fn percentile(&self, quantile: f64) -> Option<f64> {
+        let total = self.count();
+        if total <= 0 {
+            return None;
+        }
+        let rank = quantile.clamp(0.0, 1.0) * total as f64;
+        let mut below = 0;
+        for (bucket, &count) in self.0.iter().enumerate() {
+            if count <= 0 || ((below + count) as f64) < rank {
+                below += count;
+                continue;
+            }
+            let lower = match bucket {
+                0 => 0,
+                _ => LATENCY_BOUNDS_MS[bucket - 1],
+            } as f64;
+            let Some(&upper) = LATENCY_BOUNDS_MS.get(bucket) else {
+                return Some(lower);
+            };
+            let fraction = (rank - below as f64) / count as f64;
+            return Some(lower + (upper as f64 - lower) * fraction);
+        }
+        LATENCY_BOUNDS_MS.last().map(|bound| *bound as f64)
+    }
+
+    fn add(&mut self, other: &Self, sign: i64) {
+        for (count, other) in self.0.iter_mut().zip(other.0) {
+            *count += sign * other;
+        }
+    }
+}
+
+/// The hour and dimensions a request is counted under
+///
+/// `None` for a dimension the read model does not know, such as the model of
+/// a request whose body was never parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UsageKey {
+    pub hour: DateTime<Utc>,
+    pub application_id: Option<String>,
+    pub environment_id: Option<String>,
+    pub provider: Option<String>,
+    pub model_id: Option<String>,
+}
+
+impl UsageKey {
+    fn dimension(&self, dimension: UsageDimension) -> Option<String> {
+        match dimension {
+            UsageDimension::Application => self.application_id.clone(),
+            UsageDimension::Environment => self.environment_id.clone(),
+            UsageDimension::Provider => self.provider.clone(),
+            UsageDimension::Model => self.model_id.clone(),
+        }
+    }
+}
+
+/// Counts and sums of a set of requests
+///
+/// Tokens and cost come from priced responses only, since usage is recorded
+/// when it is priced. Latency covers completed requests.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UsageTotals {
+    pub requests: i64,
+    pub errors: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub cost_usd: Decimal,
+    pub latency: LatencyHistogram,
+}
+
+impl UsageTotals {
+    /// Add `other` to these totals
+    pub fn add(&mut self, other: &Self) {
+        self.combine(other, 1);
+    }
+
+    /// Take `other` out of these totals
+    pub fn subtract(&mut self, other: &Self) {
+        self.combine(other, -1);
+    }
+
+    /// Whether these totals change nothing when added
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn combine(&mut self, other: &Self, sign: i64) {
+        self.requests += sign * other.requests;
+        self.errors += sign * other.errors;
+        self.input_tokens += sign * other.input_tokens;
+        self.output_tokens += sign * other.output_tokens;
+        self.cache_read_tokens += sign * other.cache_read_tokens;
+        self.cache_write_tokens += sign * other.cache_write_tokens;
+        self.cost_usd += Decimal::from(sign) * other.cost_usd;
+        self.latency.add(&other.latency, sign);
+    }
+}
+
+/// What one request contributes to the rollup
+pub type RequestUsage = (UsageKey, UsageTotals);
+
+/// Changes to make to the rollup when requests' contributions go from
+/// `before` to `after`, ordered by key
+pub fn usage_changes(before: &[RequestUsage], after: &[RequestUsage]) -> Vec<RequestUsage> {
+    let mut changes: HashMap<&UsageKey, UsageTotals> = HashMap::new();
+    for (key, totals) in before {
+        changes.entry(key).or_default().subtract(totals);
+    }
+    for (key, totals) in after {
+        changes.entry(key).or_default().add(totals);
+    }
+    let mut changes: Vec<RequestUsage> = changes
+        .into_iter()
+        .filter(|(_, totals)| !totals.is_empty())
+        .map(|(key, totals)| (key.clone(), totals))
+        .collect();
+    changes.sort_by(|(a, _), (b, _)| a.cmp(b));
+    changes
+}
+
+/// The UTC hour `at` falls in
+pub fn hour_of(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.duration_trunc(TimeDelta::hours(1)).unwrap_or(at)
+}
+
+/// A session whose application or environment may change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionKeyChange {
+    pub session_id: Uuid,
+    pub application_id: Option<String>,
+    pub environment_id: Option<String>,
+}
+
+/// Requests and sessions whose rollup contribution a page of updates may change
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageScope {
+    pub requests: Vec<Uuid>,
+    pub sessions: Vec<SessionKeyChange>,
+}
+
+impl UsageScope {
+    /// What `updates` may change; session activity, tags and request starts
+    /// or cancellations never do
+    pub fn of(updates: &[ReadModelUpdate]) -> Self {
+        use ReadModelUpdate as U;
+
+        let mut scope = Self::default();
+        for update in updates {
+            match update {
+                U::RequestDeferred { request_id, .. }
+                | U::RequestReceived { request_id, .. }
+                | U::ResponseReceived { request_id, .. }
+                | U::RequestFailed { request_id, .. }
+                | U::CostRecorded { request_id, .. } => scope.requests.push(*request_id.as_ref()),
+                U::SessionStarted {
+                    session_id,
+                    application_id,
+                    ..
+                } => scope.sessions.push(SessionKeyChange {
+                    session_id: *session_id.as_ref(),
+                    application_id: Some(application_id.to_string()),
+                    environment_id: None,
+                }),
+                U::SessionContextRecorded {
+                    session_id,
+                    metadata,
+                } => {
+                    let application_id = metadata.application_id().map(|id| id.to_string());
+                    let environment_id = metadata.environment_id().map(|id| id.to_string());
+                    if application_id.is_some() || environment_id.is_some() {
+                        scope.sessions.push(SessionKeyChange {
+                            session_id: *session_id.as_ref(),
+                            application_id,
+                            environment_id,
+                        });
+                    }
+                }
+                U::SessionActivity { .. }
+                | U::SessionEnded { .. }
+                | U::SessionTagged { .. }
+                | U::RequestStarted { .. }
+                | U::RequestCancelled { .. } => {}
+            }
+        }
+        scope.requests.sort_unstable();
+        scope.requests.dedup();
+        scope
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty() && self.sessions.is_empty()
+    }
+}
+
+/// Dimension usage can be grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageDimension {
+    Application,
+    Environment,
+    Provider,
+    Model,
+}
+
+impl UsageDimension {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "application" => Some(Self::Application),
+            "environment" => Some(Self::Environment),
+            "provider" => Some(Self::Provider),
+            "model" => Some(Self::Model),
+            _ => None,
+        }
+    }
+}
+
+/// Width of the time buckets usage is reported in, aligned to UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageBucket {
+    #[default]
+    Hour,
+    Day,
+    /// Weeks start on Monday
+    Week,
+}
+
+impl UsageBucket {
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "hour" => Some(Self::Hour),
+            "day" => Some(Self::Day),
+            "week" => Some(Self::Week),
+            _ => None,
+        }
+    }
+
+    pub fn width(&self) -> TimeDelta {
+        match self {
+            Self::Hour => TimeDelta::hours(1),
+            Self::Day => TimeDelta::days(1),
+            Self::Week => TimeDelta::weeks(1),
+        }
+    }
+
+    /// Start of the bucket `at` falls in
+    pub fn start_of(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::Hour => hour_of(at),
+            Self::Day => at.duration_trunc(TimeDelta::days(1)).unwrap_or(at),
+            Self::Week => {
+                let day = at.duration_trunc(TimeDelta::days(1)).unwrap_or(at);
+                day - TimeDelta::days(i64::from(day.weekday().num_days_from_monday()))
+            }
+        }
+    }
+}
+
+/// Usage over a time range, bucketed and grouped
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageQuery {
+    /// Hours ending after this instant are included
+    pub from: DateTime<Utc>,
+    /// Hours starting before this instant are included
+    pub to: DateTime<Utc>,
+    pub bucket: UsageBucket,
+    pub group_by: Vec<UsageDimension>,
+    pub application_id: Option<ApplicationId>,
+    pub environment_id: Option<EnvironmentId>,
+    pub provider: Option<ProviderName>,
+    pub model_id: Option<ModelId>,
+}
+
+impl UsageQuery {
+    pub fn new(from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        Self {
+            from,
+            to,
+            bucket: UsageBucket::default(),
+            group_by: Vec::new(),
+            application_id: None,
+            environment_id: None,
+            provider: None,
+            model_id: None,
+        }
+    }
+
+    /// Number of buckets the range spans
+    pub fn bucket_count(&self) -> i64 {
+        let first = self.bucket.start_of(self.from);
+        let span = self.to - first;
+        (span.num_seconds() + self.bucket.width().num_seconds() - 1)
+            / self.bucket.width().num_seconds()
+    }
+}
+
+/// Latency percentiles in milliseconds, estimated from the histogram
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LatencyPercentiles {
+    pub p50: Option<f64>,
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+}
+
+impl From<&LatencyHistogram> for LatencyPercentiles {
+    fn from(histogram: &LatencyHistogram) -> Self {
+        Self {
+            p50: histogram.percentile(0.50),
+            p95: histogram.percentile(0.95),
+            p99: histogram.percentile(0.99),
+        }
+    }
+}
+
+/// Usage of one group in one bucket
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsagePoint {
+    pub start: DateTime<Utc>,
+    /// Value of each grouped dimension; `null` when not known
+    pub group: BTreeMap<UsageDimension, Option<String>>,
+    pub requests: i64,
+    pub errors: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub cost_usd: Decimal,
+    pub latency_ms: LatencyPercentiles,
+}
+
+/// Points ordered by bucket, then group; buckets without requests are left out
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageReport {
+    pub bucket: UsageBucket,
+    pub points: Vec<UsagePoint>,
+}
+
+/// Add hourly `rows` up into the buckets and groups of `query`
+pub fn summarize(rows: &[RequestUsage], query: &UsageQuery) -> UsageReport {
+    let mut groups: BTreeMap<(DateTime<Utc>, Vec<Option<String>>), UsageTotals> = BTreeMap::new();
+    for (key, totals) in rows {
+        let group = query
+            .group_by
+            .iter()
+            .map(|dimension| key.dimension(*dimension))
+            .collect();
+        groups
+            .entry((query.bucket.start_of(key.hour), group))
+            .or_default()
+            .add(totals);
+    }
+
+    let points = groups
+        .into_iter()
+        .map(|((start, group), totals)| UsagePoint {
+            start,
+            group: query.group_by.iter().copied().zip(group).collect(),
+            requests: totals.requests,
+            errors: totals.errors,
+            input_tokens: totals.input_tokens,
+            output_tokens: totals.output_tokens,
+            cache_read_tokens: totals.cache_read_tokens,
+            cache_write_tokens: totals.cache_write_tokens,
+            cost_usd: totals.cost_usd.normalize(),
+            latency_ms: LatencyPercentiles::from(&totals.latency),
+        })
+        .collect();
+    UsageReport {
+        bucket: query.bucket,
+        points,
+    }
+}
+
+/// Source of usage reports
+#[async_trait]
+pub trait UsageRollup: Send + Sync {
+    async fn usage(&self, query: &UsageQuery) -> Result<UsageReport>;
+}
+
+#[async_trait]
+impl UsageRollup for PostgresReadModel {
+    async fn usage(&self, query: &UsageQuery) -> Result<UsageReport> {
+        let mut sql = QueryBuilder::<Postgres>::new(
+            "SELECT hour, application_id, environment_id, provider, model_id,
+                request_count, error_count, input_tokens, output_tokens,
+                cache_read_tokens, cache_write_tokens, cost_usd::text AS cost_usd,
+                latency_histogram
+             FROM usage_hourly WHERE hour > ",
+        );
+        sql.push_bind(query.from - TimeDelta::hours(1))
+            .push(" AND hour < ")
+            .push_bind(query.to);
+        let filters = [
+            (
+                "application_id",
+                query.application_id.as_ref().map(|id| id.to_string()),
+            ),
+            (
+                "environment_id",
+                query.environment_id.as_ref().map(|id| id.to_string()),
+            ),
+            (
+                "provider",
+                query.provider.as_ref().map(|name| name.to_string()),
+            ),
+            ("model_id", query.model_id.as_ref().map(|id| id.to_string())),
+        ];
+        for (column, value) in filters {
+            if let Some(value) = value {
+                sql.push(format_args!(" AND {column} = ")).push_bind(value);
+            }
+        }
+
+        let rows = sql.build().fetch_all(self.pool()).await?;
+        let rows = rows
+            .iter()
+            .map(|row| {
+                let key = UsageKey {
+                    hour: row.try_get("hour")?,
+                    application_id: known(row.try_get("application_id")?),
+                    environment_id: known(row.try_get("environment_id")?),
+                    provider: known(row.try_get("provider")?),
+                    model_id: known(row.try_get("model_id")?),
+                };
+                let histogram: Vec<i64> = row.try_get("latency_histogram")?;
+                let totals = UsageTotals {
+                    requests: row.try_get("request_count")?,
+                    errors: row.try_get("error_count")?,
+                    input_tokens: row.try_get("input_tokens")?,
+                    output_tokens: row.try_get("output_tokens")?,
+                    cache_read_tokens: row.try_get("cache_read_tokens")?,
+                    cache_write_tokens: row.try_get("cache_write_tokens")?,
+                    cost_usd: decimal(row.try_get("cost_usd")?)?,
+                    latency: LatencyHistogram::from_counts(&histogram).unwrap_or_default(),
+                };
+                Ok((key, totals))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(summarize(&rows, query))
+    }
+}
+
+/// What `requests`, and every request in `sessions`, contribute to the rollup right now
+pub(super) async fn load_request_usage(
+    tx: &mut Transaction<'_, Postgres>,
+    requests: &[Uuid],
+    sessions: &[Uuid],
+) -> Result<Vec<RequestUsage>> {
+    if requests.is_empty() && sessions.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let rows = sqlx::query(
+        "SELECT r.received_at, s.application_id, s.environment_id, r.provider, r.model_id,
+            r.status, r.input_tokens, r.output_tokens, r.cache_read_tokens,
+            r.cache_write_tokens, r.cost_usd::text AS cost_usd, p.latency_ms
+         FROM llm_requests r
+         LEFT JOIN sessions s ON s.session_id = r.session_id
+         LEFT JOIN llm_responses p ON p.request_id = r.request_id
+         WHERE r.request_id = ANY($1) OR r.session_id = ANY($2)",
+    )
+    .bind(requests)
+    .bind(sessions)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let status: String = row.try_get("status")?;
+            let cost: Option<String> = row.try_get("cost_usd")?;
+            let latency_ms: Option<i64> = row.try_get("latency_ms")?;
+            let tokens = |column: &str| -> Result<i64> {
+                Ok(row.try_get::<Option<i64>, _>(column)?.unwrap_or(0))
+            };
+            let key = UsageKey {
+                hour: hour_of(row.try_get("received_at")?),
+                application_id: row.try_get("application_id")?,
+                environment_id: row.try_get("environment_id")?,
+                provider: row.try_get("provider")?,
+                model_id: row.try_get("model_id")?,
+            };
+            let totals = UsageTotals {
+                requests: 1,
+                errors: i64::from(status == request_status_label(&RequestStatus::Failed)),
+                input_tokens: tokens("input_tokens")?,
+                output_tokens: tokens("output_tokens")?,
+                cache_read_tokens: tokens("cache_read_tokens")?,
+                cache_write_tokens: tokens("cache_write_tokens")?,
+                cost_usd: cost.map(decimal).transpose()?.unwrap_or_default(),
+                latency: latency_ms
+                    .and_then(|latency| u64::try_from(latency).ok())
+                    .map(LatencyHistogram::of)
+                    .unwrap_or_default(),
+            };
+            Ok((key, totals))
+        })
+        .collect()
+}
+
+/// Write `changes` to `usage_hourly`, dropping rows left without requests
+pub(super) async fn apply_usage_changes(
+    tx: &mut Transaction<'_, Postgres>,
+    changes: &[RequestUsage],
+) -> Result<()> {
+    for (key, totals) in changes {
+        sqlx::query(
+            "INSERT INTO usage_hourly
+                (hour, application_id, environment_id, provider, model_id,
+                 request_count, error_count, input_tokens, output_tokens,
+                 cache_read_tokens, cache_write_tokens, cost_usd, latency_histogram)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12::numeric, $13)
+             ON CONFLICT (hour, application_id, environment_id, provider, model_id)
+             DO UPDATE SET
+                request_count = usage_hourly.request_count + EXCLUDED.request_count,
+                error_count = usage_hourly.error_count + EXCLUDED.error_count,
+                input_tokens = usage_hourly.input_tokens + EXCLUDED.input_tokens,
+                output_tokens = usage_hourly.output_tokens + EXCLUDED.output_tokens,
+                cache_read_tokens = usage_hourly.cache_read_tokens + EXCLUDED.cache_read_tokens,
+                cache_write_tokens =
+                    usage_hourly.cache_write_tokens + EXCLUDED.cache_write_tokens,
+                cost_usd = usage_hourly.cost_usd + EXCLUDED.cost_usd,
+                latency_histogram = ARRAY(
+                    SELECT current + change
+                    FROM unnest(usage_hourly.latency_histogram, EXCLUDED.latency_histogram)
+                        WITH ORDINALITY AS buckets(current, change, position)
+                    ORDER BY position
+                )",
+        )
+        .bind(key.hour)
+        .bind(stored(&key.application_id))
+        .bind(stored(&key.environment_id))
+        .bind(stored(&key.provider))
+        .bind(stored(&key.model_id))
+        .bind(totals.requests)
+        .bind(totals.errors)
+        .bind(totals.input_tokens)
+        .bind(totals.output_tokens)
+        .bind(totals.cache_read_tokens)
+        .bind(totals.cache_write_tokens)
+        .bind(totals.cost_usd.to_string())
+        .bind(totals.latency.counts())
+        .execute(&mut **tx)
+        .await?;
+
+        if totals.requests < 0 {
+            sqlx::query(
+                "DELETE FROM usage_hourly
+                 WHERE hour = $1 AND application_id = $2 AND environment_id = $3
+                    AND provider = $4 AND model_id = $5 AND request_count = 0",
+            )
+            .bind(key.hour)
+            .bind(stored(&key.application_id))
+            .bind(stored(&key.environment_id))
+            .bind(stored(&key.provider))
+            .bind(stored(&key.model_id))
+            .execute(&mut **tx)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Sessions in `changes` whose application or environment is about to change
+///
+/// Session context is recorded with every request, so most of it repeats
+/// what the session already has; only real changes move the session's
+/// requests between rows. Resolve them before the changes are applied.
+pub(super) async fn changing_sessions(
+    tx: &mut Transaction<'_, Postgres>,
+    changes: &[SessionKeyChange],
+) -> Result<Vec<Uuid>> {
+    if changes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let session_ids: Vec<Uuid> = changes.iter().map(|change| change.session_id).collect();
+    let rows = sqlx::query(
+        "SELECT session_id, application_id, environment_id FROM sessions
+         WHERE session_id = ANY($1)",
+    )
+    .bind(&session_ids)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let mut current = HashMap::new();
+    for row in &rows {
+        let session_id: Uuid = row.try_get("session_id")?;
+        let application_id: Option<String> = row.try_get("application_id")?;
+        let environment_id: Option<String> = row.try_get("environment_id")?;
+        current.insert(session_id, (application_id, environment_id));
+    }
+
+    let mut changing: Vec<Uuid> = changes
+        .iter()
+        .filter(|change| {
+            current
+                .get(&change.session_id)
+                .is_some_and(|(application_id, environment_id)| {
+                    differs(&change.application_id, application_id)
+                        || differs(&change.environment_id, environment_id)
+                })
+        })
+        .map(|change| change.session_id)
+        .collect();
+    changing.sort_unstable();
+    changing.dedup();
+    Ok(changing)
+}
+
+/// Whether setting `new`, when given, changes `current`
+fn differs(new: &Option<String>, current: &Option<String>) -> bool {
+    new.is_some() && new != current
+}
+
+fn stored(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or(UNKNOWN)
+}
+
+fn known(value: String) -> Option<String> {
+    (value != UNKNOWN).then_some(value)
+}
+
+fn decimal(value: String) -> Result<Decimal> {
+    Decimal::from_str(&value)
+        .map_err(|e| crate::Error::application(format!("Invalid amount {value}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::llm::RequestId;
+    use crate::domain::metrics::Timestamp;
+    use crate::domain::session::{SessionId, SessionMetadata};
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn key(hour: DateTime<Utc>, application: &str, model: Option<&str>) -> UsageKey {
+        UsageKey {
+            hour,
+            application_id: Some(application.to_string()),
+            environment_id: Some("production".to_string()),
+            provider: Some("openai".to_string()),
+            model_id: model.map(str::to_string),
+        }
+    }
+
+    fn request(cost: &str, latency_ms: u64, failed: bool) -> UsageTotals {
+        UsageTotals {
+            requests: 1,
+            errors: i64::from(failed),
+            input_tokens: 100,
+            output_tokens: 20,
+            cost_usd: Decimal::from_str(cost).unwrap(),
+            latency: LatencyHistogram::of(latency_ms),
+            ..UsageTotals::default()
+        }
+    }
+
+    #[test]
+    fn histogram_places_latencies_by_upper_bound() {
+        assert_eq!(LatencyHistogram::of(0).counts()[0], 1);
+        assert_eq!(LatencyHistogram::of(25).counts()[0], 1);
+        assert_eq!(LatencyHistogram::of(26).counts()[1], 1);
+        assert_eq!(
+            LatencyHistogram::of(500_000).counts()[LATENCY_BUCKETS - 1],
+            1
+        );
+        assert!(LatencyHistogram::from_counts(&[1, 2]).is_none());
+    }
+
+    #[test]
+    fn percentiles_interpolate_within_buckets() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.percentile(0.5), None);
+
+        // 100 requests between 100ms and 250ms
+        for _ in 0..100 {
+            histogram.add(&LatencyHistogram::of(200), 1);
+        }
+        assert_eq!(histogram.percentile(0.5), Some(175.0));
+        assert_eq!(histogram.percentile(0.99), Some(248.5));
+
+        // Requests past the last bound are estimated at the bound
+        let slow = LatencyHistogram::of(200_000);
+        assert_eq!(slow.percentile(0.5), Some(120_000.0));
+    }
+
+    #[test]
+    fn changes_move_a_request_between_rows() {
+        let hour = at(12, 9, 0);
+        let unparsed = key(hour, "support-bot", None);
+        let parsed = key(hour, "support-bot", Some("gpt-4o"));
+        let totals = request("0.0035", 300, false);
+
+        let changes = usage_changes(
+            &[(unparsed.clone(), totals.clone())],
+            &[(parsed.clone(), totals.clone())],
+        );
+
+        assert_eq!(changes.len(), 2);
+        let mut removed = UsageTotals::default();
+        removed.subtract(&totals);
+        assert!(changes.contains(&(unparsed, removed)));
+        assert!(changes.contains(&(parsed, totals)));
+    }
+
+    #[test]
+    fn unchanged_requests_produce_no_changes() {
+        let row = (
+            key(at(12, 9, 0), "support-bot", Some("gpt-4o")),
+            request("0.01", 80, false),
+        );
+        let rows = [row];
+        assert!(usage_changes(&rows, &rows).is_empty());
+    }
+
+    #[test]
+    fn scope_covers_request_updates_and_session_context() {
+        let request_id = RequestId::generate();
+        let session_id = SessionId::generate();
+        let updates = [
+            ReadModelUpdate::SessionActivity {
+                session_id: session_id.clone(),
+                at: Timestamp::now(),
+            },
+            ReadModelUpdate::RequestDeferred {
+                request_id: request_id.clone(),
+                session_id: session_id.clone(),
+                received_at: Timestamp::now(),
+            },
+            ReadModelUpdate::RequestStarted {
+                request_id: request_id.clone(),
+                started_at: Timestamp::now(),
+            },
+            ReadModelUpdate::RequestFailed {
+                request_id: request_id.clone(),
+                error_message: crate::domain::types::ErrorMessage::try_new("boom".to_string())
+                    .unwrap(),
+                failed_at: Timestamp::now(),
+            },
+            ReadModelUpdate::SessionContextRecorded {
+                session_id: session_id.clone(),
+                metadata: SessionMetadata::new()
+                    .with_environment_id(EnvironmentId::try_new("staging".to_string()).unwrap()),
+            },
+            // Context without an application or environment changes no key
+            ReadModelUpdate::SessionContextRecorded {
+                session_id: SessionId::generate(),
+                metadata: SessionMetadata::new(),
+            },
+        ];
+
+        let scope = UsageScope::of(&updates);
+
+        assert_eq!(scope.requests, vec![*request_id.as_ref()]);
+        assert_eq!(
+            scope.sessions,
+            vec![SessionKeyChange {
+                session_id: *session_id.as_ref(),
+                application_id: None,
+                environment_id: Some("staging".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn buckets_align_to_utc_hours_days_and_weeks() {
+        // 2026-10-15 is a Thursday
+        let time = at(15, 13, 45);
+        assert_eq!(UsageBucket::Hour.start_of(time), at(15, 13, 0));
+        assert_eq!(UsageBucket::Day.start_of(time), at(15, 0, 0));
+        assert_eq!(UsageBucket::Week.start_of(time), at(12, 0, 0));
+
+        let query = UsageQuery::new(at(12, 0, 30), at(13, 0, 0));
+        assert_eq!(query.bucket_count(), 24);
+    }
+
+    #[test]
+    fn summarize_adds_hours_into_buckets_and_groups() {
+        let rows = vec![
+            (
+                key(at(12, 9, 0), "support-bot", Some("gpt-4o")),
+                request("0.50", 300, false),
+            ),
+            (
+                key(at(12, 17, 0), "support-bot", Some("gpt-4o-mini")),
+                request("0.25", 90, true),
+            ),
+            (
+                key(at(12, 18, 0), "search", Some("gpt-4o")),
+                request("1.00", 1_500, false),
+            ),
+            (
+                key(at(13, 1, 0), "support-bot", Some("gpt-4o")),
+                request("2", 300, false),
+            ),
+        ];
+        let query = UsageQuery {
+            bucket: UsageBucket::Day,
+            group_by: vec![UsageDimension::Application],
+            ..UsageQuery::new(at(12, 0, 0), at(14, 0, 0))
+        };
+
+        let report = summarize(&rows, &query);
+
+        let summary: Vec<_> = report
+            .points
+            .iter()
+            .map(|point| {
+                (
+                    point.start,
+                    point.group[&UsageDimension::Application].as_deref(),
+                    point.requests,
+                    point.errors,
+                    point.cost_usd.to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (at(12, 0, 0), Some("search"), 1, 0, "1".to_string()),
+                (at(12, 0, 0), Some("support-bot"), 2, 1, "0.75".to_string()),
+                (at(13, 0, 0), Some("support-bot"), 1, 0, "2".to_string()),
+            ]
+        );
+        assert_eq!(report.points[1].input_tokens, 200);
+        assert!(report.points[1].latency_ms.p50.is_some());
+    }
+
+    #[test]
+    fn ungrouped_reports_have_one_point_per_bucket() {
+        let rows = vec![
+            (
+                key(at(12, 9, 0), "support-bot", Some("gpt-4o")),
+                request("0.50", 300, false),
+            ),
+            (
+                key(at(12, 9, 0), "search", None),
+                request("0.25", 90, false),
+            ),
+        ];
+
+        let report = summarize(&rows, &UsageQuery::new(at(12, 0, 0), at(13, 0, 0)));
+
+        assert_eq!(report.points.len(), 1);
+        assert!(report.points[0].group.is_empty());
+        assert_eq!(report.points[0].requests, 2);
+        assert_eq!(
+            serde_json::to_value(&report.points[0]).unwrap()["cost_usd"],
+            "0.75"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires database connection"]
+    async fn rollup_follows_late_facts_about_a_request() {
+        use crate::domain::llm::{LlmProvider, ModelVersion, ResponseMetadata};
+        use crate::domain::pricing::{TokenUsage, UsdAmount};
+        use crate::domain::types::{Latency, LlmParameters, Prompt, ResponseText, TokenCount};
+        use crate::domain::user::UserId;
+        use crate::infrastructure::eventcore::projections::ReadModelStore;
+        use eventcore_types::StreamPosition;
+
+        let settings = crate::config::Settings::new().unwrap();
+        let pool = sqlx::PgPool::connect(&settings.database_url())
+            .await
+            .unwrap();
+        let read_model = PostgresReadModel::new(pool);
+        read_model.ensure_schema().await.unwrap();
+
+        let application_id = ApplicationId::try_new(format!("usage-{}", Uuid::now_v7())).unwrap();
+        let session_id = SessionId::generate();
+        let request_id = RequestId::generate();
+        let now = Timestamp::now();
+        let model_version = ModelVersion {
+            provider: LlmProvider::OpenAI,
+            model_id: ModelId::try_new("gpt-4o".to_string()).unwrap(),
+        };
+        let apply = |updates: Vec<ReadModelUpdate>| {
+            let read_model = &read_model;
+            async move {
+                read_model
+                    .apply(&updates, StreamPosition::new(Uuid::now_v7()))
+                    .await
+                    .unwrap()
+            }
+        };
+
+        apply(vec![
+            ReadModelUpdate::SessionStarted {
+                session_id: session_id.clone(),
+                user_id: UserId::generate(),
+                application_id: application_id.clone(),
+                started_at: now,
+            },
+            ReadModelUpdate::RequestDeferred {
+                request_id: request_id.clone(),
+                session_id: session_id.clone(),
+                received_at: now,
+            },
+        ])
+        .await;
+        apply(vec![
+            ReadModelUpdate::RequestReceived {
+                request_id: request_id.clone(),
+                session_id: session_id.clone(),
+                model_version: model_version.clone(),
+                prompt: Prompt::try_new("Hello".to_string()).unwrap(),
+                parameters: LlmParameters::new(serde_json::json!({})),
+                received_at: now,
+            },
+            ReadModelUpdate::ResponseReceived {
+                request_id: request_id.clone(),
+                response_text: ResponseText::try_new("Hi".to_string()).unwrap(),
+                metadata: ResponseMetadata::new().with_latency_ms(Latency::try_new(180).unwrap()),
+                received_at: now,
+            },
+            ReadModelUpdate::CostRecorded {
+                request_id: request_id.clone(),
+                session_id: session_id.clone(),
+                application_id: None,
+                environment_id: None,
+                model_version,
+                usage: TokenUsage {
+                    input: TokenCount::try_new(1000).unwrap(),
+                    output: TokenCount::try_new(100).unwrap(),
+                    cache_read: TokenCount::try_new(0).unwrap(),
+                    cache_write: TokenCount::try_new(0).unwrap(),
+                },
+                total: UsdAmount::try_new(Decimal::from_str("0.0035").unwrap()).unwrap(),
+                calculated_at: now,
+            },
+        ])
+        .await;
+        apply(vec![ReadModelUpdate::SessionContextRecorded {
+            session_id: session_id.clone(),
+            metadata: SessionMetadata::new()
+                .with_environment_id(EnvironmentId::try_new("staging".to_string()).unwrap()),
+        }])
+        .await;
+
+        let query = UsageQuery {
+            group_by: vec![UsageDimension::Environment, UsageDimension::Model],
+            application_id: Some(application_id),
+            ..UsageQuery::new(
+                now.into_datetime() - TimeDelta::hours(1),
+                now.into_datetime() + TimeDelta::hours(1),
+            )
+        };
+        let report = read_model.usage(&query).await.unwrap();
+
+        assert_eq!(report.points.len(), 1);
+        let point = &report.points[0];
+        assert_eq!(
+            point.group[&UsageDimension::Environment].as_deref(),
+            Some("staging")
+        );
+        assert_eq!(
+            point.group[&UsageDimension::Model].as_deref(),
+            Some("gpt-4o")
+        );
+        assert_eq!(point.requests, 1);
+        assert_eq!(point.errors, 0);
+        assert_eq!(point.input_tokens, 1000);
+        assert_eq!(point.cost_usd, Decimal::from_str("0.0035").unwrap());
+        assert!(point.latency_ms.p50.is_some());
+    }
+}
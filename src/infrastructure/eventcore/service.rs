@@ -7,11 +7,11 @@ use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::Duration;
 
-use eventcore::{CommandLogic, RetryPolicy};
+use eventcore::{CommandError, CommandLogic, RetryPolicy};
 #[cfg(test)]
 use eventcore_memory::InMemoryEventStore;
 use eventcore_postgres::{MaxConnections, PostgresConfig, PostgresEventStore};
-use eventcore_types::{Event, EventStore};
+use eventcore_types::{Event, EventFilter, EventPage, EventReader, EventStore, StreamPosition};
 
 use super::EventCoreConfig;
 use crate::domain::events::DomainEvent;
@@ -59,6 +59,12 @@ impl EventCoreService {
         }
     }
 
+    /// The in-memory backend, for tests that write events directly
+    #[cfg(test)]
+    pub fn memory_store(&self) -> Option<&Arc<InMemoryEventStore>> {
+        self.memory_store.as_ref()
+    }
+
     /// Execute a command against the configured event store
     pub async fn execute_command<C>(&self, command: C) -> crate::error::Result<()>
     where
@@ -68,14 +74,14 @@ impl EventCoreService {
         if let Some(store) = &self.memory_store {
             eventcore::execute(store.as_ref(), command, RetryPolicy::default())
                 .await
-                .map_err(|e| eventcore_error(e.to_string()))?;
+                .map_err(command_error)?;
             return Ok(());
         }
 
         if let Some(store) = &self.postgres_store {
             eventcore::execute(store.as_ref(), command, RetryPolicy::default())
                 .await
-                .map_err(|e| eventcore_error(e.to_string()))?;
+                .map_err(command_error)?;
             return Ok(());
         }
 
@@ -123,6 +129,43 @@ impl EventCoreService {
     }
 }
 
+impl EventReader for EventCoreService {
+    type Error = Error;
+
+    async fn read_events<E: Event>(
+        &self,
+        filter: EventFilter,
+        page: EventPage,
+    ) -> Result<Vec<(E, StreamPosition)>, Self::Error> {
+        #[cfg(test)]
+        if let Some(store) = &self.memory_store {
+            return store
+                .read_events(filter, page)
+                .await
+                .map_err(|e| eventcore_error(e.to_string()));
+        }
+
+        if let Some(store) = &self.postgres_store {
+            return store
+                .read_events(filter, page)
+                .await
+                .map_err(|e| eventcore_error(e.to_string()));
+        }
+
+        Err(eventcore_error("No event store configured".to_string()))
+    }
+}
+
+/// Separate storage failures, which are worth retrying, from rejected commands
+fn command_error(e: CommandError) -> Error {
+    match e {
+        CommandError::EventStoreError(e) => ErrorMessage::try_new(e.to_string())
+            .map(Error::EventStoreUnavailable)
+            .unwrap_or(Error::Internal),
+        e => eventcore_error(e.to_string()),
+    }
+}
+
 /// Convert a string into an EventCore error variant
 fn eventcore_error(s: String) -> Error {
     // ErrorMessage rejects empty strings; eventcore never emits them.
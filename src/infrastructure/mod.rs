@@ -4,8 +4,10 @@
 //! Currently includes:
 //! - Database access via SQLx
 //! - EventCore integration for event sourcing
+//! - The dead-letter store for audit events that could not be processed
 
 pub mod database;
+pub mod dead_letters;
 pub mod eventcore;
 pub mod log_messages;
 
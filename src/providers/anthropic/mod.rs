@@ -0,0 +1,19 @@
+//! Anthropic provider implementation
+//!
+//! Serves the Anthropic Messages API behind the `/anthropic/` prefix.
+//!
+//! ## Features
+//!
+//! - `x-api-key` and `anthropic-version` pass-through
+//! - Messages, token counting and batch endpoints
+//! - Model, token and prompt cache usage from JSON bodies and the
+//!   `message_start`/`message_delta` stream events
+//!
+//! Because `x-api-key` carries the Anthropic key, clients authenticate to the
+//! proxy with `Authorization: Bearer` on these paths. That header is dropped
+//! before the request is forwarded.
+
+pub mod provider;
+pub mod usage;
+
+pub use provider::AnthropicProvider;
@@ -0,0 +1,220 @@
+//! Anthropic provider implementation
+
+use crate::providers::bedrock::provider::{BaseUrl, PathPrefix};
+use crate::providers::constants::{http::headers, paths};
+use crate::providers::{
+    passthrough::{self, PathPrefixed},
+    HealthStatus, Provider, ProviderError, ProviderId, ProviderMetadata,
+};
+use crate::proxy::http::UpstreamClient;
+use async_trait::async_trait;
+use axum::body::Body;
+use hyper::{header, HeaderMap, Request, Response, Uri};
+
+/// Anthropic Messages API provider
+pub struct AnthropicProvider {
+    base_url: BaseUrl,
+    path_prefix: PathPrefix,
+}
+
+impl AnthropicProvider {
+    /// Create a provider for the public Anthropic API
+    pub fn new() -> Self {
+        let base_url = BaseUrl::try_new(paths::anthropic::DEFAULT_BASE_URL.to_string()).unwrap();
+        Self {
+            base_url,
+            path_prefix: PathPrefix::anthropic(),
+        }
+    }
+
+    /// Create a provider with a custom base URL (for testing or gateways)
+    pub fn with_base_url(base_url: String) -> Result<Self, ProviderError> {
+        Ok(Self {
+            base_url: passthrough::base_url(base_url)?,
+            ..Self::new()
+        })
+    }
+
+    /// Build the target URL, keeping the query string
+    fn build_target_url(&self, uri: &Uri) -> Result<Uri, ProviderError> {
+        let upstream_path = passthrough::strip_prefix(&self.path_prefix, uri)?;
+        passthrough::target_url(self.base_url.as_ref(), upstream_path)
+    }
+}
+
+impl Default for AnthropicProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The Anthropic key must be present; it is forwarded untouched
+fn validate_api_key(request_headers: &HeaderMap) -> Result<(), ProviderError> {
+    if request_headers.contains_key(headers::anthropic::API_KEY) {
+        Ok(())
+    } else {
+        Err(ProviderError::AuthenticationError(format!(
+            "Anthropic requests must include {}",
+            headers::anthropic::API_KEY
+        )))
+    }
+}
+
+impl PathPrefixed for AnthropicProvider {
+    fn path_prefix_mut(&mut self) -> &mut PathPrefix {
+        &mut self.path_prefix
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicProvider {
+    fn id(&self) -> ProviderId {
+        ProviderId::anthropic()
+    }
+
+    fn matches_path(&self, path: &str) -> bool {
+        self.path_prefix.matches(path)
+    }
+
+    fn transform_url(&self, url: &Uri) -> Result<Uri, ProviderError> {
+        self.build_target_url(url)
+    }
+
+    async fn forward_request(
+        &self,
+        request: Request<Body>,
+        client: &UpstreamClient,
+    ) -> Result<Response<Body>, ProviderError> {
+        let (mut parts, body) = request.into_parts();
+
+        validate_api_key(&parts.headers)?;
+
+        let uri = self.transform_url(&parts.uri)?;
+        // Authorization carries the proxy key on these paths, not Anthropic's
+        parts.headers.remove(header::AUTHORIZATION);
+
+        passthrough::forward(client, parts, body, uri).await
+    }
+
+    fn extract_metadata(
+        &self,
+        _request: &Request<Body>,
+        response: &Response<Body>,
+    ) -> ProviderMetadata {
+        // Model and token usage live in the body; see `ProviderResponseProcessor`
+        let provider_request_id =
+            passthrough::provider_request_id(response, headers::anthropic::REQUEST_ID);
+
+        ProviderMetadata {
+            provider_id: self.id(),
+            provider_request_id,
+            ..Default::default()
+        }
+    }
+
+    async fn health_check(&self, _client: &UpstreamClient) -> HealthStatus {
+        passthrough::configured_health()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::http::build_upstream_client;
+    use crate::proxy::types::UpstreamTlsConfig;
+    use mockito::{Matcher, Server};
+
+    #[test]
+    fn test_provider_id() {
+        assert_eq!(AnthropicProvider::new().id(), ProviderId::anthropic());
+    }
+
+    #[test]
+    fn test_matches_path() {
+        let provider = AnthropicProvider::new();
+
+        assert!(provider.matches_path("/anthropic/v1/messages"));
+        assert!(provider.matches_path("/anthropic/v1/messages/count_tokens"));
+        assert!(!provider.matches_path("/openai/v1/chat/completions"));
+        assert!(!provider.matches_path("/bedrock/model/claude-3/invoke"));
+    }
+
+    #[test]
+    fn test_transform_url() {
+        let provider = AnthropicProvider::new();
+
+        let input: Uri = "/anthropic/v1/messages/batches?limit=10".parse().unwrap();
+
+        assert_eq!(
+            provider.transform_url(&input).unwrap().to_string(),
+            "https://api.anthropic.com/v1/messages/batches?limit=10"
+        );
+    }
+
+    #[test]
+    fn test_invalid_base_url_is_rejected() {
+        assert!(matches!(
+            AnthropicProvider::with_base_url("not a url".to_string()),
+            Err(ProviderError::InvalidUrl(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_forwards_anthropic_headers_without_proxy_credentials() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/messages")
+            .match_header("x-api-key", "sk-ant-test")
+            .match_header("anthropic-version", "2023-06-01")
+            .match_header("authorization", Matcher::Missing)
+            .match_header("host", Matcher::Regex(r"^127\.0\.0\.1:\d+$".to_string()))
+            .with_status(200)
+            .with_header("request-id", "req_018EeWyXxfu5pfWkrYcMdjWG")
+            .with_body(r#"{"model":"claude-3-5-haiku-20241022","usage":{"input_tokens":3,"output_tokens":1}}"#)
+            .create_async()
+            .await;
+        let provider = AnthropicProvider::with_base_url(server.url()).unwrap();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/anthropic/v1/messages")
+            .header("host", "proxy.internal:8080")
+            .header("authorization", "Bearer proxy-key")
+            .header("x-api-key", "sk-ant-test")
+            .header("anthropic-version", "2023-06-01")
+            .body(Body::from(
+                r#"{"model":"claude-3-5-haiku-20241022","max_tokens":16,"messages":[]}"#,
+            ))
+            .unwrap();
+        let metadata_request = Request::builder().body(Body::empty()).unwrap();
+
+        let response = provider.forward_request(request, &client).await.unwrap();
+
+        mock.assert_async().await;
+        let metadata = provider.extract_metadata(&metadata_request, &response);
+        assert_eq!(metadata.provider_id, ProviderId::anthropic());
+        assert_eq!(
+            metadata.provider_request_id.map(|id| id.to_string()),
+            Some("req_018EeWyXxfu5pfWkrYcMdjWG".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_request_without_api_key() {
+        let provider = AnthropicProvider::new();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/anthropic/v1/messages")
+            .header("anthropic-version", "2023-06-01")
+            .body(Body::empty())
+            .unwrap();
+
+        assert!(matches!(
+            provider.forward_request(request, &client).await,
+            Err(ProviderError::AuthenticationError(_))
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+//! Model and token usage extraction for Anthropic responses
+//!
+//! A complete message carries its `usage` object directly. Streams report
+//! input and cache tokens on `message_start` (nested under `message`) and the
+//! cumulative output count on `message_delta`.
+
+use crate::providers::bedrock::types::{
+    CacheReadTokens, CacheWriteTokens, InputTokens, ModelId, OutputTokens, TotalTokens,
+};
+use crate::providers::constants::json_fields;
+use crate::providers::sse::sse_events;
+use serde_json::Value;
+
+/// Model and token counts reported by an Anthropic response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageUsage {
+    pub model_id: Option<ModelId>,
+    pub input_tokens: Option<InputTokens>,
+    pub output_tokens: Option<OutputTokens>,
+    pub cache_read_tokens: Option<CacheReadTokens>,
+    pub cache_write_tokens: Option<CacheWriteTokens>,
+}
+
+impl MessageUsage {
+    /// True when nothing could be extracted
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Input plus output tokens, matching how Bedrock reports Claude usage
+    ///
+    /// Cache reads and writes are billed separately and are not included.
+    pub fn total_tokens(&self) -> Option<TotalTokens> {
+        let input = self.input_tokens.map_or(0, |t| t.into_inner());
+        let output = self.output_tokens.map_or(0, |t| t.into_inner());
+        TotalTokens::try_new(input.saturating_add(output)).ok()
+    }
+}
+
+/// Extract model and usage from a JSON message or an SSE stream
+///
+/// Counts are merged field by field, so the `message_delta` output count
+/// completes the input counts from `message_start`.
+pub fn extract_message_usage(body: &[u8]) -> MessageUsage {
+    let mut usage = MessageUsage::default();
+
+    match serde_json::from_slice::<Value>(body) {
+        Ok(json) => merge_event(&mut usage, &json),
+        Err(_) => {
+            for json in sse_events(body) {
+                merge_event(&mut usage, &json);
+            }
+        }
+    }
+
+    usage
+}
+
+fn merge_event(usage: &mut MessageUsage, event: &Value) {
+    let object = event
+        .get(json_fields::anthropic::MESSAGE)
+        .filter(|m| m.is_object())
+        .unwrap_or(event);
+
+    if let Some(model_id) = object
+        .get(json_fields::common::MODEL)
+        .and_then(Value::as_str)
+        .and_then(|model| ModelId::try_new(model.to_string()).ok())
+    {
+        usage.model_id = Some(model_id);
+    }
+
+    let Some(counts) = object
+        .get(json_fields::claude::USAGE)
+        .filter(|u| u.is_object())
+    else {
+        return;
+    };
+
+    if let Some(n) = count(counts, json_fields::claude::INPUT_TOKENS) {
+        usage.input_tokens = InputTokens::try_new(n).ok();
+    }
+    if let Some(n) = count(counts, json_fields::claude::OUTPUT_TOKENS) {
+        usage.output_tokens = OutputTokens::try_new(n).ok();
+    }
+    if let Some(n) = count(counts, json_fields::claude::CACHE_READ_INPUT_TOKENS) {
+        usage.cache_read_tokens = CacheReadTokens::try_new(n).ok();
+    }
+    if let Some(n) = count(counts, json_fields::claude::CACHE_CREATION_INPUT_TOKENS) {
+        usage.cache_write_tokens = CacheWriteTokens::try_new(n).ok();
+    }
+}
+
+fn count(usage: &Value, field: &str) -> Option<u32> {
+    usage
+        .get(field)?
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tokens(usage: &MessageUsage) -> (Option<u32>, Option<u32>, Option<u32>, Option<u32>) {
+        (
+            usage.input_tokens.map(|t| t.into_inner()),
+            usage.output_tokens.map(|t| t.into_inner()),
+            usage.cache_read_tokens.map(|t| t.into_inner()),
+            usage.cache_write_tokens.map(|t| t.into_inner()),
+        )
+    }
+
+    fn model(usage: &MessageUsage) -> Option<String> {
+        usage.model_id.clone().map(|m| m.into_inner())
+    }
+
+    #[test]
+    fn extracts_message_usage_with_cache_tokens() {
+        let body = json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-20250514",
+            "content": [{"type": "text", "text": "Hi"}],
+            "usage": {
+                "input_tokens": 21,
+                "output_tokens": 4,
+                "cache_creation_input_tokens": 1200,
+                "cache_read_input_tokens": 0
+            }
+        });
+
+        let usage = extract_message_usage(body.to_string().as_bytes());
+
+        assert_eq!(model(&usage).as_deref(), Some("claude-sonnet-4-20250514"));
+        assert_eq!(tokens(&usage), (Some(21), Some(4), None, Some(1200)));
+        assert_eq!(usage.total_tokens().map(|t| t.into_inner()), Some(25));
+    }
+
+    #[test]
+    fn combines_message_start_and_message_delta() {
+        let body = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_01\",\"model\":\"claude-3-5-haiku-20241022\",\"usage\":{\"input_tokens\":25,\"output_tokens\":1,\"cache_read_input_tokens\":512,\"cache_creation_input_tokens\":0}}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":15}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+
+        let usage = extract_message_usage(body.as_bytes());
+
+        assert_eq!(model(&usage).as_deref(), Some("claude-3-5-haiku-20241022"));
+        assert_eq!(tokens(&usage), (Some(25), Some(15), Some(512), None));
+        assert_eq!(usage.total_tokens().map(|t| t.into_inner()), Some(40));
+    }
+
+    #[test]
+    fn message_delta_alone_reports_output_tokens() {
+        let body = "event: message_delta\ndata: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":7}}\n\n";
+
+        let usage = extract_message_usage(body.as_bytes());
+
+        assert_eq!(usage.model_id, None);
+        assert_eq!(tokens(&usage), (None, Some(7), None, None));
+    }
+
+    #[test]
+    fn error_body_is_empty() {
+        let body = json!({
+            "type": "error",
+            "error": {"type": "overloaded_error", "message": "Overloaded"}
+        });
+
+        assert!(extract_message_usage(body.to_string().as_bytes()).is_empty());
+        assert!(extract_message_usage(b"<html>bad gateway</html>").is_empty());
+    }
+}
@@ -0,0 +1,19 @@
+//! Azure OpenAI provider implementation
+//!
+//! Serves Azure OpenAI deployments behind `/azure/{resource}/`, so
+//! `/azure/contoso/openai/deployments/chat/chat/completions?api-version=...`
+//! reaches `https://contoso.openai.azure.com/openai/deployments/chat/...`.
+//!
+//! ## Features
+//!
+//! - `api-key` and Entra ID Bearer token pass-through
+//! - Only configured resources and deployments are forwarded
+//! - Model identity from the deployment mapping, since deployment names are
+//!   arbitrary and the body's `model` is not authoritative
+//! - Token usage from the OpenAI-format body
+
+pub mod provider;
+pub mod types;
+
+pub use provider::AzureOpenAiProvider;
+pub use types::{AzureDeploymentName, AzureOpenAiConfig, AzureResourceConfig, AzureResourceName};
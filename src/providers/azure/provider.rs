@@ -0,0 +1,345 @@
+//! Azure OpenAI provider implementation
+
+use crate::providers::azure::types::{AzureDeploymentName, AzureOpenAiConfig, AzureResourceName};
+use crate::providers::bedrock::provider::{BaseUrl, PathPrefix};
+use crate::providers::bedrock::types::ModelId;
+use crate::providers::constants::{http::headers, paths};
+use crate::providers::{
+    passthrough::{self, PathPrefixed},
+    HealthStatus, Provider, ProviderError, ProviderId, ProviderMetadata,
+};
+use crate::proxy::http::{UpstreamClient, BEARER_PREFIX};
+use async_trait::async_trait;
+use axum::body::Body;
+use hyper::{header, HeaderMap, Request, Response, Uri};
+use std::collections::HashMap;
+
+/// A configured resource with its endpoint resolved
+struct AzureResource {
+    base_url: BaseUrl,
+    deployments: HashMap<AzureDeploymentName, ModelId>,
+}
+
+/// Where a request is going, parsed from its path
+struct AzureTarget<'a> {
+    resource: &'a AzureResource,
+    model_id: &'a ModelId,
+    /// Upstream path and query, starting at `/openai/deployments/`
+    upstream_path: &'a str,
+}
+
+/// Azure OpenAI provider with deployment-based model identity
+pub struct AzureOpenAiProvider {
+    resources: HashMap<AzureResourceName, AzureResource>,
+    path_prefix: PathPrefix,
+}
+
+impl AzureOpenAiProvider {
+    /// Create a provider for the configured resources
+    ///
+    /// Fails if a resource names an endpoint that is not a URL.
+    pub fn new(config: &AzureOpenAiConfig) -> Result<Self, ProviderError> {
+        let resources = config
+            .resources
+            .iter()
+            .map(|resource| {
+                let endpoint = resource.endpoint.clone().unwrap_or_else(|| {
+                    format!("https://{}.{}", resource.name, paths::azure::HOST_SUFFIX)
+                });
+                let azure_resource = AzureResource {
+                    base_url: passthrough::base_url(endpoint)?,
+                    deployments: resource.deployments.clone(),
+                };
+                Ok((resource.name.clone(), azure_resource))
+            })
+            .collect::<Result<_, ProviderError>>()?;
+
+        Ok(Self {
+            resources,
+            path_prefix: PathPrefix::azure(),
+        })
+    }
+
+    /// Resolve the resource and deployment named by `/azure/{resource}/openai/deployments/{deployment}/...`
+    fn resolve<'a>(&'a self, uri: &'a Uri) -> Result<AzureTarget<'a>, ProviderError> {
+        let (resource_name, upstream_path) = self
+            .path_prefix
+            .strip(passthrough::path_and_query(uri))
+            .and_then(|rest| rest[1..].find('/').map(|i| rest[1..].split_at(i)))
+            .ok_or_else(|| {
+                ProviderError::InvalidPath(format!("Expected {}{{resource}}/...", self.path_prefix))
+            })?;
+
+        let resource = AzureResourceName::try_new(resource_name)
+            .ok()
+            .and_then(|name| self.resources.get(&name))
+            .ok_or_else(|| {
+                ProviderError::InvalidPath(format!("Unknown Azure resource: {resource_name}"))
+            })?;
+
+        let deployment_name = upstream_path
+            .strip_prefix(paths::azure::DEPLOYMENTS_PATH_PREFIX)
+            .and_then(|rest| rest.split(['/', '?']).next())
+            .ok_or_else(|| {
+                ProviderError::InvalidPath(format!(
+                    "Expected {}{{deployment}}/...",
+                    paths::azure::DEPLOYMENTS_PATH_PREFIX
+                ))
+            })?;
+
+        let model_id = AzureDeploymentName::try_new(deployment_name)
+            .ok()
+            .and_then(|name| resource.deployments.get(&name))
+            .ok_or_else(|| {
+                ProviderError::InvalidPath(format!(
+                    "Unknown Azure deployment {deployment_name} on {resource_name}"
+                ))
+            })?;
+
+        Ok(AzureTarget {
+            resource,
+            model_id,
+            upstream_path,
+        })
+    }
+}
+
+/// Azure accepts an `api-key` or an Entra ID Bearer token; either passes through
+fn validate_azure_auth(request_headers: &HeaderMap) -> Result<(), ProviderError> {
+    let has_api_key = request_headers.contains_key(headers::azure::API_KEY);
+    let has_bearer = request_headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|auth| auth.starts_with(BEARER_PREFIX));
+
+    if has_api_key || has_bearer {
+        Ok(())
+    } else {
+        Err(ProviderError::AuthenticationError(format!(
+            "Azure OpenAI requests must include {} or a Bearer token",
+            headers::azure::API_KEY
+        )))
+    }
+}
+
+impl PathPrefixed for AzureOpenAiProvider {
+    fn path_prefix_mut(&mut self) -> &mut PathPrefix {
+        &mut self.path_prefix
+    }
+}
+
+#[async_trait]
+impl Provider for AzureOpenAiProvider {
+    fn id(&self) -> ProviderId {
+        ProviderId::azure()
+    }
+
+    fn matches_path(&self, path: &str) -> bool {
+        self.path_prefix.matches(path)
+    }
+
+    fn transform_url(&self, url: &Uri) -> Result<Uri, ProviderError> {
+        let target = self.resolve(url)?;
+        passthrough::target_url(target.resource.base_url.as_ref(), target.upstream_path)
+    }
+
+    async fn forward_request(
+        &self,
+        request: Request<Body>,
+        client: &UpstreamClient,
+    ) -> Result<Response<Body>, ProviderError> {
+        let (parts, body) = request.into_parts();
+
+        validate_azure_auth(&parts.headers)?;
+
+        let uri = self.transform_url(&parts.uri)?;
+        passthrough::forward(client, parts, body, uri).await
+    }
+
+    fn extract_metadata(
+        &self,
+        request: &Request<Body>,
+        response: &Response<Body>,
+    ) -> ProviderMetadata {
+        // The deployment mapping is the source of truth for the model;
+        // token usage comes from the body via `ProviderResponseProcessor`
+        let model_id = self
+            .resolve(request.uri())
+            .ok()
+            .map(|target| target.model_id.clone());
+
+        let provider_request_id =
+            passthrough::provider_request_id(response, headers::azure::REQUEST_ID);
+
+        ProviderMetadata {
+            provider_id: self.id(),
+            model_id,
+            provider_request_id,
+            ..Default::default()
+        }
+    }
+
+    async fn health_check(&self, _client: &UpstreamClient) -> HealthStatus {
+        if self.resources.is_empty() {
+            HealthStatus::Degraded("No Azure OpenAI resources configured".to_string())
+        } else {
+            passthrough::configured_health()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::azure::types::AzureResourceConfig;
+    use crate::proxy::http::build_upstream_client;
+    use crate::proxy::types::UpstreamTlsConfig;
+    use mockito::{Matcher, Server};
+
+    const CHAT_PATH: &str =
+        "/azure/contoso/openai/deployments/prod-chat/chat/completions?api-version=2024-10-21";
+
+    fn config(endpoint: Option<String>) -> AzureOpenAiConfig {
+        AzureOpenAiConfig {
+            resources: vec![AzureResourceConfig {
+                name: AzureResourceName::try_new("contoso").unwrap(),
+                endpoint,
+                deployments: HashMap::from([(
+                    AzureDeploymentName::try_new("prod-chat").unwrap(),
+                    ModelId::try_new("gpt-4o-2024-08-06".to_string()).unwrap(),
+                )]),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_provider_id_and_paths() {
+        let provider = AzureOpenAiProvider::new(&config(None)).unwrap();
+
+        assert_eq!(provider.id(), ProviderId::azure());
+        assert!(provider.matches_path("/azure/contoso/openai/deployments/x/chat/completions"));
+        assert!(!provider.matches_path("/openai/v1/chat/completions"));
+    }
+
+    #[test]
+    fn test_transform_url_targets_resource_endpoint() {
+        let provider = AzureOpenAiProvider::new(&config(None)).unwrap();
+
+        let uri: Uri = CHAT_PATH.parse().unwrap();
+
+        assert_eq!(
+            provider.transform_url(&uri).unwrap().to_string(),
+            "https://contoso.openai.azure.com/openai/deployments/prod-chat/chat/completions?api-version=2024-10-21"
+        );
+    }
+
+    #[test]
+    fn test_unknown_resource_or_deployment_is_rejected() {
+        let provider = AzureOpenAiProvider::new(&config(None)).unwrap();
+
+        for path in [
+            "/azure/fabrikam/openai/deployments/prod-chat/chat/completions",
+            "/azure/contoso/openai/deployments/staging-chat/chat/completions",
+            "/azure/contoso/openai/models",
+            "/azure/contoso",
+        ] {
+            let uri: Uri = path.parse().unwrap();
+            assert!(
+                matches!(
+                    provider.transform_url(&uri),
+                    Err(ProviderError::InvalidPath(_))
+                ),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_endpoint_is_rejected() {
+        assert!(matches!(
+            AzureOpenAiProvider::new(&config(Some("not a url".to_string()))),
+            Err(ProviderError::InvalidUrl(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_forwards_api_key_and_reports_mapped_model() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/openai/deployments/prod-chat/chat/completions")
+            .match_query(Matcher::UrlEncoded(
+                "api-version".into(),
+                "2024-10-21".into(),
+            ))
+            .match_header("api-key", "azure-secret")
+            .with_status(200)
+            .with_header("apim-request-id", "5f0c2b9e-8d3a-4c1e-9b7a-2e6f1d4c8a90")
+            .with_body(r#"{"model":"gpt-4o","usage":{"prompt_tokens":1,"completion_tokens":1,"total_tokens":2}}"#)
+            .create_async()
+            .await;
+        let provider = AzureOpenAiProvider::new(&config(Some(server.url()))).unwrap();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(CHAT_PATH)
+            .header("api-key", "azure-secret")
+            .body(Body::from(r#"{"messages":[]}"#))
+            .unwrap();
+        let metadata_request = Request::builder()
+            .uri(CHAT_PATH)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = provider.forward_request(request, &client).await.unwrap();
+
+        mock.assert_async().await;
+        let metadata = provider.extract_metadata(&metadata_request, &response);
+        assert_eq!(metadata.provider_id, ProviderId::azure());
+        assert_eq!(
+            metadata.model_id,
+            Some(ModelId::try_new("gpt-4o-2024-08-06".to_string()).unwrap())
+        );
+        assert_eq!(
+            metadata.provider_request_id.map(|id| id.to_string()),
+            Some("5f0c2b9e-8d3a-4c1e-9b7a-2e6f1d4c8a90".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_request_without_credentials() {
+        let provider = AzureOpenAiProvider::new(&config(None)).unwrap();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(CHAT_PATH)
+            .body(Body::empty())
+            .unwrap();
+
+        assert!(matches!(
+            provider.forward_request(request, &client).await,
+            Err(ProviderError::AuthenticationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_metadata_names_the_mapped_model_version() {
+        use crate::domain::llm::LlmProvider;
+
+        let provider = AzureOpenAiProvider::new(&config(None)).unwrap();
+        let request = Request::builder()
+            .uri(CHAT_PATH)
+            .body(Body::empty())
+            .unwrap();
+        let response = Response::new(Body::empty());
+
+        let model_version = provider
+            .extract_metadata(&request, &response)
+            .model_version()
+            .unwrap();
+
+        assert_eq!(model_version.provider, LlmProvider::Azure);
+        assert_eq!(model_version.model_id.as_ref(), "gpt-4o-2024-08-06");
+    }
+}
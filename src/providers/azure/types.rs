@@ -0,0 +1,92 @@
+//! Type definitions and configuration for the Azure OpenAI provider
+
+use crate::providers::bedrock::types::ModelId;
+use nutype::nutype;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Azure OpenAI resource name, the `{resource}` in `{resource}.openai.azure.com`
+#[nutype(
+    sanitize(trim, lowercase),
+    validate(not_empty, len_char_max = 64, regex = r"^[a-z0-9][a-z0-9-]*$"),
+    derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        Hash,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display
+    )
+)]
+pub struct AzureResourceName(String);
+
+/// Deployment name chosen by the resource owner
+#[nutype(
+    sanitize(trim),
+    validate(not_empty, len_char_max = 64, regex = r"^[a-zA-Z0-9_.-]+$"),
+    derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        Hash,
+        Serialize,
+        Deserialize,
+        AsRef,
+        Display
+    )
+)]
+pub struct AzureDeploymentName(String);
+
+/// Azure OpenAI resources the proxy may forward to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AzureOpenAiConfig {
+    #[serde(default)]
+    pub resources: Vec<AzureResourceConfig>,
+}
+
+/// One Azure OpenAI resource and the model behind each of its deployments
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AzureResourceConfig {
+    pub name: AzureResourceName,
+    /// Endpoint to use instead of `https://{name}.openai.azure.com`
+    /// (custom domains, private endpoints, mocks)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Deployment name to the model it serves, e.g. `gpt-4o-2024-08-06`
+    #[serde(default)]
+    pub deployments: HashMap<AzureDeploymentName, ModelId>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_resources_and_deployments() {
+        let config: AzureOpenAiConfig = serde_json::from_str(
+            r#"{"resources": [{
+                "name": "Contoso-EastUS",
+                "deployments": {"prod-chat": "gpt-4o-2024-08-06", "embed": "text-embedding-3-large"}
+            }]}"#,
+        )
+        .unwrap();
+
+        let resource = &config.resources[0];
+        assert_eq!(resource.name.as_ref(), "contoso-eastus");
+        assert_eq!(resource.endpoint, None);
+        assert_eq!(
+            resource.deployments[&AzureDeploymentName::try_new("prod-chat").unwrap()],
+            ModelId::try_new("gpt-4o-2024-08-06".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_resource_names_that_are_not_host_labels() {
+        assert!(AzureResourceName::try_new("contoso.evil.com").is_err());
+        assert!(AzureResourceName::try_new("-contoso").is_err());
+    }
+}
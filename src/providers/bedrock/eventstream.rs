@@ -0,0 +1,380 @@
+//! Decoder for the AWS `application/vnd.amazon.eventstream` framing
+//!
+//! Bedrock's streaming endpoints (InvokeModelWithResponseStream and
+//! ConverseStream) send binary frames rather than SSE. Each frame is:
+//!
+//! ```text
+//! total length (u32) | headers length (u32) | prelude CRC32 (u32)
+//! headers | payload | message CRC32 (u32)
+//! ```
+//!
+//! All integers are big-endian and both checksums are CRC32 (IEEE).
+
+use crate::providers::constants::http::headers::eventstream;
+use bytes::{Buf, Bytes, BytesMut};
+
+/// Length of total length, headers length and prelude CRC
+const PRELUDE_LEN: usize = 12;
+/// Length of the trailing message CRC
+const MESSAGE_CRC_LEN: usize = 4;
+/// Smallest valid frame: a prelude and a message CRC with no headers or payload
+const MIN_FRAME_LEN: usize = PRELUDE_LEN + MESSAGE_CRC_LEN;
+/// AWS rejects frames above 16 MiB; anything larger means a corrupt prelude
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Why a frame could not be decoded
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EventStreamError {
+    #[error("Invalid frame length: {0}")]
+    InvalidLength(usize),
+
+    #[error("Prelude checksum mismatch")]
+    PreludeChecksum,
+
+    #[error("Message checksum mismatch")]
+    MessageChecksum,
+
+    #[error("Malformed header: {0}")]
+    MalformedHeader(String),
+}
+
+/// Typed value of an event-stream header
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderValue {
+    Bool(bool),
+    Byte(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Bytes(Bytes),
+    String(String),
+    Timestamp(i64),
+    Uuid([u8; 16]),
+}
+
+/// One decoded event-stream message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub headers: Vec<(String, HeaderValue)>,
+    pub payload: Bytes,
+}
+
+impl Message {
+    /// String value of the named header
+    pub fn header_str(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find_map(|(key, value)| match value {
+            HeaderValue::String(s) if key == name => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    /// `:message-type`: `event`, `exception` or `error`
+    pub fn message_type(&self) -> Option<&str> {
+        self.header_str(eventstream::MESSAGE_TYPE)
+    }
+
+    /// `:event-type` for events, falling back to `:exception-type`
+    pub fn event_type(&self) -> Option<&str> {
+        self.header_str(eventstream::EVENT_TYPE)
+            .or_else(|| self.header_str(eventstream::EXCEPTION_TYPE))
+    }
+}
+
+/// Incremental decoder; bytes can arrive split at any point
+#[derive(Debug, Default)]
+pub struct EventStreamDecoder {
+    buffer: BytesMut,
+}
+
+impl EventStreamDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append received bytes
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Number of buffered bytes not yet returned as a message
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Decode the next complete message, or `None` if more bytes are needed
+    ///
+    /// After an error the stream cannot be resynchronised, so callers should
+    /// stop decoding.
+    pub fn next_message(&mut self) -> Result<Option<Message>, EventStreamError> {
+        if self.buffer.len() < PRELUDE_LEN {
+            return Ok(None);
+        }
+
+        let total_len = read_u32(&self.buffer[0..4]) as usize;
+        let headers_len = read_u32(&self.buffer[4..8]) as usize;
+        if !(MIN_FRAME_LEN..=MAX_FRAME_LEN).contains(&total_len)
+            || headers_len > total_len - MIN_FRAME_LEN
+        {
+            return Err(EventStreamError::InvalidLength(total_len));
+        }
+        if crc32fast::hash(&self.buffer[0..8]) != read_u32(&self.buffer[8..12]) {
+            return Err(EventStreamError::PreludeChecksum);
+        }
+        if self.buffer.len() < total_len {
+            return Ok(None);
+        }
+
+        let mut frame = self.buffer.split_to(total_len).freeze();
+        let crc_offset = total_len - MESSAGE_CRC_LEN;
+        if crc32fast::hash(&frame[..crc_offset]) != read_u32(&frame[crc_offset..]) {
+            return Err(EventStreamError::MessageChecksum);
+        }
+
+        frame.truncate(crc_offset);
+        frame.advance(PRELUDE_LEN);
+        let headers = decode_headers(frame.split_to(headers_len))?;
+
+        Ok(Some(Message {
+            headers,
+            payload: frame,
+        }))
+    }
+}
+
+/// Decode every complete message in `body`
+///
+/// Stops at the first malformed frame or at a trailing partial frame.
+pub fn decode_messages(body: &[u8]) -> impl Iterator<Item = Message> {
+    let mut decoder = EventStreamDecoder::new();
+    decoder.push(body);
+    std::iter::from_fn(move || decoder.next_message().ok().flatten())
+}
+
+/// Whether `body` starts with a well-formed event-stream prelude
+pub fn looks_like_event_stream(body: &[u8]) -> bool {
+    body.len() >= PRELUDE_LEN && crc32fast::hash(&body[0..8]) == read_u32(&body[8..12])
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn decode_headers(mut bytes: Bytes) -> Result<Vec<(String, HeaderValue)>, EventStreamError> {
+    let mut headers = Vec::new();
+
+    while bytes.has_remaining() {
+        let name_len = take(&mut bytes, 1)?.get_u8() as usize;
+        let name = utf8(take(&mut bytes, name_len)?)?;
+        let value_type = take(&mut bytes, 1)?.get_u8();
+
+        let value = match value_type {
+            0 => HeaderValue::Bool(true),
+            1 => HeaderValue::Bool(false),
+            2 => HeaderValue::Byte(take(&mut bytes, 1)?.get_i8()),
+            3 => HeaderValue::Int16(take(&mut bytes, 2)?.get_i16()),
+            4 => HeaderValue::Int32(take(&mut bytes, 4)?.get_i32()),
+            5 => HeaderValue::Int64(take(&mut bytes, 8)?.get_i64()),
+            6 | 7 => {
+                let len = take(&mut bytes, 2)?.get_u16() as usize;
+                let value = take(&mut bytes, len)?;
+                if value_type == 6 {
+                    HeaderValue::Bytes(value)
+                } else {
+                    HeaderValue::String(utf8(value)?)
+                }
+            }
+            8 => HeaderValue::Timestamp(take(&mut bytes, 8)?.get_i64()),
+            9 => {
+                let mut uuid = [0u8; 16];
+                take(&mut bytes, 16)?.copy_to_slice(&mut uuid);
+                HeaderValue::Uuid(uuid)
+            }
+            other => {
+                return Err(EventStreamError::MalformedHeader(format!(
+                    "unknown value type {other} for {name}"
+                )))
+            }
+        };
+        headers.push((name, value));
+    }
+
+    Ok(headers)
+}
+
+fn take(bytes: &mut Bytes, len: usize) -> Result<Bytes, EventStreamError> {
+    if bytes.remaining() < len {
+        return Err(EventStreamError::MalformedHeader(
+            "header runs past the header block".to_string(),
+        ));
+    }
+    Ok(bytes.split_to(len))
+}
+
+fn utf8(bytes: Bytes) -> Result<String, EventStreamError> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| EventStreamError::MalformedHeader("header is not UTF-8".to_string()))
+}
+
+/// Encode a frame with string headers, as Bedrock sends them
+#[cfg(test)]
+pub(crate) fn encode_frame(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+    let mut header_bytes = Vec::new();
+    for (name, value) in headers {
+        header_bytes.push(name.len() as u8);
+        header_bytes.extend_from_slice(name.as_bytes());
+        header_bytes.push(7);
+        header_bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        header_bytes.extend_from_slice(value.as_bytes());
+    }
+
+    let total_len = MIN_FRAME_LEN + header_bytes.len() + payload.len();
+    let mut frame = Vec::with_capacity(total_len);
+    frame.extend_from_slice(&(total_len as u32).to_be_bytes());
+    frame.extend_from_slice(&(header_bytes.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&crc32fast::hash(&frame).to_be_bytes());
+    frame.extend_from_slice(&header_bytes);
+    frame.extend_from_slice(payload);
+    frame.extend_from_slice(&crc32fast::hash(&frame).to_be_bytes());
+    frame
+}
+
+/// Encode a Bedrock `event` message
+#[cfg(test)]
+pub(crate) fn encode_event(event_type: &str, payload: &str) -> Vec<u8> {
+    encode_frame(
+        &[
+            (eventstream::EVENT_TYPE, event_type),
+            (":content-type", "application/json"),
+            (eventstream::MESSAGE_TYPE, "event"),
+        ],
+        payload.as_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_event_headers_and_payload() {
+        let frame = encode_event("messageStop", r#"{"stopReason":"end_turn"}"#);
+
+        let messages: Vec<_> = decode_messages(&frame).collect();
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message_type(), Some("event"));
+        assert_eq!(messages[0].event_type(), Some("messageStop"));
+        assert_eq!(&messages[0].payload[..], br#"{"stopReason":"end_turn"}"#);
+    }
+
+    #[test]
+    fn reassembles_frames_split_across_pushes() {
+        let mut body = encode_event("messageStart", r#"{"role":"assistant"}"#);
+        body.extend(encode_event(
+            "messageStop",
+            r#"{"stopReason":"max_tokens"}"#,
+        ));
+        let mut decoder = EventStreamDecoder::new();
+        let mut event_types = Vec::new();
+
+        for byte in &body {
+            decoder.push(std::slice::from_ref(byte));
+            while let Some(message) = decoder.next_message().unwrap() {
+                event_types.push(message.event_type().unwrap().to_string());
+            }
+        }
+
+        assert_eq!(event_types, ["messageStart", "messageStop"]);
+        assert_eq!(decoder.buffered_len(), 0);
+    }
+
+    #[test]
+    fn decodes_every_header_type() {
+        let mut headers = Vec::new();
+        let mut push = |name: &str, value_type: u8, value: &[u8]| {
+            headers.push(name.len() as u8);
+            headers.extend_from_slice(name.as_bytes());
+            headers.push(value_type);
+            headers.extend_from_slice(value);
+        };
+        push("t", 0, &[]);
+        push("f", 1, &[]);
+        push("b", 2, &[0xff]);
+        push("s", 3, &7i16.to_be_bytes());
+        push("i", 4, &(-3i32).to_be_bytes());
+        push("l", 5, &9i64.to_be_bytes());
+        push("y", 6, &[0, 2, 0xde, 0xad]);
+        push("ts", 8, &1_700_000_000_000i64.to_be_bytes());
+        push("u", 9, &[1; 16]);
+
+        let total_len = MIN_FRAME_LEN + headers.len();
+        let mut frame = (total_len as u32).to_be_bytes().to_vec();
+        frame.extend_from_slice(&(headers.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&crc32fast::hash(&frame).to_be_bytes());
+        frame.extend_from_slice(&headers);
+        frame.extend_from_slice(&crc32fast::hash(&frame).to_be_bytes());
+
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&frame);
+        let message = decoder.next_message().unwrap().unwrap();
+
+        let values: Vec<_> = message.headers.into_iter().map(|(_, v)| v).collect();
+        assert_eq!(
+            values,
+            [
+                HeaderValue::Bool(true),
+                HeaderValue::Bool(false),
+                HeaderValue::Byte(-1),
+                HeaderValue::Int16(7),
+                HeaderValue::Int32(-3),
+                HeaderValue::Int64(9),
+                HeaderValue::Bytes(Bytes::from_static(&[0xde, 0xad])),
+                HeaderValue::Timestamp(1_700_000_000_000),
+                HeaderValue::Uuid([1; 16]),
+            ]
+        );
+        assert!(message.payload.is_empty());
+    }
+
+    #[test]
+    fn rejects_corrupted_frames() {
+        let frame = encode_event("chunk", r#"{"bytes":""}"#);
+
+        let mut bad_prelude = frame.clone();
+        bad_prelude[9] ^= 0xff;
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&bad_prelude);
+        assert_eq!(
+            decoder.next_message(),
+            Err(EventStreamError::PreludeChecksum)
+        );
+
+        let mut bad_payload = frame.clone();
+        let last_payload_byte = frame.len() - MESSAGE_CRC_LEN - 1;
+        bad_payload[last_payload_byte] ^= 0xff;
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&bad_payload);
+        assert_eq!(
+            decoder.next_message(),
+            Err(EventStreamError::MessageChecksum)
+        );
+    }
+
+    #[test]
+    fn waits_for_the_rest_of_a_partial_frame() {
+        let frame = encode_event("chunk", r#"{"bytes":""}"#);
+        let mut decoder = EventStreamDecoder::new();
+
+        decoder.push(&frame[..frame.len() - 1]);
+        assert_eq!(decoder.next_message(), Ok(None));
+
+        decoder.push(&frame[frame.len() - 1..]);
+        assert!(decoder.next_message().unwrap().is_some());
+    }
+
+    #[test]
+    fn json_is_not_an_event_stream() {
+        assert!(!looks_like_event_stream(br#"{"usage":{"inputTokens":1}}"#));
+        assert!(looks_like_event_stream(&encode_event("metadata", "{}")));
+    }
+}
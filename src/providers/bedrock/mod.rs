@@ -6,15 +6,18 @@
 //! ## Features
 //!
 //! - SigV4 authentication pass-through
-//! - Support for InvokeModel and InvokeModelWithResponseStream
+//! - Support for InvokeModel, InvokeModelWithResponseStream, Converse and ConverseStream
 //! - Model-specific request/response handling
+//! - Decoding of the binary `application/vnd.amazon.eventstream` framing
 //! - Cost calculation based on token usage
 //! - Zero-copy streaming for minimal latency
 
 pub mod auth;
+pub mod eventstream;
 pub mod models;
 pub mod provider;
 pub mod types;
+pub mod usage;
 
 #[cfg(test)]
 mod tests;
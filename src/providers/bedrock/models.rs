@@ -11,6 +11,9 @@ use crate::providers::ProviderError;
 use serde_json::Value;
 
 /// Extract model ID from the request path
+///
+/// SDKs percent-encode IDs containing `:` or `/` (versioned IDs, inference
+/// profile ARNs), so the segment is decoded.
 pub fn extract_model_id(path: &str) -> Option<ModelId> {
     // Path format: /bedrock/model/{model-id}/invoke
     let parts: Vec<&str> = path.split('/').collect();
@@ -20,7 +23,8 @@ pub fn extract_model_id(path: &str) -> Option<ModelId> {
         .iter()
         .position(|&p| p == paths::bedrock::MODEL_SEGMENT)
         .and_then(|i| parts.get(i + 1))
-        .and_then(|&s| ModelId::try_new(s.to_string()).ok())
+        .and_then(|&s| urlencoding::decode(s).ok())
+        .and_then(|s| ModelId::try_new(s.into_owned()).ok())
 }
 
 /// Extract token usage from response based on model family
@@ -162,6 +166,16 @@ mod tests {
             Some(ModelId::try_new("amazon.titan-text-express-v1".to_string()).unwrap())
         );
 
+        let path = "/bedrock/model/us.anthropic.claude-3-5-haiku-20241022-v1%3A0/converse-stream";
+        let model_id = extract_model_id(path);
+        assert_eq!(
+            model_id,
+            Some(
+                ModelId::try_new("us.anthropic.claude-3-5-haiku-20241022-v1:0".to_string())
+                    .unwrap()
+            )
+        );
+
         let path = "/bedrock/invoke";
         let model_id = extract_model_id(path);
         assert_eq!(model_id, None);
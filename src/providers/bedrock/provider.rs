@@ -8,6 +8,7 @@ use crate::providers::bedrock::{
 use crate::providers::{
     HealthStatus, Provider, ProviderError, ProviderId, ProviderMetadata, RequestId,
 };
+use crate::proxy::http::UpstreamClient;
 use async_trait::async_trait;
 use axum::body::Body;
 use hyper::{Request, Response, Uri};
@@ -27,11 +28,11 @@ impl BaseUrl {
     }
 }
 
-/// API path prefix for routing
+/// API path prefix for routing, such as `/bedrock/`
 #[nutype(
     sanitize(trim),
-    validate(not_empty, predicate = |s| s.starts_with('/')),
-    derive(Debug, Clone, PartialEq)
+    validate(not_empty, predicate = |s| s.len() > 1 && s.starts_with('/') && s.ends_with('/')),
+    derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, AsRef, Display)
 )]
 pub struct PathPrefix(String);
 
@@ -39,15 +40,53 @@ impl PathPrefix {
     pub const BEDROCK: &'static str = "/bedrock/";
     pub const OPENAI: &'static str = "/openai/";
     pub const ANTHROPIC: &'static str = "/anthropic/";
+    pub const VERTEX: &'static str = "/vertex/";
+    pub const AZURE: &'static str = "/azure/";
 
     pub fn bedrock() -> Self {
         Self::try_new(Self::BEDROCK.to_string()).unwrap()
     }
+
+    pub fn openai() -> Self {
+        Self::try_new(Self::OPENAI.to_string()).unwrap()
+    }
+
+    pub fn anthropic() -> Self {
+        Self::try_new(Self::ANTHROPIC.to_string()).unwrap()
+    }
+
+    pub fn vertex() -> Self {
+        Self::try_new(Self::VERTEX.to_string()).unwrap()
+    }
+
+    pub fn azure() -> Self {
+        Self::try_new(Self::AZURE.to_string()).unwrap()
+    }
+
+    /// Check whether `path` falls under this prefix
+    pub fn matches(&self, path: &str) -> bool {
+        path.starts_with(self.as_ref())
+    }
+
+    /// Remove the prefix from `path`, keeping the leading slash of the rest
+    pub fn strip<'a>(&self, path: &'a str) -> Option<&'a str> {
+        let prefix = self.as_ref();
+        path.starts_with(prefix).then(|| &path[prefix.len() - 1..])
+    }
+
+    /// Error for a path that does not start with this prefix
+    pub fn missing(&self) -> ProviderError {
+        ProviderError::InvalidPath(format!(
+            "Missing {} prefix",
+            self.as_ref().trim_end_matches('/')
+        ))
+    }
 }
 
 /// AWS Bedrock provider
 pub struct BedrockProvider {
     base_url: BaseUrl,
+    path_prefix: PathPrefix,
 }
 
 impl BedrockProvider {
@@ -55,22 +94,33 @@ impl BedrockProvider {
     pub fn new(region: AwsRegion) -> Self {
         let url_string = format!("https://bedrock-runtime.{}.amazonaws.com", region.as_ref());
         let base_url = BaseUrl::try_new(url_string).unwrap();
-        Self { base_url }
+        Self {
+            base_url,
+            path_prefix: PathPrefix::bedrock(),
+        }
     }
 
     /// Create a new Bedrock provider with a custom base URL (for testing)
     pub fn with_base_url(base_url: String) -> Self {
         let base_url = BaseUrl::try_new(base_url).unwrap();
-        Self { base_url }
+        Self {
+            base_url,
+            path_prefix: PathPrefix::bedrock(),
+        }
+    }
+
+    /// Serve this provider under a different prefix than `/bedrock/`
+    pub fn with_path_prefix(mut self, path_prefix: PathPrefix) -> Self {
+        self.path_prefix = path_prefix;
+        self
     }
 
     /// Build the target URL for Bedrock API
     fn build_target_url(&self, path: &str) -> Result<Uri, ProviderError> {
-        // Remove /bedrock prefix from path
-        let bedrock_prefix = PathPrefix::BEDROCK.trim_end_matches('/'); // Remove trailing slash for strip_prefix
-        let bedrock_path = path
-            .strip_prefix(bedrock_prefix)
-            .ok_or_else(|| ProviderError::InvalidPath("Missing /bedrock prefix".to_string()))?;
+        let bedrock_path = self
+            .path_prefix
+            .strip(path)
+            .ok_or_else(|| self.path_prefix.missing())?;
 
         let target_url = format!("{}{}", self.base_url.as_ref(), bedrock_path);
 
@@ -87,7 +137,7 @@ impl Provider for BedrockProvider {
     }
 
     fn matches_path(&self, path: &str) -> bool {
-        path.starts_with(PathPrefix::BEDROCK)
+        self.path_prefix.matches(path)
     }
 
     fn transform_url(&self, url: &Uri) -> Result<Uri, ProviderError> {
@@ -97,10 +147,7 @@ impl Provider for BedrockProvider {
     async fn forward_request(
         &self,
         request: Request<Body>,
-        client: &hyper_util::client::legacy::Client<
-            hyper_util::client::legacy::connect::HttpConnector,
-            Body,
-        >,
+        client: &UpstreamClient,
     ) -> Result<Response<Body>, ProviderError> {
         let (mut parts, body) = request.into_parts();
 
@@ -166,13 +213,7 @@ impl Provider for BedrockProvider {
         metadata
     }
 
-    async fn health_check(
-        &self,
-        _client: &hyper_util::client::legacy::Client<
-            hyper_util::client::legacy::connect::HttpConnector,
-            Body,
-        >,
-    ) -> HealthStatus {
+    async fn health_check(&self, _client: &UpstreamClient) -> HealthStatus {
         // For MVP, we assume Bedrock is healthy
         // Future enhancement: Implement actual health check
         HealthStatus::Healthy
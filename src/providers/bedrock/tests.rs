@@ -4,6 +4,8 @@
 mod invoke_model_tests {
     use crate::providers::bedrock::{provider::BedrockProvider, types::AwsRegion};
     use crate::providers::{Provider, ProviderError};
+    use crate::proxy::http::build_upstream_client;
+    use crate::proxy::types::UpstreamTlsConfig;
     use axum::body::Body;
     use http_body_util::BodyExt;
     use hyper::{Request, StatusCode};
@@ -73,9 +75,7 @@ mod invoke_model_tests {
             .unwrap();
 
         // Create a test HTTP client
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         // Forward the request through the provider
         let response = provider.forward_request(request, &client).await.unwrap();
@@ -150,9 +150,7 @@ mod invoke_model_tests {
             .unwrap();
 
         // Create a test HTTP client
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         // Forward the request through the provider
         let response = provider.forward_request(request, &client).await.unwrap();
@@ -182,9 +180,7 @@ mod invoke_model_tests {
             .body(Body::empty())
             .unwrap();
 
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         // Should fail due to missing auth headers
         let result = provider.forward_request(request, &client).await;
@@ -203,6 +199,8 @@ mod invoke_model_tests {
 mod streaming_tests {
     use crate::providers::bedrock::{provider::BedrockProvider, types::AwsRegion};
     use crate::providers::Provider;
+    use crate::proxy::http::build_upstream_client;
+    use crate::proxy::types::UpstreamTlsConfig;
     use axum::body::Body;
     use futures_util::StreamExt;
     use http_body_util::BodyExt;
@@ -277,9 +275,7 @@ mod streaming_tests {
             .unwrap();
 
         // Create a test HTTP client
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         // Forward the request through the provider
         let response = provider.forward_request(request, &client).await.unwrap();
@@ -309,6 +305,81 @@ mod streaming_tests {
         mock.assert_async().await;
     }
 
+    #[tokio::test]
+    async fn test_converse_stream_passthrough_and_usage() {
+        use crate::providers::bedrock::eventstream::encode_event;
+        use crate::providers::constants::http::content_types::AMAZON_EVENT_STREAM;
+        use crate::providers::response_processor::ProviderResponseProcessor;
+
+        let mut server = Server::new_async().await;
+        let provider = BedrockProvider::with_base_url(server.url());
+
+        let mut frames = encode_event("messageStart", r#"{"role":"assistant"}"#);
+        frames.extend(encode_event(
+            "contentBlockDelta",
+            r#"{"contentBlockIndex":0,"delta":{"text":"Hello"}}"#,
+        ));
+        frames.extend(encode_event("messageStop", r#"{"stopReason":"end_turn"}"#));
+        frames.extend(encode_event(
+            "metadata",
+            r#"{"usage":{"inputTokens":11,"outputTokens":3,"totalTokens":14},"metrics":{"latencyMs":95}}"#,
+        ));
+        let frames_for_mock = frames.clone();
+
+        let mock = server
+            .mock(
+                "POST",
+                "/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse-stream",
+            )
+            .match_header("authorization", Matcher::Any)
+            .match_header("x-amz-date", Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", AMAZON_EVENT_STREAM)
+            .with_chunked_body(move |w| {
+                // Split mid-frame to exercise reassembly downstream
+                for piece in frames_for_mock.chunks(7) {
+                    w.write_all(piece)?;
+                }
+                Ok(())
+            })
+            .create_async()
+            .await;
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/bedrock/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse-stream")
+            .header("authorization", "AWS4-HMAC-SHA256 Credential=test")
+            .header("x-amz-date", "20250126T120000Z")
+            .body(Body::from(
+                json!({"messages": [{"role": "user", "content": [{"text": "Hi"}]}]}).to_string(),
+            ))
+            .unwrap();
+        let metadata_request = Request::builder()
+            .uri("/bedrock/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse-stream")
+            .body(Body::empty())
+            .unwrap();
+
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+        let response = provider.forward_request(request, &client).await.unwrap();
+        let base_metadata = provider.extract_metadata(&metadata_request, &response);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+
+        // Frames reach the client byte for byte
+        assert_eq!(&body[..], &frames[..]);
+
+        let metadata = ProviderResponseProcessor::new(base_metadata).process_complete_body(&body);
+        assert_eq!(
+            metadata.model_id.unwrap().as_ref(),
+            "anthropic.claude-3-haiku-20240307-v1:0"
+        );
+        assert_eq!(metadata.request_tokens.unwrap().into_inner(), 11);
+        assert_eq!(metadata.response_tokens.unwrap().into_inner(), 3);
+        assert_eq!(metadata.total_tokens.unwrap().into_inner(), 14);
+        assert_eq!(metadata.stop_reason.unwrap().as_ref(), "end_turn");
+
+        mock.assert_async().await;
+    }
+
     #[tokio::test]
     async fn test_streaming_error_handling() {
         let mut server = Server::new_async().await;
@@ -347,9 +418,7 @@ mod streaming_tests {
             .body(Body::from(json!({"prompt": "Hello"}).to_string()))
             .unwrap();
 
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         // Forward the request
         let response = provider.forward_request(request, &client).await.unwrap();
@@ -398,9 +467,7 @@ mod streaming_tests {
             .body(Body::empty())
             .unwrap();
 
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         let start = std::time::Instant::now();
         let response = provider.forward_request(request, &client).await.unwrap();
@@ -426,6 +493,8 @@ mod streaming_tests {
 mod error_handling_tests {
     use crate::providers::bedrock::provider::BedrockProvider;
     use crate::providers::Provider;
+    use crate::proxy::http::build_upstream_client;
+    use crate::proxy::types::UpstreamTlsConfig;
     use axum::body::Body;
     use http_body_util::BodyExt;
     use hyper::{Request, StatusCode};
@@ -463,9 +532,7 @@ mod error_handling_tests {
             .body(Body::empty())
             .unwrap();
 
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         let response = provider.forward_request(request, &client).await.unwrap();
 
@@ -520,9 +587,7 @@ mod error_handling_tests {
             .body(Body::empty())
             .unwrap();
 
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         let response = provider.forward_request(request, &client).await.unwrap();
 
@@ -573,9 +638,7 @@ mod error_handling_tests {
             .body(Body::empty())
             .unwrap();
 
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         let response = provider.forward_request(request, &client).await.unwrap();
 
@@ -622,9 +685,7 @@ mod error_handling_tests {
             .body(Body::empty())
             .unwrap();
 
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         let response = provider.forward_request(request, &client).await.unwrap();
 
@@ -672,9 +733,7 @@ mod error_handling_tests {
             .body(Body::empty())
             .unwrap();
 
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .build_http();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
 
         let response = provider.forward_request(request, &client).await.unwrap();
 
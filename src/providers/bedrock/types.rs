@@ -25,18 +25,24 @@ pub struct ModelId(String);
 pub enum BedrockEndpoint {
     InvokeModel,
     InvokeModelWithResponseStream,
+    Converse,
+    ConverseStream,
 }
 
 impl BedrockEndpoint {
+    const ALL: [Self; 4] = [
+        Self::InvokeModel,
+        Self::InvokeModelWithResponseStream,
+        Self::Converse,
+        Self::ConverseStream,
+    ];
+
     /// Parse endpoint from path
     pub fn from_path(path: &str) -> Option<Self> {
-        if path.ends_with(&format!("/{}", paths::bedrock::INVOKE_ENDPOINT)) {
-            Some(Self::InvokeModel)
-        } else if path.ends_with(&format!("/{}", paths::bedrock::INVOKE_STREAM_ENDPOINT)) {
-            Some(Self::InvokeModelWithResponseStream)
-        } else {
-            None
-        }
+        let last_segment = path.rsplit('/').next()?;
+        Self::ALL
+            .into_iter()
+            .find(|endpoint| endpoint.suffix() == last_segment)
     }
 
     /// Get the endpoint suffix
@@ -44,6 +50,8 @@ impl BedrockEndpoint {
         match self {
             Self::InvokeModel => paths::bedrock::INVOKE_ENDPOINT,
             Self::InvokeModelWithResponseStream => paths::bedrock::INVOKE_STREAM_ENDPOINT,
+            Self::Converse => paths::bedrock::CONVERSE_ENDPOINT,
+            Self::ConverseStream => paths::bedrock::CONVERSE_STREAM_ENDPOINT,
         }
     }
 }
@@ -126,6 +134,28 @@ pub struct OutputTokens(u32);
 )]
 pub struct TotalTokens(u32);
 
+/// Token count for input tokens served from the prompt cache
+#[nutype(
+    validate(greater = 0),
+    derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)
+)]
+pub struct CacheReadTokens(u32);
+
+/// Token count for input tokens written to the prompt cache
+#[nutype(
+    validate(greater = 0),
+    derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)
+)]
+pub struct CacheWriteTokens(u32);
+
+/// Why the model stopped generating (`end_turn`, `max_tokens`, `tool_use`, ...)
+#[nutype(
+    sanitize(trim),
+    validate(not_empty),
+    derive(Debug, Clone, PartialEq, Serialize, Deserialize, AsRef, Display)
+)]
+pub struct StopReason(String);
+
 /// Token usage information using type-safe token counts
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenUsage {
@@ -158,3 +188,27 @@ pub struct BedrockError {
 // Model-specific request/response types will be added as needed
 // For now, we'll use generic JSON values and add specific types
 // as we implement model-specific handling
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_from_path() {
+        for (path, endpoint) in [
+            ("/model/m/invoke", BedrockEndpoint::InvokeModel),
+            (
+                "/model/m/invoke-with-response-stream",
+                BedrockEndpoint::InvokeModelWithResponseStream,
+            ),
+            ("/model/m/converse", BedrockEndpoint::Converse),
+            ("/model/m/converse-stream", BedrockEndpoint::ConverseStream),
+        ] {
+            assert_eq!(BedrockEndpoint::from_path(path), Some(endpoint), "{path}");
+        }
+        assert_eq!(
+            BedrockEndpoint::from_path("/model/m/converse-streaming"),
+            None
+        );
+    }
+}
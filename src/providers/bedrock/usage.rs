@@ -0,0 +1,357 @@
+//! Token usage and stop reason extraction for Bedrock responses
+//!
+//! Handles all four runtime endpoints:
+//!
+//! - **InvokeModel**: per-family JSON, see [`extract_token_usage`]
+//! - **Converse**: one JSON shape for every model, with `usage` and `stopReason`
+//! - **InvokeModelWithResponseStream**: event-stream `chunk` events whose
+//!   base64 `bytes` hold the model's own stream events; the last one carries
+//!   `amazon-bedrock-invocationMetrics`
+//! - **ConverseStream**: event-stream `messageStop` and `metadata` events
+
+use crate::providers::anthropic::usage::extract_message_usage;
+use crate::providers::bedrock::eventstream::{decode_messages, looks_like_event_stream};
+use crate::providers::bedrock::models::extract_token_usage;
+use crate::providers::bedrock::types::{
+    CacheReadTokens, CacheWriteTokens, InputTokens, ModelFamily, ModelId, OutputTokens, StopReason,
+    TotalTokens,
+};
+use crate::providers::constants::http::headers::eventstream;
+use crate::providers::constants::json_fields;
+use base64::Engine;
+use serde_json::Value;
+
+/// Token counts and stop reason reported by a Bedrock response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InvocationUsage {
+    pub input_tokens: Option<InputTokens>,
+    pub output_tokens: Option<OutputTokens>,
+    pub total_tokens: Option<TotalTokens>,
+    pub cache_read_tokens: Option<CacheReadTokens>,
+    pub cache_write_tokens: Option<CacheWriteTokens>,
+    pub stop_reason: Option<StopReason>,
+}
+
+impl InvocationUsage {
+    /// True when nothing could be extracted
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn fill_total(&mut self) {
+        if self.total_tokens.is_none() {
+            let input = self.input_tokens.map_or(0, |t| t.into_inner());
+            let output = self.output_tokens.map_or(0, |t| t.into_inner());
+            self.total_tokens = TotalTokens::try_new(input.saturating_add(output)).ok();
+        }
+    }
+}
+
+/// Extract usage from a JSON body or an event stream
+///
+/// `model_id` selects the per-family InvokeModel format; Converse and
+/// streaming bodies do not need it.
+pub fn extract_invocation_usage(model_id: Option<&ModelId>, body: &[u8]) -> InvocationUsage {
+    let mut usage = InvocationUsage::default();
+
+    if looks_like_event_stream(body) {
+        for message in decode_messages(body) {
+            if message.message_type() != Some(eventstream::EVENT) {
+                continue;
+            }
+            let Ok(payload) = serde_json::from_slice::<Value>(&message.payload) else {
+                continue;
+            };
+            match message.event_type() {
+                Some(eventstream::CHUNK) => merge_invoke_chunk(&mut usage, &payload),
+                Some(eventstream::MESSAGE_STOP) => {
+                    merge_stop_reason(&mut usage, &payload, json_fields::converse::STOP_REASON)
+                }
+                Some(eventstream::METADATA) => merge_converse_usage(&mut usage, &payload),
+                _ => {}
+            }
+        }
+    } else if let Ok(json) = serde_json::from_slice::<Value>(body) {
+        if is_converse_response(&json) {
+            merge_converse_usage(&mut usage, &json);
+            merge_stop_reason(&mut usage, &json, json_fields::converse::STOP_REASON);
+        } else {
+            merge_invoke_response(&mut usage, model_id, &json);
+        }
+    }
+
+    if usage.input_tokens.is_some() || usage.output_tokens.is_some() {
+        usage.fill_total();
+    }
+    usage
+}
+
+fn is_converse_response(json: &Value) -> bool {
+    json.get(json_fields::converse::USAGE)
+        .and_then(|usage| usage.get(json_fields::converse::INPUT_TOKENS))
+        .is_some()
+}
+
+/// InvokeModel JSON, whose layout depends on the model family
+fn merge_invoke_response(usage: &mut InvocationUsage, model_id: Option<&ModelId>, json: &Value) {
+    if let Some(model_id) = model_id {
+        if let Some(tokens) = extract_token_usage(&ModelFamily::from_model_id(model_id), json) {
+            usage.input_tokens = Some(tokens.input_tokens);
+            usage.output_tokens = Some(tokens.output_tokens);
+            usage.total_tokens = Some(tokens.total_tokens);
+        }
+    }
+    merge_stop_reason(usage, json, json_fields::claude::STOP_REASON);
+}
+
+/// `usage` object of a Converse response or ConverseStream `metadata` event
+fn merge_converse_usage(usage: &mut InvocationUsage, json: &Value) {
+    let Some(counts) = json
+        .get(json_fields::converse::USAGE)
+        .filter(|u| u.is_object())
+    else {
+        return;
+    };
+
+    if let Some(n) = count(counts, json_fields::converse::INPUT_TOKENS) {
+        usage.input_tokens = InputTokens::try_new(n).ok();
+    }
+    if let Some(n) = count(counts, json_fields::converse::OUTPUT_TOKENS) {
+        usage.output_tokens = OutputTokens::try_new(n).ok();
+    }
+    if let Some(n) = count(counts, json_fields::converse::TOTAL_TOKENS) {
+        usage.total_tokens = TotalTokens::try_new(n).ok();
+    }
+    if let Some(n) = count(counts, json_fields::converse::CACHE_READ_INPUT_TOKENS) {
+        usage.cache_read_tokens = CacheReadTokens::try_new(n).ok();
+    }
+    if let Some(n) = count(counts, json_fields::converse::CACHE_WRITE_INPUT_TOKENS) {
+        usage.cache_write_tokens = CacheWriteTokens::try_new(n).ok();
+    }
+}
+
+/// InvokeModelWithResponseStream `chunk` event: `{"bytes": "<base64 JSON>"}`
+fn merge_invoke_chunk(usage: &mut InvocationUsage, payload: &Value) {
+    let Some(bytes) = payload
+        .get(json_fields::invoke_stream::BYTES)
+        .and_then(Value::as_str)
+        .and_then(|encoded| {
+            base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .ok()
+        })
+    else {
+        return;
+    };
+    let Ok(event) = serde_json::from_slice::<Value>(&bytes) else {
+        return;
+    };
+
+    // Claude chunks are Anthropic Messages stream events
+    let message_usage = extract_message_usage(&bytes);
+    usage.input_tokens = message_usage.input_tokens.or(usage.input_tokens);
+    usage.output_tokens = message_usage.output_tokens.or(usage.output_tokens);
+    usage.cache_read_tokens = message_usage.cache_read_tokens.or(usage.cache_read_tokens);
+    usage.cache_write_tokens = message_usage
+        .cache_write_tokens
+        .or(usage.cache_write_tokens);
+
+    // Claude reports the stop reason in `message_delta.delta`, Llama and
+    // Mistral at the top level, Titan as `completionReason`
+    if let Some(delta) = event.get(json_fields::claude::DELTA) {
+        merge_stop_reason(usage, delta, json_fields::claude::STOP_REASON);
+    }
+    merge_stop_reason(usage, &event, json_fields::claude::STOP_REASON);
+    merge_stop_reason(usage, &event, json_fields::titan::COMPLETION_REASON);
+
+    // Bedrock appends its own metrics to the final chunk for every family
+    if let Some(metrics) = event.get(json_fields::invoke_stream::INVOCATION_METRICS) {
+        if let Some(n) = count(metrics, json_fields::invoke_stream::INPUT_TOKEN_COUNT) {
+            usage.input_tokens = InputTokens::try_new(n).ok();
+        }
+        if let Some(n) = count(metrics, json_fields::invoke_stream::OUTPUT_TOKEN_COUNT) {
+            usage.output_tokens = OutputTokens::try_new(n).ok();
+        }
+        if let Some(n) = count(
+            metrics,
+            json_fields::invoke_stream::CACHE_READ_INPUT_TOKEN_COUNT,
+        ) {
+            usage.cache_read_tokens = CacheReadTokens::try_new(n).ok();
+        }
+        if let Some(n) = count(
+            metrics,
+            json_fields::invoke_stream::CACHE_WRITE_INPUT_TOKEN_COUNT,
+        ) {
+            usage.cache_write_tokens = CacheWriteTokens::try_new(n).ok();
+        }
+    }
+}
+
+fn merge_stop_reason(usage: &mut InvocationUsage, json: &Value, field: &str) {
+    if let Some(reason) = json
+        .get(field)
+        .and_then(Value::as_str)
+        .and_then(|reason| StopReason::try_new(reason.to_string()).ok())
+    {
+        usage.stop_reason = Some(reason);
+    }
+}
+
+fn count(usage: &Value, field: &str) -> Option<u32> {
+    usage
+        .get(field)?
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::bedrock::eventstream::{encode_event, encode_frame};
+    use serde_json::json;
+
+    fn tokens(usage: &InvocationUsage) -> (Option<u32>, Option<u32>, Option<u32>) {
+        (
+            usage.input_tokens.map(|t| t.into_inner()),
+            usage.output_tokens.map(|t| t.into_inner()),
+            usage.total_tokens.map(|t| t.into_inner()),
+        )
+    }
+
+    fn stop_reason(usage: &InvocationUsage) -> Option<String> {
+        usage.stop_reason.clone().map(|r| r.into_inner())
+    }
+
+    fn chunk(event: Value) -> Vec<u8> {
+        let bytes = base64::engine::general_purpose::STANDARD.encode(event.to_string());
+        encode_event("chunk", &json!({ "bytes": bytes }).to_string())
+    }
+
+    #[test]
+    fn extracts_converse_response() {
+        let body = json!({
+            "output": {"message": {"role": "assistant", "content": [{"text": "Hi"}]}},
+            "stopReason": "end_turn",
+            "usage": {
+                "inputTokens": 30,
+                "outputTokens": 4,
+                "totalTokens": 34,
+                "cacheReadInputTokens": 12
+            },
+            "metrics": {"latencyMs": 420}
+        });
+
+        let usage = extract_invocation_usage(None, body.to_string().as_bytes());
+
+        assert_eq!(tokens(&usage), (Some(30), Some(4), Some(34)));
+        assert_eq!(usage.cache_read_tokens.unwrap().into_inner(), 12);
+        assert_eq!(stop_reason(&usage).as_deref(), Some("end_turn"));
+    }
+
+    #[test]
+    fn extracts_converse_stream() {
+        let mut body = encode_event("messageStart", r#"{"role":"assistant"}"#);
+        body.extend(encode_event(
+            "contentBlockDelta",
+            r#"{"contentBlockIndex":0,"delta":{"text":"Hi"}}"#,
+        ));
+        body.extend(encode_event(
+            "contentBlockStop",
+            r#"{"contentBlockIndex":0}"#,
+        ));
+        body.extend(encode_event(
+            "messageStop",
+            r#"{"stopReason":"max_tokens"}"#,
+        ));
+        body.extend(encode_event(
+            "metadata",
+            r#"{"usage":{"inputTokens":9,"outputTokens":64,"totalTokens":73,"cacheWriteInputTokens":5},"metrics":{"latencyMs":800}}"#,
+        ));
+
+        let usage = extract_invocation_usage(None, &body);
+
+        assert_eq!(tokens(&usage), (Some(9), Some(64), Some(73)));
+        assert_eq!(usage.cache_write_tokens.unwrap().into_inner(), 5);
+        assert_eq!(stop_reason(&usage).as_deref(), Some("max_tokens"));
+    }
+
+    #[test]
+    fn extracts_claude_invoke_stream() {
+        let mut body = chunk(json!({
+            "type": "message_start",
+            "message": {"model": "claude-3-haiku-20240307", "usage": {"input_tokens": 14, "output_tokens": 1}}
+        }));
+        body.extend(chunk(json!({
+            "type": "content_block_delta",
+            "index": 0,
+            "delta": {"type": "text_delta", "text": "Hi"}
+        })));
+        body.extend(chunk(json!({
+            "type": "message_delta",
+            "delta": {"stop_reason": "end_turn", "stop_sequence": null},
+            "usage": {"output_tokens": 6}
+        })));
+        body.extend(chunk(json!({
+            "type": "message_stop",
+            "amazon-bedrock-invocationMetrics": {
+                "inputTokenCount": 14,
+                "outputTokenCount": 6,
+                "invocationLatency": 512,
+                "firstByteLatency": 210
+            }
+        })));
+
+        let usage = extract_invocation_usage(None, &body);
+
+        assert_eq!(tokens(&usage), (Some(14), Some(6), Some(20)));
+        assert_eq!(stop_reason(&usage).as_deref(), Some("end_turn"));
+    }
+
+    #[test]
+    fn extracts_titan_invoke_stream_from_invocation_metrics() {
+        let body = chunk(json!({
+            "outputText": "Hello",
+            "completionReason": "FINISH",
+            "amazon-bedrock-invocationMetrics": {"inputTokenCount": 3, "outputTokenCount": 2}
+        }));
+
+        let usage = extract_invocation_usage(None, &body);
+
+        assert_eq!(tokens(&usage), (Some(3), Some(2), Some(5)));
+        assert_eq!(stop_reason(&usage).as_deref(), Some("FINISH"));
+    }
+
+    #[test]
+    fn extracts_invoke_model_response_by_family() {
+        let model_id = ModelId::try_new("meta.llama3-70b-instruct-v1:0").unwrap();
+        let body = json!({
+            "generation": "Hello!",
+            "prompt_token_count": 15,
+            "generation_token_count": 7,
+            "stop_reason": "stop"
+        });
+
+        let usage = extract_invocation_usage(Some(&model_id), body.to_string().as_bytes());
+
+        assert_eq!(tokens(&usage), (Some(15), Some(7), Some(22)));
+        assert_eq!(stop_reason(&usage).as_deref(), Some("stop"));
+    }
+
+    #[test]
+    fn ignores_exception_messages() {
+        let body = encode_frame(
+            &[
+                (":exception-type", "throttlingException"),
+                (":message-type", "exception"),
+            ],
+            br#"{"message":"Too many requests"}"#,
+        );
+
+        assert!(extract_invocation_usage(None, &body).is_empty());
+    }
+
+    #[test]
+    fn unparseable_body_is_empty() {
+        assert!(extract_invocation_usage(None, b"<html>bad gateway</html>").is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+//! Declarative provider configuration
+//!
+//! Each [`ProviderConfig`] describes one provider instance, so the same kind
+//! can be registered several times under different path prefixes: two
+//! Bedrock regions, a staging OpenAI-compatible server next to OpenAI, or a
+//! mock alongside the real thing.
+
+use crate::providers::anthropic::AnthropicProvider;
+use crate::providers::azure::{AzureOpenAiConfig, AzureOpenAiProvider, AzureResourceConfig};
+use crate::providers::bedrock::provider::{BaseUrl, BedrockProvider, PathPrefix};
+use crate::providers::bedrock::types::AwsRegion;
+use crate::providers::openai::OpenAiProvider;
+use crate::providers::vertex::VertexProvider;
+use crate::providers::{PathPrefixed, Provider, ProviderError, ProviderRegistry};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Region used for Bedrock instances that do not name one
+const DEFAULT_BEDROCK_REGION: &str = "us-east-1";
+
+/// The provider implementation an instance runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    Bedrock,
+    OpenAi,
+    Anthropic,
+    Vertex,
+    Azure,
+}
+
+impl ProviderKind {
+    /// Prefix used when an instance does not set `path_prefix`
+    pub fn default_path_prefix(self) -> PathPrefix {
+        match self {
+            Self::Bedrock => PathPrefix::bedrock(),
+            Self::OpenAi => PathPrefix::openai(),
+            Self::Anthropic => PathPrefix::anthropic(),
+            Self::Vertex => PathPrefix::vertex(),
+            Self::Azure => PathPrefix::azure(),
+        }
+    }
+}
+
+/// One provider instance
+///
+/// Only the fields that apply to `kind` may be set: `region` is for Bedrock,
+/// `resources` for Azure, and `base_url` for everything but Azure, whose
+/// endpoints are set per resource.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderConfig {
+    #[serde(rename = "type")]
+    pub kind: ProviderKind,
+    /// Defaults to `/{type}/`
+    #[serde(default)]
+    pub path_prefix: Option<PathPrefix>,
+    /// Upstream endpoint replacing the provider's public one
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// AWS region for Bedrock; defaults to `us-east-1`
+    #[serde(default)]
+    pub region: Option<AwsRegion>,
+    /// Time allowed for the upstream to start responding; defaults to the
+    /// proxy's request timeout
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Azure OpenAI resources and their deployments
+    #[serde(default)]
+    pub resources: Vec<AzureResourceConfig>,
+}
+
+impl ProviderConfig {
+    /// An instance of `kind` with every optional field unset
+    pub fn new(kind: ProviderKind) -> Self {
+        Self {
+            kind,
+            path_prefix: None,
+            base_url: None,
+            region: None,
+            timeout_ms: None,
+            resources: Vec::new(),
+        }
+    }
+
+    /// The instances registered when no providers are configured
+    ///
+    /// Bedrock uses `bedrock_region`; Azure is left out because it cannot
+    /// route anywhere without configured resources.
+    pub fn defaults(bedrock_region: Option<AwsRegion>) -> Vec<Self> {
+        vec![
+            Self {
+                region: bedrock_region,
+                ..Self::new(ProviderKind::Bedrock)
+            },
+            Self::new(ProviderKind::OpenAi),
+            Self::new(ProviderKind::Anthropic),
+            Self::new(ProviderKind::Vertex),
+        ]
+    }
+
+    /// The prefix this instance serves
+    pub fn resolved_path_prefix(&self) -> PathPrefix {
+        self.path_prefix
+            .clone()
+            .unwrap_or_else(|| self.kind.default_path_prefix())
+    }
+
+    /// Build the provider this entry describes
+    pub fn build(&self) -> Result<Arc<dyn Provider>, ProviderError> {
+        self.check_fields()?;
+        let prefix = self.resolved_path_prefix();
+
+        let provider: Arc<dyn Provider> = match self.kind {
+            ProviderKind::Bedrock => {
+                let provider = match &self.base_url {
+                    Some(base_url) => {
+                        // `with_base_url` expects a URL that is already valid
+                        BaseUrl::try_new(base_url.clone())
+                            .map_err(|_| ProviderError::InvalidUrl(base_url.clone()))?;
+                        BedrockProvider::with_base_url(base_url.clone())
+                    }
+                    None => BedrockProvider::new(self.region.clone().unwrap_or_else(|| {
+                        AwsRegion::try_new(DEFAULT_BEDROCK_REGION.to_string())
+                            .expect("default region is valid")
+                    })),
+                };
+                Arc::new(provider.with_path_prefix(prefix))
+            }
+            ProviderKind::OpenAi => {
+                let provider = match &self.base_url {
+                    Some(base_url) => OpenAiProvider::with_base_url(base_url.clone())?,
+                    None => OpenAiProvider::new(),
+                };
+                Arc::new(provider.with_path_prefix(prefix))
+            }
+            ProviderKind::Anthropic => {
+                let provider = match &self.base_url {
+                    Some(base_url) => AnthropicProvider::with_base_url(base_url.clone())?,
+                    None => AnthropicProvider::new(),
+                };
+                Arc::new(provider.with_path_prefix(prefix))
+            }
+            ProviderKind::Vertex => {
+                let provider = match &self.base_url {
+                    Some(base_url) => VertexProvider::with_base_url(base_url.clone())?,
+                    None => VertexProvider::new(),
+                };
+                Arc::new(provider.with_path_prefix(prefix))
+            }
+            ProviderKind::Azure => {
+                let config = AzureOpenAiConfig {
+                    resources: self.resources.clone(),
+                };
+                Arc::new(AzureOpenAiProvider::new(&config)?.with_path_prefix(prefix))
+            }
+        };
+
+        Ok(provider)
+    }
+
+    /// Upstream timeout for this instance, if it sets one
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_ms.map(Duration::from_millis)
+    }
+
+    fn check_fields(&self) -> Result<(), ProviderError> {
+        let misplaced = |field: &str| {
+            Err(ProviderError::Configuration(format!(
+                "`{field}` does not apply to {:?} providers",
+                self.kind
+            )))
+        };
+
+        if self.region.is_some() && self.kind != ProviderKind::Bedrock {
+            return misplaced("region");
+        }
+        if !self.resources.is_empty() && self.kind != ProviderKind::Azure {
+            return misplaced("resources");
+        }
+        if self.base_url.is_some() && self.kind == ProviderKind::Azure {
+            return misplaced("base_url");
+        }
+        if self.kind == ProviderKind::Azure && self.resources.is_empty() {
+            return Err(ProviderError::Configuration(
+                "Azure providers need at least one resource".to_string(),
+            ));
+        }
+        if self.timeout_ms == Some(0) {
+            return Err(ProviderError::Configuration(
+                "`timeout_ms` must be greater than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl ProviderRegistry {
+    /// Build a registry with one provider per entry, in order
+    ///
+    /// Fails if an entry is invalid or two entries share a path prefix.
+    pub fn from_config(configs: &[ProviderConfig]) -> Result<Self, ProviderError> {
+        let mut registry = Self::new();
+        let mut prefixes: Vec<PathPrefix> = Vec::with_capacity(configs.len());
+
+        for config in configs {
+            let prefix = config.resolved_path_prefix();
+            if prefixes.contains(&prefix) {
+                return Err(ProviderError::Configuration(format!(
+                    "more than one provider uses {prefix}"
+                )));
+            }
+
+            let provider = config.build().map_err(|e| match e {
+                ProviderError::Configuration(msg) => {
+                    ProviderError::Configuration(format!("{prefix}: {msg}"))
+                }
+                other => ProviderError::Configuration(format!("{prefix}: {other}")),
+            })?;
+            registry.register_with_timeout(provider, config.timeout());
+            prefixes.push(prefix);
+        }
+
+        Ok(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::ProviderId;
+
+    fn parse(json: &str) -> Vec<ProviderConfig> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn registers_several_instances_of_one_kind() {
+        let configs = parse(
+            r#"[
+                {"type": "bedrock", "region": "us-east-1"},
+                {"type": "bedrock", "path_prefix": "/bedrock-west/", "region": "us-west-2", "timeout_ms": 5000},
+                {"type": "openai", "path_prefix": "/openai-staging/", "base_url": "http://vllm.staging:8000"}
+            ]"#,
+        );
+
+        let registry = ProviderRegistry::from_config(&configs).unwrap();
+
+        let east = registry.resolve("/bedrock/model/m/invoke").unwrap();
+        let west = registry.resolve("/bedrock-west/model/m/invoke").unwrap();
+        assert_eq!(east.provider.id(), ProviderId::bedrock());
+        assert_eq!(east.timeout, None);
+        assert_eq!(
+            west.provider
+                .transform_url(&"/bedrock-west/model/m/invoke".parse().unwrap())
+                .unwrap()
+                .to_string(),
+            "https://bedrock-runtime.us-west-2.amazonaws.com/model/m/invoke"
+        );
+        assert_eq!(west.timeout, Some(Duration::from_secs(5)));
+
+        let staging = registry.route("/openai-staging/v1/models").unwrap();
+        assert_eq!(
+            staging
+                .transform_url(&"/openai-staging/v1/models".parse().unwrap())
+                .unwrap()
+                .to_string(),
+            "http://vllm.staging:8000/v1/models"
+        );
+        assert!(registry.route("/openai/v1/models").is_none());
+    }
+
+    #[test]
+    fn defaults_cover_every_kind_but_azure() {
+        let registry = ProviderRegistry::from_config(&ProviderConfig::defaults(None)).unwrap();
+
+        for path in ["/bedrock/x", "/openai/x", "/anthropic/x", "/vertex/x"] {
+            assert!(registry.route(path).is_some(), "{path}");
+        }
+        assert!(registry.route("/azure/x").is_none());
+    }
+
+    #[test]
+    fn rejects_duplicate_prefixes() {
+        let configs =
+            parse(r#"[{"type": "openai"}, {"type": "anthropic", "path_prefix": "/openai/"}]"#);
+
+        assert!(matches!(
+            ProviderRegistry::from_config(&configs),
+            Err(ProviderError::Configuration(msg)) if msg.contains("/openai/")
+        ));
+    }
+
+    #[test]
+    fn rejects_fields_that_do_not_apply() {
+        for json in [
+            r#"[{"type": "openai", "region": "us-east-1"}]"#,
+            r#"[{"type": "azure"}]"#,
+            r#"[{"type": "bedrock", "timeout_ms": 0}]"#,
+            r#"[{"type": "vertex", "base_url": "not a url"}]"#,
+        ] {
+            assert!(
+                matches!(
+                    ProviderRegistry::from_config(&parse(json)),
+                    Err(ProviderError::Configuration(_))
+                ),
+                "{json}"
+            );
+        }
+    }
+
+    #[test]
+    fn path_prefix_must_be_wrapped_in_slashes() {
+        assert!(serde_json::from_str::<ProviderConfig>(
+            r#"{"type": "openai", "path_prefix": "openai-staging"}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<ProviderConfig>(
+            r#"{"type": "openai", "path_prefix": "/openai-staging"}"#
+        )
+        .is_err());
+    }
+}
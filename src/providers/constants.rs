@@ -11,6 +11,10 @@ pub mod json_fields {
         pub const USAGE: &str = "usage";
         pub const INPUT_TOKENS: &str = "input_tokens";
         pub const OUTPUT_TOKENS: &str = "output_tokens";
+        pub const CACHE_CREATION_INPUT_TOKENS: &str = "cache_creation_input_tokens";
+        pub const CACHE_READ_INPUT_TOKENS: &str = "cache_read_input_tokens";
+        pub const STOP_REASON: &str = "stop_reason";
+        pub const DELTA: &str = "delta";
         pub const CONTENT: &str = "content";
         pub const TYPE: &str = "type";
         pub const TEXT: &str = "text";
@@ -22,6 +26,7 @@ pub mod json_fields {
         pub const RESULTS: &str = "results";
         pub const TOKEN_COUNT: &str = "tokenCount";
         pub const OUTPUT_TEXT: &str = "outputText";
+        pub const COMPLETION_REASON: &str = "completionReason";
     }
 
     /// Llama model response fields
@@ -32,6 +37,27 @@ pub mod json_fields {
     }
 
     /// Jurassic model response fields
+    /// Bedrock Converse and ConverseStream fields
+    pub mod converse {
+        pub const USAGE: &str = "usage";
+        pub const INPUT_TOKENS: &str = "inputTokens";
+        pub const OUTPUT_TOKENS: &str = "outputTokens";
+        pub const TOTAL_TOKENS: &str = "totalTokens";
+        pub const CACHE_READ_INPUT_TOKENS: &str = "cacheReadInputTokens";
+        pub const CACHE_WRITE_INPUT_TOKENS: &str = "cacheWriteInputTokens";
+        pub const STOP_REASON: &str = "stopReason";
+    }
+
+    /// Bedrock InvokeModelWithResponseStream chunk fields
+    pub mod invoke_stream {
+        pub const BYTES: &str = "bytes";
+        pub const INVOCATION_METRICS: &str = "amazon-bedrock-invocationMetrics";
+        pub const INPUT_TOKEN_COUNT: &str = "inputTokenCount";
+        pub const OUTPUT_TOKEN_COUNT: &str = "outputTokenCount";
+        pub const CACHE_READ_INPUT_TOKEN_COUNT: &str = "cacheReadInputTokenCount";
+        pub const CACHE_WRITE_INPUT_TOKEN_COUNT: &str = "cacheWriteInputTokenCount";
+    }
+
     pub mod jurassic {
         pub const COMPLETIONS: &str = "completions";
         pub const DATA: &str = "data";
@@ -45,6 +71,35 @@ pub mod json_fields {
         pub const COMPLETION_TOKENS: &str = "completion_tokens";
     }
 
+    /// OpenAI response fields
+    pub mod openai {
+        pub const USAGE: &str = "usage";
+        pub const PROMPT_TOKENS: &str = "prompt_tokens";
+        pub const COMPLETION_TOKENS: &str = "completion_tokens";
+        pub const INPUT_TOKENS: &str = "input_tokens";
+        pub const OUTPUT_TOKENS: &str = "output_tokens";
+        pub const TOTAL_TOKENS: &str = "total_tokens";
+        /// Responses API stream events wrap the response object
+        pub const RESPONSE: &str = "response";
+    }
+
+    /// Anthropic Messages API stream fields
+    pub mod anthropic {
+        /// `message_start` events wrap the message object
+        pub const MESSAGE: &str = "message";
+    }
+
+    /// Vertex AI `generateContent` response fields
+    pub mod vertex {
+        pub const USAGE_METADATA: &str = "usageMetadata";
+        pub const PROMPT_TOKEN_COUNT: &str = "promptTokenCount";
+        pub const CANDIDATES_TOKEN_COUNT: &str = "candidatesTokenCount";
+        pub const TOTAL_TOKEN_COUNT: &str = "totalTokenCount";
+        pub const CACHED_CONTENT_TOKEN_COUNT: &str = "cachedContentTokenCount";
+        /// Concrete model version that served the request
+        pub const MODEL_VERSION: &str = "modelVersion";
+    }
+
     /// Common fields used across providers
     pub mod common {
         pub const MODEL: &str = "model";
@@ -62,10 +117,48 @@ pub mod paths {
         pub const MODEL_SEGMENT: &str = "model";
         pub const INVOKE_ENDPOINT: &str = "invoke";
         pub const INVOKE_STREAM_ENDPOINT: &str = "invoke-with-response-stream";
+        pub const CONVERSE_ENDPOINT: &str = "converse";
+        pub const CONVERSE_STREAM_ENDPOINT: &str = "converse-stream";
 
         /// Base path pattern for bedrock model endpoints
         pub const MODEL_PATH_PREFIX: &str = "/bedrock/model/";
     }
+
+    /// OpenAI API path components
+    pub mod openai {
+        pub const DEFAULT_BASE_URL: &str = "https://api.openai.com";
+    }
+
+    /// Vertex AI path components
+    pub mod vertex {
+        /// Host suffix for regional endpoints (`{location}-aiplatform.googleapis.com`)
+        pub const API_HOST: &str = "aiplatform.googleapis.com";
+        pub const GLOBAL_LOCATION: &str = "global";
+        pub const LOCATIONS_SEGMENT: &str = "locations";
+        pub const MODELS_SEGMENT: &str = "models";
+        /// Separates the model from the method (`models/gemini-2.0-flash:generateContent`)
+        pub const METHOD_SEPARATOR: char = ':';
+        pub const GENERATE_CONTENT: &str = "generateContent";
+        pub const STREAM_GENERATE_CONTENT: &str = "streamGenerateContent";
+    }
+
+    /// Azure OpenAI path components
+    pub mod azure {
+        /// Host suffix for resource endpoints (`{resource}.openai.azure.com`)
+        pub const HOST_SUFFIX: &str = "openai.azure.com";
+        pub const DEPLOYMENTS_PATH_PREFIX: &str = "/openai/deployments/";
+    }
+
+    /// Anthropic API path components
+    pub mod anthropic {
+        pub const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+    }
+}
+
+/// Server-sent events framing
+pub mod sse {
+    pub const DATA_PREFIX: &str = "data:";
+    pub const DONE_MARKER: &str = "[DONE]";
 }
 
 /// HTTP-related constants
@@ -75,6 +168,7 @@ pub mod http {
         pub const APPLICATION_JSON: &str = "application/json";
         pub const APPLICATION_OCTET_STREAM: &str = "application/octet-stream";
         pub const TEXT_EVENT_STREAM: &str = "text/event-stream";
+        pub const AMAZON_EVENT_STREAM: &str = "application/vnd.amazon.eventstream";
         pub const TEXT_PLAIN: &str = "text/plain";
         pub const TEXT_HTML: &str = "text/html; charset=utf-8";
     }
@@ -88,6 +182,39 @@ pub mod http {
             pub const AMZ_SECURITY_TOKEN: &str = "x-amz-security-token";
             pub const AMZ_CONTENT_SHA256: &str = "x-amz-content-sha256";
         }
+
+        /// Headers carried inside AWS event-stream messages
+        pub mod eventstream {
+            pub const MESSAGE_TYPE: &str = ":message-type";
+            pub const EVENT_TYPE: &str = ":event-type";
+            pub const EXCEPTION_TYPE: &str = ":exception-type";
+
+            /// `:message-type` of regular events
+            pub const EVENT: &str = "event";
+
+            /// `:event-type` values used by Bedrock
+            pub const CHUNK: &str = "chunk";
+            pub const MESSAGE_STOP: &str = "messageStop";
+            pub const METADATA: &str = "metadata";
+        }
+
+        /// OpenAI-specific headers
+        pub mod openai {
+            pub const REQUEST_ID: &str = "x-request-id";
+        }
+
+        /// Azure OpenAI-specific headers
+        pub mod azure {
+            pub const API_KEY: &str = "api-key";
+            pub const REQUEST_ID: &str = "apim-request-id";
+        }
+
+        /// Anthropic-specific headers
+        pub mod anthropic {
+            pub const API_KEY: &str = "x-api-key";
+            pub const VERSION: &str = "anthropic-version";
+            pub const REQUEST_ID: &str = "request-id";
+        }
     }
 }
 
@@ -230,6 +357,9 @@ pub mod config_defaults {
     /// Default EventCore flush interval
     pub const EVENTCORE_FLUSH_INTERVAL: i64 = 1000;
 
+    /// Default number of parallel audit consumers
+    pub const EVENTCORE_AUDIT_CONSUMERS: i64 = 4;
+
     /// Default log level
     pub const LOG_LEVEL: &str = "info";
 
@@ -353,6 +483,7 @@ mod tests {
         assert_eq!(config_defaults::DB_MAX_CONNECTIONS, 10);
         assert_eq!(config_defaults::EVENTCORE_BATCH_SIZE, 100);
         assert_eq!(config_defaults::EVENTCORE_FLUSH_INTERVAL, 1000);
+        assert_eq!(config_defaults::EVENTCORE_AUDIT_CONSUMERS, 4);
         assert_eq!(config_defaults::LOG_LEVEL, "info");
         assert_eq!(config_defaults::LOG_FORMAT, "json");
     }
@@ -3,22 +3,45 @@
 //! This module implements the provider abstraction layer as defined in ADR-0011,
 //! supporting multiple LLM providers with URL-based routing and preserving API compatibility.
 
+pub mod anthropic;
+pub mod azure;
 pub mod bedrock;
+pub mod config;
 pub mod constants;
+pub mod openai;
+mod passthrough;
 pub mod response_processor;
+mod sse;
+pub mod vertex;
 
+pub use passthrough::PathPrefixed;
+
+use crate::domain::config_types::ProviderName;
+use crate::domain::llm::{LlmProvider, ModelVersion};
+use crate::proxy::http::UpstreamClient;
 use crate::proxy::types::ProxyError;
 use async_trait::async_trait;
 use axum::body::Body;
 use hyper::{Request, Response};
 use nutype::nutype;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Provider identifier newtype for type safety
 #[nutype(
     sanitize(trim, lowercase),
     validate(not_empty, regex = r"^[a-z][a-z0-9-]*$"),
-    derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Display)
+    derive(
+        Debug,
+        Clone,
+        PartialEq,
+        Eq,
+        Hash,
+        Serialize,
+        Deserialize,
+        Display,
+        AsRef
+    )
 )]
 pub struct ProviderId(String);
 
@@ -26,6 +49,8 @@ impl ProviderId {
     pub const BEDROCK: &'static str = "bedrock";
     pub const OPENAI: &'static str = "openai";
     pub const ANTHROPIC: &'static str = "anthropic";
+    pub const VERTEX: &'static str = "vertex";
+    pub const AZURE: &'static str = "azure";
 
     /// Create a new ProviderId for well-known providers without validation
     pub fn bedrock() -> Self {
@@ -39,20 +64,51 @@ impl ProviderId {
     pub fn anthropic() -> Self {
         Self::try_new(Self::ANTHROPIC.to_string()).unwrap()
     }
+
+    pub fn vertex() -> Self {
+        Self::try_new(Self::VERTEX.to_string()).unwrap()
+    }
+
+    pub fn azure() -> Self {
+        Self::try_new(Self::AZURE.to_string()).unwrap()
+    }
+
+    /// The domain provider this identifier reports usage under
+    ///
+    /// Vertex AI serves Google models; providers without a domain variant
+    /// are reported by name.
+    pub fn llm_provider(&self) -> Option<LlmProvider> {
+        let provider = match self.as_ref() {
+            Self::OPENAI => LlmProvider::OpenAI,
+            Self::ANTHROPIC => LlmProvider::Anthropic,
+            Self::VERTEX => LlmProvider::Google,
+            Self::AZURE => LlmProvider::Azure,
+            other => LlmProvider::Other(ProviderName::try_new(other.to_string()).ok()?),
+        };
+        Some(provider)
+    }
 }
 
 /// Request ID from provider for tracking
 #[nutype(
     sanitize(trim),
-    validate(not_empty, regex = r"^[a-zA-Z0-9-]+$"),
+    validate(not_empty, regex = r"^[a-zA-Z0-9_-]+$"),
     derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display)
 )]
 pub struct RequestId(String);
 
+/// A registered provider and the settings the router applies to it
+#[derive(Clone)]
+pub struct RegisteredProvider {
+    pub provider: Arc<dyn Provider>,
+    /// Upstream timeout, overriding the proxy-wide request timeout
+    pub timeout: Option<Duration>,
+}
+
 /// Registry of all available providers
 #[derive(Default)]
 pub struct ProviderRegistry {
-    providers: Vec<Arc<dyn Provider>>,
+    providers: Vec<RegisteredProvider>,
 }
 
 impl ProviderRegistry {
@@ -63,15 +119,29 @@ impl ProviderRegistry {
 
     /// Register a provider
     pub fn register(&mut self, provider: Arc<dyn Provider>) {
-        self.providers.push(provider);
+        self.register_with_timeout(provider, None);
+    }
+
+    /// Register a provider with its own upstream timeout
+    pub fn register_with_timeout(
+        &mut self,
+        provider: Arc<dyn Provider>,
+        timeout: Option<Duration>,
+    ) {
+        self.providers
+            .push(RegisteredProvider { provider, timeout });
     }
 
     /// Route a request to the appropriate provider based on path
     pub fn route(&self, path: &str) -> Option<Arc<dyn Provider>> {
+        self.resolve(path).map(|entry| entry.provider.clone())
+    }
+
+    /// Find the registration handling `path`; the first match wins
+    pub fn resolve(&self, path: &str) -> Option<&RegisteredProvider> {
         self.providers
             .iter()
-            .find(|p| p.matches_path(path))
-            .cloned()
+            .find(|p| p.provider.matches_path(path))
     }
 }
 
@@ -91,10 +161,7 @@ pub trait Provider: Send + Sync {
     async fn forward_request(
         &self,
         request: Request<Body>,
-        client: &hyper_util::client::legacy::Client<
-            hyper_util::client::legacy::connect::HttpConnector,
-            Body,
-        >,
+        client: &UpstreamClient,
     ) -> Result<Response<Body>, ProviderError>;
 
     /// Extract metadata for audit logging
@@ -105,13 +172,7 @@ pub trait Provider: Send + Sync {
     ) -> ProviderMetadata;
 
     /// Provider-specific health check
-    async fn health_check(
-        &self,
-        client: &hyper_util::client::legacy::Client<
-            hyper_util::client::legacy::connect::HttpConnector,
-            Body,
-        >,
-    ) -> HealthStatus;
+    async fn health_check(&self, client: &UpstreamClient) -> HealthStatus;
 }
 
 /// Provider-specific error type
@@ -126,6 +187,9 @@ pub enum ProviderError {
     #[error("Authentication error: {0}")]
     AuthenticationError(String),
 
+    #[error("Invalid provider configuration: {0}")]
+    Configuration(String),
+
     #[error("Provider unavailable: {0}")]
     Unavailable(String),
 
@@ -145,6 +209,7 @@ impl From<ProviderError> for ProxyError {
             ProviderError::AuthenticationError(msg) => {
                 ProxyError::Internal(format!("Authentication error: {msg}"))
             }
+            ProviderError::Configuration(msg) => ProxyError::ProviderConfiguration(msg),
             ProviderError::Unavailable(msg) | ProviderError::RequestFailed(msg) => {
                 ProxyError::Internal(format!("Provider error: {msg}"))
             }
@@ -161,6 +226,9 @@ pub struct ProviderMetadata {
     pub request_tokens: Option<crate::providers::bedrock::types::InputTokens>,
     pub response_tokens: Option<crate::providers::bedrock::types::OutputTokens>,
     pub total_tokens: Option<crate::providers::bedrock::types::TotalTokens>,
+    pub cache_read_tokens: Option<crate::providers::bedrock::types::CacheReadTokens>,
+    pub cache_write_tokens: Option<crate::providers::bedrock::types::CacheWriteTokens>,
+    pub stop_reason: Option<crate::providers::bedrock::types::StopReason>,
     pub provider_request_id: Option<RequestId>,
 }
 
@@ -172,11 +240,25 @@ impl Default for ProviderMetadata {
             request_tokens: None,
             response_tokens: None,
             total_tokens: None,
+            cache_read_tokens: None,
+            cache_write_tokens: None,
+            stop_reason: None,
             provider_request_id: None,
         }
     }
 }
 
+impl ProviderMetadata {
+    /// Domain model version for version tracking, once the model is known
+    pub fn model_version(&self) -> Option<ModelVersion> {
+        let model_id = self.model_id.clone()?.into_inner();
+        Some(ModelVersion {
+            provider: self.provider_id.llm_provider()?,
+            model_id: crate::domain::types::ModelId::try_new(model_id).ok()?,
+        })
+    }
+}
+
 /// Health status for a provider
 #[derive(Debug, Clone)]
 pub enum HealthStatus {
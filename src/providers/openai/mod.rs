@@ -0,0 +1,16 @@
+//! OpenAI provider implementation
+//!
+//! Serves the OpenAI API behind the `/openai/` prefix. The base URL is
+//! configurable, so the same provider fronts OpenAI-compatible servers such
+//! as vLLM, Ollama and LM Studio.
+//!
+//! ## Features
+//!
+//! - Bearer token pass-through
+//! - Chat completions, completions, embeddings and the Responses API
+//! - Model and token usage from JSON bodies and SSE `usage` chunks
+
+pub mod provider;
+pub mod usage;
+
+pub use provider::OpenAiProvider;
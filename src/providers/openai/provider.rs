@@ -0,0 +1,234 @@
+//! OpenAI provider implementation
+
+use crate::providers::bedrock::provider::{BaseUrl, PathPrefix};
+use crate::providers::constants::{http::headers, paths};
+use crate::providers::{
+    passthrough::{self, PathPrefixed},
+    HealthStatus, Provider, ProviderError, ProviderId, ProviderMetadata,
+};
+use crate::proxy::http::{UpstreamClient, BEARER_PREFIX};
+use async_trait::async_trait;
+use axum::body::Body;
+use hyper::{header, HeaderMap, Request, Response, Uri};
+
+/// OpenAI provider, also used for OpenAI-compatible servers
+pub struct OpenAiProvider {
+    base_url: BaseUrl,
+    path_prefix: PathPrefix,
+}
+
+impl OpenAiProvider {
+    /// Create a provider for the public OpenAI API
+    pub fn new() -> Self {
+        let base_url = BaseUrl::try_new(paths::openai::DEFAULT_BASE_URL.to_string()).unwrap();
+        Self {
+            base_url,
+            path_prefix: PathPrefix::openai(),
+        }
+    }
+
+    /// Create a provider for an OpenAI-compatible server (vLLM, Ollama, LM Studio)
+    pub fn with_base_url(base_url: String) -> Result<Self, ProviderError> {
+        Ok(Self {
+            base_url: passthrough::base_url(base_url)?,
+            ..Self::new()
+        })
+    }
+
+    /// Build the target URL, keeping the query string
+    fn build_target_url(&self, uri: &Uri) -> Result<Uri, ProviderError> {
+        let upstream_path = passthrough::strip_prefix(&self.path_prefix, uri)?;
+        passthrough::target_url(self.base_url.as_ref(), upstream_path)
+    }
+}
+
+impl Default for OpenAiProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Bearer tokens pass through untouched; other schemes are rejected
+///
+/// A missing header is allowed because local OpenAI-compatible servers often
+/// run without authentication.
+fn validate_bearer_auth(headers: &HeaderMap) -> Result<(), ProviderError> {
+    match headers.get(header::AUTHORIZATION) {
+        None => Ok(()),
+        Some(value) => match value.to_str() {
+            Ok(auth) if auth.starts_with(BEARER_PREFIX) => Ok(()),
+            _ => Err(ProviderError::AuthenticationError(
+                "OpenAI requests must use Bearer authorization".to_string(),
+            )),
+        },
+    }
+}
+
+impl PathPrefixed for OpenAiProvider {
+    fn path_prefix_mut(&mut self) -> &mut PathPrefix {
+        &mut self.path_prefix
+    }
+}
+
+#[async_trait]
+impl Provider for OpenAiProvider {
+    fn id(&self) -> ProviderId {
+        ProviderId::openai()
+    }
+
+    fn matches_path(&self, path: &str) -> bool {
+        self.path_prefix.matches(path)
+    }
+
+    fn transform_url(&self, url: &Uri) -> Result<Uri, ProviderError> {
+        self.build_target_url(url)
+    }
+
+    async fn forward_request(
+        &self,
+        request: Request<Body>,
+        client: &UpstreamClient,
+    ) -> Result<Response<Body>, ProviderError> {
+        let (parts, body) = request.into_parts();
+
+        validate_bearer_auth(&parts.headers)?;
+
+        let uri = self.transform_url(&parts.uri)?;
+        passthrough::forward(client, parts, body, uri).await
+    }
+
+    fn extract_metadata(
+        &self,
+        _request: &Request<Body>,
+        response: &Response<Body>,
+    ) -> ProviderMetadata {
+        // Model and token usage live in the body; see `ProviderResponseProcessor`
+        let provider_request_id =
+            passthrough::provider_request_id(response, headers::openai::REQUEST_ID);
+
+        ProviderMetadata {
+            provider_id: self.id(),
+            provider_request_id,
+            ..Default::default()
+        }
+    }
+
+    async fn health_check(&self, _client: &UpstreamClient) -> HealthStatus {
+        passthrough::configured_health()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::http::build_upstream_client;
+    use crate::proxy::types::UpstreamTlsConfig;
+    use mockito::{Matcher, Server};
+
+    #[test]
+    fn test_provider_id() {
+        assert_eq!(OpenAiProvider::new().id(), ProviderId::openai());
+    }
+
+    #[test]
+    fn test_matches_path() {
+        let provider = OpenAiProvider::new();
+
+        assert!(provider.matches_path("/openai/v1/chat/completions"));
+        assert!(provider.matches_path("/openai/v1/responses"));
+        assert!(!provider.matches_path("/bedrock/model/claude-3/invoke"));
+        assert!(!provider.matches_path("/anthropic/v1/messages"));
+    }
+
+    #[test]
+    fn test_transform_url() {
+        let provider = OpenAiProvider::new();
+
+        for endpoint in [
+            "/v1/chat/completions",
+            "/v1/completions",
+            "/v1/embeddings",
+            "/v1/responses",
+        ] {
+            let input: Uri = format!("/openai{endpoint}").parse().unwrap();
+            assert_eq!(
+                provider.transform_url(&input).unwrap().to_string(),
+                format!("https://api.openai.com{endpoint}")
+            );
+        }
+    }
+
+    #[test]
+    fn test_transform_url_for_compatible_server_keeps_query() {
+        let provider =
+            OpenAiProvider::with_base_url("http://localhost:11434/".to_string()).unwrap();
+
+        let input: Uri = "/openai/v1/responses/resp_1?include=usage".parse().unwrap();
+
+        assert_eq!(
+            provider.transform_url(&input).unwrap().to_string(),
+            "http://localhost:11434/v1/responses/resp_1?include=usage"
+        );
+    }
+
+    #[test]
+    fn test_invalid_base_url_is_rejected() {
+        assert!(matches!(
+            OpenAiProvider::with_base_url("not a url".to_string()),
+            Err(ProviderError::InvalidUrl(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_forwards_bearer_token_to_upstream() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_header("authorization", "Bearer sk-test")
+            .match_header("host", Matcher::Regex(r"^127\.0\.0\.1:\d+$".to_string()))
+            .with_status(200)
+            .with_header("x-request-id", "req_abc123")
+            .with_body(r#"{"model":"gpt-4o","usage":{"prompt_tokens":1,"completion_tokens":1,"total_tokens":2}}"#)
+            .create_async()
+            .await;
+        let provider = OpenAiProvider::with_base_url(server.url()).unwrap();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/openai/v1/chat/completions")
+            .header("host", "proxy.internal:8080")
+            .header("authorization", "Bearer sk-test")
+            .body(Body::from(r#"{"model":"gpt-4o","messages":[]}"#))
+            .unwrap();
+        let metadata_request = Request::builder().body(Body::empty()).unwrap();
+
+        let response = provider.forward_request(request, &client).await.unwrap();
+
+        mock.assert_async().await;
+        let metadata = provider.extract_metadata(&metadata_request, &response);
+        assert_eq!(metadata.provider_id, ProviderId::openai());
+        assert_eq!(
+            metadata.provider_request_id.map(|id| id.to_string()),
+            Some("req_abc123".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_bearer_authorization() {
+        let provider = OpenAiProvider::new();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/openai/v1/chat/completions")
+            .header("authorization", "Basic dXNlcjpwYXNz")
+            .body(Body::empty())
+            .unwrap();
+
+        assert!(matches!(
+            provider.forward_request(request, &client).await,
+            Err(ProviderError::AuthenticationError(_))
+        ));
+    }
+}
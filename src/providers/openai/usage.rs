@@ -0,0 +1,189 @@
+//! Model and token usage extraction for OpenAI responses
+//!
+//! Handles plain JSON bodies as well as server-sent event streams. Chat and
+//! completion streams report usage in a final chunk (when the client asks for
+//! it with `stream_options.include_usage`); Responses API streams carry it on
+//! the `response.completed` event.
+
+use crate::providers::bedrock::types::{InputTokens, ModelId, OutputTokens, TotalTokens};
+use crate::providers::constants::json_fields;
+use crate::providers::sse::sse_events;
+use serde_json::Value;
+
+/// Model and token counts reported by an OpenAI response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseUsage {
+    pub model_id: Option<ModelId>,
+    pub input_tokens: Option<InputTokens>,
+    pub output_tokens: Option<OutputTokens>,
+    pub total_tokens: Option<TotalTokens>,
+}
+
+impl ResponseUsage {
+    /// True when nothing could be extracted
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Extract model and usage from a JSON body or an SSE stream
+///
+/// Later stream events win, so the final usage chunk overrides anything
+/// reported earlier.
+pub fn extract_response_usage(body: &[u8]) -> ResponseUsage {
+    let mut usage = ResponseUsage::default();
+
+    match serde_json::from_slice::<Value>(body) {
+        Ok(json) => merge_event(&mut usage, &json),
+        Err(_) => {
+            for json in sse_events(body) {
+                merge_event(&mut usage, &json);
+            }
+        }
+    }
+
+    usage
+}
+
+fn merge_event(usage: &mut ResponseUsage, event: &Value) {
+    // Responses API stream events nest the response object
+    let object = event.get(json_fields::openai::RESPONSE).unwrap_or(event);
+
+    if let Some(model_id) = object
+        .get(json_fields::common::MODEL)
+        .and_then(Value::as_str)
+        .and_then(|model| ModelId::try_new(model.to_string()).ok())
+    {
+        usage.model_id = Some(model_id);
+    }
+
+    let Some(counts) = object
+        .get(json_fields::openai::USAGE)
+        .filter(|u| u.is_object())
+    else {
+        return;
+    };
+
+    // Chat/completions/embeddings use prompt/completion; Responses uses input/output
+    let input = count(counts, json_fields::openai::PROMPT_TOKENS)
+        .or_else(|| count(counts, json_fields::openai::INPUT_TOKENS));
+    let output = count(counts, json_fields::openai::COMPLETION_TOKENS)
+        .or_else(|| count(counts, json_fields::openai::OUTPUT_TOKENS));
+    let total = count(counts, json_fields::openai::TOTAL_TOKENS);
+
+    usage.input_tokens = input.and_then(|n| InputTokens::try_new(n).ok());
+    usage.output_tokens = output.and_then(|n| OutputTokens::try_new(n).ok());
+    usage.total_tokens = total.and_then(|n| TotalTokens::try_new(n).ok());
+}
+
+fn count(usage: &Value, field: &str) -> Option<u32> {
+    usage
+        .get(field)?
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tokens(usage: &ResponseUsage) -> (Option<u32>, Option<u32>, Option<u32>) {
+        (
+            usage.input_tokens.map(|t| t.into_inner()),
+            usage.output_tokens.map(|t| t.into_inner()),
+            usage.total_tokens.map(|t| t.into_inner()),
+        )
+    }
+
+    fn model(usage: &ResponseUsage) -> Option<String> {
+        usage.model_id.clone().map(|m| m.into_inner())
+    }
+
+    #[test]
+    fn extracts_chat_completion_usage() {
+        let body = json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "model": "gpt-4o-2024-08-06",
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi"}}],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15}
+        });
+
+        let usage = extract_response_usage(body.to_string().as_bytes());
+
+        assert_eq!(model(&usage).as_deref(), Some("gpt-4o-2024-08-06"));
+        assert_eq!(tokens(&usage), (Some(12), Some(3), Some(15)));
+    }
+
+    #[test]
+    fn extracts_embedding_usage_without_output_tokens() {
+        let body = json!({
+            "object": "list",
+            "data": [{"object": "embedding", "index": 0, "embedding": [0.1, 0.2]}],
+            "model": "text-embedding-3-small",
+            "usage": {"prompt_tokens": 8, "total_tokens": 8}
+        });
+
+        let usage = extract_response_usage(body.to_string().as_bytes());
+
+        assert_eq!(tokens(&usage), (Some(8), None, Some(8)));
+    }
+
+    #[test]
+    fn extracts_responses_api_usage() {
+        let body = json!({
+            "id": "resp_123",
+            "object": "response",
+            "model": "gpt-4.1",
+            "usage": {"input_tokens": 20, "output_tokens": 7, "total_tokens": 27}
+        });
+
+        let usage = extract_response_usage(body.to_string().as_bytes());
+
+        assert_eq!(tokens(&usage), (Some(20), Some(7), Some(27)));
+    }
+
+    #[test]
+    fn extracts_usage_chunk_from_chat_stream() {
+        let body = concat!(
+            "data: {\"id\":\"c1\",\"model\":\"gpt-4o\",\"choices\":[{\"delta\":{\"content\":\"Hi\"}}],\"usage\":null}\n\n",
+            "data: {\"id\":\"c1\",\"model\":\"gpt-4o\",\"choices\":[],\"usage\":{\"prompt_tokens\":9,\"completion_tokens\":2,\"total_tokens\":11}}\n\n",
+            "data: [DONE]\n\n",
+        );
+
+        let usage = extract_response_usage(body.as_bytes());
+
+        assert_eq!(model(&usage).as_deref(), Some("gpt-4o"));
+        assert_eq!(tokens(&usage), (Some(9), Some(2), Some(11)));
+    }
+
+    #[test]
+    fn extracts_usage_from_responses_completed_event() {
+        let body = concat!(
+            "event: response.created\n",
+            "data: {\"type\":\"response.created\",\"response\":{\"model\":\"gpt-4.1\",\"usage\":null}}\n\n",
+            "event: response.completed\n",
+            "data: {\"type\":\"response.completed\",\"response\":{\"model\":\"gpt-4.1\",\"usage\":{\"input_tokens\":5,\"output_tokens\":4,\"total_tokens\":9}}}\n\n",
+        );
+
+        let usage = extract_response_usage(body.as_bytes());
+
+        assert_eq!(tokens(&usage), (Some(5), Some(4), Some(9)));
+    }
+
+    #[test]
+    fn stream_without_usage_chunk_reports_model_only() {
+        let body = "data: {\"model\":\"llama3\",\"choices\":[]}\n\ndata: [DONE]\n\n";
+
+        let usage = extract_response_usage(body.as_bytes());
+
+        assert!(usage.model_id.is_some());
+        assert_eq!(tokens(&usage), (None, None, None));
+    }
+
+    #[test]
+    fn unparseable_body_is_empty() {
+        assert!(extract_response_usage(b"<html>bad gateway</html>").is_empty());
+    }
+}
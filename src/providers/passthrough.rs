@@ -0,0 +1,121 @@
+//! Request handling shared by the providers that forward requests unchanged
+//!
+//! OpenAI, Anthropic, Vertex AI and Azure OpenAI strip their path prefix,
+//! append the rest of the path and query to an upstream endpoint and stream
+//! the upstream response back. Only Bedrock rewrites requests.
+
+use crate::providers::bedrock::provider::{BaseUrl, PathPrefix};
+use crate::providers::{HealthStatus, ProviderError, RequestId};
+use crate::proxy::http::UpstreamClient;
+use axum::body::Body;
+use hyper::http::request::Parts;
+use hyper::{header, Request, Response, Uri};
+
+/// A provider served under a configurable path prefix
+pub trait PathPrefixed: Sized {
+    /// The prefix the provider serves
+    fn path_prefix_mut(&mut self) -> &mut PathPrefix;
+
+    /// Serve the provider under `path_prefix` instead of its default one
+    fn with_path_prefix(mut self, path_prefix: PathPrefix) -> Self {
+        *self.path_prefix_mut() = path_prefix;
+        self
+    }
+}
+
+/// Validate a configured upstream endpoint
+pub(crate) fn base_url(base_url: String) -> Result<BaseUrl, ProviderError> {
+    BaseUrl::try_new(base_url.clone()).map_err(|_| ProviderError::InvalidUrl(base_url))
+}
+
+/// The path and query of `uri`
+pub(crate) fn path_and_query(uri: &Uri) -> &str {
+    uri.path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or_else(|| uri.path())
+}
+
+/// The path and query of `uri` after `path_prefix`
+pub(crate) fn strip_prefix<'a>(
+    path_prefix: &PathPrefix,
+    uri: &'a Uri,
+) -> Result<&'a str, ProviderError> {
+    path_prefix
+        .strip(path_and_query(uri))
+        .ok_or_else(|| path_prefix.missing())
+}
+
+/// `upstream_path` on the endpoint at `base_url`
+pub(crate) fn target_url(base_url: &str, upstream_path: &str) -> Result<Uri, ProviderError> {
+    let target_url = format!("{}{}", base_url.trim_end_matches('/'), upstream_path);
+
+    target_url
+        .parse()
+        .map_err(|_| ProviderError::InvalidUrl(target_url))
+}
+
+/// Send the request made of `parts` and `body` to `uri`, streaming the response back
+pub(crate) async fn forward(
+    client: &UpstreamClient,
+    mut parts: Parts,
+    body: Body,
+    uri: Uri,
+) -> Result<Response<Body>, ProviderError> {
+    parts.uri = uri;
+    // Let the client derive Host from the upstream URL
+    parts.headers.remove(header::HOST);
+
+    let response = client
+        .request(Request::from_parts(parts, body))
+        .await
+        .map_err(|e| ProviderError::RequestFailed(format!("Request failed: {e}")))?;
+
+    let (parts, incoming_body) = response.into_parts();
+    Ok(Response::from_parts(parts, Body::new(incoming_body)))
+}
+
+/// The upstream's id for a request, from the response header `name`
+pub(crate) fn provider_request_id(response: &Response<Body>, name: &str) -> Option<RequestId> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|id| RequestId::try_new(id.to_string()).ok())
+}
+
+/// Health of a provider that is ready once configured
+///
+/// Checking the upstream itself would need the caller's credentials, which
+/// the proxy does not hold.
+pub(crate) fn configured_health() -> HealthStatus {
+    HealthStatus::Healthy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_prefix_keeps_the_query() {
+        let uri: Uri = "/openai/v1/responses?include=usage".parse().unwrap();
+
+        assert_eq!(
+            strip_prefix(&PathPrefix::openai(), &uri).unwrap(),
+            "/v1/responses?include=usage"
+        );
+        assert!(matches!(
+            strip_prefix(&PathPrefix::anthropic(), &uri),
+            Err(ProviderError::InvalidPath(_))
+        ));
+    }
+
+    #[test]
+    fn test_target_url_joins_without_doubling_slashes() {
+        assert_eq!(
+            target_url("http://localhost:11434/", "/v1/models")
+                .unwrap()
+                .to_string(),
+            "http://localhost:11434/v1/models"
+        );
+    }
+}
@@ -1,12 +1,12 @@
 //! Response processor for extracting provider metadata from responses
 
-use crate::providers::bedrock::{
-    models::extract_token_usage,
-    types::{ModelFamily, ModelId},
-};
+use crate::providers::anthropic::usage::{extract_message_usage, MessageUsage};
+use crate::providers::bedrock::types::ModelId;
+use crate::providers::bedrock::usage::{extract_invocation_usage, InvocationUsage};
+use crate::providers::openai::usage::{extract_response_usage, ResponseUsage};
+use crate::providers::vertex::usage::{extract_generate_content_usage, GenerateContentUsage};
 use crate::providers::{ProviderId, ProviderMetadata};
 use bytes::Bytes;
-use serde_json::Value;
 
 /// Process response body and extract provider metadata
 pub struct ProviderResponseProcessor {
@@ -26,66 +26,120 @@ impl ProviderResponseProcessor {
     }
 
     /// Process a response body chunk and extract metadata
+    ///
+    /// Bedrock event streams are decoded up to the last complete frame in
+    /// `chunk`; use [`Self::process_complete_body`] when frames may be split.
     pub fn process_body_chunk(&self, chunk: &Bytes) -> Option<ProviderMetadata> {
-        // Only process if we have a model ID
-        let model_id = self.model_id.as_ref()?;
-
-        // Try to parse JSON from the chunk
-        let json_value: Value = serde_json::from_slice(chunk).ok()?;
-
-        // Extract metadata based on provider
+        if self.provider_id == ProviderId::openai() {
+            let usage = extract_response_usage(chunk);
+            return (!usage.is_empty()).then(|| self.merge_openai_usage(usage));
+        }
+        if self.provider_id == ProviderId::anthropic() {
+            let usage = extract_message_usage(chunk);
+            return (!usage.is_empty()).then(|| self.merge_anthropic_usage(usage));
+        }
+        if self.provider_id == ProviderId::vertex() {
+            let usage = extract_generate_content_usage(chunk);
+            return (!usage.is_empty()).then(|| self.merge_vertex_usage(usage));
+        }
+        if self.provider_id == ProviderId::azure() {
+            let usage = extract_response_usage(chunk);
+            return (!usage.is_empty()).then(|| self.merge_azure_usage(usage));
+        }
         if self.provider_id == ProviderId::bedrock() {
-            self.process_bedrock_response(model_id, &json_value)
-        } else {
-            None
+            let usage = extract_invocation_usage(self.model_id.as_ref(), chunk);
+            return (!usage.is_empty()).then(|| self.merge_bedrock_usage(usage));
         }
+
+        None
     }
 
     /// Process complete response body and extract final metadata
     pub fn process_complete_body(&self, body: &[u8]) -> ProviderMetadata {
-        let mut metadata = self.base_metadata.clone();
-
-        // Only process if we have a model ID
-        if let Some(model_id) = &self.model_id {
-            // Try to parse JSON from the body
-            if let Ok(json_value) = serde_json::from_slice::<Value>(body) {
-                // Extract metadata based on provider
-                if let Some(extracted) = if self.provider_id == ProviderId::bedrock() {
-                    self.process_bedrock_response(model_id, &json_value)
-                } else {
-                    None
-                } {
-                    // Merge extracted metadata
-                    metadata.request_tokens = extracted.request_tokens.or(metadata.request_tokens);
-                    metadata.response_tokens =
-                        extracted.response_tokens.or(metadata.response_tokens);
-                    metadata.total_tokens = extracted.total_tokens.or(metadata.total_tokens);
-                }
-            }
+        if self.provider_id == ProviderId::openai() {
+            return self.merge_openai_usage(extract_response_usage(body));
+        }
+        if self.provider_id == ProviderId::anthropic() {
+            return self.merge_anthropic_usage(extract_message_usage(body));
+        }
+        if self.provider_id == ProviderId::vertex() {
+            return self.merge_vertex_usage(extract_generate_content_usage(body));
+        }
+        if self.provider_id == ProviderId::azure() {
+            return self.merge_azure_usage(extract_response_usage(body));
+        }
+        if self.provider_id == ProviderId::bedrock() {
+            return self
+                .merge_bedrock_usage(extract_invocation_usage(self.model_id.as_ref(), body));
         }
 
-        metadata
+        self.base_metadata.clone()
     }
 
-    /// Process Bedrock response and extract metadata
-    fn process_bedrock_response(
-        &self,
-        model_id: &ModelId,
-        json_value: &Value,
-    ) -> Option<ProviderMetadata> {
-        let mut metadata = self.base_metadata.clone();
+    /// Merge model and usage found in an OpenAI body into the base metadata
+    fn merge_openai_usage(&self, usage: ResponseUsage) -> ProviderMetadata {
+        let metadata = self.base_metadata.clone();
+        ProviderMetadata {
+            model_id: usage.model_id.or(metadata.model_id),
+            request_tokens: usage.input_tokens.or(metadata.request_tokens),
+            response_tokens: usage.output_tokens.or(metadata.response_tokens),
+            total_tokens: usage.total_tokens.or(metadata.total_tokens),
+            ..metadata
+        }
+    }
 
-        // Determine model family
-        let model_family = ModelFamily::from_model_id(model_id);
+    /// Merge model, token and cache usage found in an Anthropic body
+    fn merge_anthropic_usage(&self, usage: MessageUsage) -> ProviderMetadata {
+        let metadata = self.base_metadata.clone();
+        ProviderMetadata {
+            model_id: usage.model_id.clone().or(metadata.model_id),
+            request_tokens: usage.input_tokens.or(metadata.request_tokens),
+            response_tokens: usage.output_tokens.or(metadata.response_tokens),
+            total_tokens: usage.total_tokens().or(metadata.total_tokens),
+            cache_read_tokens: usage.cache_read_tokens.or(metadata.cache_read_tokens),
+            cache_write_tokens: usage.cache_write_tokens.or(metadata.cache_write_tokens),
+            ..metadata
+        }
+    }
 
-        // Extract token usage based on model family
-        if let Some(token_usage) = extract_token_usage(&model_family, json_value) {
-            metadata.request_tokens = Some(token_usage.input_tokens);
-            metadata.response_tokens = Some(token_usage.output_tokens);
-            metadata.total_tokens = Some(token_usage.total_tokens);
+    /// Merge token usage from an Azure OpenAI body
+    ///
+    /// The body's `model` is ignored; the deployment mapping already set the
+    /// real model in the base metadata.
+    fn merge_azure_usage(&self, usage: ResponseUsage) -> ProviderMetadata {
+        self.merge_openai_usage(ResponseUsage {
+            model_id: None,
+            ..usage
+        })
+    }
+
+    /// Merge the model version and usage found in a Vertex AI body
+    fn merge_vertex_usage(&self, usage: GenerateContentUsage) -> ProviderMetadata {
+        let metadata = self.base_metadata.clone();
+        ProviderMetadata {
+            model_id: usage.model_id.or(metadata.model_id),
+            request_tokens: usage.input_tokens.or(metadata.request_tokens),
+            response_tokens: usage.output_tokens.or(metadata.response_tokens),
+            total_tokens: usage.total_tokens.or(metadata.total_tokens),
+            cache_read_tokens: usage.cache_read_tokens.or(metadata.cache_read_tokens),
+            ..metadata
         }
+    }
 
-        Some(metadata)
+    /// Merge token usage and stop reason from any Bedrock endpoint
+    ///
+    /// The model comes from the request path, so the base metadata keeps it.
+    fn merge_bedrock_usage(&self, usage: InvocationUsage) -> ProviderMetadata {
+        let metadata = self.base_metadata.clone();
+        ProviderMetadata {
+            request_tokens: usage.input_tokens.or(metadata.request_tokens),
+            response_tokens: usage.output_tokens.or(metadata.response_tokens),
+            total_tokens: usage.total_tokens.or(metadata.total_tokens),
+            cache_read_tokens: usage.cache_read_tokens.or(metadata.cache_read_tokens),
+            cache_write_tokens: usage.cache_write_tokens.or(metadata.cache_write_tokens),
+            stop_reason: usage.stop_reason.or(metadata.stop_reason),
+            ..metadata
+        }
     }
 }
 
@@ -165,6 +219,31 @@ mod tests {
         assert_eq!(metadata.total_tokens.unwrap().into_inner(), 32);
     }
 
+    #[test]
+    fn test_process_converse_stream_chunk() {
+        use crate::providers::bedrock::eventstream::encode_event;
+
+        let model_id = ModelId::try_new("amazon.nova-pro-v1:0".to_string()).unwrap();
+        let processor = ProviderResponseProcessor::new(ProviderMetadata {
+            provider_id: ProviderId::bedrock(),
+            model_id: Some(model_id.clone()),
+            ..Default::default()
+        });
+
+        let mut chunk = encode_event("messageStop", r#"{"stopReason":"tool_use"}"#);
+        chunk.extend(encode_event(
+            "metadata",
+            r#"{"usage":{"inputTokens":40,"outputTokens":12,"totalTokens":52}}"#,
+        ));
+        let metadata = processor
+            .process_body_chunk(&Bytes::from(chunk))
+            .expect("usage in chunk");
+
+        assert_eq!(metadata.model_id, Some(model_id));
+        assert_eq!(metadata.total_tokens.unwrap().into_inner(), 52);
+        assert_eq!(metadata.stop_reason.unwrap().as_ref(), "tool_use");
+    }
+
     #[test]
     fn test_process_invalid_json() {
         let base_metadata = ProviderMetadata {
@@ -184,4 +263,111 @@ mod tests {
         assert_eq!(metadata.request_tokens, None);
         assert_eq!(metadata.response_tokens, None);
     }
+
+    #[test]
+    fn test_process_openai_stream_without_model_id() {
+        let processor = ProviderResponseProcessor::new(ProviderMetadata {
+            provider_id: ProviderId::openai(),
+            ..Default::default()
+        });
+
+        let body = concat!(
+            "data: {\"model\":\"gpt-4o-mini\",\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n",
+            "data: {\"model\":\"gpt-4o-mini\",\"choices\":[],\"usage\":{\"prompt_tokens\":4,\"completion_tokens\":1,\"total_tokens\":5}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let metadata = processor.process_complete_body(body.as_bytes());
+
+        assert_eq!(
+            metadata.model_id,
+            Some(ModelId::try_new("gpt-4o-mini".to_string()).unwrap())
+        );
+        assert_eq!(
+            metadata.request_tokens,
+            Some(InputTokens::try_new(4).unwrap())
+        );
+        assert_eq!(
+            metadata.response_tokens,
+            Some(OutputTokens::try_new(1).unwrap())
+        );
+        assert_eq!(metadata.total_tokens.unwrap().into_inner(), 5);
+    }
+
+    #[test]
+    fn test_process_anthropic_stream_with_cache_tokens() {
+        let processor = ProviderResponseProcessor::new(ProviderMetadata {
+            provider_id: ProviderId::anthropic(),
+            ..Default::default()
+        });
+
+        let body = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-sonnet-4-20250514\",\"usage\":{\"input_tokens\":10,\"output_tokens\":1,\"cache_read_input_tokens\":300,\"cache_creation_input_tokens\":40}}}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":6}}\n\n",
+        );
+        let metadata = processor.process_complete_body(body.as_bytes());
+
+        assert_eq!(
+            metadata.model_id,
+            Some(ModelId::try_new("claude-sonnet-4-20250514".to_string()).unwrap())
+        );
+        assert_eq!(
+            metadata.request_tokens,
+            Some(InputTokens::try_new(10).unwrap())
+        );
+        assert_eq!(
+            metadata.response_tokens,
+            Some(OutputTokens::try_new(6).unwrap())
+        );
+        assert_eq!(metadata.total_tokens.unwrap().into_inner(), 16);
+        assert_eq!(metadata.cache_read_tokens.unwrap().into_inner(), 300);
+        assert_eq!(metadata.cache_write_tokens.unwrap().into_inner(), 40);
+    }
+
+    #[test]
+    fn test_process_vertex_stream_keeps_path_model_without_model_version() {
+        let path_model = ModelId::try_new("gemini-2.0-flash".to_string()).unwrap();
+        let processor = ProviderResponseProcessor::new(ProviderMetadata {
+            provider_id: ProviderId::vertex(),
+            model_id: Some(path_model.clone()),
+            ..Default::default()
+        });
+
+        let body = json!([
+            {"candidates": [], "usageMetadata": {"promptTokenCount": 3, "totalTokenCount": 3}},
+            {"candidates": [], "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 5, "totalTokenCount": 8}}
+        ]);
+        let metadata = processor.process_complete_body(body.to_string().as_bytes());
+
+        assert_eq!(metadata.model_id, Some(path_model));
+        assert_eq!(
+            metadata.request_tokens,
+            Some(InputTokens::try_new(3).unwrap())
+        );
+        assert_eq!(
+            metadata.response_tokens,
+            Some(OutputTokens::try_new(5).unwrap())
+        );
+        assert_eq!(metadata.total_tokens.unwrap().into_inner(), 8);
+    }
+
+    #[test]
+    fn test_process_azure_response_keeps_deployment_model() {
+        let deployment_model = ModelId::try_new("gpt-4o-2024-08-06".to_string()).unwrap();
+        let processor = ProviderResponseProcessor::new(ProviderMetadata {
+            provider_id: ProviderId::azure(),
+            model_id: Some(deployment_model.clone()),
+            ..Default::default()
+        });
+
+        let body = json!({
+            "model": "gpt-4o",
+            "usage": {"prompt_tokens": 7, "completion_tokens": 2, "total_tokens": 9}
+        });
+        let metadata = processor.process_complete_body(body.to_string().as_bytes());
+
+        assert_eq!(metadata.model_id, Some(deployment_model));
+        assert_eq!(metadata.total_tokens.unwrap().into_inner(), 9);
+    }
 }
@@ -0,0 +1,17 @@
+//! Server-sent event parsing shared by streaming providers
+
+use crate::providers::constants::sse;
+use serde_json::Value;
+
+/// Parse the JSON payload of every `data:` line in an SSE stream
+///
+/// `event:` lines, comments, non-JSON payloads and the `[DONE]` marker are
+/// skipped.
+pub(crate) fn sse_events(body: &[u8]) -> impl Iterator<Item = Value> + '_ {
+    body.split(|&b| b == b'\n')
+        .filter_map(|line| std::str::from_utf8(line).ok())
+        .filter_map(|line| line.trim().strip_prefix(sse::DATA_PREFIX))
+        .map(str::trim)
+        .filter(|data| *data != sse::DONE_MARKER)
+        .filter_map(|data| serde_json::from_str(data).ok())
+}
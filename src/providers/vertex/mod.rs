@@ -0,0 +1,18 @@
+//! Google Vertex AI provider implementation
+//!
+//! Serves Gemini models on Vertex AI behind the `/vertex/` prefix. The rest
+//! of the path is the regular Vertex resource path, and its `locations/{location}`
+//! segment picks the regional `aiplatform` endpoint.
+//!
+//! ## Features
+//!
+//! - OAuth bearer token pass-through
+//! - `generateContent` and `streamGenerateContent` (JSON array or `alt=sse`)
+//! - Model from the request path, refined by the response `modelVersion`
+//! - Token usage from `usageMetadata`
+
+pub mod provider;
+pub mod types;
+pub mod usage;
+
+pub use provider::VertexProvider;
@@ -0,0 +1,259 @@
+//! Google Vertex AI provider implementation
+
+use crate::providers::bedrock::provider::{BaseUrl, PathPrefix};
+use crate::providers::vertex::types::VertexModelPath;
+use crate::providers::{
+    passthrough::{self, PathPrefixed},
+    HealthStatus, Provider, ProviderError, ProviderId, ProviderMetadata,
+};
+use crate::proxy::http::{UpstreamClient, BEARER_PREFIX};
+use async_trait::async_trait;
+use axum::body::Body;
+use hyper::{header, HeaderMap, Request, Response, Uri};
+
+/// Google Vertex AI provider for Gemini models
+pub struct VertexProvider {
+    /// Fixed endpoint; `None` routes each request to its location's endpoint
+    base_url: Option<BaseUrl>,
+    path_prefix: PathPrefix,
+}
+
+impl VertexProvider {
+    /// Create a provider that routes to regional `aiplatform` endpoints
+    pub fn new() -> Self {
+        Self {
+            base_url: None,
+            path_prefix: PathPrefix::vertex(),
+        }
+    }
+
+    /// Create a provider that sends every location to one endpoint (for
+    /// testing or Private Service Connect)
+    pub fn with_base_url(base_url: String) -> Result<Self, ProviderError> {
+        Ok(Self {
+            base_url: Some(passthrough::base_url(base_url)?),
+            ..Self::new()
+        })
+    }
+
+    /// Build the target URL from the location in the path
+    fn build_target_url(&self, uri: &Uri) -> Result<Uri, ProviderError> {
+        let vertex_path = passthrough::strip_prefix(&self.path_prefix, uri)?;
+        let model_path = VertexModelPath::parse(vertex_path.split('?').next().unwrap_or(""))?;
+
+        match &self.base_url {
+            Some(base_url) => passthrough::target_url(base_url.as_ref(), vertex_path),
+            None => passthrough::target_url(
+                &format!("https://{}", model_path.location.api_host()),
+                vertex_path,
+            ),
+        }
+    }
+}
+
+impl Default for VertexProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Vertex AI needs a Google OAuth access token, passed through untouched
+fn validate_oauth_bearer(request_headers: &HeaderMap) -> Result<(), ProviderError> {
+    match request_headers
+        .get(header::AUTHORIZATION)
+        .map(|value| value.to_str())
+    {
+        Some(Ok(auth)) if auth.starts_with(BEARER_PREFIX) => Ok(()),
+        _ => Err(ProviderError::AuthenticationError(
+            "Vertex AI requests must carry an OAuth Bearer token".to_string(),
+        )),
+    }
+}
+
+impl PathPrefixed for VertexProvider {
+    fn path_prefix_mut(&mut self) -> &mut PathPrefix {
+        &mut self.path_prefix
+    }
+}
+
+#[async_trait]
+impl Provider for VertexProvider {
+    fn id(&self) -> ProviderId {
+        ProviderId::vertex()
+    }
+
+    fn matches_path(&self, path: &str) -> bool {
+        self.path_prefix.matches(path)
+    }
+
+    fn transform_url(&self, url: &Uri) -> Result<Uri, ProviderError> {
+        self.build_target_url(url)
+    }
+
+    async fn forward_request(
+        &self,
+        request: Request<Body>,
+        client: &UpstreamClient,
+    ) -> Result<Response<Body>, ProviderError> {
+        let (parts, body) = request.into_parts();
+
+        validate_oauth_bearer(&parts.headers)?;
+
+        let uri = self.transform_url(&parts.uri)?;
+        passthrough::forward(client, parts, body, uri).await
+    }
+
+    fn extract_metadata(
+        &self,
+        request: &Request<Body>,
+        _response: &Response<Body>,
+    ) -> ProviderMetadata {
+        // The path names the model; `ProviderResponseProcessor` refines it
+        // with `modelVersion` and adds token usage from the body
+        let model_id = passthrough::strip_prefix(&self.path_prefix, request.uri())
+            .and_then(|path| VertexModelPath::parse(path.split('?').next().unwrap_or("")))
+            .map(|model_path| model_path.model_id)
+            .ok();
+
+        ProviderMetadata {
+            provider_id: self.id(),
+            model_id,
+            ..Default::default()
+        }
+    }
+
+    async fn health_check(&self, _client: &UpstreamClient) -> HealthStatus {
+        passthrough::configured_health()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::bedrock::types::ModelId;
+    use crate::proxy::http::build_upstream_client;
+    use crate::proxy::types::UpstreamTlsConfig;
+    use mockito::{Matcher, Server};
+
+    const GENERATE_PATH: &str = "/vertex/v1/projects/acme/locations/us-central1/publishers/google/models/gemini-2.0-flash:generateContent";
+
+    #[test]
+    fn test_provider_id() {
+        assert_eq!(VertexProvider::new().id(), ProviderId::vertex());
+    }
+
+    #[test]
+    fn test_matches_path() {
+        let provider = VertexProvider::new();
+
+        assert!(provider.matches_path(GENERATE_PATH));
+        assert!(!provider.matches_path("/openai/v1/chat/completions"));
+        assert!(!provider.matches_path("/bedrock/model/claude-3/invoke"));
+    }
+
+    #[test]
+    fn test_transform_url_uses_regional_endpoint() {
+        let provider = VertexProvider::new();
+
+        let regional: Uri = GENERATE_PATH.parse().unwrap();
+        assert_eq!(
+            provider.transform_url(&regional).unwrap().to_string(),
+            "https://us-central1-aiplatform.googleapis.com/v1/projects/acme/locations/us-central1/publishers/google/models/gemini-2.0-flash:generateContent"
+        );
+
+        let global: Uri = "/vertex/v1/projects/acme/locations/global/publishers/google/models/gemini-2.5-pro:streamGenerateContent?alt=sse"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            provider.transform_url(&global).unwrap().to_string(),
+            "https://aiplatform.googleapis.com/v1/projects/acme/locations/global/publishers/google/models/gemini-2.5-pro:streamGenerateContent?alt=sse"
+        );
+    }
+
+    #[test]
+    fn test_transform_url_rejects_unsupported_method() {
+        let provider = VertexProvider::new();
+        let uri: Uri = "/vertex/v1/projects/acme/locations/us-central1/publishers/google/models/gemini-2.0-flash:predict"
+            .parse()
+            .unwrap();
+
+        assert!(matches!(
+            provider.transform_url(&uri),
+            Err(ProviderError::InvalidPath(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_forwards_oauth_token_and_reports_path_model() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock(
+                "POST",
+                "/v1/projects/acme/locations/us-central1/publishers/google/models/gemini-2.0-flash:generateContent",
+            )
+            .match_header("authorization", "Bearer ya29.token")
+            .match_header("host", Matcher::Regex(r"^127\.0\.0\.1:\d+$".to_string()))
+            .with_status(200)
+            .with_body(r#"{"candidates":[],"usageMetadata":{"promptTokenCount":1,"totalTokenCount":1}}"#)
+            .create_async()
+            .await;
+        let provider = VertexProvider::with_base_url(server.url()).unwrap();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(GENERATE_PATH)
+            .header("host", "proxy.internal:8080")
+            .header("authorization", "Bearer ya29.token")
+            .body(Body::from(r#"{"contents":[]}"#))
+            .unwrap();
+        let metadata_request = Request::builder()
+            .uri(GENERATE_PATH)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = provider.forward_request(request, &client).await.unwrap();
+
+        mock.assert_async().await;
+        let metadata = provider.extract_metadata(&metadata_request, &response);
+        assert_eq!(metadata.provider_id, ProviderId::vertex());
+        assert_eq!(
+            metadata.model_id,
+            Some(ModelId::try_new("gemini-2.0-flash".to_string()).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_metadata_names_the_model_of_streaming_requests() {
+        let provider = VertexProvider::new();
+        let request = Request::builder()
+            .uri("/vertex/v1/projects/acme/locations/us-central1/publishers/google/models/gemini-2.0-flash:streamGenerateContent?alt=sse")
+            .body(Body::empty())
+            .unwrap();
+        let response = Response::new(Body::empty());
+
+        let metadata = provider.extract_metadata(&request, &response);
+
+        assert_eq!(
+            metadata.model_id,
+            Some(ModelId::try_new("gemini-2.0-flash".to_string()).unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_request_without_bearer_token() {
+        let provider = VertexProvider::new();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(GENERATE_PATH)
+            .body(Body::empty())
+            .unwrap();
+
+        assert!(matches!(
+            provider.forward_request(request, &client).await,
+            Err(ProviderError::AuthenticationError(_))
+        ));
+    }
+}
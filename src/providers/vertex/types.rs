@@ -0,0 +1,140 @@
+//! Type definitions for the Vertex AI provider
+
+use crate::providers::bedrock::types::ModelId;
+use crate::providers::constants::paths;
+use crate::providers::ProviderError;
+use nutype::nutype;
+
+/// Google Cloud location, such as `us-central1` or `global`
+#[nutype(
+    sanitize(trim, lowercase),
+    validate(not_empty, regex = r"^[a-z]+(-[a-z]+\d+)?$"),
+    derive(Debug, Clone, PartialEq, AsRef)
+)]
+pub struct GcpLocation(String);
+
+impl GcpLocation {
+    /// Host serving this location
+    ///
+    /// `global` uses the bare API host; every other location has a regional one.
+    pub fn api_host(&self) -> String {
+        if self.as_ref() == paths::vertex::GLOBAL_LOCATION {
+            paths::vertex::API_HOST.to_string()
+        } else {
+            format!("{}-{}", self.as_ref(), paths::vertex::API_HOST)
+        }
+    }
+}
+
+/// Vertex AI methods the proxy forwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VertexEndpoint {
+    GenerateContent,
+    StreamGenerateContent,
+}
+
+impl VertexEndpoint {
+    fn from_method(method: &str) -> Option<Self> {
+        match method {
+            paths::vertex::GENERATE_CONTENT => Some(Self::GenerateContent),
+            paths::vertex::STREAM_GENERATE_CONTENT => Some(Self::StreamGenerateContent),
+            _ => None,
+        }
+    }
+}
+
+/// The parts of a Vertex model path the proxy routes on
+///
+/// Paths look like
+/// `/v1/projects/{project}/locations/{location}/publishers/google/models/{model}:{method}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VertexModelPath {
+    pub location: GcpLocation,
+    pub model_id: ModelId,
+    pub endpoint: VertexEndpoint,
+}
+
+impl VertexModelPath {
+    /// Parse a path with the `/vertex` prefix already removed
+    pub fn parse(path: &str) -> Result<Self, ProviderError> {
+        let segments: Vec<&str> = path.split('/').collect();
+        let after = |name: &str| {
+            segments
+                .iter()
+                .position(|&s| s == name)
+                .and_then(|i| segments.get(i + 1))
+                .copied()
+        };
+
+        let location = after(paths::vertex::LOCATIONS_SEGMENT)
+            .and_then(|s| GcpLocation::try_new(s.to_string()).ok())
+            .ok_or_else(|| {
+                ProviderError::InvalidPath("Vertex path is missing a valid location".to_string())
+            })?;
+
+        let (model, method) = after(paths::vertex::MODELS_SEGMENT)
+            .and_then(|s| s.split_once(paths::vertex::METHOD_SEPARATOR))
+            .ok_or_else(|| {
+                ProviderError::InvalidPath("Vertex path is missing models/{model}:{method}".into())
+            })?;
+
+        let model_id = ModelId::try_new(model.to_string())
+            .map_err(|_| ProviderError::InvalidPath("Vertex model is empty".to_string()))?;
+        let endpoint = VertexEndpoint::from_method(method).ok_or_else(|| {
+            ProviderError::InvalidPath(format!("Unsupported Vertex AI method: {method}"))
+        })?;
+
+        Ok(Self {
+            location,
+            model_id,
+            endpoint,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GEMINI_PATH: &str =
+        "/v1/projects/acme/locations/europe-west4/publishers/google/models/gemini-2.0-flash:streamGenerateContent";
+
+    #[test]
+    fn parses_location_model_and_method() {
+        let parsed = VertexModelPath::parse(GEMINI_PATH).unwrap();
+
+        assert_eq!(parsed.location.as_ref(), "europe-west4");
+        assert_eq!(
+            parsed.model_id,
+            ModelId::try_new("gemini-2.0-flash".to_string()).unwrap()
+        );
+        assert_eq!(parsed.endpoint, VertexEndpoint::StreamGenerateContent);
+    }
+
+    #[test]
+    fn regional_and_global_hosts() {
+        let regional = GcpLocation::try_new("us-central1".to_string()).unwrap();
+        let global = GcpLocation::try_new("global".to_string()).unwrap();
+
+        assert_eq!(regional.api_host(), "us-central1-aiplatform.googleapis.com");
+        assert_eq!(global.api_host(), "aiplatform.googleapis.com");
+    }
+
+    #[test]
+    fn rejects_paths_without_location_or_supported_method() {
+        for path in [
+            "/v1/projects/acme/publishers/google/models/gemini-2.0-flash:generateContent",
+            "/v1/projects/acme/locations/us-central1/publishers/google/models/gemini-2.0-flash",
+            "/v1/projects/acme/locations/us-central1/publishers/google/models/gemini-2.0-flash:predict",
+            "/v1/projects/acme/locations/../publishers/google/models/gemini:generateContent",
+        ] {
+            assert!(
+                matches!(
+                    VertexModelPath::parse(path),
+                    Err(ProviderError::InvalidPath(_))
+                ),
+                "{path}"
+            );
+        }
+    }
+}
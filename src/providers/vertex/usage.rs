@@ -0,0 +1,153 @@
+//! Model and token usage extraction for Vertex AI responses
+//!
+//! `generateContent` returns one JSON object. `streamGenerateContent` returns
+//! a JSON array of the same objects, or SSE `data:` lines with `alt=sse`.
+//! Every chunk may carry `usageMetadata`; the last one holds the final counts.
+
+use crate::providers::bedrock::types::{
+    CacheReadTokens, InputTokens, ModelId, OutputTokens, TotalTokens,
+};
+use crate::providers::constants::json_fields;
+use crate::providers::sse::sse_events;
+use serde_json::Value;
+
+/// Model and token counts reported by a Vertex AI response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GenerateContentUsage {
+    pub model_id: Option<ModelId>,
+    pub input_tokens: Option<InputTokens>,
+    pub output_tokens: Option<OutputTokens>,
+    pub total_tokens: Option<TotalTokens>,
+    pub cache_read_tokens: Option<CacheReadTokens>,
+}
+
+impl GenerateContentUsage {
+    /// True when nothing could be extracted
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Extract model version and usage from any `generateContent` response shape
+pub fn extract_generate_content_usage(body: &[u8]) -> GenerateContentUsage {
+    let mut usage = GenerateContentUsage::default();
+
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Array(chunks)) => {
+            for chunk in &chunks {
+                merge_chunk(&mut usage, chunk);
+            }
+        }
+        Ok(json) => merge_chunk(&mut usage, &json),
+        Err(_) => {
+            for json in sse_events(body) {
+                merge_chunk(&mut usage, &json);
+            }
+        }
+    }
+
+    usage
+}
+
+fn merge_chunk(usage: &mut GenerateContentUsage, chunk: &Value) {
+    if let Some(model_id) = chunk
+        .get(json_fields::vertex::MODEL_VERSION)
+        .and_then(Value::as_str)
+        .and_then(|model| ModelId::try_new(model.to_string()).ok())
+    {
+        usage.model_id = Some(model_id);
+    }
+
+    let Some(counts) = chunk
+        .get(json_fields::vertex::USAGE_METADATA)
+        .filter(|u| u.is_object())
+    else {
+        return;
+    };
+
+    usage.input_tokens = count(counts, json_fields::vertex::PROMPT_TOKEN_COUNT)
+        .and_then(|n| InputTokens::try_new(n).ok());
+    usage.output_tokens = count(counts, json_fields::vertex::CANDIDATES_TOKEN_COUNT)
+        .and_then(|n| OutputTokens::try_new(n).ok());
+    usage.total_tokens = count(counts, json_fields::vertex::TOTAL_TOKEN_COUNT)
+        .and_then(|n| TotalTokens::try_new(n).ok());
+    usage.cache_read_tokens = count(counts, json_fields::vertex::CACHED_CONTENT_TOKEN_COUNT)
+        .and_then(|n| CacheReadTokens::try_new(n).ok());
+}
+
+fn count(usage: &Value, field: &str) -> Option<u32> {
+    usage
+        .get(field)?
+        .as_u64()
+        .and_then(|n| u32::try_from(n).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tokens(usage: &GenerateContentUsage) -> (Option<u32>, Option<u32>, Option<u32>) {
+        (
+            usage.input_tokens.map(|t| t.into_inner()),
+            usage.output_tokens.map(|t| t.into_inner()),
+            usage.total_tokens.map(|t| t.into_inner()),
+        )
+    }
+
+    #[test]
+    fn extracts_generate_content_usage() {
+        let body = json!({
+            "candidates": [{"content": {"role": "model", "parts": [{"text": "Hi"}]}}],
+            "usageMetadata": {
+                "promptTokenCount": 11,
+                "candidatesTokenCount": 2,
+                "totalTokenCount": 13,
+                "cachedContentTokenCount": 8
+            },
+            "modelVersion": "gemini-2.0-flash-001"
+        });
+
+        let usage = extract_generate_content_usage(body.to_string().as_bytes());
+
+        assert_eq!(
+            usage.model_id.clone().map(|m| m.into_inner()).as_deref(),
+            Some("gemini-2.0-flash-001")
+        );
+        assert_eq!(tokens(&usage), (Some(11), Some(2), Some(13)));
+        assert_eq!(usage.cache_read_tokens.map(|t| t.into_inner()), Some(8));
+    }
+
+    #[test]
+    fn last_chunk_of_json_array_stream_wins() {
+        let body = json!([
+            {"candidates": [], "usageMetadata": {"promptTokenCount": 6, "totalTokenCount": 6}},
+            {"candidates": [], "usageMetadata": {"promptTokenCount": 6, "candidatesTokenCount": 9, "totalTokenCount": 15}, "modelVersion": "gemini-1.5-pro-002"}
+        ]);
+
+        let usage = extract_generate_content_usage(body.to_string().as_bytes());
+
+        assert_eq!(tokens(&usage), (Some(6), Some(9), Some(15)));
+    }
+
+    #[test]
+    fn extracts_usage_from_sse_stream() {
+        let body = concat!(
+            "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"He\"}]}}],\"usageMetadata\":{\"promptTokenCount\":4,\"totalTokenCount\":4}}\r\n\r\n",
+            "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"llo\"}]}}],\"usageMetadata\":{\"promptTokenCount\":4,\"candidatesTokenCount\":3,\"totalTokenCount\":7},\"modelVersion\":\"gemini-2.0-flash\"}\r\n\r\n",
+        );
+
+        let usage = extract_generate_content_usage(body.as_bytes());
+
+        assert!(usage.model_id.is_some());
+        assert_eq!(tokens(&usage), (Some(4), Some(3), Some(7)));
+        assert_eq!(usage.cache_read_tokens, None);
+    }
+
+    #[test]
+    fn error_body_is_empty() {
+        let body = json!({"error": {"code": 429, "message": "Quota exceeded", "status": "RESOURCE_EXHAUSTED"}});
+
+        assert!(extract_generate_content_usage(body.to_string().as_bytes()).is_empty());
+    }
+}
@@ -0,0 +1,638 @@
+//! Encoding of audit events carried by the ring buffer and spill log
+//!
+//! The first byte of every payload names its format, so payloads written by
+//! an older release can still be read back after an upgrade (and the other
+//! way round while `audit_format` is pinned to JSON during a rollout):
+//!
+//! - `{` — a JSON document, the original format
+//! - `0x01` — compact binary, version 1
+//!
+//! The binary format writes integers and lengths as LEB128 varints, UUIDs as
+//! 16 raw bytes, timestamps as seconds and nanoseconds since the epoch, and
+//! body bytes as they are. A payload is a format tag, the request and session
+//! IDs, the timestamp, then an event kind byte followed by the fields of that
+//! kind in declaration order.
+
+use crate::proxy::types::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// First byte of a JSON payload
+const FORMAT_JSON: u8 = b'{';
+
+/// First byte of a version 1 binary payload
+const FORMAT_BINARY_V1: u8 = 0x01;
+
+/// Bytes in a binary payload before the event fields
+const BINARY_HEADER_SIZE: usize = 1 + 16 + 16 + 12 + 1;
+
+/// Format audit events are written to the ring buffer in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventFormat {
+    /// JSON, readable by every release
+    Json,
+    /// Compact binary; readable by releases that understand the format tag
+    #[default]
+    Binary,
+}
+
+/// Errors decoding an audit event payload
+#[derive(Debug, Error)]
+pub enum AuditCodecError {
+    #[error("Unknown audit event format tag {0:#04x}")]
+    UnknownFormat(u8),
+    #[error("Audit event payload is empty")]
+    Empty,
+    #[error("Audit event payload ends early")]
+    Truncated,
+    #[error("Unknown audit event kind {0}")]
+    UnknownKind(u8),
+    #[error("Invalid {0} in audit event")]
+    InvalidField(&'static str),
+    #[error("JSON audit event: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl AuditEventFormat {
+    /// Encode `event` in this format
+    pub fn encode(self, event: &AuditEvent) -> Result<Vec<u8>, AuditCodecError> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(event)?),
+            Self::Binary => Ok(encode_binary(event)),
+        }
+    }
+}
+
+/// Decode an audit event written in any supported format
+pub fn decode_audit_event(data: &[u8]) -> Result<AuditEvent, AuditCodecError> {
+    match data.first() {
+        None => Err(AuditCodecError::Empty),
+        Some(&FORMAT_JSON) => Ok(serde_json::from_slice(data)?),
+        Some(&FORMAT_BINARY_V1) => decode_binary(&data[1..]),
+        Some(&tag) => Err(AuditCodecError::UnknownFormat(tag)),
+    }
+}
+
+fn encode_binary(event: &AuditEvent) -> Vec<u8> {
+    let mut out = Writer(Vec::with_capacity(
+        BINARY_HEADER_SIZE + payload_size_hint(&event.event_type),
+    ));
+    out.u8(FORMAT_BINARY_V1);
+    out.uuid(event.request_id.as_ref());
+    out.uuid(event.session_id.as_ref());
+    out.timestamp(&event.timestamp);
+
+    match &event.event_type {
+        AuditEventType::RequestReceived {
+            method,
+            uri,
+            headers,
+            body_size,
+        } => {
+            out.u8(0);
+            out.str(method.as_ref());
+            out.str(uri.as_ref());
+            out.headers(headers);
+            out.varint(*body_size.as_ref() as u64);
+        }
+        AuditEventType::RequestForwarded {
+            target_url,
+            start_time,
+        } => {
+            out.u8(1);
+            out.str(target_url.as_ref());
+            out.timestamp(start_time);
+        }
+        AuditEventType::ResponseReceived {
+            status,
+            headers,
+            body_size,
+            duration_ms,
+        } => {
+            out.u8(2);
+            out.varint(u64::from(*status.as_ref()));
+            out.headers(headers);
+            out.varint(*body_size.as_ref() as u64);
+            out.varint(*duration_ms.as_ref());
+        }
+        AuditEventType::ResponseReturned { duration_ms } => {
+            out.u8(3);
+            out.varint(*duration_ms.as_ref());
+        }
+        AuditEventType::RequestBody { content, truncated } => {
+            out.u8(4);
+            out.bytes(content);
+            out.bool(*truncated);
+        }
+        AuditEventType::ResponseBody { content, truncated } => {
+            out.u8(5);
+            out.bytes(content);
+            out.bool(*truncated);
+        }
+        AuditEventType::RequestChunk { offset, data } => {
+            out.u8(6);
+            out.varint(*offset.as_ref() as u64);
+            out.bytes(data);
+        }
+        AuditEventType::ResponseChunk { offset, data } => {
+            out.u8(7);
+            out.varint(*offset.as_ref() as u64);
+            out.bytes(data);
+        }
+        AuditEventType::RequestBodyComplete {
+            body_size,
+            truncated,
+        } => {
+            out.u8(8);
+            out.varint(*body_size.as_ref() as u64);
+            out.bool(*truncated);
+        }
+        AuditEventType::ResponseBodyComplete {
+            body_size,
+            truncated,
+        } => {
+            out.u8(9);
+            out.varint(*body_size.as_ref() as u64);
+            out.bool(*truncated);
+        }
+        AuditEventType::SessionContextReceived { context } => {
+            out.u8(10);
+            out.optional_str(context.session_id.as_deref());
+            out.optional_str(context.parent_id.as_deref());
+            out.optional_str(context.user_id.as_deref());
+            out.optional_str(context.application_id.as_deref());
+            out.optional_str(context.environment.as_deref());
+            out.varint(context.metadata.len() as u64);
+            for (key, value) in &context.metadata {
+                out.str(key);
+                out.str(value);
+            }
+            // Arbitrary JSON has no fixed shape, so it travels as its text
+            let application_context = context
+                .application_context
+                .as_ref()
+                .map(|value| value.to_string());
+            out.optional_str(application_context.as_deref());
+        }
+        AuditEventType::ProviderResolved {
+            provider_id,
+            model_id,
+        } => {
+            out.u8(12);
+            out.str(provider_id);
+            out.optional_str(model_id.as_deref());
+        }
+        AuditEventType::Error { error, phase } => {
+            out.u8(11);
+            out.str(error);
+            out.u8(match phase {
+                ErrorPhase::RequestParsing => 0,
+                ErrorPhase::RequestForwarding => 1,
+                ErrorPhase::ResponseReceiving => 2,
+                ErrorPhase::ResponseReturning => 3,
+                ErrorPhase::AuditRecording => 4,
+            });
+        }
+    }
+
+    out.0
+}
+
+fn decode_binary(data: &[u8]) -> Result<AuditEvent, AuditCodecError> {
+    let mut input = Reader(data);
+    let request_id = RequestId::try_new(input.uuid()?)
+        .map_err(|_| AuditCodecError::InvalidField("request ID"))?;
+    let session_id = SessionId::try_new(input.uuid()?)
+        .map_err(|_| AuditCodecError::InvalidField("session ID"))?;
+    let timestamp = input.timestamp()?;
+
+    let event_type = match input.u8()? {
+        0 => AuditEventType::RequestReceived {
+            method: HttpMethod::try_new(input.string()?)
+                .map_err(|_| AuditCodecError::InvalidField("HTTP method"))?,
+            uri: RequestUri::try_new(input.string()?)
+                .map_err(|_| AuditCodecError::InvalidField("request URI"))?,
+            headers: input.headers()?,
+            body_size: BodySize::from(input.usize()?),
+        },
+        1 => AuditEventType::RequestForwarded {
+            target_url: TargetUrl::try_new(input.string()?)
+                .map_err(|_| AuditCodecError::InvalidField("target URL"))?,
+            start_time: input.timestamp()?,
+        },
+        2 => AuditEventType::ResponseReceived {
+            status: u16::try_from(input.varint()?)
+                .ok()
+                .and_then(|code| HttpStatusCode::try_new(code).ok())
+                .ok_or(AuditCodecError::InvalidField("HTTP status"))?,
+            headers: input.headers()?,
+            body_size: BodySize::from(input.usize()?),
+            duration_ms: DurationMillis::from(input.varint()?),
+        },
+        3 => AuditEventType::ResponseReturned {
+            duration_ms: DurationMillis::from(input.varint()?),
+        },
+        4 => AuditEventType::RequestBody {
+            content: input.bytes()?.to_vec(),
+            truncated: input.bool()?,
+        },
+        5 => AuditEventType::ResponseBody {
+            content: input.bytes()?.to_vec(),
+            truncated: input.bool()?,
+        },
+        6 => AuditEventType::RequestChunk {
+            offset: ChunkOffset::from(input.usize()?),
+            data: input.bytes()?.to_vec(),
+        },
+        7 => AuditEventType::ResponseChunk {
+            offset: ChunkOffset::from(input.usize()?),
+            data: input.bytes()?.to_vec(),
+        },
+        8 => AuditEventType::RequestBodyComplete {
+            body_size: BodySize::from(input.usize()?),
+            truncated: input.bool()?,
+        },
+        9 => AuditEventType::ResponseBodyComplete {
+            body_size: BodySize::from(input.usize()?),
+            truncated: input.bool()?,
+        },
+        10 => {
+            let session_id = input.optional_string()?;
+            let parent_id = input.optional_string()?;
+            let user_id = input.optional_string()?;
+            let application_id = input.optional_string()?;
+            let environment = input.optional_string()?;
+            let metadata = (0..input.len()?)
+                .map(|_| Ok((input.string()?, input.string()?)))
+                .collect::<Result<_, AuditCodecError>>()?;
+            let application_context = input
+                .optional_string()?
+                .map(|text| serde_json::from_str(&text))
+                .transpose()?;
+            AuditEventType::SessionContextReceived {
+                context: SessionContext {
+                    session_id,
+                    parent_id,
+                    user_id,
+                    application_id,
+                    environment,
+                    metadata,
+                    application_context,
+                },
+            }
+        }
+        11 => AuditEventType::Error {
+            error: input.string()?,
+            phase: match input.u8()? {
+                0 => ErrorPhase::RequestParsing,
+                1 => ErrorPhase::RequestForwarding,
+                2 => ErrorPhase::ResponseReceiving,
+                3 => ErrorPhase::ResponseReturning,
+                4 => ErrorPhase::AuditRecording,
+                _ => return Err(AuditCodecError::InvalidField("error phase")),
+            },
+        },
+        12 => AuditEventType::ProviderResolved {
+            provider_id: input.string()?,
+            model_id: input.optional_string()?,
+        },
+        kind => return Err(AuditCodecError::UnknownKind(kind)),
+    };
+
+    Ok(AuditEvent {
+        request_id,
+        session_id,
+        timestamp,
+        event_type,
+    })
+}
+
+/// Bytes needed for the variable part of an event, to size the output once
+fn payload_size_hint(event_type: &AuditEventType) -> usize {
+    match event_type {
+        AuditEventType::RequestBody { content, .. }
+        | AuditEventType::ResponseBody { content, .. } => content.len() + 11,
+        AuditEventType::RequestChunk { data, .. } | AuditEventType::ResponseChunk { data, .. } => {
+            data.len() + 20
+        }
+        _ => 256,
+    }
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.0.push(u8::from(value));
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn uuid(&mut self, value: &Uuid) {
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn timestamp(&mut self, value: &DateTime<Utc>) {
+        self.0.extend_from_slice(&value.timestamp().to_le_bytes());
+        self.0
+            .extend_from_slice(&value.timestamp_subsec_nanos().to_le_bytes());
+    }
+
+    fn bytes(&mut self, value: &[u8]) {
+        self.varint(value.len() as u64);
+        self.0.extend_from_slice(value);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.bytes(value.as_bytes());
+    }
+
+    fn optional_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.str(value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn headers(&mut self, headers: &Headers) {
+        self.varint(headers.as_vec().len() as u64);
+        for (name, value) in headers.as_vec() {
+            self.str(name.as_ref());
+            self.str(value.as_ref());
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AuditCodecError> {
+        if self.0.len() < len {
+            return Err(AuditCodecError::Truncated);
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], AuditCodecError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, AuditCodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, AuditCodecError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(AuditCodecError::InvalidField("flag")),
+        }
+    }
+
+    fn varint(&mut self) -> Result<u64, AuditCodecError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(AuditCodecError::InvalidField("integer"))
+    }
+
+    fn usize(&mut self) -> Result<usize, AuditCodecError> {
+        usize::try_from(self.varint()?).map_err(|_| AuditCodecError::InvalidField("integer"))
+    }
+
+    /// A length prefix; never more than the bytes left, so it is safe to allocate
+    fn len(&mut self) -> Result<usize, AuditCodecError> {
+        let len = self.usize()?;
+        if len > self.0.len() {
+            return Err(AuditCodecError::Truncated);
+        }
+        Ok(len)
+    }
+
+    fn uuid(&mut self) -> Result<Uuid, AuditCodecError> {
+        Ok(Uuid::from_bytes(self.array()?))
+    }
+
+    fn timestamp(&mut self) -> Result<DateTime<Utc>, AuditCodecError> {
+        let seconds = i64::from_le_bytes(self.array()?);
+        let nanos = u32::from_le_bytes(self.array()?);
+        DateTime::from_timestamp(seconds, nanos).ok_or(AuditCodecError::InvalidField("timestamp"))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], AuditCodecError> {
+        let len = self.len()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, AuditCodecError> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| AuditCodecError::InvalidField("text"))
+    }
+
+    fn optional_string(&mut self) -> Result<Option<String>, AuditCodecError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => self.string().map(Some),
+            _ => Err(AuditCodecError::InvalidField("optional text")),
+        }
+    }
+
+    fn headers(&mut self) -> Result<Headers, AuditCodecError> {
+        let pairs = (0..self.len()?)
+            .map(|_| Ok((self.string()?, self.string()?)))
+            .collect::<Result<Vec<_>, AuditCodecError>>()?;
+        Headers::from_vec(pairs).map_err(|_| AuditCodecError::InvalidField("header"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: AuditEventType) -> AuditEvent {
+        AuditEvent {
+            request_id: RequestId::new(),
+            session_id: SessionId::new(),
+            timestamp: Utc::now(),
+            event_type,
+        }
+    }
+
+    fn round_trip(event: &AuditEvent) -> AuditEvent {
+        let encoded = AuditEventFormat::Binary.encode(event).unwrap();
+        assert_eq!(encoded[0], FORMAT_BINARY_V1);
+        decode_audit_event(&encoded).unwrap()
+    }
+
+    fn all_event_types() -> Vec<AuditEventType> {
+        let headers = Headers::from_vec(vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("x-trace".to_string(), "ünïcødé".to_string()),
+        ])
+        .unwrap();
+        vec![
+            AuditEventType::RequestReceived {
+                method: HttpMethod::try_new("POST".to_string()).unwrap(),
+                uri: RequestUri::try_new("/v1/chat/completions".to_string()).unwrap(),
+                headers: headers.clone(),
+                body_size: BodySize::from(1024),
+            },
+            AuditEventType::RequestForwarded {
+                target_url: TargetUrl::try_new("https://api.openai.com".to_string()).unwrap(),
+                start_time: Utc::now(),
+            },
+            AuditEventType::ResponseReceived {
+                status: HttpStatusCode::try_new(201).unwrap(),
+                headers,
+                body_size: BodySize::from(usize::MAX),
+                duration_ms: DurationMillis::from(150),
+            },
+            AuditEventType::ResponseReturned {
+                duration_ms: DurationMillis::from(u64::MAX),
+            },
+            AuditEventType::RequestBody {
+                content: b"{\"model\":\"gpt-4\"}".to_vec(),
+                truncated: false,
+            },
+            AuditEventType::ResponseBody {
+                content: Vec::new(),
+                truncated: true,
+            },
+            AuditEventType::RequestChunk {
+                offset: ChunkOffset::from(0),
+                data: vec![0, 1, 2, 255],
+            },
+            AuditEventType::ResponseChunk {
+                offset: ChunkOffset::from(16_384),
+                data: (0..=255).collect(),
+            },
+            AuditEventType::RequestBodyComplete {
+                body_size: BodySize::from(4),
+                truncated: false,
+            },
+            AuditEventType::ResponseBodyComplete {
+                body_size: BodySize::from(256),
+                truncated: true,
+            },
+            AuditEventType::SessionContextReceived {
+                context: SessionContext {
+                    session_id: Some("conversation-17".to_string()),
+                    parent_id: None,
+                    user_id: Some("user-3".to_string()),
+                    application_id: Some("support-bot".to_string()),
+                    environment: None,
+                    metadata: vec![("team".to_string(), "search".to_string())],
+                    application_context: Some(serde_json::json!({"feature": ["a", 1]})),
+                },
+            },
+            AuditEventType::ProviderResolved {
+                provider_id: "azure".to_string(),
+                model_id: Some("openai.gpt-4".to_string()),
+            },
+            AuditEventType::Error {
+                error: "upstream reset".to_string(),
+                phase: ErrorPhase::ResponseReceiving,
+            },
+        ]
+    }
+
+    #[test]
+    fn every_event_type_round_trips_through_the_binary_format() {
+        for event_type in all_event_types() {
+            let original = event(event_type);
+            let decoded = round_trip(&original);
+
+            // Events have no equality, but their JSON form covers every field
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                serde_json::to_value(&original).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn json_payloads_are_still_decoded() {
+        let original = event(AuditEventType::ResponseReturned {
+            duration_ms: DurationMillis::from(42),
+        });
+        let encoded = AuditEventFormat::Json.encode(&original).unwrap();
+
+        let decoded = decode_audit_event(&encoded).unwrap();
+
+        assert_eq!(decoded.request_id, original.request_id);
+        assert!(matches!(
+            decoded.event_type,
+            AuditEventType::ResponseReturned { duration_ms } if *duration_ms.as_ref() == 42
+        ));
+    }
+
+    #[test]
+    fn binary_chunks_are_much_smaller_than_json() {
+        let chunk = event(AuditEventType::ResponseChunk {
+            offset: ChunkOffset::from(0),
+            data: vec![b'x'; BYTES_16KB],
+        });
+
+        let json = AuditEventFormat::Json.encode(&chunk).unwrap();
+        let binary = AuditEventFormat::Binary.encode(&chunk).unwrap();
+
+        assert!(binary.len() < BYTES_16KB + 64, "{}", binary.len());
+        assert!(json.len() > binary.len() * 5 / 4, "{}", json.len());
+    }
+
+    #[test]
+    fn malformed_payloads_are_rejected() {
+        assert!(matches!(
+            decode_audit_event(&[]),
+            Err(AuditCodecError::Empty)
+        ));
+        assert!(matches!(
+            decode_audit_event(&[0x7f, 0, 0]),
+            Err(AuditCodecError::UnknownFormat(0x7f))
+        ));
+
+        let encoded = AuditEventFormat::Binary
+            .encode(&event(AuditEventType::RequestChunk {
+                offset: ChunkOffset::from(0),
+                data: vec![1; 100],
+            }))
+            .unwrap();
+        for len in 1..encoded.len() {
+            assert!(
+                decode_audit_event(&encoded[..len]).is_err(),
+                "prefix of {len} bytes decoded"
+            );
+        }
+
+        let mut unknown_kind = encoded.clone();
+        unknown_kind[BINARY_HEADER_SIZE - 1] = 200;
+        assert!(matches!(
+            decode_audit_event(&unknown_kind),
+            Err(AuditCodecError::UnknownKind(200))
+        ));
+    }
+}
@@ -1,21 +1,103 @@
 //! Audit path implementation for processing events from the ring buffer
 
+use crate::adapters::llm_usage::calculate_cost;
 use crate::adapters::proxy_audit::convert_audit_event;
+use crate::domain::audit_types;
+use crate::domain::commands::{
+    RecordAuditEvent, RecordAuditEvents, RecordVersionUsage, RecordVersionUsages,
+};
+use crate::domain::config_types::{BatchSize, FlushIntervalMs};
+use crate::domain::llm::ModelVersion;
+use crate::domain::pricing::PricingCatalog;
+use crate::error::Error;
+use crate::infrastructure::dead_letters::{DeadLetterPhase, DeadLetterStore, NewDeadLetter};
 use crate::infrastructure::eventcore::service::EventCoreService;
 use crate::proxy::{
-    audit_steps::{AuditEffect, LogLevel, Observation, ProcessorState, Step},
+    audit_codec::{decode_audit_event, AuditEventFormat},
+    audit_steps::{
+        AuditCounters, AuditEffect, FailedAudit, LogLevel, Observation, ProcessorState, Step,
+    },
+    audit_tracing::{finish_batch, AuditSpans},
+    audit_usage::{ObservedBatch, RequestModels},
+    metrics::AuditMetrics,
+    otlp::Tracer,
     ring_buffer::RingBuffer,
-    types::*,
+    spill_log::SpillLog,
 };
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::task::{JoinError, JoinHandle};
+use tokio::time::Instant;
 use tracing::{debug, error, info, warn};
 
-/// Audit path processor that reads from the ring buffer
+/// How audit events are grouped into event store writes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuditBatching {
+    /// Events that trigger a write without waiting for the flush interval
+    pub batch_size: BatchSize,
+    /// Longest an event waits in a partial batch
+    pub flush_interval: FlushIntervalMs,
+}
+
+/// What every processor started by [`AuditPathProcessor::spawn_all`] works with
+///
+/// Each field matches one of the processor's `with_*` builders; left at the
+/// default, processors only count the events they read.
+#[derive(Clone, Default)]
+pub struct AuditPathConfig {
+    pub event_store: Option<Arc<EventCoreService>>,
+    /// Must have been opened with the ring buffer's shard count
+    pub spill: Option<Arc<SpillLog>>,
+    pub dead_letters: Option<Arc<dyn DeadLetterStore>>,
+    /// Must have a slot for every shard of the ring buffer
+    pub metrics: Option<Arc<AuditMetrics>>,
+    pub tracer: Option<Tracer>,
+    pub pricing: Option<Arc<PricingCatalog>>,
+    pub batching: AuditBatching,
+}
+
+impl AuditBatching {
+    fn flush_after(&self) -> Duration {
+        Duration::from_millis(*self.flush_interval.as_ref())
+    }
+}
+
+/// Audit path processor that reads one shard of the ring buffer
+///
+/// The processor parks while its shard is empty and is woken by writers.
+/// Events are persisted in batches of [`AuditBatching::batch_size`], or after
+/// [`AuditBatching::flush_interval`] when traffic is light. Run one processor
+/// per shard to persist in parallel; see [`AuditPathProcessor::spawn_all`].
+///
+/// With a spill log, the processor reads the shard's log once the ring is
+/// empty and acknowledges spilled events after they are persisted. While the
+/// event store is unavailable it diverts new events for its shard to the log.
+///
+/// With a dead-letter store, events that cannot be deserialized, converted or
+/// persisted are kept there instead of only being counted.
+///
+/// Captured request bodies are parsed as they are persisted, so deferred
+/// requests are recorded with their model.
+///
+/// With a tracer, each batch records a span for every traced request it
+/// persists events for; see [`crate::proxy::observability`].
+///
+/// With a pricing catalog, the usage reported by each response body is priced
+/// and recorded as a `CostCalculated` event alongside the body.
 pub struct AuditPathProcessor {
     ring_buffer: Arc<RingBuffer>,
+    spill: Option<Arc<SpillLog>>,
+    dead_letters: Option<Arc<dyn DeadLetterStore>>,
+    metrics: Option<Arc<AuditMetrics>>,
+    tracer: Option<Tracer>,
+    audit_spans: AuditSpans,
+    pricing: Option<Arc<PricingCatalog>>,
+    request_models: RequestModels,
+    shard: usize,
     shutdown_rx: mpsc::Receiver<()>,
     event_store: Option<Arc<EventCoreService>>,
+    batching: AuditBatching,
 }
 
 impl AuditPathProcessor {
@@ -25,8 +107,17 @@ impl AuditPathProcessor {
         (
             Self {
                 ring_buffer,
+                spill: None,
+                dead_letters: None,
+                metrics: None,
+                tracer: None,
+                audit_spans: AuditSpans::default(),
+                pricing: None,
+                request_models: RequestModels::default(),
+                shard: 0,
                 shutdown_rx,
                 event_store: None,
+                batching: AuditBatching::default(),
             },
             shutdown_tx,
         )
@@ -41,17 +132,133 @@ impl AuditPathProcessor {
         (
             Self {
                 ring_buffer,
+                spill: None,
+                dead_letters: None,
+                metrics: None,
+                tracer: None,
+                audit_spans: AuditSpans::default(),
+                pricing: None,
+                request_models: RequestModels::default(),
+                shard: 0,
                 shutdown_rx,
                 event_store: Some(event_store),
+                batching: AuditBatching::default(),
             },
             shutdown_tx,
         )
     }
 
+    /// Group persisted events as configured instead of by the defaults
+    pub fn with_batching(mut self, batching: AuditBatching) -> Self {
+        self.batching = batching;
+        self
+    }
+
+    /// Drain the shard's spill log after its ring buffer shard
+    ///
+    /// `spill` must have been opened with the ring buffer's shard count.
+    pub fn with_spill_log(mut self, spill: Arc<SpillLog>) -> Self {
+        self.spill = Some(spill);
+        self
+    }
+
+    /// Keep events that fail processing in `store`
+    pub fn with_dead_letters(mut self, store: Arc<dyn DeadLetterStore>) -> Self {
+        self.dead_letters = Some(store);
+        self
+    }
+
+    /// Publish the processor's counters to `metrics` as it runs
+    ///
+    /// `metrics` must have a slot for the processor's shard.
+    pub fn with_metrics(mut self, metrics: Arc<AuditMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Record a span for each traced request persisted in a batch
+    pub fn with_tracer(mut self, tracer: Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// Price the usage of every response body with `catalog`
+    pub fn with_pricing(mut self, catalog: Arc<PricingCatalog>) -> Self {
+        self.pricing = Some(catalog);
+        self
+    }
+
+    /// Read the given shard of the ring buffer instead of the first
+    ///
+    /// # Panics
+    ///
+    /// Running the processor panics if `shard` is not below the ring
+    /// buffer's shard count.
+    pub fn for_shard(mut self, shard: usize) -> Self {
+        self.shard = shard;
+        self
+    }
+
+    /// Spawn the processor on the Tokio runtime
+    pub fn spawn(self, shutdown_tx: mpsc::Sender<()>) -> AuditProcessorHandle {
+        AuditProcessorHandle {
+            consumers: vec![(shutdown_tx, tokio::spawn(self.run()))],
+        }
+    }
+
+    /// Spawn one processor for every shard of `ring_buffer`
+    pub fn spawn_all(
+        ring_buffer: &Arc<RingBuffer>,
+        config: &AuditPathConfig,
+    ) -> AuditProcessorHandle {
+        (0..ring_buffer.shard_count())
+            .map(|shard| {
+                let (processor, shutdown_tx) = Self::new(Arc::clone(ring_buffer));
+                processor
+                    .with_config(config.clone())
+                    .for_shard(shard)
+                    .spawn(shutdown_tx)
+            })
+            .collect()
+    }
+
+    fn with_config(self, config: AuditPathConfig) -> Self {
+        let AuditPathConfig {
+            event_store,
+            spill,
+            dead_letters,
+            metrics,
+            tracer,
+            pricing,
+            batching,
+        } = config;
+        Self {
+            event_store,
+            spill,
+            dead_letters,
+            metrics,
+            tracer,
+            pricing,
+            batching,
+            ..self
+        }
+    }
+
     /// Run the audit path processor
-    pub async fn run(mut self) {
-        info!("Audit path processor started");
-        let mut state = ProcessorState::default();
+    ///
+    /// After a shutdown request the processor keeps reading until the ring
+    /// buffer is empty, so events written by in-flight requests are persisted.
+    /// Dropping the shutdown sender counts as a shutdown request.
+    pub async fn run(mut self) -> AuditCounters {
+        info!("Audit path processor started for shard {}", self.shard);
+        if self.event_store.is_none() {
+            warn!(
+                "No event store configured; audit events of shard {} will not be persisted",
+                self.shard
+            );
+        }
+        let mut state = ProcessorState::new(self.batching.batch_size);
+        let mut flush_deadline = None;
 
         'outer: loop {
             // Check for shutdown signal
@@ -63,15 +270,22 @@ impl AuditPathProcessor {
                     info!("Audit path processor shutting down");
                     break;
                 }
+                info!("Audit path processor draining ring buffer");
             }
 
-            // Read from ring buffer
-            let read_result = self.ring_buffer.read();
+            // Read from ring buffer, then from the events it could not take
+            let read_result = self
+                .ring_buffer
+                .read_shard(self.shard)
+                .or_else(|| self.spill.as_ref().and_then(|spill| spill.read(self.shard)));
             let mut observation = Observation::RingBufferRead(read_result);
 
             loop {
                 let (new_state, step) = crate::proxy::audit_steps::step(state, observation);
                 state = new_state;
+                if let Some(metrics) = &self.metrics {
+                    metrics.publish(self.shard, state.counters());
+                }
 
                 match step {
                     Step::Stop => {
@@ -83,47 +297,139 @@ impl AuditPathProcessor {
                         continue 'outer;
                     }
                     Step::Effect(effect) => {
-                        observation = Self::perform_effect(effect, &self.event_store).await;
+                        observation = self.perform_effect(effect, &mut flush_deadline).await;
                     }
                 }
             }
         }
 
-        info!("Audit path processor stopped");
-        info!("  events_processed={}", state.events_processed);
-        info!(
-            "  deserialization_failures={}",
-            state.deserialization_failures
+        let counters = state.counters();
+        debug!(
+            "Audit path processor for shard {} stopped: {counters:?}",
+            self.shard
         );
-        info!("  conversion_failures={}", state.conversion_failures);
-        info!("  persist_failures={}", state.persist_failures);
+        counters
     }
 
     /// Perform a single audit effect and return the observation
     async fn perform_effect(
+        &mut self,
         effect: AuditEffect,
-        event_store: &Option<Arc<EventCoreService>>,
+        flush_deadline: &mut Option<Instant>,
     ) -> Observation {
         match effect {
-            AuditEffect::Deserialize { data } => {
-                let result = serde_json::from_slice::<AuditEvent>(&data).map_err(|e| e.to_string());
-                Observation::Deserialized(result)
+            AuditEffect::Deserialize { request_id, data } => match decode_audit_event(&data) {
+                Ok(event) => Observation::Deserialized(Ok(event)),
+                Err(e) => Observation::Deserialized(Err(FailedAudit::Undecodable {
+                    request_id,
+                    data,
+                    error: e.to_string(),
+                })),
+            },
+            AuditEffect::ConvertToDomain { event } => match convert_audit_event(&event) {
+                Ok(command) => Observation::Converted(Ok(command)),
+                Err(e) => Observation::Converted(Err(FailedAudit::Unconvertible {
+                    error: e.to_string(),
+                    event,
+                })),
+            },
+            AuditEffect::PersistBatch { mut commands } => {
+                *flush_deadline = None;
+                let ObservedBatch {
+                    parsed_bodies,
+                    usage,
+                } = self.request_models.observe(&commands);
+                for body in parsed_bodies {
+                    commands[body.index].parsed_request = Some(body.parsed);
+                }
+                if let Some(pricing) = &self.pricing {
+                    for response in &usage {
+                        let command = &mut commands[response.index];
+                        let cost = calculate_cost(
+                            pricing,
+                            &response.model,
+                            &response.metadata,
+                            command.timestamp,
+                        );
+                        command.calculated_cost = cost;
+                    }
+                }
+                let spans = match &self.tracer {
+                    Some(tracer) => self.audit_spans.start_batch(
+                        tracer,
+                        &commands,
+                        &self.request_models,
+                        &usage,
+                    ),
+                    None => Vec::new(),
+                };
+                let versions = version_usage(&commands);
+                let observation = self.persist(commands).await;
+                finish_batch(spans, &observation);
+                if matches!(observation, Observation::BatchPersisted { .. }) {
+                    self.record_versions(versions).await;
+                }
+                observation
+            }
+            AuditEffect::StartFlushTimer => {
+                *flush_deadline = Some(Instant::now() + self.batching.flush_after());
+                Observation::FlushTimerStarted
+            }
+            AuditEffect::WaitForEvents => {
+                let deadline = *flush_deadline;
+                let flush_due = async move {
+                    match deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline).await,
+                        None => std::future::pending().await,
+                    }
+                };
+                let spill = self.spill.clone();
+                let shard = self.shard;
+                let spilled = async move {
+                    match &spill {
+                        Some(spill) => spill.readable(shard).await,
+                        None => std::future::pending().await,
+                    }
+                };
+                tokio::select! {
+                    () = self.ring_buffer.readable(self.shard) => Observation::Woken,
+                    () = spilled => Observation::Woken,
+                    () = flush_due => {
+                        *flush_deadline = None;
+                        Observation::FlushDue
+                    }
+                    _ = self.shutdown_rx.recv() => Observation::ShutdownRequested,
+                }
             }
-            AuditEffect::ConvertToDomain { event } => {
-                let result = convert_audit_event(&event).map_err(|e| e.to_string());
-                Observation::Converted(result)
+            AuditEffect::RetryAfter { delay } => {
+                tokio::select! {
+                    () = tokio::time::sleep(delay) => Observation::RetryDue,
+                    _ = self.shutdown_rx.recv() => Observation::ShutdownRequested,
+                }
             }
-            AuditEffect::Persist { command } => {
-                let result = if let Some(store) = event_store {
-                    store
-                        .execute_command(command)
-                        .await
-                        .map_err(|e| e.to_string())
-                } else {
-                    warn!("No event store configured; skipping persistence");
-                    Err("No event store configured".to_string())
+            AuditEffect::RecordDeadLetters { failures } => {
+                let Some(store) = &self.dead_letters else {
+                    return Observation::DeadLettersRecorded {
+                        recorded: 0,
+                        failures: Vec::new(),
+                    };
                 };
-                Observation::Persisted(result)
+                let mut recorded = 0;
+                let mut errors = Vec::new();
+                for failure in failures {
+                    let stored = match dead_letter(failure) {
+                        Ok(letter) => store.record(letter).await.map_err(|e| e.to_string()),
+                        Err(e) => Err(e),
+                    };
+                    match stored {
+                        Ok(_) => recorded += 1,
+                        Err(e) => errors.push(e),
+                    }
+                }
+                Observation::DeadLettersRecorded {
+                    recorded,
+                    failures: errors,
+                }
             }
             AuditEffect::Log { level, message } => {
                 match level {
@@ -134,11 +440,214 @@ impl AuditPathProcessor {
                 }
                 Observation::LogComplete
             }
-            AuditEffect::Sleep { duration } => {
-                tokio::time::sleep(duration).await;
-                Observation::SleepComplete
+        }
+    }
+
+    /// Record the usage of the model versions providers reported, in one write
+    async fn record_versions(&self, versions: Vec<RecordVersionUsage>) {
+        let (Some(store), Some(usages)) = (&self.event_store, RecordVersionUsages::new(versions))
+        else {
+            return;
+        };
+        if let Err(e) = store.execute_command(usages).await {
+            warn!("Failed to record model version usage: {e}");
+        }
+    }
+
+    /// Persist a batch, acknowledging the spilled events it covers once stored
+    async fn persist(&self, commands: Vec<RecordAuditEvent>) -> Observation {
+        let Some(spill) = &self.spill else {
+            return Self::persist_batch(commands, &self.event_store).await;
+        };
+
+        // Spilled events read so far are in this batch or already persisted
+        let position = spill.position(self.shard);
+        let observation = Self::persist_batch(commands, &self.event_store).await;
+        let unavailable = matches!(observation, Observation::StoreUnavailable { .. });
+        spill.set_diverted(self.shard, unavailable);
+        if !unavailable {
+            if let Err(e) = spill.acknowledge(self.shard, position) {
+                error!("Failed to acknowledge spilled audit events: {e}");
+            }
+        }
+        observation
+    }
+
+    /// Persist a batch in one write, falling back to one write per command
+    ///
+    /// A batch is stored atomically, so a single rejected command would
+    /// otherwise lose every event written alongside it. Commands the event
+    /// store is unavailable for are handed back for a retry.
+    async fn persist_batch(
+        commands: Vec<RecordAuditEvent>,
+        event_store: &Option<Arc<EventCoreService>>,
+    ) -> Observation {
+        // `run` warned about the missing store once already
+        let Some(store) = event_store else {
+            return Observation::PersistenceSkipped;
+        };
+
+        if commands.len() < 2 {
+            return Self::persist_each(store, commands).await;
+        }
+        let batch = RecordAuditEvents::new(commands).expect("batch has commands");
+        let size = batch.len() as u64;
+        // The store drops its clone once the write finishes, so a failed
+        // write hands the commands back without copying them
+        match store.execute_command(batch.clone()).await {
+            Ok(()) => Observation::BatchPersisted {
+                persisted: size,
+                failures: Vec::new(),
+            },
+            Err(e @ Error::EventStoreUnavailable(_)) => Observation::StoreUnavailable {
+                persisted: 0,
+                failures: Vec::new(),
+                unwritten: batch.into_commands(),
+                error: e.to_string(),
+            },
+            Err(e) => {
+                warn!("Batched audit write failed, retrying {size} events one by one: {e}");
+                Self::persist_each(store, batch.into_commands()).await
+            }
+        }
+    }
+
+    async fn persist_each(
+        store: &EventCoreService,
+        commands: Vec<RecordAuditEvent>,
+    ) -> Observation {
+        let mut persisted = 0;
+        let mut failures = Vec::new();
+        let mut commands = commands.into_iter();
+        while let Some(command) = commands.next() {
+            match store.execute_command(command.clone()).await {
+                Ok(()) => persisted += 1,
+                Err(e @ Error::EventStoreUnavailable(_)) => {
+                    return Observation::StoreUnavailable {
+                        persisted,
+                        failures,
+                        unwritten: std::iter::once(command).chain(commands).collect(),
+                        error: e.to_string(),
+                    };
+                }
+                Err(e) => failures.push(FailedAudit::Unpersisted {
+                    command,
+                    error: e.to_string(),
+                }),
             }
         }
+        Observation::BatchPersisted {
+            persisted,
+            failures,
+        }
+    }
+}
+
+/// Version usage of the requests in a batch whose provider named the model
+///
+/// Only the provider knows the model of some requests, like those sent to an
+/// Azure deployment, so its report is what version tracking follows.
+fn version_usage(commands: &[RecordAuditEvent]) -> Vec<RecordVersionUsage> {
+    commands
+        .iter()
+        .filter_map(|command| {
+            let audit_types::AuditEventType::ProviderResolved {
+                provider,
+                model_id: Some(model_id),
+            } = &command.audit_event
+            else {
+                return None;
+            };
+            let model_version = ModelVersion {
+                provider: provider.clone(),
+                model_id: model_id.clone(),
+            };
+            RecordVersionUsage::new(command.session_id.clone(), model_version, command.timestamp)
+                .ok()
+        })
+        .collect()
+}
+
+/// The dead letter kept for an event the audit path gave up on
+///
+/// Events that were decoded are stored in the compact binary format; see
+/// [`crate::infrastructure::dead_letters`] for what each phase holds.
+fn dead_letter(failure: FailedAudit) -> Result<NewDeadLetter, String> {
+    match failure {
+        FailedAudit::Undecodable {
+            request_id,
+            data,
+            error,
+        } => Ok(NewDeadLetter {
+            request_id: Some(*request_id.as_ref()),
+            phase: DeadLetterPhase::Deserialize,
+            error,
+            payload: data,
+        }),
+        FailedAudit::Unconvertible { event, error } => Ok(NewDeadLetter {
+            request_id: Some(*event.request_id.as_ref()),
+            phase: DeadLetterPhase::Convert,
+            error,
+            payload: AuditEventFormat::Binary
+                .encode(&event)
+                .map_err(|e| e.to_string())?,
+        }),
+        FailedAudit::Unpersisted { command, error } => Ok(NewDeadLetter {
+            request_id: Some(*command.request_id.as_ref()),
+            phase: DeadLetterPhase::Persist,
+            error,
+            payload: serde_json::to_vec(&command).map_err(|e| e.to_string())?,
+        }),
+    }
+}
+
+/// Handle to one or more spawned audit path processors
+///
+/// Handles for processors of different shards can be collected into one.
+pub struct AuditProcessorHandle {
+    consumers: Vec<(mpsc::Sender<()>, JoinHandle<AuditCounters>)>,
+}
+
+impl AuditProcessorHandle {
+    /// Request shutdown and wait for every processor to drain its shard
+    ///
+    /// Returns the processors' counters summed together.
+    pub async fn shutdown(self) -> Result<AuditCounters, JoinError> {
+        let mut tasks = Vec::with_capacity(self.consumers.len());
+        for (shutdown_tx, task) in self.consumers {
+            // A closed channel means the processor has already stopped
+            let _ = shutdown_tx.send(()).await;
+            tasks.push(task);
+        }
+
+        let mut counters = AuditCounters::default();
+        for task in tasks {
+            counters += task.await?;
+        }
+
+        info!("Audit path processors stopped");
+        info!("  events_processed={}", counters.events_processed);
+        info!(
+            "  deserialization_failures={}",
+            counters.deserialization_failures
+        );
+        info!("  conversion_failures={}", counters.conversion_failures);
+        info!("  persist_failures={}", counters.persist_failures);
+        info!("  batches_persisted={}", counters.batches_persisted);
+        info!("  incomplete_bodies={}", counters.incomplete_bodies);
+        info!("  dead_lettered={}", counters.dead_lettered);
+        Ok(counters)
+    }
+}
+
+impl FromIterator<AuditProcessorHandle> for AuditProcessorHandle {
+    fn from_iter<I: IntoIterator<Item = AuditProcessorHandle>>(handles: I) -> Self {
+        Self {
+            consumers: handles
+                .into_iter()
+                .flat_map(|handle| handle.consumers)
+                .collect(),
+        }
     }
 }
 
@@ -146,8 +655,8 @@ impl AuditPathProcessor {
 mod tests {
     use super::*;
     use crate::infrastructure::eventcore::service::EventCoreService;
-    use crate::proxy::types::{RequestId, RingBufferConfig, SessionId};
-
+    use crate::proxy::types::*;
+    use crate::proxy::types::{RequestId, RingBufferConfig, SessionId, SpillConfig};
     #[tokio::test]
     async fn test_audit_processor_creation() {
         let config = RingBufferConfig::default();
@@ -349,6 +858,230 @@ mod tests {
         assert!(!persisted.is_empty(), "Expected events to be persisted");
     }
 
+    fn request_received(session_id: SessionId) -> AuditEvent {
+        AuditEvent {
+            request_id: RequestId::new(),
+            session_id,
+            timestamp: chrono::Utc::now(),
+            event_type: AuditEventType::RequestReceived {
+                method: HttpMethod::try_new(METHOD_POST.to_string()).unwrap(),
+                uri: RequestUri::try_new("/v1/chat/completions".to_string()).unwrap(),
+                headers: Headers::new(),
+                body_size: BodySize::from(0),
+            },
+        }
+    }
+
+    async fn session_event_count(event_store: &EventCoreService, session_id: SessionId) -> usize {
+        let domain_session_id = crate::domain::session::SessionId::new(*session_id.as_ref());
+        let session_stream =
+            crate::domain::commands::audit_commands::RecordAuditEvent::session_stream_id(
+                &domain_session_id,
+            )
+            .unwrap();
+        event_store
+            .read_stream::<crate::domain::events::DomainEvent>(session_stream)
+            .await
+            .unwrap()
+            .into_iter()
+            .count()
+    }
+
+    async fn wait_for_events(event_store: &EventCoreService, session_id: SessionId, count: usize) {
+        tokio::time::timeout(tokio::time::Duration::from_secs(2), async {
+            while session_event_count(event_store, session_id).await < count {
+                tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("events should be persisted before shutdown");
+    }
+
+    #[tokio::test]
+    async fn test_full_batch_is_persisted_without_waiting_for_the_flush_interval() {
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+        let (processor, shutdown_tx) =
+            AuditPathProcessor::with_event_store(ring_buffer.clone(), Arc::clone(&event_store));
+        let handle = processor
+            .with_batching(AuditBatching {
+                batch_size: BatchSize::try_new(3).unwrap(),
+                flush_interval: FlushIntervalMs::try_new(60_000).unwrap(),
+            })
+            .spawn(shutdown_tx);
+
+        // Let the processor park on the empty ring buffer before writing
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+        let session_id = SessionId::new();
+        for _ in 0..3 {
+            let event = request_received(session_id);
+            let serialized = serde_json::to_vec(&event).unwrap();
+            ring_buffer.write(event.request_id, &serialized).unwrap();
+        }
+
+        wait_for_events(&event_store, session_id, 3).await;
+        handle.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_partial_batch_is_persisted_after_the_flush_interval() {
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+        let (processor, shutdown_tx) =
+            AuditPathProcessor::with_event_store(ring_buffer.clone(), Arc::clone(&event_store));
+        let handle = processor
+            .with_batching(AuditBatching {
+                batch_size: BatchSize::try_new(100).unwrap(),
+                flush_interval: FlushIntervalMs::try_new(20).unwrap(),
+            })
+            .spawn(shutdown_tx);
+
+        let session_id = SessionId::new();
+        let event = request_received(session_id);
+        let serialized = serde_json::to_vec(&event).unwrap();
+        ring_buffer.write(event.request_id, &serialized).unwrap();
+
+        wait_for_events(&event_store, session_id, 1).await;
+        handle.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_consumers_publish_counters_while_running() {
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig {
+            shards: ShardCount::try_new(2).unwrap(),
+            ..RingBufferConfig::default()
+        }));
+        let metrics = Arc::new(AuditMetrics::new(ring_buffer.shard_count()));
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+        let handle = AuditPathProcessor::spawn_all(
+            &ring_buffer,
+            &AuditPathConfig {
+                event_store: Some(event_store),
+                metrics: Some(Arc::clone(&metrics)),
+                ..AuditPathConfig::default()
+            },
+        );
+
+        for _ in 0..4 {
+            let event = request_received(SessionId::new());
+            let serialized = serde_json::to_vec(&event).unwrap();
+            ring_buffer.write(event.request_id, &serialized).unwrap();
+        }
+
+        let published = tokio::time::timeout(Duration::from_secs(5), async {
+            while metrics.totals().events_processed < 4 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(published.is_ok(), "counters were not published");
+
+        let counters = handle.shutdown().await.unwrap();
+        assert_eq!(metrics.totals(), counters);
+    }
+
+    #[tokio::test]
+    async fn test_consumers_drain_every_shard_and_sum_their_counters() {
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig {
+            shards: ShardCount::try_new(4).unwrap(),
+            ..RingBufferConfig::default()
+        }));
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+
+        let sessions: Vec<SessionId> = (0..8).map(|_| SessionId::new()).collect();
+        for session_id in &sessions {
+            let event = request_received(*session_id);
+            let serialized = serde_json::to_vec(&event).unwrap();
+            ring_buffer.write(event.request_id, &serialized).unwrap();
+        }
+
+        let handle = AuditPathProcessor::spawn_all(
+            &ring_buffer,
+            &AuditPathConfig {
+                event_store: Some(Arc::clone(&event_store)),
+                ..AuditPathConfig::default()
+            },
+        );
+        let counters = handle.shutdown().await.unwrap();
+
+        assert_eq!(counters.events_processed, 8);
+        assert_eq!(counters.persist_failures, 0);
+        for session_id in sessions {
+            assert_eq!(session_event_count(&event_store, session_id).await, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_events_are_kept_as_dead_letters() {
+        use crate::infrastructure::dead_letters::{DeadLetterQuery, InMemoryDeadLetterStore};
+
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+        let store = Arc::new(InMemoryDeadLetterStore::new());
+        let garbage_id = RequestId::new();
+        ring_buffer
+            .write(garbage_id, b"not an audit event")
+            .unwrap();
+        let event = request_received(SessionId::new());
+        let serialized = AuditEventFormat::Binary.encode(&event).unwrap();
+        ring_buffer.write(event.request_id, &serialized).unwrap();
+
+        // Without an event store decoded events are skipped, not dead-lettered
+        let (processor, shutdown_tx) = AuditPathProcessor::new(ring_buffer);
+        let counters = processor
+            .with_dead_letters(store.clone())
+            .spawn(shutdown_tx)
+            .shutdown()
+            .await
+            .unwrap();
+
+        assert_eq!(counters.deserialization_failures, 1);
+        assert_eq!(counters.persist_failures, 0);
+        assert_eq!(counters.dead_lettered, 1);
+        let letters = store.list(&DeadLetterQuery::default()).await.unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].phase, DeadLetterPhase::Deserialize);
+        assert_eq!(letters[0].request_id, Some(*garbage_id.as_ref()));
+
+        let garbage = store.get(letters[0].id).await.unwrap().unwrap();
+        assert_eq!(garbage.payload, b"not an audit event");
+    }
+
+    #[tokio::test]
+    async fn test_spilled_events_are_persisted_and_acknowledged() {
+        let dir = tempfile::tempdir().unwrap();
+        let session_id = SessionId::new();
+        {
+            // Left unpersisted by an earlier run
+            let spill = SpillLog::open(&SpillConfig::new(dir.path()), 1).unwrap();
+            let event = request_received(session_id);
+            let serialized = serde_json::to_vec(&event).unwrap();
+            spill.append(0, event.request_id, &serialized).unwrap();
+        }
+
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+        let spill = Arc::new(SpillLog::open(&SpillConfig::new(dir.path()), 1).unwrap());
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+        let event = request_received(session_id);
+        let serialized = serde_json::to_vec(&event).unwrap();
+        ring_buffer.write(event.request_id, &serialized).unwrap();
+
+        let handle = AuditPathProcessor::spawn_all(
+            &ring_buffer,
+            &AuditPathConfig {
+                event_store: Some(Arc::clone(&event_store)),
+                spill: Some(Arc::clone(&spill)),
+                ..AuditPathConfig::default()
+            },
+        );
+        let counters = handle.shutdown().await.unwrap();
+
+        assert_eq!(counters.events_processed, 2);
+        assert_eq!(session_event_count(&event_store, session_id).await, 2);
+        let stats = spill.stats();
+        assert_eq!(stats.pending_records, 0);
+        assert_eq!(stats.bytes_on_disk, 0);
+    }
+
     #[tokio::test]
     #[ignore = "requires database connection"]
     async fn test_audit_path_persists_to_postgres() {
@@ -410,4 +1143,55 @@ mod tests {
             "Expected events to be persisted to PostgreSQL"
         );
     }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_pending_events() {
+        let config = RingBufferConfig::default();
+        let ring_buffer = Arc::new(RingBuffer::new(&config));
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+
+        let session_id = SessionId::new();
+        for _ in 0..3 {
+            let event = AuditEvent {
+                request_id: RequestId::new(),
+                session_id,
+                timestamp: chrono::Utc::now(),
+                event_type: AuditEventType::RequestReceived {
+                    method: HttpMethod::try_new(METHOD_GET.to_string()).unwrap(),
+                    uri: RequestUri::try_new("/test".to_string()).unwrap(),
+                    headers: Headers::new(),
+                    body_size: BodySize::from(0),
+                },
+            };
+            let serialized = serde_json::to_vec(&event).unwrap();
+            ring_buffer.write(event.request_id, &serialized).unwrap();
+        }
+
+        let (processor, shutdown_tx) =
+            AuditPathProcessor::with_event_store(ring_buffer.clone(), Arc::clone(&event_store));
+        let handle = processor.spawn(shutdown_tx);
+
+        // Shut down immediately; the pending events must still be persisted
+        tokio::time::timeout(tokio::time::Duration::from_secs(2), handle.shutdown())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(ring_buffer.stats().total_reads, 3);
+
+        let domain_session_id = crate::domain::session::SessionId::new(*session_id.as_ref());
+        let session_stream =
+            crate::domain::commands::audit_commands::RecordAuditEvent::session_stream_id(
+                &domain_session_id,
+            )
+            .unwrap();
+        let events = event_store
+            .read_stream::<crate::domain::events::DomainEvent>(session_stream)
+            .await
+            .unwrap();
+        assert!(
+            !events.is_empty(),
+            "Expected drained events to be persisted"
+        );
+    }
 }
@@ -1,10 +1,17 @@
 //! Shared audit recording functionality for streaming implementations
 
+use crate::providers::ProviderMetadata;
+use crate::proxy::audit_codec::AuditEventFormat;
 use crate::proxy::hot_path_planner::{PlannedRequestAudit, PlannedResponseAudit};
 use crate::proxy::ring_buffer::RingBuffer;
+use crate::proxy::spill_log::SpillLog;
 use crate::proxy::types::*;
 use bytes::Bytes;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::task::{Context, Poll};
 use tokio::sync::mpsc;
 
 /// Size of chunks for streaming capture
@@ -12,38 +19,134 @@ pub const CAPTURE_CHUNK_SIZE: usize = BYTES_16KB;
 
 /// Trait for common audit recording operations
 pub trait AuditRecorder {
+    /// Record the session context the client sent with a request
+    fn record_session_context(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        context: SessionContext,
+    );
+
+    /// Record what the provider serving a routed request reported about it
+    fn record_provider_metadata(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        metadata: &ProviderMetadata,
+    );
+
     /// Record a planned request audit event
-    fn record_request_audit(&self, request_id: RequestId, planned: PlannedRequestAudit);
+    fn record_request_audit(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        planned: PlannedRequestAudit,
+    );
 
     /// Record a planned response audit event
-    fn record_response_audit(&self, request_id: RequestId, planned: PlannedResponseAudit);
+    fn record_response_audit(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        planned: PlannedResponseAudit,
+    );
 
     /// Record an error event
-    fn record_error_event(&self, request_id: RequestId, error: String, phase: ErrorPhase);
+    fn record_error_event(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        error: String,
+        phase: ErrorPhase,
+    );
 
     /// Record a chunk event (request or response)
     fn record_chunk_event(
         &self,
         request_id: RequestId,
+        session_id: SessionId,
         offset: ChunkOffset,
         data: Vec<u8>,
         is_request: bool,
     );
+
+    /// Record that every chunk of a body has been written
+    fn record_body_complete(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        body_size: BodySize,
+        truncated: bool,
+        is_request: bool,
+    );
 }
 
 /// Default implementation of audit recording using ring buffer
+#[derive(Clone)]
 pub struct RingBufferAuditRecorder {
     ring_buffer: Arc<RingBuffer>,
+    spill: Option<Arc<SpillLog>>,
+    format: AuditEventFormat,
 }
 
 impl RingBufferAuditRecorder {
     pub fn new(ring_buffer: Arc<RingBuffer>) -> Self {
-        Self { ring_buffer }
+        Self {
+            ring_buffer,
+            spill: None,
+            format: AuditEventFormat::default(),
+        }
+    }
+
+    /// Encode events in `format` instead of the default compact binary
+    pub fn with_format(self, format: AuditEventFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Append events the ring buffer cannot take to `spill` instead of dropping them
+    ///
+    /// `spill` must have been opened with the ring buffer's shard count.
+    pub fn with_spill_log(self, spill: Arc<SpillLog>) -> Self {
+        Self {
+            spill: Some(spill),
+            ..self
+        }
     }
 }
 
 impl AuditRecorder for RingBufferAuditRecorder {
-    fn record_request_audit(&self, request_id: RequestId, planned: PlannedRequestAudit) {
+    fn record_session_context(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        context: SessionContext,
+    ) {
+        let event_type = AuditEventType::SessionContextReceived { context };
+        self.write_audit_event(request_id, session_id, event_type);
+    }
+
+    fn record_provider_metadata(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        metadata: &ProviderMetadata,
+    ) {
+        let event_type = AuditEventType::ProviderResolved {
+            provider_id: metadata.provider_id.to_string(),
+            model_id: metadata
+                .model_id
+                .as_ref()
+                .map(|model_id| model_id.as_ref().to_string()),
+        };
+        self.write_audit_event(request_id, session_id, event_type);
+    }
+
+    fn record_request_audit(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        planned: PlannedRequestAudit,
+    ) {
         let event_type = match planned {
             PlannedRequestAudit::Received {
                 method,
@@ -61,10 +164,15 @@ impl AuditRecorder for RingBufferAuditRecorder {
             }
         };
 
-        self.write_audit_event(request_id, event_type);
+        self.write_audit_event(request_id, session_id, event_type);
     }
 
-    fn record_response_audit(&self, request_id: RequestId, planned: PlannedResponseAudit) {
+    fn record_response_audit(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        planned: PlannedResponseAudit,
+    ) {
         let event_type = match planned {
             PlannedResponseAudit::Received {
                 status,
@@ -82,17 +190,24 @@ impl AuditRecorder for RingBufferAuditRecorder {
             }
         };
 
-        self.write_audit_event(request_id, event_type);
+        self.write_audit_event(request_id, session_id, event_type);
     }
 
-    fn record_error_event(&self, request_id: RequestId, error: String, phase: ErrorPhase) {
+    fn record_error_event(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        error: String,
+        phase: ErrorPhase,
+    ) {
         let event_type = AuditEventType::Error { error, phase };
-        self.write_audit_event(request_id, event_type);
+        self.write_audit_event(request_id, session_id, event_type);
     }
 
     fn record_chunk_event(
         &self,
         request_id: RequestId,
+        session_id: SessionId,
         offset: ChunkOffset,
         data: Vec<u8>,
         is_request: bool,
@@ -103,23 +218,62 @@ impl AuditRecorder for RingBufferAuditRecorder {
             AuditEventType::ResponseChunk { offset, data }
         };
 
-        self.write_audit_event(request_id, event_type);
+        self.write_audit_event(request_id, session_id, event_type);
+    }
+
+    fn record_body_complete(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        body_size: BodySize,
+        truncated: bool,
+        is_request: bool,
+    ) {
+        let event_type = if is_request {
+            AuditEventType::RequestBodyComplete {
+                body_size,
+                truncated,
+            }
+        } else {
+            AuditEventType::ResponseBodyComplete {
+                body_size,
+                truncated,
+            }
+        };
+
+        self.write_audit_event(request_id, session_id, event_type);
     }
 }
 
 impl RingBufferAuditRecorder {
     /// Write an audit event to the ring buffer
-    fn write_audit_event(&self, request_id: RequestId, event_type: AuditEventType) {
+    fn write_audit_event(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        event_type: AuditEventType,
+    ) {
         let audit_event = AuditEvent {
             request_id,
-            session_id: SessionId::new(),
+            session_id,
             timestamp: chrono::Utc::now(),
             event_type,
         };
 
         // Fire-and-forget write to ring buffer
-        if let Ok(serialized) = serde_json::to_vec(&audit_event) {
+        let Ok(serialized) = self.format.encode(&audit_event) else {
+            return;
+        };
+        let Some(spill) = &self.spill else {
             let _ = self.ring_buffer.write(request_id, &serialized);
+            return;
+        };
+
+        // Once a shard has spilled, keep spilling until the log is read back
+        // so that earlier events are not overtaken
+        let shard = self.ring_buffer.shard_of(request_id);
+        if spill.should_spill(shard) || self.ring_buffer.write(request_id, &serialized).is_err() {
+            let _ = spill.append(shard, request_id, &serialized);
         }
     }
 }
@@ -128,28 +282,45 @@ impl RingBufferAuditRecorder {
 pub struct ChunkCapture {
     recorder: Arc<dyn AuditRecorder + Send + Sync>,
     request_id: RequestId,
+    session_id: SessionId,
     is_request: bool,
+    limit: usize,
 }
 
 impl ChunkCapture {
     pub fn new(
         recorder: Arc<dyn AuditRecorder + Send + Sync>,
         request_id: RequestId,
+        session_id: SessionId,
         is_request: bool,
     ) -> Self {
         Self {
             recorder,
             request_id,
+            session_id,
             is_request,
+            limit: usize::MAX,
         }
     }
 
+    /// Record at most `limit` bytes; anything beyond marks the capture truncated
+    pub fn with_limit(self, limit: usize) -> Self {
+        Self { limit, ..self }
+    }
+
     /// Start a chunk capture task that receives chunks via mpsc and writes to audit
-    pub fn start_capture_task(&self) -> mpsc::Sender<Bytes> {
-        let (tx, mut rx) = mpsc::channel::<Bytes>(CHANNEL_BUFFER_SIZE);
+    ///
+    /// Chunks are recorded in `CAPTURE_CHUNK_SIZE` pieces so each event fits
+    /// a ring buffer slot. When the sender goes away the task records the
+    /// captured size, flagged as truncated unless the sender was finished.
+    pub fn start_capture_task(&self) -> CaptureSender {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Bytes>();
+        let finished = Arc::new(AtomicBool::new(false));
         let recorder = self.recorder.clone();
         let request_id = self.request_id;
+        let session_id = self.session_id;
         let is_request = self.is_request;
+        let task_finished = finished.clone();
 
         tokio::spawn(async move {
             let mut buffer = Vec::with_capacity(CAPTURE_CHUNK_SIZE);
@@ -159,22 +330,140 @@ impl ChunkCapture {
                 buffer.extend_from_slice(&chunk);
                 total_size += chunk.len();
 
-                // Write to ring buffer when we have enough data
-                if buffer.len() >= CAPTURE_CHUNK_SIZE {
-                    let offset = ChunkOffset::from(total_size - buffer.len());
-                    recorder.record_chunk_event(request_id, offset, buffer.clone(), is_request);
-                    buffer.clear();
+                // Write to ring buffer whenever a full chunk is available
+                while buffer.len() >= CAPTURE_CHUNK_SIZE {
+                    let rest = buffer.split_off(CAPTURE_CHUNK_SIZE);
+                    let offset = ChunkOffset::from(total_size - buffer.len() - rest.len());
+                    recorder.record_chunk_event(
+                        request_id,
+                        session_id,
+                        offset,
+                        std::mem::replace(&mut buffer, rest),
+                        is_request,
+                    );
                 }
             }
 
             // Write any remaining data when stream ends
             if !buffer.is_empty() {
                 let offset = ChunkOffset::from(total_size - buffer.len());
-                recorder.record_chunk_event(request_id, offset, buffer, is_request);
+                recorder.record_chunk_event(request_id, session_id, offset, buffer, is_request);
             }
+
+            let truncated = !task_finished.load(Ordering::Acquire);
+            recorder.record_body_complete(
+                request_id,
+                session_id,
+                BodySize::from(total_size),
+                truncated,
+                is_request,
+            );
         });
 
-        tx
+        CaptureSender {
+            tx: Some(tx),
+            remaining: self.limit,
+            finished,
+        }
+    }
+}
+
+/// Feeds body bytes to a running capture task without ever waiting
+///
+/// The queue is bounded by bytes rather than frames: it never holds more
+/// than the capture limit, however many frames arrive before the task runs.
+pub struct CaptureSender {
+    tx: Option<mpsc::UnboundedSender<Bytes>>,
+    remaining: usize,
+    finished: Arc<AtomicBool>,
+}
+
+impl CaptureSender {
+    /// Queue bytes for capture
+    ///
+    /// Bytes beyond the capture limit are dropped and the capture is
+    /// abandoned, so it is recorded as truncated after the bytes that fit.
+    pub fn send(&mut self, bytes: Bytes) {
+        let Some(tx) = &self.tx else {
+            return;
+        };
+        let within_limit = bytes.len() <= self.remaining;
+        let kept = bytes.slice(..bytes.len().min(self.remaining));
+        self.remaining -= kept.len();
+        if (!kept.is_empty() && tx.send(kept).is_err()) || !within_limit {
+            self.tx = None;
+        }
+    }
+
+    /// Mark the body as fully sent
+    pub fn finish(mut self) {
+        if let Some(tx) = self.tx.take() {
+            // The capture task reads the flag once the channel closes, so set it first
+            self.finished.store(true, Ordering::Release);
+            drop(tx);
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Body wrapper that copies each data frame to a capture task as it streams
+    pub struct TeeBody<B> {
+        #[pin]
+        inner: B,
+        capture: Option<CaptureSender>,
+    }
+}
+
+impl<B: HttpBody<Data = Bytes>> TeeBody<B> {
+    pub fn new(inner: B, capture: CaptureSender) -> Self {
+        // Bodies already at their end may never be polled
+        let capture = if inner.is_end_stream() {
+            capture.finish();
+            None
+        } else {
+            Some(capture)
+        };
+        Self { inner, capture }
+    }
+}
+
+impl<B: HttpBody<Data = Bytes>> HttpBody for TeeBody<B> {
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let polled = this.inner.as_mut().poll_frame(cx);
+
+        match &polled {
+            Poll::Ready(Some(Ok(frame))) => {
+                if let (Some(data), Some(capture)) = (frame.data_ref(), this.capture.as_mut()) {
+                    capture.send(data.clone());
+                }
+            }
+            Poll::Ready(Some(Err(_))) => *this.capture = None,
+            Poll::Ready(None) | Poll::Pending => {}
+        }
+
+        // Servers stop polling once the body reports its end
+        if matches!(polled, Poll::Ready(None)) || this.inner.is_end_stream() {
+            if let Some(capture) = this.capture.take() {
+                capture.finish();
+            }
+        }
+
+        polled
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
     }
 }
 
@@ -209,6 +498,7 @@ pub fn parse_http_status(status: hyper::StatusCode) -> Result<HttpStatusCode, u1
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::proxy::audit_codec::decode_audit_event;
 
     #[test]
     fn test_audit_recorder_creation() {
@@ -219,6 +509,7 @@ mod tests {
         // Test that we can record events without panicking
         recorder.record_error_event(
             RequestId::new(),
+            SessionId::new(),
             "Test error".to_string(),
             ErrorPhase::RequestParsing,
         );
@@ -230,10 +521,170 @@ mod tests {
         let ring_buffer = Arc::new(RingBuffer::new(&config));
         let recorder = Arc::new(RingBufferAuditRecorder::new(ring_buffer));
 
-        let _capture = ChunkCapture::new(recorder, RequestId::new(), true);
+        let _capture = ChunkCapture::new(recorder, RequestId::new(), SessionId::new(), true);
         // Test successful creation
     }
 
+    /// Read ring buffer events until a body completion event arrives
+    async fn events_until_body_complete(ring_buffer: &RingBuffer) -> Vec<AuditEvent> {
+        let mut events = Vec::new();
+        tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            loop {
+                match ring_buffer.read() {
+                    Some((_, data)) => {
+                        let event = decode_audit_event(&data).unwrap();
+                        let done = matches!(
+                            event.event_type,
+                            AuditEventType::RequestBodyComplete { .. }
+                                | AuditEventType::ResponseBodyComplete { .. }
+                        );
+                        events.push(event);
+                        if done {
+                            return;
+                        }
+                    }
+                    None => tokio::time::sleep(std::time::Duration::from_millis(5)).await,
+                }
+            }
+        })
+        .await
+        .expect("capture should complete");
+        events
+    }
+
+    fn capture_for(ring_buffer: &Arc<RingBuffer>, is_request: bool) -> ChunkCapture {
+        let recorder = Arc::new(RingBufferAuditRecorder::new(ring_buffer.clone()));
+        ChunkCapture::new(recorder, RequestId::new(), SessionId::new(), is_request)
+    }
+
+    #[tokio::test]
+    async fn test_capture_splits_large_bodies_into_slot_sized_chunks() {
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+        let body: Vec<u8> = (0..40_000).map(|i| (i % 251) as u8).collect();
+
+        let mut sender = capture_for(&ring_buffer, true).start_capture_task();
+        sender.send(Bytes::from(body.clone()));
+        sender.finish();
+
+        let events = events_until_body_complete(&ring_buffer).await;
+        let mut captured = Vec::new();
+        for event in &events[..events.len() - 1] {
+            let AuditEventType::RequestChunk { offset, data } = &event.event_type else {
+                panic!("expected RequestChunk, got {:?}", event.event_type);
+            };
+            assert_eq!(*offset.as_ref(), captured.len());
+            assert!(data.len() <= CAPTURE_CHUNK_SIZE);
+            captured.extend_from_slice(data);
+        }
+        assert_eq!(captured, body);
+        assert!(matches!(
+            events.last().unwrap().event_type,
+            AuditEventType::RequestBodyComplete { body_size, truncated: false }
+                if *body_size.as_ref() == body.len()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_capture_beyond_limit_is_truncated() {
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+
+        let mut sender = capture_for(&ring_buffer, false)
+            .with_limit(8)
+            .start_capture_task();
+        sender.send(Bytes::from_static(b"hello "));
+        sender.send(Bytes::from_static(b"world"));
+        sender.finish();
+
+        let events = events_until_body_complete(&ring_buffer).await;
+        assert!(matches!(
+            &events[0].event_type,
+            AuditEventType::ResponseChunk { data, .. } if data == b"hello wo"
+        ));
+        assert!(matches!(
+            events[1].event_type,
+            AuditEventType::ResponseBodyComplete { body_size, truncated: true }
+                if *body_size.as_ref() == 8
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_capture_keeps_many_small_frames_sent_before_yielding() {
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+        let frame = b"data: {\"delta\":\"x\"}\n\n";
+
+        let mut sender = capture_for(&ring_buffer, false)
+            .with_limit(1024 * 1024)
+            .start_capture_task();
+        for _ in 0..1_000 {
+            sender.send(Bytes::from_static(frame));
+        }
+        sender.finish();
+
+        let events = events_until_body_complete(&ring_buffer).await;
+        let captured: Vec<u8> = events[..events.len() - 1]
+            .iter()
+            .flat_map(|event| match &event.event_type {
+                AuditEventType::ResponseChunk { data, .. } => data.clone(),
+                other => panic!("expected ResponseChunk, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(captured, frame.repeat(1_000));
+        assert!(matches!(
+            events.last().unwrap().event_type,
+            AuditEventType::ResponseBodyComplete {
+                truncated: false,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unfinished_capture_is_truncated() {
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+
+        let mut sender = capture_for(&ring_buffer, false).start_capture_task();
+        sender.send(Bytes::from_static(b"partial"));
+        drop(sender);
+
+        let events = events_until_body_complete(&ring_buffer).await;
+        assert!(matches!(
+            events.last().unwrap().event_type,
+            AuditEventType::ResponseBodyComplete {
+                truncated: true,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tee_body_passes_data_through_and_captures_it() {
+        use http_body_util::BodyExt;
+
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+        let sender = capture_for(&ring_buffer, false).start_capture_task();
+        let stream = futures_util::stream::iter(vec![
+            Ok::<_, std::io::Error>(Frame::data(Bytes::from_static(b"data: one\n\n"))),
+            Ok(Frame::data(Bytes::from_static(b"data: two\n\n"))),
+        ]);
+        let body = TeeBody::new(http_body_util::StreamBody::new(stream), sender);
+
+        let forwarded = body.collect().await.unwrap().to_bytes();
+        assert_eq!(&forwarded[..], b"data: one\n\ndata: two\n\n");
+
+        let events = events_until_body_complete(&ring_buffer).await;
+        assert!(matches!(
+            &events[0].event_type,
+            AuditEventType::ResponseChunk { data, .. } if data[..] == forwarded[..]
+        ));
+        assert!(matches!(
+            events[1].event_type,
+            AuditEventType::ResponseBodyComplete {
+                truncated: false,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_helper_functions() {
         let method = hyper::Method::GET;
@@ -264,4 +715,72 @@ mod tests {
         // All these should succeed since hyper::Method ensures valid method strings
         // and our HttpMethod type only validates non-empty strings
     }
+
+    #[test]
+    fn test_events_carry_the_request_session() {
+        let config = crate::proxy::types::RingBufferConfig::default();
+        let ring_buffer = Arc::new(RingBuffer::new(&config));
+        let recorder = RingBufferAuditRecorder::new(ring_buffer.clone());
+        let request_id = RequestId::new();
+        let session_id = SessionId::new();
+
+        recorder.record_session_context(request_id, session_id, SessionContext::default());
+        recorder.record_error_event(
+            request_id,
+            session_id,
+            "boom".to_string(),
+            ErrorPhase::RequestForwarding,
+        );
+
+        for _ in 0..2 {
+            let (_, data) = ring_buffer.read().expect("event should be written");
+            let event = decode_audit_event(&data).unwrap();
+            assert_eq!(event.session_id.as_ref(), session_id.as_ref());
+        }
+    }
+
+    #[test]
+    fn test_events_spill_once_the_ring_buffer_is_full() {
+        let ring_buffer = Arc::new(RingBuffer::new(&RingBufferConfig {
+            buffer_size: BufferSize::try_new(BYTES_64KB).unwrap(),
+            slot_size: SlotSize::try_new(BYTES_64KB).unwrap(),
+            ..RingBufferConfig::default()
+        }));
+        let dir = tempfile::tempdir().unwrap();
+        let spill = Arc::new(SpillLog::open(&SpillConfig::new(dir.path()), 1).unwrap());
+        let recorder =
+            RingBufferAuditRecorder::new(ring_buffer.clone()).with_spill_log(spill.clone());
+        let request_id = RequestId::new();
+        let session_id = SessionId::new();
+        let record = |error: &str| {
+            recorder.record_error_event(
+                request_id,
+                session_id,
+                error.to_string(),
+                ErrorPhase::RequestForwarding,
+            )
+        };
+
+        record("first");
+        record("second");
+        assert!(ring_buffer.read().is_some());
+
+        // The ring has room again, but the spilled event must not be overtaken
+        record("third");
+        assert!(ring_buffer.read().is_none());
+        spill.flush();
+
+        let errors: Vec<String> = std::iter::from_fn(|| spill.read(0))
+            .map(
+                |(_, data)| match decode_audit_event(&data).unwrap().event_type {
+                    AuditEventType::Error { error, .. } => error,
+                    other => panic!("unexpected event {other:?}"),
+                },
+            )
+            .collect();
+        assert_eq!(errors, vec!["second", "third"]);
+
+        record("fourth");
+        assert!(ring_buffer.read().is_some());
+    }
 }
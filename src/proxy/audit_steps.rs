@@ -3,11 +3,43 @@
 //! This module defines the effect-driven workflow for processing audit events.
 //! All decisions are pure functions; IO is performed by the interpreter in
 //! `audit_path.rs`.
+//!
+//! Converted commands are collected into a batch that is persisted once it
+//! holds `batch_size` commands, once the flush timer started by its first
+//! command fires, or when the ring buffer runs empty while draining.
+//!
+//! When the event store is unavailable the batch is kept and retried with
+//! exponential backoff instead of being dropped. The processor stops reading
+//! meanwhile, so new events back up into the ring buffer and its spill log.
+//! A batch that still cannot be written once shutdown is requested is given up.
+//!
+//! Body chunks are buffered until their body completes. Bodies that receive
+//! no chunk for [`BODY_BUFFER_IDLE_TIMEOUT`] are dropped and counted as
+//! incomplete, so lost completion events do not hold on to memory.
+//!
+//! Events that cannot be deserialized, converted or persisted are counted,
+//! logged and then handed back to the interpreter as [`FailedAudit`]s to be
+//! kept in the dead-letter store.
 
+use crate::domain::commands::audit_buffer::{
+    AuditBufferError, AuditBufferManager, ChunkData, ChunkOffset,
+};
 use crate::domain::commands::audit_commands::RecordAuditEvent;
-use crate::proxy::types::{AuditEvent, RequestId};
+use crate::domain::config_types::BatchSize;
+use crate::domain::llm;
+use crate::proxy::types::{AuditEvent, AuditEventType, RequestId};
+use chrono::{DateTime, Utc};
 use std::time::Duration;
 
+/// Wait before the first retry of a batch the event store could not take
+pub const STORE_RETRY_INITIAL_DELAY: Duration = Duration::from_millis(100);
+
+/// Longest wait between retries; the delay doubles up to this
+pub const STORE_RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// How long a partially received body is kept without new chunks
+pub const BODY_BUFFER_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
 /// Log levels for audit path logging effects
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -23,15 +55,53 @@ pub enum LogLevel {
 #[allow(clippy::large_enum_variant)]
 pub enum AuditEffect {
     /// Deserialize ring buffer bytes into a proxy AuditEvent
-    Deserialize { data: Vec<u8> },
+    Deserialize {
+        request_id: RequestId,
+        data: Vec<u8>,
+    },
     /// Convert a proxy AuditEvent into a domain RecordAuditEvent command
     ConvertToDomain { event: AuditEvent },
-    /// Persist a domain command through EventCore
-    Persist { command: RecordAuditEvent },
+    /// Persist a batch of domain commands through EventCore
+    PersistBatch { commands: Vec<RecordAuditEvent> },
+    /// Start the timer that flushes a newly opened batch
+    StartFlushTimer,
+    /// Park until a writer publishes, the flush timer fires or shutdown is requested
+    WaitForEvents,
+    /// Wait before retrying a batch, unless shutdown is requested first
+    RetryAfter { delay: Duration },
+    /// Keep events the audit path gave up on in the dead-letter store
+    RecordDeadLetters { failures: Vec<FailedAudit> },
     /// Log a message at the given level
     Log { level: LogLevel, message: String },
-    /// Sleep before the next iteration
-    Sleep { duration: Duration },
+}
+
+/// An event the audit path gave up on, with the error that stopped it
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum FailedAudit {
+    /// Ring buffer bytes that could not be deserialized
+    Undecodable {
+        request_id: RequestId,
+        data: Vec<u8>,
+        error: String,
+    },
+    /// An event that could not be converted into a domain command
+    Unconvertible { event: AuditEvent, error: String },
+    /// A command the event store rejected
+    Unpersisted {
+        command: RecordAuditEvent,
+        error: String,
+    },
+}
+
+impl FailedAudit {
+    pub fn error(&self) -> &str {
+        match self {
+            Self::Undecodable { error, .. }
+            | Self::Unconvertible { error, .. }
+            | Self::Unpersisted { error, .. } => error,
+        }
+    }
 }
 
 /// Observations fed back into the pure core after an effect is performed
@@ -45,19 +115,77 @@ pub enum Observation {
     /// Result of reading the ring buffer
     RingBufferRead(Option<(RequestId, Vec<u8>)>),
     /// Result of deserializing bytes
-    Deserialized(Result<AuditEvent, String>),
+    Deserialized(Result<AuditEvent, FailedAudit>),
     /// Result of converting proxy event to domain command
-    Converted(Result<RecordAuditEvent, String>),
-    /// Result of persisting the command
-    Persisted(Result<(), String>),
+    Converted(Result<RecordAuditEvent, FailedAudit>),
+    /// Result of persisting a batch: how many commands were stored and the
+    /// ones that were rejected
+    BatchPersisted {
+        persisted: u64,
+        failures: Vec<FailedAudit>,
+    },
+    /// No event store is configured, so the batch was not written
+    PersistenceSkipped,
+    /// The event store became unavailable part way through a batch;
+    /// `unwritten` holds the commands from the first one it could not take
+    StoreUnavailable {
+        persisted: u64,
+        failures: Vec<FailedAudit>,
+        unwritten: Vec<RecordAuditEvent>,
+        error: String,
+    },
+    /// The retry delay has passed
+    RetryDue,
+    /// Dead letters were stored; `failures` says why the others were not
+    DeadLettersRecorded {
+        recorded: u64,
+        failures: Vec<String>,
+    },
+    /// The flush timer is running
+    FlushTimerStarted,
+    /// A writer published to the ring buffer
+    Woken,
+    /// The flush timer fired
+    FlushDue,
     /// Log effect completed
     LogComplete,
-    /// Sleep effect completed
-    SleepComplete,
     /// Shutdown was requested
     ShutdownRequested,
 }
 
+/// Outcome counters of one or more audit consumers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AuditCounters {
+    pub events_processed: u64,
+    pub deserialization_failures: u64,
+    pub conversion_failures: u64,
+    pub persist_failures: u64,
+    pub batches_persisted: u64,
+    pub incomplete_bodies: u64,
+    pub dead_lettered: u64,
+}
+
+impl std::ops::AddAssign for AuditCounters {
+    fn add_assign(&mut self, other: Self) {
+        self.events_processed += other.events_processed;
+        self.deserialization_failures += other.deserialization_failures;
+        self.conversion_failures += other.conversion_failures;
+        self.persist_failures += other.persist_failures;
+        self.batches_persisted += other.batches_persisted;
+        self.incomplete_bodies += other.incomplete_bodies;
+        self.dead_lettered += other.dead_lettered;
+    }
+}
+
+impl std::iter::Sum for AuditCounters {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut total, counters| {
+            total += counters;
+            total
+        })
+    }
+}
+
 /// State tracked by the audit processor
 #[derive(Debug, Clone, Default)]
 pub struct ProcessorState {
@@ -65,6 +193,54 @@ pub struct ProcessorState {
     pub deserialization_failures: u64,
     pub conversion_failures: u64,
     pub persist_failures: u64,
+    /// Batches handed to the event store
+    pub batches_persisted: u64,
+    /// Bodies dropped because chunks were lost before reaching the audit path
+    pub incomplete_bodies: u64,
+    /// Failed events kept in the dead-letter store
+    pub dead_lettered: u64,
+    /// Failed events waiting to be handed to the dead-letter store
+    pub dead_letters: Vec<FailedAudit>,
+    /// Request and response chunks waiting for their completion event
+    pub body_buffers: AuditBufferManager,
+    /// Capture time from which idle bodies are looked for again
+    pub next_idle_body_sweep: Option<DateTime<Utc>>,
+    /// Converted commands waiting to be persisted
+    pub batch: Vec<RecordAuditEvent>,
+    /// Number of commands that triggers a flush without waiting for the timer
+    pub batch_size: BatchSize,
+    /// Commands waiting for the event store to become available again
+    pub retry: Vec<RecordAuditEvent>,
+    /// Consecutive attempts the event store was unavailable for
+    pub retry_attempts: u32,
+    /// Delay to wait once the unavailability has been logged
+    pub retry_delay: Option<Duration>,
+    /// Set once shutdown is requested; the processor flushes and stops at the
+    /// next empty read
+    pub draining: bool,
+}
+
+impl ProcessorState {
+    /// Initial state for batches of at most `batch_size` commands
+    pub fn new(batch_size: BatchSize) -> Self {
+        Self {
+            batch_size,
+            ..Self::default()
+        }
+    }
+
+    /// Snapshot of the outcome counters
+    pub fn counters(&self) -> AuditCounters {
+        AuditCounters {
+            events_processed: self.events_processed,
+            deserialization_failures: self.deserialization_failures,
+            conversion_failures: self.conversion_failures,
+            persist_failures: self.persist_failures,
+            batches_persisted: self.batches_persisted,
+            incomplete_bodies: self.incomplete_bodies,
+            dead_lettered: self.dead_lettered,
+        }
+    }
 }
 
 /// The next action the interpreter should take
@@ -85,81 +261,352 @@ pub fn step(state: ProcessorState, observation: Observation) -> (ProcessorState,
     use Observation::*;
 
     match observation {
-        RingBufferRead(None) => (
-            state,
-            Step::Effect(Sleep {
-                duration: Duration::from_millis(10),
-            }),
-        ),
-        RingBufferRead(Some((_request_id, data))) => (state, Step::Effect(Deserialize { data })),
-        Deserialized(Err(e)) => {
-            let state = ProcessorState {
+        RingBufferRead(None) if state.draining && !state.batch.is_empty() => flush(state),
+        RingBufferRead(None) if state.draining => (state, Step::Stop),
+        RingBufferRead(None) => (state, Step::Effect(WaitForEvents)),
+        RingBufferRead(Some((request_id, data))) => {
+            (state, Step::Effect(Deserialize { request_id, data }))
+        }
+        Deserialized(Err(failure)) => {
+            let mut state = ProcessorState {
                 deserialization_failures: state.deserialization_failures + 1,
                 ..state
             };
+            let message = format!("Failed to deserialize audit event: {}", failure.error());
+            state.dead_letters.push(failure);
             (
                 state,
                 Step::Effect(Log {
                     level: LogLevel::Warn,
-                    message: format!("Failed to deserialize audit event: {e}"),
+                    message,
                 }),
             )
         }
-        Deserialized(Ok(event)) => (state, Step::Effect(ConvertToDomain { event })),
-        Converted(Err(e)) => {
-            let state = ProcessorState {
+        Deserialized(Ok(event)) => route_event(state, event),
+        Converted(Err(failure)) => {
+            let mut state = ProcessorState {
                 conversion_failures: state.conversion_failures + 1,
                 ..state
             };
+            let message = format!("Failed to convert audit event: {}", failure.error());
+            state.dead_letters.push(failure);
+            (
+                state,
+                Step::Effect(Log {
+                    level: LogLevel::Warn,
+                    message,
+                }),
+            )
+        }
+        Converted(Ok(command)) => {
+            let mut state = state;
+            state.batch.push(command);
+            if state.batch.len() >= *state.batch_size.as_ref() {
+                flush(state)
+            } else if state.batch.len() == 1 {
+                (state, Step::Effect(StartFlushTimer))
+            } else {
+                (state, Step::Continue)
+            }
+        }
+        FlushDue if !state.batch.is_empty() => flush(state),
+        BatchPersisted {
+            persisted,
+            failures,
+        } => {
+            let failed = failures.len();
+            let mut state = ProcessorState {
+                batches_persisted: state.batches_persisted + 1,
+                retry_attempts: 0,
+                ..record_persisted(state, persisted, failed)
+            };
+            if failures.is_empty() {
+                return (state, Step::Continue);
+            }
+            let mut errors: Vec<&str> = failures.iter().map(FailedAudit::error).collect();
+            errors.sort_unstable();
+            errors.dedup();
+            let message = format!(
+                "Failed to persist {failed} audit event(s): {}",
+                errors.join("; ")
+            );
+            state.dead_letters.extend(failures);
+            (
+                state,
+                Step::Effect(Log {
+                    level: LogLevel::Error,
+                    message,
+                }),
+            )
+        }
+        StoreUnavailable {
+            persisted,
+            failures,
+            unwritten,
+            error,
+        } => {
+            let mut state = record_persisted(state, persisted, failures.len());
+            state.dead_letters.extend(failures);
+            let count = unwritten.len();
+            if state.draining {
+                state.persist_failures += count as u64;
+                let message = format!(
+                    "Event store unavailable during shutdown; gave up on {count} audit event(s): {error}"
+                );
+                state
+                    .dead_letters
+                    .extend(
+                        unwritten
+                            .into_iter()
+                            .map(|command| FailedAudit::Unpersisted {
+                                command,
+                                error: error.clone(),
+                            }),
+                    );
+                return (
+                    state,
+                    Step::Effect(Log {
+                        level: LogLevel::Error,
+                        message,
+                    }),
+                );
+            }
+
+            let delay = retry_delay(state.retry_attempts);
+            state.retry_attempts += 1;
+            state.retry = unwritten;
+            state.retry_delay = Some(delay);
             (
                 state,
                 Step::Effect(Log {
                     level: LogLevel::Warn,
-                    message: format!("Failed to convert audit event: {e}"),
+                    message: format!(
+                        "Event store unavailable; retrying {count} audit event(s) in {delay:?}: {error}"
+                    ),
                 }),
             )
         }
-        Converted(Ok(command)) => (state, Step::Effect(Persist { command })),
-        Persisted(Err(e)) => {
+        LogComplete if !state.dead_letters.is_empty() => {
+            let mut state = state;
+            let failures = std::mem::take(&mut state.dead_letters);
+            (state, Step::Effect(RecordDeadLetters { failures }))
+        }
+        LogComplete => after_log(state),
+        DeadLettersRecorded { recorded, failures } => {
             let state = ProcessorState {
-                persist_failures: state.persist_failures + 1,
+                dead_lettered: state.dead_lettered + recorded,
                 ..state
             };
+            if failures.is_empty() {
+                return after_log(state);
+            }
             (
                 state,
                 Step::Effect(Log {
                     level: LogLevel::Error,
-                    message: format!("Failed to persist audit event: {e}"),
+                    message: format!(
+                        "Failed to record {} dead letter(s); the events are lost: {}",
+                        failures.len(),
+                        failures.join("; ")
+                    ),
                 }),
             )
         }
-        Persisted(Ok(())) => {
+        RetryDue => retry(state),
+        PersistenceSkipped | FlushTimerStarted | Woken | FlushDue => (state, Step::Continue),
+        ShutdownRequested => {
+            let state = ProcessorState {
+                draining: true,
+                ..state
+            };
+            if state.retry.is_empty() {
+                (state, Step::Continue)
+            } else {
+                // One last attempt before the batch is given up
+                retry(state)
+            }
+        }
+    }
+}
+
+fn record_persisted(state: ProcessorState, persisted: u64, failed: usize) -> ProcessorState {
+    ProcessorState {
+        events_processed: state.events_processed + persisted,
+        persist_failures: state.persist_failures + failed as u64,
+        ..state
+    }
+}
+
+/// Wait out a pending retry delay once failures have been logged and recorded
+fn after_log(mut state: ProcessorState) -> (ProcessorState, Step) {
+    match state.retry_delay.take() {
+        Some(delay) => (state, Step::Effect(AuditEffect::RetryAfter { delay })),
+        None => (state, Step::Continue),
+    }
+}
+
+/// Exponential backoff for the given number of earlier attempts
+fn retry_delay(attempts: u32) -> Duration {
+    STORE_RETRY_INITIAL_DELAY
+        .saturating_mul(1 << attempts.min(16))
+        .min(STORE_RETRY_MAX_DELAY)
+}
+
+/// Persist the commands held back while the event store was unavailable
+fn retry(mut state: ProcessorState) -> (ProcessorState, Step) {
+    let commands = std::mem::take(&mut state.retry);
+    (state, Step::Effect(AuditEffect::PersistBatch { commands }))
+}
+
+/// Hand the whole batch to the interpreter for persistence
+fn flush(mut state: ProcessorState) -> (ProcessorState, Step) {
+    let commands = std::mem::take(&mut state.batch);
+    (state, Step::Effect(AuditEffect::PersistBatch { commands }))
+}
+
+/// Buffer body chunks until their body completes; other events go to the domain
+fn route_event(mut state: ProcessorState, event: AuditEvent) -> (ProcessorState, Step) {
+    let AuditEvent {
+        request_id,
+        session_id,
+        timestamp,
+        event_type,
+    } = event;
+    let buffer_key = llm::RequestId::new(*request_id.as_ref());
+
+    let event_type = match event_type {
+        AuditEventType::RequestChunk { offset, data } => {
+            let added = state.body_buffers.add_request_chunk(
+                buffer_key,
+                ChunkOffset::new(*offset.as_ref()),
+                ChunkData::new(data),
+                timestamp,
+            );
+            return chunk_buffered(state, added, timestamp);
+        }
+        AuditEventType::ResponseChunk { offset, data } => {
+            let added = state.body_buffers.add_response_chunk(
+                buffer_key,
+                ChunkOffset::new(*offset.as_ref()),
+                ChunkData::new(data),
+                timestamp,
+            );
+            return chunk_buffered(state, added, timestamp);
+        }
+        AuditEventType::RequestBodyComplete {
+            body_size,
+            truncated,
+        } => match state
+            .body_buffers
+            .finish_request_body(&buffer_key, *body_size.as_ref())
+        {
+            Some(content) => AuditEventType::RequestBody { content, truncated },
+            None => return body_incomplete(state, request_id, "request"),
+        },
+        AuditEventType::ResponseBodyComplete {
+            body_size,
+            truncated,
+        } => match state
+            .body_buffers
+            .finish_response_body(&buffer_key, *body_size.as_ref())
+        {
+            Some(content) => AuditEventType::ResponseBody { content, truncated },
+            None => return body_incomplete(state, request_id, "response"),
+        },
+        other => other,
+    };
+
+    let event = AuditEvent {
+        request_id,
+        session_id,
+        timestamp,
+        event_type,
+    };
+    (state, Step::Effect(AuditEffect::ConvertToDomain { event }))
+}
+
+fn chunk_buffered(
+    state: ProcessorState,
+    added: Result<(), AuditBufferError>,
+    captured_at: DateTime<Utc>,
+) -> (ProcessorState, Step) {
+    let (state, evicted) = evict_idle_bodies(state, captured_at);
+    match added {
+        Ok(()) if evicted == 0 => (state, Step::Continue),
+        Ok(()) => (
+            state,
+            Step::Effect(AuditEffect::Log {
+                level: LogLevel::Warn,
+                message: format!(
+                    "Dropping {evicted} bodies without chunks for {BODY_BUFFER_IDLE_TIMEOUT:?}: their completion never arrived"
+                ),
+            }),
+        ),
+        Err(e) => {
             let state = ProcessorState {
-                events_processed: state.events_processed + 1,
+                conversion_failures: state.conversion_failures + 1,
                 ..state
             };
-            (state, Step::Continue)
+            (
+                state,
+                Step::Effect(AuditEffect::Log {
+                    level: LogLevel::Warn,
+                    message: format!("Failed to buffer body chunk: {e}"),
+                }),
+            )
         }
-        LogComplete | SleepComplete => (state, Step::Continue),
-        ShutdownRequested => (state, Step::Stop),
     }
 }
 
+/// Drop idle bodies, looking at most once per idle timeout
+fn evict_idle_bodies(
+    mut state: ProcessorState,
+    captured_at: DateTime<Utc>,
+) -> (ProcessorState, usize) {
+    let idle_timeout = chrono::Duration::from_std(BODY_BUFFER_IDLE_TIMEOUT)
+        .expect("idle timeout fits a chrono duration");
+    if state
+        .next_idle_body_sweep
+        .is_some_and(|next_sweep| captured_at < next_sweep)
+    {
+        return (state, 0);
+    }
+    state.next_idle_body_sweep = Some(captured_at + idle_timeout);
+
+    let evicted = state.body_buffers.evict_idle(captured_at - idle_timeout);
+    state.incomplete_bodies += evicted as u64;
+    (state, evicted)
+}
+
+fn body_incomplete(
+    state: ProcessorState,
+    request_id: RequestId,
+    body: &str,
+) -> (ProcessorState, Step) {
+    let state = ProcessorState {
+        incomplete_bodies: state.incomplete_bodies + 1,
+        ..state
+    };
+    (
+        state,
+        Step::Effect(AuditEffect::Log {
+            level: LogLevel::Warn,
+            message: format!("Dropping {body} body for request {request_id}: chunks are missing"),
+        }),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn empty_ring_buffer_sleeps() {
+    fn empty_ring_buffer_waits_for_events() {
         let state = ProcessorState::default();
         let (_, step) = step(state, Observation::RingBufferRead(None));
         assert!(
-            matches!(
-                &step,
-                Step::Effect(AuditEffect::Sleep { duration })
-                    if duration.as_millis() == 10
-            ),
-            "Expected Sleep effect with 10ms, got {step:?}"
+            matches!(&step, Step::Effect(AuditEffect::WaitForEvents)),
+            "Expected WaitForEvents effect, got {step:?}"
         );
     }
 
@@ -175,8 +622,9 @@ mod tests {
         assert!(
             matches!(
                 &step,
-                Step::Effect(AuditEffect::Deserialize { data: d })
-                    if *d == data
+                Step::Effect(AuditEffect::Deserialize { request_id, data: d })
+                    if *request_id == req_id
+                    && *d == data
             ),
             "Expected Deserialize effect, got {step:?}"
         );
@@ -187,9 +635,14 @@ mod tests {
         let state = ProcessorState::default();
         let (new_state, step) = step(
             state,
-            Observation::Deserialized(Err("bad json".to_string())),
+            Observation::Deserialized(Err(FailedAudit::Undecodable {
+                request_id: RequestId::new(),
+                data: b"{".to_vec(),
+                error: "bad json".to_string(),
+            })),
         );
         assert_eq!(new_state.deserialization_failures, 1);
+        assert_eq!(new_state.dead_letters.len(), 1);
         assert!(
             matches!(
                 &step,
@@ -205,8 +658,15 @@ mod tests {
     #[test]
     fn successful_persist_increments_counter() {
         let state = ProcessorState::default();
-        let (new_state, step) = step(state, Observation::Persisted(Ok(())));
-        assert_eq!(new_state.events_processed, 1);
+        let (new_state, step) = step(
+            state,
+            Observation::BatchPersisted {
+                persisted: 3,
+                failures: Vec::new(),
+            },
+        );
+        assert_eq!(new_state.events_processed, 3);
+        assert_eq!(new_state.batches_persisted, 1);
         assert!(
             matches!(step, Step::Continue),
             "Expected Continue, got {step:?}"
@@ -216,27 +676,517 @@ mod tests {
     #[test]
     fn persist_failure_logs_and_counts() {
         let state = ProcessorState::default();
-        let (new_state, step) = step(state, Observation::Persisted(Err("db down".to_string())));
-        assert_eq!(new_state.persist_failures, 1);
+        let (new_state, step) = step(
+            state,
+            Observation::BatchPersisted {
+                persisted: 1,
+                failures: vec![unpersisted("db down"), unpersisted("db down")],
+            },
+        );
+        assert_eq!(new_state.events_processed, 1);
+        assert_eq!(new_state.persist_failures, 2);
+        assert_eq!(new_state.dead_letters.len(), 2);
         assert!(
             matches!(
                 &step,
                 Step::Effect(AuditEffect::Log {
                     level: LogLevel::Error,
                     message,
-                }) if message.contains("db down")
+                }) if message == "Failed to persist 2 audit event(s): db down"
             ),
             "Expected Error log for db down, got {step:?}"
         );
     }
 
     #[test]
-    fn shutdown_requested_stops() {
+    fn skipped_persistence_is_neither_a_failure_nor_a_dead_letter() {
         let state = ProcessorState::default();
-        let (_, step) = step(state, Observation::ShutdownRequested);
+        let (new_state, step) = step(state, Observation::PersistenceSkipped);
+        assert_eq!(new_state.persist_failures, 0);
+        assert!(new_state.dead_letters.is_empty());
+        assert!(
+            matches!(step, Step::Continue),
+            "Expected Continue, got {step:?}"
+        );
+    }
+
+    fn converted() -> Observation {
+        let event = event(
+            RequestId::new(),
+            AuditEventType::RequestReceived {
+                method: crate::proxy::types::HttpMethod::try_new("GET".to_string()).unwrap(),
+                uri: crate::proxy::types::RequestUri::try_new("/test".to_string()).unwrap(),
+                headers: crate::proxy::types::Headers::new(),
+                body_size: crate::proxy::types::BodySize::from(0),
+            },
+        );
+        Observation::Converted(Ok(crate::adapters::proxy_audit::convert_audit_event(
+            &event,
+        )
+        .unwrap()))
+    }
+
+    #[test]
+    fn first_command_starts_the_flush_timer() {
+        let (state, next) = step(ProcessorState::default(), converted());
+        assert_eq!(state.batch.len(), 1);
+        assert!(
+            matches!(next, Step::Effect(AuditEffect::StartFlushTimer)),
+            "Expected StartFlushTimer, got {next:?}"
+        );
+
+        let (state, next) = step(state, converted());
+        assert_eq!(state.batch.len(), 2);
+        assert!(matches!(next, Step::Continue), "got {next:?}");
+    }
+
+    #[test]
+    fn full_batch_is_persisted_without_waiting() {
+        let mut state = ProcessorState::new(BatchSize::try_new(2).unwrap());
+        (state, _) = step(state, converted());
+        let (state, next) = step(state, converted());
+        assert!(state.batch.is_empty());
+        assert!(
+            matches!(&next, Step::Effect(AuditEffect::PersistBatch { commands }) if commands.len() == 2),
+            "Expected PersistBatch of 2, got {next:?}"
+        );
+    }
+
+    #[test]
+    fn flush_timer_persists_partial_batch() {
+        let (state, _) = step(ProcessorState::default(), converted());
+        let (state, next) = step(state, Observation::RingBufferRead(None));
+        assert!(
+            matches!(next, Step::Effect(AuditEffect::WaitForEvents)),
+            "Expected WaitForEvents, got {next:?}"
+        );
+
+        let (state, next) = step(state, Observation::FlushDue);
+        assert!(state.batch.is_empty());
+        assert!(
+            matches!(&next, Step::Effect(AuditEffect::PersistBatch { commands }) if commands.len() == 1),
+            "Expected PersistBatch of 1, got {next:?}"
+        );
+
+        let (_, next) = step(state, Observation::FlushDue);
+        assert!(matches!(next, Step::Continue), "got {next:?}");
+    }
+
+    #[test]
+    fn draining_flushes_the_batch_before_stopping() {
+        let (state, _) = step(ProcessorState::default(), converted());
+        let (state, _) = step(state, Observation::ShutdownRequested);
+
+        let (state, next) = step(state, Observation::RingBufferRead(None));
+        assert!(
+            matches!(&next, Step::Effect(AuditEffect::PersistBatch { commands }) if commands.len() == 1),
+            "Expected PersistBatch while draining, got {next:?}"
+        );
+
+        let (_, next) = step(state, Observation::RingBufferRead(None));
+        assert!(matches!(next, Step::Stop), "Expected Stop, got {next:?}");
+    }
+
+    fn command() -> RecordAuditEvent {
+        match converted() {
+            Observation::Converted(Ok(command)) => command,
+            other => panic!("Expected a converted command, got {other:?}"),
+        }
+    }
+
+    fn unpersisted(error: &str) -> FailedAudit {
+        FailedAudit::Unpersisted {
+            command: command(),
+            error: error.to_string(),
+        }
+    }
+
+    fn store_unavailable(unwritten: Vec<RecordAuditEvent>) -> Observation {
+        Observation::StoreUnavailable {
+            persisted: 0,
+            failures: Vec::new(),
+            unwritten,
+            error: "connection refused".to_string(),
+        }
+    }
+
+    #[test]
+    fn unavailable_store_is_retried_with_growing_delays() {
+        let (state, next) = step(
+            ProcessorState::default(),
+            store_unavailable(vec![command(), command()]),
+        );
+        assert_eq!(state.retry.len(), 2);
+        assert_eq!(state.persist_failures, 0);
+        assert!(
+            matches!(
+                &next,
+                Step::Effect(AuditEffect::Log {
+                    level: LogLevel::Warn,
+                    ..
+                })
+            ),
+            "Expected Warn log, got {next:?}"
+        );
+
+        let (state, next) = step(state, Observation::LogComplete);
+        assert!(
+            matches!(next, Step::Effect(AuditEffect::RetryAfter { delay }) if delay == STORE_RETRY_INITIAL_DELAY),
+            "Expected first retry delay, got {next:?}"
+        );
+
+        let (state, next) = step(state, Observation::RetryDue);
+        assert!(state.retry.is_empty());
+        let Step::Effect(AuditEffect::PersistBatch { commands }) = next else {
+            panic!("Expected PersistBatch, got {next:?}");
+        };
+
+        let (state, _) = step(state, store_unavailable(commands));
+        let (state, next) = step(state, Observation::LogComplete);
+        assert!(
+            matches!(next, Step::Effect(AuditEffect::RetryAfter { delay }) if delay == 2 * STORE_RETRY_INITIAL_DELAY),
+            "Expected doubled retry delay, got {next:?}"
+        );
+
+        let (state, _) = step(state, Observation::RetryDue);
+        let (state, _) = step(
+            state,
+            Observation::BatchPersisted {
+                persisted: 2,
+                failures: Vec::new(),
+            },
+        );
+        assert_eq!(state.events_processed, 2);
+        assert_eq!(state.retry_attempts, 0);
+    }
+
+    #[test]
+    fn retry_delay_is_capped() {
+        assert_eq!(retry_delay(0), STORE_RETRY_INITIAL_DELAY);
+        assert_eq!(retry_delay(100), STORE_RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn shutdown_during_retry_makes_one_last_attempt() {
+        let (state, _) = step(
+            ProcessorState::default(),
+            store_unavailable(vec![command()]),
+        );
+        let (state, _) = step(state, Observation::LogComplete);
+
+        let (state, next) = step(state, Observation::ShutdownRequested);
+        assert!(state.draining);
+        let Step::Effect(AuditEffect::PersistBatch { commands }) = next else {
+            panic!("Expected a last PersistBatch, got {next:?}");
+        };
+
+        let (state, next) = step(state, store_unavailable(commands));
+        assert!(state.retry.is_empty());
+        assert_eq!(state.persist_failures, 1);
+        assert!(
+            matches!(
+                &next,
+                Step::Effect(AuditEffect::Log {
+                    level: LogLevel::Error,
+                    ..
+                })
+            ),
+            "Expected Error log, got {next:?}"
+        );
+        let (state, next) = step(state, Observation::LogComplete);
+        assert!(
+            matches!(&next, Step::Effect(AuditEffect::RecordDeadLetters { failures })
+                if matches!(failures.as_slice(), [FailedAudit::Unpersisted { error, .. }] if error == "connection refused")),
+            "Expected the given-up command to be dead-lettered, got {next:?}"
+        );
+        let (state, next) = step(
+            state,
+            Observation::DeadLettersRecorded {
+                recorded: 1,
+                failures: Vec::new(),
+            },
+        );
+        assert_eq!(state.dead_lettered, 1);
+        assert!(matches!(next, Step::Continue), "got {next:?}");
+    }
+
+    #[test]
+    fn conversion_failures_are_dead_lettered_after_logging() {
+        let failed = event(
+            RequestId::new(),
+            AuditEventType::RequestChunk {
+                offset: crate::proxy::types::ChunkOffset::from(0),
+                data: vec![1],
+            },
+        );
+        let (state, next) = step(
+            ProcessorState::default(),
+            Observation::Converted(Err(FailedAudit::Unconvertible {
+                event: failed,
+                error: "chunks are not commands".to_string(),
+            })),
+        );
+        assert_eq!(state.conversion_failures, 1);
+        assert!(
+            matches!(
+                &next,
+                Step::Effect(AuditEffect::Log {
+                    level: LogLevel::Warn,
+                    ..
+                })
+            ),
+            "Expected Warn log, got {next:?}"
+        );
+
+        let (state, next) = step(state, Observation::LogComplete);
+        assert!(state.dead_letters.is_empty());
+        assert!(
+            matches!(&next, Step::Effect(AuditEffect::RecordDeadLetters { failures }) if failures.len() == 1),
+            "Expected RecordDeadLetters, got {next:?}"
+        );
+
+        let (state, next) = step(
+            state,
+            Observation::DeadLettersRecorded {
+                recorded: 1,
+                failures: Vec::new(),
+            },
+        );
+        assert_eq!(state.counters().dead_lettered, 1);
+        assert!(matches!(next, Step::Continue), "got {next:?}");
+    }
+
+    #[test]
+    fn rejected_commands_are_dead_lettered_before_waiting_to_retry() {
+        let (state, _) = step(
+            ProcessorState::default(),
+            Observation::StoreUnavailable {
+                persisted: 0,
+                failures: vec![unpersisted("duplicate request")],
+                unwritten: vec![command()],
+                error: "connection refused".to_string(),
+            },
+        );
+        assert_eq!(state.persist_failures, 1);
+        assert_eq!(state.retry.len(), 1);
+
+        let (state, next) = step(state, Observation::LogComplete);
+        assert!(
+            matches!(&next, Step::Effect(AuditEffect::RecordDeadLetters { failures }) if failures.len() == 1),
+            "Expected RecordDeadLetters, got {next:?}"
+        );
+
+        let (_, next) = step(
+            state,
+            Observation::DeadLettersRecorded {
+                recorded: 1,
+                failures: Vec::new(),
+            },
+        );
+        assert!(
+            matches!(next, Step::Effect(AuditEffect::RetryAfter { .. })),
+            "Expected RetryAfter, got {next:?}"
+        );
+    }
+
+    #[test]
+    fn failed_dead_letter_writes_are_logged() {
+        let (state, next) = step(
+            ProcessorState::default(),
+            Observation::DeadLettersRecorded {
+                recorded: 1,
+                failures: vec!["disk full".to_string()],
+            },
+        );
+        assert_eq!(state.dead_lettered, 1);
+        assert!(
+            matches!(
+                &next,
+                Step::Effect(AuditEffect::Log {
+                    level: LogLevel::Error,
+                    message,
+                }) if message.contains("disk full")
+            ),
+            "Expected Error log, got {next:?}"
+        );
+
+        let (_, next) = step(state, Observation::LogComplete);
+        assert!(matches!(next, Step::Continue), "got {next:?}");
+    }
+
+    #[test]
+    fn shutdown_requested_starts_draining() {
+        let state = ProcessorState::default();
+        let (new_state, step) = step(state, Observation::ShutdownRequested);
+        assert!(new_state.draining);
+        assert!(
+            matches!(step, Step::Continue),
+            "Expected Continue, got {step:?}"
+        );
+    }
+
+    #[test]
+    fn draining_keeps_reading_until_buffer_is_empty() {
+        let state = ProcessorState {
+            draining: true,
+            ..ProcessorState::default()
+        };
+        let req_id = RequestId::new();
+        let (state, next) = step(
+            state,
+            Observation::RingBufferRead(Some((req_id, b"test".to_vec()))),
+        );
+        assert!(
+            matches!(next, Step::Effect(AuditEffect::Deserialize { .. })),
+            "Expected Deserialize while draining, got {next:?}"
+        );
+
+        let (_, step) = step(state, Observation::RingBufferRead(None));
         assert!(matches!(step, Step::Stop), "Expected Stop, got {step:?}");
     }
 
+    fn event(request_id: RequestId, event_type: AuditEventType) -> AuditEvent {
+        AuditEvent {
+            request_id,
+            session_id: crate::proxy::types::SessionId::new(),
+            timestamp: chrono::Utc::now(),
+            event_type,
+        }
+    }
+
+    fn response_chunk(request_id: RequestId, offset: usize, data: &[u8]) -> Observation {
+        response_chunk_at(request_id, offset, data, chrono::Utc::now())
+    }
+
+    fn response_chunk_at(
+        request_id: RequestId,
+        offset: usize,
+        data: &[u8],
+        timestamp: DateTime<Utc>,
+    ) -> Observation {
+        Observation::Deserialized(Ok(AuditEvent {
+            timestamp,
+            ..event(
+                request_id,
+                AuditEventType::ResponseChunk {
+                    offset: crate::proxy::types::ChunkOffset::from(offset),
+                    data: data.to_vec(),
+                },
+            )
+        }))
+    }
+
+    fn response_complete(request_id: RequestId, size: usize, truncated: bool) -> Observation {
+        Observation::Deserialized(Ok(event(
+            request_id,
+            AuditEventType::ResponseBodyComplete {
+                body_size: crate::proxy::types::BodySize::from(size),
+                truncated,
+            },
+        )))
+    }
+
+    #[test]
+    fn chunks_are_reassembled_into_a_body_event() {
+        let req_id = RequestId::new();
+        let state = ProcessorState::default();
+
+        let (state, next) = step(state, response_chunk(req_id, 5, b" world"));
+        assert!(matches!(next, Step::Continue), "got {next:?}");
+        let (state, next) = step(state, response_chunk(req_id, 0, b"hello"));
+        assert!(matches!(next, Step::Continue), "got {next:?}");
+
+        let (state, next) = step(state, response_complete(req_id, 11, true));
+        assert!(
+            matches!(
+                &next,
+                Step::Effect(AuditEffect::ConvertToDomain { event }) if matches!(
+                    &event.event_type,
+                    AuditEventType::ResponseBody { content, truncated: true }
+                        if content == b"hello world"
+                )
+            ),
+            "Expected reassembled ResponseBody, got {next:?}"
+        );
+        assert_eq!(state.body_buffers.pending_count(), 0);
+    }
+
+    #[test]
+    fn empty_body_completes_without_chunks() {
+        let (_, next) = step(
+            ProcessorState::default(),
+            response_complete(RequestId::new(), 0, false),
+        );
+        assert!(
+            matches!(
+                &next,
+                Step::Effect(AuditEffect::ConvertToDomain { event }) if matches!(
+                    &event.event_type,
+                    AuditEventType::ResponseBody { content, truncated: false } if content.is_empty()
+                )
+            ),
+            "Expected empty ResponseBody, got {next:?}"
+        );
+    }
+
+    #[test]
+    fn body_with_missing_chunks_is_dropped_and_counted() {
+        let req_id = RequestId::new();
+        let (state, _) = step(
+            ProcessorState::default(),
+            response_chunk(req_id, 0, b"hello"),
+        );
+
+        let (state, next) = step(state, response_complete(req_id, 11, false));
+        assert_eq!(state.incomplete_bodies, 1);
+        assert_eq!(state.body_buffers.pending_count(), 0);
+        assert!(
+            matches!(
+                &next,
+                Step::Effect(AuditEffect::Log { level: LogLevel::Warn, message })
+                    if message.contains("chunks are missing")
+            ),
+            "Expected Warn log, got {next:?}"
+        );
+    }
+
+    #[test]
+    fn bodies_whose_completion_never_arrives_are_evicted_and_counted() {
+        let start = chrono::Utc::now();
+        let idle_timeout = chrono::Duration::from_std(BODY_BUFFER_IDLE_TIMEOUT).unwrap();
+        let (state, _) = step(
+            ProcessorState::default(),
+            response_chunk_at(RequestId::new(), 0, b"lost", start),
+        );
+
+        let later = start + idle_timeout + chrono::Duration::seconds(1);
+        let (state, next) = step(
+            state,
+            response_chunk_at(RequestId::new(), 0, b"next", later),
+        );
+        assert_eq!(state.incomplete_bodies, 1);
+        assert_eq!(state.body_buffers.pending_count(), 1);
+        assert!(
+            matches!(
+                &next,
+                Step::Effect(AuditEffect::Log { level: LogLevel::Warn, message })
+                    if message.contains("completion never arrived")
+            ),
+            "Expected Warn log, got {next:?}"
+        );
+    }
+
+    #[test]
+    fn counters_sum_across_consumers() {
+        let consumer = |events_processed, persist_failures| AuditCounters {
+            events_processed,
+            persist_failures,
+            ..AuditCounters::default()
+        };
+        let total: AuditCounters = [consumer(3, 0), consumer(4, 1)].into_iter().sum();
+        assert_eq!(total, consumer(7, 1));
+    }
+
     #[test]
     fn log_complete_continues() {
         let state = ProcessorState::default();
@@ -0,0 +1,400 @@
+//! Spans for audit persistence
+//!
+//! Each persisted batch records one `union_square.audit.persist` span for
+//! every request it holds events for, as a child of the span that served the
+//! request. The trace comes from the `traceparent` captured with the request
+//! headers, which may have been persisted by an earlier batch, so it is
+//! remembered for the most recent requests. The model and token usage
+//! followed by [`RequestModels`] are attached using the GenAI semantic
+//! conventions.
+
+use crate::domain::audit_types::{AuditEventType, HttpHeaders};
+use crate::domain::commands::audit_commands::RecordAuditEvent;
+use crate::domain::llm;
+use crate::proxy::audit_steps::{FailedAudit, Observation};
+use crate::proxy::audit_usage::{RecentRequests, RequestModels, ResponseUsage};
+use crate::proxy::otlp::{
+    attributes, gen_ai_system, span_names, ActiveSpan, SpanKind, SpanStatus, Tracer,
+};
+use crate::proxy::trace_context::{TraceContext, TRACEPARENT};
+
+/// Traces of recently received requests
+#[derive(Debug, Default)]
+pub struct AuditSpans {
+    traces: RecentRequests<TraceContext>,
+}
+
+/// A span covering one request's events in a batch being persisted
+#[derive(Debug)]
+pub struct PersistSpan {
+    request_id: llm::RequestId,
+    events: u32,
+    span: ActiveSpan,
+}
+
+impl AuditSpans {
+    /// Start a span for every traced request with events in `commands`
+    ///
+    /// `models` and `usage` must already have observed `commands`.
+    pub fn start_batch(
+        &mut self,
+        tracer: &Tracer,
+        commands: &[RecordAuditEvent],
+        models: &RequestModels,
+        usage: &[ResponseUsage],
+    ) -> Vec<PersistSpan> {
+        let mut spans: Vec<PersistSpan> = Vec::new();
+        for (index, command) in commands.iter().enumerate() {
+            if let AuditEventType::RequestReceived { headers, .. } = &command.audit_event {
+                if let Some(context) = traceparent(headers) {
+                    self.traces.insert(command.request_id.clone(), context);
+                }
+            }
+            let Some(context) = self.traces.get(&command.request_id) else {
+                continue;
+            };
+
+            let position = match spans
+                .iter()
+                .position(|span| span.request_id == command.request_id)
+            {
+                Some(position) => position,
+                None => {
+                    let mut span =
+                        tracer.start(span_names::AUDIT_PERSIST, SpanKind::Internal, Some(context));
+                    span.set_attribute(attributes::REQUEST_ID, command.request_id.to_string());
+                    spans.push(PersistSpan {
+                        request_id: command.request_id.clone(),
+                        events: 0,
+                        span,
+                    });
+                    spans.len() - 1
+                }
+            };
+            let persist = &mut spans[position];
+            persist.events += 1;
+            let span = &mut persist.span;
+            span.set_attribute(attributes::AUDIT_EVENTS, persist.events);
+
+            if let Some(response) = usage.iter().find(|response| response.index == index) {
+                let metadata = &response.metadata;
+                if let Some(model_id) = &metadata.model_id {
+                    span.set_attribute(attributes::GEN_AI_RESPONSE_MODEL, model_id.as_ref());
+                }
+                if let Some(tokens) = metadata.request_tokens {
+                    span.set_attribute(attributes::GEN_AI_USAGE_INPUT_TOKENS, tokens.into_inner());
+                }
+                if let Some(tokens) = metadata.response_tokens {
+                    span.set_attribute(attributes::GEN_AI_USAGE_OUTPUT_TOKENS, tokens.into_inner());
+                }
+            }
+
+            if let Some(model) = models.model(&command.request_id) {
+                span.set_attribute(
+                    attributes::GEN_AI_SYSTEM,
+                    gen_ai_system(model.provider.as_str()),
+                );
+                span.set_attribute(attributes::GEN_AI_REQUEST_MODEL, model.model_id.as_ref());
+            }
+        }
+        spans
+    }
+}
+
+/// End the spans of a batch, marking requests whose events were not persisted
+pub fn finish_batch(spans: Vec<PersistSpan>, observation: &Observation) {
+    let (failures, unwritten): (&[FailedAudit], &[RecordAuditEvent]) = match observation {
+        Observation::BatchPersisted { failures, .. } => (failures, &[]),
+        Observation::StoreUnavailable {
+            failures,
+            unwritten,
+            ..
+        } => (failures, unwritten),
+        _ => (&[], &[]),
+    };
+
+    for PersistSpan {
+        request_id,
+        mut span,
+        ..
+    } in spans
+    {
+        let failure = failures.iter().find_map(|failure| match failure {
+            FailedAudit::Unpersisted { command, error } if command.request_id == request_id => {
+                Some(error.clone())
+            }
+            _ => None,
+        });
+        let failure = failure.or_else(|| {
+            let Observation::StoreUnavailable { error, .. } = observation else {
+                return None;
+            };
+            unwritten
+                .iter()
+                .any(|command| command.request_id == request_id)
+                .then(|| error.clone())
+        });
+        if let Some(error) = failure {
+            span.set_status(SpanStatus::Error(error));
+        }
+    }
+}
+
+/// The trace context named by captured request headers
+fn traceparent(headers: &HttpHeaders) -> Option<TraceContext> {
+    headers
+        .as_pairs()
+        .iter()
+        .find(|(name, _)| name.as_ref().eq_ignore_ascii_case(TRACEPARENT))
+        .and_then(|(_, value)| TraceContext::parse(value.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::audit_types::{BodyContent, BodySize, HttpMethod, RequestUri};
+    use crate::domain::metrics::Timestamp;
+    use crate::domain::session::SessionId;
+    use crate::proxy::audit_usage::TRACKED_REQUESTS;
+    use crate::proxy::otlp::{AttributeValue, SpanRecord};
+    use tokio::sync::mpsc;
+
+    const PARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    fn command(request_id: &llm::RequestId, audit_event: AuditEventType) -> RecordAuditEvent {
+        let session_id = SessionId::generate();
+        RecordAuditEvent {
+            session_stream: RecordAuditEvent::session_stream_id(&session_id).unwrap(),
+            request_stream: RecordAuditEvent::request_stream_id(request_id).unwrap(),
+            request_id: request_id.clone(),
+            session_id,
+            audit_event,
+            timestamp: Timestamp::now(),
+            parsed_request: None,
+            calculated_cost: None,
+        }
+    }
+
+    fn request_received(request_id: &llm::RequestId, traceparent: &str) -> RecordAuditEvent {
+        let headers =
+            HttpHeaders::try_from_pairs(vec![("traceparent".to_string(), traceparent.to_string())])
+                .unwrap();
+        command(
+            request_id,
+            AuditEventType::RequestReceived {
+                method: HttpMethod::try_new("POST").unwrap(),
+                uri: RequestUri::try_new("/v1/chat/completions").unwrap(),
+                headers,
+                body_size: BodySize::from(0),
+            },
+        )
+    }
+
+    fn request_body(request_id: &llm::RequestId) -> RecordAuditEvent {
+        command(
+            request_id,
+            AuditEventType::RequestBodyCaptured {
+                body: BodyContent::new(
+                    br#"{"model":"gpt-4o","messages":[{"role":"user","content":"hi"}]}"#.to_vec(),
+                ),
+                truncated: false,
+            },
+        )
+    }
+
+    fn response_body(request_id: &llm::RequestId) -> RecordAuditEvent {
+        command(
+            request_id,
+            AuditEventType::ResponseBodyCaptured {
+                body: BodyContent::new(
+                    br#"{"model":"gpt-4o-2024-08-06","usage":{"prompt_tokens":12,"completion_tokens":5}}"#
+                        .to_vec(),
+                ),
+                truncated: false,
+            },
+        )
+    }
+
+    /// Audit spans with the request models the processor gives them
+    #[derive(Default)]
+    struct Tracked {
+        spans: AuditSpans,
+        models: RequestModels,
+    }
+
+    impl Tracked {
+        fn start_batch(
+            &mut self,
+            tracer: &Tracer,
+            commands: &[RecordAuditEvent],
+        ) -> Vec<PersistSpan> {
+            let usage = self.models.observe(commands).usage;
+            self.spans
+                .start_batch(tracer, commands, &self.models, &usage)
+        }
+    }
+
+    fn exported(spans: &mut mpsc::Receiver<SpanRecord>) -> Vec<SpanRecord> {
+        std::iter::from_fn(|| spans.try_recv().ok()).collect()
+    }
+
+    fn persisted() -> Observation {
+        Observation::BatchPersisted {
+            persisted: 1,
+            failures: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_span_continues_the_request_trace_with_gen_ai_attributes() {
+        let (tracer, mut receiver) = Tracer::channel(8);
+        let mut audit_spans = Tracked::default();
+        let request_id = llm::RequestId::generate();
+
+        let spans = audit_spans.start_batch(
+            &tracer,
+            &[
+                request_received(&request_id, PARENT),
+                request_body(&request_id),
+                response_body(&request_id),
+            ],
+        );
+        finish_batch(spans, &persisted());
+
+        let spans = exported(&mut receiver);
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.name, span_names::AUDIT_PERSIST);
+        assert_eq!(
+            span.context.trace_id.to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(span.parent_span_id.unwrap().to_string(), "00f067aa0ba902b7");
+        assert_eq!(span.status, SpanStatus::Unset);
+        for (key, value) in [
+            (attributes::AUDIT_EVENTS, AttributeValue::Int(3)),
+            (attributes::GEN_AI_SYSTEM, "openai".into()),
+            (attributes::GEN_AI_REQUEST_MODEL, "gpt-4o".into()),
+            (
+                attributes::GEN_AI_RESPONSE_MODEL,
+                "gpt-4o-2024-08-06".into(),
+            ),
+            (
+                attributes::GEN_AI_USAGE_INPUT_TOKENS,
+                AttributeValue::Int(12),
+            ),
+            (
+                attributes::GEN_AI_USAGE_OUTPUT_TOKENS,
+                AttributeValue::Int(5),
+            ),
+            (attributes::REQUEST_ID, request_id.to_string().into()),
+        ] {
+            assert_eq!(span.attribute(key), Some(&value), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_later_batches_reuse_the_remembered_trace_and_model() {
+        let (tracer, mut receiver) = Tracer::channel(8);
+        let mut audit_spans = Tracked::default();
+        let request_id = llm::RequestId::generate();
+
+        let first = audit_spans.start_batch(
+            &tracer,
+            &[
+                request_received(&request_id, PARENT),
+                request_body(&request_id),
+            ],
+        );
+        finish_batch(first, &persisted());
+        let second = audit_spans.start_batch(&tracer, &[response_body(&request_id)]);
+        finish_batch(second, &persisted());
+
+        let spans = exported(&mut receiver);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[1].context.trace_id, spans[0].context.trace_id);
+        assert_eq!(
+            spans[1].attribute(attributes::GEN_AI_USAGE_INPUT_TOKENS),
+            Some(&AttributeValue::Int(12))
+        );
+    }
+
+    #[test]
+    fn test_requests_without_a_trace_get_no_span() {
+        let (tracer, mut receiver) = Tracer::channel(8);
+        let mut audit_spans = Tracked::default();
+        let request_id = llm::RequestId::generate();
+
+        let spans = audit_spans.start_batch(
+            &tracer,
+            &[
+                request_received(&request_id, "not-a-traceparent"),
+                request_body(&request_id),
+            ],
+        );
+        finish_batch(spans, &persisted());
+
+        assert!(exported(&mut receiver).is_empty());
+    }
+
+    #[test]
+    fn test_unpersisted_requests_are_marked_as_errors() {
+        let (tracer, mut receiver) = Tracer::channel(8);
+        let mut audit_spans = Tracked::default();
+        let failed = llm::RequestId::generate();
+        let stored = llm::RequestId::generate();
+
+        let spans = audit_spans.start_batch(
+            &tracer,
+            &[
+                request_received(&failed, PARENT),
+                request_received(&stored, PARENT),
+            ],
+        );
+        finish_batch(
+            spans,
+            &Observation::BatchPersisted {
+                persisted: 1,
+                failures: vec![FailedAudit::Unpersisted {
+                    command: request_received(&failed, PARENT),
+                    error: "stream conflict".to_string(),
+                }],
+            },
+        );
+
+        let spans = exported(&mut receiver);
+        let status_of = |request_id: &llm::RequestId| {
+            spans
+                .iter()
+                .find(|span| {
+                    span.attribute(attributes::REQUEST_ID) == Some(&request_id.to_string().into())
+                })
+                .map(|span| span.status.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            status_of(&failed),
+            SpanStatus::Error("stream conflict".to_string())
+        );
+        assert_eq!(status_of(&stored), SpanStatus::Unset);
+    }
+
+    #[test]
+    fn test_only_the_most_recent_requests_are_remembered() {
+        let (tracer, _receiver) = Tracer::channel(1);
+        let mut audit_spans = Tracked::default();
+        let commands: Vec<_> = (0..TRACKED_REQUESTS + 10)
+            .map(|_| request_received(&llm::RequestId::generate(), PARENT))
+            .collect();
+
+        for command in &commands {
+            drop(audit_spans.start_batch(&tracer, std::slice::from_ref(command)));
+        }
+
+        let traced = |command: &RecordAuditEvent| {
+            audit_spans.spans.traces.get(&command.request_id).is_some()
+        };
+        assert!(!traced(&commands[0]));
+        assert!(traced(&commands[10]));
+    }
+}
@@ -0,0 +1,326 @@
+//! Models and token usage of audited requests
+//!
+//! A request's model is parsed from its captured body, using the URI and
+//! headers captured when it was received, and its token usage is read from
+//! the response body once the model says how. The parsed body is handed back
+//! so the deferred request can be recorded with its model. A model reported
+//! by the provider that routed the request takes precedence over the parsed
+//! one, since some providers, like Azure, name the model outside the body.
+//! These are captured as separate events that may be persisted by different
+//! batches, so what is needed of a request is remembered for the most recent
+//! requests.
+
+use crate::adapters::llm_usage::extract_response_metadata;
+use crate::adapters::proxy_audit::parse_request_body;
+use crate::domain::audit_types::{AuditEventType, HttpHeaders, RequestUri};
+use crate::domain::commands::audit_commands::{ParsedLlmRequestWithError, RecordAuditEvent};
+use crate::domain::llm::{self, ModelVersion};
+use crate::providers::ProviderMetadata;
+use std::collections::{HashMap, VecDeque};
+
+/// Requests remembered across batches
+pub const TRACKED_REQUESTS: usize = 4096;
+
+/// Values kept for the most recent requests, oldest first out
+#[derive(Debug)]
+pub struct RecentRequests<T> {
+    requests: HashMap<llm::RequestId, T>,
+    order: VecDeque<llm::RequestId>,
+}
+
+impl<T> Default for RecentRequests<T> {
+    fn default() -> Self {
+        Self {
+            requests: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+}
+
+impl<T> RecentRequests<T> {
+    /// Remember `value` for `request_id`, forgetting the oldest request if full
+    pub fn insert(&mut self, request_id: llm::RequestId, value: T) {
+        if self.requests.insert(request_id.clone(), value).is_none() {
+            self.order.push_back(request_id);
+        }
+        while self.order.len() > TRACKED_REQUESTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.requests.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, request_id: &llm::RequestId) -> Option<&T> {
+        self.requests.get(request_id)
+    }
+
+    pub fn get_mut(&mut self, request_id: &llm::RequestId) -> Option<&mut T> {
+        self.requests.get_mut(request_id)
+    }
+}
+
+#[derive(Debug)]
+enum TrackedRequest {
+    /// Waiting for the request body
+    Received {
+        uri: RequestUri,
+        headers: HttpHeaders,
+    },
+    /// The body was parsed; `None` when it named no model
+    Parsed(Option<ModelVersion>),
+}
+
+/// Usage reported by a response body in a batch
+#[derive(Debug, Clone)]
+pub struct ResponseUsage {
+    /// Position of the response body's command in the batch
+    pub index: usize,
+    /// The model the request asked for
+    pub model: ModelVersion,
+    pub metadata: ProviderMetadata,
+}
+
+/// A request body in a batch that parsed as an LLM request
+#[derive(Debug, Clone)]
+pub struct ParsedBody {
+    /// Position of the request body's command in the batch
+    pub index: usize,
+    pub parsed: ParsedLlmRequestWithError,
+}
+
+/// What was learned from the request and response bodies of a batch
+#[derive(Debug, Clone, Default)]
+pub struct ObservedBatch {
+    pub parsed_bodies: Vec<ParsedBody>,
+    pub usage: Vec<ResponseUsage>,
+}
+
+/// Models of recently received requests
+#[derive(Debug, Default)]
+pub struct RequestModels {
+    requests: RecentRequests<TrackedRequest>,
+    resolved: RecentRequests<ModelVersion>,
+}
+
+impl RequestModels {
+    /// Follow the requests of a batch, returning the request bodies that
+    /// parsed and the usage of every response body whose request's model is
+    /// known
+    pub fn observe(&mut self, commands: &[RecordAuditEvent]) -> ObservedBatch {
+        let mut observed = ObservedBatch::default();
+        for (index, command) in commands.iter().enumerate() {
+            match &command.audit_event {
+                AuditEventType::RequestReceived { uri, headers, .. } => {
+                    self.requests.insert(
+                        command.request_id.clone(),
+                        TrackedRequest::Received {
+                            uri: uri.clone(),
+                            headers: headers.clone(),
+                        },
+                    );
+                }
+                AuditEventType::RequestBodyCaptured { body, .. } => {
+                    let Some(tracked) = self.requests.get_mut(&command.request_id) else {
+                        continue;
+                    };
+                    if let TrackedRequest::Received { uri, headers } = tracked {
+                        let parsed = parse_request_body(body.as_ref(), uri, headers);
+                        let model = parsed
+                            .parsed
+                            .as_ref()
+                            .map(|parsed| parsed.model_version.clone());
+                        *tracked = TrackedRequest::Parsed(model);
+                        if parsed.parsed.is_some() {
+                            observed.parsed_bodies.push(ParsedBody { index, parsed });
+                        }
+                    }
+                }
+                AuditEventType::ProviderResolved {
+                    provider,
+                    model_id: Some(model_id),
+                } => {
+                    self.resolved.insert(
+                        command.request_id.clone(),
+                        ModelVersion {
+                            provider: provider.clone(),
+                            model_id: model_id.clone(),
+                        },
+                    );
+                }
+                AuditEventType::ResponseBodyCaptured { body, .. } => {
+                    if let Some(model) = self.model(&command.request_id) {
+                        observed.usage.push(ResponseUsage {
+                            index,
+                            model: model.clone(),
+                            metadata: extract_response_metadata(model, body.as_ref()),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        observed
+    }
+
+    /// The model `request_id` asked for, once its provider reported it or
+    /// its body has been seen
+    pub fn model(&self, request_id: &llm::RequestId) -> Option<&ModelVersion> {
+        if let Some(model) = self.resolved.get(request_id) {
+            return Some(model);
+        }
+        match self.requests.get(request_id)? {
+            TrackedRequest::Parsed(model) => model.as_ref(),
+            TrackedRequest::Received { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::audit_types::{BodyContent, BodySize, HttpMethod};
+    use crate::domain::metrics::Timestamp;
+    use crate::domain::session::SessionId;
+
+    fn command(request_id: &llm::RequestId, audit_event: AuditEventType) -> RecordAuditEvent {
+        let session_id = SessionId::generate();
+        RecordAuditEvent {
+            session_stream: RecordAuditEvent::session_stream_id(&session_id).unwrap(),
+            request_stream: RecordAuditEvent::request_stream_id(request_id).unwrap(),
+            request_id: request_id.clone(),
+            session_id,
+            audit_event,
+            timestamp: Timestamp::now(),
+            parsed_request: None,
+            calculated_cost: None,
+        }
+    }
+
+    fn request_received(request_id: &llm::RequestId) -> RecordAuditEvent {
+        command(
+            request_id,
+            AuditEventType::RequestReceived {
+                method: HttpMethod::try_new("POST").unwrap(),
+                uri: RequestUri::try_new("/v1/chat/completions").unwrap(),
+                headers: HttpHeaders::new(),
+                body_size: BodySize::from(0),
+            },
+        )
+    }
+
+    fn body(
+        request_id: &llm::RequestId,
+        event: fn(BodyContent) -> AuditEventType,
+        body: &[u8],
+    ) -> RecordAuditEvent {
+        command(request_id, event(BodyContent::new(body.to_vec())))
+    }
+
+    fn request_body(content: BodyContent) -> AuditEventType {
+        AuditEventType::RequestBodyCaptured {
+            body: content,
+            truncated: false,
+        }
+    }
+
+    fn response_body(content: BodyContent) -> AuditEventType {
+        AuditEventType::ResponseBodyCaptured {
+            body: content,
+            truncated: false,
+        }
+    }
+
+    const REQUEST: &[u8] = br#"{"model":"gpt-4o","messages":[{"role":"user","content":"hi"}]}"#;
+    const RESPONSE: &[u8] =
+        br#"{"model":"gpt-4o-2024-08-06","usage":{"prompt_tokens":12,"completion_tokens":5}}"#;
+
+    #[test]
+    fn test_usage_is_read_with_the_request_model() {
+        let mut models = RequestModels::default();
+        let request_id = llm::RequestId::generate();
+
+        let observed = models.observe(&[
+            request_received(&request_id),
+            body(&request_id, request_body, REQUEST),
+            body(&request_id, response_body, RESPONSE),
+        ]);
+
+        assert_eq!(observed.parsed_bodies.len(), 1);
+        assert_eq!(observed.parsed_bodies[0].index, 1);
+        let usage = observed.usage;
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].index, 2);
+        assert_eq!(usage[0].model.model_id.as_ref(), "gpt-4o");
+        assert_eq!(usage[0].metadata.request_tokens.unwrap().into_inner(), 12);
+        assert_eq!(usage[0].metadata.response_tokens.unwrap().into_inner(), 5);
+    }
+
+    #[test]
+    fn test_the_model_is_remembered_across_batches() {
+        let mut models = RequestModels::default();
+        let request_id = llm::RequestId::generate();
+
+        models.observe(&[request_received(&request_id)]);
+        models.observe(&[body(&request_id, request_body, REQUEST)]);
+        let usage = models
+            .observe(&[body(&request_id, response_body, RESPONSE)])
+            .usage;
+
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].index, 0);
+    }
+
+    #[test]
+    fn test_the_model_reported_by_the_provider_is_preferred() {
+        let mut models = RequestModels::default();
+        let request_id = llm::RequestId::generate();
+        let resolved = command(
+            &request_id,
+            AuditEventType::ProviderResolved {
+                provider: llm::LlmProvider::Azure,
+                model_id: Some(crate::domain::types::ModelId::try_new("gpt-4").unwrap()),
+            },
+        );
+
+        let usage = models
+            .observe(&[
+                request_received(&request_id),
+                body(&request_id, request_body, br#"{"messages":[]}"#),
+                resolved,
+                body(&request_id, response_body, RESPONSE),
+            ])
+            .usage;
+
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].model.provider, llm::LlmProvider::Azure);
+        assert_eq!(usage[0].model.model_id.as_ref(), "gpt-4");
+        assert_eq!(usage[0].metadata.request_tokens.unwrap().into_inner(), 12);
+    }
+
+    #[test]
+    fn test_responses_of_unknown_requests_have_no_usage() {
+        let mut models = RequestModels::default();
+        let request_id = llm::RequestId::generate();
+
+        let observed = models.observe(&[body(&request_id, response_body, RESPONSE)]);
+
+        assert!(observed.usage.is_empty());
+        assert!(models.model(&request_id).is_none());
+    }
+
+    #[test]
+    fn test_only_the_most_recent_requests_are_remembered() {
+        let mut recent = RecentRequests::default();
+        let request_ids: Vec<_> = (0..TRACKED_REQUESTS + 10)
+            .map(|_| llm::RequestId::generate())
+            .collect();
+
+        for request_id in &request_ids {
+            recent.insert(request_id.clone(), ());
+        }
+
+        assert_eq!(recent.requests.len(), TRACKED_REQUESTS);
+        assert!(recent.get(&request_ids[0]).is_none());
+        assert!(recent.get(&request_ids[TRACKED_REQUESTS + 9]).is_some());
+    }
+}
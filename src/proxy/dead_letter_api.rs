@@ -0,0 +1,528 @@
+//! Dead-letter admin API
+//!
+//! Audit events the audit path could not process are kept in a
+//! [`DeadLetterStore`]. These endpoints inspect them and, once the cause is
+//! fixed, replay them through the event store or purge them:
+//!
+//! - `GET /api/v1/dead-letters` lists dead letters, oldest first
+//! - `GET /api/v1/dead-letters/{id}` returns one with its base64 payload
+//! - `POST /api/v1/dead-letters/{id}/replay` replays one
+//! - `POST /api/v1/dead-letters/replay` replays a page of them
+//! - `DELETE /api/v1/dead-letters/{id}` removes one
+//! - `DELETE /api/v1/dead-letters` purges all of them, or one `phase`
+//!
+//! A replayed dead letter is removed once its command is stored; a failed
+//! replay keeps it with the new error and a higher retry count.
+
+use crate::adapters::proxy_audit::convert_audit_event;
+use crate::domain::commands::RecordAuditEvent;
+use crate::infrastructure::dead_letters::{
+    DeadLetter, DeadLetterId, DeadLetterPhase, DeadLetterQuery, DeadLetterStore, DeadLetterSummary,
+};
+use crate::infrastructure::eventcore::projections::PageSize;
+use crate::infrastructure::eventcore::service::EventCoreService;
+use crate::proxy::audit_codec::decode_audit_event;
+use crate::proxy::error_response::ErrorResponse;
+use crate::proxy::session_api::{bad_request, internal_error, InvalidQueryParameter};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use tracing::error;
+
+/// A page of dead letters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeadLetterPage {
+    pub dead_letters: Vec<DeadLetterSummary>,
+    /// Pass as `after` for the next page; absent on the last page
+    pub next_after: Option<DeadLetterId>,
+}
+
+/// What happened to one replayed dead letter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReplayOutcome {
+    pub id: DeadLetterId,
+    pub replayed: bool,
+    /// Why the replay failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Failed replays so far, including this one
+    pub retry_count: u32,
+}
+
+/// Outcome of replaying a page of dead letters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReplayReport {
+    pub replayed: usize,
+    pub failed: Vec<ReplayOutcome>,
+    /// Pass as `after` to replay the next page; absent on the last page
+    pub next_after: Option<DeadLetterId>,
+}
+
+/// Build a dead-letter query from raw query-string pairs
+///
+/// Supported parameters: `phase` (`deserialize`, `convert` or `persist`),
+/// `after` (a dead-letter ID, for paging) and `limit`.
+pub fn parse_dead_letter_query(
+    params: &[(String, String)],
+) -> Result<DeadLetterQuery, InvalidQueryParameter> {
+    let mut query = DeadLetterQuery::default();
+
+    for (name, value) in params {
+        let invalid = |reason: &dyn std::fmt::Display| InvalidQueryParameter::new(name, reason);
+
+        match name.as_str() {
+            "phase" => query.phase = Some(parse_phase(value).map_err(|e| invalid(&e))?),
+            "after" => query.after = Some(parse_id(value).map_err(|e| invalid(&e))?),
+            "limit" => {
+                let value: u32 = value.parse().map_err(|e| invalid(&e))?;
+                query.limit = PageSize::try_new(value).map_err(|e| invalid(&e))?;
+            }
+            _ => return Err(invalid(&"unknown parameter")),
+        }
+    }
+
+    Ok(query)
+}
+
+fn parse_phase(value: &str) -> Result<DeadLetterPhase, &'static str> {
+    DeadLetterPhase::from_label(value).ok_or("expected deserialize, convert or persist")
+}
+
+fn parse_id(value: &str) -> Result<DeadLetterId, uuid::Error> {
+    value.parse().map(DeadLetterId::new)
+}
+
+/// The domain command a dead letter's payload stands for
+pub fn command_for(letter: &DeadLetter) -> Result<RecordAuditEvent, String> {
+    match letter.phase {
+        DeadLetterPhase::Deserialize | DeadLetterPhase::Convert => {
+            let event = decode_audit_event(&letter.payload).map_err(|e| e.to_string())?;
+            convert_audit_event(&event).map_err(|e| e.to_string())
+        }
+        DeadLetterPhase::Persist => {
+            serde_json::from_slice(&letter.payload).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Replay one dead letter, removing it once stored or noting the failure
+async fn replay(
+    store: &dyn DeadLetterStore,
+    event_store: &EventCoreService,
+    letter: &DeadLetter,
+) -> crate::Result<ReplayOutcome> {
+    let stored = match command_for(letter) {
+        Ok(command) => event_store
+            .execute_command(command)
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+
+    match stored {
+        Ok(()) => {
+            store.remove(letter.id).await?;
+            Ok(ReplayOutcome {
+                id: letter.id,
+                replayed: true,
+                error: None,
+                retry_count: letter.retry_count,
+            })
+        }
+        Err(e) => {
+            store.record_retry(letter.id, &e).await?;
+            Ok(ReplayOutcome {
+                id: letter.id,
+                replayed: false,
+                error: Some(e),
+                retry_count: letter.retry_count + 1,
+            })
+        }
+    }
+}
+
+/// Respond to `GET /api/v1/dead-letters`
+pub async fn list_dead_letters(
+    store: &dyn DeadLetterStore,
+    params: &[(String, String)],
+) -> Response {
+    let query = match parse_dead_letter_query(params) {
+        Ok(query) => query,
+        Err(e) => return bad_request(&e),
+    };
+
+    match store.list(&query).await {
+        Ok(dead_letters) => {
+            let next_after = next_after(
+                &query,
+                dead_letters.last().map(|letter| letter.id),
+                dead_letters.len(),
+            );
+            Json(DeadLetterPage {
+                dead_letters,
+                next_after,
+            })
+            .into_response()
+        }
+        Err(e) => {
+            error!("Failed to list dead letters: {e}");
+            internal_error()
+        }
+    }
+}
+
+/// Respond to `GET /api/v1/dead-letters/{id}`
+pub async fn dead_letter(store: &dyn DeadLetterStore, id: &str) -> Response {
+    let id = match parse_id(id) {
+        Ok(id) => id,
+        Err(e) => return bad_request(&InvalidQueryParameter::new("id", e)),
+    };
+
+    match store.get(id).await {
+        Ok(Some(letter)) => Json(letter).into_response(),
+        Ok(None) => not_found(id),
+        Err(e) => {
+            error!("Failed to load dead letter {id}: {e}");
+            internal_error()
+        }
+    }
+}
+
+/// Respond to `POST /api/v1/dead-letters/{id}/replay`
+///
+/// A failed replay answers `422 Unprocessable Entity` with the outcome.
+pub async fn replay_dead_letter(
+    store: &dyn DeadLetterStore,
+    event_store: Option<&EventCoreService>,
+    id: &str,
+) -> Response {
+    let id = match parse_id(id) {
+        Ok(id) => id,
+        Err(e) => return bad_request(&InvalidQueryParameter::new("id", e)),
+    };
+    let Some(event_store) = event_store else {
+        return no_event_store();
+    };
+
+    let letter = match store.get(id).await {
+        Ok(Some(letter)) => letter,
+        Ok(None) => return not_found(id),
+        Err(e) => {
+            error!("Failed to load dead letter {id}: {e}");
+            return internal_error();
+        }
+    };
+
+    match replay(store, event_store, &letter).await {
+        Ok(outcome) if outcome.replayed => Json(outcome).into_response(),
+        Ok(outcome) => (StatusCode::UNPROCESSABLE_ENTITY, Json(outcome)).into_response(),
+        Err(e) => {
+            error!("Failed to replay dead letter {id}: {e}");
+            internal_error()
+        }
+    }
+}
+
+/// Respond to `POST /api/v1/dead-letters/replay`
+///
+/// Replays the page of dead letters the same parameters would list.
+pub async fn replay_dead_letters(
+    store: &dyn DeadLetterStore,
+    event_store: Option<&EventCoreService>,
+    params: &[(String, String)],
+) -> Response {
+    let query = match parse_dead_letter_query(params) {
+        Ok(query) => query,
+        Err(e) => return bad_request(&e),
+    };
+    let Some(event_store) = event_store else {
+        return no_event_store();
+    };
+
+    let page = match store.list(&query).await {
+        Ok(page) => page,
+        Err(e) => {
+            error!("Failed to list dead letters: {e}");
+            return internal_error();
+        }
+    };
+
+    let mut report = ReplayReport {
+        replayed: 0,
+        failed: Vec::new(),
+        next_after: next_after(&query, page.last().map(|letter| letter.id), page.len()),
+    };
+    for summary in &page {
+        let letter = match store.get(summary.id).await {
+            Ok(Some(letter)) => letter,
+            // Removed since it was listed
+            Ok(None) => continue,
+            Err(e) => {
+                error!("Failed to load dead letter {}: {e}", summary.id);
+                return internal_error();
+            }
+        };
+        match replay(store, event_store, &letter).await {
+            Ok(outcome) if outcome.replayed => report.replayed += 1,
+            Ok(outcome) => report.failed.push(outcome),
+            Err(e) => {
+                error!("Failed to replay dead letter {}: {e}", summary.id);
+                return internal_error();
+            }
+        }
+    }
+
+    Json(report).into_response()
+}
+
+/// Respond to `DELETE /api/v1/dead-letters/{id}`
+pub async fn remove_dead_letter(store: &dyn DeadLetterStore, id: &str) -> Response {
+    let id = match parse_id(id) {
+        Ok(id) => id,
+        Err(e) => return bad_request(&InvalidQueryParameter::new("id", e)),
+    };
+
+    match store.remove(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => not_found(id),
+        Err(e) => {
+            error!("Failed to remove dead letter {id}: {e}");
+            internal_error()
+        }
+    }
+}
+
+/// Respond to `DELETE /api/v1/dead-letters`; `phase` limits what is purged
+pub async fn purge_dead_letters(
+    store: &dyn DeadLetterStore,
+    params: &[(String, String)],
+) -> Response {
+    let mut phase = None;
+    for (name, value) in params {
+        match name.as_str() {
+            "phase" => match parse_phase(value) {
+                Ok(value) => phase = Some(value),
+                Err(e) => return bad_request(&InvalidQueryParameter::new(name, e)),
+            },
+            _ => return bad_request(&InvalidQueryParameter::new(name, "unknown parameter")),
+        }
+    }
+
+    match store.purge(phase).await {
+        Ok(purged) => Json(serde_json::json!({ "purged": purged })).into_response(),
+        Err(e) => {
+            error!("Failed to purge dead letters: {e}");
+            internal_error()
+        }
+    }
+}
+
+/// Where the next page starts, when this one was full
+fn next_after(
+    query: &DeadLetterQuery,
+    last: Option<DeadLetterId>,
+    len: usize,
+) -> Option<DeadLetterId> {
+    if len < *query.limit.as_ref() as usize {
+        return None;
+    }
+    last
+}
+
+fn not_found(id: DeadLetterId) -> Response {
+    ErrorResponse::new("DEAD_LETTER_NOT_FOUND", format!("No dead letter {id}"))
+        .into_response_with_status(StatusCode::NOT_FOUND)
+}
+
+fn no_event_store() -> Response {
+    ErrorResponse::new(
+        "NO_EVENT_STORE",
+        "Dead letters cannot be replayed without an event store",
+    )
+    .into_response_with_status(StatusCode::SERVICE_UNAVAILABLE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::dead_letters::{InMemoryDeadLetterStore, NewDeadLetter};
+    use crate::proxy::audit_codec::AuditEventFormat;
+    use crate::proxy::types::*;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn request_received() -> AuditEvent {
+        AuditEvent {
+            request_id: RequestId::new(),
+            session_id: SessionId::new(),
+            timestamp: chrono::Utc::now(),
+            event_type: AuditEventType::RequestReceived {
+                method: HttpMethod::try_new("POST".to_string()).unwrap(),
+                uri: RequestUri::try_new("/v1/chat/completions".to_string()).unwrap(),
+                headers: Headers::new(),
+                body_size: BodySize::from(0),
+            },
+        }
+    }
+
+    async fn record(
+        store: &InMemoryDeadLetterStore,
+        phase: DeadLetterPhase,
+        payload: Vec<u8>,
+    ) -> DeadLetterId {
+        store
+            .record(NewDeadLetter {
+                request_id: None,
+                phase,
+                error: "event store rejected the command".to_string(),
+                payload,
+            })
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_phase_paging_and_limit() {
+        let after = DeadLetterId::generate();
+        let query = parse_dead_letter_query(&params(&[
+            ("phase", "persist"),
+            ("after", &after.to_string()),
+            ("limit", "10"),
+        ]))
+        .unwrap();
+
+        assert_eq!(query.phase, Some(DeadLetterPhase::Persist));
+        assert_eq!(query.after, Some(after));
+        assert_eq!(*query.limit.as_ref(), 10);
+
+        for pairs in [
+            vec![("phase", "replay")],
+            vec![("after", "not-a-uuid")],
+            vec![("limit", "0")],
+            vec![("status", "failed")],
+        ] {
+            assert!(
+                parse_dead_letter_query(&params(&pairs)).is_err(),
+                "{pairs:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn payloads_are_read_back_by_phase() {
+        let event = request_received();
+        let encoded = AuditEventFormat::Binary.encode(&event).unwrap();
+        let command = convert_audit_event(&event).unwrap();
+        let letter = |phase, payload| DeadLetter {
+            id: DeadLetterId::generate(),
+            request_id: None,
+            phase,
+            error: String::new(),
+            retry_count: 0,
+            payload,
+            failed_at: chrono::Utc::now(),
+            last_retried_at: None,
+        };
+
+        for letter in [
+            letter(DeadLetterPhase::Deserialize, encoded.clone()),
+            letter(DeadLetterPhase::Convert, encoded),
+            letter(
+                DeadLetterPhase::Persist,
+                serde_json::to_vec(&command).unwrap(),
+            ),
+        ] {
+            let replayed = command_for(&letter).unwrap();
+            assert_eq!(replayed.request_id, command.request_id);
+        }
+
+        assert!(command_for(&letter(DeadLetterPhase::Convert, b"garbage".to_vec())).is_err());
+    }
+
+    #[tokio::test]
+    async fn replay_stores_the_command_and_removes_the_dead_letter() {
+        let store = InMemoryDeadLetterStore::new();
+        let event_store = EventCoreService::with_memory_store();
+        let event = request_received();
+        let id = record(
+            &store,
+            DeadLetterPhase::Persist,
+            serde_json::to_vec(&convert_audit_event(&event).unwrap()).unwrap(),
+        )
+        .await;
+
+        let response = replay_dead_letter(&store, Some(&event_store), &id.to_string()).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(store.get(id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn failed_replays_keep_the_dead_letter_and_count_the_retry() {
+        let store = InMemoryDeadLetterStore::new();
+        let event_store = EventCoreService::with_memory_store();
+        let broken = record(&store, DeadLetterPhase::Deserialize, b"garbage".to_vec()).await;
+        let event = request_received();
+        record(
+            &store,
+            DeadLetterPhase::Convert,
+            AuditEventFormat::Binary.encode(&event).unwrap(),
+        )
+        .await;
+
+        let response = replay_dead_letters(&store, Some(&event_store), &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let remaining = store.list(&DeadLetterQuery::default()).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, broken);
+        assert_eq!(remaining[0].retry_count, 1);
+
+        let response = replay_dead_letter(&store, Some(&event_store), &broken.to_string()).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(store.get(broken).await.unwrap().unwrap().retry_count, 2);
+    }
+
+    #[tokio::test]
+    async fn replay_needs_an_event_store() {
+        let store = InMemoryDeadLetterStore::new();
+        let id = record(&store, DeadLetterPhase::Persist, Vec::new()).await;
+
+        let response = replay_dead_letter(&store, None, &id.to_string()).await;
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(store.get(id).await.unwrap().unwrap().retry_count, 0);
+    }
+
+    #[tokio::test]
+    async fn purge_removes_one_phase_or_everything() {
+        let store = InMemoryDeadLetterStore::new();
+        record(&store, DeadLetterPhase::Deserialize, Vec::new()).await;
+        let kept = record(&store, DeadLetterPhase::Persist, Vec::new()).await;
+
+        let response = purge_dead_letters(&store, &params(&[("phase", "deserialize")])).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(store.get(kept).await.unwrap().is_some());
+
+        let response = remove_dead_letter(&store, &kept.to_string()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = remove_dead_letter(&store, &kept.to_string()).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        record(&store, DeadLetterPhase::Convert, Vec::new()).await;
+        purge_dead_letters(&store, &[]).await;
+        assert!(store
+            .list(&DeadLetterQuery::default())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -66,6 +66,13 @@ impl ErrorResponse {
     }
 }
 
+/// Response extension marking an error body the client should receive as is
+///
+/// The error handling middleware replaces every other error body with the
+/// standard one for its status.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientFacingError;
+
 /// Extension trait for consistent error formatting
 pub trait ErrorResponseExt {
     /// Convert to standardized error response
@@ -138,6 +145,35 @@ impl ErrorResponseExt for ProxyError {
                     }),
                 )
             }
+            TlsConfiguration(msg) => ErrorResponse::new(
+                "TLS_CONFIGURATION_ERROR",
+                format!("TLS configuration error: {msg}"),
+            ),
+            ProviderConfiguration(msg) => ErrorResponse::new(
+                "PROVIDER_CONFIGURATION_ERROR",
+                format!("Provider configuration error: {msg}"),
+            ),
+            BudgetExceeded(rejection) => ErrorResponse::new(
+                "BUDGET_EXCEEDED",
+                format!(
+                    "The {} budget of {} is spent until {}",
+                    rejection.period,
+                    rejection.scope,
+                    rejection.resets_at.to_rfc3339()
+                ),
+            )
+            .with_details(serde_json::json!({
+                "application_id": rejection.scope.application_id,
+                "environment": rejection.scope.environment_id,
+                "period": rejection.period,
+                "limit_usd": rejection.limit,
+                "spent_usd": rejection.spent,
+                "resets_at": rejection.resets_at,
+            })),
+            UnboundApiKey => ErrorResponse::new(
+                "APPLICATION_BINDING_REQUIRED",
+                "Budgets are enforced and the API key is not bound to an application",
+            ),
         }
     }
 
@@ -154,11 +190,17 @@ impl ErrorResponseExt for ProxyError {
             InvalidHttpStatusCode(_) => StatusCode::BAD_GATEWAY,
             InvalidHeader { .. } => StatusCode::BAD_REQUEST,
             RingBufferOverflow { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            BudgetExceeded(rejection) => StatusCode::from_u16(*rejection.status.as_ref())
+                .unwrap_or(StatusCode::TOO_MANY_REQUESTS),
+            UnboundApiKey => StatusCode::FORBIDDEN,
             HttpError(_) | HyperError(_) => StatusCode::BAD_GATEWAY,
             Internal(msg) if msg.starts_with("Connection error:") => StatusCode::BAD_GATEWAY,
-            IoError(_) | SerializationError(_) | Internal(_) | AuditEventCreationFailed(_) => {
-                StatusCode::INTERNAL_SERVER_ERROR
-            }
+            IoError(_)
+            | SerializationError(_)
+            | Internal(_)
+            | AuditEventCreationFailed(_)
+            | TlsConfiguration(_)
+            | ProviderConfiguration(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
@@ -232,6 +274,33 @@ mod tests {
         assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
     }
 
+    #[test]
+    fn test_budget_exceeded_uses_the_budget_status() {
+        use crate::domain::budget::{BudgetPeriod, BudgetRejection, BudgetScope, RejectStatus};
+        use crate::domain::pricing::UsdAmount;
+        use crate::domain::session::ApplicationId;
+
+        let limit = UsdAmount::try_new(rust_decimal::Decimal::from(50)).unwrap();
+        let error = ProxyError::BudgetExceeded(Box::new(BudgetRejection {
+            scope: BudgetScope {
+                application_id: ApplicationId::try_new("support-bot".to_string()).unwrap(),
+                environment_id: None,
+            },
+            period: BudgetPeriod::Monthly,
+            limit,
+            spent: limit,
+            resets_at: chrono::Utc::now(),
+            status: RejectStatus::try_new(402).unwrap(),
+        }));
+
+        let response = error.to_error_response();
+        assert_eq!(response.code, "BUDGET_EXCEEDED");
+        assert_eq!(error.status_code(), StatusCode::PAYMENT_REQUIRED);
+        let details = response.details.unwrap();
+        assert_eq!(details["application_id"], "support-bot");
+        assert_eq!(details["period"], "monthly");
+    }
+
     #[test]
     fn test_standard_error_responses() {
         let response = standard_error_response(StatusCode::NOT_FOUND, Some("req-123"));
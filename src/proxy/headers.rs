@@ -24,6 +24,36 @@ pub const BEARER_PREFIX: &str = "Bearer ";
 /// Standard header re-exports for convenience
 pub use header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, HOST, USER_AGENT};
 
+/// Session identification headers defined by ADR-0012
+///
+/// Header names are stored lowercase; `http::HeaderMap` normalizes incoming
+/// names, so matching is case-insensitive.
+pub mod session {
+    /// Prefix shared by every Union Square header; all of them are stripped upstream
+    pub const PREFIX: &str = "x-unionsquare-";
+
+    /// Client-chosen session identifier (any string format)
+    pub const SESSION_ID: &str = "x-unionsquare-session-id";
+
+    /// Parent session identifier for nested sessions
+    pub const PARENT_ID: &str = "x-unionsquare-parent-id";
+
+    /// End-user identifier
+    pub const USER_ID: &str = "x-unionsquare-user-id";
+
+    /// Application the request is made on behalf of; budgets apply per application
+    pub const APPLICATION_ID: &str = "x-unionsquare-application-id";
+
+    /// Deployment environment of the application (e.g. `production`)
+    pub const ENVIRONMENT: &str = "x-unionsquare-environment";
+
+    /// Structured JSON metadata
+    pub const APPLICATION_CONTEXT: &str = "x-unionsquare-application-context";
+
+    /// Prefix for arbitrary key/value metadata
+    pub const METADATA_PREFIX: &str = "x-unionsquare-metadata-";
+}
+
 /// Well-known paths
 pub mod paths {
     /// Default path when none is specified
@@ -34,6 +64,33 @@ pub mod paths {
 
     /// Metrics endpoint path
     pub const METRICS: &str = "/metrics";
+
+    /// Live view of current sessions and request rates
+    pub const LIVE: &str = "/api/v1/live";
+
+    /// Session listing
+    pub const SESSIONS: &str = "/api/v1/sessions";
+
+    /// One session with its requests and responses
+    pub const SESSION: &str = "/api/v1/sessions/{session_id}";
+
+    /// Full-text search over prompts and responses
+    pub const SEARCH: &str = "/api/v1/search";
+
+    /// Hourly usage and cost rollup
+    pub const USAGE: &str = "/api/v1/usage";
+
+    /// Audit events the audit path could not process
+    pub const DEAD_LETTERS: &str = "/api/v1/dead-letters";
+
+    /// Replay of the oldest dead letters
+    pub const DEAD_LETTERS_REPLAY: &str = "/api/v1/dead-letters/replay";
+
+    /// One dead letter with its payload
+    pub const DEAD_LETTER: &str = "/api/v1/dead-letters/{id}";
+
+    /// Replay of one dead letter
+    pub const DEAD_LETTER_REPLAY: &str = "/api/v1/dead-letters/{id}/replay";
 }
 
 /// Common content types (re-exported from centralized constants)
@@ -51,6 +108,13 @@ mod tests {
         assert!(X_TARGET_URL.starts_with("x-"));
         assert!(X_REQUEST_ID.starts_with("x-"));
         assert!(X_SESSION_ID.starts_with("x-"));
+        assert!(session::SESSION_ID.starts_with(session::PREFIX));
+        assert!(session::PARENT_ID.starts_with(session::PREFIX));
+        assert!(session::USER_ID.starts_with(session::PREFIX));
+        assert!(session::APPLICATION_ID.starts_with(session::PREFIX));
+        assert!(session::ENVIRONMENT.starts_with(session::PREFIX));
+        assert!(session::APPLICATION_CONTEXT.starts_with(session::PREFIX));
+        assert!(session::METADATA_PREFIX.starts_with(session::PREFIX));
 
         // Ensure paths are valid
         assert!(paths::DEFAULT.starts_with('/'));
@@ -37,17 +37,25 @@
 //! `hot_path_planner`. This module (the imperative shell) calls those
 //! planners and then interprets the planned effects.
 
+use crate::domain::session::SessionScope;
+use crate::providers::ProviderMetadata;
 use crate::proxy::audit_recorder::{
     extract_headers_vec, parse_http_method, parse_http_status, parse_request_uri, AuditRecorder,
-    RingBufferAuditRecorder,
+    ChunkCapture, RingBufferAuditRecorder, TeeBody,
 };
 use crate::proxy::hot_path_planner::{plan_request_audit, plan_response_audit};
+use crate::proxy::otlp::{end_upstream_span, start_upstream_span, Tracer};
+use crate::proxy::provider_router::ProviderRouter;
 use crate::proxy::ring_buffer::RingBuffer;
+use crate::proxy::session_headers::resolve_session_id;
+use crate::proxy::spill_log::SpillLog;
 use crate::proxy::types::*;
+use crate::proxy::upstream_client::UpstreamClient;
 use crate::proxy::url_resolver::UrlResolver;
 use axum::body::Body;
 use http_body_util::BodyExt;
 use hyper::{Request, Response};
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -56,45 +64,120 @@ use std::time::Instant;
 pub struct StreamingHotPathService {
     config: Arc<ProxyConfig>,
     audit_recorder: Arc<RingBufferAuditRecorder>,
-    client: hyper_util::client::legacy::Client<
-        hyper_util::client::legacy::connect::HttpConnector,
-        Body,
-    >,
+    client: UpstreamClient,
+    tracer: Option<Tracer>,
 }
 
 impl StreamingHotPathService {
-    /// Create a new streaming hot path service
-    pub fn new(config: ProxyConfig, ring_buffer: Arc<RingBuffer>) -> Self {
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .http1_title_case_headers(true)
-                .http1_preserve_header_case(true)
-                .build_http();
-
-        let audit_recorder = Arc::new(RingBufferAuditRecorder::new(ring_buffer));
+    /// Create a new streaming hot path service forwarding through `client`
+    pub fn new(config: ProxyConfig, ring_buffer: Arc<RingBuffer>, client: UpstreamClient) -> Self {
+        let audit_recorder =
+            Arc::new(RingBufferAuditRecorder::new(ring_buffer).with_format(config.audit_format));
 
         Self {
             config: Arc::new(config),
             audit_recorder,
             client,
+            tracer: None,
         }
     }
 
+    /// Spill audit events the ring buffer cannot take to `spill`
+    pub fn with_spill_log(mut self, spill: Arc<SpillLog>) -> Self {
+        self.audit_recorder = Arc::new((*self.audit_recorder).clone().with_spill_log(spill));
+        self
+    }
+
+    /// Record a span for each upstream call with `tracer`
+    pub fn with_tracer(mut self, tracer: Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
     /// Forward a request to the target URL with streaming
+    ///
+    /// `session` holds the `X-UnionSquare-*` headers already removed from
+    /// `request`; every audit event for the request is recorded under the
+    /// session they identify.
     pub async fn forward_request(
         &self,
         request: Request<Body>,
         target_url: TargetUrl,
         request_id: RequestId,
+        session: SessionContext,
+    ) -> ProxyResult<Response<Body>> {
+        // Resolve the target URI using centralized strategy
+        let (mut parts, body) = request.into_parts();
+        parts.uri = UrlResolver::resolve_target_uri(&target_url, &parts.uri)?;
+        let request = Request::from_parts(parts, body);
+
+        self.forward_audited(request, request_id, session, |outgoing| async move {
+            // The audit record keeps the traceparent received; the provider sees the upstream span
+            let (mut parts, body) = outgoing.into_parts();
+            let upstream_span =
+                start_upstream_span(self.tracer.as_ref(), parts.uri.host(), &mut parts.headers);
+
+            // Forward the request with timeout
+            let response_future = self.client.request(Request::from_parts(parts, body));
+            let timeout_duration = self.config.request_timeout;
+
+            let response = tokio::time::timeout(timeout_duration, response_future)
+                .await
+                .map_err(|_| ProxyError::RequestTimeout(timeout_duration))
+                .and_then(|response| {
+                    response.map_err(|e| ProxyError::Internal(format!("Connection error: {e}")))
+                });
+            end_upstream_span(upstream_span, &response);
+            Ok((response?.map(Body::new), None))
+        })
+        .await
+    }
+
+    /// Forward a request to the provider `router` resolves for its path
+    ///
+    /// Audited like [`Self::forward_request`], with what the provider
+    /// reports about the request recorded once it responds.
+    pub async fn forward_to_provider(
+        &self,
+        request: Request<Body>,
+        router: &ProviderRouter,
+        request_id: RequestId,
+        session: SessionContext,
     ) -> ProxyResult<Response<Body>> {
+        self.forward_audited(request, request_id, session, |outgoing| async move {
+            let (response, metadata) = router.route_request(outgoing).await?;
+            Ok((response, Some(metadata)))
+        })
+        .await
+    }
+
+    /// Audit a request while `forward` sends it upstream
+    async fn forward_audited<F, Fut>(
+        &self,
+        request: Request<Body>,
+        request_id: RequestId,
+        session: SessionContext,
+        forward: F,
+    ) -> ProxyResult<Response<Body>>
+    where
+        F: FnOnce(Request<Body>) -> Fut,
+        Fut: Future<Output = ProxyResult<(Response<Body>, Option<ProviderMetadata>)>>,
+    {
         let start_time = Instant::now();
+        // Set by the auth middleware; session IDs are only unique per caller
+        let scope = request
+            .extensions()
+            .get::<SessionScope>()
+            .copied()
+            .unwrap_or_default();
+        let session_id = resolve_session_id(&session, scope);
+        if !session.is_empty() {
+            self.audit_recorder
+                .record_session_context(request_id, session_id, session);
+        }
 
         // Extract parts from the incoming request
-        let (mut parts, body) = request.into_parts();
-
-        // Resolve the target URI using centralized strategy
-        let resolved_uri = UrlResolver::resolve_target_uri(&target_url, &parts.uri)?;
-        parts.uri = resolved_uri;
+        let (parts, body) = request.into_parts();
 
         // --- Boundary conversion (structural -> semantic) ---
         let headers_vec = extract_headers_vec(&parts.headers);
@@ -131,19 +214,16 @@ impl StreamingHotPathService {
 
         // --- Imperative effect: fire-and-forget audit write ---
         self.audit_recorder
-            .record_request_audit(request_id, request_audit_plan);
+            .record_request_audit(request_id, session_id, request_audit_plan);
+        let mut request_capture = self
+            .chunk_capture(request_id, session_id, true)
+            .start_capture_task();
+        request_capture.send(body_bytes_buf.clone());
+        request_capture.finish();
 
         // Create outgoing request with the collected body
         let outgoing_request = Request::from_parts(parts, Body::from(body_bytes_buf));
-
-        // Forward the request with timeout
-        let response_future = self.client.request(outgoing_request);
-        let timeout_duration = self.config.request_timeout;
-
-        let response = tokio::time::timeout(timeout_duration, response_future)
-            .await
-            .map_err(|_| ProxyError::RequestTimeout(timeout_duration))?
-            .map_err(|e| ProxyError::Internal(format!("Connection error: {e}")))?;
+        let (response, provider_metadata) = forward(outgoing_request).await?;
 
         let duration_ms = start_time.elapsed().as_millis() as u64;
 
@@ -154,39 +234,194 @@ impl StreamingHotPathService {
         let headers_vec = extract_headers_vec(&response_parts.headers);
         let status_result = parse_http_status(response_parts.status);
 
+        // The full size is only known once streaming ends; until then use
+        // the upstream's declared length when it sends one
+        let declared_size = http_body::Body::size_hint(&response_body)
+            .exact()
+            .unwrap_or(0);
+
         // --- Pure planning: decide what audit facts to record ---
         let response_audit_plan = plan_response_audit(
             status_result,
             headers_vec,
-            BodySize::from(0), // We don't know the size in streaming mode
+            BodySize::from(declared_size as usize),
             DurationMillis::from(duration_ms),
         );
 
         // --- Imperative effect: fire-and-forget audit write ---
         self.audit_recorder
-            .record_response_audit(request_id, response_audit_plan);
+            .record_response_audit(request_id, session_id, response_audit_plan);
+        if let Some(metadata) = &provider_metadata {
+            self.audit_recorder
+                .record_provider_metadata(request_id, session_id, metadata);
+        }
 
-        // For MVP, return the streaming response as-is
-        // TODO: Add chunked capture to ring buffer while streaming
+        // Tee the response into the ring buffer while it streams to the client
+        let response_capture = self
+            .chunk_capture(request_id, session_id, false)
+            .with_limit(*self.config.max_response_size.as_ref())
+            .start_capture_task();
         Ok(Response::from_parts(
             response_parts,
-            Body::new(response_body),
+            Body::new(TeeBody::new(response_body, response_capture)),
         ))
     }
+
+    fn chunk_capture(
+        &self,
+        request_id: RequestId,
+        session_id: SessionId,
+        is_request: bool,
+    ) -> ChunkCapture {
+        ChunkCapture::new(
+            self.audit_recorder.clone(),
+            request_id,
+            session_id,
+            is_request,
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::proxy::audit_codec::decode_audit_event;
     use crate::proxy::types::ProxyConfig;
+    use crate::proxy::upstream_client::build_upstream_client;
+
+    fn service_for(config: ProxyConfig, ring_buffer: Arc<RingBuffer>) -> StreamingHotPathService {
+        let client = build_upstream_client(&config.upstream_tls).unwrap();
+        StreamingHotPathService::new(config, ring_buffer, client)
+    }
 
     #[tokio::test]
     async fn test_streaming_hot_path_creation() {
         let config = ProxyConfig::default();
         let ring_buffer = Arc::new(RingBuffer::new(&config.ring_buffer));
-        let service = StreamingHotPathService::new(config, ring_buffer);
+        let service = service_for(config, ring_buffer);
 
         // Service should be created successfully
         let _ = service;
     }
+
+    #[tokio::test]
+    async fn test_events_for_a_request_share_the_client_session() {
+        use crate::proxy::session_headers::take_session_context;
+
+        // Upstream echoes back the header names it received
+        let upstream = axum::Router::new().fallback(|headers: hyper::HeaderMap| async move {
+            headers
+                .keys()
+                .map(|name| name.as_str().to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, upstream).await.unwrap();
+        });
+
+        let config = ProxyConfig::default();
+        let ring_buffer = Arc::new(RingBuffer::new(&config.ring_buffer));
+        let service = service_for(config, ring_buffer.clone());
+
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .header("X-UnionSquare-Session-Id", "conversation-17")
+            .header("X-UnionSquare-Metadata-Feature", "chat")
+            .body(Body::from("{}"))
+            .unwrap();
+        let session = take_session_context(request.headers_mut());
+        let target = TargetUrl::try_new(format!("http://{upstream_addr}")).unwrap();
+
+        let response = service
+            .forward_request(request, target, RequestId::new(), session)
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let upstream_headers = String::from_utf8(body.to_vec()).unwrap();
+        assert!(
+            !upstream_headers.contains("x-unionsquare"),
+            "session headers leaked upstream: {upstream_headers}"
+        );
+
+        let mut events = Vec::new();
+        while let Some((_, data)) = ring_buffer.read() {
+            events.push(decode_audit_event(&data).unwrap());
+        }
+        assert!(matches!(
+            &events[0].event_type,
+            AuditEventType::SessionContextReceived { context }
+                if context.session_id.as_deref() == Some("conversation-17")
+        ));
+        assert!(events
+            .iter()
+            .all(|event| event.session_id.as_ref() == events[0].session_id.as_ref()));
+        assert_eq!(events[0].session_id.as_ref().get_version_num(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_response_body_is_captured_while_streaming() {
+        let upstream_body: String = "0123456789".repeat(4_000);
+        let served = upstream_body.clone();
+        let upstream = axum::Router::new().fallback(move || async move { served });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, upstream).await.unwrap();
+        });
+
+        let config = ProxyConfig::default();
+        let ring_buffer = Arc::new(RingBuffer::new(&config.ring_buffer));
+        let service = service_for(config, ring_buffer.clone());
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .body(Body::from(r#"{"model":"gpt-4"}"#))
+            .unwrap();
+        let target = TargetUrl::try_new(format!("http://{upstream_addr}")).unwrap();
+
+        let response = service
+            .forward_request(request, target, RequestId::new(), SessionContext::default())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.len(), upstream_body.len());
+
+        let mut request_body = Vec::new();
+        let mut response_body = Vec::new();
+        let mut response_complete = None;
+        tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            while response_complete.is_none() {
+                let Some((_, data)) = ring_buffer.read() else {
+                    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                    continue;
+                };
+                match decode_audit_event(&data).unwrap().event_type {
+                    AuditEventType::RequestChunk { data, .. } => request_body.extend(data),
+                    AuditEventType::ResponseReceived { body_size, .. } => {
+                        assert_eq!(*body_size.as_ref(), upstream_body.len());
+                    }
+                    AuditEventType::ResponseChunk { data, .. } => response_body.extend(data),
+                    AuditEventType::ResponseBodyComplete {
+                        body_size,
+                        truncated,
+                    } => response_complete = Some((*body_size.as_ref(), truncated)),
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .expect("response capture should complete");
+
+        assert_eq!(request_body, br#"{"model":"gpt-4"}"#);
+        assert_eq!(response_body, upstream_body.as_bytes());
+        assert_eq!(response_complete, Some((upstream_body.len(), false)));
+    }
 }
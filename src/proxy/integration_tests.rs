@@ -1,5 +1,6 @@
 //! Integration tests for end-to-end proxy flow
 
+use crate::proxy::audit_codec::decode_audit_event;
 use crate::proxy::service::ProxyService;
 use crate::proxy::types::*;
 use crate::proxy::AuthConfig;
@@ -80,6 +81,9 @@ mod tests {
             request_timeout: Duration::from_secs(5),
             ring_buffer: RingBufferConfig::default(),
             bedrock_region: None,
+            upstream_tls: Default::default(),
+            providers: Vec::new(),
+            audit_format: Default::default(),
         };
 
         // Create auth configuration
@@ -165,6 +169,183 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_metrics_endpoint_reports_proxied_requests() {
+        run_mock_backend(8089)
+            .await
+            .expect("Failed to start mock backend");
+
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .api_keys
+            .insert(ApiKey::try_new("test-key".to_string()).unwrap());
+        let app = ProxyService::new(ProxyConfig::default()).into_router(auth_config);
+
+        for (uri, body) in [("/echo", "ping"), ("/status/503", "")] {
+            let request = Request::builder()
+                .method(if body.is_empty() { "GET" } else { "POST" })
+                .uri(format!("http://localhost:8080{uri}"))
+                .header("Authorization", "Bearer test-key")
+                .header(
+                    crate::proxy::headers::X_TARGET_URL,
+                    format!("http://localhost:8089{uri}"),
+                )
+                .body(Body::from(body))
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+        }
+
+        let request = Request::builder()
+            .uri("http://localhost:8080/metrics")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            crate::proxy::observability::PROMETHEUS_CONTENT_TYPE
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let metrics = String::from_utf8(body.to_vec()).unwrap();
+
+        for line in [
+            "union_square_requests_total 2",
+            "union_square_requests_failed_total 1",
+            "union_square_received_bytes_total 4",
+            // The echoed "ping"; the 503 body is replaced by the error middleware
+            "union_square_sent_bytes_total 4",
+            "union_square_provider_requests_total{provider=\"passthrough\"} 2",
+            "union_square_request_duration_seconds_count 2",
+        ] {
+            assert!(
+                metrics.lines().any(|l| l == line),
+                "missing {line}:\n{metrics}"
+            );
+        }
+        assert!(metrics.contains("union_square_ring_buffer_writes_total "));
+        assert!(metrics.contains("union_square_audit_events_processed_total "));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_continues_the_client_trace() {
+        use crate::proxy::http::build_upstream_client;
+        use crate::proxy::observability::{span_names, OtlpExporter, TraceContext};
+        use crate::proxy::test_utils::collector::{attribute, spawn_collector};
+
+        const CLIENT_TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        // Upstream reporting the traceparent it received
+        let upstream = axum::Router::new().route(
+            "/v1/chat/completions",
+            axum::routing::post(|headers: axum::http::HeaderMap| async move {
+                (
+                    [("x-received-traceparent", headers["traceparent"].clone())],
+                    r#"{"model":"gpt-4o-2024-08-06","usage":{"prompt_tokens":9,"completion_tokens":4}}"#,
+                )
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, upstream).await.unwrap() });
+
+        let collector = spawn_collector().await;
+        let mut otlp = OtlpConfig::new(TargetUrl::try_new(collector.endpoint()).unwrap());
+        otlp.export_interval_ms = ExportIntervalMs::try_new(20).unwrap();
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+        let (tracer, _exporter) = OtlpExporter::spawn(&otlp, client).unwrap();
+
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .api_keys
+            .insert(ApiKey::try_new("test-key".to_string()).unwrap());
+        let app = ProxyService::new(ProxyConfig::default())
+            .with_tracer(tracer)
+            .into_router(auth_config);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("http://localhost:8080/v1/chat/completions")
+            .header("Authorization", "Bearer test-key")
+            .header("traceparent", CLIENT_TRACEPARENT)
+            .header(
+                crate::proxy::headers::X_TARGET_URL,
+                format!("http://{upstream_addr}"),
+            )
+            .body(Body::from(
+                r#"{"model":"gpt-4o","messages":[{"role":"user","content":"hi"}]}"#,
+            ))
+            .unwrap();
+        // Holding on to `app` keeps the audit processor running until the spans are checked
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let received = TraceContext::parse(
+            response.headers()["x-received-traceparent"]
+                .to_str()
+                .unwrap(),
+        )
+        .unwrap();
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        // The provider sees the proxy's upstream span as its parent, in the client's trace
+        let client = TraceContext::parse(CLIENT_TRACEPARENT).unwrap();
+        assert_eq!(received.trace_id, client.trace_id);
+        assert_ne!(received.span_id, client.span_id);
+
+        let proxy_span = collector
+            .wait_for_span(|span| span["name"] == span_names::PROXY)
+            .await;
+        assert_eq!(proxy_span["traceId"], client.trace_id.to_string());
+        assert_eq!(proxy_span["parentSpanId"], client.span_id.to_string());
+        assert_eq!(proxy_span["kind"], 2);
+        assert_eq!(
+            attribute(&proxy_span, "http.response.status_code"),
+            Some(&serde_json::json!({"intValue": "200"}))
+        );
+
+        let upstream_span = collector
+            .wait_for_span(|span| span["name"] == span_names::UPSTREAM)
+            .await;
+        assert_eq!(upstream_span["spanId"], received.span_id.to_string());
+        assert_eq!(upstream_span["parentSpanId"], proxy_span["spanId"]);
+
+        // Audit persistence is attributed to the proxy hop, with GenAI attributes
+        let audit_span = collector
+            .wait_for_span(|span| {
+                span["name"] == span_names::AUDIT_PERSIST
+                    && attribute(span, "gen_ai.usage.output_tokens").is_some()
+            })
+            .await;
+        assert_eq!(audit_span["traceId"], client.trace_id.to_string());
+        assert_eq!(audit_span["parentSpanId"], proxy_span["spanId"]);
+        for (key, value) in [
+            (
+                "gen_ai.system",
+                serde_json::json!({"stringValue": "openai"}),
+            ),
+            (
+                "gen_ai.request.model",
+                serde_json::json!({"stringValue": "gpt-4o"}),
+            ),
+            (
+                "gen_ai.usage.input_tokens",
+                serde_json::json!({"intValue": "9"}),
+            ),
+            (
+                "gen_ai.usage.output_tokens",
+                serde_json::json!({"intValue": "4"}),
+            ),
+        ] {
+            assert_eq!(attribute(&audit_span, key), Some(&value), "{key}");
+        }
+    }
+
     #[tokio::test]
     async fn test_error_handling() {
         // Start mock backend
@@ -237,6 +418,9 @@ mod tests {
             request_timeout: Duration::from_secs(5),
             ring_buffer: RingBufferConfig::default(),
             bedrock_region: None,
+            upstream_tls: Default::default(),
+            providers: Vec::new(),
+            audit_format: Default::default(),
         };
 
         let mut auth_config = AuthConfig::default();
@@ -278,6 +462,9 @@ mod tests {
             request_timeout: Duration::from_secs(5),
             ring_buffer: RingBufferConfig::default(),
             bedrock_region: None,
+            upstream_tls: Default::default(),
+            providers: Vec::new(),
+            audit_format: Default::default(),
         };
 
         let mut auth_config = AuthConfig::default();
@@ -319,6 +506,9 @@ mod tests {
             request_timeout: Duration::from_millis(50), // Very short timeout
             ring_buffer: RingBufferConfig::default(),
             bedrock_region: None,
+            upstream_tls: Default::default(),
+            providers: Vec::new(),
+            audit_format: Default::default(),
         };
 
         let mut auth_config = AuthConfig::default();
@@ -452,6 +642,388 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_live_view_endpoint() {
+        use crate::domain::metrics::Timestamp;
+        use crate::domain::session::SessionId as DomainSessionId;
+        use crate::infrastructure::eventcore::projections::{
+            LiveView, ReadModelStore, ReadModelUpdate,
+        };
+
+        let live_view = Arc::new(LiveView::default());
+        let now = chrono::Utc::now();
+        live_view
+            .apply(
+                &[ReadModelUpdate::SessionActivity {
+                    session_id: DomainSessionId::generate(),
+                    at: Timestamp::try_new(now).unwrap(),
+                }],
+                eventcore_types::StreamPosition::new(uuid::Uuid::now_v7()),
+            )
+            .await
+            .unwrap();
+
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .admin_api_keys
+            .insert(ApiKey::try_new("admin-key".to_string()).unwrap());
+        let app = ProxyService::new(ProxyConfig::default())
+            .with_live_view(live_view)
+            .into_router(auth_config);
+
+        let request = Request::builder()
+            .uri(crate::proxy::headers::paths::LIVE)
+            .header("Authorization", "Bearer admin-key")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let snapshot: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(snapshot["active_sessions"].as_array().unwrap().len(), 1);
+        assert_eq!(snapshot["rate_window_secs"], 60);
+    }
+
+    #[tokio::test]
+    async fn test_spent_hard_budget_rejects_requests() {
+        use crate::domain::budget::{BudgetCatalog, BudgetEntry, BudgetPeriod, RejectStatus};
+        use crate::domain::llm::{LlmProvider, ModelVersion, RequestId as DomainRequestId};
+        use crate::domain::metrics::Timestamp;
+        use crate::domain::pricing::{TokenUsage, UsdAmount};
+        use crate::domain::session::{ApplicationId, SessionId as DomainSessionId};
+        use crate::domain::types::{ModelId, TokenCount};
+        use crate::infrastructure::eventcore::projections::{
+            BudgetLedger, ReadModelStore, ReadModelUpdate,
+        };
+        use crate::proxy::error_response::ErrorResponse;
+
+        let application_id = ApplicationId::try_new("support-bot".to_string()).unwrap();
+        let catalog = BudgetCatalog::new(vec![BudgetEntry {
+            application_id: application_id.clone(),
+            environment: None,
+            period: BudgetPeriod::Daily,
+            limit_usd: UsdAmount::try_new(rust_decimal::Decimal::from(10)).unwrap(),
+            alert_at_percent: Vec::new(),
+            hard_limit: true,
+            reject_status: RejectStatus::default(),
+        }])
+        .unwrap();
+        let ledger = Arc::new(BudgetLedger::new(Arc::new(catalog)));
+        let session_id = DomainSessionId::generate();
+        let tokens = TokenCount::try_new(0).unwrap();
+        ledger
+            .apply(
+                &[ReadModelUpdate::CostRecorded {
+                    request_id: DomainRequestId::generate(),
+                    session_id,
+                    application_id: Some(application_id),
+                    environment_id: None,
+                    model_version: ModelVersion {
+                        provider: LlmProvider::OpenAI,
+                        model_id: ModelId::try_new("gpt-4o".to_string()).unwrap(),
+                    },
+                    usage: TokenUsage {
+                        input: tokens,
+                        output: tokens,
+                        cache_read: tokens,
+                        cache_write: tokens,
+                    },
+                    total: UsdAmount::try_new(rust_decimal::Decimal::from(12)).unwrap(),
+                    calculated_at: Timestamp::now(),
+                }],
+                eventcore_types::StreamPosition::new(uuid::Uuid::now_v7()),
+            )
+            .await
+            .unwrap();
+
+        let mut auth_config = AuthConfig::default();
+        for (key, application) in [
+            ("support-key", Some("support-bot")),
+            ("billing-key", Some("billing-bot")),
+            ("unbound-key", None),
+        ] {
+            let key = ApiKey::try_new(key.to_string()).unwrap();
+            auth_config.api_keys.insert(key.clone());
+            if let Some(application) = application {
+                let binding = ApplicationBinding {
+                    application_id: ApplicationId::try_new(application.to_string()).unwrap(),
+                    environment: None,
+                };
+                auth_config.application_bindings.insert(key, binding);
+            }
+        }
+        let app = ProxyService::new(ProxyConfig::default())
+            .with_budget_ledger(ledger)
+            .into_router(auth_config);
+        // The application header names the other application, and is ignored
+        let request = |key: &str, application: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("http://localhost:8080/v1/chat/completions")
+                .header("Authorization", format!("Bearer {key}"))
+                .header(crate::proxy::headers::session::APPLICATION_ID, application)
+                .header(
+                    crate::proxy::headers::X_TARGET_URL,
+                    "http://localhost:9999/",
+                ) // Non-existent port
+                .body(Body::empty())
+                .unwrap()
+        };
+        let error = |response: axum::response::Response| async move {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<ErrorResponse>(&body).unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("support-key", "billing-bot"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(hyper::header::RETRY_AFTER));
+        let error_body = error(response).await;
+        assert_eq!(error_body.code, "BUDGET_EXCEEDED");
+        let details = error_body.details.unwrap();
+        assert_eq!(details["limit_usd"], "10");
+        assert_eq!(details["spent_usd"], "12");
+        assert_eq!(details["period"], "daily");
+
+        // Other applications are still forwarded
+        let response = app
+            .clone()
+            .oneshot(request("billing-key", "support-bot"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        // Spend of a key bound to no application could not be budgeted
+        let response = app
+            .clone()
+            .oneshot(request("unbound-key", "billing-bot"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(error(response).await.code, "APPLICATION_BINDING_REQUIRED");
+    }
+
+    #[tokio::test]
+    async fn test_session_query_endpoints() {
+        use crate::domain::events::DomainEvent;
+        use crate::domain::metrics::Timestamp;
+        use crate::domain::session::SessionId as DomainSessionId;
+        use crate::domain::streams::session_stream;
+        use crate::infrastructure::eventcore::projections::{
+            SessionDirectory, SessionListQuery, SessionPage,
+        };
+        use crate::infrastructure::eventcore::service::EventCoreService;
+        use eventcore_types::{EventStore, StreamVersion, StreamWrites};
+
+        /// Answers every listing with no sessions, remembering the query
+        #[derive(Default)]
+        struct EmptyDirectory {
+            last_query: std::sync::Mutex<Option<SessionListQuery>>,
+        }
+
+        #[async_trait::async_trait]
+        impl SessionDirectory for EmptyDirectory {
+            async fn list_sessions(&self, query: &SessionListQuery) -> crate::Result<SessionPage> {
+                *self.last_query.lock().unwrap() = Some(query.clone());
+                Ok(SessionPage {
+                    sessions: Vec::new(),
+                    next_cursor: None,
+                })
+            }
+        }
+
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+        let session_id = DomainSessionId::generate();
+        let stream_id = session_stream(&session_id).unwrap();
+        let writes = StreamWrites::new()
+            .register_stream(stream_id.clone(), StreamVersion::new(0))
+            .unwrap()
+            .append(DomainEvent::LlmRequestDeferred {
+                stream_id,
+                request_id: crate::domain::llm::RequestId::generate(),
+                session_id: session_id.clone(),
+                received_at: Timestamp::now(),
+            })
+            .unwrap();
+        event_store
+            .memory_store()
+            .unwrap()
+            .append_events(writes)
+            .await
+            .unwrap();
+
+        let directory = Arc::new(EmptyDirectory::default());
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .admin_api_keys
+            .insert(ApiKey::try_new("admin-key".to_string()).unwrap());
+        let app = ProxyService::new(ProxyConfig::default())
+            .with_event_store(event_store)
+            .with_session_directory(directory.clone())
+            .into_router(auth_config);
+
+        let get = |uri: String| {
+            Request::builder()
+                .uri(uri)
+                .header("Authorization", "Bearer admin-key")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(get(format!(
+                "{}?application_id=support-bot&has_errors=true&metadata.customer=acme",
+                crate::proxy::headers::paths::SESSIONS
+            )))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let query = directory.last_query.lock().unwrap().clone().unwrap();
+        assert_eq!(query.has_errors, Some(true));
+        assert_eq!(query.metadata.len(), 1);
+
+        let response = app
+            .clone()
+            .oneshot(get(format!(
+                "{}?status=bogus",
+                crate::proxy::headers::paths::SESSIONS
+            )))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(get(format!(
+                "{}/{}",
+                crate::proxy::headers::paths::SESSIONS,
+                session_id.as_ref()
+            )))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let detail: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(detail["requests"].as_array().unwrap().len(), 1);
+        assert_eq!(detail["requests"][0]["status"], "Pending");
+
+        let response = app
+            .oneshot(get(format!(
+                "{}/{}",
+                crate::proxy::headers::paths::SESSIONS,
+                uuid::Uuid::now_v7()
+            )))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_search_endpoint() {
+        use crate::infrastructure::eventcore::projections::{
+            SearchField, SearchHit, SearchIndex, SearchQuery, SearchResults,
+        };
+
+        /// Returns one highlighted hit for whatever was searched
+        struct EchoIndex;
+
+        #[async_trait::async_trait]
+        impl SearchIndex for EchoIndex {
+            async fn search(&self, query: &SearchQuery) -> crate::Result<SearchResults> {
+                Ok(SearchResults {
+                    hits: vec![SearchHit {
+                        session_id: uuid::Uuid::now_v7(),
+                        request_id: uuid::Uuid::now_v7(),
+                        application_id: None,
+                        model_id: query.model_id.as_ref().map(|m| m.to_string()),
+                        field: SearchField::Response,
+                        snippet: format!("<mark>{}</mark>", query.text),
+                        rank: 1.0,
+                        received_at: chrono::Utc::now(),
+                    }],
+                })
+            }
+        }
+
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .admin_api_keys
+            .insert(ApiKey::try_new("admin-key".to_string()).unwrap());
+        let app = ProxyService::new(ProxyConfig::default())
+            .with_search_index(Arc::new(EchoIndex))
+            .into_router(auth_config);
+
+        let get = |query: &str| {
+            Request::builder()
+                .uri(format!("{}?{query}", crate::proxy::headers::paths::SEARCH))
+                .header("Authorization", "Bearer admin-key")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(get("q=%22refund+policy%22&model=gpt-4o"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let results: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            results["hits"][0]["snippet"],
+            "<mark>\"refund policy\"</mark>"
+        );
+        assert_eq!(results["hits"][0]["field"], "response");
+        assert_eq!(results["hits"][0]["model_id"], "gpt-4o");
+
+        let response = app.oneshot(get("model=gpt-4o")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_admin_apis_refuse_proxy_keys() {
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .api_keys
+            .insert(ApiKey::try_new("test-key".to_string()).unwrap());
+        auth_config
+            .admin_api_keys
+            .insert(ApiKey::try_new("admin-key".to_string()).unwrap());
+        let app = ProxyService::new(ProxyConfig::default())
+            .with_dead_letter_store(Arc::new(
+                crate::infrastructure::dead_letters::InMemoryDeadLetterStore::new(),
+            ))
+            .into_router(auth_config);
+
+        let purge = |key: &str| {
+            Request::builder()
+                .method("DELETE")
+                .uri(crate::proxy::headers::paths::DEAD_LETTERS)
+                .header("Authorization", format!("Bearer {key}"))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(purge("test-key")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.oneshot(purge("admin-key")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
     #[tokio::test]
     async fn test_invalid_http_methods() {
         // Start mock backend
@@ -501,7 +1073,7 @@ mod tests {
         // Verify no error events for invalid methods
         let mut error_events = 0;
         while let Some((_, data)) = ring_buffer.read() {
-            if let Ok(event) = serde_json::from_slice::<AuditEvent>(&data) {
+            if let Ok(event) = decode_audit_event(&data) {
                 if matches!(
                     event.event_type,
                     AuditEventType::Error {
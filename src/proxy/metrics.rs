@@ -0,0 +1,679 @@
+//! Operational metrics served at `/metrics` in the Prometheus text format
+//!
+//! Hot path metrics follow ADR-0016: plain atomic counters in fixed-size
+//! arrays, so recording a request never allocates or takes a lock. Audit
+//! processors publish their counters into [`AuditMetrics`] as they run.
+//! [`render_prometheus`] turns a [`MetricsSnapshot`] of these, the ring
+//! buffer and the spill log into the exposition format.
+
+use crate::providers::ProviderId;
+use crate::proxy::audit_steps::AuditCounters;
+use crate::proxy::ring_buffer::RingBufferStats;
+use crate::proxy::spill_log::SpillStats;
+use bytes::Bytes;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use std::fmt::Write;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// `Content-Type` of the Prometheus text exposition format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Number of per-provider counter slots
+pub const MAX_PROVIDERS: usize = 7;
+
+/// Number of latency histogram buckets
+///
+/// Bucket `n` counts requests that took under 2^n milliseconds; the last
+/// bucket takes everything slower.
+pub const LATENCY_BUCKETS: usize = 16;
+
+const PROVIDER_LABELS: [&str; MAX_PROVIDERS] = [
+    "passthrough",
+    ProviderId::BEDROCK,
+    ProviderId::OPENAI,
+    ProviderId::ANTHROPIC,
+    ProviderId::VERTEX,
+    ProviderId::AZURE,
+    "other",
+];
+
+/// Where a request's per-provider counters are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderSlot(usize);
+
+impl ProviderSlot {
+    /// Requests forwarded to the URL named in their headers
+    pub const PASSTHROUGH: Self = Self(0);
+    /// Requests to providers without a slot of their own
+    pub const OTHER: Self = Self(MAX_PROVIDERS - 1);
+
+    /// The slot of a registered provider
+    pub fn for_provider(id: &ProviderId) -> Self {
+        PROVIDER_LABELS
+            .iter()
+            .position(|label| *label == id.as_ref())
+            .map_or(Self::OTHER, Self)
+    }
+
+    /// The `provider` label the slot is exported under
+    pub fn label(self) -> &'static str {
+        PROVIDER_LABELS[self.0]
+    }
+}
+
+/// Zero-allocation request counters updated by the hot path
+#[derive(Debug, Default)]
+pub struct HotPathMetrics {
+    requests_total: AtomicU64,
+    requests_failed: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    provider_requests: [AtomicU64; MAX_PROVIDERS],
+    provider_latency_sum_us: [AtomicU64; MAX_PROVIDERS],
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS],
+    latency_sum_us: AtomicU64,
+}
+
+impl HotPathMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one proxied request
+    ///
+    /// `latency` runs until the response headers are returned; `success`
+    /// is false for proxy errors and upstream 5xx responses.
+    #[inline]
+    pub fn record(&self, provider: ProviderSlot, latency: Duration, success: bool) {
+        let latency_us = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.requests_total.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.requests_failed.fetch_add(1, Ordering::Relaxed);
+        }
+
+        self.provider_requests[provider.0].fetch_add(1, Ordering::Relaxed);
+        self.provider_latency_sum_us[provider.0].fetch_add(latency_us, Ordering::Relaxed);
+
+        self.latency_buckets[latency_bucket(latency_us / 1000)].fetch_add(1, Ordering::Relaxed);
+        self.latency_sum_us.fetch_add(latency_us, Ordering::Relaxed);
+    }
+
+    /// Count body bytes read from clients
+    #[inline]
+    pub fn add_bytes_received(&self, bytes: u64) {
+        self.bytes_received.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Count body bytes streamed back to clients
+    #[inline]
+    pub fn add_bytes_sent(&self, bytes: u64) {
+        self.bytes_sent.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> HotPathSnapshot {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        HotPathSnapshot {
+            requests_total: load(&self.requests_total),
+            requests_failed: load(&self.requests_failed),
+            bytes_sent: load(&self.bytes_sent),
+            bytes_received: load(&self.bytes_received),
+            provider_requests: self.provider_requests.each_ref().map(load),
+            provider_latency_sum_us: self.provider_latency_sum_us.each_ref().map(load),
+            latency_buckets: self.latency_buckets.each_ref().map(load),
+            latency_sum_us: load(&self.latency_sum_us),
+        }
+    }
+}
+
+/// Histogram bucket for a latency: 0 below 1ms, then one per power of two
+#[inline]
+fn latency_bucket(latency_ms: u64) -> usize {
+    ((u64::BITS - latency_ms.leading_zeros()) as usize).min(LATENCY_BUCKETS - 1)
+}
+
+/// Point-in-time copy of [`HotPathMetrics`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HotPathSnapshot {
+    pub requests_total: u64,
+    pub requests_failed: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub provider_requests: [u64; MAX_PROVIDERS],
+    pub provider_latency_sum_us: [u64; MAX_PROVIDERS],
+    pub latency_buckets: [u64; LATENCY_BUCKETS],
+    pub latency_sum_us: u64,
+}
+
+/// Which byte counter a [`MeteredBody`] adds to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteDirection {
+    /// Request bodies read from clients
+    Received,
+    /// Response bodies streamed to clients
+    Sent,
+}
+
+pin_project_lite::pin_project! {
+    /// Body wrapper counting data frames into [`HotPathMetrics`] as they stream
+    pub struct MeteredBody<B> {
+        #[pin]
+        inner: B,
+        metrics: Arc<HotPathMetrics>,
+        direction: ByteDirection,
+    }
+}
+
+impl<B> MeteredBody<B> {
+    pub fn new(inner: B, metrics: Arc<HotPathMetrics>, direction: ByteDirection) -> Self {
+        Self {
+            inner,
+            metrics,
+            direction,
+        }
+    }
+}
+
+impl<B: HttpBody<Data = Bytes>> HttpBody for MeteredBody<B> {
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let polled = this.inner.poll_frame(cx);
+
+        if let Poll::Ready(Some(Ok(frame))) = &polled {
+            if let Some(data) = frame.data_ref() {
+                let bytes = data.len() as u64;
+                match this.direction {
+                    ByteDirection::Received => this.metrics.add_bytes_received(bytes),
+                    ByteDirection::Sent => this.metrics.add_bytes_sent(bytes),
+                }
+            }
+        }
+
+        polled
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Counters of the running audit processors, one set per ring buffer shard
+///
+/// Each processor publishes its own counters after every step, so a scrape
+/// sees them without waiting for shutdown.
+#[derive(Debug)]
+pub struct AuditMetrics {
+    shards: Vec<ShardCounters>,
+}
+
+#[derive(Debug, Default)]
+struct ShardCounters {
+    events_processed: AtomicU64,
+    deserialization_failures: AtomicU64,
+    conversion_failures: AtomicU64,
+    persist_failures: AtomicU64,
+    batches_persisted: AtomicU64,
+    incomplete_bodies: AtomicU64,
+    dead_lettered: AtomicU64,
+}
+
+impl AuditMetrics {
+    pub fn new(shard_count: usize) -> Self {
+        Self {
+            shards: (0..shard_count).map(|_| ShardCounters::default()).collect(),
+        }
+    }
+
+    /// Replace the counters published for `shard`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard` is not below the shard count.
+    pub fn publish(&self, shard: usize, counters: AuditCounters) {
+        let shard = &self.shards[shard];
+        let store = |cell: &AtomicU64, value| cell.store(value, Ordering::Relaxed);
+        store(&shard.events_processed, counters.events_processed);
+        store(
+            &shard.deserialization_failures,
+            counters.deserialization_failures,
+        );
+        store(&shard.conversion_failures, counters.conversion_failures);
+        store(&shard.persist_failures, counters.persist_failures);
+        store(&shard.batches_persisted, counters.batches_persisted);
+        store(&shard.incomplete_bodies, counters.incomplete_bodies);
+        store(&shard.dead_lettered, counters.dead_lettered);
+    }
+
+    /// Counters of every shard summed together
+    pub fn totals(&self) -> AuditCounters {
+        self.shards
+            .iter()
+            .map(|shard| {
+                let load = |cell: &AtomicU64| cell.load(Ordering::Relaxed);
+                AuditCounters {
+                    events_processed: load(&shard.events_processed),
+                    deserialization_failures: load(&shard.deserialization_failures),
+                    conversion_failures: load(&shard.conversion_failures),
+                    persist_failures: load(&shard.persist_failures),
+                    batches_persisted: load(&shard.batches_persisted),
+                    incomplete_bodies: load(&shard.incomplete_bodies),
+                    dead_lettered: load(&shard.dead_lettered),
+                }
+            })
+            .sum()
+    }
+}
+
+/// Everything `/metrics` reports, read at one point in time
+#[derive(Debug, Clone)]
+pub struct MetricsSnapshot {
+    pub hot_path: HotPathSnapshot,
+    pub ring_buffer: RingBufferStats,
+    pub ring_buffer_shards: usize,
+    /// Absent when no spill log is configured
+    pub spill: Option<SpillStats>,
+    pub audit: AuditCounters,
+}
+
+/// Render a snapshot in the Prometheus text exposition format
+pub fn render_prometheus(snapshot: &MetricsSnapshot) -> String {
+    let mut out = Exposition(String::with_capacity(4096));
+    let hot_path = &snapshot.hot_path;
+
+    out.counter(
+        "union_square_requests_total",
+        "Requests proxied",
+        hot_path.requests_total,
+    );
+    out.counter(
+        "union_square_requests_failed_total",
+        "Requests that failed in the proxy or got a 5xx response upstream",
+        hot_path.requests_failed,
+    );
+    out.counter(
+        "union_square_received_bytes_total",
+        "Request body bytes read from clients",
+        hot_path.bytes_received,
+    );
+    out.counter(
+        "union_square_sent_bytes_total",
+        "Response body bytes streamed to clients",
+        hot_path.bytes_sent,
+    );
+
+    out.header(
+        "union_square_provider_requests_total",
+        "Requests proxied per provider",
+        "counter",
+    );
+    for (slot, requests) in hot_path.provider_requests.iter().enumerate() {
+        out.provider_sample(
+            "union_square_provider_requests_total",
+            ProviderSlot(slot),
+            *requests,
+        );
+    }
+    out.header(
+        "union_square_provider_request_duration_seconds_total",
+        "Time until response headers, summed per provider",
+        "counter",
+    );
+    for (slot, latency_us) in hot_path.provider_latency_sum_us.iter().enumerate() {
+        out.provider_sample(
+            "union_square_provider_request_duration_seconds_total",
+            ProviderSlot(slot),
+            seconds(*latency_us),
+        );
+    }
+
+    out.header(
+        "union_square_request_duration_seconds",
+        "Time until response headers are returned",
+        "histogram",
+    );
+    let mut cumulative = 0;
+    for (bucket, count) in hot_path.latency_buckets[..LATENCY_BUCKETS - 1]
+        .iter()
+        .enumerate()
+    {
+        cumulative += count;
+        let le = (1u64 << bucket) as f64 / 1000.0;
+        out.line(format_args!(
+            "union_square_request_duration_seconds_bucket{{le=\"{le}\"}} {cumulative}"
+        ));
+    }
+    let count: u64 = hot_path.latency_buckets.iter().sum();
+    out.line(format_args!(
+        "union_square_request_duration_seconds_bucket{{le=\"+Inf\"}} {count}"
+    ));
+    out.line(format_args!(
+        "union_square_request_duration_seconds_sum {}",
+        seconds(hot_path.latency_sum_us)
+    ));
+    out.line(format_args!(
+        "union_square_request_duration_seconds_count {count}"
+    ));
+
+    let ring = &snapshot.ring_buffer;
+    out.gauge(
+        "union_square_ring_buffer_shards",
+        "Ring buffer shards, each drained by one audit processor",
+        snapshot.ring_buffer_shards as u64,
+    );
+    out.counter(
+        "union_square_ring_buffer_writes_total",
+        "Entries written to the ring buffer",
+        ring.total_writes,
+    );
+    out.counter(
+        "union_square_ring_buffer_reads_total",
+        "Entries read from the ring buffer",
+        ring.total_reads,
+    );
+    out.gauge(
+        "union_square_ring_buffer_pending",
+        "Entries written to the ring buffer and not yet read",
+        ring.total_writes.saturating_sub(ring.total_reads),
+    );
+    out.counter(
+        "union_square_ring_buffer_dropped_events_total",
+        "Audit events the ring buffer had no room for",
+        *ring.dropped_events.as_ref(),
+    );
+    out.counter(
+        "union_square_ring_buffer_truncated_events_total",
+        "Audit events cut short because they were larger than a shard",
+        ring.truncated_events,
+    );
+
+    if let Some(spill) = &snapshot.spill {
+        out.counter(
+            "union_square_spill_records_total",
+            "Audit events appended to the spill log",
+            spill.spilled_records,
+        );
+        out.counter(
+            "union_square_spill_dropped_records_total",
+            "Audit events the spill log was full for or could not write",
+            spill.dropped_records,
+        );
+        out.gauge(
+            "union_square_spill_pending_records",
+            "Spilled audit events not yet read by their audit processor",
+            spill.pending_records,
+        );
+        out.gauge(
+            "union_square_spill_bytes",
+            "Size of the spill log on disk",
+            spill.bytes_on_disk,
+        );
+    }
+
+    let audit = &snapshot.audit;
+    out.counter(
+        "union_square_audit_events_processed_total",
+        "Audit events read by the audit processors",
+        audit.events_processed,
+    );
+    out.counter(
+        "union_square_audit_deserialization_failures_total",
+        "Audit entries that could not be decoded",
+        audit.deserialization_failures,
+    );
+    out.counter(
+        "union_square_audit_conversion_failures_total",
+        "Audit events that could not be converted to domain commands",
+        audit.conversion_failures,
+    );
+    out.counter(
+        "union_square_audit_persist_failures_total",
+        "Audit commands the event store rejected or was never reached for",
+        audit.persist_failures,
+    );
+    out.counter(
+        "union_square_audit_batches_persisted_total",
+        "Audit batches written to the event store",
+        audit.batches_persisted,
+    );
+    out.counter(
+        "union_square_audit_incomplete_bodies_total",
+        "Request or response bodies whose capture never completed",
+        audit.incomplete_bodies,
+    );
+    out.counter(
+        "union_square_audit_dead_lettered_total",
+        "Failed audit events kept in the dead-letter store",
+        audit.dead_lettered,
+    );
+
+    out.0
+}
+
+fn seconds(micros: u64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+/// Writer for metric families; `String` writes cannot fail
+struct Exposition(String);
+
+impl Exposition {
+    fn header(&mut self, name: &str, help: &str, kind: &str) {
+        self.line(format_args!("# HELP {name} {help}"));
+        self.line(format_args!("# TYPE {name} {kind}"));
+    }
+
+    fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.header(name, help, "counter");
+        self.line(format_args!("{name} {value}"));
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: u64) {
+        self.header(name, help, "gauge");
+        self.line(format_args!("{name} {value}"));
+    }
+
+    fn provider_sample(&mut self, name: &str, slot: ProviderSlot, value: impl std::fmt::Display) {
+        self.line(format_args!(
+            "{name}{{provider=\"{}\"}} {value}",
+            slot.label()
+        ));
+    }
+
+    fn line(&mut self, line: std::fmt::Arguments<'_>) {
+        let _ = self.0.write_fmt(line);
+        self.0.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::types::DroppedEventCount;
+    use http_body_util::BodyExt;
+
+    fn snapshot(hot_path: HotPathSnapshot) -> MetricsSnapshot {
+        MetricsSnapshot {
+            hot_path,
+            ring_buffer: RingBufferStats {
+                total_writes: 10,
+                total_reads: 7,
+                dropped_events: DroppedEventCount::from(2),
+                truncated_events: 1,
+            },
+            ring_buffer_shards: 4,
+            spill: None,
+            audit: AuditCounters {
+                events_processed: 7,
+                dead_lettered: 3,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn sample<'a>(rendered: &'a str, series: &str) -> Option<&'a str> {
+        rendered
+            .lines()
+            .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+    }
+
+    #[test]
+    fn latency_buckets_are_powers_of_two_milliseconds() {
+        assert_eq!(latency_bucket(0), 0);
+        assert_eq!(latency_bucket(1), 1);
+        assert_eq!(latency_bucket(3), 2);
+        assert_eq!(latency_bucket(4), 3);
+        assert_eq!(latency_bucket(16_383), 14);
+        assert_eq!(latency_bucket(16_384), 15);
+        assert_eq!(latency_bucket(u64::MAX), 15);
+    }
+
+    #[test]
+    fn providers_share_slots_by_name() {
+        assert_eq!(
+            ProviderSlot::for_provider(&ProviderId::openai()).label(),
+            "openai"
+        );
+        assert_eq!(
+            ProviderSlot::for_provider(&ProviderId::azure()).label(),
+            "azure"
+        );
+        let custom = ProviderId::try_new("mistral".to_string()).unwrap();
+        assert_eq!(ProviderSlot::for_provider(&custom), ProviderSlot::OTHER);
+        assert_eq!(ProviderSlot::PASSTHROUGH.label(), "passthrough");
+    }
+
+    #[test]
+    fn records_requests_per_provider_and_bucket() {
+        let metrics = HotPathMetrics::new();
+        let openai = ProviderSlot::for_provider(&ProviderId::openai());
+
+        metrics.record(openai, Duration::from_micros(500), true);
+        metrics.record(openai, Duration::from_millis(3), false);
+        metrics.record(ProviderSlot::PASSTHROUGH, Duration::from_secs(60), true);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests_total, 3);
+        assert_eq!(snapshot.requests_failed, 1);
+        assert_eq!(snapshot.provider_requests[openai.0], 2);
+        assert_eq!(snapshot.provider_latency_sum_us[openai.0], 3_500);
+        assert_eq!(snapshot.latency_buckets[0], 1);
+        assert_eq!(snapshot.latency_buckets[2], 1);
+        assert_eq!(snapshot.latency_buckets[LATENCY_BUCKETS - 1], 1);
+    }
+
+    #[test]
+    fn renders_cumulative_latency_histogram() {
+        let metrics = HotPathMetrics::new();
+        metrics.record(ProviderSlot::PASSTHROUGH, Duration::from_micros(500), true);
+        metrics.record(ProviderSlot::PASSTHROUGH, Duration::from_millis(3), true);
+        metrics.record(ProviderSlot::PASSTHROUGH, Duration::from_secs(60), true);
+
+        let rendered = render_prometheus(&snapshot(metrics.snapshot()));
+
+        let bucket = |le: &str| {
+            sample(
+                &rendered,
+                &format!("union_square_request_duration_seconds_bucket{{le=\"{le}\"}}"),
+            )
+        };
+        assert_eq!(bucket("0.001"), Some("1"));
+        assert_eq!(bucket("0.002"), Some("1"));
+        assert_eq!(bucket("0.004"), Some("2"));
+        assert_eq!(bucket("16.384"), Some("2"));
+        assert_eq!(bucket("+Inf"), Some("3"));
+        assert_eq!(
+            sample(&rendered, "union_square_request_duration_seconds_sum"),
+            Some("60.0035")
+        );
+        assert_eq!(
+            sample(&rendered, "union_square_request_duration_seconds_count"),
+            Some("3")
+        );
+    }
+
+    #[test]
+    fn renders_ring_buffer_and_audit_counters() {
+        let rendered = render_prometheus(&snapshot(HotPathSnapshot::default()));
+
+        for (series, value) in [
+            ("union_square_ring_buffer_shards", "4"),
+            ("union_square_ring_buffer_pending", "3"),
+            ("union_square_ring_buffer_dropped_events_total", "2"),
+            ("union_square_ring_buffer_truncated_events_total", "1"),
+            ("union_square_audit_events_processed_total", "7"),
+            ("union_square_audit_dead_lettered_total", "3"),
+            (
+                "union_square_provider_requests_total{provider=\"bedrock\"}",
+                "0",
+            ),
+        ] {
+            assert_eq!(sample(&rendered, series), Some(value), "{series}");
+        }
+        assert!(rendered.contains("# TYPE union_square_ring_buffer_pending gauge\n"));
+        assert!(rendered.contains("# TYPE union_square_request_duration_seconds histogram\n"));
+        assert!(!rendered.contains("union_square_spill_"));
+    }
+
+    #[test]
+    fn renders_spill_stats_when_configured() {
+        let mut snapshot = snapshot(HotPathSnapshot::default());
+        snapshot.spill = Some(SpillStats {
+            spilled_records: 5,
+            dropped_records: 0,
+            pending_records: 2,
+            bytes_on_disk: 1024,
+        });
+
+        let rendered = render_prometheus(&snapshot);
+
+        assert_eq!(
+            sample(&rendered, "union_square_spill_records_total"),
+            Some("5")
+        );
+        assert_eq!(sample(&rendered, "union_square_spill_bytes"), Some("1024"));
+    }
+
+    #[test]
+    fn audit_totals_sum_the_published_shards() {
+        let metrics = AuditMetrics::new(2);
+        let counters = |events_processed| AuditCounters {
+            events_processed,
+            ..Default::default()
+        };
+
+        metrics.publish(0, counters(3));
+        metrics.publish(1, counters(4));
+        metrics.publish(0, counters(5));
+
+        assert_eq!(metrics.totals().events_processed, 9);
+    }
+
+    #[tokio::test]
+    async fn metered_bodies_count_streamed_bytes() {
+        let metrics = Arc::new(HotPathMetrics::new());
+        let body = MeteredBody::new(
+            axum::body::Body::from("hello"),
+            Arc::clone(&metrics),
+            ByteDirection::Sent,
+        );
+
+        body.collect().await.unwrap();
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.bytes_sent, 5);
+        assert_eq!(snapshot.bytes_received, 0);
+    }
+}
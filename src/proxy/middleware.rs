@@ -1,5 +1,7 @@
 //! Middleware implementations for the proxy service
 
+use crate::domain::session::SessionScope;
+use crate::providers::bedrock::provider::PathPrefix;
 use crate::proxy::headers::{self, BEARER_PREFIX, X_REQUEST_ID};
 use crate::proxy::http_types::HttpPath;
 use crate::proxy::types::*;
@@ -9,7 +11,7 @@ use axum::{
     middleware::Next,
     response::{IntoResponse, Response},
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::{error, info, warn};
@@ -20,8 +22,16 @@ use uuid::Uuid;
 pub struct AuthConfig {
     /// Valid API keys
     pub api_keys: HashSet<ApiKey>,
+    /// API keys for the session, search, usage, live view and dead letter
+    /// APIs; keys in `api_keys` are refused there
+    pub admin_api_keys: HashSet<ApiKey>,
+    /// Applications the requests of some keys are attributed to
+    pub application_bindings: HashMap<ApiKey, ApplicationBinding>,
     /// Paths that bypass authentication
     pub bypass_paths: HashSet<BypassPath>,
+    /// Prefixes whose requests carry the provider's own key in `X-API-Key`;
+    /// only a Bearer token authenticates them with the proxy
+    pub upstream_api_key_prefixes: Vec<PathPrefix>,
 }
 
 impl Default for AuthConfig {
@@ -40,11 +50,27 @@ impl Default for AuthConfig {
 
         Self {
             api_keys: HashSet::new(),
+            admin_api_keys: HashSet::new(),
+            application_bindings: HashMap::new(),
             bypass_paths,
+            upstream_api_key_prefixes: vec![PathPrefix::anthropic()],
         }
     }
 }
 
+/// The kind of key a request was authenticated with
+///
+/// Added to the request's extensions by [`auth_middleware`], together with
+/// the key's [`ApplicationBinding`] when it has one and the [`SessionScope`]
+/// its client session IDs are resolved in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthScope {
+    /// A key from [`AuthConfig::api_keys`]
+    Proxy,
+    /// A key from [`AuthConfig::admin_api_keys`]
+    Admin,
+}
+
 /// Request ID middleware - ensures every request has a unique ID for tracing
 pub async fn request_id_middleware(
     mut request: Request,
@@ -96,7 +122,7 @@ pub async fn request_id_middleware(
 /// Authentication middleware - validates API keys
 pub async fn auth_middleware(
     State(auth_config): State<Arc<AuthConfig>>,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, ProxyError> {
     // Check if path should bypass auth
@@ -108,12 +134,23 @@ pub async fn auth_middleware(
     }
 
     // Extract API key from either X-API-Key header or Authorization header
-    // Priority: X-API-Key > Authorization Bearer token
-    let api_key_str = if let Some(api_key_header) = request
-        .headers()
-        .get(headers::X_API_KEY)
-        .and_then(|h| h.to_str().ok())
+    // Priority: X-API-Key > Authorization Bearer token. Anthropic requests
+    // carry the provider's own key in X-API-Key, so only Bearer counts there.
+    let path = request.uri().path();
+    let x_api_key = if auth_config
+        .upstream_api_key_prefixes
+        .iter()
+        .any(|prefix| prefix.matches(path))
     {
+        None
+    } else {
+        request
+            .headers()
+            .get(headers::X_API_KEY)
+            .and_then(|h| h.to_str().ok())
+    };
+    let from_x_api_key = x_api_key.is_some();
+    let api_key_str = if let Some(api_key_header) = x_api_key {
         api_key_header.trim()
     } else if let Some(auth_header) = request
         .headers()
@@ -149,9 +186,32 @@ pub async fn auth_middleware(
     };
 
     // Validate API key
-    if let Ok(api_key) = ApiKey::try_new(api_key_str.to_string()) {
-        if auth_config.api_keys.contains(&api_key) {
+    let api_key_str = api_key_str.to_string();
+    if let Ok(api_key) = ApiKey::try_new(api_key_str.clone()) {
+        let scope = if auth_config.admin_api_keys.contains(&api_key) {
+            Some(AuthScope::Admin)
+        } else if auth_config.api_keys.contains(&api_key) {
+            Some(AuthScope::Proxy)
+        } else {
+            None
+        };
+        if let Some(scope) = scope {
+            // The proxy's key is not meant for upstreams or the audit log
+            if from_x_api_key {
+                request.headers_mut().remove(headers::X_API_KEY);
+            } else {
+                request.headers_mut().remove(header::AUTHORIZATION);
+            }
             // Process authenticated request
+            request.extensions_mut().insert(scope);
+            let session_scope = match auth_config.application_bindings.get(&api_key) {
+                Some(binding) => {
+                    request.extensions_mut().insert(binding.clone());
+                    SessionScope::application(&binding.application_id, binding.environment.as_ref())
+                }
+                None => SessionScope::api_key(api_key.as_ref()),
+            };
+            request.extensions_mut().insert(session_scope);
             return Ok(next.run(request).await);
         }
     }
@@ -169,6 +229,27 @@ pub async fn auth_middleware(
     Ok(error.into_response_with_status(StatusCode::UNAUTHORIZED))
 }
 
+/// Admin API middleware - admits only requests authenticated with an admin key
+///
+/// Runs inside [`auth_middleware`], so it only sees authenticated requests.
+pub async fn admin_auth_middleware(request: Request, next: Next) -> Result<Response, ProxyError> {
+    if request.extensions().get::<AuthScope>() == Some(&AuthScope::Admin) {
+        return Ok(next.run(request).await);
+    }
+
+    use crate::proxy::error_response::{extract_request_id, ErrorResponse};
+
+    warn!("Admin API requested without an admin API key");
+    let request_id = extract_request_id(request.headers());
+    let error = ErrorResponse::new("FORBIDDEN", "Admin API key required");
+    let error = if let Some(id) = request_id {
+        error.with_request_id(id)
+    } else {
+        error
+    };
+    Ok(error.into_response_with_status(StatusCode::FORBIDDEN))
+}
+
 /// Logging middleware - logs request/response details with timing
 pub async fn logging_middleware(request: Request, next: Next) -> Result<Response, ProxyError> {
     let start = Instant::now();
@@ -209,12 +290,15 @@ pub async fn logging_middleware(request: Request, next: Next) -> Result<Response
 
 /// Error handling wrapper that converts ProxyError to HTTP responses
 pub async fn error_handling_middleware(request: Request, next: Next) -> Response {
-    use crate::proxy::error_response::{extract_request_id, standard_error_response};
+    use crate::proxy::error_response::{
+        extract_request_id, standard_error_response, ClientFacingError,
+    };
 
     let request_id = extract_request_id(request.headers());
 
     match next.run(request).await.into_response() {
         response if response.status().is_success() => response,
+        response if response.extensions().get::<ClientFacingError>().is_some() => response,
         error_response => {
             let status = error_response.status();
 
@@ -365,4 +449,159 @@ mod tests {
         let response = service.oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn test_auth_middleware_ignores_x_api_key_on_anthropic_paths() {
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .api_keys
+            .insert(ApiKey::try_new("valid-key-123".to_string()).unwrap());
+        let auth_config = Arc::new(auth_config);
+
+        let handler = tower::service_fn(|_req: Request| async move {
+            Ok::<_, std::convert::Infallible>(
+                Response::builder()
+                    .status(StatusCode::OK)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        });
+
+        let service = tower::ServiceBuilder::new()
+            .layer(from_fn_with_state(auth_config, auth_middleware))
+            .service(handler);
+
+        // The Anthropic key in X-API-Key must not shadow the proxy key
+        let request = Request::builder()
+            .method("POST")
+            .uri("/anthropic/v1/messages")
+            .header(headers::X_API_KEY, "sk-ant-upstream")
+            .header(header::AUTHORIZATION, "Bearer valid-key-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // ...and is not accepted as a proxy key on its own
+        let request = Request::builder()
+            .method("POST")
+            .uri("/anthropic/v1/messages")
+            .header(headers::X_API_KEY, "valid-key-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_auth_middleware_scopes_sessions_to_the_caller() {
+        use crate::domain::session::ApplicationId;
+
+        let application_id = ApplicationId::try_new("support-bot".to_string()).unwrap();
+        let mut auth_config = AuthConfig::default();
+        for key in ["bound-key", "other-bound-key", "unbound-key"] {
+            auth_config
+                .api_keys
+                .insert(ApiKey::try_new(key.to_string()).unwrap());
+        }
+        for key in ["bound-key", "other-bound-key"] {
+            auth_config.application_bindings.insert(
+                ApiKey::try_new(key.to_string()).unwrap(),
+                ApplicationBinding {
+                    application_id: application_id.clone(),
+                    environment: None,
+                },
+            );
+        }
+
+        let (scopes_tx, mut scopes) = tokio::sync::mpsc::unbounded_channel();
+        let handler = tower::service_fn(move |req: Request| {
+            let scopes_tx = scopes_tx.clone();
+            async move {
+                scopes_tx
+                    .send(req.extensions().get::<SessionScope>().copied())
+                    .unwrap();
+                Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
+            }
+        });
+        let service = tower::ServiceBuilder::new()
+            .layer(from_fn_with_state(Arc::new(auth_config), auth_middleware))
+            .service(handler);
+
+        for key in ["bound-key", "other-bound-key", "unbound-key"] {
+            let request = Request::builder()
+                .uri("/v1/chat/completions")
+                .header(header::AUTHORIZATION, format!("Bearer {key}"))
+                .body(Body::empty())
+                .unwrap();
+            service.clone().oneshot(request).await.unwrap();
+        }
+
+        // Keys bound to one application share its sessions
+        let application = Some(SessionScope::application(&application_id, None));
+        assert_eq!(scopes.recv().await.unwrap(), application);
+        assert_eq!(scopes.recv().await.unwrap(), application);
+        assert_eq!(
+            scopes.recv().await.unwrap(),
+            Some(SessionScope::api_key("unbound-key"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auth_middleware_strips_the_proxy_key() {
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .api_keys
+            .insert(ApiKey::try_new("valid-key-123".to_string()).unwrap());
+
+        // Reports the credentials that made it past the middleware
+        let handler = tower::service_fn(|req: Request| async move {
+            let forwarded = |name: &str| {
+                req.headers()
+                    .get(name)
+                    .and_then(|h| h.to_str().ok())
+                    .unwrap_or("none")
+                    .to_string()
+            };
+            let body = format!(
+                "{} {}",
+                forwarded(headers::X_API_KEY),
+                forwarded(header::AUTHORIZATION.as_str())
+            );
+            Ok::<_, std::convert::Infallible>(Response::new(Body::from(body)))
+        });
+
+        let service = tower::ServiceBuilder::new()
+            .layer(from_fn_with_state(Arc::new(auth_config), auth_middleware))
+            .service(handler);
+
+        let forwarded = |request: Request| {
+            let service = service.clone();
+            async move {
+                let response = service.oneshot(request).await.unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            }
+        };
+
+        // The provider's Bearer token stays when the proxy key is in X-API-Key
+        let request = Request::builder()
+            .uri("/openai/v1/chat/completions")
+            .header(headers::X_API_KEY, "valid-key-123")
+            .header(header::AUTHORIZATION, "Bearer sk-upstream")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(forwarded(request).await, "none Bearer sk-upstream");
+
+        // ...and the provider's X-API-Key stays when the proxy key is Bearer
+        let request = Request::builder()
+            .uri("/anthropic/v1/messages")
+            .header(headers::X_API_KEY, "sk-ant-upstream")
+            .header(header::AUTHORIZATION, "Bearer valid-key-123")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(forwarded(request).await, "sk-ant-upstream none");
+    }
 }
@@ -195,7 +195,7 @@ mod tests {
     use crate::proxy::headers::{paths, X_REQUEST_ID};
     use crate::proxy::types::ApiKey;
     use axum::{body::Body, http::StatusCode, response::IntoResponse};
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
     use tower::ServiceExt;
 
     #[tokio::test]
@@ -311,7 +311,10 @@ mod tests {
         // Test that bypass paths are not added when disabled
         let mut auth_config = AuthConfig {
             api_keys: HashSet::new(),
+            admin_api_keys: HashSet::new(),
+            application_bindings: HashMap::new(),
             bypass_paths: HashSet::new(), // Start with empty bypass paths
+            upstream_api_key_prefixes: Vec::new(),
         };
         auth_config
             .api_keys
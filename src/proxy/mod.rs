@@ -39,7 +39,10 @@
 //! - `http`: HTTP utilities and type-safe wrappers
 //! - `middleware`: Tower middleware stack components
 //! - `paths`: Hot path and audit path implementations
-//! - `storage`: Ring buffer for event passing
+//! - `observability`: Counters served at `/metrics`, W3C trace propagation
+//!   and OTLP span export
+//! - `storage`: Ring buffer for event passing, its on-disk overflow log and
+//!   the encoding of the audit events they carry
 //!
 //! ## Example Usage
 //!
@@ -73,6 +76,10 @@ pub mod http {
     pub use super::error_response::{ErrorResponse, ErrorResponseExt};
     pub use super::headers::*;
     pub use super::http_types::*;
+    pub use super::session_headers::{
+        resolve_session_id, take_session_context, MAX_METADATA_ENTRIES,
+    };
+    pub use super::upstream_client::{build_upstream_client, UpstreamClient};
     pub use super::url_resolver::{UrlResolver, UrlResolverConfig};
 }
 
@@ -82,33 +89,66 @@ pub use middleware_stack::{ProxyMiddlewareConfig, ProxyMiddlewareStack};
 
 // Path implementations
 pub mod paths {
-    pub use super::audit_path::AuditPathProcessor;
+    pub use super::audit_path::{
+        AuditBatching, AuditPathConfig, AuditPathProcessor, AuditProcessorHandle,
+    };
     pub use super::audit_recorder::{
         extract_headers_vec, parse_http_method, parse_http_status, parse_request_uri,
-        AuditRecorder, ChunkCapture, RingBufferAuditRecorder, CAPTURE_CHUNK_SIZE,
+        AuditRecorder, CaptureSender, ChunkCapture, RingBufferAuditRecorder, TeeBody,
+        CAPTURE_CHUNK_SIZE,
     };
+    pub use super::audit_steps::AuditCounters;
     pub use super::hot_path::StreamingHotPathService;
 }
 
+// Operational metrics
+pub mod observability {
+    pub use super::metrics::{
+        render_prometheus, AuditMetrics, ByteDirection, HotPathMetrics, HotPathSnapshot,
+        MeteredBody, MetricsSnapshot, ProviderSlot, LATENCY_BUCKETS, MAX_PROVIDERS,
+        PROMETHEUS_CONTENT_TYPE,
+    };
+    pub use super::otlp::{
+        attributes, encode_export_request, gen_ai_system, span_names, ActiveSpan, AttributeValue,
+        OtlpExporter, SpanKind, SpanRecord, SpanStatus, Tracer, MAX_EXPORT_BATCH, MAX_QUEUED_SPANS,
+    };
+    pub use super::trace_context::{SpanId, TraceContext, TraceId, TRACEPARENT, TRACESTATE};
+}
+
 // Storage and persistence
 pub mod storage {
+    pub use super::audit_codec::{decode_audit_event, AuditCodecError, AuditEventFormat};
     pub use super::ring_buffer::{RingBuffer, RingBufferStats};
+    pub use super::spill_log::{SpillError, SpillLog, SpillPosition, SpillStats};
 }
 
 // Internal modules (not part of public API)
+mod audit_codec;
 mod audit_path;
 mod audit_recorder;
 mod audit_steps;
+mod audit_tracing;
+mod audit_usage;
+mod dead_letter_api;
 mod error_response;
 mod headers;
 mod hot_path;
 mod hot_path_planner;
 mod http_types;
+mod metrics;
 mod middleware;
 mod middleware_stack;
+mod otlp;
 mod provider_router;
 mod ring_buffer;
+mod search_api;
+mod session_api;
+mod session_headers;
+mod spill_log;
+mod trace_context;
+mod upstream_client;
 mod url_resolver;
+mod usage_api;
 
 // Test modules
 #[cfg(test)]
@@ -0,0 +1,757 @@
+//! Span recording and export over OTLP
+//!
+//! The proxy records a span for each request it serves, one for the upstream
+//! call it makes and one per request for each audit batch that persists the
+//! request's events. Spans are handed to a background exporter through a
+//! bounded queue, so recording never waits on the collector; when the queue
+//! is full, spans are dropped and counted.
+//!
+//! The exporter batches spans and posts them to the collector as OTLP/HTTP
+//! JSON (`{endpoint}/v1/traces`) through the same hyper client used for
+//! upstream providers.
+
+use crate::proxy::trace_context::{SpanId, TraceContext};
+use crate::proxy::types::{OtlpConfig, ProxyError, ProxyResult};
+use crate::proxy::upstream_client::UpstreamClient;
+use axum::body::Body;
+use bytes::Bytes;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use http_body_util::BodyExt;
+use hyper::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use hyper::{Request, Response, Uri};
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::task::{JoinError, JoinHandle};
+use tracing::{debug, warn};
+
+/// Finished spans waiting for export before new ones are dropped
+pub const MAX_QUEUED_SPANS: usize = 4096;
+
+/// Most spans posted to the collector in one request
+pub const MAX_EXPORT_BATCH: usize = 512;
+
+/// Path of the OTLP/HTTP trace endpoint below the collector's base URL
+const TRACES_PATH: &str = "/v1/traces";
+
+/// Longest an export may take before it is abandoned
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Names of the spans the proxy records
+pub mod span_names {
+    /// A request served by the proxy, from arrival to the end of the response body
+    pub const PROXY: &str = "union_square.proxy";
+    /// The call to the upstream provider, until its response headers arrive
+    pub const UPSTREAM: &str = "union_square.upstream";
+    /// An audit batch persisting a request's events
+    pub const AUDIT_PERSIST: &str = "union_square.audit.persist";
+}
+
+/// Span attribute keys, following the OpenTelemetry semantic conventions
+pub mod attributes {
+    pub const HTTP_REQUEST_METHOD: &str = "http.request.method";
+    pub const HTTP_RESPONSE_STATUS_CODE: &str = "http.response.status_code";
+    pub const URL_PATH: &str = "url.path";
+    pub const SERVER_ADDRESS: &str = "server.address";
+    pub const GEN_AI_SYSTEM: &str = "gen_ai.system";
+    pub const GEN_AI_REQUEST_MODEL: &str = "gen_ai.request.model";
+    pub const GEN_AI_RESPONSE_MODEL: &str = "gen_ai.response.model";
+    pub const GEN_AI_USAGE_INPUT_TOKENS: &str = "gen_ai.usage.input_tokens";
+    pub const GEN_AI_USAGE_OUTPUT_TOKENS: &str = "gen_ai.usage.output_tokens";
+    /// Correlates a span with the audit events recorded for its request
+    pub const REQUEST_ID: &str = "union_square.request_id";
+    /// Audit events of the request persisted by one batch
+    pub const AUDIT_EVENTS: &str = "union_square.audit.events";
+}
+
+/// The `gen_ai.system` value for a provider or parsed LLM provider name
+///
+/// Names without a well-known value are reported unchanged.
+pub fn gen_ai_system(provider: &str) -> &str {
+    match provider {
+        "bedrock" => "aws.bedrock",
+        "vertex" | "google" => "vertex_ai",
+        "azure" => "az.ai.openai",
+        other => other,
+    }
+}
+
+/// Start the client span for a call to `host` about to be sent with `headers`
+///
+/// The forwarded `traceparent` is rewritten to name the new span.
+pub(crate) fn start_upstream_span(
+    tracer: Option<&Tracer>,
+    host: Option<&str>,
+    headers: &mut HeaderMap,
+) -> Option<ActiveSpan> {
+    let mut span = tracer?.start_propagated(span_names::UPSTREAM, SpanKind::Client, headers);
+    if let Some(host) = host {
+        span.set_attribute(attributes::SERVER_ADDRESS, host);
+    }
+    Some(span)
+}
+
+/// Record the outcome of an upstream call on its span and end the span
+pub(crate) fn end_upstream_span<B>(
+    span: Option<ActiveSpan>,
+    result: &Result<Response<B>, ProxyError>,
+) {
+    let Some(mut span) = span else {
+        return;
+    };
+    match result {
+        Ok(response) => {
+            let status = response.status();
+            span.set_attribute(attributes::HTTP_RESPONSE_STATUS_CODE, status.as_u16());
+            if status.is_server_error() {
+                span.set_status(SpanStatus::Error(status.to_string()));
+            }
+        }
+        Err(e) => span.set_status(SpanStatus::Error(e.to_string())),
+    }
+}
+
+/// Relationship of a span to its neighbours in the trace
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpanKind {
+    Internal,
+    Server,
+    Client,
+}
+
+impl SpanKind {
+    fn otlp_code(self) -> u8 {
+        match self {
+            SpanKind::Internal => 1,
+            SpanKind::Server => 2,
+            SpanKind::Client => 3,
+        }
+    }
+}
+
+/// Outcome of the operation a span covers
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SpanStatus {
+    #[default]
+    Unset,
+    Ok,
+    Error(String),
+}
+
+/// Value of a span attribute
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        AttributeValue::String(value.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::String(value)
+    }
+}
+
+impl From<i64> for AttributeValue {
+    fn from(value: i64) -> Self {
+        AttributeValue::Int(value)
+    }
+}
+
+impl From<u32> for AttributeValue {
+    fn from(value: u32) -> Self {
+        AttributeValue::Int(value.into())
+    }
+}
+
+impl From<u16> for AttributeValue {
+    fn from(value: u16) -> Self {
+        AttributeValue::Int(value.into())
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(value: bool) -> Self {
+        AttributeValue::Bool(value)
+    }
+}
+
+/// A finished span
+#[derive(Clone, Debug)]
+pub struct SpanRecord {
+    pub name: &'static str,
+    pub kind: SpanKind,
+    /// The span's own trace and span id
+    pub context: TraceContext,
+    pub parent_span_id: Option<SpanId>,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(&'static str, AttributeValue)>,
+    pub status: SpanStatus,
+}
+
+impl SpanRecord {
+    /// The value of the attribute `key`, if set
+    pub fn attribute(&self, key: &str) -> Option<&AttributeValue> {
+        self.attributes
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Starts spans and queues them for export when they end
+///
+/// Cheap to clone; every clone feeds the same exporter.
+#[derive(Clone, Debug)]
+pub struct Tracer {
+    spans: mpsc::Sender<SpanRecord>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Tracer {
+    /// A tracer queueing at most `capacity` finished spans on the returned receiver
+    pub fn channel(capacity: usize) -> (Self, mpsc::Receiver<SpanRecord>) {
+        let (spans, receiver) = mpsc::channel(capacity);
+        (
+            Self {
+                spans,
+                dropped: Arc::new(AtomicU64::new(0)),
+            },
+            receiver,
+        )
+    }
+
+    /// Start a span as a child of `parent`, or as the root of a new trace
+    pub fn start(
+        &self,
+        name: &'static str,
+        kind: SpanKind,
+        parent: Option<&TraceContext>,
+    ) -> ActiveSpan {
+        let context = parent.map_or_else(TraceContext::root, TraceContext::child);
+        ActiveSpan {
+            tracer: self.clone(),
+            record: Some(SpanRecord {
+                name,
+                kind,
+                context,
+                parent_span_id: parent.map(|parent| parent.span_id),
+                start: SystemTime::now(),
+                end: SystemTime::now(),
+                attributes: Vec::new(),
+                status: SpanStatus::Unset,
+            }),
+        }
+    }
+
+    /// Start a span continuing the trace named by `headers`
+    ///
+    /// The `traceparent` in `headers` is replaced with the new span's, so
+    /// whoever receives them next sees the new span as its parent.
+    pub fn start_propagated(
+        &self,
+        name: &'static str,
+        kind: SpanKind,
+        headers: &mut HeaderMap,
+    ) -> ActiveSpan {
+        let parent = TraceContext::from_headers(headers);
+        let span = self.start(name, kind, parent.as_ref());
+        span.context().inject(headers);
+        span
+    }
+
+    /// Spans dropped because the export queue was full
+    pub fn dropped_spans(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn finish(&self, record: SpanRecord) {
+        if self.spans.try_send(record).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A span that has started and not yet ended
+///
+/// The span ends when it is dropped; sampled spans are then queued for export.
+#[derive(Debug)]
+pub struct ActiveSpan {
+    tracer: Tracer,
+    record: Option<SpanRecord>,
+}
+
+impl ActiveSpan {
+    /// The span's trace context, to pass on to its children
+    pub fn context(&self) -> TraceContext {
+        self.record().context
+    }
+
+    pub fn set_attribute(&mut self, key: &'static str, value: impl Into<AttributeValue>) {
+        let value = value.into();
+        let attributes = &mut self.record_mut().attributes;
+        match attributes.iter_mut().find(|(name, _)| *name == key) {
+            Some((_, existing)) => *existing = value,
+            None => attributes.push((key, value)),
+        }
+    }
+
+    pub fn set_status(&mut self, status: SpanStatus) {
+        self.record_mut().status = status;
+    }
+
+    /// End the span now
+    pub fn end(self) {}
+
+    fn record(&self) -> &SpanRecord {
+        self.record
+            .as_ref()
+            .expect("span record is present until drop")
+    }
+
+    fn record_mut(&mut self) -> &mut SpanRecord {
+        self.record
+            .as_mut()
+            .expect("span record is present until drop")
+    }
+}
+
+impl Drop for ActiveSpan {
+    fn drop(&mut self) {
+        if let Some(mut record) = self.record.take() {
+            if record.context.sampled {
+                record.end = SystemTime::now();
+                self.tracer.finish(record);
+            }
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Body wrapper ending a span once the body has been streamed
+    ///
+    /// The span also ends if the body is dropped before it completes, e.g.
+    /// when the client disconnects.
+    pub struct TracedBody<B> {
+        #[pin]
+        inner: B,
+        span: Option<ActiveSpan>,
+    }
+}
+
+impl<B> TracedBody<B> {
+    pub fn new(inner: B, span: Option<ActiveSpan>) -> Self {
+        Self { inner, span }
+    }
+}
+
+impl<B: HttpBody<Data = Bytes>> HttpBody for TracedBody<B>
+where
+    B::Error: std::fmt::Display,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let polled = this.inner.poll_frame(cx);
+
+        match &polled {
+            Poll::Ready(None) => {
+                this.span.take();
+            }
+            Poll::Ready(Some(Err(e))) => {
+                if let Some(mut span) = this.span.take() {
+                    span.set_status(SpanStatus::Error(e.to_string()));
+                }
+            }
+            _ => {}
+        }
+
+        polled
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Background task posting finished spans to an OTLP collector
+pub struct OtlpExporter {
+    shutdown_tx: mpsc::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl OtlpExporter {
+    /// Start exporting to the collector in `config` through `client`
+    ///
+    /// Fails if the endpoint or one of the configured headers is invalid.
+    pub fn spawn(config: &OtlpConfig, client: UpstreamClient) -> ProxyResult<(Tracer, Self)> {
+        let endpoint = traces_uri(config)?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            let invalid = || ProxyError::InvalidHeader { name: name.clone() };
+            headers.insert(
+                HeaderName::try_from(name.as_str()).map_err(|_| invalid())?,
+                HeaderValue::try_from(value.as_str()).map_err(|_| invalid())?,
+            );
+        }
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let (tracer, spans) = Tracer::channel(MAX_QUEUED_SPANS);
+        let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
+        let task = ExportTask {
+            client,
+            endpoint,
+            headers,
+            service_name: config.service_name.to_string(),
+            interval: Duration::from_millis(*config.export_interval_ms.as_ref()),
+            dropped: Arc::clone(&tracer.dropped),
+            reported_dropped: 0,
+        };
+        let task = tokio::spawn(task.run(spans, shutdown_rx));
+        Ok((tracer, Self { shutdown_tx, task }))
+    }
+
+    /// Export the spans queued so far and stop
+    ///
+    /// Spans ended after this call are dropped.
+    pub async fn shutdown(self) -> Result<(), JoinError> {
+        let _ = self.shutdown_tx.send(()).await;
+        self.task.await
+    }
+}
+
+fn traces_uri(config: &OtlpConfig) -> ProxyResult<Uri> {
+    let base = config.endpoint.as_ref().trim_end_matches('/');
+    format!("{base}{TRACES_PATH}")
+        .parse()
+        .map_err(|e| ProxyError::InvalidTargetUrl(format!("{}: {e}", config.endpoint)))
+}
+
+struct ExportTask {
+    client: UpstreamClient,
+    endpoint: Uri,
+    headers: HeaderMap,
+    service_name: String,
+    interval: Duration,
+    dropped: Arc<AtomicU64>,
+    reported_dropped: u64,
+}
+
+impl ExportTask {
+    async fn run(
+        mut self,
+        mut spans: mpsc::Receiver<SpanRecord>,
+        mut shutdown_rx: mpsc::Receiver<()>,
+    ) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut batch = Vec::new();
+
+        loop {
+            tokio::select! {
+                span = spans.recv() => match span {
+                    Some(span) => {
+                        batch.push(span);
+                        if batch.len() >= MAX_EXPORT_BATCH {
+                            self.export(&mut batch).await;
+                        }
+                    }
+                    None => break,
+                },
+                _ = interval.tick() => self.export(&mut batch).await,
+                _ = shutdown_rx.recv() => break,
+            }
+        }
+
+        while let Ok(span) = spans.try_recv() {
+            batch.push(span);
+            if batch.len() >= MAX_EXPORT_BATCH {
+                self.export(&mut batch).await;
+            }
+        }
+        self.export(&mut batch).await;
+    }
+
+    /// Post `batch` to the collector, leaving it empty
+    ///
+    /// Failed exports are logged and their spans discarded, so an
+    /// unreachable collector costs no memory.
+    async fn export(&mut self, batch: &mut Vec<SpanRecord>) {
+        let dropped = self.dropped.load(Ordering::Relaxed);
+        if dropped > self.reported_dropped {
+            warn!(
+                "Dropped {} spans because the OTLP export queue was full",
+                dropped - self.reported_dropped
+            );
+            self.reported_dropped = dropped;
+        }
+        if batch.is_empty() {
+            return;
+        }
+
+        let body = encode_export_request(&self.service_name, batch).to_string();
+        let count = batch.len();
+        batch.clear();
+
+        let mut request = Request::post(self.endpoint.clone())
+            .body(Body::from(body))
+            .expect("export request is valid");
+        *request.headers_mut() = self.headers.clone();
+
+        let exported = tokio::time::timeout(EXPORT_TIMEOUT, async {
+            let response = self
+                .client
+                .request(request)
+                .await
+                .map_err(|e| e.to_string())?;
+            let status = response.status();
+            // Read the body so the connection can be reused
+            let _ = response.into_body().collect().await;
+            if status.is_success() {
+                Ok(())
+            } else {
+                Err(format!("collector answered {status}"))
+            }
+        })
+        .await
+        .unwrap_or_else(|_| Err(format!("timed out after {EXPORT_TIMEOUT:?}")));
+
+        match exported {
+            Ok(()) => debug!("Exported {count} spans"),
+            Err(e) => warn!("Failed to export {count} spans to {}: {e}", self.endpoint),
+        }
+    }
+}
+
+/// The OTLP/HTTP JSON `ExportTraceServiceRequest` for `spans`
+pub fn encode_export_request(service_name: &str, spans: &[SpanRecord]) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [encode_attribute("service.name", &service_name.into())],
+            },
+            "scopeSpans": [{
+                "scope": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "spans": spans.iter().map(encode_span).collect::<Vec<_>>(),
+            }],
+        }],
+    })
+}
+
+fn encode_span(span: &SpanRecord) -> Value {
+    let mut encoded = json!({
+        "traceId": span.context.trace_id.to_string(),
+        "spanId": span.context.span_id.to_string(),
+        "name": span.name,
+        "kind": span.kind.otlp_code(),
+        "startTimeUnixNano": unix_nanos(span.start),
+        "endTimeUnixNano": unix_nanos(span.end),
+        "attributes": span
+            .attributes
+            .iter()
+            .map(|(key, value)| encode_attribute(key, value))
+            .collect::<Vec<_>>(),
+        "status": match &span.status {
+            SpanStatus::Unset => json!({"code": 0}),
+            SpanStatus::Ok => json!({"code": 1}),
+            SpanStatus::Error(message) => json!({"code": 2, "message": message}),
+        },
+    });
+    if let Some(parent) = span.parent_span_id {
+        encoded["parentSpanId"] = parent.to_string().into();
+    }
+    encoded
+}
+
+fn encode_attribute(key: &str, value: &AttributeValue) -> Value {
+    let value = match value {
+        AttributeValue::String(value) => json!({"stringValue": value}),
+        // Protobuf JSON encodes 64-bit integers as strings
+        AttributeValue::Int(value) => json!({"intValue": value.to_string()}),
+        AttributeValue::Bool(value) => json!({"boolValue": value}),
+    };
+    json!({"key": key, "value": value})
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::test_utils::collector::spawn_collector;
+    use crate::proxy::types::{TargetUrl, UpstreamTlsConfig};
+    use crate::proxy::upstream_client::build_upstream_client;
+
+    const PARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_child_span_continues_the_parent_trace() {
+        let (tracer, mut spans) = Tracer::channel(8);
+        let parent = TraceContext::parse(PARENT).unwrap();
+
+        let mut span = tracer.start(span_names::PROXY, SpanKind::Server, Some(&parent));
+        span.set_attribute(attributes::HTTP_RESPONSE_STATUS_CODE, 200u16);
+        span.end();
+
+        let record = spans.try_recv().unwrap();
+        assert_eq!(record.context.trace_id, parent.trace_id);
+        assert_eq!(record.parent_span_id, Some(parent.span_id));
+        assert_ne!(record.context.span_id, parent.span_id);
+        assert_eq!(
+            record.attribute(attributes::HTTP_RESPONSE_STATUS_CODE),
+            Some(&AttributeValue::Int(200))
+        );
+        assert!(record.end >= record.start);
+    }
+
+    #[test]
+    fn test_unsampled_spans_are_not_exported() {
+        let (tracer, mut spans) = Tracer::channel(8);
+        let parent =
+            TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00").unwrap();
+
+        tracer.start(span_names::PROXY, SpanKind::Server, Some(&parent));
+
+        assert!(spans.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_full_queue_drops_and_counts_spans() {
+        let (tracer, _spans) = Tracer::channel(1);
+        tracer.start(span_names::PROXY, SpanKind::Server, None);
+        tracer.start(span_names::PROXY, SpanKind::Server, None);
+        assert_eq!(tracer.dropped_spans(), 1);
+    }
+
+    #[test]
+    fn test_start_propagated_rewrites_the_traceparent() {
+        let (tracer, _spans) = Tracer::channel(8);
+        let mut headers = HeaderMap::new();
+        headers.insert("traceparent", HeaderValue::from_static(PARENT));
+
+        let span = tracer.start_propagated(span_names::UPSTREAM, SpanKind::Client, &mut headers);
+
+        let forwarded = TraceContext::from_headers(&headers).unwrap();
+        assert_eq!(forwarded, span.context());
+        assert_eq!(
+            forwarded.trace_id.to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    }
+
+    #[test]
+    fn test_encodes_spans_as_otlp_json() {
+        let parent = TraceContext::parse(PARENT).unwrap();
+        let context = parent.child();
+        let span = SpanRecord {
+            name: span_names::UPSTREAM,
+            kind: SpanKind::Client,
+            context,
+            parent_span_id: Some(parent.span_id),
+            start: UNIX_EPOCH + Duration::from_nanos(1_500),
+            end: UNIX_EPOCH + Duration::from_nanos(2_500),
+            attributes: vec![
+                (attributes::GEN_AI_SYSTEM, "openai".into()),
+                (attributes::GEN_AI_USAGE_INPUT_TOKENS, 12u32.into()),
+            ],
+            status: SpanStatus::Error("upstream timed out".to_string()),
+        };
+
+        let request = encode_export_request("union_square", &[span]);
+
+        let resource = &request["resourceSpans"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0],
+            json!({"key": "service.name", "value": {"stringValue": "union_square"}})
+        );
+        let encoded = &resource["scopeSpans"][0]["spans"][0];
+        assert_eq!(encoded["traceId"], "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(encoded["spanId"], context.span_id.to_string());
+        assert_eq!(encoded["parentSpanId"], "00f067aa0ba902b7");
+        assert_eq!(encoded["name"], "union_square.upstream");
+        assert_eq!(encoded["kind"], 3);
+        assert_eq!(encoded["startTimeUnixNano"], "1500");
+        assert_eq!(encoded["endTimeUnixNano"], "2500");
+        assert_eq!(
+            encoded["attributes"][1],
+            json!({"key": "gen_ai.usage.input_tokens", "value": {"intValue": "12"}})
+        );
+        assert_eq!(
+            encoded["status"],
+            json!({"code": 2, "message": "upstream timed out"})
+        );
+    }
+
+    #[test]
+    fn test_gen_ai_system_uses_semantic_convention_names() {
+        assert_eq!(gen_ai_system("bedrock"), "aws.bedrock");
+        assert_eq!(gen_ai_system("vertex"), "vertex_ai");
+        assert_eq!(gen_ai_system("azure"), "az.ai.openai");
+        assert_eq!(gen_ai_system("openai"), "openai");
+        assert_eq!(gen_ai_system("anthropic"), "anthropic");
+    }
+
+    #[tokio::test]
+    async fn test_exporter_posts_spans_to_the_collector() {
+        let collector = spawn_collector().await;
+        let mut config = OtlpConfig::new(TargetUrl::try_new(collector.endpoint()).unwrap());
+        config
+            .headers
+            .insert("x-collector-token".to_string(), "secret".to_string());
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+        let (tracer, exporter) = OtlpExporter::spawn(&config, client).unwrap();
+
+        tracer
+            .start(span_names::AUDIT_PERSIST, SpanKind::Internal, None)
+            .end();
+        exporter.shutdown().await.unwrap();
+
+        let spans = collector.spans();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0]["name"], "union_square.audit.persist");
+        assert_eq!(spans[0]["kind"], 1);
+        assert_eq!(collector.headers()[0]["x-collector-token"], "secret");
+    }
+
+    #[test]
+    fn test_spawn_rejects_invalid_headers() {
+        let mut config =
+            OtlpConfig::new(TargetUrl::try_new("http://localhost:4318".to_string()).unwrap());
+        config
+            .headers
+            .insert("bad header".to_string(), "value".to_string());
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let result = OtlpExporter::spawn(&config, client);
+
+        assert!(matches!(result, Err(ProxyError::InvalidHeader { name }) if name == "bad header"));
+    }
+}
@@ -2,24 +2,137 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::providers::bedrock::types::AwsRegion;
-    use crate::proxy::types::ApiKey;
+    use crate::domain::commands::audit_commands::RecordAuditEvent;
+    use crate::domain::config_types::ProviderName;
+    use crate::domain::events::DomainEvent;
+    use crate::domain::llm::LlmProvider;
+    use crate::domain::pricing::{PricingCatalog, PricingEntry, TokenPrice};
+    use crate::domain::session::{ClientSessionId, SessionId, SessionScope};
+    use crate::domain::types::ModelId;
+    use crate::infrastructure::eventcore::service::EventCoreService;
+    use crate::providers::config::{ProviderConfig, ProviderKind};
+    use crate::proxy::types::{ApiKey, ProxyError};
     use crate::proxy::{AuthConfig, ProxyConfig, ProxyService};
     use axum::body::Body;
+    use bytes::Bytes;
+    use chrono::NaiveDate;
     use http_body_util::BodyExt;
     use hyper::{Request, StatusCode};
     use mockito::{Matcher, Server};
     use serde_json::json;
+    use std::sync::Arc;
     use std::time::Duration;
 
+    /// Proxy config with a single `kind` provider sending to `base_url`
+    fn routed_to(kind: ProviderKind, base_url: String) -> ProxyConfig {
+        ProxyConfig {
+            providers: vec![ProviderConfig {
+                base_url: Some(base_url),
+                ..ProviderConfig::new(kind)
+            }],
+            ..ProxyConfig::default()
+        }
+    }
+
+    fn price(provider: &str, model: &str, input: &str, output: &str) -> PricingEntry {
+        PricingEntry {
+            provider: ProviderName::try_new(provider.to_string()).unwrap(),
+            model: ModelId::try_new(model.to_string()).unwrap(),
+            effective_from: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            input_per_million: TokenPrice::try_new(input.parse().unwrap()).unwrap(),
+            output_per_million: TokenPrice::try_new(output.parse().unwrap()).unwrap(),
+            cache_read_per_million: None,
+            cache_write_per_million: None,
+        }
+    }
+
+    /// A proxied response and the audit events persisted for its request
+    struct Audited {
+        status: StatusCode,
+        body: Bytes,
+        /// Events on the stream of the session the request was sent in
+        session_events: Vec<DomainEvent>,
+        /// Events on the request's own stream
+        request_events: Vec<DomainEvent>,
+    }
+
+    /// Send `request` with proxy key `test-key` through `service`, then wait
+    /// for its response body to be audited to `event_store`
+    async fn send_audited(
+        service: ProxyService,
+        event_store: Arc<EventCoreService>,
+        mut request: Request<Body>,
+    ) -> Audited {
+        use tower::ServiceExt;
+
+        let client_session = "provider-audit";
+        request
+            .headers_mut()
+            .insert("x-unionsquare-session-id", client_session.parse().unwrap());
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .api_keys
+            .insert(ApiKey::try_new("test-key".to_string()).unwrap());
+        let (router, audit_handle) = service
+            .with_event_store(Arc::clone(&event_store))
+            .into_router_with_audit_handle(auth_config);
+
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+
+        let session_id = SessionId::from_client_session_id(
+            SessionScope::api_key("test-key"),
+            &ClientSessionId::try_new(client_session.to_string()).unwrap(),
+        );
+        let session_stream = RecordAuditEvent::session_stream_id(&session_id).unwrap();
+        let read = |stream| {
+            let event_store = Arc::clone(&event_store);
+            async move {
+                event_store
+                    .read_stream::<DomainEvent>(stream)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .collect::<Vec<_>>()
+            }
+        };
+        let (session_events, request_events) =
+            tokio::time::timeout(Duration::from_secs(5), async {
+                loop {
+                    let session_events = read(session_stream.clone()).await;
+                    if let Some(request_id) =
+                        session_events.iter().find_map(DomainEvent::request_id)
+                    {
+                        let request_stream =
+                            RecordAuditEvent::request_stream_id(request_id).unwrap();
+                        let request_events = read(request_stream).await;
+                        if request_events.iter().any(|event| {
+                            matches!(event, DomainEvent::LlmResponseBodyCaptured { .. })
+                        }) {
+                            return (session_events, request_events);
+                        }
+                    }
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .expect("the response body should be audited");
+        audit_handle.shutdown().await.unwrap();
+
+        Audited {
+            status,
+            body,
+            session_events,
+            request_events,
+        }
+    }
+
     #[tokio::test]
     async fn test_bedrock_provider_integration() {
         let mut mock_server = Server::new_async().await;
         let mock_url = mock_server.url();
 
-        // Override the bedrock region to use mock URL
-        std::env::set_var("BEDROCK_ENDPOINT_OVERRIDE", mock_url.clone());
-
         // Create mock Bedrock endpoint
         let mock = mock_server
             .mock("POST", "/model/anthropic.claude-3-sonnet-20240229/invoke")
@@ -52,9 +165,8 @@ mod tests {
 
         // Create proxy with Bedrock provider enabled
         let config = ProxyConfig {
-            bedrock_region: Some(AwsRegion::try_new("us-east-1".to_string()).unwrap()),
             request_timeout: Duration::from_secs(5),
-            ..Default::default()
+            ..routed_to(ProviderKind::Bedrock, mock_url)
         };
 
         let proxy_service = ProxyService::new(config);
@@ -134,6 +246,533 @@ mod tests {
         mock.assert_async().await;
     }
 
+    #[tokio::test]
+    async fn test_openai_provider_integration() {
+        let mut mock_server = Server::new_async().await;
+
+        let mock = mock_server
+            .mock("POST", "/v1/chat/completions")
+            .match_header("authorization", "Bearer sk-test")
+            // The proxy key is for the proxy alone
+            .match_header("x-api-key", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "model": "gpt-4o",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi!"}}],
+                    "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7}
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        // Point the OpenAI provider at the mock, as for a local compatible server
+        let proxy_service = ProxyService::new(routed_to(ProviderKind::OpenAi, mock_server.url()));
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+
+        let request_body =
+            json!({"model": "gpt-4o", "messages": [{"role": "user", "content": "Hello!"}]})
+                .to_string();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/openai/v1/chat/completions")
+            .header("x-api-key", "test-key")
+            .header("authorization", "Bearer sk-test")
+            .header("content-type", "application/json")
+            .body(Body::from(request_body.clone()))
+            .unwrap();
+
+        let audited = send_audited(proxy_service, event_store, request).await;
+
+        assert_eq!(audited.status, StatusCode::OK);
+        let response_json: serde_json::Value = serde_json::from_slice(&audited.body).unwrap();
+        assert_eq!(response_json["choices"][0]["message"]["content"], "Hi!");
+        mock.assert_async().await;
+
+        // Provider-routed requests are audited like any other
+        assert!(audited.session_events.iter().any(|event| matches!(
+            event,
+            DomainEvent::LlmRequestReceived { model_version, .. }
+                if model_version.model_id.as_ref() == "gpt-4o"
+        )));
+        assert!(audited.request_events.iter().any(|event| matches!(
+            event,
+            DomainEvent::LlmRequestBodyCaptured { body, .. }
+                if body.as_ref() == request_body.as_bytes()
+        )));
+        assert!(audited.request_events.iter().any(|event| matches!(
+            event,
+            DomainEvent::LlmResponseBodyCaptured { body, .. }
+                if body.as_ref() == audited.body.as_ref()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_provider_routed_responses_are_priced() {
+        let mut mock_server = Server::new_async().await;
+        let mock = mock_server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "model": "gpt-4o",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi!"}}],
+                    "usage": {"prompt_tokens": 1000, "completion_tokens": 100}
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let proxy_service = ProxyService::new(routed_to(ProviderKind::OpenAi, mock_server.url()))
+            .with_pricing(Arc::new(
+                PricingCatalog::new(vec![price("openai", "gpt-4o", "2.50", "10")]).unwrap(),
+            ));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/openai/v1/chat/completions")
+            .header("x-api-key", "test-key")
+            .header("authorization", "Bearer sk-test")
+            .body(Body::from(
+                json!({"model": "gpt-4o", "messages": [{"role": "user", "content": "Hello!"}]})
+                    .to_string(),
+            ))
+            .unwrap();
+        let audited = send_audited(
+            proxy_service,
+            Arc::new(EventCoreService::with_memory_store()),
+            request,
+        )
+        .await;
+
+        assert_eq!(audited.status, StatusCode::OK);
+        mock.assert_async().await;
+        let cost = audited.request_events.iter().find_map(|event| match event {
+            DomainEvent::CostCalculated { cost, .. } => Some(cost),
+            _ => None,
+        });
+        let cost = cost.expect("the response should be priced");
+        assert_eq!(cost.total.into_inner(), "0.0035".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_provider_integration() {
+        use tower::ServiceExt;
+
+        let mut mock_server = Server::new_async().await;
+
+        // The proxy key travels as Bearer and must not reach Anthropic
+        let mock = mock_server
+            .mock("POST", "/v1/messages")
+            .match_header("x-api-key", "sk-ant-test")
+            .match_header("anthropic-version", "2023-06-01")
+            .match_header("authorization", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("request-id", "req_01")
+            .with_body(
+                json!({
+                    "id": "msg_01",
+                    "type": "message",
+                    "role": "assistant",
+                    "model": "claude-3-5-haiku-20241022",
+                    "content": [{"type": "text", "text": "Hi!"}],
+                    "usage": {"input_tokens": 5, "output_tokens": 2}
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let proxy_service =
+            ProxyService::new(routed_to(ProviderKind::Anthropic, mock_server.url()));
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .api_keys
+            .insert(ApiKey::try_new("test-key".to_string()).unwrap());
+        let router = proxy_service.into_router(auth_config);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/anthropic/v1/messages")
+            .header("authorization", "Bearer test-key")
+            .header("x-api-key", "sk-ant-test")
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({
+                    "model": "claude-3-5-haiku-20241022",
+                    "max_tokens": 64,
+                    "messages": [{"role": "user", "content": "Hello!"}]
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let response_json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(response_json["content"][0]["text"], "Hi!");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_anthropic_streaming_usage_is_recorded() {
+        let mut mock_server = Server::new_async().await;
+
+        // Input tokens arrive on message_start, the output count on message_delta
+        let mock = mock_server
+            .mock("POST", "/v1/messages")
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(concat!(
+                "event: message_start\n",
+                "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_01\",\"model\":\"claude-3-5-haiku-20241022\",\"usage\":{\"input_tokens\":25,\"output_tokens\":1,\"cache_read_input_tokens\":512}}}\n\n",
+                "event: content_block_delta\n",
+                "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi!\"}}\n\n",
+                "event: message_delta\n",
+                "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":15}}\n\n",
+                "event: message_stop\n",
+                "data: {\"type\":\"message_stop\"}\n\n",
+            ))
+            .create_async()
+            .await;
+
+        let proxy_service =
+            ProxyService::new(routed_to(ProviderKind::Anthropic, mock_server.url())).with_pricing(
+                Arc::new(
+                    PricingCatalog::new(vec![price(
+                        "anthropic",
+                        "claude-3-5-haiku-20241022",
+                        "0.80",
+                        "4",
+                    )])
+                    .unwrap(),
+                ),
+            );
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/anthropic/v1/messages")
+            .header("authorization", "Bearer test-key")
+            .header("x-api-key", "sk-ant-test")
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({
+                    "model": "claude-3-5-haiku-20241022",
+                    "max_tokens": 64,
+                    "stream": true,
+                    "messages": [{"role": "user", "content": "Hello!"}]
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let audited = send_audited(proxy_service, event_store, request).await;
+
+        assert_eq!(audited.status, StatusCode::OK);
+        mock.assert_async().await;
+        let usage = audited.request_events.iter().find_map(|event| match event {
+            DomainEvent::CostCalculated { usage, .. } => Some(usage),
+            _ => None,
+        });
+        let usage = usage.expect("the streamed response should be priced");
+        assert_eq!(usage.input.into_inner(), 25);
+        assert_eq!(usage.output.into_inner(), 15);
+        assert_eq!(usage.cache_read.into_inner(), 512);
+    }
+
+    #[tokio::test]
+    async fn test_vertex_provider_integration() {
+        let mut mock_server = Server::new_async().await;
+
+        let mock = mock_server
+            .mock(
+                "POST",
+                "/v1/projects/acme/locations/us-central1/publishers/google/models/gemini-2.0-flash:streamGenerateContent",
+            )
+            .match_query(Matcher::UrlEncoded("alt".into(), "sse".into()))
+            .match_header("authorization", "Bearer ya29.token")
+            // The proxy key is for the proxy alone
+            .match_header("x-api-key", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(concat!(
+                "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"Hi!\"}]}}],",
+                "\"usageMetadata\":{\"promptTokenCount\":2,\"candidatesTokenCount\":1,\"totalTokenCount\":3}}\r\n\r\n",
+            ))
+            .create_async()
+            .await;
+
+        // Vertex bodies do not name the model, so cost relies on the path
+        let proxy_service = ProxyService::new(routed_to(ProviderKind::Vertex, mock_server.url()))
+            .with_pricing(Arc::new(
+                PricingCatalog::new(vec![price("google", "gemini-2.0-flash", "1", "4")]).unwrap(),
+            ));
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/vertex/v1/projects/acme/locations/us-central1/publishers/google/models/gemini-2.0-flash:streamGenerateContent?alt=sse")
+            .header("x-api-key", "test-key")
+            .header("authorization", "Bearer ya29.token")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"contents": [{"role": "user", "parts": [{"text": "Hello!"}]}]}).to_string(),
+            ))
+            .unwrap();
+
+        let audited = send_audited(proxy_service, event_store, request).await;
+
+        assert_eq!(audited.status, StatusCode::OK);
+        assert!(String::from_utf8_lossy(&audited.body).contains("Hi!"));
+        mock.assert_async().await;
+        let cost = audited.request_events.iter().find_map(|event| match event {
+            DomainEvent::CostCalculated {
+                model_version,
+                usage,
+                ..
+            } => Some((model_version, usage)),
+            _ => None,
+        });
+        let (model_version, usage) = cost.expect("the response should be priced");
+        assert_eq!(model_version.provider, LlmProvider::Google);
+        assert_eq!(model_version.model_id.as_ref(), "gemini-2.0-flash");
+        assert_eq!(usage.input.into_inner(), 2);
+        assert_eq!(usage.output.into_inner(), 1);
+    }
+
+    /// Proxy config with Azure resource `contoso` at `endpoint`, whose
+    /// `prod-chat` deployment serves `gpt-4o-2024-08-06`
+    fn azure_config(endpoint: String) -> ProxyConfig {
+        use crate::providers::azure::{
+            AzureDeploymentName, AzureResourceConfig, AzureResourceName,
+        };
+
+        ProxyConfig {
+            providers: vec![ProviderConfig {
+                resources: vec![AzureResourceConfig {
+                    name: AzureResourceName::try_new("contoso").unwrap(),
+                    endpoint: Some(endpoint),
+                    deployments: [(
+                        AzureDeploymentName::try_new("prod-chat").unwrap(),
+                        crate::providers::bedrock::types::ModelId::try_new(
+                            "gpt-4o-2024-08-06".to_string(),
+                        )
+                        .unwrap(),
+                    )]
+                    .into(),
+                }],
+                ..ProviderConfig::new(ProviderKind::Azure)
+            }],
+            ..ProxyConfig::default()
+        }
+    }
+
+    /// Send a chat completion for the `prod-chat` deployment to an Azure
+    /// mock, authenticating to the proxy with `proxy_key_header`
+    async fn send_to_azure(proxy_key_header: (&str, &str)) {
+        use tower::ServiceExt;
+
+        let mut mock_server = Server::new_async().await;
+
+        // Azure reads its own api-key; the proxy key is for the proxy alone
+        let mock = mock_server
+            .mock("POST", "/openai/deployments/prod-chat/chat/completions")
+            .match_query(Matcher::UrlEncoded(
+                "api-version".into(),
+                "2024-10-21".into(),
+            ))
+            .match_header("api-key", "azure-secret")
+            .match_header("x-api-key", Matcher::Missing)
+            .match_header("authorization", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "model": "gpt-4o",
+                    "choices": [{"index": 0, "message": {"role": "assistant", "content": "Hi!"}}],
+                    "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7}
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let proxy_service = ProxyService::new(azure_config(mock_server.url()));
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .api_keys
+            .insert(ApiKey::try_new("test-key".to_string()).unwrap());
+        let router = proxy_service.into_router(auth_config);
+
+        let (name, value) = proxy_key_header;
+        let request = Request::builder()
+            .method("POST")
+            .uri("/azure/contoso/openai/deployments/prod-chat/chat/completions?api-version=2024-10-21")
+            .header(name, value)
+            .header("api-key", "azure-secret")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"messages": [{"role": "user", "content": "Hello!"}]}).to_string(),
+            ))
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_azure_openai_provider_integration() {
+        send_to_azure(("x-api-key", "test-key")).await;
+    }
+
+    #[tokio::test]
+    async fn test_azure_bearer_proxy_key_is_not_forwarded() {
+        send_to_azure(("authorization", "Bearer test-key")).await;
+    }
+
+    #[tokio::test]
+    async fn test_azure_deployment_model_feeds_version_tracking() {
+        use crate::domain::commands::version_commands::version_stream_id;
+        use crate::domain::llm::ModelVersion;
+
+        let mut mock_server = Server::new_async().await;
+        let mock = mock_server
+            .mock("POST", "/openai/deployments/prod-chat/chat/completions")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"choices":[],"usage":{"prompt_tokens":5,"completion_tokens":2}}"#)
+            .create_async()
+            .await;
+        let event_store = Arc::new(EventCoreService::with_memory_store());
+
+        // The body names no model; the deployment mapping does
+        let request = Request::builder()
+            .method("POST")
+            .uri("/azure/contoso/openai/deployments/prod-chat/chat/completions?api-version=2024-10-21")
+            .header("x-api-key", "test-key")
+            .header("api-key", "azure-secret")
+            .body(Body::from(
+                json!({"messages": [{"role": "user", "content": "Hello!"}]}).to_string(),
+            ))
+            .unwrap();
+        let audited = send_audited(
+            ProxyService::new(azure_config(mock_server.url())),
+            Arc::clone(&event_store),
+            request,
+        )
+        .await;
+
+        assert_eq!(audited.status, StatusCode::OK);
+        mock.assert_async().await;
+        let model_version = ModelVersion {
+            provider: LlmProvider::Azure,
+            model_id: ModelId::try_new("gpt-4o-2024-08-06".to_string()).unwrap(),
+        };
+        let version_events: Vec<_> = event_store
+            .read_stream::<DomainEvent>(version_stream_id(&model_version).unwrap())
+            .await
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert!(matches!(
+            version_events.as_slice(),
+            [
+                DomainEvent::VersionFirstSeen { .. },
+                DomainEvent::VersionUsageRecorded { model_version: recorded, .. },
+            ] if *recorded == model_version
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_configured_instances_route_by_prefix() {
+        use crate::providers::bedrock::provider::PathPrefix;
+        use tower::ServiceExt;
+
+        let mut production = Server::new_async().await;
+        let mut staging = Server::new_async().await;
+
+        let production_mock = production
+            .mock("POST", "/v1/messages")
+            .expect(0)
+            .create_async()
+            .await;
+        // A staging Anthropic instance keeps Anthropic's X-API-Key handling
+        let staging_mock = staging
+            .mock("POST", "/v1/messages")
+            .match_header("x-api-key", "sk-ant-staging")
+            .match_header("authorization", Matcher::Missing)
+            .with_status(200)
+            .with_body(r#"{"content":[{"type":"text","text":"from staging"}]}"#)
+            .create_async()
+            .await;
+
+        let config = ProxyConfig {
+            providers: vec![
+                ProviderConfig {
+                    base_url: Some(production.url()),
+                    ..ProviderConfig::new(ProviderKind::Anthropic)
+                },
+                ProviderConfig {
+                    path_prefix: Some(PathPrefix::try_new("/anthropic-staging/").unwrap()),
+                    base_url: Some(staging.url()),
+                    ..ProviderConfig::new(ProviderKind::Anthropic)
+                },
+            ],
+            ..ProxyConfig::default()
+        };
+        let mut auth_config = AuthConfig::default();
+        auth_config
+            .api_keys
+            .insert(ApiKey::try_new("test-key".to_string()).unwrap());
+        let router = ProxyService::new(config).into_router(auth_config);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/anthropic-staging/v1/messages")
+            .header("authorization", "Bearer test-key")
+            .header("x-api-key", "sk-ant-staging")
+            .body(Body::from(r#"{"model":"claude-3-5-haiku-20241022"}"#))
+            .unwrap();
+
+        let response = router.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&body_bytes).contains("from staging"));
+        staging_mock.assert_async().await;
+        production_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_invalid_provider_config_is_rejected() {
+        let config = ProxyConfig {
+            providers: vec![
+                ProviderConfig::new(ProviderKind::OpenAi),
+                ProviderConfig::new(ProviderKind::OpenAi),
+            ],
+            ..ProxyConfig::default()
+        };
+
+        assert!(matches!(
+            ProxyService::try_new(config),
+            Err(ProxyError::ProviderConfiguration(_))
+        ));
+    }
+
     #[tokio::test]
     async fn test_fallback_to_header_routing() {
         let mut mock_server = Server::new_async().await;
@@ -3,52 +3,94 @@
 //! This module implements the URL-based routing pattern defined in ADR-0011,
 //! routing requests to appropriate providers based on URL path prefixes.
 
-use crate::providers::ProviderRegistry;
-use crate::proxy::types::{ProxyError, RequestId};
+use crate::providers::{ProviderId, ProviderMetadata, ProviderRegistry};
+use crate::proxy::otlp::{end_upstream_span, start_upstream_span, Tracer};
+use crate::proxy::types::ProxyError;
+use crate::proxy::upstream_client::UpstreamClient;
 use axum::body::Body;
 use hyper::{Request, Response};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Router for provider-based request handling
+#[derive(Clone)]
 pub struct ProviderRouter {
     registry: Arc<ProviderRegistry>,
-    client: hyper_util::client::legacy::Client<
-        hyper_util::client::legacy::connect::HttpConnector,
-        Body,
-    >,
+    client: UpstreamClient,
+    default_timeout: Option<Duration>,
+    tracer: Option<Tracer>,
 }
 
 impl ProviderRouter {
-    /// Create a new provider router
-    pub fn new(registry: Arc<ProviderRegistry>) -> Self {
-        let client =
-            hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
-                .http1_title_case_headers(true)
-                .http1_preserve_header_case(true)
-                .build_http();
-
-        Self { registry, client }
+    /// Create a new provider router forwarding through `client`
+    pub fn new(registry: Arc<ProviderRegistry>, client: UpstreamClient) -> Self {
+        Self {
+            registry,
+            client,
+            default_timeout: None,
+            tracer: None,
+        }
+    }
+
+    /// Bound upstream calls for providers registered without their own timeout
+    pub fn with_default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Record a span for each upstream call with `tracer`
+    pub fn with_tracer(mut self, tracer: Tracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// The provider serving `path`, if one is registered for it
+    pub fn provider_id(&self, path: &str) -> Option<ProviderId> {
+        self.registry.resolve(path).map(|entry| entry.provider.id())
     }
 
     /// Route and forward a request to the appropriate provider
+    ///
+    /// Returns the provider's response with the metadata the provider
+    /// extracted from the request and response headers.
     pub async fn route_request(
         &self,
-        request: Request<Body>,
-        _request_id: RequestId,
-    ) -> Result<Response<Body>, ProxyError> {
+        mut request: Request<Body>,
+    ) -> Result<(Response<Body>, ProviderMetadata), ProxyError> {
         let path = request.uri().path();
 
         // Find the provider that handles this path
-        let provider = self
+        let entry = self
             .registry
-            .route(path)
+            .resolve(path)
             .ok_or_else(|| ProxyError::InvalidTargetUrl(format!("No provider for path: {path}")))?;
 
+        let target = entry.provider.transform_url(request.uri()).ok();
+        let span = start_upstream_span(
+            self.tracer.as_ref(),
+            target.as_ref().and_then(|target| target.host()),
+            request.headers_mut(),
+        );
+
+        // The provider consumes the request, so keep what metadata needs of it
+        let mut received = Request::new(Body::empty());
+        *received.method_mut() = request.method().clone();
+        *received.uri_mut() = request.uri().clone();
+        *received.headers_mut() = request.headers().clone();
+
         // Forward the request to the provider
-        provider
-            .forward_request(request, &self.client)
-            .await
-            .map_err(|e| e.into())
+        let forward = entry.provider.forward_request(request, &self.client);
+        let result = match entry.timeout.or(self.default_timeout) {
+            Some(timeout) => tokio::time::timeout(timeout, forward)
+                .await
+                .map_err(|_| ProxyError::RequestTimeout(timeout))
+                .and_then(|result| result.map_err(Into::into)),
+            None => forward.await.map_err(Into::into),
+        };
+        end_upstream_span(span, &result);
+        let response = result?;
+        let metadata = entry.provider.extract_metadata(&received, &response);
+        Ok((response, metadata))
     }
 }
 
@@ -56,8 +98,16 @@ impl ProviderRouter {
 mod tests {
     use super::*;
     use crate::providers::bedrock::{provider::BedrockProvider, types::AwsRegion};
+    use crate::providers::openai::OpenAiProvider;
+    use crate::proxy::test_utils::tls::{spawn_tls_upstream, TestCertificates};
+    use crate::proxy::types::UpstreamTlsConfig;
+    use crate::proxy::upstream_client::build_upstream_client;
     use serde_json::json;
 
+    fn default_client() -> UpstreamClient {
+        build_upstream_client(&UpstreamTlsConfig::default()).unwrap()
+    }
+
     #[tokio::test]
     async fn test_provider_routing() {
         let mut registry = ProviderRegistry::new();
@@ -68,7 +118,7 @@ mod tests {
         ));
         registry.register(bedrock);
 
-        let router = ProviderRouter::new(Arc::new(registry));
+        let router = ProviderRouter::new(Arc::new(registry), default_client());
 
         // Test Bedrock routing
         let request = Request::builder()
@@ -80,14 +130,14 @@ mod tests {
             .unwrap();
 
         // This will fail in a real test without a mock server, but it validates routing works
-        let result = router.route_request(request, RequestId::new()).await;
+        let result = router.route_request(request).await;
         assert!(result.is_err()); // Expected since we're not mocking the actual provider endpoint
     }
 
     #[tokio::test]
     async fn test_no_provider_found() {
         let registry = Arc::new(ProviderRegistry::new());
-        let router = ProviderRouter::new(registry);
+        let router = ProviderRouter::new(registry, default_client());
 
         let request = Request::builder()
             .method("POST")
@@ -95,7 +145,7 @@ mod tests {
             .body(Body::empty())
             .unwrap();
 
-        let result = router.route_request(request, RequestId::new()).await;
+        let result = router.route_request(request).await;
         assert!(result.is_err());
 
         match result.unwrap_err() {
@@ -105,4 +155,78 @@ mod tests {
             _ => panic!("Expected InvalidTargetUrl error"),
         }
     }
+
+    #[tokio::test]
+    async fn test_forwards_to_https_provider() {
+        let certs = TestCertificates::generate();
+        let dir = certs.write_to_tempdir();
+        let addr = spawn_tls_upstream(&certs, false, r#"{"completion":"hi"}"#).await;
+
+        let mut registry = ProviderRegistry::new();
+        registry.register(Arc::new(BedrockProvider::with_base_url(format!(
+            "https://localhost:{}",
+            addr.port()
+        ))));
+        let client = build_upstream_client(&UpstreamTlsConfig {
+            extra_root_certificates: vec![dir.path().join(TestCertificates::CA_FILE)],
+            client_certificate: None,
+        })
+        .unwrap();
+        let router = ProviderRouter::new(Arc::new(registry), client);
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/bedrock/model/test/invoke")
+            .header(
+                "authorization",
+                "AWS4-HMAC-SHA256 Credential=test/20250126/us-east-1/bedrock/aws4_request",
+            )
+            .header("x-amz-date", "20250126T120000Z")
+            .body(Body::from(json!({"prompt": "hi"}).to_string()))
+            .unwrap();
+
+        let (response, metadata) = router.route_request(request).await.unwrap();
+        assert_eq!(response.status(), hyper::StatusCode::OK);
+        assert_eq!(metadata.provider_id, ProviderId::bedrock());
+        assert_eq!(
+            metadata.model_id.map(|model_id| model_id.into_inner()),
+            Some("test".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_provider_timeout_bounds_upstream_call() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let mut registry = ProviderRegistry::new();
+        registry.register_with_timeout(
+            Arc::new(OpenAiProvider::with_base_url(format!("http://{addr}")).unwrap()),
+            Some(Duration::from_millis(50)),
+        );
+        let router = ProviderRouter::new(Arc::new(registry), default_client())
+            .with_default_timeout(Duration::from_secs(30));
+        assert_eq!(
+            router.provider_id("/openai/v1/models"),
+            Some(ProviderId::openai())
+        );
+        assert_eq!(router.provider_id("/bedrock/model/test/invoke"), None);
+
+        let request = Request::builder()
+            .uri("/openai/v1/models")
+            .body(Body::empty())
+            .unwrap();
+
+        let result = router.route_request(request).await;
+        assert!(
+            matches!(result, Err(ProxyError::RequestTimeout(t)) if t == Duration::from_millis(50))
+        );
+    }
 }
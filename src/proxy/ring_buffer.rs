@@ -3,6 +3,14 @@
 //! This implementation provides a lock-free Multi-Producer Single-Consumer (MPSC) ring buffer
 //! designed for Union Square's dual-path architecture. It maintains strict safety invariants
 //! through atomic state coordination while providing <1μs write latency.
+//!
+//! The buffer is split into shards so that several audit consumers can drain it in
+//! parallel. Every entry for a request lands in the same shard, and each shard is
+//! FIFO with exactly one consumer, so per-request order is preserved.
+//!
+//! An entry larger than one slot is framed across consecutive slots (ADR-0017).
+//! Each slot carries its frame's sequence number and a flag saying whether more
+//! frames follow, and the reader reassembles the frames into the original entry.
 
 // SAFETY: This module uses unsafe code for a performance-critical lock-free data structure.
 // All unsafe operations are bounded by documented safety invariants enforced through atomic
@@ -11,7 +19,9 @@
 
 use crate::proxy::types::*;
 use std::cell::UnsafeCell;
-use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
 use uuid::Uuid;
 
 /// Slot states for the ring buffer state machine
@@ -39,21 +49,35 @@ impl From<u8> for SlotState {
     }
 }
 
+/// Where a slot's bytes sit within an entry framed across several slots
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Frame {
+    /// Index of this frame within its entry, starting at 0
+    sequence: u32,
+    /// Another frame of the same entry is in the next slot
+    continues: bool,
+}
+
 /// A single slot in the ring buffer
 ///
 /// # Safety Invariants
 ///
 /// - Only the thread that successfully transitions state from Empty to Writing may access
-///   the non-atomic fields (timestamp, request_id, data)
+///   the non-atomic fields (timestamp, request_id, frame, data)
 /// - Non-atomic fields must only be accessed when holding the Writing state
 /// - State transitions must follow: Empty → Writing → Ready → Reading → Empty
+/// - A writer may only claim the slot for the write position stored in `position`,
+///   which the reader advances by one lap when it empties the slot
 /// - Cache line alignment prevents false sharing between slots
 #[repr(C, align(64))] // Cache line alignment
 pub struct Slot {
     state: AtomicU8,
     size: AtomicU32,
+    /// Write position the slot is free for once its previous entry has been read
+    position: AtomicU64,
     timestamp: UnsafeCell<TimestampNanos>,
     request_id: UnsafeCell<[u8; UUID_SIZE_BYTES]>, // UUID bytes
+    frame: UnsafeCell<Frame>,
     data: UnsafeCell<Vec<u8>>,
 }
 
@@ -65,22 +89,27 @@ unsafe impl Send for Slot {}
 unsafe impl Sync for Slot {}
 
 impl Slot {
-    fn new(slot_size: SlotSize) -> Self {
+    fn new(slot_size: SlotSize, position: u64) -> Self {
         Self {
             state: AtomicU8::new(SlotState::Empty as u8),
             size: AtomicU32::new(0),
+            position: AtomicU64::new(position),
             timestamp: UnsafeCell::new(TimestampNanos::from(0)),
             request_id: UnsafeCell::new([0; UUID_SIZE_BYTES]),
+            frame: UnsafeCell::new(Frame::default()),
             data: UnsafeCell::new(vec![0; *slot_size.as_ref()]),
         }
     }
 }
 
 /// Statistics about ring buffer usage
+#[derive(Clone, Copy, Debug)]
 pub struct RingBufferStats {
     pub total_writes: u64,
     pub total_reads: u64,
     pub dropped_events: DroppedEventCount,
+    /// Entries cut short because they were larger than a whole shard
+    pub truncated_events: u64,
 }
 
 /// Lock-free ring buffer for audit event handoff
@@ -94,36 +123,62 @@ pub struct RingBufferStats {
 /// - <1μs write latency (when slots are available)
 /// - Zero heap allocations after initialization
 /// - Lock-free concurrent writes from multiple producers
-/// - Sequential reads from single consumer
+/// - Sequential reads from one consumer per shard
+/// - Consumers park on [`RingBuffer::readable`] instead of polling; a write
+///   only touches the notifier's lock when its shard's consumer is parked
 ///
 /// # Safety
 ///
 /// Uses unsafe code for performance-critical path, but maintains strict safety invariants
 /// through atomic state coordination. All unsafe operations are documented and bounded.
 pub struct RingBuffer {
+    shards: Vec<Shard>,
+    /// Shard the next [`RingBuffer::read`] starts from
+    next_read_shard: AtomicUsize,
+}
+
+/// One independently consumed ring of slots
+struct Shard {
     slots: Vec<Slot>,
     slot_count: usize,
     slot_size: SlotSize,
     write_position: AtomicU64,
     read_position: AtomicU64,
     overflow_count: AtomicU64,
+    truncated_count: AtomicU64,
     successful_writes: AtomicU64,
     successful_reads: AtomicU64,
+    readable: Notify,
 }
 
 impl RingBuffer {
-    /// Statistics about ring buffer usage
+    /// Statistics about ring buffer usage, summed over all shards
     pub fn stats(&self) -> RingBufferStats {
+        let (total_writes, total_reads) =
+            self.shards.iter().fold((0, 0), |(writes, reads), shard| {
+                (
+                    writes + shard.successful_writes.load(Ordering::Relaxed),
+                    reads + shard.successful_reads.load(Ordering::Relaxed),
+                )
+            });
         RingBufferStats {
-            total_writes: self.successful_writes.load(Ordering::Relaxed),
-            total_reads: self.successful_reads.load(Ordering::Relaxed),
-            dropped_events: DroppedEventCount::from(self.overflow_count.load(Ordering::Relaxed)),
+            total_writes,
+            total_reads,
+            dropped_events: DroppedEventCount::from(self.overflow_count()),
+            truncated_events: self
+                .shards
+                .iter()
+                .map(|shard| shard.truncated_count.load(Ordering::Relaxed))
+                .sum(),
         }
     }
 
     /// Create a new ring buffer with the given configuration
+    ///
+    /// The buffer's slots are divided evenly between `config.shards` shards.
     pub fn new(config: &RingBufferConfig) -> Self {
         let calculated_slot_count = *config.buffer_size.as_ref() / *config.slot_size.as_ref();
+        let calculated_slot_count = calculated_slot_count / *config.shards.as_ref();
         // Ensure power of 2 for efficient modulo, but don't exceed calculated count
         let mut slot_count_value = calculated_slot_count.next_power_of_two();
 
@@ -135,27 +190,34 @@ impl RingBuffer {
         // Ensure at least 1 slot
         let slot_count = slot_count_value.max(1);
 
-        let slots = (0..slot_count)
-            .map(|_| Slot::new(config.slot_size))
-            .collect();
-
         Self {
-            slots,
-            slot_count,
-            slot_size: config.slot_size,
-            write_position: AtomicU64::new(0),
-            read_position: AtomicU64::new(0),
-            overflow_count: AtomicU64::new(0),
-            successful_writes: AtomicU64::new(0),
-            successful_reads: AtomicU64::new(0),
+            shards: (0..*config.shards.as_ref())
+                .map(|_| Shard::new(slot_count, config.slot_size))
+                .collect(),
+            next_read_shard: AtomicUsize::new(0),
         }
     }
 
-    /// Write data to the ring buffer
+    /// Number of shards, and so of consumers that can read in parallel
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Shard that every entry for `request_id` is written to
     ///
-    /// Returns Ok(()) on success, or Err(overflow_count) if the buffer is full.
+    /// Uses the random low bits of the v7 UUID, so requests spread evenly
+    /// without hashing on the write path.
+    pub fn shard_of(&self, request_id: RequestId) -> usize {
+        let (_, random) = request_id.as_ref().as_u64_pair();
+        (random % self.shards.len() as u64) as usize
+    }
+
+    /// Write data to the shard for `request_id`
+    ///
+    /// Returns Ok(()) on success, or Err(overflow_count) if the shard is full.
     /// This maintains the fail-when-busy semantics required by ADR-0009 for proper
-    /// backpressure and prevents corruption of multi-slot chunked payloads (ADR-0017).
+    /// backpressure and prevents corruption of multi-slot chunked payloads (ADR-0017):
+    /// the slots of a multi-slot entry are claimed together or not at all.
     ///
     /// # Performance
     ///
@@ -165,80 +227,205 @@ impl RingBuffer {
     ///
     /// # Data Handling
     ///
-    /// - Data larger than slot size is truncated
+    /// - Data larger than slot size is framed across consecutive slots
+    /// - Data larger than the whole shard is truncated to fit and counted
     /// - Timestamps are captured at write time
     /// - Request IDs are stored for correlation
     pub fn write(&self, request_id: RequestId, data: &[u8]) -> Result<(), u64> {
-        // Get next write position
-        let position = self.write_position.fetch_add(1, Ordering::Relaxed);
-        let slot_index = (position & (self.slot_count as u64 - 1)) as usize;
-        let slot = &self.slots[slot_index];
-
-        // Try to claim the slot
-        let current_state = slot.state.load(Ordering::Acquire);
-        if current_state != SlotState::Empty as u8 {
-            // Slot not available, increment overflow counter
-            let overflow = self.overflow_count.fetch_add(1, Ordering::Relaxed) + 1;
-            return Err(overflow);
+        self.shards[self.shard_of(request_id)]
+            .write(request_id, data)
+            .map_err(|()| self.overflow_count())
+    }
+
+    /// Read the next available entry from any shard
+    ///
+    /// For a consumer that drains every shard on its own; it must not run
+    /// alongside per-shard consumers using [`RingBuffer::read_shard`].
+    pub fn read(&self) -> Option<(RequestId, Vec<u8>)> {
+        let start = self.next_read_shard.load(Ordering::Relaxed);
+        (0..self.shards.len())
+            .map(|offset| (start + offset) % self.shards.len())
+            .find_map(|shard| {
+                let entry = self.shards[shard].read()?;
+                self.next_read_shard.store(shard + 1, Ordering::Relaxed);
+                Some(entry)
+            })
+    }
+
+    /// Read the next available entry from one shard
+    ///
+    /// Each shard supports exactly one consumer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard` is not below [`RingBuffer::shard_count`].
+    pub fn read_shard(&self, shard: usize) -> Option<(RequestId, Vec<u8>)> {
+        self.shards[shard].read()
+    }
+
+    /// Wait until a write has reached `shard` since its consumer last waited
+    ///
+    /// Writes made between an empty [`RingBuffer::read_shard`] and this call
+    /// are not missed: the notifier keeps a permit, so the future completes
+    /// at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard` is not below [`RingBuffer::shard_count`].
+    pub fn readable(&self, shard: usize) -> Notified<'_> {
+        self.shards[shard].readable.notified()
+    }
+
+    /// Get the current overflow count
+    ///
+    /// Returns the number of write attempts that failed due to unavailable slots.
+    /// This provides visibility into backpressure and helps with capacity planning.
+    pub fn overflow_count(&self) -> u64 {
+        self.shards
+            .iter()
+            .map(|shard| shard.overflow_count.load(Ordering::Relaxed))
+            .sum()
+    }
+}
+
+impl Shard {
+    fn new(slot_count: usize, slot_size: SlotSize) -> Self {
+        let slots = (0..slot_count)
+            .map(|index| Slot::new(slot_size, index as u64))
+            .collect();
+
+        Self {
+            slots,
+            slot_count,
+            slot_size,
+            write_position: AtomicU64::new(0),
+            read_position: AtomicU64::new(0),
+            overflow_count: AtomicU64::new(0),
+            truncated_count: AtomicU64::new(0),
+            successful_writes: AtomicU64::new(0),
+            successful_reads: AtomicU64::new(0),
+            readable: Notify::new(),
         }
+    }
 
-        // Try to transition to Writing state
-        match slot.state.compare_exchange(
-            SlotState::Empty as u8,
-            SlotState::Writing as u8,
-            Ordering::AcqRel,
-            Ordering::Acquire,
-        ) {
-            Ok(_) => {
-                // We have exclusive access to the slot
-
-                // Safety check in debug mode
-                debug_assert_eq!(
-                    slot.state.load(Ordering::Acquire),
-                    SlotState::Writing as u8,
-                    "State should be Writing after successful CAS"
-                );
-
-                // Capture timestamp before entering unsafe block
-                let timestamp_value = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64;
-
-                // Copy data (with truncation if needed)
-                let copy_size = data.len().min(*self.slot_size.as_ref());
-
-                // SAFETY: Exclusive access guaranteed by successful CAS transition from Empty to Writing.
-                // - We successfully transitioned the state from Empty to Writing using a CAS operation
-                // - Only one thread can win the compare_exchange operation, ensuring exclusive access
-                // - The state machine guarantees no other thread accesses these fields until Ready state
-                // - The data Vec is pre-allocated, so no reallocation or memory race occurs
-                unsafe {
-                    let data_ref = &mut *slot.data.get();
-                    data_ref[..copy_size].copy_from_slice(&data[..copy_size]);
-
-                    *slot.timestamp.get() = TimestampNanos::from(timestamp_value);
-
-                    (*slot.request_id.get()).copy_from_slice(request_id.as_ref().as_bytes());
-                }
+    fn slot_at(&self, position: u64) -> &Slot {
+        &self.slots[(position & (self.slot_count as u64 - 1)) as usize]
+    }
+
+    /// Claim enough slots for `data` and copy it into them; fails when they are busy
+    fn write(&self, request_id: RequestId, data: &[u8]) -> Result<(), ()> {
+        let slot_size = *self.slot_size.as_ref();
+        let needed = data.len().div_ceil(slot_size).max(1);
+        let frames = needed.min(self.slot_count);
+        let data = if needed > frames {
+            self.truncated_count.fetch_add(1, Ordering::Relaxed);
+            &data[..frames * slot_size]
+        } else {
+            data
+        };
+
+        let Some(position) = self.claim(frames as u64) else {
+            // Slots not available, increment overflow counter
+            self.overflow_count.fetch_add(1, Ordering::Relaxed);
+            return Err(());
+        };
+
+        // Capture timestamp before entering unsafe block
+        let timestamp_value = chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64;
+
+        // Publish the last frame first, so a reader that sees the first frame
+        // Ready finds every later frame Ready too
+        for sequence in (0..frames).rev() {
+            let slot = self.slot_at(position + sequence as u64);
+            let chunk = &data[(sequence * slot_size).min(data.len())
+                ..((sequence + 1) * slot_size).min(data.len())];
+            let frame = Frame {
+                sequence: sequence as u32,
+                continues: sequence + 1 < frames,
+            };
+
+            // Try to transition to Writing state
+            let claimed = slot.state.compare_exchange(
+                SlotState::Empty as u8,
+                SlotState::Writing as u8,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            );
+            debug_assert!(
+                claimed.is_ok(),
+                "A slot free for its write position must be Empty"
+            );
+
+            // SAFETY: Exclusive access guaranteed by the transition from Empty to Writing.
+            // - Winning the write position CAS in `claim` makes this thread the only writer
+            //   for this slot until the reader empties it on the next lap
+            // - The reader consumed the previous entry and stored Empty before advancing
+            //   `position`, which `claim` observed with Acquire ordering
+            // - The state machine guarantees no other thread accesses these fields until Ready state
+            // - The data Vec is pre-allocated, so no reallocation or memory race occurs
+            unsafe {
+                let data_ref = &mut *slot.data.get();
+                data_ref[..chunk.len()].copy_from_slice(chunk);
+
+                *slot.timestamp.get() = TimestampNanos::from(timestamp_value);
+
+                (*slot.request_id.get()).copy_from_slice(request_id.as_ref().as_bytes());
+
+                *slot.frame.get() = frame;
+            }
 
-                // Store actual size
-                slot.size.store(copy_size as u32, Ordering::Release);
+            // Store actual size
+            slot.size.store(chunk.len() as u32, Ordering::Release);
+
+            // Mark as ready for reading
+            slot.state.store(SlotState::Ready as u8, Ordering::Release);
+        }
 
-                // Mark as ready for reading
-                slot.state.store(SlotState::Ready as u8, Ordering::Release);
+        // Increment successful writes counter
+        self.successful_writes.fetch_add(1, Ordering::Relaxed);
 
-                // Increment successful writes counter
-                self.successful_writes.fetch_add(1, Ordering::Relaxed);
+        // Wake the consumer if it is parked; otherwise this leaves a permit
+        self.readable.notify_one();
 
-                Ok(())
+        Ok(())
+    }
+
+    /// Reserve `frames` consecutive write positions whose slots are all free
+    ///
+    /// Returns the first position, or None when a slot in the range still
+    /// holds an entry from the previous lap. Positions are only taken once
+    /// every slot is free, so a failed write never leaves a gap for the reader.
+    fn claim(&self, frames: u64) -> Option<u64> {
+        let mut position = self.write_position.load(Ordering::Relaxed);
+        'retry: loop {
+            for offset in 0..frames {
+                let free_for = self
+                    .slot_at(position + offset)
+                    .position
+                    .load(Ordering::Acquire);
+                if free_for < position + offset {
+                    return None;
+                }
+                if free_for > position + offset {
+                    // Another writer has moved past this position
+                    position = self.write_position.load(Ordering::Relaxed);
+                    continue 'retry;
+                }
             }
-            Err(_) => {
-                // Someone else got the slot, count as overflow
-                let overflow = self.overflow_count.fetch_add(1, Ordering::Relaxed) + 1;
-                Err(overflow)
+
+            match self.write_position.compare_exchange_weak(
+                position,
+                position + frames,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Some(position),
+                Err(current) => position = current,
             }
         }
     }
 
-    /// Read the next available entry from the ring buffer
+    /// Read the next available entry from this shard
     ///
     /// Returns Some((request_id, data)) if data is available, None otherwise.
     /// This follows the single-consumer pattern - only one thread should call read().
@@ -247,84 +434,88 @@ impl RingBuffer {
     ///
     /// This method is designed for single-consumer use. Multiple concurrent readers
     /// would violate safety invariants and could cause data corruption.
-    pub fn read(&self) -> Option<(RequestId, Vec<u8>)> {
+    fn read(&self) -> Option<(RequestId, Vec<u8>)> {
         let read_pos = self.read_position.load(Ordering::Relaxed);
-        let slot_index = (read_pos & (self.slot_count as u64 - 1)) as usize;
-        let slot = &self.slots[slot_index];
+        let slot = self.slot_at(read_pos);
 
         // Check if slot is ready for reading
         if slot.state.load(Ordering::Acquire) != SlotState::Ready as u8 {
             return None;
         }
 
-        // Try to transition to Reading state
-        match slot.state.compare_exchange(
-            SlotState::Ready as u8,
-            SlotState::Reading as u8,
-            Ordering::AcqRel,
-            Ordering::Acquire,
-        ) {
-            Ok(_) => {
-                // We have exclusive access to read the slot
-
-                // Safety check in debug mode
-                debug_assert_eq!(
-                    slot.state.load(Ordering::Acquire),
-                    SlotState::Reading as u8,
-                    "State should be Reading after successful CAS"
-                );
-
-                let size = slot.size.load(Ordering::Acquire) as usize;
-
-                // SAFETY: Exclusive access guaranteed by successful CAS transition from Ready to Reading.
-                // - The CAS operation successfully transitioned from Ready to Reading, ensuring only
-                //   this thread can proceed with reading. Other threads would fail the CAS
-                // - The writer completed and marked the slot Ready, all writes are finalized
-                // - The state machine enforces no other thread accesses during Reading state
-                // - Memory ordering with Acquire ensures we observe all writes from Writing phase
-                let (request_id, data) = unsafe {
-                    let request_id_bytes = *slot.request_id.get();
-                    let uuid = Uuid::from_bytes(request_id_bytes);
-                    let request_id = match RequestId::try_new(uuid) {
-                        Ok(id) => id,
-                        Err(_) => {
-                            // Invalid UUID in slot — mark empty and skip
-                            slot.state.store(SlotState::Empty as u8, Ordering::Release);
-                            self.read_position.store(read_pos + 1, Ordering::Relaxed);
-                            return None;
-                        }
-                    };
+        let (request_id_bytes, frame, mut data) = self.take(slot, read_pos)?;
+        debug_assert_eq!(frame.sequence, 0, "An entry starts with its first frame");
+
+        // Later frames were published before the first one
+        let mut frames = 1;
+        let mut continues = frame.continues;
+        while continues {
+            let position = read_pos + frames;
+            let Some((_, frame, chunk)) = self.take(self.slot_at(position), position) else {
+                break;
+            };
+            debug_assert_eq!(u64::from(frame.sequence), frames, "Frames arrive in order");
+            data.extend_from_slice(&chunk);
+            continues = frame.continues;
+            frames += 1;
+        }
 
-                    let data_ref = &*slot.data.get();
-                    let data = data_ref[..size].to_vec();
+        // Advance read position
+        self.read_position
+            .store(read_pos + frames, Ordering::Relaxed);
 
-                    (request_id, data)
-                };
+        let uuid = Uuid::from_bytes(request_id_bytes);
+        // Invalid UUID in slot — skip the entry
+        let request_id = RequestId::try_new(uuid).ok()?;
 
-                // Mark slot as empty for reuse
-                slot.state.store(SlotState::Empty as u8, Ordering::Release);
+        // Increment successful reads counter
+        self.successful_reads.fetch_add(1, Ordering::Relaxed);
 
-                // Advance read position
-                self.read_position.store(read_pos + 1, Ordering::Relaxed);
+        Some((request_id, data))
+    }
 
-                // Increment successful reads counter
-                self.successful_reads.fetch_add(1, Ordering::Relaxed);
+    /// Copy one Ready slot out and free it for the writer of the next lap
+    fn take(&self, slot: &Slot, position: u64) -> Option<([u8; UUID_SIZE_BYTES], Frame, Vec<u8>)> {
+        // Try to transition to Reading state
+        slot.state
+            .compare_exchange(
+                SlotState::Ready as u8,
+                SlotState::Reading as u8,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .ok()?;
+
+        // Safety check in debug mode
+        debug_assert_eq!(
+            slot.state.load(Ordering::Acquire),
+            SlotState::Reading as u8,
+            "State should be Reading after successful CAS"
+        );
+
+        let size = slot.size.load(Ordering::Acquire) as usize;
+
+        // SAFETY: Exclusive access guaranteed by successful CAS transition from Ready to Reading.
+        // - The CAS operation successfully transitioned from Ready to Reading, ensuring only
+        //   this thread can proceed with reading. Other threads would fail the CAS
+        // - The writer completed and marked the slot Ready, all writes are finalized
+        // - The state machine enforces no other thread accesses during Reading state
+        // - Memory ordering with Acquire ensures we observe all writes from Writing phase
+        let taken = unsafe {
+            let data_ref = &*slot.data.get();
+            (
+                *slot.request_id.get(),
+                *slot.frame.get(),
+                data_ref[..size].to_vec(),
+            )
+        };
 
-                Some((request_id, data))
-            }
-            Err(_) => {
-                // Someone else is reading or slot became unavailable
-                None
-            }
-        }
-    }
+        // Mark slot as empty, then free it for the next lap's writer
+        slot.state.store(SlotState::Empty as u8, Ordering::Release);
+        slot.position
+            .store(position + self.slot_count as u64, Ordering::Release);
 
-    /// Get the current overflow count
-    ///
-    /// Returns the number of write attempts that failed due to unavailable slots.
-    /// This provides visibility into backpressure and helps with capacity planning.
-    pub fn overflow_count(&self) -> u64 {
-        self.overflow_count.load(Ordering::Relaxed)
+        Some(taken)
     }
 }
 
@@ -338,6 +529,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(1024 * 1024).expect("valid size"), // 1MB
             slot_size: SlotSize::try_new(1024).expect("valid size"),            // 1KB
+            ..Default::default()
         };
 
         let buffer = RingBuffer::new(&config);
@@ -355,6 +547,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(1024 * 1024).expect("valid size"),
             slot_size: SlotSize::try_new(1024).expect("valid size"),
+            ..Default::default()
         };
 
         let buffer = RingBuffer::new(&config);
@@ -379,6 +572,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(256).expect("valid size"), // Very small buffer
             slot_size: SlotSize::try_new(64).expect("valid size"),
+            ..Default::default()
         };
 
         let buffer = RingBuffer::new(&config);
@@ -400,24 +594,74 @@ mod tests {
     }
 
     #[test]
-    fn test_data_truncation() {
+    fn test_large_data_is_framed_across_slots() {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(1024).expect("valid size"),
             slot_size: SlotSize::try_new(64).expect("valid size"), // Small slots for testing
+            ..Default::default()
         };
 
         let buffer = RingBuffer::new(&config);
         let request_id = RequestId::new();
-        let large_data = vec![42u8; 128]; // Larger than slot size
+        let large_data: Vec<u8> = (0..150u8).collect(); // Three slots' worth
+        let small_id = RequestId::new();
 
-        // Write should succeed (data will be truncated)
         assert!(buffer.write(request_id, &large_data).is_ok());
+        assert!(buffer.write(small_id, b"after").is_ok());
 
-        // Read should return truncated data
+        // Read should return the whole entry, then the next one
         let (read_id, read_data) = buffer.read().expect("Should read event");
-        assert_eq!(read_id.as_ref(), request_id.as_ref());
-        assert_eq!(read_data.len(), 64); // Truncated to slot size
-        assert_eq!(&read_data[..], &large_data[..64]);
+        assert_eq!(read_id, request_id);
+        assert_eq!(read_data, large_data);
+        assert_eq!(buffer.read(), Some((small_id, b"after".to_vec())));
+
+        let stats = buffer.stats();
+        assert_eq!(stats.total_writes, 2);
+        assert_eq!(stats.total_reads, 2);
+        assert_eq!(stats.truncated_events, 0);
+    }
+
+    #[test]
+    fn test_framed_write_fails_whole_when_slots_are_busy() {
+        let config = RingBufferConfig {
+            buffer_size: BufferSize::try_new(256).expect("valid size"),
+            slot_size: SlotSize::try_new(64).expect("valid size"), // 4 slots
+            ..Default::default()
+        };
+
+        let buffer = RingBuffer::new(&config);
+        for i in 0..2 {
+            buffer
+                .write(RequestId::new(), format!("event {i}").as_bytes())
+                .unwrap();
+        }
+
+        // Needs three slots but only two are free
+        assert!(buffer.write(RequestId::new(), &[7; 150]).is_err());
+        assert!(buffer.write(RequestId::new(), &[8; 100]).is_ok());
+
+        assert!(buffer.read().is_some());
+        assert!(buffer.read().is_some());
+        assert_eq!(buffer.read().map(|(_, data)| data), Some(vec![8; 100]));
+        assert!(buffer.read().is_none());
+    }
+
+    #[test]
+    fn test_data_larger_than_the_shard_is_truncated() {
+        let config = RingBufferConfig {
+            buffer_size: BufferSize::try_new(256).expect("valid size"),
+            slot_size: SlotSize::try_new(64).expect("valid size"), // 4 slots
+            ..Default::default()
+        };
+
+        let buffer = RingBuffer::new(&config);
+        let large_data = vec![42u8; 300];
+
+        assert!(buffer.write(RequestId::new(), &large_data).is_ok());
+
+        let (_, read_data) = buffer.read().expect("Should read event");
+        assert_eq!(read_data, &large_data[..256]); // Truncated to the shard
+        assert_eq!(buffer.stats().truncated_events, 1);
     }
 
     #[test]
@@ -428,6 +672,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(1024 * 1024).expect("valid size"),
             slot_size: SlotSize::try_new(1024).expect("valid size"),
+            ..Default::default()
         };
 
         let buffer = Arc::new(RingBuffer::new(&config));
@@ -466,11 +711,93 @@ mod tests {
         assert!(buffer.read().is_none());
     }
 
+    fn sharded_config(shards: usize) -> RingBufferConfig {
+        RingBufferConfig {
+            buffer_size: BufferSize::try_new(64 * 1024).expect("valid size"),
+            slot_size: SlotSize::try_new(1024).expect("valid size"),
+            shards: ShardCount::try_new(shards).expect("valid shard count"),
+        }
+    }
+
+    #[test]
+    fn test_request_entries_stay_in_order_within_their_shard() {
+        let buffer = RingBuffer::new(&sharded_config(4));
+        assert_eq!(buffer.shard_count(), 4);
+
+        let requests: Vec<RequestId> = (0..8).map(|_| RequestId::new()).collect();
+        for i in 0..3 {
+            for request_id in &requests {
+                buffer
+                    .write(*request_id, format!("{i}").as_bytes())
+                    .unwrap();
+            }
+        }
+
+        for shard in 0..buffer.shard_count() {
+            let mut seen: std::collections::HashMap<RequestId, Vec<u8>> =
+                std::collections::HashMap::new();
+            while let Some((request_id, data)) = buffer.read_shard(shard) {
+                assert_eq!(buffer.shard_of(request_id), shard);
+                seen.entry(request_id).or_default().extend(data);
+            }
+            for entries in seen.values() {
+                assert_eq!(entries, b"012");
+            }
+        }
+        assert_eq!(buffer.stats().total_reads, 24);
+    }
+
+    #[test]
+    fn test_shards_split_capacity_and_sum_stats() {
+        let buffer = RingBuffer::new(&sharded_config(4));
+        let request_id = RequestId::new();
+
+        // 64 slots over 4 shards leaves 16 for each request's shard
+        let written = (0..20)
+            .filter(|_| buffer.write(request_id, b"event").is_ok())
+            .count();
+        assert_eq!(written, 16);
+
+        let stats = buffer.stats();
+        assert_eq!(stats.total_writes, 16);
+        assert_eq!(*stats.dropped_events.as_ref(), 4);
+        assert_eq!(std::iter::from_fn(|| buffer.read()).count(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_readable_wakes_parked_consumer() {
+        let buffer = std::sync::Arc::new(RingBuffer::new(&RingBufferConfig::default()));
+        assert!(buffer.read().is_none());
+
+        let writer = std::sync::Arc::clone(&buffer);
+        let write = tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            writer.write(RequestId::new(), b"event").unwrap();
+        });
+
+        tokio::time::timeout(std::time::Duration::from_secs(1), buffer.readable(0))
+            .await
+            .expect("a write should wake the consumer");
+        write.await.unwrap();
+        assert!(buffer.read().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_write_before_wait_is_not_missed() {
+        let buffer = RingBuffer::new(&RingBufferConfig::default());
+        buffer.write(RequestId::new(), b"event").unwrap();
+
+        tokio::time::timeout(std::time::Duration::from_millis(100), buffer.readable(0))
+            .await
+            .expect("the notifier should keep a permit for the earlier write");
+    }
+
     #[test]
     fn test_fifo_ordering() {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(1024 * 1024).expect("valid size"),
             slot_size: SlotSize::try_new(1024).expect("valid size"),
+            ..Default::default()
         };
 
         let buffer = RingBuffer::new(&config);
@@ -498,6 +825,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(256).expect("valid size"), // Very small buffer
             slot_size: SlotSize::try_new(64).expect("valid size"),
+            ..Default::default()
         };
 
         let buffer = RingBuffer::new(&config);
@@ -530,6 +858,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(1024 * 1024).expect("valid size"), // Larger buffer
             slot_size: SlotSize::try_new(256).expect("valid size"),
+            ..Default::default()
         };
 
         let buffer = RingBuffer::new(&config);
@@ -16,6 +16,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(1024 * 1024).expect("valid size"), // 1MB
             slot_size: SlotSize::try_new(1024).expect("valid size"),            // 1KB
+            ..Default::default()
         };
 
         let request_id = RequestId::new();
@@ -52,6 +53,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(4 * 1024 * 1024).expect("valid size"), // 4MB
             slot_size: SlotSize::try_new(1024).expect("valid size"),                // 1KB
+            ..Default::default()
         };
 
         let thread_count = 4;
@@ -103,6 +105,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(1024).expect("valid size"),
             slot_size: SlotSize::try_new(256).expect("valid size"),
+            ..Default::default()
         };
 
         // Test that the ring buffer works correctly
@@ -123,6 +126,7 @@ mod tests {
         let config = RingBufferConfig {
             buffer_size: BufferSize::try_new(4 * 1024 * 1024).expect("valid size"), // 4MB
             slot_size: SlotSize::try_new(1024).expect("valid size"),                // 1KB
+            ..Default::default()
         };
 
         let ring_buffer = Arc::new(RingBuffer::new(&config));
@@ -17,6 +17,7 @@ prop_compose! {
         RingBufferConfig {
             buffer_size: BufferSize::try_new(buffer_size).unwrap(),
             slot_size: SlotSize::try_new(slot_size).unwrap(),
+            ..Default::default()
         }
     }
 }
@@ -30,6 +31,18 @@ prop_compose! {
     }
 }
 
+/// Slots in the single shard of `config` after power-of-2 adjustment
+fn ring_buffer_capacity(config: &RingBufferConfig) -> usize {
+    let calculated = config.buffer_size.as_ref() / config.slot_size.as_ref();
+    let next_pow2 = calculated.next_power_of_two();
+    if next_pow2 > calculated {
+        next_pow2 / 2
+    } else {
+        next_pow2
+    }
+    .max(1)
+}
+
 proptest! {
     #[test]
     fn prop_ring_buffer_never_loses_data_under_capacity(
@@ -44,15 +57,7 @@ proptest! {
         let mut request_ids = Vec::new();
 
         // Calculate actual slot count after power-of-2 adjustment
-        let actual_slot_count = {
-            let calculated = config.buffer_size.as_ref() / config.slot_size.as_ref();
-            let next_pow2 = calculated.next_power_of_two();
-            if next_pow2 > calculated {
-                next_pow2 / 2
-            } else {
-                next_pow2
-            }.max(1)
-        };
+        let actual_slot_count = ring_buffer_capacity(&config);
 
         // Write all data (up to slot capacity)
         for (i, data) in data_sets.iter().enumerate() {
@@ -131,7 +136,7 @@ proptest! {
     }
 
     #[test]
-    fn prop_data_too_large_is_truncated(
+    fn prop_data_larger_than_a_slot_is_reassembled(
         config in buffer_config_strategy(),
         excess in 1usize..=1000usize,
     ) {
@@ -139,19 +144,22 @@ proptest! {
         let request_id = RequestId::new();
 
         // Create data that exceeds slot size
-        let max_data_size = config.slot_size.as_ref();
-        let oversized_data = vec![42u8; max_data_size + excess];
+        let slot_size = *config.slot_size.as_ref();
+        let oversized_data: Vec<u8> = (0..slot_size + excess).map(|i| i as u8).collect();
 
-        // Write should succeed but data will be truncated
+        // Write should succeed; data beyond the whole buffer is truncated
         prop_assert!(ring_buffer.write(request_id, &oversized_data).is_ok());
+        let capacity = ring_buffer_capacity(&config) * slot_size;
+        let expected = &oversized_data[..oversized_data.len().min(capacity)];
 
-        // Read back and verify truncation
+        // Read back the whole entry
         if let Some((read_id, read_data)) = ring_buffer.read() {
             prop_assert_eq!(read_id, request_id);
-            // Data should be truncated to slot size
-            prop_assert_eq!(read_data.len(), *max_data_size);
-            // Check truncated data matches
-            prop_assert_eq!(&read_data[..], &oversized_data[..*max_data_size]);
+            prop_assert_eq!(&read_data[..], expected);
+            prop_assert_eq!(
+                ring_buffer.stats().truncated_events,
+                u64::from(expected.len() < oversized_data.len())
+            );
         } else {
             panic!("Expected to read data back");
         }
@@ -242,6 +250,7 @@ fn test_ring_buffer_memory_layout() {
     let config = RingBufferConfig {
         buffer_size: BufferSize::try_new(BYTES_1KB).unwrap(),
         slot_size: SlotSize::try_new(SLOT_SIZE_SMALL).unwrap(),
+        ..Default::default()
     };
 
     let ring_buffer = RingBuffer::new(&config);
@@ -293,6 +302,7 @@ fn test_ring_buffer_concurrent_stress() {
     let config = RingBufferConfig {
         buffer_size: BufferSize::try_new(BYTES_1MB).unwrap(),
         slot_size: SlotSize::try_new(BYTES_1KB).unwrap(),
+        ..Default::default()
     };
 
     let ring_buffer = Arc::new(RingBuffer::new(&config));
@@ -363,3 +373,70 @@ fn test_ring_buffer_concurrent_stress() {
     assert!(total_reads > 0);
     assert!(stats.total_writes >= total_reads as u64);
 }
+
+#[test]
+fn test_framed_entries_stay_intact_under_concurrent_writers() {
+    // Entries spanning several slots must never interleave with other writers
+    let config = RingBufferConfig {
+        buffer_size: BufferSize::try_new(BYTES_1MB).unwrap(),
+        slot_size: SlotSize::try_new(SLOT_SIZE_SMALL).unwrap(),
+        shards: ShardCount::try_new(1).unwrap(),
+    };
+
+    let ring_buffer = Arc::new(RingBuffer::new(&config));
+    let writers: Vec<_> = (0..TEST_THREAD_COUNT)
+        .map(|thread_id| {
+            let rb = Arc::clone(&ring_buffer);
+            thread::spawn(move || {
+                let mut written = 0usize;
+                for i in 0..TEST_ITERATIONS_LARGE {
+                    // Sizes from one to four slots, filled with a checkable pattern
+                    let len = 1 + (i * 37) % (SLOT_SIZE_SMALL * 4);
+                    let data: Vec<u8> = (0..len).map(|b| (thread_id + b) as u8).collect();
+                    if rb.write(RequestId::new(), &data).is_ok() {
+                        written += 1;
+                    }
+                    thread::yield_now();
+                }
+                written
+            })
+        })
+        .collect();
+
+    let shutdown = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let reader = {
+        let rb = Arc::clone(&ring_buffer);
+        let shutdown = Arc::clone(&shutdown);
+        thread::spawn(move || {
+            let mut read = 0usize;
+            let check = |data: &[u8]| {
+                let start = data[0] as usize;
+                assert!(data
+                    .iter()
+                    .enumerate()
+                    .all(|(b, &byte)| byte == (start + b) as u8));
+            };
+            while !shutdown.load(std::sync::atomic::Ordering::Acquire) {
+                match rb.read() {
+                    Some((_, data)) => {
+                        check(&data);
+                        read += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+            while let Some((_, data)) = rb.read() {
+                check(&data);
+                read += 1;
+            }
+            read
+        })
+    };
+
+    let written: usize = writers.into_iter().map(|h| h.join().unwrap()).sum();
+    shutdown.store(true, std::sync::atomic::Ordering::Release);
+    let read = reader.join().unwrap();
+
+    assert_eq!(read, written);
+    assert_eq!(ring_buffer.stats().truncated_events, 0);
+}
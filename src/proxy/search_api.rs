@@ -0,0 +1,127 @@
+//! Full-text search API
+//!
+//! `GET /api/v1/search?q=...` searches captured prompts and responses and
+//! returns highlighted snippets that link back to their session and request.
+
+use crate::domain::session::ApplicationId;
+use crate::domain::types::ModelId;
+use crate::infrastructure::eventcore::projections::{
+    PageSize, SearchIndex, SearchQuery, SearchText,
+};
+use crate::proxy::session_api::{bad_request, internal_error, parse_time, InvalidQueryParameter};
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::error;
+
+/// Build a search from raw query-string pairs
+///
+/// `q` is required and uses web search syntax (`"exact phrase"`, `or`,
+/// `-excluded`). Optional filters are `application_id`, `model`, `from` and
+/// `to` (RFC 3339, bounding when the request was received) and `limit`.
+pub fn parse_search_query(
+    params: &[(String, String)],
+) -> Result<SearchQuery, InvalidQueryParameter> {
+    let mut text = None;
+    let mut application_id = None;
+    let mut model_id = None;
+    let mut received_from = None;
+    let mut received_to = None;
+    let mut limit = PageSize::default();
+
+    for (name, value) in params {
+        let invalid = |reason: &dyn std::fmt::Display| InvalidQueryParameter::new(name, reason);
+
+        match name.as_str() {
+            "q" => text = Some(SearchText::try_new(value.clone()).map_err(|e| invalid(&e))?),
+            "application_id" => {
+                application_id =
+                    Some(ApplicationId::try_new(value.clone()).map_err(|e| invalid(&e))?)
+            }
+            "model" => model_id = Some(ModelId::try_new(value.clone()).map_err(|e| invalid(&e))?),
+            "from" => received_from = Some(parse_time(value).map_err(|e| invalid(&e))?),
+            "to" => received_to = Some(parse_time(value).map_err(|e| invalid(&e))?),
+            "limit" => {
+                let value: u32 = value.parse().map_err(|e| invalid(&e))?;
+                limit = PageSize::try_new(value).map_err(|e| invalid(&e))?;
+            }
+            _ => return Err(invalid(&"unknown parameter")),
+        }
+    }
+
+    let text = text.ok_or_else(|| InvalidQueryParameter::new("q", "is required"))?;
+    if let (Some(from), Some(to)) = (received_from, received_to) {
+        if from >= to {
+            return Err(InvalidQueryParameter::new("from", "must be before `to`"));
+        }
+    }
+
+    Ok(SearchQuery {
+        application_id,
+        model_id,
+        received_from,
+        received_to,
+        limit,
+        ..SearchQuery::new(text)
+    })
+}
+
+/// Respond to `GET /api/v1/search`
+pub async fn search(index: &dyn SearchIndex, params: &[(String, String)]) -> Response {
+    let query = match parse_search_query(params) {
+        Ok(query) => query,
+        Err(e) => return bad_request(&e),
+    };
+
+    match index.search(&query).await {
+        Ok(results) => Json(results).into_response(),
+        Err(e) => {
+            error!("Search failed: {e}");
+            internal_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_phrase_and_filters() {
+        let query = parse_search_query(&params(&[
+            ("q", "\"refund policy\" -shipping"),
+            ("application_id", "support-bot"),
+            ("model", "gpt-4o"),
+            ("from", "2026-10-01T00:00:00Z"),
+            ("limit", "5"),
+        ]))
+        .unwrap();
+
+        assert_eq!(query.text.as_ref(), "\"refund policy\" -shipping");
+        assert_eq!(query.application_id.unwrap().as_ref(), "support-bot");
+        assert_eq!(query.model_id.unwrap().as_ref(), "gpt-4o");
+        assert!(query.received_from.is_some());
+        assert_eq!(query.received_to, None);
+        assert_eq!(*query.limit.as_ref(), 5);
+    }
+
+    #[test]
+    fn requires_search_text_and_known_parameters() {
+        for pairs in [
+            vec![],
+            vec![("q", "  ")],
+            vec![("q", "refund"), ("sort", "rank")],
+            vec![("q", "refund"), ("limit", "1000")],
+        ] {
+            assert!(parse_search_query(&params(&pairs)).is_err(), "{pairs:?}");
+        }
+    }
+}
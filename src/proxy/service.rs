@@ -27,68 +27,237 @@
 //! - **Hot Path**: Handles request/response streaming with minimal latency
 //! - **Ring Buffer**: Lock-free buffer for passing events to audit path
 //! - **Audit Processor**: Background task consuming events from ring buffer
+//! - **Metrics**: Hot path, ring buffer and audit counters served at `/metrics`
+//! - **Tracing**: Optional spans continuing the client's W3C trace, exported over OTLP
+//! - **Budgets**: Optional hard spending limits that refuse an application's requests
 //! - **Middleware Stack**: Tower middleware for auth, logging, etc.
 
+use crate::domain::pricing::PricingCatalog;
+use crate::infrastructure::dead_letters::DeadLetterStore;
+use crate::infrastructure::eventcore::projections::{
+    BudgetLedger, LiveView, SearchIndex, SessionDirectory, UsageRollup,
+};
+use crate::infrastructure::eventcore::service::EventCoreService;
+use crate::providers::bedrock::provider::PathPrefix;
+use crate::providers::config::{ProviderConfig, ProviderKind};
 use crate::providers::ProviderRegistry;
+use crate::proxy::audit_path::{AuditBatching, AuditProcessorHandle};
 use crate::proxy::hot_path::StreamingHotPathService;
+use crate::proxy::metrics::{
+    render_prometheus, AuditMetrics, ByteDirection, HotPathMetrics, MeteredBody, MetricsSnapshot,
+    ProviderSlot, PROMETHEUS_CONTENT_TYPE,
+};
+use crate::proxy::otlp::{
+    attributes, gen_ai_system, span_names, SpanKind, SpanStatus, TracedBody, Tracer,
+};
 use crate::proxy::provider_router::ProviderRouter;
+use crate::proxy::session_headers::take_session_context;
+use crate::proxy::upstream_client::build_upstream_client;
 use crate::proxy::{
-    audit_path::AuditPathProcessor, middleware_stack::ProxyMiddlewareStack,
-    ring_buffer::RingBuffer, types::*, url_resolver::UrlResolver,
+    audit_path::{AuditPathConfig, AuditPathProcessor},
+    middleware_stack::ProxyMiddlewareStack,
+    ring_buffer::RingBuffer,
+    spill_log::SpillLog,
+    types::*,
+    url_resolver::UrlResolver,
 };
+use crate::proxy::{dead_letter_api, search_api, session_api, usage_api};
 use axum::{
     body::Body,
-    extract::{Request, State},
+    extract::{Path, Query, Request, State},
     response::{IntoResponse, Response},
+    Json,
 };
 use std::sync::Arc;
-use tokio::sync::mpsc;
-
-/// Environment variable name for Bedrock endpoint override (primarily for testing)
-const BEDROCK_ENDPOINT_OVERRIDE_ENV: &str = "BEDROCK_ENDPOINT_OVERRIDE";
+use std::time::Instant;
 
 /// Main proxy service combining hot and audit paths
 pub struct ProxyService {
     hot_path: StreamingHotPathService,
     ring_buffer: Arc<RingBuffer>,
-    audit_shutdown_tx: Option<mpsc::Sender<()>>,
+    spill: Option<Arc<SpillLog>>,
+    audit_handle: Option<AuditProcessorHandle>,
+    audit_batching: AuditBatching,
+    metrics: Arc<HotPathMetrics>,
+    audit_metrics: Arc<AuditMetrics>,
+    tracer: Option<Tracer>,
+    pricing: Option<Arc<PricingCatalog>>,
+    budget_ledger: Option<Arc<BudgetLedger>>,
+    event_store: Option<Arc<EventCoreService>>,
     provider_router: Arc<ProviderRouter>,
+    /// Prefixes of Anthropic instances, whose clients send the provider key in `X-API-Key`
+    anthropic_prefixes: Vec<PathPrefix>,
+    live_view: Option<Arc<LiveView>>,
+    session_directory: Option<Arc<dyn SessionDirectory>>,
+    search_index: Option<Arc<dyn SearchIndex>>,
+    usage_rollup: Option<Arc<dyn UsageRollup>>,
+    dead_letters: Option<Arc<dyn DeadLetterStore>>,
 }
 
 impl ProxyService {
     /// Create a new proxy service
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.upstream_tls` names certificate files that cannot be
+    /// loaded or `config.providers` is invalid; use [`ProxyService::try_new`]
+    /// to handle those cases.
     pub fn new(config: ProxyConfig) -> Self {
-        let ring_buffer = Arc::new(RingBuffer::new(&config.ring_buffer));
-        let hot_path = StreamingHotPathService::new(config.clone(), ring_buffer.clone());
-
-        // Initialize provider registry with configured providers
-        let mut registry = ProviderRegistry::new();
+        Self::try_new(config).expect("proxy configuration should be valid")
+    }
 
-        // Register Bedrock provider (MVP provider)
-        use crate::providers::bedrock::provider::BedrockProvider;
-        use crate::providers::bedrock::types::AwsRegion;
+    /// Create a new proxy service, failing on unusable upstream TLS or provider settings
+    pub fn try_new(config: ProxyConfig) -> ProxyResult<Self> {
+        let ring_buffer = Arc::new(RingBuffer::new(&config.ring_buffer));
+        let client = build_upstream_client(&config.upstream_tls)?;
+        let hot_path =
+            StreamingHotPathService::new(config.clone(), ring_buffer.clone(), client.clone());
 
-        // Check for endpoint override (for testing)
-        if let Ok(endpoint_override) = std::env::var(BEDROCK_ENDPOINT_OVERRIDE_ENV) {
-            let bedrock_provider = Arc::new(BedrockProvider::with_base_url(endpoint_override));
-            registry.register(bedrock_provider);
+        let providers = if config.providers.is_empty() {
+            ProviderConfig::defaults(config.bedrock_region.clone())
         } else {
-            let bedrock_region = config.bedrock_region.clone().unwrap_or_else(|| {
-                AwsRegion::try_new("us-east-1".to_string())
-                    .expect("Failed to create AWS region 'us-east-1'")
-            });
-            let bedrock_provider = Arc::new(BedrockProvider::new(bedrock_region));
-            registry.register(bedrock_provider);
-        }
+            config.providers.clone()
+        };
+        let registry = ProviderRegistry::from_config(&providers)?;
+        let anthropic_prefixes = providers
+            .iter()
+            .filter(|provider| provider.kind == ProviderKind::Anthropic)
+            .map(ProviderConfig::resolved_path_prefix)
+            .collect();
+
+        let provider_router = Arc::new(
+            ProviderRouter::new(Arc::new(registry), client)
+                .with_default_timeout(config.request_timeout),
+        );
 
-        // Create provider router
-        let provider_router = Arc::new(ProviderRouter::new(Arc::new(registry)));
+        let audit_metrics = Arc::new(AuditMetrics::new(ring_buffer.shard_count()));
 
-        Self {
+        Ok(Self {
             hot_path,
             ring_buffer,
-            audit_shutdown_tx: None,
+            spill: None,
+            audit_handle: None,
+            audit_batching: AuditBatching::default(),
+            metrics: Arc::new(HotPathMetrics::new()),
+            audit_metrics,
+            tracer: None,
+            pricing: None,
+            budget_ledger: None,
+            event_store: None,
             provider_router,
+            anthropic_prefixes,
+            live_view: None,
+            session_directory: None,
+            search_index: None,
+            usage_rollup: None,
+            dead_letters: None,
+        })
+    }
+
+    /// Persist audit events through the given EventCore service
+    pub fn with_event_store(mut self, event_store: Arc<EventCoreService>) -> Self {
+        self.event_store = Some(event_store);
+        self
+    }
+
+    /// Group audit events into event store writes as configured
+    pub fn with_audit_batching(mut self, batching: AuditBatching) -> Self {
+        self.audit_batching = batching;
+        self
+    }
+
+    /// Spill audit events the ring buffer cannot take to disk
+    ///
+    /// Opens the log in `config.directory`, recovering events a previous run
+    /// left unpersisted; the audit processors persist them first.
+    pub fn with_spill(mut self, config: &SpillConfig) -> ProxyResult<Self> {
+        let spill = Arc::new(SpillLog::open(config, self.ring_buffer.shard_count())?);
+        self.hot_path = self.hot_path.with_spill_log(Arc::clone(&spill));
+        self.spill = Some(spill);
+        Ok(self)
+    }
+
+    /// Record spans for proxied requests, upstream calls and audit persistence
+    ///
+    /// Requests continue the trace named by their `traceparent` header, and
+    /// the `traceparent` forwarded upstream names the proxy's span.
+    pub fn with_tracer(mut self, tracer: Tracer) -> Self {
+        self.hot_path = self.hot_path.with_tracer(tracer.clone());
+        self.provider_router =
+            Arc::new((*self.provider_router).clone().with_tracer(tracer.clone()));
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// Record the cost of every response whose model has a price in `catalog`
+    pub fn with_pricing(mut self, catalog: Arc<PricingCatalog>) -> Self {
+        self.pricing = Some(catalog);
+        self
+    }
+
+    /// Refuse new requests of applications that spent a hard budget limit
+    ///
+    /// The application and environment are those the request's key is bound
+    /// to in `proxy.bound_api_keys`. While budgets are set, requests made
+    /// with unbound keys are refused.
+    pub fn with_budget_ledger(mut self, ledger: Arc<BudgetLedger>) -> Self {
+        self.budget_ledger = Some(ledger);
+        self
+    }
+
+    /// Serve the live view at `/api/v1/live`
+    pub fn with_live_view(mut self, live_view: Arc<LiveView>) -> Self {
+        self.live_view = Some(live_view);
+        self
+    }
+
+    /// Serve the session listing at `/api/v1/sessions`
+    ///
+    /// Single sessions at `/api/v1/sessions/{session_id}` are read from the
+    /// event store and are served whenever one is configured.
+    pub fn with_session_directory(mut self, directory: Arc<dyn SessionDirectory>) -> Self {
+        self.session_directory = Some(directory);
+        self
+    }
+
+    /// Serve full-text search at `/api/v1/search`
+    pub fn with_search_index(mut self, index: Arc<dyn SearchIndex>) -> Self {
+        self.search_index = Some(index);
+        self
+    }
+
+    /// Serve the hourly usage rollup at `/api/v1/usage`
+    pub fn with_usage_rollup(mut self, rollup: Arc<dyn UsageRollup>) -> Self {
+        self.usage_rollup = Some(rollup);
+        self
+    }
+
+    /// Keep audit events that fail processing in `store`
+    ///
+    /// The store is also served at `/api/v1/dead-letters`, where dead
+    /// letters can be inspected, replayed through the event store or purged
+    /// by callers holding an admin API key.
+    pub fn with_dead_letter_store(mut self, store: Arc<dyn DeadLetterStore>) -> Self {
+        self.dead_letters = Some(store);
+        self
+    }
+
+    /// Refuse a request whose key is bound to `binding` if budgets do not allow it
+    ///
+    /// While budgets are configured, requests of keys without a binding are
+    /// refused, since their spend could not be attributed to an application.
+    fn check_budget(&self, binding: Option<&ApplicationBinding>) -> Result<(), ProxyError> {
+        let Some(ledger) = self.budget_ledger.as_ref() else {
+            return Ok(());
+        };
+        let binding = binding.ok_or(ProxyError::UnboundApiKey)?;
+        match ledger.rejection(
+            &binding.application_id,
+            binding.environment.as_ref(),
+            chrono::Utc::now(),
+        ) {
+            Some(rejection) => Err(ProxyError::BudgetExceeded(Box::new(rejection))),
+            None => Ok(()),
         }
     }
 
@@ -97,25 +266,70 @@ impl ProxyService {
         Arc::clone(&self.ring_buffer)
     }
 
+    /// The spill log, when one is configured
+    pub fn spill_log(&self) -> Option<Arc<SpillLog>> {
+        self.spill.clone()
+    }
+
+    /// Current hot path, ring buffer, spill log and audit processor counters
+    pub fn metrics(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            hot_path: self.metrics.snapshot(),
+            ring_buffer: self.ring_buffer.stats(),
+            ring_buffer_shards: self.ring_buffer.shard_count(),
+            spill: self.spill.as_ref().map(|spill| spill.stats()),
+            audit: self.audit_metrics.totals(),
+        }
+    }
+
     /// Start the audit path processor
     pub fn start_audit_processor(&mut self) {
-        let (processor, shutdown_tx) = AuditPathProcessor::new(Arc::clone(&self.ring_buffer));
-
-        // Start the processor in a background task
-        tokio::spawn(async move {
-            processor.run().await;
-        });
+        self.audit_handle = Some(self.spawn_audit_processor());
+    }
 
-        self.audit_shutdown_tx = Some(shutdown_tx);
+    fn spawn_audit_processor(&self) -> AuditProcessorHandle {
+        AuditPathProcessor::spawn_all(
+            &self.ring_buffer,
+            &AuditPathConfig {
+                event_store: self.event_store.clone(),
+                spill: self.spill.clone(),
+                dead_letters: self.dead_letters.clone(),
+                metrics: Some(Arc::clone(&self.audit_metrics)),
+                tracer: self.tracer.clone(),
+                pricing: self.pricing.clone(),
+                batching: self.audit_batching,
+            },
+        )
     }
 
     /// Create an Axum router for the proxy service with middleware
     pub fn into_router(mut self, auth_config: crate::proxy::AuthConfig) -> axum::Router {
         // Start the audit processor before creating the router
         self.start_audit_processor();
+        self.build_router(auth_config)
+    }
+
+    /// Create an Axum router and return the audit processor handle to the caller
+    ///
+    /// Used by servers that must flush the ring buffer on shutdown: stop
+    /// accepting requests first, then call [`AuditProcessorHandle::shutdown`].
+    pub fn into_router_with_audit_handle(
+        self,
+        auth_config: crate::proxy::AuthConfig,
+    ) -> (axum::Router, AuditProcessorHandle) {
+        let audit_handle = self.spawn_audit_processor();
+        (self.build_router(auth_config), audit_handle)
+    }
+
+    fn build_router(self, mut auth_config: crate::proxy::AuthConfig) -> axum::Router {
+        for prefix in &self.anthropic_prefixes {
+            if !auth_config.upstream_api_key_prefixes.contains(prefix) {
+                auth_config.upstream_api_key_prefixes.push(prefix.clone());
+            }
+        }
 
         // Create base router
-        let router = axum::Router::new()
+        let mut router = axum::Router::new()
             .route(
                 crate::proxy::headers::paths::HEALTH,
                 axum::routing::get(health_handler),
@@ -123,9 +337,65 @@ impl ProxyService {
             .route(
                 crate::proxy::headers::paths::METRICS,
                 axum::routing::get(metrics_handler),
-            )
-            .fallback(proxy_handler)
-            .with_state(Arc::new(self));
+            );
+
+        // Captured traffic and the dead letter store need an admin key
+        let mut admin = axum::Router::new();
+        if self.live_view.is_some() {
+            admin = admin.route(
+                crate::proxy::headers::paths::LIVE,
+                axum::routing::get(live_handler),
+            );
+        }
+        if self.session_directory.is_some() {
+            admin = admin.route(
+                crate::proxy::headers::paths::SESSIONS,
+                axum::routing::get(sessions_handler),
+            );
+        }
+        if self.event_store.is_some() {
+            admin = admin.route(
+                crate::proxy::headers::paths::SESSION,
+                axum::routing::get(session_handler),
+            );
+        }
+        if self.search_index.is_some() {
+            admin = admin.route(
+                crate::proxy::headers::paths::SEARCH,
+                axum::routing::get(search_handler),
+            );
+        }
+        if self.usage_rollup.is_some() {
+            admin = admin.route(
+                crate::proxy::headers::paths::USAGE,
+                axum::routing::get(usage_handler),
+            );
+        }
+        if self.dead_letters.is_some() {
+            admin = admin
+                .route(
+                    crate::proxy::headers::paths::DEAD_LETTERS,
+                    axum::routing::get(dead_letters_handler).delete(purge_dead_letters_handler),
+                )
+                .route(
+                    crate::proxy::headers::paths::DEAD_LETTERS_REPLAY,
+                    axum::routing::post(replay_dead_letters_handler),
+                )
+                .route(
+                    crate::proxy::headers::paths::DEAD_LETTER,
+                    axum::routing::get(dead_letter_handler).delete(remove_dead_letter_handler),
+                )
+                .route(
+                    crate::proxy::headers::paths::DEAD_LETTER_REPLAY,
+                    axum::routing::post(replay_dead_letter_handler),
+                );
+        }
+        if admin.has_routes() {
+            router = router.merge(admin.route_layer(axum::middleware::from_fn(
+                crate::proxy::middleware::admin_auth_middleware,
+            )));
+        }
+        let router = router.fallback(proxy_handler).with_state(Arc::new(self));
 
         // Apply middleware stack using the builder
         let middleware_stack = ProxyMiddlewareStack::new(auth_config);
@@ -136,42 +406,115 @@ impl ProxyService {
 /// Axum handler for proxying requests
 async fn proxy_handler(
     State(proxy): State<Arc<ProxyService>>,
-    request: Request<Body>,
+    mut request: Request<Body>,
 ) -> Result<Response, ProxyError> {
     // Generate request ID for correlation
     let request_id = RequestId::new();
 
-    // Check if this is a provider-routed request (URL-based routing)
-    let path = request.uri().path();
-    if path.starts_with("/bedrock/")
-        || path.starts_with("/openai/")
-        || path.starts_with("/anthropic/")
-    {
-        // Use provider-based routing
+    let started = Instant::now();
+
+    // Session headers are for Union Square only and must not reach the provider
+    let mut session = take_session_context(request.headers_mut());
+    // A bound key decides the application, whatever the headers claim
+    let binding = request.extensions().get::<ApplicationBinding>().cloned();
+    if let Some(binding) = &binding {
+        session.bind(binding);
+    }
+    let provider = proxy.provider_router.provider_id(request.uri().path());
+    let mut span = proxy.tracer.as_ref().map(|tracer| {
+        let mut span =
+            tracer.start_propagated(span_names::PROXY, SpanKind::Server, request.headers_mut());
+        span.set_attribute(attributes::HTTP_REQUEST_METHOD, request.method().as_str());
+        span.set_attribute(attributes::URL_PATH, request.uri().path());
+        span.set_attribute(attributes::REQUEST_ID, request_id.to_string());
+        if let Some(provider) = &provider {
+            span.set_attribute(attributes::GEN_AI_SYSTEM, gen_ai_system(provider.as_ref()));
+        }
+        span
+    });
+    let request = request.map(|body| {
+        Body::new(MeteredBody::new(
+            body,
+            Arc::clone(&proxy.metrics),
+            ByteDirection::Received,
+        ))
+    });
+
+    let result = if let Err(refusal) = proxy.check_budget(binding.as_ref()) {
+        Err(refusal)
+    } else if provider.is_some() {
+        // Use provider-based routing (URL-based routing)
         proxy
-            .provider_router
-            .route_request(request, request_id)
+            .hot_path
+            .forward_to_provider(request, &proxy.provider_router, request_id, session)
             .await
     } else {
         // Fall back to header-based routing for backward compatibility
-        let target_url = UrlResolver::extract_target_url(&request)?;
+        match UrlResolver::extract_target_url(&request) {
+            // Forward the request using streaming hot path
+            Ok(target_url) => {
+                proxy
+                    .hot_path
+                    .forward_request(request, target_url, request_id, session)
+                    .await
+            }
+            Err(e) => Err(e),
+        }
+    };
 
-        // Forward the request using streaming hot path
-        proxy
-            .hot_path
-            .forward_request(request, target_url, request_id)
-            .await
+    let slot = provider
+        .as_ref()
+        .map_or(ProviderSlot::PASSTHROUGH, ProviderSlot::for_provider);
+    let success = matches!(&result, Ok(response) if !response.status().is_server_error());
+    proxy.metrics.record(slot, started.elapsed(), success);
+
+    if let Some(span) = &mut span {
+        match &result {
+            Ok(response) => {
+                let status = response.status();
+                span.set_attribute(attributes::HTTP_RESPONSE_STATUS_CODE, status.as_u16());
+                if status.is_server_error() {
+                    span.set_status(SpanStatus::Error(status.to_string()));
+                }
+            }
+            Err(e) => span.set_status(SpanStatus::Error(e.to_string())),
+        }
     }
+
+    result.map(|response| {
+        response.map(|body| {
+            // The proxy span covers streaming the response to the client
+            Body::new(TracedBody::new(
+                MeteredBody::new(body, Arc::clone(&proxy.metrics), ByteDirection::Sent),
+                span,
+            ))
+        })
+    })
 }
 
 /// Error conversion for Axum responses using standardized format
 impl IntoResponse for ProxyError {
     fn into_response(self) -> Response {
-        use crate::proxy::error_response::ErrorResponseExt;
+        use crate::proxy::error_response::{ClientFacingError, ErrorResponseExt};
 
         let status = self.status_code();
-        let error_response = self.to_error_response();
-        error_response.into_response_with_status(status)
+        let mut response = self.to_error_response().into_response_with_status(status);
+        // A spent budget accepts requests again once its next period starts
+        if let ProxyError::BudgetExceeded(rejection) = &self {
+            let wait = (rejection.resets_at - chrono::Utc::now())
+                .num_seconds()
+                .max(0);
+            response
+                .headers_mut()
+                .insert(hyper::header::RETRY_AFTER, wait.into());
+        }
+        if matches!(
+            self,
+            ProxyError::BudgetExceeded(_) | ProxyError::UnboundApiKey
+        ) {
+            response.extensions_mut().insert(ClientFacingError);
+        }
+        response
     }
 }
 
@@ -180,7 +523,139 @@ async fn health_handler() -> &'static str {
     "OK"
 }
 
-/// Metrics handler - placeholder for now
-async fn metrics_handler() -> &'static str {
-    "metrics: placeholder"
+/// Live view handler; only routed when a live view is configured
+async fn live_handler(State(proxy): State<Arc<ProxyService>>) -> Response {
+    match &proxy.live_view {
+        Some(live_view) => Json(live_view.snapshot(chrono::Utc::now())).into_response(),
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Session listing handler; only routed when a session directory is configured
+async fn sessions_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    match &proxy.session_directory {
+        Some(directory) => session_api::list_sessions(directory.as_ref(), &params).await,
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Single session handler; only routed when an event store is configured
+async fn session_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Path(session_id): Path<String>,
+) -> Response {
+    match &proxy.event_store {
+        Some(event_store) => session_api::session_detail(event_store, &session_id).await,
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Search handler; only routed when a search index is configured
+async fn search_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    match &proxy.search_index {
+        Some(index) => search_api::search(index.as_ref(), &params).await,
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Usage handler; only routed when a usage rollup is configured
+async fn usage_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    match &proxy.usage_rollup {
+        Some(rollup) => usage_api::usage(rollup.as_ref(), &params).await,
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Dead-letter listing handler; only routed when a dead-letter store is configured
+async fn dead_letters_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    match &proxy.dead_letters {
+        Some(store) => dead_letter_api::list_dead_letters(store.as_ref(), &params).await,
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Single dead letter handler, with its payload
+async fn dead_letter_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Path(id): Path<String>,
+) -> Response {
+    match &proxy.dead_letters {
+        Some(store) => dead_letter_api::dead_letter(store.as_ref(), &id).await,
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Replays one dead letter through the event store
+async fn replay_dead_letter_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Path(id): Path<String>,
+) -> Response {
+    match &proxy.dead_letters {
+        Some(store) => {
+            dead_letter_api::replay_dead_letter(store.as_ref(), proxy.event_store.as_deref(), &id)
+                .await
+        }
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Replays the oldest dead letters through the event store
+async fn replay_dead_letters_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    match &proxy.dead_letters {
+        Some(store) => {
+            dead_letter_api::replay_dead_letters(
+                store.as_ref(),
+                proxy.event_store.as_deref(),
+                &params,
+            )
+            .await
+        }
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Removes one dead letter
+async fn remove_dead_letter_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Path(id): Path<String>,
+) -> Response {
+    match &proxy.dead_letters {
+        Some(store) => dead_letter_api::remove_dead_letter(store.as_ref(), &id).await,
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Removes every dead letter, or those of the `phase` given
+async fn purge_dead_letters_handler(
+    State(proxy): State<Arc<ProxyService>>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    match &proxy.dead_letters {
+        Some(store) => dead_letter_api::purge_dead_letters(store.as_ref(), &params).await,
+        None => hyper::StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Metrics handler serving the Prometheus text exposition format
+async fn metrics_handler(State(proxy): State<Arc<ProxyService>>) -> Response {
+    (
+        [(http::header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        render_prometheus(&proxy.metrics()),
+    )
+        .into_response()
 }
@@ -0,0 +1,219 @@
+//! Session query API
+//!
+//! `GET /api/v1/sessions` lists sessions from the read model with filters and
+//! cursor pagination; `GET /api/v1/sessions/{session_id}` returns one session
+//! with its requests and responses, rebuilt from the event store. This module
+//! parses query strings into [`SessionListQuery`] and shapes the responses;
+//! the handlers in [`super::service`] route to it.
+
+use crate::domain::session::{
+    ApplicationId, ClientSessionId, MetadataKey, MetadataValue, SessionId,
+};
+use crate::domain::types::Tag;
+use crate::infrastructure::eventcore::projections::read_model::session_status_from_label;
+use crate::infrastructure::eventcore::projections::{
+    load_session_detail, PageSize, SessionCursor, SessionDirectory, SessionListQuery,
+};
+use crate::infrastructure::eventcore::service::EventCoreService;
+use crate::proxy::error_response::ErrorResponse;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use tracing::error;
+
+/// Prefix of query parameters that filter on custom session metadata
+pub const METADATA_PARAM_PREFIX: &str = "metadata.";
+
+/// A query parameter that could not be used
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid query parameter `{name}`: {reason}")]
+pub struct InvalidQueryParameter {
+    pub name: String,
+    pub reason: String,
+}
+
+impl InvalidQueryParameter {
+    pub(crate) fn new(name: &str, reason: impl std::fmt::Display) -> Self {
+        Self {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Build a listing query from raw query-string pairs
+///
+/// Supported parameters: `application_id`, `status`, `from` and `to`
+/// (RFC 3339, bounding the start time), `has_errors`, `tag`,
+/// `client_session_id`, `metadata.<key>`, `cursor` and `limit`. Unknown
+/// parameters are rejected so a misspelt filter never widens the result.
+pub fn parse_list_query(
+    params: &[(String, String)],
+) -> Result<SessionListQuery, InvalidQueryParameter> {
+    let mut query = SessionListQuery::default();
+
+    for (name, value) in params {
+        let invalid = |reason: &dyn std::fmt::Display| InvalidQueryParameter::new(name, reason);
+
+        match name.as_str() {
+            "application_id" => {
+                query.application_id =
+                    Some(ApplicationId::try_new(value.clone()).map_err(|e| invalid(&e))?)
+            }
+            "status" => {
+                query.status =
+                    Some(session_status_from_label(value).ok_or_else(|| {
+                        invalid(&"expected active, completed, failed or cancelled")
+                    })?)
+            }
+            "from" => query.started_from = Some(parse_time(value).map_err(|e| invalid(&e))?),
+            "to" => query.started_to = Some(parse_time(value).map_err(|e| invalid(&e))?),
+            "has_errors" => {
+                query.has_errors = Some(value.parse().map_err(|e| invalid(&e))?);
+            }
+            "tag" => query.tag = Some(Tag::try_new(value.clone()).map_err(|e| invalid(&e))?),
+            "client_session_id" => {
+                query.client_session_id =
+                    Some(ClientSessionId::try_new(value.clone()).map_err(|e| invalid(&e))?)
+            }
+            "cursor" => {
+                query.cursor =
+                    Some(SessionCursor::decode(value).ok_or_else(|| invalid(&"malformed cursor"))?)
+            }
+            "limit" => {
+                let limit: u32 = value.parse().map_err(|e| invalid(&e))?;
+                query.limit = PageSize::try_new(limit).map_err(|e| invalid(&e))?;
+            }
+            other => match other.strip_prefix(METADATA_PARAM_PREFIX) {
+                Some(key) => {
+                    let key = MetadataKey::try_new(key.to_string()).map_err(|e| invalid(&e))?;
+                    let value = MetadataValue::try_new(value.clone()).map_err(|e| invalid(&e))?;
+                    query.metadata.insert(key, value);
+                }
+                None => return Err(invalid(&"unknown parameter")),
+            },
+        }
+    }
+
+    if let (Some(from), Some(to)) = (query.started_from, query.started_to) {
+        if from >= to {
+            return Err(InvalidQueryParameter::new("from", "must be before `to`"));
+        }
+    }
+
+    Ok(query)
+}
+
+pub(crate) fn parse_time(value: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
+    DateTime::parse_from_rfc3339(value).map(|time| time.with_timezone(&Utc))
+}
+
+/// Respond to `GET /api/v1/sessions`
+pub async fn list_sessions(
+    directory: &dyn SessionDirectory,
+    params: &[(String, String)],
+) -> Response {
+    let query = match parse_list_query(params) {
+        Ok(query) => query,
+        Err(e) => return bad_request(&e),
+    };
+
+    match directory.list_sessions(&query).await {
+        Ok(page) => Json(page).into_response(),
+        Err(e) => {
+            error!("Failed to list sessions: {e}");
+            internal_error()
+        }
+    }
+}
+
+/// Respond to `GET /api/v1/sessions/{session_id}`
+pub async fn session_detail(event_store: &EventCoreService, session_id: &str) -> Response {
+    let session_id = match session_id.parse() {
+        Ok(uuid) => SessionId::new(uuid),
+        Err(e) => return bad_request(&InvalidQueryParameter::new("session_id", e)),
+    };
+
+    match load_session_detail(event_store, &session_id).await {
+        Ok(Some(detail)) => Json(detail).into_response(),
+        Ok(None) => ErrorResponse::new("SESSION_NOT_FOUND", format!("No session {session_id:?}"))
+            .into_response_with_status(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Failed to load session {session_id:?}: {e}");
+            internal_error()
+        }
+    }
+}
+
+pub(crate) fn bad_request(error: &InvalidQueryParameter) -> Response {
+    ErrorResponse::new("INVALID_QUERY", error.to_string())
+        .into_response_with_status(StatusCode::BAD_REQUEST)
+}
+
+pub(crate) fn internal_error() -> Response {
+    ErrorResponse::new("INTERNAL_ERROR", "Failed to run query")
+        .into_response_with_status(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::session::SessionStatus;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_every_filter() {
+        let query = parse_list_query(&params(&[
+            ("application_id", "support-bot"),
+            ("status", "failed"),
+            ("from", "2026-10-01T00:00:00Z"),
+            ("to", "2026-10-02T00:00:00+02:00"),
+            ("has_errors", "true"),
+            ("tag", "escalated"),
+            ("client_session_id", "conv-42"),
+            ("metadata.Customer", "acme"),
+            ("limit", "10"),
+        ]))
+        .unwrap();
+
+        assert_eq!(query.application_id.unwrap().as_ref(), "support-bot");
+        assert_eq!(query.status, Some(SessionStatus::Failed));
+        assert_eq!(
+            query.started_to.unwrap().to_rfc3339(),
+            "2026-10-01T22:00:00+00:00"
+        );
+        assert_eq!(query.has_errors, Some(true));
+        assert_eq!(query.tag.unwrap().as_ref(), "escalated");
+        assert_eq!(query.client_session_id.unwrap().as_ref(), "conv-42");
+        // Metadata keys are case-insensitive, like the headers they come from
+        let (key, value) = query.metadata.iter().next().unwrap();
+        assert_eq!((key.as_ref(), value.as_ref()), ("customer", "acme"));
+        assert_eq!(*query.limit.as_ref(), 10);
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_parameters() {
+        for pairs in [
+            vec![("statsu", "failed")],
+            vec![("status", "exploded")],
+            vec![("from", "yesterday")],
+            vec![("limit", "0")],
+            vec![("cursor", "%%%")],
+            vec![
+                ("from", "2026-10-02T00:00:00Z"),
+                ("to", "2026-10-01T00:00:00Z"),
+            ],
+        ] {
+            assert!(parse_list_query(&params(&pairs)).is_err(), "{pairs:?}");
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Session identification from `X-UnionSquare-*` request headers
+//!
+//! Implements the header scheme from ADR-0012. The headers are consumed at
+//! the proxy boundary and removed from the request, so they never reach the
+//! upstream provider.
+
+use crate::domain::session::{self as domain_session, ClientSessionId, SessionScope};
+use crate::proxy::headers::session;
+use crate::proxy::types::{SessionContext, SessionId};
+use hyper::HeaderMap;
+
+/// Maximum number of `X-UnionSquare-Metadata-*` entries kept per request
+pub const MAX_METADATA_ENTRIES: usize = 64;
+
+/// Remove every `X-UnionSquare-*` header and return the session context they carried
+///
+/// Values that are not valid UTF-8 are dropped, as is an application context
+/// that is not valid JSON.
+pub fn take_session_context(headers: &mut HeaderMap) -> SessionContext {
+    let names: Vec<_> = headers
+        .keys()
+        .filter(|name| name.as_str().starts_with(session::PREFIX))
+        .cloned()
+        .collect();
+
+    let mut context = SessionContext::default();
+    for name in names {
+        let Some(value) = headers
+            .remove(&name)
+            .and_then(|value| value.to_str().ok().map(str::to_string))
+        else {
+            continue;
+        };
+
+        match name.as_str() {
+            session::SESSION_ID => context.session_id = Some(value),
+            session::PARENT_ID => context.parent_id = Some(value),
+            session::USER_ID => context.user_id = Some(value),
+            session::APPLICATION_ID => context.application_id = Some(value),
+            session::ENVIRONMENT => context.environment = Some(value),
+            session::APPLICATION_CONTEXT => {
+                context.application_context = serde_json::from_str(&value).ok();
+            }
+            other => {
+                if let Some(key) = other.strip_prefix(session::METADATA_PREFIX) {
+                    if context.metadata.len() < MAX_METADATA_ENTRIES {
+                        context.metadata.push((key.to_string(), value));
+                    }
+                }
+            }
+        }
+    }
+
+    context
+}
+
+/// Session a request belongs to
+///
+/// Requests of one caller `scope` with a client session ID share a
+/// deterministic session; requests without one are tracked individually
+/// under a fresh ID.
+pub fn resolve_session_id(context: &SessionContext, scope: SessionScope) -> SessionId {
+    context
+        .session_id
+        .clone()
+        .and_then(|raw| ClientSessionId::try_new(raw).ok())
+        .map(|id| domain_session::SessionId::from_client_session_id(scope, &id))
+        .and_then(|id| SessionId::try_new(*id.as_ref()).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::{HeaderValue, CONTENT_TYPE};
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    #[test]
+    fn extracts_all_session_headers() {
+        let mut map = headers(&[
+            ("X-UnionSquare-Session-Id", "conversation-17"),
+            ("X-UnionSquare-Parent-Id", "workflow-3"),
+            ("X-UnionSquare-User-Id", "user@example.com"),
+            ("X-UnionSquare-Application-Id", "support-bot"),
+            ("X-UnionSquare-Environment", "production"),
+            ("X-UnionSquare-Metadata-Feature", "chat"),
+            (
+                "X-UnionSquare-Application-Context",
+                r#"{"priority":"high"}"#,
+            ),
+        ]);
+
+        let context = take_session_context(&mut map);
+
+        assert_eq!(context.session_id.as_deref(), Some("conversation-17"));
+        assert_eq!(context.parent_id.as_deref(), Some("workflow-3"));
+        assert_eq!(context.user_id.as_deref(), Some("user@example.com"));
+        assert_eq!(context.application_id.as_deref(), Some("support-bot"));
+        assert_eq!(context.environment.as_deref(), Some("production"));
+        assert_eq!(
+            context.metadata,
+            vec![("feature".to_string(), "chat".to_string())]
+        );
+        assert_eq!(
+            context.application_context,
+            Some(serde_json::json!({"priority": "high"}))
+        );
+    }
+
+    #[test]
+    fn strips_session_headers_and_keeps_others() {
+        let mut map = headers(&[
+            ("x-unionsquare-session-id", "s-1"),
+            ("x-unionsquare-unknown", "ignored"),
+            ("content-type", "application/json"),
+        ]);
+
+        take_session_context(&mut map);
+
+        assert_eq!(map.len(), 1);
+        assert!(map.contains_key(CONTENT_TYPE));
+    }
+
+    #[test]
+    fn invalid_application_context_is_dropped() {
+        let mut map = headers(&[("x-unionsquare-application-context", "{not json")]);
+
+        let context = take_session_context(&mut map);
+
+        assert!(context.application_context.is_none());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn metadata_entries_are_capped() {
+        let mut map = HeaderMap::new();
+        for i in 0..MAX_METADATA_ENTRIES + 5 {
+            let name = hyper::header::HeaderName::try_from(format!("x-unionsquare-metadata-k{i}"))
+                .unwrap();
+            map.insert(name, HeaderValue::from_static("v"));
+        }
+
+        let context = take_session_context(&mut map);
+
+        assert_eq!(context.metadata.len(), MAX_METADATA_ENTRIES);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn same_client_session_id_resolves_to_same_session() {
+        let context = SessionContext {
+            session_id: Some("conversation-17".to_string()),
+            ..SessionContext::default()
+        };
+
+        let scope = SessionScope::api_key("key-1");
+
+        assert_eq!(
+            resolve_session_id(&context, scope).as_ref(),
+            resolve_session_id(&context.clone(), scope).as_ref()
+        );
+        assert_ne!(
+            resolve_session_id(&context, scope).as_ref(),
+            resolve_session_id(&context, SessionScope::api_key("key-2")).as_ref()
+        );
+    }
+
+    #[test]
+    fn missing_session_id_gets_a_fresh_session() {
+        let context = SessionContext::default();
+
+        assert_ne!(
+            resolve_session_id(&context, SessionScope::default()).as_ref(),
+            resolve_session_id(&context, SessionScope::default()).as_ref()
+        );
+    }
+}
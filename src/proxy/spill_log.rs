@@ -0,0 +1,926 @@
+//! Durable overflow tier for the audit ring buffer
+//!
+//! When a ring buffer shard is full, or the event store is unavailable, the
+//! hot path appends audit entries to an append-only segment log on local disk
+//! instead of dropping them. Each ring buffer shard has its own log under
+//! `shard-{n}/`, drained by that shard's audit consumer once the ring is
+//! empty:
+//!
+//! - Once a shard has spilled, later writes for it keep spilling until its
+//!   consumer has read the log back, so spilled entries are never overtaken
+//!   by newer ones in the ring and per-request order is preserved.
+//! - Records are framed as `[length][crc32][request id][payload]`. A record
+//!   torn by a crash fails its checksum and is cut off when the log reopens.
+//! - The consumer acknowledges records once they are persisted. The position
+//!   is kept in a `cursor` file that is replaced atomically, and fully
+//!   acknowledged segments are deleted. Unacknowledged records are read again
+//!   after a restart.
+//! - Appends that would take the log past [`SpillConfig::max_bytes`] are
+//!   dropped and counted.
+//!
+//! Appends are framed and checked against the capacity on the caller's
+//! thread, then written by a dedicated writer thread, so the hot path never
+//! waits on the disk. An entry counts as pending as soon as it is queued, so
+//! later writes for its shard spill behind it even before it is written.
+
+use crate::proxy::types::*;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Bytes in front of every payload: length, checksum and request ID
+const RECORD_HEADER_BYTES: u64 = 4 + 4 + UUID_SIZE_BYTES as u64;
+
+/// Extension of segment files, which are named by their sequence number
+const SEGMENT_EXTENSION: &str = "seg";
+
+/// File holding the acknowledged position of a shard's log
+const CURSOR_FILE: &str = "cursor";
+
+/// Why an entry could not be spilled
+#[derive(Debug, thiserror::Error)]
+pub enum SpillError {
+    #[error("Spill log is full")]
+    Full,
+
+    #[error("Spill log IO error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// A place in a shard's log
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SpillPosition {
+    segment: u64,
+    offset: u64,
+}
+
+/// Statistics about spill log usage, summed over all shards
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SpillStats {
+    /// Entries appended since the log was opened
+    pub spilled_records: u64,
+    /// Entries rejected because the log was full or could not be written
+    pub dropped_records: u64,
+    /// Entries waiting to be read by their consumer
+    pub pending_records: u64,
+    pub bytes_on_disk: u64,
+}
+
+/// Append-only overflow log with one segment log per ring buffer shard
+pub struct SpillLog {
+    shared: Arc<SharedLog>,
+    /// Closed on drop, which stops the writer once the queue is written
+    writes: Option<mpsc::Sender<WriterTask>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+/// State shared between the log's users and its writer thread
+struct SharedLog {
+    shards: Vec<ShardLog>,
+    max_bytes: u64,
+    segment_bytes: u64,
+    bytes_on_disk: AtomicU64,
+    spilled_records: AtomicU64,
+    dropped_records: AtomicU64,
+}
+
+/// Work for the writer thread, handled in the order it was queued
+enum WriterTask {
+    Append {
+        shard: usize,
+        record: Vec<u8>,
+    },
+    /// Signalled once every earlier append has been written
+    Flush(mpsc::SyncSender<()>),
+}
+
+/// Segment log of one shard
+struct ShardLog {
+    directory: PathBuf,
+    segments: Mutex<Segments>,
+    /// Records queued or written but not yet read
+    pending: AtomicU64,
+    /// Set while the event store is unavailable, so writes spill at once
+    diverted: AtomicBool,
+    readable: Notify,
+}
+
+/// Segment files of a shard, oldest first; the last one takes appends
+struct Segments {
+    files: VecDeque<Segment>,
+    writer: File,
+    reader: Option<BufReader<File>>,
+    read: SpillPosition,
+    /// Records of the segment being read that come before `read`
+    read_records: u64,
+    acknowledged: SpillPosition,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Segment {
+    sequence: u64,
+    len: u64,
+    records: u64,
+}
+
+impl SpillLog {
+    /// Open the log in `config.directory` for a ring buffer with `shard_count` shards
+    ///
+    /// Records left by a previous run are recovered: segments before the
+    /// acknowledged position are deleted and torn records are cut off.
+    pub fn open(config: &SpillConfig, shard_count: usize) -> io::Result<Self> {
+        fs::create_dir_all(&config.directory)?;
+        let shards = (0..shard_count)
+            .map(|shard| ShardLog::open(config.directory.join(format!("shard-{shard}"))))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let shared = Arc::new(SharedLog {
+            max_bytes: *config.max_bytes.as_ref(),
+            segment_bytes: *config.segment_bytes.as_ref(),
+            bytes_on_disk: AtomicU64::new(shards.iter().map(ShardLog::bytes_on_disk).sum()),
+            spilled_records: AtomicU64::new(0),
+            dropped_records: AtomicU64::new(0),
+            shards,
+        });
+        let (writes, tasks) = mpsc::channel();
+        let writer = thread::Builder::new()
+            .name("spill-writer".to_string())
+            .spawn({
+                let shared = Arc::clone(&shared);
+                move || shared.run_writer(tasks)
+            })?;
+        let log = Self {
+            shared,
+            writes: Some(writes),
+            writer: Some(writer),
+        };
+        let pending = log.stats().pending_records;
+        if pending > 0 {
+            info!(
+                "Recovered {pending} spilled audit events from {}",
+                config.directory.display()
+            );
+        }
+        Ok(log)
+    }
+
+    /// Number of shards, which matches the ring buffer the log was opened for
+    pub fn shard_count(&self) -> usize {
+        self.shared.shards.len()
+    }
+
+    /// Whether writes for `shard` must go to the log rather than the ring buffer
+    ///
+    /// True while the shard has unread records, so that they are not
+    /// overtaken, and while its consumer reports the event store unavailable.
+    pub fn should_spill(&self, shard: usize) -> bool {
+        let shard = &self.shared.shards[shard];
+        shard.pending.load(Ordering::Acquire) > 0 || shard.diverted.load(Ordering::Relaxed)
+    }
+
+    /// Send writes for `shard` to the log while the event store is unavailable
+    pub fn set_diverted(&self, shard: usize, diverted: bool) {
+        self.shared.shards[shard]
+            .diverted
+            .store(diverted, Ordering::Relaxed);
+    }
+
+    /// Queue an entry for the log of `shard`
+    ///
+    /// Returns once the entry is queued; the writer thread writes it and
+    /// wakes the shard's reader. An entry that then fails to be written is
+    /// logged and counted as dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard` is not below [`SpillLog::shard_count`].
+    pub fn append(
+        &self,
+        shard: usize,
+        request_id: RequestId,
+        data: &[u8],
+    ) -> Result<(), SpillError> {
+        let result = self.try_append(shard, request_id, data);
+        if result.is_err() {
+            self.shared.dropped_records.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    fn try_append(
+        &self,
+        shard: usize,
+        request_id: RequestId,
+        data: &[u8],
+    ) -> Result<(), SpillError> {
+        let record = encode_record(request_id, data)?;
+        let size = record.len() as u64;
+        let shared = &self.shared;
+        if shared.bytes_on_disk.fetch_add(size, Ordering::Relaxed) + size > shared.max_bytes {
+            shared.bytes_on_disk.fetch_sub(size, Ordering::Relaxed);
+            return Err(SpillError::Full);
+        }
+
+        let log = &shared.shards[shard];
+        log.pending.fetch_add(1, Ordering::Release);
+        let queued = self
+            .writes
+            .as_ref()
+            .is_some_and(|writes| writes.send(WriterTask::Append { shard, record }).is_ok());
+        if !queued {
+            log.pending.fetch_sub(1, Ordering::Release);
+            shared.bytes_on_disk.fetch_sub(size, Ordering::Relaxed);
+            return Err(
+                io::Error::new(io::ErrorKind::BrokenPipe, "spill writer has stopped").into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Block until every entry queued so far has been written
+    pub fn flush(&self) {
+        let (done, written) = mpsc::sync_channel(1);
+        if let Some(writes) = &self.writes {
+            if writes.send(WriterTask::Flush(done)).is_ok() {
+                let _ = written.recv();
+            }
+        }
+    }
+
+    /// Read the next unread entry of `shard`
+    ///
+    /// Each shard supports exactly one reader. A record that fails its
+    /// checksum skips the rest of its segment.
+    pub fn read(&self, shard: usize) -> Option<(RequestId, Vec<u8>)> {
+        let shard = &self.shared.shards[shard];
+        if shard.pending.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+        shard.read()
+    }
+
+    /// Position just past the last entry read from `shard`
+    pub fn position(&self, shard: usize) -> SpillPosition {
+        self.shared.shards[shard].segments.lock().read
+    }
+
+    /// Record that every entry of `shard` before `position` has been persisted
+    ///
+    /// Fully acknowledged segments are deleted, and a drained log is emptied
+    /// so it gives its space back.
+    pub fn acknowledge(&self, shard: usize, position: SpillPosition) -> io::Result<()> {
+        let freed = self.shared.shards[shard].acknowledge(position)?;
+        self.shared
+            .bytes_on_disk
+            .fetch_sub(freed, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Wait until an entry has been appended to `shard` since its reader last waited
+    pub fn readable(&self, shard: usize) -> Notified<'_> {
+        self.shared.shards[shard].readable.notified()
+    }
+
+    pub fn stats(&self) -> SpillStats {
+        let shared = &self.shared;
+        SpillStats {
+            spilled_records: shared.spilled_records.load(Ordering::Relaxed),
+            dropped_records: shared.dropped_records.load(Ordering::Relaxed),
+            pending_records: shared
+                .shards
+                .iter()
+                .map(|shard| shard.pending.load(Ordering::Relaxed))
+                .sum(),
+            bytes_on_disk: shared.bytes_on_disk.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for SpillLog {
+    fn drop(&mut self) {
+        // Closing the queue lets the writer finish what was appended, then stop
+        self.writes.take();
+        if let Some(writer) = self.writer.take() {
+            if writer.join().is_err() {
+                warn!("Spill writer thread panicked");
+            }
+        }
+    }
+}
+
+impl SharedLog {
+    fn run_writer(&self, tasks: mpsc::Receiver<WriterTask>) {
+        for task in tasks {
+            match task {
+                WriterTask::Append { shard, record } => self.write(shard, &record),
+                WriterTask::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    fn write(&self, shard: usize, record: &[u8]) {
+        let log = &self.shards[shard];
+        match log.append(record, self.segment_bytes) {
+            Ok(()) => {
+                self.spilled_records.fetch_add(1, Ordering::Relaxed);
+                log.readable.notify_one();
+            }
+            Err(e) => {
+                warn!("Dropping an audit event the spill log failed to write: {e}");
+                log.pending.fetch_sub(1, Ordering::Release);
+                self.bytes_on_disk
+                    .fetch_sub(record.len() as u64, Ordering::Relaxed);
+                self.dropped_records.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl ShardLog {
+    fn open(directory: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&directory)?;
+        let acknowledged = read_cursor(&directory)?.unwrap_or_default();
+
+        let mut files = VecDeque::new();
+        // Resume at the acknowledged position, or at the oldest segment if
+        // the one it points into is gone
+        let mut resume = None;
+        for sequence in segment_sequences(&directory)? {
+            let path = segment_path(&directory, sequence);
+            if sequence < acknowledged.segment {
+                fs::remove_file(&path)?;
+                continue;
+            }
+            let (len, records) = recover_segment(&path)?;
+            if sequence == acknowledged.segment {
+                let offset = acknowledged.offset.min(len);
+                let read_records = records.iter().filter(|&&start| start < offset).count();
+                resume = Some((
+                    SpillPosition {
+                        segment: sequence,
+                        offset,
+                    },
+                    read_records as u64,
+                ));
+            }
+            files.push_back(Segment {
+                sequence,
+                len,
+                records: records.len() as u64,
+            });
+        }
+        if files.is_empty() {
+            File::create(segment_path(&directory, acknowledged.segment))?;
+            files.push_back(Segment {
+                sequence: acknowledged.segment,
+                len: 0,
+                records: 0,
+            });
+        }
+        let (read, read_records) = resume.unwrap_or((
+            SpillPosition {
+                segment: files[0].sequence,
+                offset: 0,
+            },
+            0,
+        ));
+        let pending = files.iter().map(|segment| segment.records).sum::<u64>() - read_records;
+
+        let last = files[files.len() - 1].sequence;
+        let writer = OpenOptions::new()
+            .append(true)
+            .open(segment_path(&directory, last))?;
+
+        Ok(Self {
+            directory,
+            segments: Mutex::new(Segments {
+                files,
+                writer,
+                reader: None,
+                read,
+                read_records,
+                acknowledged: read,
+            }),
+            pending: AtomicU64::new(pending),
+            diverted: AtomicBool::new(false),
+            readable: Notify::new(),
+        })
+    }
+
+    fn bytes_on_disk(&self) -> u64 {
+        self.segments
+            .lock()
+            .files
+            .iter()
+            .map(|segment| segment.len)
+            .sum()
+    }
+
+    fn append(&self, record: &[u8], segment_bytes: u64) -> io::Result<()> {
+        let mut segments = self.segments.lock();
+        let size = record.len() as u64;
+        let tail = segments.files[segments.files.len() - 1];
+
+        if tail.len > 0 && tail.len + size > segment_bytes {
+            segments.writer.sync_data()?;
+            let sequence = tail.sequence + 1;
+            segments.writer = OpenOptions::new()
+                .append(true)
+                .create_new(true)
+                .open(segment_path(&self.directory, sequence))?;
+            segments.files.push_back(Segment {
+                sequence,
+                len: 0,
+                records: 0,
+            });
+        }
+
+        let tail_index = segments.files.len() - 1;
+        let tail_len = segments.files[tail_index].len;
+        if let Err(e) = segments.writer.write_all(record) {
+            // Cut off whatever part of the record made it to the file
+            let _ = segments.writer.set_len(tail_len);
+            return Err(e);
+        }
+        let tail = &mut segments.files[tail_index];
+        tail.len += size;
+        tail.records += 1;
+        Ok(())
+    }
+
+    fn read(&self) -> Option<(RequestId, Vec<u8>)> {
+        let mut segments = self.segments.lock();
+        loop {
+            let index = segments
+                .files
+                .iter()
+                .position(|segment| segment.sequence == segments.read.segment)?;
+            let segment = segments.files[index];
+            if segments.read.offset >= segment.len {
+                // Move on to the next segment once this one is finished
+                let next = segments.files.get(index + 1)?.sequence;
+                segments.read = SpillPosition {
+                    segment: next,
+                    offset: 0,
+                };
+                segments.read_records = 0;
+                segments.reader = None;
+                continue;
+            }
+
+            let position = segments.read;
+            if segments.reader.is_none() {
+                match open_reader(&self.directory, position) {
+                    Ok(reader) => segments.reader = Some(reader),
+                    Err(e) => {
+                        warn!("Failed to open spill segment {}: {e}", position.segment);
+                        self.skip_segment(&mut segments, segment);
+                        continue;
+                    }
+                }
+            }
+
+            let reader = segments.reader.as_mut().expect("reader was just opened");
+            match read_record(reader, segment.len - position.offset) {
+                Ok(Some((request_id, data))) => {
+                    segments.read.offset += RECORD_HEADER_BYTES + data.len() as u64;
+                    segments.read_records += 1;
+                    self.pending.fetch_sub(1, Ordering::Release);
+                    return Some((request_id, data));
+                }
+                Ok(None) => {
+                    segments.read.offset = segment.len;
+                }
+                Err(e) => {
+                    warn!(
+                        "Skipping the rest of spill segment {} after offset {}: {e}",
+                        position.segment, position.offset
+                    );
+                    self.skip_segment(&mut segments, segment);
+                }
+            }
+        }
+    }
+
+    /// Treat the unread records of `segment` as read
+    fn skip_segment(&self, segments: &mut Segments, segment: Segment) {
+        let skipped = segment.records.saturating_sub(segments.read_records);
+        self.pending.fetch_sub(skipped, Ordering::Release);
+        segments.read.offset = segment.len;
+        segments.read_records = segment.records;
+        segments.reader = None;
+    }
+
+    /// Returns the number of bytes deleted from disk
+    fn acknowledge(&self, position: SpillPosition) -> io::Result<u64> {
+        let mut segments = self.segments.lock();
+        if position <= segments.acknowledged {
+            return Ok(0);
+        }
+
+        let tail = segments.files[segments.files.len() - 1];
+        if tail.sequence == position.segment && tail.len == position.offset {
+            // Everything written has been persisted: empty the log. The cursor
+            // moves first, so a crash in between replays rather than loses.
+            let start = SpillPosition {
+                segment: tail.sequence,
+                offset: 0,
+            };
+            write_cursor(&self.directory, start)?;
+            let mut freed = 0;
+            while segments.files.len() > 1 {
+                let segment = segments.files.pop_front().expect("more than one segment");
+                fs::remove_file(segment_path(&self.directory, segment.sequence))?;
+                freed += segment.len;
+            }
+            segments.writer.set_len(0)?;
+            freed += tail.len;
+            segments.files[0] = Segment {
+                len: 0,
+                records: 0,
+                ..tail
+            };
+            segments.read = start;
+            segments.read_records = 0;
+            segments.reader = None;
+            segments.acknowledged = start;
+            return Ok(freed);
+        }
+
+        write_cursor(&self.directory, position)?;
+        segments.acknowledged = position;
+        let mut freed = 0;
+        while segments.files[0].sequence < position.segment {
+            let segment = segments
+                .files
+                .pop_front()
+                .expect("segment before the cursor");
+            fs::remove_file(segment_path(&self.directory, segment.sequence))?;
+            freed += segment.len;
+        }
+        Ok(freed)
+    }
+}
+
+fn segment_path(directory: &Path, sequence: u64) -> PathBuf {
+    directory.join(format!("{sequence:020}.{SEGMENT_EXTENSION}"))
+}
+
+/// Sequence numbers of the segment files in `directory`, oldest first
+fn segment_sequences(directory: &Path) -> io::Result<Vec<u64>> {
+    let mut sequences = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+        if let Some(sequence) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse().ok())
+        {
+            sequences.push(sequence);
+        }
+    }
+    sequences.sort_unstable();
+    Ok(sequences)
+}
+
+fn read_cursor(directory: &Path) -> io::Result<Option<SpillPosition>> {
+    let contents = match fs::read_to_string(directory.join(CURSOR_FILE)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut fields = contents.split_whitespace().map(str::parse::<u64>);
+    match (fields.next(), fields.next()) {
+        (Some(Ok(segment)), Some(Ok(offset))) => Ok(Some(SpillPosition { segment, offset })),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("malformed spill cursor in {}", directory.display()),
+        )),
+    }
+}
+
+/// Replace the cursor file so that it always holds a complete position
+fn write_cursor(directory: &Path, position: SpillPosition) -> io::Result<()> {
+    let temporary = directory.join(format!("{CURSOR_FILE}.tmp"));
+    let mut file = File::create(&temporary)?;
+    writeln!(file, "{} {}", position.segment, position.offset)?;
+    file.sync_data()?;
+    fs::rename(temporary, directory.join(CURSOR_FILE))
+}
+
+/// Cut a segment back to its last intact record
+///
+/// Returns the segment's length and the offsets of its records.
+fn recover_segment(path: &Path) -> io::Result<(u64, Vec<u64>)> {
+    let file_len = fs::metadata(path)?.len();
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    let mut offset = 0;
+    loop {
+        match read_record(&mut reader, file_len - offset) {
+            Ok(Some((_, data))) => {
+                records.push(offset);
+                offset += RECORD_HEADER_BYTES + data.len() as u64;
+            }
+            Ok(None) => break,
+            Err(e) => {
+                warn!(
+                    "Truncating spill segment {} at offset {offset}: {e}",
+                    path.display()
+                );
+                OpenOptions::new().write(true).open(path)?.set_len(offset)?;
+                break;
+            }
+        }
+    }
+    Ok((offset, records))
+}
+
+fn open_reader(directory: &Path, position: SpillPosition) -> io::Result<BufReader<File>> {
+    let mut file = File::open(segment_path(directory, position.segment))?;
+    file.seek(SeekFrom::Start(position.offset))?;
+    Ok(BufReader::new(file))
+}
+
+fn encode_record(request_id: RequestId, data: &[u8]) -> io::Result<Vec<u8>> {
+    let len = u32::try_from(data.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "audit entry too large to spill",
+        )
+    })?;
+    let id = request_id.as_ref().as_bytes();
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(id);
+    hasher.update(data);
+
+    let mut record = Vec::with_capacity(RECORD_HEADER_BYTES as usize + data.len());
+    record.extend_from_slice(&len.to_le_bytes());
+    record.extend_from_slice(&hasher.finalize().to_le_bytes());
+    record.extend_from_slice(id);
+    record.extend_from_slice(data);
+    Ok(record)
+}
+
+/// Read one record from at most `available` bytes
+///
+/// Returns `None` at a clean end and an error for a torn or corrupt record.
+fn read_record(reader: &mut impl Read, available: u64) -> io::Result<Option<(RequestId, Vec<u8>)>> {
+    if available == 0 {
+        return Ok(None);
+    }
+    let corrupt = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+    if available < RECORD_HEADER_BYTES {
+        return Err(corrupt("torn record header"));
+    }
+
+    let mut header = [0; RECORD_HEADER_BYTES as usize];
+    reader.read_exact(&mut header)?;
+    let len = u32::from_le_bytes(header[0..4].try_into().expect("4 bytes"));
+    let checksum = u32::from_le_bytes(header[4..8].try_into().expect("4 bytes"));
+    if u64::from(len) > available - RECORD_HEADER_BYTES {
+        return Err(corrupt("torn record payload"));
+    }
+
+    let mut data = vec![0; len as usize];
+    reader.read_exact(&mut data)?;
+    let id = &header[8..];
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(id);
+    hasher.update(&data);
+    if hasher.finalize() != checksum {
+        return Err(corrupt("checksum mismatch"));
+    }
+
+    let uuid = Uuid::from_bytes(id.try_into().expect("16 bytes"));
+    let request_id = RequestId::try_new(uuid).map_err(|e| corrupt(&e.to_string()))?;
+    Ok(Some((request_id, data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(directory: &Path) -> SpillConfig {
+        SpillConfig::new(directory)
+    }
+
+    fn small_segments(directory: &Path) -> SpillConfig {
+        SpillConfig {
+            segment_bytes: SegmentSize::try_new(100).unwrap(),
+            ..SpillConfig::new(directory)
+        }
+    }
+
+    fn drain(log: &SpillLog, shard: usize) -> Vec<(RequestId, Vec<u8>)> {
+        std::iter::from_fn(|| log.read(shard)).collect()
+    }
+
+    #[test]
+    fn reads_back_appended_entries_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = SpillLog::open(&config(dir.path()), 2).unwrap();
+        let request_id = RequestId::new();
+
+        assert!(!log.should_spill(0));
+        for payload in [b"one".as_slice(), b"two", b"three"] {
+            log.append(0, request_id, payload).unwrap();
+        }
+
+        // Queued entries already count, before the writer gets to them
+        assert!(log.should_spill(0));
+        assert!(!log.should_spill(1));
+        log.flush();
+        let entries = drain(&log, 0);
+        assert_eq!(
+            entries,
+            vec![
+                (request_id, b"one".to_vec()),
+                (request_id, b"two".to_vec()),
+                (request_id, b"three".to_vec()),
+            ]
+        );
+        assert!(!log.should_spill(0));
+        assert_eq!(log.read(1), None);
+    }
+
+    #[test]
+    fn unacknowledged_entries_are_read_again_after_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_id = RequestId::new();
+        {
+            let log = SpillLog::open(&small_segments(dir.path()), 1).unwrap();
+            for i in 0..10u8 {
+                log.append(0, request_id, &[i; 20]).unwrap();
+            }
+            log.flush();
+            for _ in 0..4 {
+                log.read(0).unwrap();
+            }
+            log.acknowledge(0, log.position(0)).unwrap();
+            // Read but never acknowledged
+            log.read(0).unwrap();
+        }
+
+        let log = SpillLog::open(&small_segments(dir.path()), 1).unwrap();
+        assert_eq!(log.stats().pending_records, 6);
+        let payloads: Vec<u8> = drain(&log, 0)
+            .into_iter()
+            .map(|(_, data)| data[0])
+            .collect();
+        assert_eq!(payloads, vec![4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn acknowledging_deletes_finished_segments_and_empties_a_drained_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = SpillLog::open(&small_segments(dir.path()), 1).unwrap();
+        for i in 0..10u8 {
+            log.append(0, RequestId::new(), &[i; 20]).unwrap();
+        }
+        log.flush();
+        let shard_dir = dir.path().join("shard-0");
+        assert!(segment_sequences(&shard_dir).unwrap().len() > 1);
+
+        for _ in 0..5 {
+            log.read(0).unwrap();
+        }
+        log.acknowledge(0, log.position(0)).unwrap();
+        let remaining = segment_sequences(&shard_dir).unwrap();
+        assert!(remaining.len() > 1 && remaining[0] > 0, "{remaining:?}");
+
+        drain(&log, 0);
+        log.acknowledge(0, log.position(0)).unwrap();
+        assert_eq!(segment_sequences(&shard_dir).unwrap().len(), 1);
+        assert_eq!(log.stats().bytes_on_disk, 0);
+
+        // The emptied log keeps taking entries
+        let request_id = RequestId::new();
+        log.append(0, request_id, b"after").unwrap();
+        log.flush();
+        assert_eq!(log.read(0), Some((request_id, b"after".to_vec())));
+    }
+
+    #[test]
+    fn torn_tail_is_cut_off_on_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let request_id = RequestId::new();
+        {
+            let log = SpillLog::open(&config(dir.path()), 1).unwrap();
+            log.append(0, request_id, b"complete").unwrap();
+            log.append(0, request_id, b"torn by a crash").unwrap();
+        }
+        let segment = segment_path(&dir.path().join("shard-0"), 0);
+        let len = fs::metadata(&segment).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&segment)
+            .unwrap()
+            .set_len(len - 3)
+            .unwrap();
+
+        let log = SpillLog::open(&config(dir.path()), 1).unwrap();
+        assert_eq!(drain(&log, 0), vec![(request_id, b"complete".to_vec())]);
+
+        // Appends continue from the intact records
+        log.append(0, request_id, b"next").unwrap();
+        log.flush();
+        assert_eq!(log.read(0), Some((request_id, b"next".to_vec())));
+    }
+
+    #[test]
+    fn corrupt_record_fails_its_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let log = SpillLog::open(&config(dir.path()), 1).unwrap();
+            log.append(0, RequestId::new(), b"flipped").unwrap();
+        }
+        let segment = segment_path(&dir.path().join("shard-0"), 0);
+        let mut bytes = fs::read(&segment).unwrap();
+        *bytes.last_mut().unwrap() ^= 0xff;
+        fs::write(&segment, bytes).unwrap();
+
+        let log = SpillLog::open(&config(dir.path()), 1).unwrap();
+        assert_eq!(log.stats().pending_records, 0);
+        assert_eq!(log.read(0), None);
+    }
+
+    #[test]
+    fn appends_beyond_the_capacity_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SpillConfig {
+            max_bytes: SpillCapacity::try_new(2 * (RECORD_HEADER_BYTES + 10)).unwrap(),
+            ..SpillConfig::new(dir.path())
+        };
+        let log = SpillLog::open(&config, 1).unwrap();
+
+        log.append(0, RequestId::new(), &[0; 10]).unwrap();
+        log.append(0, RequestId::new(), &[1; 10]).unwrap();
+        assert!(matches!(
+            log.append(0, RequestId::new(), &[2; 10]),
+            Err(SpillError::Full)
+        ));
+        log.flush();
+
+        let stats = log.stats();
+        assert_eq!(stats.spilled_records, 2);
+        assert_eq!(stats.dropped_records, 1);
+        assert_eq!(stats.bytes_on_disk, config.max_bytes.into_inner());
+
+        // Space returns once the entries are persisted
+        drain(&log, 0);
+        log.acknowledge(0, log.position(0)).unwrap();
+        log.append(0, RequestId::new(), &[3; 10]).unwrap();
+    }
+
+    #[test]
+    fn entries_the_writer_fails_to_write_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = SpillLog::open(&small_segments(dir.path()), 1).unwrap();
+        log.append(0, RequestId::new(), &[0; 20]).unwrap();
+        log.append(0, RequestId::new(), &[1; 20]).unwrap();
+        log.flush();
+
+        // The next append starts a segment, which cannot be created
+        fs::remove_dir_all(dir.path().join("shard-0")).unwrap();
+        log.append(0, RequestId::new(), &[2; 20]).unwrap();
+        log.flush();
+
+        let stats = log.stats();
+        assert_eq!(stats.spilled_records, 2);
+        assert_eq!(stats.dropped_records, 1);
+        assert_eq!(stats.pending_records, 2);
+        assert_eq!(stats.bytes_on_disk, 2 * (RECORD_HEADER_BYTES + 20));
+    }
+
+    #[tokio::test]
+    async fn appends_wake_the_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = std::sync::Arc::new(SpillLog::open(&config(dir.path()), 1).unwrap());
+
+        let writer = std::sync::Arc::clone(&log);
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            writer.append(0, RequestId::new(), b"wake").unwrap();
+        });
+
+        tokio::time::timeout(std::time::Duration::from_secs(1), log.readable(0))
+            .await
+            .expect("append should wake the reader");
+        assert!(log.read(0).is_some());
+    }
+}
@@ -19,6 +19,9 @@ pub mod test_helpers {
             max_response_size: ResponseSizeLimit::try_new(10 * 1024 * 1024).expect("10MB is valid"), // 10MB
             ring_buffer: test_ring_buffer_config(),
             bedrock_region: None,
+            upstream_tls: Default::default(),
+            providers: Vec::new(),
+            audit_format: Default::default(),
         }
     }
 
@@ -27,6 +30,7 @@ pub mod test_helpers {
         RingBufferConfig {
             buffer_size: BufferSize::try_new(1024 * 1024).expect("1MB is valid power of 2"), // 1MB for tests
             slot_size: SlotSize::try_new(64 * 1024).expect("64KB is valid"),
+            ..Default::default()
         }
     }
 
@@ -170,6 +174,230 @@ pub mod test_helpers {
     }
 }
 
+/// Local TLS stand-in for upstream providers
+#[cfg(test)]
+pub mod tls {
+    use bytes::Bytes;
+    use http_body_util::Full;
+    use hyper::{service::service_fn, Response};
+    use hyper_util::rt::TokioIo;
+    use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, IsCa, KeyPair};
+    use rustls::{server::WebPkiClientVerifier, RootCertStore, ServerConfig};
+    use rustls_pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    /// PEM material for a private CA, a `localhost` server and a client identity
+    pub struct TestCertificates {
+        pub ca: String,
+        pub server_certificate: String,
+        pub server_key: String,
+        pub client_certificate: String,
+        pub client_key: String,
+    }
+
+    impl TestCertificates {
+        pub const CA_FILE: &'static str = "ca.pem";
+        pub const CLIENT_CERT_FILE: &'static str = "client.pem";
+        pub const CLIENT_KEY_FILE: &'static str = "client-key.pem";
+
+        pub fn generate() -> Self {
+            let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+            ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca = CertifiedIssuer::self_signed(ca_params, KeyPair::generate().unwrap()).unwrap();
+
+            let server_key = KeyPair::generate().unwrap();
+            let server_certificate = CertificateParams::new(vec!["localhost".to_string()])
+                .unwrap()
+                .signed_by(&server_key, &ca)
+                .unwrap();
+
+            let client_key = KeyPair::generate().unwrap();
+            let client_certificate = CertificateParams::new(vec!["union-square".to_string()])
+                .unwrap()
+                .signed_by(&client_key, &ca)
+                .unwrap();
+
+            Self {
+                ca: ca.pem(),
+                server_certificate: server_certificate.pem(),
+                server_key: server_key.serialize_pem(),
+                client_certificate: client_certificate.pem(),
+                client_key: client_key.serialize_pem(),
+            }
+        }
+
+        /// Write the CA and client identity where `UpstreamTlsConfig` can load them
+        pub fn write_to_tempdir(&self) -> tempfile::TempDir {
+            let dir = tempfile::tempdir().unwrap();
+            std::fs::write(dir.path().join(Self::CA_FILE), &self.ca).unwrap();
+            std::fs::write(
+                dir.path().join(Self::CLIENT_CERT_FILE),
+                &self.client_certificate,
+            )
+            .unwrap();
+            std::fs::write(dir.path().join(Self::CLIENT_KEY_FILE), &self.client_key).unwrap();
+            dir
+        }
+    }
+
+    /// Serve `body` over HTTPS on an ephemeral localhost port
+    ///
+    /// With `require_client_certificate` the handshake fails unless the client
+    /// presents a certificate issued by the test CA.
+    pub async fn spawn_tls_upstream(
+        certs: &TestCertificates,
+        require_client_certificate: bool,
+        body: &'static str,
+    ) -> SocketAddr {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let chain =
+            vec![CertificateDer::from_pem_slice(certs.server_certificate.as_bytes()).unwrap()];
+        let key = PrivateKeyDer::from_pem_slice(certs.server_key.as_bytes()).unwrap();
+
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .unwrap();
+        let builder = if require_client_certificate {
+            let mut roots = RootCertStore::empty();
+            roots
+                .add(CertificateDer::from_pem_slice(certs.ca.as_bytes()).unwrap())
+                .unwrap();
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .unwrap();
+            builder.with_client_cert_verifier(verifier)
+        } else {
+            builder.with_no_client_auth()
+        };
+        let config = builder.with_single_cert(chain, key).unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let Ok(stream) = acceptor.accept(stream).await else {
+                        return;
+                    };
+                    let service = service_fn(move |_request| async move {
+                        Ok::<_, std::convert::Infallible>(Response::new(Full::new(
+                            Bytes::from_static(body.as_bytes()),
+                        )))
+                    });
+                    let _ = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+        addr
+    }
+}
+
+/// In-process stand-in for an OTLP collector
+#[cfg(test)]
+pub mod collector {
+    use axum::{extract::State, http::HeaderMap, routing::post, Json, Router};
+    use parking_lot::Mutex;
+    use serde_json::Value;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+
+    type Exports = Arc<Mutex<Vec<(HeaderMap, Value)>>>;
+
+    /// Collector accepting OTLP/HTTP JSON trace exports on an ephemeral port
+    pub struct TestCollector {
+        addr: SocketAddr,
+        exports: Exports,
+    }
+
+    impl TestCollector {
+        /// Base URL to configure as the OTLP endpoint
+        pub fn endpoint(&self) -> String {
+            format!("http://{}", self.addr)
+        }
+
+        /// Every span received so far, in arrival order
+        pub fn spans(&self) -> Vec<Value> {
+            self.exports
+                .lock()
+                .iter()
+                .flat_map(|(_, body)| {
+                    body["resourceSpans"]
+                        .as_array()
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .flat_map(|resource| {
+                    resource["scopeSpans"]
+                        .as_array()
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .flat_map(|scope| scope["spans"].as_array().cloned().unwrap_or_default())
+                .collect()
+        }
+
+        /// Headers of each export request received so far
+        pub fn headers(&self) -> Vec<HeaderMap> {
+            self.exports
+                .lock()
+                .iter()
+                .map(|(headers, _)| headers.clone())
+                .collect()
+        }
+
+        /// Wait until a span matching `predicate` arrives and return it
+        ///
+        /// # Panics
+        ///
+        /// Panics if none arrives within five seconds.
+        pub async fn wait_for_span(&self, predicate: impl Fn(&Value) -> bool) -> Value {
+            for _ in 0..500 {
+                if let Some(span) = self.spans().into_iter().find(&predicate) {
+                    return span;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            panic!("no matching span exported; got {:?}", self.spans());
+        }
+    }
+
+    /// The value of the attribute `key` of an exported span, if set
+    pub fn attribute<'a>(span: &'a Value, key: &str) -> Option<&'a Value> {
+        span["attributes"]
+            .as_array()?
+            .iter()
+            .find(|attribute| attribute["key"] == key)
+            .map(|attribute| &attribute["value"])
+    }
+
+    async fn collect(State(exports): State<Exports>, headers: HeaderMap, Json(body): Json<Value>) {
+        exports.lock().push((headers, body));
+    }
+
+    /// Start a collector on an ephemeral localhost port
+    pub async fn spawn_collector() -> TestCollector {
+        let exports = Exports::default();
+        let router = Router::new()
+            .route("/v1/traces", post(collect))
+            .with_state(Arc::clone(&exports));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+        TestCollector { addr, exports }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::test_helpers::*;
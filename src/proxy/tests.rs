@@ -38,8 +38,12 @@ mod proxy_service_tests {
             ring_buffer: crate::proxy::types::RingBufferConfig {
                 buffer_size: BufferSize::try_new(512 * 1024 * 1024).expect("valid size"), // 512MB
                 slot_size: SlotSize::try_new(32 * 1024).expect("valid size"),             // 32KB
+                ..Default::default()
             },
             bedrock_region: None,
+            upstream_tls: Default::default(),
+            providers: Vec::new(),
+            audit_format: Default::default(),
         };
 
         let _service = ProxyService::new(config.clone());
@@ -206,6 +210,7 @@ mod streaming_tests {
             ring_buffer: crate::proxy::types::RingBufferConfig {
                 buffer_size: BufferSize::try_new(1024 * 1024).expect("valid size"), // 1MB buffer
                 slot_size: SlotSize::try_new(64 * 1024).expect("valid size"),       // 64KB slots
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -0,0 +1,307 @@
+//! W3C Trace Context propagation
+//!
+//! Clients that take part in distributed tracing send a `traceparent` header
+//! naming their trace and the span that made the call. The proxy continues
+//! that trace: its own spans become children of the caller's span, and the
+//! `traceparent` it forwards names the proxy's span so the provider call is
+//! attributed to the proxy hop. Requests without a valid `traceparent` start
+//! a new trace.
+//!
+//! See <https://www.w3.org/TR/trace-context/> for the header format.
+
+use hyper::header::{HeaderMap, HeaderValue};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Header carrying the trace and parent span of a request
+pub const TRACEPARENT: &str = "traceparent";
+
+/// Vendor-specific trace state, forwarded unchanged
+pub const TRACESTATE: &str = "tracestate";
+
+/// The only `traceparent` version this implementation writes
+const VERSION: &str = "00";
+
+/// Length of a version `00` header: `00-<32 hex>-<16 hex>-<2 hex>`
+const TRACEPARENT_LEN: usize = 55;
+
+/// Bit of the trace flags marking the trace as sampled by the caller
+const SAMPLED_FLAG: u8 = 0x01;
+
+/// Identifier shared by every span of a trace; never all zeroes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TraceId([u8; 16]);
+
+impl TraceId {
+    /// A new random trace id
+    pub fn generate() -> Self {
+        loop {
+            let mut bytes = [0; 16];
+            bytes[..8].copy_from_slice(&random_u64().to_be_bytes());
+            bytes[8..].copy_from_slice(&random_u64().to_be_bytes());
+            if let Some(id) = Self::from_bytes(bytes) {
+                return id;
+            }
+        }
+    }
+
+    /// The id with these bytes, unless they are all zero
+    pub fn from_bytes(bytes: [u8; 16]) -> Option<Self> {
+        (bytes != [0; 16]).then_some(Self(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+/// Identifier of one span within a trace; never all zeroes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpanId([u8; 8]);
+
+impl SpanId {
+    /// A new random span id
+    pub fn generate() -> Self {
+        loop {
+            if let Some(id) = Self::from_bytes(random_u64().to_be_bytes()) {
+                return id;
+            }
+        }
+    }
+
+    /// The id with these bytes, unless they are all zero
+    pub fn from_bytes(bytes: [u8; 8]) -> Option<Self> {
+        (bytes != [0; 8]).then_some(Self(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for SpanId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+/// A position in a trace: the trace, the current span and whether it is sampled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceContext {
+    pub trace_id: TraceId,
+    pub span_id: SpanId,
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// The first span of a new, sampled trace
+    pub fn root() -> Self {
+        Self {
+            trace_id: TraceId::generate(),
+            span_id: SpanId::generate(),
+            sampled: true,
+        }
+    }
+
+    /// A new span in the same trace, with the same sampling decision
+    pub fn child(&self) -> Self {
+        Self {
+            trace_id: self.trace_id,
+            span_id: SpanId::generate(),
+            sampled: self.sampled,
+        }
+    }
+
+    /// Parse a `traceparent` header value
+    ///
+    /// Returns `None` for malformed values, the invalid version `ff` and
+    /// all-zero ids. Values of later versions are read by their version `00`
+    /// prefix, as the specification requires.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let bytes = value.as_bytes();
+        if bytes.len() < TRACEPARENT_LEN || !value.is_ascii() {
+            return None;
+        }
+        let version = decode_hex::<1>(&value[0..2])?[0];
+        if version == 0xff
+            || (version == 0 && bytes.len() != TRACEPARENT_LEN)
+            || (bytes.len() > TRACEPARENT_LEN && bytes[TRACEPARENT_LEN] != b'-')
+        {
+            return None;
+        }
+        if bytes[2] != b'-' || bytes[35] != b'-' || bytes[52] != b'-' {
+            return None;
+        }
+
+        let trace_id = TraceId::from_bytes(decode_hex(&value[3..35])?)?;
+        let span_id = SpanId::from_bytes(decode_hex(&value[36..52])?)?;
+        let flags = decode_hex::<1>(&value[53..55])?[0];
+        Some(Self {
+            trace_id,
+            span_id,
+            sampled: flags & SAMPLED_FLAG != 0,
+        })
+    }
+
+    /// The context named by the `traceparent` header in `headers`, if valid
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        headers
+            .get(TRACEPARENT)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Self::parse)
+    }
+
+    /// Replace the `traceparent` in `headers` with this context
+    pub fn inject(&self, headers: &mut HeaderMap) {
+        let value = HeaderValue::try_from(self.to_string())
+            .expect("traceparent is always a valid header value");
+        headers.insert(TRACEPARENT, value);
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = if self.sampled { SAMPLED_FLAG } else { 0 };
+        write!(
+            f,
+            "{VERSION}-{}-{}-{flags:02x}",
+            self.trace_id, self.span_id
+        )
+    }
+}
+
+/// 64 random bits for a new id
+///
+/// A process-wide counter hashed with randomly keyed SipHash: unique within
+/// the process and unpredictable across processes, at a fraction of the cost
+/// of a UUID, since ids are generated on the request path.
+fn random_u64() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+}
+
+/// Decode exactly `N` bytes of lowercase hex
+fn decode_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    let digits = hex.as_bytes();
+    if digits.len() != N * 2 {
+        return None;
+    }
+    let mut bytes = [0; N];
+    for (byte, [high, low]) in bytes.iter_mut().zip(digits.as_chunks::<2>().0) {
+        *byte = (hex_digit(*high)? << 4) | hex_digit(*low)?;
+    }
+    Some(bytes)
+}
+
+fn hex_digit(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'..=b'9' => Some(digit - b'0'),
+        b'a'..=b'f' => Some(digit - b'a' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_parse_round_trips_a_valid_traceparent() {
+        let context = TraceContext::parse(EXAMPLE).unwrap();
+        assert!(context.sampled);
+        assert_eq!(
+            context.trace_id.to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(context.span_id.to_string(), "00f067aa0ba902b7");
+        assert_eq!(context.to_string(), EXAMPLE);
+    }
+
+    #[test]
+    fn test_parse_reads_the_sampled_flag() {
+        let context =
+            TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00").unwrap();
+        assert!(!context.sampled);
+        assert!(context.to_string().ends_with("-00"));
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_values() {
+        for value in [
+            "",
+            "garbage",
+            // All-zero trace id
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            // All-zero span id
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            // Forbidden version
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            // Uppercase hex
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            // Non-ASCII
+            "00-4bf92f3577b34da6a3ce929d0e0e47é-00f067aa0ba902b7-01",
+            // Wrong separators
+            "00_4bf92f3577b34da6a3ce929d0e0e4736_00f067aa0ba902b7_01",
+            // Version 00 with trailing data
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert!(TraceContext::parse(value).is_none(), "accepted {value:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_accepts_later_versions_by_their_prefix() {
+        let context = TraceContext::parse(
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-future-field",
+        )
+        .unwrap();
+        assert_eq!(context.to_string(), EXAMPLE);
+    }
+
+    #[test]
+    fn test_child_keeps_the_trace_and_sampling() {
+        let parent = TraceContext::parse(EXAMPLE).unwrap();
+        let child = parent.child();
+        assert_eq!(child.trace_id, parent.trace_id);
+        assert_eq!(child.sampled, parent.sampled);
+        assert_ne!(child.span_id, parent.span_id);
+    }
+
+    #[test]
+    fn test_generated_ids_are_valid_and_distinct() {
+        let first = TraceContext::root();
+        let second = TraceContext::root();
+        assert_ne!(first.trace_id, second.trace_id);
+        assert_ne!(first.span_id, second.span_id);
+        assert_eq!(TraceContext::parse(&first.to_string()), Some(first));
+    }
+
+    #[test]
+    fn test_inject_replaces_the_traceparent_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(TRACEPARENT, HeaderValue::from_static(EXAMPLE));
+        let child = TraceContext::from_headers(&headers).unwrap().child();
+
+        child.inject(&mut headers);
+
+        assert_eq!(headers.get_all(TRACEPARENT).iter().count(), 1);
+        assert_eq!(TraceContext::from_headers(&headers), Some(child));
+    }
+}
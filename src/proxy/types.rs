@@ -9,14 +9,17 @@
 //! ### Size and Capacity Types
 //! Types for representing various sizes and capacities with validation:
 //! - `RequestSizeLimit`, `ResponseSizeLimit`: Maximum sizes for HTTP payloads
-//! - `BufferSize`, `SlotSize`: Ring buffer dimensions
+//! - `BufferSize`, `SlotSize`, `ShardCount`: Ring buffer dimensions
+//! - `SpillCapacity`, `SegmentSize`: Bounds of the on-disk overflow log
+//! - `ExportIntervalMs`: Longest a finished span waits before OTLP export
 //! - `BodySize`, `DataSize`: Actual data sizes
 //!
 //! ### Identifier Types
 //! Unique identifiers with specific formats:
 //! - `RequestId`: V7 UUID for request correlation
-//! - `SessionId`: V7 UUID for session tracking
+//! - `SessionId`: V7 UUID for proxy-assigned sessions, V5 for client-supplied ones
 //! - `ApiKey`: Non-empty string for authentication
+//! - `ServiceName`: Name the proxy reports its spans under
 //!
 //! ### HTTP Types
 //! HTTP-specific types with validation:
@@ -49,9 +52,15 @@
 //! };
 //! ```
 
+use crate::domain::budget::BudgetRejection;
+use crate::domain::session::{ApplicationId, EnvironmentId};
 use crate::providers::bedrock::types::AwsRegion;
+use crate::providers::config::ProviderConfig;
+use crate::proxy::audit_codec::AuditEventFormat;
 use nutype::nutype;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::Duration;
 use thiserror::Error;
 use uuid::Uuid;
@@ -86,6 +95,38 @@ pub struct BufferSize(usize);
 )]
 pub struct SlotSize(usize);
 
+/// Number of ring buffer shards, each drained by its own audit consumer
+#[nutype(
+    derive(Clone, Copy, Debug, Display, Default, Deserialize, Serialize, TryFrom, AsRef),
+    validate(predicate = |count: &usize| (1..=64).contains(count)),
+    default = 1
+)]
+pub struct ShardCount(usize);
+
+/// Most bytes the spill log may keep on disk across all shards
+#[nutype(
+    derive(Clone, Copy, Debug, Display, Default, Deserialize, Serialize, TryFrom, AsRef),
+    validate(predicate = |bytes: &u64| *bytes > 0),
+    default = 1_073_741_824
+)]
+pub struct SpillCapacity(u64);
+
+/// Size at which the spill log starts a new segment file
+#[nutype(
+    derive(Clone, Copy, Debug, Display, Default, Deserialize, Serialize, TryFrom, AsRef),
+    validate(predicate = |bytes: &u64| *bytes > 0),
+    default = 67_108_864
+)]
+pub struct SegmentSize(u64);
+
+/// Longest a finished span waits before it is exported
+#[nutype(
+    derive(Clone, Copy, Debug, Display, Default, Deserialize, Serialize, TryFrom, AsRef),
+    validate(predicate = |ms: &u64| *ms > 0),
+    default = 1_000
+)]
+pub struct ExportIntervalMs(u64);
+
 /// Actual size of data in a buffer slot
 #[nutype(
     derive(Clone, Copy, Debug, Display, Deserialize, Serialize, TryFrom, AsRef),
@@ -257,9 +298,6 @@ pub const TIMEOUT_SHORT_MS: u64 = 100;
 pub const TIMEOUT_DEFAULT_SECS: u64 = 30;
 pub const TIMEOUT_LONG_SECS: u64 = 60;
 
-/// Channel buffer sizes
-pub const CHANNEL_BUFFER_SIZE: usize = 16;
-
 /// Proxy configuration
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ProxyConfig {
@@ -271,8 +309,18 @@ pub struct ProxyConfig {
     pub request_timeout: Duration,
     /// Ring buffer configuration
     pub ring_buffer: RingBufferConfig,
-    /// AWS region for Bedrock provider
+    /// AWS region for the default Bedrock provider when `providers` is empty
     pub bedrock_region: Option<AwsRegion>,
+    /// TLS settings for connections to upstream providers
+    #[serde(default)]
+    pub upstream_tls: UpstreamTlsConfig,
+    /// Provider instances to route to; empty means one of each built-in
+    /// provider under its default prefix
+    #[serde(default)]
+    pub providers: Vec<ProviderConfig>,
+    /// Encoding of audit events on the ring buffer and spill log
+    #[serde(default)]
+    pub audit_format: AuditEventFormat,
 }
 
 impl Default for ProxyConfig {
@@ -283,10 +331,36 @@ impl Default for ProxyConfig {
             request_timeout: Duration::from_secs(TIMEOUT_DEFAULT_SECS),
             ring_buffer: RingBufferConfig::default(),
             bedrock_region: None,
+            upstream_tls: UpstreamTlsConfig::default(),
+            providers: Vec::new(),
+            audit_format: AuditEventFormat::default(),
         }
     }
 }
 
+/// TLS settings for upstream connections
+///
+/// Public roots from `webpki-roots` are always trusted; the paths here add to
+/// them rather than replace them.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct UpstreamTlsConfig {
+    /// PEM files with additional trusted root certificates (e.g. a private CA)
+    #[serde(default)]
+    pub extra_root_certificates: Vec<PathBuf>,
+    /// Client certificate presented to upstreams that require mutual TLS
+    #[serde(default)]
+    pub client_certificate: Option<ClientCertificateConfig>,
+}
+
+/// Client identity for mutual TLS egress
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClientCertificateConfig {
+    /// PEM file with the client certificate followed by any intermediates
+    pub certificate_chain: PathBuf,
+    /// PEM file with the private key for the client certificate
+    pub private_key: PathBuf,
+}
+
 /// Ring buffer configuration
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RingBufferConfig {
@@ -294,6 +368,9 @@ pub struct RingBufferConfig {
     pub buffer_size: BufferSize,
     /// Size of each slot in bytes
     pub slot_size: SlotSize,
+    /// Shards the slots are divided between
+    #[serde(default)]
+    pub shards: ShardCount,
 }
 
 impl Default for RingBufferConfig {
@@ -301,6 +378,70 @@ impl Default for RingBufferConfig {
         Self {
             buffer_size: BufferSize::try_new(BYTES_1GB).expect("1GB is valid power of 2"),
             slot_size: SlotSize::try_new(BYTES_64KB).expect("64KB is valid"),
+            shards: ShardCount::default(),
+        }
+    }
+}
+
+/// On-disk overflow tier for audit events the ring buffer cannot take
+///
+/// Absent by default: without it, events are dropped when a ring buffer
+/// shard is full.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpillConfig {
+    /// Directory holding one segment log per ring buffer shard
+    pub directory: PathBuf,
+    #[serde(default)]
+    pub max_bytes: SpillCapacity,
+    #[serde(default)]
+    pub segment_bytes: SegmentSize,
+}
+
+impl SpillConfig {
+    /// Spill into `directory` with the default bounds
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            max_bytes: SpillCapacity::default(),
+            segment_bytes: SegmentSize::default(),
+        }
+    }
+}
+
+/// Name the proxy reports its spans under (the OTLP `service.name`)
+#[nutype(
+    sanitize(trim),
+    validate(not_empty),
+    derive(Clone, Debug, Display, Default, Deserialize, Serialize, TryFrom, AsRef),
+    default = "union_square"
+)]
+pub struct ServiceName(String);
+
+/// Collector that proxy spans are exported to over OTLP/HTTP
+///
+/// Absent by default: without it, `traceparent` headers are forwarded
+/// unchanged and no spans are recorded.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OtlpConfig {
+    /// Base URL of the collector; spans are posted to `{endpoint}/v1/traces`
+    pub endpoint: TargetUrl,
+    #[serde(default)]
+    pub service_name: ServiceName,
+    /// Headers sent with every export, e.g. collector credentials
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub export_interval_ms: ExportIntervalMs,
+}
+
+impl OtlpConfig {
+    /// Export to the collector at `endpoint` with the default settings
+    pub fn new(endpoint: TargetUrl) -> Self {
+        Self {
+            endpoint,
+            service_name: ServiceName::default(),
+            headers: BTreeMap::new(),
+            export_interval_ms: ExportIntervalMs::default(),
         }
     }
 }
@@ -340,10 +481,39 @@ pub struct TargetUrl(String);
 )]
 pub struct ApiKey(String);
 
+/// The application and environment a proxy key's requests are attributed to
+///
+/// Budgets are enforced and spend is counted against the binding of the key
+/// a request authenticated with, never against its session headers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ApplicationBinding {
+    pub application_id: ApplicationId,
+    pub environment: Option<EnvironmentId>,
+}
+
+/// A proxy key bound to an application, as configured in `proxy.bound_api_keys`
+#[derive(Clone, Debug, Deserialize)]
+pub struct BoundApiKey {
+    pub key: ApiKey,
+    pub application_id: ApplicationId,
+    #[serde(default)]
+    pub environment: Option<EnvironmentId>,
+}
+
+impl BoundApiKey {
+    /// The binding requests authenticated with this key receive
+    pub fn binding(&self) -> ApplicationBinding {
+        ApplicationBinding {
+            application_id: self.application_id.clone(),
+            environment: self.environment.clone(),
+        }
+    }
+}
+
 /// Session ID for tracking related requests
 #[nutype(
     derive(Clone, Copy, Debug, Display, Deserialize, Serialize, TryFrom, AsRef),
-    validate(predicate = |id: &Uuid| id.get_version_num() == 7),
+    validate(predicate = |id: &Uuid| matches!(id.get_version_num(), 5 | 7)),
 )]
 pub struct SessionId(Uuid);
 
@@ -414,6 +584,18 @@ pub enum ProxyError {
 
     #[error("Failed to create audit event: {0}")]
     AuditEventCreationFailed(String),
+
+    #[error("TLS configuration error: {0}")]
+    TlsConfiguration(String),
+
+    #[error("Provider configuration error: {0}")]
+    ProviderConfiguration(String),
+
+    #[error("The {} budget of {} is spent", .0.period, .0.scope)]
+    BudgetExceeded(Box<BudgetRejection>),
+
+    #[error("Budgets are enforced and the API key is not bound to an application")]
+    UnboundApiKey,
 }
 
 /// Result type for proxy operations
@@ -451,27 +633,85 @@ pub enum AuditEventType {
         duration_ms: DurationMillis,
     },
     RequestBody {
+        #[serde(with = "crate::domain::audit_types::base64_bytes")]
         content: Vec<u8>,
         truncated: bool,
     },
     ResponseBody {
+        #[serde(with = "crate::domain::audit_types::base64_bytes")]
         content: Vec<u8>,
         truncated: bool,
     },
     RequestChunk {
         offset: ChunkOffset,
+        #[serde(with = "crate::domain::audit_types::base64_bytes")]
         data: Vec<u8>,
     },
     ResponseChunk {
         offset: ChunkOffset,
+        #[serde(with = "crate::domain::audit_types::base64_bytes")]
         data: Vec<u8>,
     },
+    /// All request chunks have been written; `body_size` is the captured length
+    RequestBodyComplete {
+        body_size: BodySize,
+        truncated: bool,
+    },
+    /// All response chunks have been written; `body_size` is the captured length
+    ResponseBodyComplete {
+        body_size: BodySize,
+        truncated: bool,
+    },
+    SessionContextReceived {
+        context: SessionContext,
+    },
+    /// What the provider serving a routed request reported about it
+    ProviderResolved {
+        provider_id: String,
+        /// The model that served the request, when the provider knows it
+        /// without reading the response body
+        model_id: Option<String>,
+    },
     Error {
         error: String,
         phase: ErrorPhase,
     },
 }
 
+/// Session identity and metadata taken from `X-UnionSquare-*` headers (ADR-0012)
+///
+/// Values are kept as the client sent them; validation happens when the
+/// event is converted into domain facts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionContext {
+    pub session_id: Option<String>,
+    pub parent_id: Option<String>,
+    pub user_id: Option<String>,
+    pub application_id: Option<String>,
+    pub environment: Option<String>,
+    pub metadata: Vec<(String, String)>,
+    pub application_context: Option<serde_json::Value>,
+}
+
+impl SessionContext {
+    /// True when the client sent no session headers at all
+    pub fn is_empty(&self) -> bool {
+        self.session_id.is_none()
+            && self.parent_id.is_none()
+            && self.user_id.is_none()
+            && self.application_id.is_none()
+            && self.environment.is_none()
+            && self.metadata.is_empty()
+            && self.application_context.is_none()
+    }
+
+    /// Replace the application and environment the client named with `binding`
+    pub fn bind(&mut self, binding: &ApplicationBinding) {
+        self.application_id = Some(binding.application_id.to_string());
+        self.environment = binding.environment.as_ref().map(ToString::to_string);
+    }
+}
+
 /// Phase where an error occurred
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ErrorPhase {
@@ -0,0 +1,185 @@
+//! HTTP client used to reach upstream LLM providers
+//!
+//! The hot path and the provider router share a single client so they also
+//! share its connection pool. Connections use rustls with the `ring` crypto
+//! provider and accept both `https://` and plain `http://` targets; the
+//! latter keeps local mocks and OpenAI-compatible dev servers working.
+
+use crate::proxy::types::{ProxyError, ProxyResult, UpstreamTlsConfig};
+use axum::body::Body;
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use rustls::{ClientConfig, RootCertStore};
+use rustls_pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Client for forwarding requests to upstream providers over HTTP or HTTPS
+pub type UpstreamClient = Client<HttpsConnector<HttpConnector>, Body>;
+
+/// Build the upstream client from TLS settings
+///
+/// Fails if a configured certificate or key file cannot be read or parsed.
+pub fn build_upstream_client(tls: &UpstreamTlsConfig) -> ProxyResult<UpstreamClient> {
+    let connector = HttpsConnectorBuilder::new()
+        .with_tls_config(build_tls_config(tls)?)
+        .https_or_http()
+        .enable_http1()
+        .build();
+
+    Ok(Client::builder(hyper_util::rt::TokioExecutor::new())
+        .http1_title_case_headers(true)
+        .http1_preserve_header_case(true)
+        .build(connector))
+}
+
+fn build_tls_config(tls: &UpstreamTlsConfig) -> ProxyResult<ClientConfig> {
+    let mut roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    for path in &tls.extra_root_certificates {
+        for certificate in load_certificates(path)? {
+            roots.add(certificate).map_err(|e| {
+                ProxyError::TlsConfiguration(format!(
+                    "invalid root certificate in {}: {e}",
+                    path.display()
+                ))
+            })?;
+        }
+    }
+
+    let builder =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| ProxyError::TlsConfiguration(e.to_string()))?
+            .with_root_certificates(roots);
+
+    match &tls.client_certificate {
+        Some(client) => {
+            let chain = load_certificates(&client.certificate_chain)?;
+            let key = PrivateKeyDer::from_pem_file(&client.private_key).map_err(|e| {
+                ProxyError::TlsConfiguration(format!(
+                    "cannot read private key {}: {e}",
+                    client.private_key.display()
+                ))
+            })?;
+            builder
+                .with_client_auth_cert(chain, key)
+                .map_err(|e| ProxyError::TlsConfiguration(format!("invalid client identity: {e}")))
+        }
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+fn load_certificates(path: &Path) -> ProxyResult<Vec<CertificateDer<'static>>> {
+    let read_error =
+        |e| ProxyError::TlsConfiguration(format!("cannot read {}: {e}", path.display()));
+
+    let certificates = CertificateDer::pem_file_iter(path)
+        .map_err(read_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_error)?;
+
+    if certificates.is_empty() {
+        return Err(ProxyError::TlsConfiguration(format!(
+            "no certificates found in {}",
+            path.display()
+        )));
+    }
+    Ok(certificates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::test_utils::tls::{spawn_tls_upstream, TestCertificates};
+    use crate::proxy::types::ClientCertificateConfig;
+    use hyper::{Request, StatusCode};
+
+    async fn get(client: &UpstreamClient, url: String) -> Result<String, String> {
+        let request = Request::get(url).body(Body::empty()).unwrap();
+        let response = client.request(request).await.map_err(|e| e.to_string())?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = http_body_util::BodyExt::collect(response.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        Ok(String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn reaches_https_upstream_signed_by_extra_root() {
+        let certs = TestCertificates::generate();
+        let dir = certs.write_to_tempdir();
+        let addr = spawn_tls_upstream(&certs, false, "secure").await;
+
+        let client = build_upstream_client(&UpstreamTlsConfig {
+            extra_root_certificates: vec![dir.path().join(TestCertificates::CA_FILE)],
+            client_certificate: None,
+        })
+        .unwrap();
+
+        let body = get(&client, format!("https://localhost:{}/", addr.port())).await;
+        assert_eq!(body.as_deref(), Ok("secure"));
+    }
+
+    #[tokio::test]
+    async fn rejects_https_upstream_with_untrusted_certificate() {
+        let certs = TestCertificates::generate();
+        let addr = spawn_tls_upstream(&certs, false, "secure").await;
+
+        let client = build_upstream_client(&UpstreamTlsConfig::default()).unwrap();
+
+        let result = get(&client, format!("https://localhost:{}/", addr.port())).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn presents_client_certificate_for_mutual_tls() {
+        let certs = TestCertificates::generate();
+        let dir = certs.write_to_tempdir();
+        let addr = spawn_tls_upstream(&certs, true, "mutual").await;
+        let url = format!("https://localhost:{}/", addr.port());
+
+        let without_identity = build_upstream_client(&UpstreamTlsConfig {
+            extra_root_certificates: vec![dir.path().join(TestCertificates::CA_FILE)],
+            client_certificate: None,
+        })
+        .unwrap();
+        assert!(get(&without_identity, url.clone()).await.is_err());
+
+        let with_identity = build_upstream_client(&UpstreamTlsConfig {
+            extra_root_certificates: vec![dir.path().join(TestCertificates::CA_FILE)],
+            client_certificate: Some(ClientCertificateConfig {
+                certificate_chain: dir.path().join(TestCertificates::CLIENT_CERT_FILE),
+                private_key: dir.path().join(TestCertificates::CLIENT_KEY_FILE),
+            }),
+        })
+        .unwrap();
+        assert_eq!(get(&with_identity, url).await.as_deref(), Ok("mutual"));
+    }
+
+    #[test]
+    fn missing_root_certificate_file_is_a_configuration_error() {
+        let result = build_upstream_client(&UpstreamTlsConfig {
+            extra_root_certificates: vec!["/nonexistent/ca.pem".into()],
+            client_certificate: None,
+        });
+
+        assert!(matches!(result, Err(ProxyError::TlsConfiguration(_))));
+    }
+
+    #[test]
+    fn file_without_certificates_is_a_configuration_error() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        let result = build_upstream_client(&UpstreamTlsConfig {
+            extra_root_certificates: vec![file.path().to_path_buf()],
+            client_certificate: None,
+        });
+
+        assert!(
+            matches!(result, Err(ProxyError::TlsConfiguration(msg)) if msg.contains("no certificates"))
+        );
+    }
+}
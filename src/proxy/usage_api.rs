@@ -0,0 +1,194 @@
+//! Usage API
+//!
+//! `GET /api/v1/usage` reports request counts, errors, tokens, cost and
+//! latency percentiles from the hourly usage rollup, bucketed by hour, day
+//! or week and grouped by any of application, environment, provider and
+//! model.
+
+use crate::domain::config_types::ProviderName;
+use crate::domain::session::{ApplicationId, EnvironmentId};
+use crate::domain::types::ModelId;
+use crate::infrastructure::eventcore::projections::usage::MAX_BUCKETS;
+use crate::infrastructure::eventcore::projections::{
+    UsageBucket, UsageDimension, UsageQuery, UsageRollup,
+};
+use crate::proxy::session_api::{bad_request, internal_error, parse_time, InvalidQueryParameter};
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use tracing::error;
+
+/// Range reported when `from` is not given, ending at `to`
+pub const DEFAULT_RANGE: TimeDelta = TimeDelta::days(1);
+
+/// Build a usage query from raw query-string pairs
+///
+/// `from` and `to` (RFC 3339) bound the range and default to the day before
+/// `now`. `bucket` is `hour` (the default), `day` or `week`. `group_by`
+/// takes a comma-separated list of `application`, `environment`, `provider`
+/// and `model`, and may be repeated. `application_id`, `environment`,
+/// `provider` and `model` filter.
+pub fn parse_usage_query(
+    params: &[(String, String)],
+    now: DateTime<Utc>,
+) -> Result<UsageQuery, InvalidQueryParameter> {
+    let mut from = None;
+    let mut to = None;
+    let mut bucket = UsageBucket::default();
+    let mut group_by = Vec::new();
+    let mut application_id = None;
+    let mut environment_id = None;
+    let mut provider = None;
+    let mut model_id = None;
+
+    for (name, value) in params {
+        let invalid = |reason: &dyn std::fmt::Display| InvalidQueryParameter::new(name, reason);
+
+        match name.as_str() {
+            "from" => from = Some(parse_time(value).map_err(|e| invalid(&e))?),
+            "to" => to = Some(parse_time(value).map_err(|e| invalid(&e))?),
+            "bucket" => {
+                bucket = UsageBucket::from_label(value)
+                    .ok_or_else(|| invalid(&"expected `hour`, `day` or `week`"))?
+            }
+            "group_by" => {
+                for label in value.split(',').map(str::trim) {
+                    let dimension = UsageDimension::from_label(label)
+                        .ok_or_else(|| invalid(&format!("unknown dimension `{label}`")))?;
+                    if !group_by.contains(&dimension) {
+                        group_by.push(dimension);
+                    }
+                }
+            }
+            "application_id" => {
+                application_id =
+                    Some(ApplicationId::try_new(value.clone()).map_err(|e| invalid(&e))?)
+            }
+            "environment" => {
+                environment_id =
+                    Some(EnvironmentId::try_new(value.clone()).map_err(|e| invalid(&e))?)
+            }
+            "provider" => {
+                provider = Some(ProviderName::try_new(value.clone()).map_err(|e| invalid(&e))?)
+            }
+            "model" => model_id = Some(ModelId::try_new(value.clone()).map_err(|e| invalid(&e))?),
+            _ => return Err(invalid(&"unknown parameter")),
+        }
+    }
+
+    let to = to.unwrap_or(now);
+    let from = from.unwrap_or(to - DEFAULT_RANGE);
+    if from >= to {
+        return Err(InvalidQueryParameter::new("from", "must be before `to`"));
+    }
+
+    let query = UsageQuery {
+        bucket,
+        group_by,
+        application_id,
+        environment_id,
+        provider,
+        model_id,
+        ..UsageQuery::new(from, to)
+    };
+    if query.bucket_count() > MAX_BUCKETS {
+        return Err(InvalidQueryParameter::new(
+            "bucket",
+            format!("the range spans more than {MAX_BUCKETS} buckets; use a wider bucket"),
+        ));
+    }
+    Ok(query)
+}
+
+/// Respond to `GET /api/v1/usage`
+pub async fn usage(rollup: &dyn UsageRollup, params: &[(String, String)]) -> Response {
+    let query = match parse_usage_query(params, Utc::now()) {
+        Ok(query) => query,
+        Err(e) => return bad_request(&e),
+    };
+
+    match rollup.usage(&query).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => {
+            error!("Usage query failed: {e}");
+            internal_error()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn now() -> DateTime<Utc> {
+        parse_time("2026-10-17T12:00:00Z").unwrap()
+    }
+
+    #[test]
+    fn parses_buckets_groups_and_filters() {
+        let query = parse_usage_query(
+            &params(&[
+                ("from", "2026-10-05T00:00:00Z"),
+                ("to", "2026-10-12T00:00:00Z"),
+                ("bucket", "day"),
+                ("group_by", "application, model"),
+                ("group_by", "application"),
+                ("environment", "production"),
+                ("provider", "openai"),
+            ]),
+            now(),
+        )
+        .unwrap();
+
+        assert_eq!(query.bucket, UsageBucket::Day);
+        assert_eq!(query.bucket_count(), 7);
+        assert_eq!(
+            query.group_by,
+            vec![UsageDimension::Application, UsageDimension::Model]
+        );
+        assert_eq!(query.environment_id.unwrap().as_ref(), "production");
+        assert_eq!(query.provider.unwrap().as_ref(), "openai");
+    }
+
+    #[test]
+    fn defaults_to_the_last_day_by_hour() {
+        let query = parse_usage_query(&[], now()).unwrap();
+
+        assert_eq!(query.to, now());
+        assert_eq!(query.from, now() - DEFAULT_RANGE);
+        assert_eq!(query.bucket, UsageBucket::Hour);
+        assert!(query.group_by.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        for pairs in [
+            vec![("bucket", "minute")],
+            vec![("group_by", "user")],
+            vec![("sort", "cost")],
+            vec![("from", "2026-10-17T12:00:00Z")],
+            vec![("from", "2020-01-01T00:00:00Z")],
+        ] {
+            assert!(
+                parse_usage_query(&params(&pairs), now()).is_err(),
+                "{pairs:?}"
+            );
+        }
+
+        // Years of data fit in weekly buckets
+        assert!(parse_usage_query(
+            &params(&[("from", "2020-01-01T00:00:00Z"), ("bucket", "week")]),
+            now()
+        )
+        .is_ok());
+    }
+}
@@ -28,6 +28,7 @@ fn test_critical_path_performance() {
     let config = RingBufferConfig {
         buffer_size: BufferSize::try_new(16 * 1024 * 1024).expect("16MB is valid"),
         slot_size: SlotSize::try_new(128 * 1024).expect("128KB is valid"),
+        ..Default::default()
     };
     let ring_buffer = RingBuffer::new(&config);
 
@@ -74,6 +75,7 @@ fn test_concurrent_performance() {
     let config = RingBufferConfig {
         buffer_size: BufferSize::try_new(128 * 1024 * 1024).expect("128MB is valid"),
         slot_size: SlotSize::try_new(64 * 1024).expect("64KB is valid"),
+        ..Default::default()
     };
     let ring_buffer = Arc::new(RingBuffer::new(&config));
 
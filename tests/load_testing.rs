@@ -37,6 +37,7 @@ async fn test_500_rps_sustained_load() {
     let config = RingBufferConfig {
         buffer_size: BufferSize::try_new(256 * 1024 * 1024).expect("256MB is valid"),
         slot_size: SlotSize::try_new(128 * 1024).expect("128KB is valid"),
+        ..Default::default()
     };
     let ring_buffer = Arc::new(RingBuffer::new(&config));
 
@@ -113,6 +114,7 @@ async fn test_2000_rps_burst_load() {
     let config = RingBufferConfig {
         buffer_size: BufferSize::try_new(512 * 1024 * 1024).expect("512MB is valid"),
         slot_size: SlotSize::try_new(128 * 1024).expect("128KB is valid"),
+        ..Default::default()
     };
     let ring_buffer = Arc::new(RingBuffer::new(&config));
 
@@ -214,6 +216,7 @@ async fn test_1000_concurrent_users() {
     let config = RingBufferConfig {
         buffer_size: BufferSize::try_new(512 * 1024 * 1024).expect("512MB is valid"),
         slot_size: SlotSize::try_new(128 * 1024).expect("128KB is valid"),
+        ..Default::default()
     };
     let ring_buffer = Arc::new(RingBuffer::new(&config));
 